@@ -0,0 +1,30 @@
+#!/usr/bin/env bash
+
+# Build an app twice with `roc build --reproducible --no-link`, and fail if the two object files differ.
+#
+# usage: ./ci/check_reproducible.sh examples/helloWorld.roc [other flags for roc build, e.g. --dev]
+# Set ROC to use a roc binary other than ./target/release/roc
+
+# https://vaneyckt.io/posts/safer_bash_scripts_with_set_euxo_pipefail/
+set -euxo pipefail
+
+ROC=$(realpath "${ROC:-./target/release/roc}")
+APP=$(realpath "$1")
+shift
+
+WORK_DIR=$(mktemp -d)
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+# roc writes the object to the current directory, so each build gets its own
+for BUILD in first second; do
+    mkdir "$WORK_DIR/$BUILD"
+    (cd "$WORK_DIR/$BUILD" && "$ROC" build --reproducible --no-link "$@" "$APP")
+done
+
+FIRST=$(find "$WORK_DIR/first" -type f -name '*.o')
+SECOND=$(find "$WORK_DIR/second" -type f -name '*.o')
+
+if ! cmp "$FIRST" "$SECOND"; then
+    echo "Building $APP twice produced different object files."
+    exit 1
+fi
@@ -281,6 +281,8 @@ fn to_pending_def<'a>(
         Value(AstValueDef::Dbg { .. }) => todo!(),
         Value(AstValueDef::Expect { .. }) => todo!(),
         Value(AstValueDef::ExpectFx { .. }) => todo!(),
+        // benches only run with `roc bench`, so there's nothing to do with them here
+        Value(AstValueDef::Bench { .. }) => None,
 
         SpaceBefore(sub_def, _) | SpaceAfter(sub_def, _) => {
             to_pending_def(env, sub_def, scope, pattern_type)
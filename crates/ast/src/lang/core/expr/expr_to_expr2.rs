@@ -3,7 +3,7 @@ use roc_can::expr::{IntValue, Recursive};
 use roc_can::num::{
     finish_parsing_base, finish_parsing_float, finish_parsing_num, ParsedNumResult,
 };
-use roc_can::operator::desugar_expr;
+use roc_can::operator::{desugar_expr, AwaitFn};
 use roc_collections::all::MutSet;
 use roc_module::symbol::Symbol;
 use roc_parse::ident::Accessor;
@@ -37,7 +37,7 @@ pub fn loc_expr_to_expr2<'a>(
     scope: &mut Scope,
     region: Region,
 ) -> (Expr2, Output) {
-    let desugared_loc_expr = desugar_expr(arena, arena.alloc(loc_expr));
+    let desugared_loc_expr = desugar_expr(arena, arena.alloc(loc_expr), AwaitFn::ResultTry);
 
     expr_to_expr2(env, scope, arena.alloc(desugared_loc_expr.value), region)
 }
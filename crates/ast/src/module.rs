@@ -16,6 +16,12 @@ pub fn load_module(
         palette: DEFAULT_PALETTE,
         threading,
         exec_mode: ExecutionMode::Check,
+        max_specializations: None,
+        coverage: false,
+        fold_constants: false,
+        hoist_loop_invariants: false,
+        strip_expects: false,
+        cancelled: None,
     };
 
     let arena = Bump::new();
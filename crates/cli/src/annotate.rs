@@ -0,0 +1,62 @@
+use std::io;
+use std::path::PathBuf;
+
+use bumpalo::Bump;
+use roc_build::program::check_load_config;
+use roc_language_server::annotate::missing_annotations;
+use roc_language_server::rename::{apply_edits, Edit};
+use roc_load::{LoadingProblem, Threading};
+use roc_packaging::cache::RocCacheDir;
+use roc_reporting::report::{Palette, RenderTarget};
+
+/// Add an annotation with the inferred type to every top-level value and function in the
+/// module at `roc_file_path` that doesn't have one, and write the module back to disk.
+pub fn annotate(roc_file_path: PathBuf, roc_cache_dir: RocCacheDir<'_>) -> io::Result<i32> {
+    let arena = Bump::new();
+    let load_config = check_load_config(
+        RenderTarget::ColorTerminal,
+        Palette::from_env(),
+        Threading::AllAvailable,
+    );
+
+    let mut loaded =
+        match roc_load::load_and_typecheck(&arena, roc_file_path, roc_cache_dir, load_config) {
+            Ok(loaded) => loaded,
+            Err(LoadingProblem::FormattedReport(report)) => {
+                print!("{}", report);
+
+                return Ok(1);
+            }
+            Err(other) => {
+                panic!("loading failed with error:\n{:?}", other);
+            }
+        };
+
+    let edits: Vec<Edit> = missing_annotations(&mut loaded)
+        .into_iter()
+        .map(|missing| missing.edit)
+        .collect();
+
+    if edits.is_empty() {
+        println!("Every top-level value already has a type annotation.");
+
+        return Ok(0);
+    }
+
+    let (path, source) = &loaded.sources[&loaded.module_id];
+
+    std::fs::write(path, apply_edits(source, &edits))?;
+
+    println!(
+        "Added {} type {} to {}.",
+        edits.len(),
+        if edits.len() == 1 {
+            "annotation"
+        } else {
+            "annotations"
+        },
+        path.display()
+    );
+
+    Ok(0)
+}
@@ -0,0 +1,195 @@
+use std::io;
+#[cfg(not(windows))]
+use std::path::Path;
+
+use clap::ArgMatches;
+use target_lexicon::Triple;
+
+#[cfg(windows)]
+pub fn bench(_matches: &ArgMatches, _triple: Triple) -> io::Result<i32> {
+    // benches run like expects, in a forked process that shares memory with this one
+    eprintln!("Running benches doesn't work on Windows yet.");
+
+    Ok(1)
+}
+
+/// Run every top-level `bench` in the app and the modules it imports, and print how long each
+/// one takes. Benches are always built with `--optimize`, since that's what's worth timing.
+///
+/// Note that LLVM may remove work whose result is never used, so a bench's body should
+/// evaluate to the result of the work being timed.
+#[cfg(not(windows))]
+pub fn bench(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
+    use std::time::Instant;
+
+    use bumpalo::Bump;
+    use roc_build::program::{
+        handle_error_module, handle_loading_problem, report_problems_monomorphized,
+    };
+    use roc_gen_llvm::llvm::build::LlvmBackendMode;
+    use roc_load::{ExecutionMode, LoadConfig, LoadMonomorphizedError, Threading};
+    use roc_mono::ir::OptLevel;
+    use roc_packaging::cache::{self, RocCacheDir};
+    use roc_repl_expect::bench::{
+        change_from_baseline, format_baseline, parse_baseline, run_toplevel_benches,
+    };
+    use roc_reporting::report::{Palette, RenderTarget};
+    use roc_target::TargetInfo;
+
+    use crate::{warnings, FLAG_BASELINE, FLAG_SAVE_BASELINE, ROC_FILE};
+
+    let start_time = Instant::now();
+    let arena = Bump::new();
+    let path = Path::new(matches.value_of_os(ROC_FILE).unwrap());
+
+    if !path.exists() {
+        eprintln!(
+            "\nThis file was not found: {}\n\nYou can run `roc help` for more information on how to provide a .roc file.\n",
+            path.display()
+        );
+
+        return Ok(1);
+    }
+
+    let baseline = match matches.value_of_os(FLAG_BASELINE) {
+        Some(baseline_path) => match std::fs::read_to_string(baseline_path) {
+            Ok(contents) => Some(parse_baseline(&contents)),
+            Err(error) => {
+                eprintln!(
+                    "Could not read the baseline at {}: {}",
+                    Path::new(baseline_path).display(),
+                    error
+                );
+
+                return Ok(1);
+            }
+        },
+        None => None,
+    };
+
+    let severity_config = warnings::severity_config(matches, path);
+
+    // Benches are built the way `roc test` builds expectations
+    let load_config = LoadConfig {
+        target_info: TargetInfo::from(&triple),
+        render: RenderTarget::ColorTerminal,
+        palette: Palette::from_env(),
+        threading: Threading::AllAvailable,
+        exec_mode: ExecutionMode::Test,
+        max_specializations: None,
+        coverage: false,
+        fold_constants: false,
+        hoist_loop_invariants: false,
+        strip_expects: false,
+        cancelled: None,
+    };
+    let load_result = roc_load::load_and_monomorphize(
+        &arena,
+        path.to_path_buf(),
+        RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
+        load_config,
+    );
+
+    let mut loaded = match load_result {
+        Ok(loaded) => loaded,
+        Err(LoadMonomorphizedError::LoadingProblem(problem)) => {
+            return handle_loading_problem(problem);
+        }
+        Err(LoadMonomorphizedError::ErrorModule(module)) => {
+            return handle_error_module(
+                module,
+                start_time.elapsed(),
+                path.as_os_str(),
+                false,
+                &severity_config,
+            );
+        }
+    };
+    let problems = report_problems_monomorphized(&mut loaded, &severity_config);
+
+    if problems.errors > 0 {
+        problems.print_to_stdout(start_time.elapsed());
+        println!();
+
+        return Ok(problems.exit_code());
+    }
+
+    if problems.warnings > 0 {
+        problems.print_to_stdout(start_time.elapsed());
+        println!(".\n");
+    }
+
+    let expectations = std::mem::take(&mut loaded.expectations);
+
+    let (lib, expects, _layout_interner) = roc_repl_expect::run::expect_mono_module_to_dylib(
+        &arena,
+        triple,
+        loaded,
+        OptLevel::Optimize,
+        LlvmBackendMode::GenTest,
+        false,
+    )
+    .unwrap();
+
+    if expects.benches.is_empty() {
+        println!("No benches were found.");
+
+        return Ok(2);
+    }
+
+    let results = run_toplevel_benches(&lib, &expectations, expects.benches);
+    let mut crashed = 0;
+
+    for result in results.iter() {
+        match &result.timing {
+            Ok(timing) => {
+                let change = baseline
+                    .as_ref()
+                    .and_then(|baseline| baseline.get(&result.label))
+                    .map(|baseline_ns| {
+                        let change = change_from_baseline(timing.median_ns, *baseline_ns);
+                        let color = if change > 0.0 { 31 } else { 32 }; // red or green
+
+                        format!("  \x1B[{color}m{change:+.1}%\x1B[39m")
+                    })
+                    .unwrap_or_default();
+
+                println!(
+                    "{:>14} ± {:<12} {}{}",
+                    format_ns(timing.median_ns),
+                    format_ns(timing.spread_ns),
+                    result.label,
+                    change
+                );
+            }
+            Err(message) => {
+                crashed += 1;
+
+                println!(
+                    "{:>14}   {:<12} {}  \x1B[31mcrashed: {}\x1B[39m",
+                    "", "", result.label, message
+                );
+            }
+        }
+    }
+
+    if let Some(baseline_path) = matches.value_of_os(FLAG_SAVE_BASELINE) {
+        std::fs::write(baseline_path, format_baseline(&results))?;
+    }
+
+    Ok((crashed > 0) as i32)
+}
+
+/// A time in nanoseconds, in the largest unit that keeps it at least 1
+#[cfg(not(windows))]
+fn format_ns(ns: f64) -> String {
+    if ns >= 1_000_000_000.0 {
+        format!("{:.2} s", ns / 1_000_000_000.0)
+    } else if ns >= 1_000_000.0 {
+        format!("{:.2} ms", ns / 1_000_000.0)
+    } else if ns >= 1_000.0 {
+        format!("{:.2} µs", ns / 1_000.0)
+    } else {
+        format!("{:.1} ns", ns)
+    }
+}
@@ -0,0 +1,43 @@
+use std::io;
+use std::path::PathBuf;
+
+use bumpalo::Bump;
+use roc_build::program::check_load_config;
+use roc_language_server::check::{diagnostics_json, loading_problem_json};
+use roc_load::Threading;
+use roc_packaging::cache::RocCacheDir;
+use roc_problem::lint::SeverityConfig;
+use roc_reporting::report::{RenderTarget, DEFAULT_PALETTE};
+
+/// `roc check --format=json`: print every problem in the modules loaded from `roc_file_path`
+/// as a JSON array, with the edits that fix them. See [diagnostics_json] for its shape.
+///
+/// Exits with the same codes as `roc check`: 0 if there are no problems, 1 otherwise.
+pub fn check_json(
+    roc_file_path: PathBuf,
+    roc_cache_dir: RocCacheDir<'_>,
+    threading: Threading,
+    severity_config: &SeverityConfig,
+) -> io::Result<i32> {
+    let arena = Bump::new();
+    // the messages go in JSON strings, so they shouldn't have color codes
+    let load_config = check_load_config(RenderTarget::Generic, DEFAULT_PALETTE, threading);
+
+    let json = match roc_load::load_and_typecheck(
+        &arena,
+        roc_file_path.clone(),
+        roc_cache_dir,
+        load_config,
+    ) {
+        Ok(mut loaded) => diagnostics_json(&mut loaded, severity_config),
+        Err(problem) => loading_problem_json(&roc_file_path, problem),
+    };
+
+    println!("{:#}", json);
+
+    let has_problems = json
+        .as_array()
+        .map_or(false, |problems| !problems.is_empty());
+
+    Ok(has_problems as i32)
+}
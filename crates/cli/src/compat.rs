@@ -0,0 +1,488 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use bumpalo::Bump;
+use roc_build::program::check_load_config;
+use roc_fmt::spaces::RemoveSpaces;
+use roc_load::{LoadingProblem, Threading};
+use roc_packaging::cache::RocCacheDir;
+use roc_parse::ast::{AssignedField, Collection, ExtractSpaces, Header, TypeAnnotation, ValueDef};
+use roc_parse::module::{self, module_defs};
+use roc_parse::parser::Parser;
+use roc_parse::state::State;
+use roc_region::all::Loc;
+use roc_reporting::report::{Palette, RenderTarget};
+use roc_types::pretty_print::{name_and_print_var, DebugPrint};
+
+/// Which part of a package's version number a change requires bumping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum VersionBump {
+    Minor,
+    Major,
+}
+
+impl fmt::Display for VersionBump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            VersionBump::Minor => "minor",
+            VersionBump::Major => "major",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiChange {
+    pub bump: VersionBump,
+    pub description: String,
+}
+
+impl ApiChange {
+    fn major(description: String) -> Self {
+        ApiChange {
+            bump: VersionBump::Major,
+            description,
+        }
+    }
+
+    fn minor(description: String) -> Self {
+        ApiChange {
+            bump: VersionBump::Minor,
+            description,
+        }
+    }
+}
+
+/// Everything a package (or a single interface module) exposes, by qualified name.
+#[derive(Debug, Default)]
+struct Api {
+    modules: Vec<String>,
+    types: Vec<String>,
+    /// The solved type of each exposed value, e.g. `Json.parse` => `Str -> Result Value [BadJson]`
+    values: BTreeMap<String, String>,
+}
+
+/// Report how the API exposed by `new` differs from the one exposed by `old`, where each is a
+/// package directory, its main.roc, or an interface module. Returns 1 if a major version bump
+/// is needed, so that CI can catch accidental breaking changes.
+pub fn check_compat(old: &Path, new: &Path, roc_cache_dir: RocCacheDir<'_>) -> io::Result<i32> {
+    let old_api = match load_api(old, roc_cache_dir)? {
+        Ok(api) => api,
+        Err(code) => return Ok(code),
+    };
+    let new_api = match load_api(new, roc_cache_dir)? {
+        Ok(api) => api,
+        Err(code) => return Ok(code),
+    };
+
+    let changes = compare_apis(&old_api, &new_api);
+
+    for change in changes.iter() {
+        println!(
+            "{}: {}",
+            change.bump.to_string().to_uppercase(),
+            change.description
+        );
+    }
+
+    match changes.iter().map(|change| change.bump).max() {
+        None => {
+            println!("The exposed API didn't change; a patch release is enough.");
+
+            Ok(0)
+        }
+        Some(VersionBump::Minor) => {
+            println!("\nThese changes are backwards compatible; they need a minor release.");
+
+            Ok(0)
+        }
+        Some(VersionBump::Major) => {
+            println!("\nSome of these changes can break code that uses this package; they need a major release.");
+
+            Ok(1)
+        }
+    }
+}
+
+fn compare_apis(old: &Api, new: &Api) -> Vec<ApiChange> {
+    let mut changes = Vec::new();
+
+    for (kind, old_names, new_names) in [
+        ("module", &old.modules, &new.modules),
+        ("type", &old.types, &new.types),
+    ] {
+        for name in old_names.iter().filter(|name| !new_names.contains(name)) {
+            changes.push(ApiChange::major(format!("removed {} `{}`", kind, name)));
+        }
+
+        for name in new_names.iter().filter(|name| !old_names.contains(name)) {
+            changes.push(ApiChange::minor(format!("added {} `{}`", kind, name)));
+        }
+    }
+
+    for (name, old_type) in old.values.iter() {
+        match new.values.get(name) {
+            None => changes.push(ApiChange::major(format!(
+                "removed `{} : {}`",
+                name, old_type
+            ))),
+            Some(new_type) => changes.extend(compare_signatures(name, old_type, new_type)),
+        }
+    }
+
+    for (name, new_type) in new.values.iter() {
+        if !old.values.contains_key(name) {
+            changes.push(ApiChange::minor(format!("added `{} : {}`", name, new_type)));
+        }
+    }
+
+    changes
+}
+
+/// How changing the type of the exposed value `name` from `old` to `new` affects code that uses
+/// it. A record gaining fields is explained field by field, since whether that breaks callers
+/// depends on whether the record goes into the function or comes out of it.
+pub fn compare_signatures(name: &str, old: &str, new: &str) -> Vec<ApiChange> {
+    let arena = Bump::new();
+    let mut changes = Vec::new();
+
+    let explained = match (parse_type(&arena, old), parse_type(&arena, new)) {
+        (Some(old_ann), Some(new_ann)) => {
+            compare_types(name, &old_ann, &new_ann, Polarity::Output, &mut changes)
+        }
+        _ => old == new,
+    };
+
+    if explained {
+        changes
+    } else {
+        vec![ApiChange::major(format!(
+            "`{}` changed type from `{}` to `{}`",
+            name, old, new
+        ))]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Polarity {
+    /// Passed in by the caller, like a function's arguments
+    Input,
+    /// Handed back to the caller
+    Output,
+}
+
+impl Polarity {
+    fn flip(self) -> Self {
+        match self {
+            Polarity::Input => Polarity::Output,
+            Polarity::Output => Polarity::Input,
+        }
+    }
+}
+
+/// Returns false if the types differ in some way the field-by-field changes can't describe.
+fn compare_types<'a>(
+    name: &str,
+    old: &TypeAnnotation<'a>,
+    new: &TypeAnnotation<'a>,
+    polarity: Polarity,
+    changes: &mut Vec<ApiChange>,
+) -> bool {
+    use TypeAnnotation::*;
+
+    match (old, new) {
+        (Function(old_args, old_ret), Function(new_args, new_ret)) => {
+            old_args.len() == new_args.len()
+                && old_args
+                    .iter()
+                    .zip(new_args.iter())
+                    .all(|(old_arg, new_arg)| {
+                        compare_types(
+                            name,
+                            &old_arg.value,
+                            &new_arg.value,
+                            polarity.flip(),
+                            changes,
+                        )
+                    })
+                && compare_types(name, &old_ret.value, &new_ret.value, polarity, changes)
+        }
+        (
+            Record {
+                fields: old_fields,
+                ext: old_ext,
+            },
+            Record {
+                fields: new_fields,
+                ext: new_ext,
+            },
+        ) => {
+            if format!("{:?}", old_ext) != format!("{:?}", new_ext) {
+                return false;
+            }
+
+            let (old_fields, new_fields) =
+                match (record_fields(old_fields), record_fields(new_fields)) {
+                    (Some(old_fields), Some(new_fields)) => (old_fields, new_fields),
+                    _ => return false,
+                };
+
+            for (label, _, _) in old_fields.iter() {
+                if !new_fields.iter().any(|(other, _, _)| other == label) {
+                    changes.push(ApiChange::major(format!(
+                        "`{}` no longer has the record field `{}`",
+                        name, label
+                    )));
+                }
+            }
+
+            for (label, new_optional, new_type) in new_fields.iter() {
+                let (old_optional, old_type) =
+                    match old_fields.iter().find(|(other, _, _)| other == label) {
+                        Some((_, old_optional, old_type)) => (old_optional, old_type),
+                        None => {
+                            changes.push(match (polarity, new_optional) {
+                                (Polarity::Input, false) => ApiChange::major(format!(
+                                    "`{}` has a newly-required record field `{}`",
+                                    name, label
+                                )),
+                                (Polarity::Input, true) => ApiChange::minor(format!(
+                                    "`{}` has a new optional record field `{}`",
+                                    name, label
+                                )),
+                                (Polarity::Output, _) => ApiChange::minor(format!(
+                                    "`{}` returns a new record field `{}`",
+                                    name, label
+                                )),
+                            });
+
+                            continue;
+                        }
+                    };
+
+                match (polarity, old_optional, new_optional) {
+                    (_, false, false) | (_, true, true) => {}
+                    (Polarity::Input, false, true) => changes.push(ApiChange::minor(format!(
+                        "`{}` made the record field `{}` optional",
+                        name, label
+                    ))),
+                    (Polarity::Input, true, false) => changes.push(ApiChange::major(format!(
+                        "`{}` made the record field `{}` required",
+                        name, label
+                    ))),
+                    (Polarity::Output, _, _) => return false,
+                }
+
+                if !compare_types(name, old_type, new_type, polarity, changes) {
+                    return false;
+                }
+            }
+
+            true
+        }
+        (Where(old_ann, old_clauses), Where(new_ann, new_clauses)) => {
+            format!("{:?}", old_clauses) == format!("{:?}", new_clauses)
+                && compare_types(name, &old_ann.value, &new_ann.value, polarity, changes)
+        }
+        _ => format!("{:?}", old) == format!("{:?}", new),
+    }
+}
+
+/// Each field's label, whether it's optional, and its type
+fn record_fields<'a>(
+    fields: &Collection<'a, Loc<AssignedField<'a, TypeAnnotation<'a>>>>,
+) -> Option<Vec<(&'a str, bool, &'a TypeAnnotation<'a>)>> {
+    fields
+        .iter()
+        .map(|field| match field.value {
+            AssignedField::RequiredValue(label, _, ann) => Some((label.value, false, &ann.value)),
+            AssignedField::OptionalValue(label, _, ann) => Some((label.value, true, &ann.value)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Parse a printed type the way an annotation for it would be parsed, without any spaces.
+fn parse_type<'a>(arena: &'a Bump, type_str: &str) -> Option<TypeAnnotation<'a>> {
+    let annotation = arena.alloc_str(&format!("x : {}\n", type_str));
+    let (_, defs, _) = module_defs()
+        .parse(arena, State::new(annotation.as_bytes()), 0)
+        .ok()?;
+
+    match defs.defs().next()? {
+        Err(ValueDef::Annotation(_, ann)) => Some(ann.value.remove_spaces(arena)),
+        _ => None,
+    }
+}
+
+fn load_api(path: &Path, roc_cache_dir: RocCacheDir<'_>) -> io::Result<Result<Api, i32>> {
+    let root = if path.is_dir() {
+        path.join("main.roc")
+    } else {
+        path.to_path_buf()
+    };
+    let dir = root.parent().unwrap_or_else(|| Path::new("."));
+
+    let mut api = Api::default();
+    let module_files: Vec<PathBuf> = {
+        let arena = Bump::new();
+        let src = std::fs::read(&root)?;
+
+        let header = match module::parse_header(&arena, State::new(&src)) {
+            Ok((module, _)) => module.header,
+            Err(problem) => {
+                eprintln!("{} doesn't parse: {:?}", root.display(), problem);
+
+                return Ok(Err(1));
+            }
+        };
+
+        match header {
+            Header::Package(header) => header
+                .exposes
+                .item
+                .iter()
+                .map(|loc| <&str>::from(loc.value.extract_spaces().item))
+                .map(|module_name| {
+                    api.modules.push(module_name.to_string());
+
+                    dir.join(module_name.replace('.', "/"))
+                        .with_extension("roc")
+                })
+                .collect(),
+            Header::Interface(_) => vec![root.clone()],
+            _ => {
+                eprintln!(
+                    "{} is not a package or an interface module, so it doesn't have an API to check.",
+                    root.display()
+                );
+
+                return Ok(Err(1));
+            }
+        }
+    };
+
+    for module_file in module_files {
+        let arena = Bump::new();
+        let load_config = check_load_config(
+            RenderTarget::ColorTerminal,
+            Palette::from_env(),
+            Threading::AllAvailable,
+        );
+
+        let mut loaded =
+            match roc_load::load_and_typecheck(&arena, module_file, roc_cache_dir, load_config) {
+                Ok(loaded) => loaded,
+                Err(LoadingProblem::FormattedReport(report)) => {
+                    print!("{}", report);
+
+                    return Ok(Err(1));
+                }
+                Err(other) => {
+                    panic!("loading failed with error:\n{:?}", other);
+                }
+            };
+
+        let home = loaded.module_id;
+        let module_name = loaded.interns.module_name(home).as_str().to_string();
+
+        for alias in loaded.exposed_aliases.keys() {
+            api.types
+                .push(format!("{}.{}", module_name, alias.as_str(&loaded.interns)));
+        }
+
+        let decls = match loaded.declarations_by_id.get(&home) {
+            Some(decls) => decls,
+            None => continue,
+        };
+        let subs = loaded.solved.inner_mut();
+
+        for symbol in loaded.exposed_values.iter() {
+            if let Some(index) = decls.symbols.iter().position(|s| s.value == *symbol) {
+                let type_str = name_and_print_var(
+                    decls.variables[index],
+                    subs,
+                    home,
+                    &loaded.interns,
+                    DebugPrint::NOTHING,
+                );
+
+                api.values.insert(
+                    format!("{}.{}", module_name, symbol.as_str(&loaded.interns)),
+                    type_str,
+                );
+            }
+        }
+    }
+
+    api.types.sort();
+
+    Ok(Ok(api))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn describe(changes: Vec<ApiChange>) -> Vec<String> {
+        changes
+            .into_iter()
+            .map(|change| format!("{}: {}", change.bump, change.description))
+            .collect()
+    }
+
+    #[test]
+    fn unchanged_signature() {
+        assert!(
+            compare_signatures("f", "Str, { a : U8 } -> Str", "Str, { a : U8 } -> Str").is_empty()
+        );
+    }
+
+    #[test]
+    fn changed_signature() {
+        assert_eq!(
+            describe(compare_signatures("f", "Str -> Str", "Str -> U8")),
+            ["major: `f` changed type from `Str -> Str` to `Str -> U8`"]
+        );
+    }
+
+    #[test]
+    fn record_fields_by_position() {
+        assert_eq!(
+            describe(compare_signatures(
+                "f",
+                "{ a : U8 } -> { b : Str }",
+                "{ a : U8, c : Str, d ? U8 } -> { b : Str, e : Str }"
+            )),
+            [
+                "major: `f` has a newly-required record field `c`",
+                "minor: `f` has a new optional record field `d`",
+                "minor: `f` returns a new record field `e`",
+            ]
+        );
+    }
+
+    #[test]
+    fn removed_and_added_exports() {
+        let old = Api {
+            modules: vec!["Json".to_string()],
+            types: Vec::new(),
+            values: BTreeMap::from([("Json.legacy".to_string(), "Str".to_string())]),
+        };
+        let new = Api {
+            modules: vec!["Json".to_string()],
+            types: vec!["Json.Value".to_string()],
+            values: BTreeMap::from([("Json.parse".to_string(), "Str -> Value".to_string())]),
+        };
+
+        assert_eq!(
+            describe(compare_apis(&old, &new)),
+            [
+                "minor: added type `Json.Value`",
+                "major: removed `Json.legacy : Str`",
+                "minor: added `Json.parse : Str -> Value`",
+            ]
+        );
+    }
+}
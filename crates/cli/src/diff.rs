@@ -0,0 +1,399 @@
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use bumpalo::Bump;
+use roc_fmt::annotation::Formattable;
+use roc_fmt::spaces::RemoveSpaces;
+use roc_fmt::Buf;
+use roc_parse::ast::{Defs, ExtractSpaces, Header, TypeDef, ValueDef};
+use roc_parse::header::ExposedName;
+
+use crate::format::parse_all;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefKind {
+    Value,
+    Type,
+    Ability,
+}
+
+impl fmt::Display for DefKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DefKind::Value => "value",
+            DefKind::Type => "type",
+            DefKind::Ability => "ability",
+        })
+    }
+}
+
+/// One difference between two versions of a module. Formatting and comments never count.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Exposed(String),
+    Unexposed(String),
+    Added {
+        kind: DefKind,
+        name: String,
+        signature: Option<String>,
+    },
+    Removed {
+        kind: DefKind,
+        name: String,
+        signature: Option<String>,
+    },
+    /// For values this is the type annotation; for types and abilities it's the whole definition.
+    SignatureChanged {
+        kind: DefKind,
+        name: String,
+        before: Option<String>,
+        after: Option<String>,
+    },
+    BodyChanged {
+        name: String,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // A type's signature is its whole definition, so it already includes the name.
+        let describe = |kind: &DefKind, name: &str, signature: &Option<String>| match signature {
+            Some(signature) if *kind == DefKind::Value => format!("`{} : {}`", name, signature),
+            Some(signature) => format!("`{}`", signature),
+            None => format!("`{}`", name),
+        };
+
+        match self {
+            Change::Exposed(name) => write!(f, "+ exposed `{}`", name),
+            Change::Unexposed(name) => write!(f, "- exposed `{}`", name),
+            Change::Added {
+                kind,
+                name,
+                signature,
+            } => write!(f, "+ {} {}", kind, describe(kind, name, signature)),
+            Change::Removed {
+                kind,
+                name,
+                signature,
+            } => write!(f, "- {} {}", kind, describe(kind, name, signature)),
+            Change::SignatureChanged {
+                kind,
+                name,
+                before,
+                after,
+            } => write!(
+                f,
+                "~ {} `{}`: {} changed to {}",
+                kind,
+                name,
+                before.as_deref().unwrap_or("no annotation"),
+                after.as_deref().unwrap_or("no annotation")
+            ),
+            Change::BodyChanged { name } => write!(f, "~ value `{}`: implementation changed", name),
+        }
+    }
+}
+
+/// Print the differences between two .roc files, like `diff` does for lines.
+/// Returns 1 if there are any, so scripts can tell whether the files match.
+pub fn diff(before: &Path, after: &Path) -> io::Result<i32> {
+    let before_src = std::fs::read_to_string(before)?;
+    let after_src = std::fs::read_to_string(after)?;
+
+    match diff_sources(&before_src, &after_src) {
+        Ok(changes) => {
+            for change in changes.iter() {
+                println!("{}", change);
+            }
+
+            Ok(!changes.is_empty() as i32)
+        }
+        Err(problem) => {
+            eprintln!("{}", problem);
+
+            Ok(2)
+        }
+    }
+}
+
+pub fn diff_sources(before: &str, after: &str) -> Result<Vec<Change>, String> {
+    let arena = Bump::new();
+
+    let before_ast = parse_all(&arena, before)
+        .map_err(|e| format!("The first file doesn't parse. Parse error was:\n\n{:?}", e))?;
+    let after_ast = parse_all(&arena, after)
+        .map_err(|e| format!("The second file doesn't parse. Parse error was:\n\n{:?}", e))?;
+
+    let mut changes = Vec::new();
+
+    let before_exposed = exposed_names(&before_ast.module.header);
+    let after_exposed = exposed_names(&after_ast.module.header);
+
+    for name in before_exposed.iter() {
+        if !after_exposed.contains(name) {
+            changes.push(Change::Unexposed(name.clone()));
+        }
+    }
+
+    for name in after_exposed.iter() {
+        if !before_exposed.contains(name) {
+            changes.push(Change::Exposed(name.clone()));
+        }
+    }
+
+    let before_defs = top_level_defs(&arena, &before_ast.defs);
+    let after_defs = top_level_defs(&arena, &after_ast.defs);
+
+    for (name, def) in before_defs.iter() {
+        if !after_defs.iter().any(|(other, _)| other == name) {
+            changes.push(Change::Removed {
+                kind: def.kind,
+                name: name.clone(),
+                signature: def.signature.clone(),
+            });
+        }
+    }
+
+    for (name, after_def) in after_defs.iter() {
+        match before_defs.iter().find(|(other, _)| other == name) {
+            None => changes.push(Change::Added {
+                kind: after_def.kind,
+                name: name.clone(),
+                signature: after_def.signature.clone(),
+            }),
+            Some((_, before_def)) => {
+                if before_def.kind != after_def.kind
+                    || before_def.signature_key != after_def.signature_key
+                {
+                    changes.push(Change::SignatureChanged {
+                        kind: after_def.kind,
+                        name: name.clone(),
+                        before: before_def.signature.clone(),
+                        after: after_def.signature.clone(),
+                    });
+                }
+
+                if before_def.body_key != after_def.body_key {
+                    changes.push(Change::BodyChanged { name: name.clone() });
+                }
+            }
+        }
+    }
+
+    Ok(changes)
+}
+
+fn exposed_names(header: &Header) -> Vec<String> {
+    fn exposed_name(name: ExposedName) -> String {
+        let text: &str = name.into();
+
+        if name.is_opaque() {
+            format!("opaque {}", text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    match header {
+        Header::Interface(header) => header
+            .exposes
+            .item
+            .iter()
+            .map(|loc| exposed_name(loc.value.extract_spaces().item))
+            .collect(),
+        Header::Hosted(header) => header
+            .exposes
+            .item
+            .iter()
+            .map(|loc| exposed_name(loc.value.extract_spaces().item))
+            .collect(),
+        Header::App(header) => header
+            .provides
+            .entries
+            .iter()
+            .map(|loc| exposed_name(loc.value.extract_spaces().item))
+            .collect(),
+        Header::Package(header) => header
+            .exposes
+            .item
+            .iter()
+            .map(|loc| <&str>::from(loc.value.extract_spaces().item).to_string())
+            .collect(),
+        Header::Platform(header) => header
+            .exposes
+            .item
+            .iter()
+            .map(|loc| <&str>::from(loc.value.extract_spaces().item).to_string())
+            .collect(),
+    }
+}
+
+struct TopLevelDef {
+    kind: DefKind,
+    /// How the signature is shown to people
+    signature: Option<String>,
+    /// What signatures are compared by: the syntax tree without any spaces or comments
+    signature_key: Option<String>,
+    body_key: Option<String>,
+}
+
+/// Top-level defs in the order they first appear. An annotation and the body it belongs to
+/// are combined even if they aren't written next to each other. Expectations and the like
+/// have no name, so they are left out.
+fn top_level_defs<'a>(arena: &'a Bump, defs: &Defs<'a>) -> Vec<(String, TopLevelDef)> {
+    let mut found: Vec<(String, TopLevelDef)> = Vec::new();
+
+    for def in defs.defs() {
+        match def {
+            Ok(type_def) => {
+                let (header, kind) = match type_def {
+                    TypeDef::Alias { header, .. } | TypeDef::Opaque { header, .. } => {
+                        (header, DefKind::Type)
+                    }
+                    TypeDef::Ability { header, .. } => (header, DefKind::Ability),
+                };
+
+                let def = entry(&mut found, header.name.value.to_string(), kind);
+                def.signature = Some(one_line(arena, type_def));
+                def.signature_key = Some(format!("{:?}", type_def.remove_spaces(arena)));
+            }
+            Err(ValueDef::Annotation(pattern, ann)) => {
+                let def = entry(&mut found, one_line(arena, pattern), DefKind::Value);
+                def.signature = Some(one_line(arena, ann));
+                def.signature_key = Some(format!("{:?}", ann.remove_spaces(arena)));
+            }
+            Err(ValueDef::Body(pattern, expr)) => {
+                let def = entry(&mut found, one_line(arena, *pattern), DefKind::Value);
+                def.body_key = Some(format!("{:?}", expr.remove_spaces(arena)));
+            }
+            Err(ValueDef::AnnotatedBody {
+                ann_pattern,
+                ann_type,
+                body_expr,
+                ..
+            }) => {
+                let def = entry(&mut found, one_line(arena, *ann_pattern), DefKind::Value);
+                def.signature = Some(one_line(arena, *ann_type));
+                def.signature_key = Some(format!("{:?}", ann_type.remove_spaces(arena)));
+                def.body_key = Some(format!("{:?}", body_expr.remove_spaces(arena)));
+            }
+            Err(
+                ValueDef::Dbg { .. }
+                | ValueDef::Expect { .. }
+                | ValueDef::ExpectFx { .. }
+                | ValueDef::Bench { .. },
+            ) => {}
+        }
+    }
+
+    found
+}
+
+fn entry(found: &mut Vec<(String, TopLevelDef)>, name: String, kind: DefKind) -> &mut TopLevelDef {
+    let index = match found.iter().position(|(other, _)| *other == name) {
+        Some(index) => index,
+        None => {
+            let def = TopLevelDef {
+                kind,
+                signature: None,
+                signature_key: None,
+                body_key: None,
+            };
+
+            found.push((name, def));
+            found.len() - 1
+        }
+    };
+
+    &mut found[index].1
+}
+
+/// Format some syntax, then squash its whitespace so it fits on one line.
+fn one_line<T: Formattable>(arena: &Bump, value: &T) -> String {
+    let mut buf = Buf::new_in(arena);
+
+    value.format(&mut buf, 0);
+
+    buf.as_str()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BEFORE: &str = indoc::indoc!(
+        r#"
+        interface Greeting
+            exposes [greet, Name, helper]
+            imports []
+
+        Name : Str
+
+        greet : Name -> Str
+        greet = \name -> "Hello, \(name)!"
+
+        helper = \x -> x
+        "#
+    );
+
+    #[test]
+    fn formatting_is_not_a_change() {
+        let after = indoc::indoc!(
+            r#"
+            interface Greeting exposes [greet, Name, helper] imports []
+
+            Name : Str
+
+            # Say hello
+            greet :  Name   ->  Str
+            greet = \name ->
+                "Hello, \(name)!"
+
+            helper = \x ->   x
+            "#
+        );
+
+        assert_eq!(diff_sources(BEFORE, after), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn def_changes() {
+        let after = indoc::indoc!(
+            r#"
+            interface Greeting
+                exposes [greet, Name, farewell]
+                imports []
+
+            Name : Str
+
+            greet : Name, Str -> Str
+            greet = \name, punctuation -> "Hello, \(name)\(punctuation)"
+
+            farewell : Name -> Str
+            farewell = \name -> "Bye, \(name)!"
+            "#
+        );
+
+        let changes: Vec<String> = diff_sources(BEFORE, after)
+            .unwrap()
+            .iter()
+            .map(|change| change.to_string())
+            .collect();
+
+        assert_eq!(
+            changes,
+            [
+                "- exposed `helper`",
+                "+ exposed `farewell`",
+                "- value `helper`",
+                "~ value `greet`: `Name -> Str` changed to `Name, Str -> Str`",
+                "~ value `greet`: implementation changed",
+                "+ value `farewell : Name -> Str`",
+            ]
+        );
+    }
+}
@@ -1,9 +1,12 @@
+use std::any::Any;
 use std::ffi::OsStr;
+use std::panic;
 use std::path::{Path, PathBuf};
 
 use crate::FormatMode;
 use bumpalo::Bump;
-use roc_error_macros::{internal_error, user_error};
+use rayon::prelude::*;
+use roc_error_macros::internal_error;
 use roc_fmt::def::fmt_defs;
 use roc_fmt::module::fmt_module;
 use roc_fmt::spaces::RemoveSpaces;
@@ -58,102 +61,313 @@ fn is_roc_file(path: &Path) -> bool {
     matches!(path.extension().and_then(OsStr::to_str), Some("roc"))
 }
 
-pub fn format(files: std::vec::Vec<PathBuf>, mode: FormatMode) -> Result<(), String> {
-    let files = flatten_directories(files);
+/// Format the given files, directories of files, and globs like `src/**/*.roc`,
+/// processing the files in parallel. Prints a summary of what changed, and returns
+/// an error if any file couldn't be formatted or, in [FormatMode::CheckOnly],
+/// if any file needs to be reformatted.
+pub fn format(paths: std::vec::Vec<PathBuf>, mode: FormatMode) -> Result<(), String> {
+    let mut files = Vec::new();
+    let mut failed = Vec::new();
 
-    for file in files {
-        let arena = Bump::new();
+    for path in paths {
+        if path.exists() {
+            files.push(path);
+        } else if is_glob(&path) {
+            let matches = expand_glob(&path);
+
+            if matches.is_empty() {
+                failed.push((path, "No files match this pattern.".to_string()));
+            }
+
+            files.extend(matches);
+        } else {
+            failed.push((path, "This file doesn't exist.".to_string()));
+        }
+    }
+
+    let mut files = flatten_directories(files);
+
+    // Overlapping directories and globs would otherwise format a file twice.
+    files.sort();
+    files.dedup();
+
+    let outcomes: Vec<_> = files
+        .par_iter()
+        .map(|file| {
+            // A bug in the parser or formatter that panics on one file shouldn't stop the rest
+            // from being formatted, so that file is reported as failed like any other.
+            let outcome = panic::catch_unwind(|| format_file(file, mode))
+                .unwrap_or_else(|payload| Err(panic_message(payload)));
+
+            (file, outcome)
+        })
+        .collect();
+
+    let mut changed = Vec::new();
+    let mut unchanged = 0;
 
-        let src = std::fs::read_to_string(&file).unwrap();
+    for (file, outcome) in outcomes {
+        match outcome {
+            Ok(true) => changed.push(file),
+            Ok(false) => unchanged += 1,
+            Err(message) => failed.push((file.clone(), message)),
+        }
+    }
+
+    for (file, message) in failed.iter() {
+        eprintln!("Couldn't format {}: {}\n", file.display(), message);
+    }
 
-        let ast = arena.alloc(parse_all(&arena, &src).unwrap_or_else(|e| {
-            user_error!("Unexpected parse failure when parsing this formatting:\n\n{:?}\n\nParse error was:\n\n{:?}\n\n", src, e)
-        }));
-        let mut buf = Buf::new_in(&arena);
-        fmt_all(&mut buf, ast);
+    match mode {
+        FormatMode::CheckOnly => {
+            for file in changed.iter() {
+                println!("Needs formatting: {}", file.display());
+            }
 
-        let reparsed_ast = arena.alloc(parse_all(&arena, buf.as_str()).unwrap_or_else(|e| {
-            let mut fail_file = file.clone();
-            fail_file.set_extension("roc-format-failed");
-            std::fs::write(&fail_file, buf.as_str()).unwrap();
-            internal_error!(
+            println!(
+                "Checked {}: {} need formatting, {} already formatted, {} failed.",
+                count(files.len(), "file"),
+                changed.len(),
+                unchanged,
+                failed.len()
+            );
+
+            if !changed.is_empty() {
+                return Err("One or more files need to be reformatted.".to_string());
+            }
+        }
+        FormatMode::Format => {
+            println!(
+                "Formatted {}: {} changed, {} unchanged, {} failed.",
+                count(files.len(), "file"),
+                changed.len(),
+                unchanged,
+                failed.len()
+            );
+        }
+    }
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} couldn't be formatted.",
+            count(failed.len(), "file")
+        ))
+    }
+}
+
+/// Format one file, returning whether formatting changed it. In [FormatMode::CheckOnly]
+/// the file is left as it is.
+fn format_file(file: &Path, mode: FormatMode) -> Result<bool, String> {
+    let arena = Bump::new();
+
+    let src = std::fs::read_to_string(file).map_err(|e| e.to_string())?;
+
+    let ast = match parse_all(&arena, &src) {
+        Ok(ast) => arena.alloc(ast),
+        Err(e) => return Err(format!("It doesn't parse. Parse error was:\n\n{:?}", e)),
+    };
+    let mut buf = Buf::new_in(&arena);
+    fmt_all(&mut buf, ast);
+
+    let reparsed_ast = match parse_all(&arena, buf.as_str()) {
+        Ok(ast) => arena.alloc(ast),
+        Err(e) => {
+            let fail_file = write_debug_file(file, "roc-format-failed", buf.as_str())?;
+
+            return Err(format!(
                 "Formatting bug; formatted code isn't valid\n\n\
                 I wrote the incorrect result to this file for debugging purposes:\n{}\n\n\
                 Parse error was: {:?}\n\n",
                 fail_file.display(),
                 e
-            );
-        }));
-
-        let ast_normalized = ast.remove_spaces(&arena);
-        let reparsed_ast_normalized = reparsed_ast.remove_spaces(&arena);
-
-        // HACK!
-        // We compare the debug format strings of the ASTs, because I'm finding in practice that _somewhere_ deep inside the ast,
-        // the PartialEq implementation is returning `false` even when the Debug-formatted impl is exactly the same.
-        // I don't have the patience to debug this right now, so let's leave it for another day...
-        // TODO: fix PartialEq impl on ast types
-        if format!("{:?}", ast_normalized) != format!("{:?}", reparsed_ast_normalized) {
-            let mut fail_file = file.clone();
-            fail_file.set_extension("roc-format-failed");
-            std::fs::write(&fail_file, buf.as_str()).unwrap();
-
-            let mut before_file = file.clone();
-            before_file.set_extension("roc-format-failed-ast-before");
-            std::fs::write(&before_file, format!("{:#?}\n", ast_normalized)).unwrap();
-
-            let mut after_file = file.clone();
-            after_file.set_extension("roc-format-failed-ast-after");
-            std::fs::write(&after_file, format!("{:#?}\n", reparsed_ast_normalized)).unwrap();
-
-            internal_error!(
-                "Formatting bug; formatting didn't reparse as the same tree\n\n\
-                I wrote the incorrect result to this file for debugging purposes:\n{}\n\n\
-                I wrote the tree before and after formatting to these files for debugging purposes:\n{}\n{}\n\n",
-                fail_file.display(),
-                before_file.display(),
-                after_file.display());
+            ));
+        }
+    };
+
+    let ast_normalized = ast.remove_spaces(&arena);
+    let reparsed_ast_normalized = reparsed_ast.remove_spaces(&arena);
+
+    // HACK!
+    // We compare the debug format strings of the ASTs, because I'm finding in practice that _somewhere_ deep inside the ast,
+    // the PartialEq implementation is returning `false` even when the Debug-formatted impl is exactly the same.
+    // I don't have the patience to debug this right now, so let's leave it for another day...
+    // TODO: fix PartialEq impl on ast types
+    if format!("{:?}", ast_normalized) != format!("{:?}", reparsed_ast_normalized) {
+        let fail_file = write_debug_file(file, "roc-format-failed", buf.as_str())?;
+        let before_file = write_debug_file(
+            file,
+            "roc-format-failed-ast-before",
+            &format!("{:#?}\n", ast_normalized),
+        )?;
+        let after_file = write_debug_file(
+            file,
+            "roc-format-failed-ast-after",
+            &format!("{:#?}\n", reparsed_ast_normalized),
+        )?;
+
+        return Err(format!(
+            "Formatting bug; formatting didn't reparse as the same tree\n\n\
+            I wrote the incorrect result to this file for debugging purposes:\n{}\n\n\
+            I wrote the tree before and after formatting to these files for debugging purposes:\n{}\n{}\n\n",
+            fail_file.display(),
+            before_file.display(),
+            after_file.display()
+        ));
+    }
+
+    // Now verify that the resultant formatting is _stable_ - i.e. that it doesn't change again if re-formatted
+    let mut reformatted_buf = Buf::new_in(&arena);
+    fmt_all(&mut reformatted_buf, reparsed_ast);
+    if buf.as_str() != reformatted_buf.as_str() {
+        let unstable_1_file = write_debug_file(file, "roc-format-unstable-1", buf.as_str())?;
+        let unstable_2_file =
+            write_debug_file(file, "roc-format-unstable-2", reformatted_buf.as_str())?;
+
+        return Err(format!(
+            "Formatting bug; formatting is not stable. Reformatting the formatted file changed it again.\n\n\
+            I wrote the result of formatting to this file for debugging purposes:\n{}\n\n\
+            I wrote the result of double-formatting here:\n{}\n\n",
+            unstable_1_file.display(),
+            unstable_2_file.display()
+        ));
+    }
+
+    let changed = buf.as_str() != src;
+
+    if changed && matches!(mode, FormatMode::Format) {
+        // If all the checks above passed, actually write out the new file.
+        std::fs::write(file, buf.as_str()).map_err(|e| e.to_string())?;
+    }
+
+    Ok(changed)
+}
+
+/// Write `contents` next to `file`, with its extension replaced by `extension`
+fn write_debug_file(file: &Path, extension: &str, contents: &str) -> Result<PathBuf, String> {
+    let mut debug_file = file.to_path_buf();
+    debug_file.set_extension(extension);
+    std::fs::write(&debug_file, contents).map_err(|e| e.to_string())?;
+
+    Ok(debug_file)
+}
+
+/// The message a panic was started with, for reporting it as a failure
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    let message = match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown cause".to_string(),
+        },
+    };
+
+    format!(
+        "The formatter crashed. This is definitely a compiler bug; please file an issue here: \
+        https://github.com/roc-lang/roc/issues/new/choose\n\n{}",
+        message
+    )
+}
+
+fn count(n: usize, noun: &str) -> String {
+    if n == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", n, noun)
+    }
+}
+
+fn is_glob(path: &Path) -> bool {
+    path.to_string_lossy().contains(['*', '?'])
+}
+
+/// The paths matching a glob, where `*` matches any part of a file name, `?` any one
+/// character, and `**` any number of directories. Hidden files are only matched by
+/// patterns that start with a `.`, the way shells do it.
+fn expand_glob(pattern: &Path) -> Vec<PathBuf> {
+    let mut base = PathBuf::new();
+    let mut components = pattern.components().peekable();
+
+    // The literal leading components, e.g. `src` in `src/**/*.roc`
+    while let Some(component) = components.peek() {
+        if is_glob(Path::new(component)) {
+            break;
         }
 
-        // Now verify that the resultant formatting is _stable_ - i.e. that it doesn't change again if re-formatted
-        let mut reformatted_buf = Buf::new_in(&arena);
-        fmt_all(&mut reformatted_buf, reparsed_ast);
-        if buf.as_str() != reformatted_buf.as_str() {
-            let mut unstable_1_file = file.clone();
-            unstable_1_file.set_extension("roc-format-unstable-1");
-            std::fs::write(&unstable_1_file, buf.as_str()).unwrap();
-
-            let mut unstable_2_file = file.clone();
-            unstable_2_file.set_extension("roc-format-unstable-2");
-            std::fs::write(&unstable_2_file, reformatted_buf.as_str()).unwrap();
-
-            internal_error!(
-                "Formatting bug; formatting is not stable. Reformatting the formatted file changed it again.\n\n\
-                I wrote the result of formatting to this file for debugging purposes:\n{}\n\n\
-                I wrote the result of double-formatting here:\n{}\n\n",
-                unstable_1_file.display(),
-                unstable_2_file.display());
+        base.push(component);
+        components.next();
+    }
+
+    let patterns: Vec<String> = components
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    let mut matches = Vec::new();
+
+    glob_walk(&base, &patterns, &mut matches);
+
+    matches
+}
+
+fn glob_walk(dir: &Path, patterns: &[String], matches: &mut Vec<PathBuf>) {
+    let (pattern, rest) = match patterns.split_first() {
+        Some(split) => split,
+        None => {
+            matches.push(dir.to_path_buf());
+
+            return;
         }
+    };
 
-        match mode {
-            FormatMode::CheckOnly => {
-                // If we notice that this file needs to be formatted, return early
-                if buf.as_str() != src {
-                    return Err("One or more files need to be reformatted.".to_string());
-                }
-            }
+    if pattern == "**" {
+        // `**` can match no directories at all
+        glob_walk(dir, rest, matches);
+    }
+
+    let read_from = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let entries = match read_from.read_dir() {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
 
-            FormatMode::Format => {
-                // If all the checks above passed, actually write out the new file.
-                std::fs::write(&file, buf.as_str()).unwrap();
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let path = dir.join(&name);
+
+        if name.starts_with('.') && !pattern.starts_with('.') {
+            continue;
+        }
+
+        if pattern == "**" {
+            if path.is_dir() {
+                glob_walk(&path, patterns, matches);
             }
+        } else if wildcard_match(
+            &pattern.chars().collect::<Vec<_>>(),
+            &name.chars().collect::<Vec<_>>(),
+        ) {
+            glob_walk(&path, rest, matches);
         }
     }
+}
 
-    Ok(())
+fn wildcard_match(pattern: &[char], name: &[char]) -> bool {
+    match (pattern.split_first(), name.split_first()) {
+        (None, None) => true,
+        (Some(('*', rest)), _) => {
+            wildcard_match(rest, name) || (!name.is_empty() && wildcard_match(pattern, &name[1..]))
+        }
+        (Some(('?', rest)), Some((_, name_rest))) => wildcard_match(rest, name_rest),
+        (Some((p, rest)), Some((n, name_rest))) if p == n => wildcard_match(rest, name_rest),
+        _ => false,
+    }
 }
 
-fn parse_all<'a>(arena: &'a Bump, src: &'a str) -> Result<Ast<'a>, SyntaxError<'a>> {
+pub(crate) fn parse_all<'a>(arena: &'a Bump, src: &'a str) -> Result<Ast<'a>, SyntaxError<'a>> {
     let (module, state) = module::parse_header(arena, State::new(src.as_bytes()))
         .map_err(|e| SyntaxError::Header(e.problem))?;
 
@@ -169,3 +383,33 @@ fn fmt_all<'a>(buf: &mut Buf<'a>, ast: &'a Ast) {
 
     buf.fmt_end_of_file();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, name: &str) -> bool {
+        wildcard_match(
+            &pattern.chars().collect::<Vec<_>>(),
+            &name.chars().collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn wildcards() {
+        assert!(matches("*.roc", "main.roc"));
+        assert!(matches("*.roc", ".roc"));
+        assert!(!matches("*.roc", "main.roc.bak"));
+        assert!(matches("Ma?n*", "Main.roc"));
+        assert!(!matches("Ma?n*", "Man.roc"));
+        assert!(matches("*a*b*", "xaxxbx"));
+    }
+
+    #[test]
+    fn panics_are_reported_as_failures() {
+        let payload =
+            panic::catch_unwind(|| internal_error!("formatter bug in {}", "Main.roc")).unwrap_err();
+
+        assert!(panic_message(payload).ends_with("formatter bug in Main.roc"));
+    }
+}
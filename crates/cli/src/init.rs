@@ -0,0 +1,148 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use roc_packaging::cache::{self, RocCacheDir};
+use roc_packaging::https::PackageMetadata;
+
+/// The platform new apps use unless another one is chosen with `--platform`.
+pub const DEFAULT_PLATFORM: &str = "https://github.com/roc-lang/basic-cli/releases/download/0.3.2/tE4xS_zLdmmxmHwHih9kHWQ7fsXtJr7W7h3425-eZFk.tar.br";
+
+const APP_MAIN_TEMPLATE: &str = include_str!("../templates/app-main.roc");
+const PACKAGE_MAIN_TEMPLATE: &str = include_str!("../templates/package-main.roc");
+const GREETING_TEMPLATE: &str = include_str!("../templates/Greeting.roc");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectKind<'a> {
+    App { platform: &'a str },
+    Package,
+}
+
+/// Create a directory called `name` containing a starter main.roc, along with a module
+/// that has some tests in it. For apps, the platform is downloaded into the cache first,
+/// which also checks that its URL is pinned to the hash of its contents.
+pub fn init(name: &str, kind: ProjectKind, roc_cache_dir: RocCacheDir<'_>) -> io::Result<i32> {
+    if let Err(problem) = check_project_name(name) {
+        eprintln!("{}", problem);
+
+        return Ok(1);
+    }
+
+    let dir = Path::new(name);
+
+    if dir.exists() {
+        eprintln!(
+            "There's already something at {}, so I can't create a new project there.",
+            dir.display()
+        );
+
+        return Ok(1);
+    }
+
+    let main_template = match kind {
+        ProjectKind::App { platform } => {
+            if let Err(problem) = PackageMetadata::try_from(platform) {
+                eprintln!(
+                    "The platform URL {} isn't valid ({:?}). It should be a https:// URL to a .tar, .tar.gz, or .tar.br file named after the hash of its contents.",
+                    platform, problem
+                );
+
+                return Ok(1);
+            }
+
+            if let Err(problem) = cache::install_package(roc_cache_dir, platform) {
+                eprintln!(
+                    "I couldn't download the platform at {}: {:?}",
+                    platform, problem
+                );
+
+                return Ok(1);
+            }
+
+            APP_MAIN_TEMPLATE
+        }
+        ProjectKind::Package => PACKAGE_MAIN_TEMPLATE,
+    };
+
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join("main.roc"), render(main_template, name, kind))?;
+    fs::write(
+        dir.join("Greeting.roc"),
+        render(GREETING_TEMPLATE, name, kind),
+    )?;
+
+    println!(
+        "Created a new {} in {}.\n\nTry it out with:\n\n\tcd {}\n\troc {}\n",
+        match kind {
+            ProjectKind::App { .. } => "app",
+            ProjectKind::Package => "package",
+        },
+        dir.display(),
+        dir.display(),
+        match kind {
+            ProjectKind::App { .. } => "dev",
+            ProjectKind::Package => "test Greeting.roc",
+        },
+    );
+
+    Ok(0)
+}
+
+/// The name ends up both in a directory name and inside a string literal in the module header.
+fn check_project_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        Err("The project name can't be empty.".to_string())
+    } else if let Some(c) = name
+        .chars()
+        .find(|&c| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+    {
+        Err(format!(
+            "The project name `{}` contains `{}`, but it may only contain letters, digits, `-` and `_`.",
+            name, c
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn render(template: &str, name: &str, kind: ProjectKind) -> String {
+    let platform = match kind {
+        ProjectKind::App { platform } => platform,
+        ProjectKind::Package => "",
+    };
+
+    template
+        .replace("{{name}}", name)
+        .replace("{{platform}}", platform)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn app_template() {
+        let platform = "https://example.com/pf/hash.tar.br";
+        let main = render(APP_MAIN_TEMPLATE, "hello", ProjectKind::App { platform });
+
+        assert!(main.starts_with("app \"hello\"\n"));
+        assert!(main.contains("packages { pf: \"https://example.com/pf/hash.tar.br\" }"));
+        assert!(!main.contains("{{"));
+    }
+
+    #[test]
+    fn package_template() {
+        let main = render(PACKAGE_MAIN_TEMPLATE, "json", ProjectKind::Package);
+
+        assert!(main.starts_with("package \"json\"\n"));
+        assert!(!main.contains("{{"));
+    }
+
+    #[test]
+    fn project_names() {
+        assert!(check_project_name("hello-world_2").is_ok());
+        assert!(check_project_name("").is_err());
+        assert!(check_project_name("../hello").is_err());
+        assert!(check_project_name("say \"hi\"").is_err());
+    }
+}
@@ -5,22 +5,28 @@ extern crate const_format;
 
 use bumpalo::Bump;
 use clap::{Arg, ArgMatches, Command, ValueSource};
-use roc_build::link::{LinkType, LinkingStrategy};
+use roc_build::build_cache::BuildCache;
+use roc_build::link::LinkType;
 use roc_build::program::{
     handle_error_module, handle_loading_problem, standard_load_config, BuildFileError,
-    BuildOrdering, BuiltFile, CodeGenBackend, CodeGenOptions, DEFAULT_ROC_FILENAME,
+    BuildOrdering, BuiltFile, CodeGenOptions, DEFAULT_ROC_FILENAME,
 };
 use roc_error_macros::{internal_error, user_error};
-use roc_gen_dev::AssemblyBackendMode;
 use roc_gen_llvm::llvm::build::LlvmBackendMode;
-use roc_load::{ExpectMetadata, Threading};
+use roc_gen_wasm::WasmFeatures;
+use roc_load::{ExpectMetadata, LoadConfig, Threading};
 use roc_mono::ir::OptLevel;
+use roc_mono::overflow::OverflowMode;
 use roc_packaging::cache::RocCacheDir;
 use roc_packaging::tarball::Compression;
+use roc_reporting::cli::Problems;
 use std::env;
-use std::ffi::{CString, OsStr};
+#[cfg(target_family = "unix")]
+use std::ffi::CString;
+use std::ffi::OsStr;
 use std::io;
 use std::mem::ManuallyDrop;
+#[cfg(target_family = "unix")]
 use std::os::raw::{c_char, c_int};
 use std::path::{Path, PathBuf};
 use std::process;
@@ -28,14 +34,41 @@ use std::time::Instant;
 use strum::{EnumIter, IntoEnumIterator, IntoStaticStr};
 use target_lexicon::BinaryFormat;
 use target_lexicon::{
-    Architecture, Environment, OperatingSystem, Triple, Vendor, X86_32Architecture,
+    Aarch64Architecture, Architecture, Environment, OperatingSystem, Triple, Vendor,
+    X86_32Architecture,
 };
 #[cfg(not(target_os = "linux"))]
 use tempfile::TempDir;
 
+mod annotate;
+pub use annotate::annotate;
+
+mod bench;
+pub use bench::bench;
+
+mod check;
+pub use check::check_json;
+
+mod compat;
+pub use compat::{check_compat, compare_signatures, ApiChange, VersionBump};
+
+mod diff;
+pub use diff::{diff, diff_sources, Change, DefKind};
+
 mod format;
 pub use format::format;
 
+mod init;
+pub use init::{init, ProjectKind};
+
+mod refs;
+pub use refs::refs;
+
+mod rename;
+pub use rename::rename;
+
+pub mod warnings;
+
 pub const CMD_BUILD: &str = "build";
 pub const CMD_RUN: &str = "run";
 pub const CMD_DEV: &str = "dev";
@@ -46,29 +79,69 @@ pub const CMD_CHECK: &str = "check";
 pub const CMD_VERSION: &str = "version";
 pub const CMD_FORMAT: &str = "format";
 pub const CMD_TEST: &str = "test";
+pub const CMD_BENCH: &str = "bench";
 pub const CMD_GLUE: &str = "glue";
+pub const CMD_GLUE_JS: &str = "glue-js";
 pub const CMD_GEN_STUB_LIB: &str = "gen-stub-lib";
+pub const CMD_EXPLAIN: &str = "explain";
+pub const CMD_RENAME: &str = "rename";
+pub const CMD_REFS: &str = "refs";
+pub const CMD_ANNOTATE: &str = "annotate";
+pub const CMD_INIT: &str = "init";
+pub const CMD_DIFF: &str = "diff";
+pub const CMD_CHECK_COMPAT: &str = "check-compat";
 
 pub const FLAG_DEBUG: &str = "debug";
 pub const FLAG_BUNDLE: &str = "bundle";
 pub const FLAG_DEV: &str = "dev";
 pub const FLAG_OPTIMIZE: &str = "optimize";
 pub const FLAG_MAX_THREADS: &str = "max-threads";
+pub const FLAG_MAX_SPECIALIZATIONS: &str = "max-specializations";
 pub const FLAG_OPT_SIZE: &str = "opt-size";
 pub const FLAG_LIB: &str = "lib";
 pub const FLAG_NO_LINK: &str = "no-link";
+pub const FLAG_STATIC_LIB: &str = "static-lib";
 pub const FLAG_TARGET: &str = "target";
 pub const FLAG_TIME: &str = "time";
+pub const FLAG_TIME_TRACE: &str = "time-trace";
 pub const FLAG_LINKER: &str = "linker";
 pub const FLAG_PREBUILT: &str = "prebuilt-platform";
 pub const FLAG_CHECK: &str = "check";
 pub const FLAG_WASM_STACK_SIZE_KB: &str = "wasm-stack-size-kb";
+pub const FLAG_WASM_FEATURES: &str = "wasm-features";
+pub const FLAG_WARNING: &str = "warning";
+pub const FLAG_FAIL_ON_WARNINGS: &str = "fail-on-warnings";
+pub const FLAG_FORMAT: &str = "format";
+pub const FLAG_BACKEND: &str = "backend";
+pub const FLAG_WATCH: &str = "watch";
+pub const FLAG_EMIT: &str = "emit";
+pub const FLAG_TRACE_CALLS: &str = "trace-calls";
+pub const FLAG_ARENA_ALLOCATION: &str = "arena-allocation";
+pub const FLAG_OVERFLOW: &str = "overflow";
+pub const FLAG_HEAP_PROFILE: &str = "heap-profile";
+pub const FLAG_STACK_TRACES: &str = "stack-traces";
+pub const FLAG_STACK_LIMIT_CHECKS: &str = "stack-limit-checks";
+pub const FLAG_SANITIZER_FRIENDLY: &str = "sanitizer-friendly";
+pub const FLAG_PROFILE_GENERATE: &str = "profile-generate";
+pub const FLAG_PROFILE_USE: &str = "profile-use";
+pub const FLAG_REPRODUCIBLE: &str = "reproducible";
+pub const FLAG_BASELINE: &str = "baseline";
+pub const FLAG_SAVE_BASELINE: &str = "save-baseline";
+pub const FLAG_COVERAGE: &str = "coverage";
+pub const FLAG_PLATFORM: &str = "platform";
+pub const FLAG_PACKAGE: &str = "package";
 pub const ROC_FILE: &str = "ROC_FILE";
 pub const ROC_DIR: &str = "ROC_DIR";
 pub const GLUE_DIR: &str = "GLUE_DIR";
 pub const GLUE_SPEC: &str = "GLUE_SPEC";
 pub const DIRECTORY_OR_FILES: &str = "DIRECTORY_OR_FILES";
 pub const ARGS_FOR_APP: &str = "ARGS_FOR_APP";
+pub const ERROR_CODE: &str = "ERROR_CODE";
+pub const VALUE_NAME: &str = "VALUE_NAME";
+pub const NEW_NAME: &str = "NEW_NAME";
+pub const PROJECT_NAME: &str = "PROJECT_NAME";
+pub const OLD_ROC_FILE: &str = "OLD_ROC_FILE";
+pub const NEW_ROC_FILE: &str = "NEW_ROC_FILE";
 
 const VERSION: &str = include_str!("../../../version.txt");
 
@@ -85,6 +158,13 @@ pub fn build_app<'a>() -> Command<'a> {
         .validator(|s| s.parse::<usize>())
         .required(false);
 
+    let flag_max_specializations = Arg::new(FLAG_MAX_SPECIALIZATIONS)
+        .long(FLAG_MAX_SPECIALIZATIONS)
+        .help("Stop with an error if any function gets specialized more times than this\n(Use this to find the cause of builds that take very long or run out of memory.)")
+        .takes_value(true)
+        .validator(|s| s.parse::<usize>())
+        .required(false);
+
     let flag_opt_size = Arg::new(FLAG_OPT_SIZE)
         .long(FLAG_OPT_SIZE)
         .help("Optimize the compiled program to have a small binary size\n(Optimization takes time to complete.)")
@@ -97,7 +177,59 @@ pub fn build_app<'a>() -> Command<'a> {
 
     let flag_debug = Arg::new(FLAG_DEBUG)
         .long(FLAG_DEBUG)
-        .help("Store LLVM debug information in the generated program")
+        .help("Store LLVM debug information in the generated program\n(With --dev and a wasm32 target, write a source map that browser devtools use to show Roc source instead.)")
+        .required(false);
+
+    let flag_trace_calls = Arg::new(FLAG_TRACE_CALLS)
+        .long(FLAG_TRACE_CALLS)
+        .help("Call the platform's roc_trace_enter and roc_trace_exit functions with the name of every Roc function as it is entered and exited\n(The platform must define both, e.g. to record timestamps for a flame graph.)")
+        .required(false);
+
+    let flag_arena_allocation = Arg::new(FLAG_ARENA_ALLOCATION)
+        .long(FLAG_ARENA_ALLOCATION)
+        .help("Allocate from large chunks and never free them, and skip decrementing what a value refers to when the value is dropped, which makes exiting faster for short-lived programs\n(Only use this with a platform that frees everything roc_alloc returned when the program exits. The chunks are only used with the LLVM backend.)")
+        .required(false);
+
+    let flag_overflow = Arg::new(FLAG_OVERFLOW)
+        .long(FLAG_OVERFLOW)
+        .help("Set whether integer +, -, and * crash the program or wrap around when the result doesn't fit\n(By default they crash, with or without --optimize, so optimizing never changes what a program computes. Pass --overflow=wrap to skip the overflow checks. Use Num.addWrap, Num.addSaturated, or Num.addChecked to choose per operation.)")
+        .possible_values(["panic", "wrap"])
+        .required(false);
+
+    let flag_heap_profile = Arg::new(FLAG_HEAP_PROFILE)
+        .long(FLAG_HEAP_PROFILE)
+        .help("Call the platform's roc_heap_profile_alloc and roc_heap_profile_dealloc functions with the layout of every box and recursive tag union that is allocated or freed\n(The platform must define both, e.g. to find values that are never freed.)")
+        .required(false);
+
+    let flag_stack_traces = Arg::new(FLAG_STACK_TRACES)
+        .long(FLAG_STACK_TRACES)
+        .help("When the compiled program crashes, add the Roc functions it was running, and where they are, to the crash message\n(This makes every function call a bit slower. Only the LLVM backend supports it.)")
+        .required(false);
+
+    let flag_stack_limit_checks = Arg::new(FLAG_STACK_LIMIT_CHECKS)
+        .long(FLAG_STACK_LIMIT_CHECKS)
+        .help("Crash with a \"stack overflow\" message when recursion gets deeper than the platform allows, instead of segfaulting\n(The platform must define roc_stack_limit, which returns the lowest address the stack may reach. This makes every function call a bit slower. Only the LLVM backend supports it.)")
+        .required(false);
+
+    let flag_sanitizer_friendly = Arg::new(FLAG_SANITIZER_FRIENDLY)
+        .long(FLAG_SANITIZER_FRIENDLY)
+        .help("Build so memory errors in the compiled program can be found with tools like Valgrind and AddressSanitizer\n(This uses the legacy linker, keeps frame pointers, and stores the tags of recursive tag unions next to their data instead of in pointers. The platform must not read recursive tag unions itself.)")
+        .required(false);
+
+    let flag_profile_generate = Arg::new(FLAG_PROFILE_GENERATE)
+        .long(FLAG_PROFILE_GENERATE)
+        .help("Count how often every function and branch runs, and write the counts next to the executable (as <app>.rocprof) when it exits\n(Run the program on typical input, then build it again with --profile-use. Only the LLVM backend supports this.)")
+        .conflicts_with(FLAG_PROFILE_USE)
+        .required(false);
+
+    let flag_profile_use = Arg::new(FLAG_PROFILE_USE)
+        .long(FLAG_PROFILE_USE)
+        .help("Optimize using the counts a --profile-generate build wrote next to the executable\n(This orders branches by how often they were taken, inlines small functions that ran often, and keeps functions that never ran out of the way. Only the LLVM backend supports this.)")
+        .required(false);
+
+    let flag_reproducible = Arg::new(FLAG_REPRODUCIBLE)
+        .long(FLAG_REPRODUCIBLE)
+        .help("Produce byte-for-byte the same output every time the same source is built\n(Procs are generated in an order that doesn't depend on how the loader threads were scheduled, so this works with any --max-threads.)")
         .required(false);
 
     let flag_time = Arg::new(FLAG_TIME)
@@ -105,6 +237,13 @@ pub fn build_app<'a>() -> Command<'a> {
         .help("Print detailed compilation time information")
         .required(false);
 
+    let flag_time_trace = Arg::new(FLAG_TIME_TRACE)
+        .long(FLAG_TIME_TRACE)
+        .help("Write a Chrome trace-event JSON file with the time each compilation phase took\n(Open it in chrome://tracing or ui.perfetto.dev.)")
+        .takes_value(true)
+        .value_name("FILE")
+        .required(false);
+
     let flag_linker = Arg::new(FLAG_LINKER)
         .long(FLAG_LINKER)
         .help("Set which linker to use\n(The surgical linker is enabled by default only when building for wasm32 or x86_64 Linux, because those are the only targets it currently supports. Otherwise the legacy linker is used by default.)")
@@ -124,6 +263,27 @@ pub fn build_app<'a>() -> Command<'a> {
         .validator(|s| s.parse::<u32>())
         .required(false);
 
+    let flag_wasm_features = Arg::new(FLAG_WASM_FEATURES)
+        .long(FLAG_WASM_FEATURES)
+        .help("Comma-separated Wasm proposals the wasm32 target may use: bulk-memory, simd\n(This only applies when --dev also provided.)")
+        .takes_value(true)
+        .validator(|s| s.parse::<WasmFeatures>())
+        .required(false);
+
+    let flag_warning = Arg::new(FLAG_WARNING)
+        .long(FLAG_WARNING)
+        .short('W')
+        .help("Set how a kind of warning is reported, e.g. `-W unused-import=off`\n(The level can be error, warning, or off. This overrides the [warnings] section of roc.toml.)")
+        .takes_value(true)
+        .multiple_occurrences(true)
+        .validator(warnings::parse_lint_level)
+        .required(false);
+
+    let flag_fail_on_warnings = Arg::new(FLAG_FAIL_ON_WARNINGS)
+        .long(FLAG_FAIL_ON_WARNINGS)
+        .help("Treat all warnings as errors")
+        .required(false);
+
     let roc_file_to_run = Arg::new(ROC_FILE)
         .help("The .roc file of an app to run")
         .allow_invalid_utf8(true)
@@ -145,13 +305,28 @@ pub fn build_app<'a>() -> Command<'a> {
             .about("Build a binary from the given .roc file, but don't run it")
             .arg(flag_optimize.clone())
             .arg(flag_max_threads.clone())
+            .arg(flag_max_specializations.clone())
             .arg(flag_opt_size.clone())
             .arg(flag_dev.clone())
             .arg(flag_debug.clone())
+            .arg(flag_trace_calls.clone())
+            .arg(flag_arena_allocation.clone())
+            .arg(flag_overflow.clone())
+            .arg(flag_heap_profile.clone())
+            .arg(flag_stack_traces.clone())
+            .arg(flag_stack_limit_checks.clone())
+            .arg(flag_sanitizer_friendly.clone())
+            .arg(flag_profile_generate)
+            .arg(flag_profile_use)
+            .arg(flag_reproducible.clone())
             .arg(flag_time.clone())
+            .arg(flag_time_trace.clone())
             .arg(flag_linker.clone())
             .arg(flag_prebuilt.clone())
+            .arg(flag_warning.clone())
+            .arg(flag_fail_on_warnings.clone())
             .arg(flag_wasm_stack_size_kb.clone())
+            .arg(flag_wasm_features.clone())
             .arg(
                 Arg::new(FLAG_TARGET)
                     .long(FLAG_TARGET)
@@ -165,7 +340,14 @@ pub fn build_app<'a>() -> Command<'a> {
             .arg(
                 Arg::new(FLAG_LIB)
                     .long(FLAG_LIB)
-                    .help("Build a C library instead of an executable")
+                    .help("Build a C library instead of an executable\n(A C header declaring what the library exposes is written next to it.)")
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_STATIC_LIB)
+                    .long(FLAG_STATIC_LIB)
+                    .help("Build a static C library instead of an executable\n(A C header declaring what the library exposes is written next to it.)")
+                    .conflicts_with_all(&[FLAG_LIB, FLAG_NO_LINK])
                     .required(false),
             )
             .arg(
@@ -182,10 +364,19 @@ pub fn build_app<'a>() -> Command<'a> {
                     .help("Do not link\n(Instead, just output the `.o` file.)")
                     .required(false),
             )
+            .arg(
+                Arg::new(FLAG_EMIT)
+                    .long(FLAG_EMIT)
+                    .help("Also write out extra information after building\n(`size-report` prints how much code size each Roc proc, generated helper, builtin, and the host contribute. `host-layouts` writes the size, alignment, and field offsets of every layout exposed to the host to a `.host-layouts.json` file next to the executable, for generating bindings in other languages. `llvm-ir` writes the app's LLVM IR before and after optimization to `.unoptimized.ll` and `.optimized.ll` files, and its optimized bitcode to a `.bc` file, next to the executable; only the LLVM backend supports this. `call-graph` writes which Roc procs call which procs, generated refcounting helpers, builtins, and host functions to `.call-graph.json` and `.call-graph.dot` files next to the executable, with the caller that keeps each one in the app. `c-header` writes C declarations of the types and functions exposed to the host to a `.h` file next to the executable.)")
+                    .possible_values(["size-report", "host-layouts", "llvm-ir", "call-graph", "c-header"])
+                    .conflicts_with(FLAG_NO_LINK)
+                    .required(false),
+            )
             .arg(
                 Arg::new(ROC_FILE)
-                    .help("The .roc file to build")
+                    .help("The .roc files to build\n(Apps on the same platform share one build of its host.)")
                     .allow_invalid_utf8(true)
+                    .multiple_values(true)
                     .required(false)
                     .default_value(DEFAULT_ROC_FILENAME),
             )
@@ -194,12 +385,23 @@ pub fn build_app<'a>() -> Command<'a> {
             .about("Run all top-level `expect`s in a main module and any modules it imports")
             .arg(flag_optimize.clone())
             .arg(flag_max_threads.clone())
+            .arg(flag_max_specializations.clone())
             .arg(flag_opt_size.clone())
             .arg(flag_dev.clone())
             .arg(flag_debug.clone())
+            .arg(flag_arena_allocation.clone())
+            .arg(flag_overflow.clone())
             .arg(flag_time.clone())
             .arg(flag_linker.clone())
             .arg(flag_prebuilt.clone())
+            .arg(flag_warning.clone())
+            .arg(flag_fail_on_warnings.clone())
+            .arg(
+                Arg::new(FLAG_COVERAGE)
+                    .long(FLAG_COVERAGE)
+                    .help("Count how often each function and branch runs, and write the counts to lcov.info\n(Counts from `expect-fx` are not included.)")
+                    .required(false),
+            )
             .arg(
                 Arg::new(ROC_FILE)
                     .help("The .roc file for the main module")
@@ -209,19 +411,74 @@ pub fn build_app<'a>() -> Command<'a> {
             )
             .arg(args_for_app.clone())
         )
+        .subcommand(Command::new(CMD_BENCH)
+            .about("Time all top-level `bench`es in a main module and any modules it imports")
+            .arg(flag_warning.clone())
+            .arg(flag_fail_on_warnings.clone())
+            .arg(
+                Arg::new(FLAG_BASELINE)
+                    .long(FLAG_BASELINE)
+                    .help("Compare the times to a baseline saved by an earlier `--save-baseline`")
+                    .takes_value(true)
+                    .allow_invalid_utf8(true)
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_SAVE_BASELINE)
+                    .long(FLAG_SAVE_BASELINE)
+                    .help("Save the times to this file, to compare later runs to with `--baseline`")
+                    .takes_value(true)
+                    .allow_invalid_utf8(true)
+                    .required(false),
+            )
+            .arg(
+                Arg::new(ROC_FILE)
+                    .help("The .roc file for the main module")
+                    .allow_invalid_utf8(true)
+                    .required(false)
+                    .default_value(DEFAULT_ROC_FILENAME)
+            )
+        )
         .subcommand(Command::new(CMD_REPL)
             .about("Launch the interactive Read Eval Print Loop (REPL)")
+            .arg(
+                Arg::new(FLAG_BACKEND)
+                    .long(FLAG_BACKEND)
+                    .help("Choose how to run the expressions you enter\n(The wasm backend runs them in an embedded WebAssembly interpreter, so it works on hosts LLVM can't JIT for.)")
+                    .possible_values(["llvm", "wasm"])
+                    .required(false),
+            )
         )
         .subcommand(Command::new(CMD_RUN)
             .about("Run a .roc file even if it has build errors")
             .arg(flag_optimize.clone())
             .arg(flag_max_threads.clone())
+            .arg(flag_max_specializations.clone())
             .arg(flag_opt_size.clone())
             .arg(flag_dev.clone())
             .arg(flag_debug.clone())
+            .arg(flag_trace_calls.clone())
+            .arg(flag_arena_allocation.clone())
+            .arg(flag_overflow.clone())
+            .arg(flag_heap_profile.clone())
+            .arg(flag_stack_traces.clone())
+            .arg(flag_stack_limit_checks.clone())
+            .arg(flag_sanitizer_friendly.clone())
+            .arg(flag_reproducible.clone())
             .arg(flag_time.clone())
+            .arg(flag_time_trace.clone())
             .arg(flag_linker.clone())
             .arg(flag_prebuilt.clone())
+            .arg(flag_warning.clone())
+            .arg(flag_fail_on_warnings.clone())
+            .arg(
+                Arg::new(FLAG_TARGET)
+                    .long(FLAG_TARGET)
+                    .help("Choose a different target\n(wasm32 programs run in an embedded WASI runtime, which supplies no `roc_alloc` or `roc_panic`, so the compiler defines any the platform leaves out.)")
+                    .default_value(Target::default().into())
+                    .possible_values([Target::System, Target::Wasm32].map(Into::<&'static str>::into))
+                    .required(false),
+            )
             .arg(roc_file_to_run.clone())
             .arg(args_for_app.clone())
         )
@@ -229,12 +486,30 @@ pub fn build_app<'a>() -> Command<'a> {
             .about("`check` a .roc file, and then run it if there were no errors")
             .arg(flag_optimize.clone())
             .arg(flag_max_threads.clone())
+            .arg(flag_max_specializations.clone())
             .arg(flag_opt_size.clone())
             .arg(flag_dev.clone())
             .arg(flag_debug.clone())
+            .arg(flag_trace_calls.clone())
+            .arg(flag_arena_allocation.clone())
+            .arg(flag_overflow.clone())
+            .arg(flag_heap_profile.clone())
+            .arg(flag_stack_traces.clone())
+            .arg(flag_stack_limit_checks.clone())
+            .arg(flag_sanitizer_friendly.clone())
+            .arg(flag_reproducible.clone())
             .arg(flag_time.clone())
+            .arg(flag_time_trace.clone())
             .arg(flag_linker.clone())
             .arg(flag_prebuilt.clone())
+            .arg(flag_warning.clone())
+            .arg(flag_fail_on_warnings.clone())
+            .arg(
+                Arg::new(FLAG_WATCH)
+                    .long(FLAG_WATCH)
+                    .help("Rebuild and rerun the app whenever a .roc file in its directory changes")
+                    .required(false),
+            )
             .arg(roc_file_to_run.clone())
             .arg(args_for_app.clone())
         )
@@ -245,6 +520,7 @@ pub fn build_app<'a>() -> Command<'a> {
                     .index(1)
                     .multiple_values(true)
                     .required(false)
+                    .help("The .roc files, directories, or globs like 'src/**/*.roc' to format\n(Defaults to every .roc file in the current directory.)")
                     .allow_invalid_utf8(true))
             .arg(
                 Arg::new(FLAG_CHECK)
@@ -259,6 +535,16 @@ pub fn build_app<'a>() -> Command<'a> {
             .about("Check the code for problems, but don’t build or run it")
             .arg(flag_time.clone())
             .arg(flag_max_threads.clone())
+            .arg(flag_warning.clone())
+            .arg(flag_fail_on_warnings.clone())
+            .arg(
+                Arg::new(FLAG_FORMAT)
+                    .long(FLAG_FORMAT)
+                    .help("How to print the problems found\n(`json` prints a JSON array with the quick fixes for each problem, for editors and scripts.)")
+                    .possible_values(["human", "json"])
+                    .default_value("human")
+                    .required(false)
+            )
             .arg(
                 Arg::new(ROC_FILE)
                     .help("The .roc file of an app to check")
@@ -301,6 +587,128 @@ pub fn build_app<'a>() -> Command<'a> {
                     .default_value(DEFAULT_ROC_FILENAME)
             )
         )
+        .subcommand(Command::new(CMD_GLUE_JS)
+            .about("Generate an ES module and TypeScript declarations for running a Roc app compiled to Wasm")
+            .arg(
+                Arg::new(GLUE_DIR)
+                    .help("The directory for the generated glue code.")
+                    .allow_invalid_utf8(true)
+                    .required(true)
+            )
+            .arg(
+                Arg::new(ROC_FILE)
+                    .help("The .roc file whose exposed types should be translated.")
+                    .allow_invalid_utf8(true)
+                    .required(false)
+                    .default_value(DEFAULT_ROC_FILENAME)
+            )
+        )
+        .subcommand(Command::new(CMD_EXPLAIN)
+            .about("Explain an error code from a compiler report in more detail")
+            .arg(
+                Arg::new(ERROR_CODE)
+                    .help("The code shown in the report's header, e.g. E0301")
+                    .required(true)
+            )
+        )
+        .subcommand(Command::new(CMD_RENAME)
+            .about("Rename a top-level value, along with every reference to it in the app's modules")
+            .arg(
+                Arg::new(VALUE_NAME)
+                    .help("The value to rename, e.g. `parse`, or `Json.parse` for one in another module")
+                    .required(true)
+            )
+            .arg(
+                Arg::new(NEW_NAME)
+                    .help("The value's new name")
+                    .required(true)
+            )
+            .arg(
+                Arg::new(ROC_FILE)
+                    .help("The .roc file of the app whose modules should be updated")
+                    .allow_invalid_utf8(true)
+                    .required(false)
+                    .default_value(DEFAULT_ROC_FILENAME),
+            )
+        )
+        .subcommand(Command::new(CMD_REFS)
+            .about("List every reference to a top-level value in the app's modules")
+            .arg(
+                Arg::new(VALUE_NAME)
+                    .help("The value to look for, e.g. `parse`, or `Json.parse` for one in another module")
+                    .required(true)
+            )
+            .arg(
+                Arg::new(ROC_FILE)
+                    .help("The .roc file of the app whose modules should be searched")
+                    .allow_invalid_utf8(true)
+                    .required(false)
+                    .default_value(DEFAULT_ROC_FILENAME),
+            )
+        )
+        .subcommand(Command::new(CMD_ANNOTATE)
+            .about("Add type annotations with the inferred types of the top-level values that don't have one")
+            .arg(
+                Arg::new(ROC_FILE)
+                    .help("The .roc file to annotate")
+                    .allow_invalid_utf8(true)
+                    .required(false)
+                    .default_value(DEFAULT_ROC_FILENAME),
+            )
+        )
+        .subcommand(Command::new(CMD_DIFF)
+            .about("Show how the definitions in two .roc files differ, ignoring formatting and comments")
+            .arg(
+                Arg::new(OLD_ROC_FILE)
+                    .help("The .roc file before the changes")
+                    .allow_invalid_utf8(true)
+                    .required(true)
+            )
+            .arg(
+                Arg::new(NEW_ROC_FILE)
+                    .help("The .roc file after the changes")
+                    .allow_invalid_utf8(true)
+                    .required(true)
+            )
+        )
+        .subcommand(Command::new(CMD_CHECK_COMPAT)
+            .about("Check whether a new version of a package can replace the old one without breaking code that uses it, and which part of its version number should change")
+            .arg(
+                Arg::new(OLD_ROC_FILE)
+                    .help("The old version's directory, or its main.roc")
+                    .allow_invalid_utf8(true)
+                    .required(true)
+            )
+            .arg(
+                Arg::new(NEW_ROC_FILE)
+                    .help("The new version's directory, or its main.roc")
+                    .allow_invalid_utf8(true)
+                    .required(true)
+            )
+        )
+        .subcommand(Command::new(CMD_INIT)
+            .about("Create a new directory with a starter app or package in it")
+            .arg(
+                Arg::new(PROJECT_NAME)
+                    .help("The name of the project, which is also used for its directory")
+                    .required(true)
+            )
+            .arg(
+                Arg::new(FLAG_PLATFORM)
+                    .long(FLAG_PLATFORM)
+                    .help("The URL of the platform the app should use")
+                    .takes_value(true)
+                    .default_value(init::DEFAULT_PLATFORM)
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_PACKAGE)
+                    .long(FLAG_PACKAGE)
+                    .help("Create a package instead of an app")
+                    .conflicts_with(FLAG_PLATFORM)
+                    .required(false),
+            )
+        )
         .subcommand(Command::new(CMD_GEN_STUB_LIB)
             .about("Generate a stubbed shared library that can be used for linking a platform binary.\nThe stubbed library has prototypes, but no function bodies.\n\nNote: This command will be removed in favor of just using `roc build` once all platforms support the surgical linker")
             .arg(
@@ -323,12 +731,24 @@ pub fn build_app<'a>() -> Command<'a> {
         .trailing_var_arg(true)
         .arg(flag_optimize)
         .arg(flag_max_threads.clone())
+        .arg(flag_max_specializations)
         .arg(flag_opt_size)
         .arg(flag_dev)
         .arg(flag_debug)
+        .arg(flag_trace_calls)
+        .arg(flag_arena_allocation)
+        .arg(flag_overflow.clone())
+        .arg(flag_heap_profile)
+        .arg(flag_stack_traces)
+        .arg(flag_stack_limit_checks)
+        .arg(flag_sanitizer_friendly)
+        .arg(flag_reproducible)
         .arg(flag_time)
+        .arg(flag_time_trace)
         .arg(flag_linker)
         .arg(flag_prebuilt)
+        .arg(flag_warning)
+        .arg(flag_fail_on_warnings)
         .arg(roc_file_to_run.required(false))
         .arg(args_for_app);
 
@@ -349,18 +769,82 @@ pub fn build_app<'a>() -> Command<'a> {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BuildConfig {
     BuildOnly,
     BuildAndRun,
     BuildAndRunIfNoErrors,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FormatMode {
     Format,
     CheckOnly,
 }
 
+fn print_built(binary_path: &Path, problems: &Problems, total_time: std::time::Duration) {
+    // If possible, report the generated executable name relative to the current dir.
+    let generated_filename = binary_path
+        .strip_prefix(env::current_dir().unwrap())
+        .unwrap_or(binary_path)
+        .to_str()
+        .unwrap();
+
+    problems.print_to_stdout(total_time);
+    println!(" while successfully building:\n\n    {generated_filename}");
+}
+
+fn print_size_report(binary_path: &Path) {
+    use roc_linker::CodeOrigin;
+
+    let bytes = std::fs::read(binary_path).unwrap_or_else(|err| {
+        user_error!(
+            "Could not read {} to report its size: {err}",
+            binary_path.display()
+        )
+    });
+
+    let entries = match roc_linker::size_report(&bytes) {
+        Some(entries) => entries,
+        None => {
+            println!(
+                "\nCould not produce a size report: the output is not an object file roc can read."
+            );
+            return;
+        }
+    };
+
+    let origins = [
+        CodeOrigin::RocProc,
+        CodeOrigin::GeneratedHelper,
+        CodeOrigin::Builtin,
+        CodeOrigin::Host,
+    ];
+
+    println!("\nCode size by origin:\n");
+
+    for origin in origins {
+        let total: u64 = entries
+            .iter()
+            .filter(|entry| entry.origin == origin)
+            .map(|entry| entry.size)
+            .sum();
+
+        println!("    {:>10} bytes  {}", total, origin.description());
+    }
+
+    // Host code is usually dominated by libc and the host language's runtime,
+    // so only its total is interesting here.
+    println!("\nCode size by function (excluding host code):\n");
+
+    for entry in entries
+        .iter()
+        .filter(|entry| entry.origin != CodeOrigin::Host)
+    {
+        println!("    {:>10} bytes  {}", entry.size, entry.name);
+    }
+}
+
 fn opt_level_from_flags(matches: &ArgMatches) -> OptLevel {
     match (
         matches.is_present(FLAG_OPTIMIZE),
@@ -383,7 +867,7 @@ pub fn test(_matches: &ArgMatches, _triple: Triple) -> io::Result<i32> {
 #[cfg(not(windows))]
 pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
     use roc_build::program::report_problems_monomorphized;
-    use roc_load::{ExecutionMode, LoadConfig, LoadMonomorphizedError};
+    use roc_load::{ExecutionMode, LoadMonomorphizedError};
     use roc_packaging::cache;
     use roc_target::TargetInfo;
 
@@ -427,6 +911,8 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
         process::exit(1);
     }
 
+    let severity_config = warnings::severity_config(matches, path);
+
     let arena = &arena;
     let target = &triple;
     let opt_level = opt_level;
@@ -437,9 +923,17 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
         target_info,
         // TODO: expose this from CLI?
         render: roc_reporting::report::RenderTarget::ColorTerminal,
-        palette: roc_reporting::report::DEFAULT_PALETTE,
+        palette: roc_reporting::report::Palette::from_env(),
         threading,
         exec_mode: ExecutionMode::Test,
+        max_specializations: matches
+            .value_of(FLAG_MAX_SPECIALIZATIONS)
+            .and_then(|s| s.parse::<usize>().ok()),
+        coverage: matches.is_present(FLAG_COVERAGE),
+        fold_constants: false,
+        hoist_loop_invariants: false,
+        strip_expects: false,
+        cancelled: None,
     };
     let load_result = roc_load::load_and_monomorphize(
         arena,
@@ -454,21 +948,55 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
             return handle_loading_problem(problem);
         }
         Err(LoadMonomorphizedError::ErrorModule(module)) => {
-            return handle_error_module(module, start_time.elapsed(), filename, false);
+            return handle_error_module(
+                module,
+                start_time.elapsed(),
+                filename,
+                false,
+                &severity_config,
+            );
         }
     };
-    let problems = report_problems_monomorphized(&mut loaded);
+    let problems = report_problems_monomorphized(&mut loaded, &severity_config);
+
+    if problems.errors > 0 {
+        // Only possible when warnings are configured to be errors.
+        problems.print_to_stdout(start_time.elapsed());
+        println!();
+
+        return Ok(problems.exit_code());
+    }
 
     let mut expectations = std::mem::take(&mut loaded.expectations);
+    let coverage_points = std::mem::take(&mut loaded.coverage_points);
+    let sources = std::mem::take(&mut loaded.sources);
 
     let interns = loaded.interns.clone();
 
+    let arena_allocation = matches.is_present(FLAG_ARENA_ALLOCATION);
+
+    if arena_allocation {
+        roc_mono::arena_allocation::skip_recursive_decrements(arena, &mut loaded.procedures);
+    }
+
+    let overflow = match matches.value_of(FLAG_OVERFLOW) {
+        Some(mode) => mode.parse().unwrap(),
+        None => OverflowMode::Panic,
+    };
+    roc_mono::overflow::apply_overflow_mode(
+        arena,
+        &loaded.layout_interner,
+        &mut loaded.procedures,
+        overflow,
+    );
+
     let (lib, expects, layout_interner) = roc_repl_expect::run::expect_mono_module_to_dylib(
         arena,
         target.clone(),
         loaded,
         opt_level,
         LlvmBackendMode::CliTest,
+        arena_allocation,
     )
     .unwrap();
 
@@ -502,6 +1030,17 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
     )
     .unwrap();
 
+    if matches.is_present(FLAG_COVERAGE) {
+        use roc_repl_expect::coverage::{lcov, read_counts};
+
+        let counts = read_counts(&lib, &coverage_points);
+        let lcov_path = Path::new("lcov.info");
+
+        std::fs::write(lcov_path, lcov(&coverage_points, &counts, &sources))?;
+
+        println!("Wrote coverage to {}", lcov_path.display());
+    }
+
     let total_time = start_time.elapsed();
 
     if failed == 0 && passed == 0 {
@@ -530,17 +1069,112 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
     }
 }
 
+/// Build the app at `ROC_FILE`, or with `roc build`, each of the apps given, one after another.
+///
+/// Apps built together only share the build of their platform's host. Each of them is still
+/// loaded, typechecked and specialized on its own, because the loader works from a single root
+/// module, and the ModuleIds it hands out depend on the order modules are found in.
 pub fn build(
     matches: &ArgMatches,
     config: BuildConfig,
     triple: Triple,
     roc_cache_dir: RocCacheDir<'_>,
     link_type: LinkType,
+) -> io::Result<i32> {
+    if config != BuildConfig::BuildOnly {
+        let filename = matches.value_of_os(ROC_FILE).unwrap();
+
+        return build_roc_file(
+            matches,
+            config,
+            triple,
+            roc_cache_dir,
+            link_type,
+            filename,
+            false,
+        );
+    }
+
+    // apps on the same platform link with the same host, so only the first one rebuilds it
+    let mut rebuilt_platforms: Vec<PathBuf> = Vec::new();
+    let mut exit_code = 0;
+
+    for filename in matches.values_of_os(ROC_FILE).unwrap() {
+        let platform = local_platform(Path::new(filename));
+        let host_rebuilt =
+            matches!(&platform, Some(platform) if rebuilt_platforms.contains(platform));
+
+        let app_exit_code = build_roc_file(
+            matches,
+            config,
+            triple.clone(),
+            roc_cache_dir,
+            link_type,
+            filename,
+            host_rebuilt,
+        )?;
+
+        exit_code = exit_code.max(app_exit_code);
+        rebuilt_platforms.extend(platform);
+    }
+
+    Ok(exit_code)
+}
+
+/// The main.roc of the platform of the app at `app_path`, unless that platform is downloaded
+fn local_platform(app_path: &Path) -> Option<PathBuf> {
+    use roc_parse::ast::{ExtractSpaces, Header};
+    use roc_parse::header::To;
+    use roc_parse::state::State;
+
+    let arena = Bump::new();
+    let src = std::fs::read(app_path).ok()?;
+
+    let header = match roc_parse::module::parse_header(&arena, State::new(&src)) {
+        Ok((module, _)) => match module.header {
+            Header::App(header) => header,
+            _ => return None,
+        },
+        Err(_) => return None,
+    };
+
+    let platform = match header.provides.to.value {
+        To::NewPackage(package_name) => package_name,
+        To::ExistingPackage(shorthand) => {
+            header
+                .packages?
+                .item
+                .iter()
+                .map(|entry| entry.value.extract_spaces().item)
+                .find(|entry| entry.shorthand == shorthand)?
+                .package_name
+                .value
+        }
+    };
+
+    if platform.as_str().starts_with("https://") {
+        None
+    } else {
+        app_path
+            .parent()?
+            .join(platform.as_str())
+            .canonicalize()
+            .ok()
+    }
+}
+
+fn build_roc_file(
+    matches: &ArgMatches,
+    config: BuildConfig,
+    triple: Triple,
+    roc_cache_dir: RocCacheDir<'_>,
+    link_type: LinkType,
+    filename: &OsStr,
+    host_rebuilt: bool,
 ) -> io::Result<i32> {
     use roc_build::program::build_file;
     use BuildConfig::*;
 
-    let filename = matches.value_of_os(ROC_FILE).unwrap();
     let path_buf = {
         let path = Path::new(filename);
 
@@ -612,6 +1246,13 @@ pub fn build(
         path.to_path_buf()
     };
 
+    // Pick versions for the project's [dependencies] before loading, so that
+    // module headers can refer to them by name.
+    let project_dir = path_buf.parent().unwrap_or_else(|| Path::new("."));
+    if let Err(problem) = roc_packaging::manifest::update_lockfile(project_dir) {
+        user_error!("{}", problem);
+    }
+
     // the process will end after this function,
     // so we don't want to spend time freeing these values
     let arena = ManuallyDrop::new(Bump::new());
@@ -622,25 +1263,49 @@ pub fn build(
         opt_level_from_flags(matches)
     };
 
-    // Note: This allows using `--dev` with `--optimize`.
-    // This means frontend optimizations and dev backend.
-    let code_gen_backend = if matches.is_present(FLAG_DEV) {
-        if matches!(triple.architecture, Architecture::Wasm32) {
-            CodeGenBackend::Wasm
-        } else {
-            CodeGenBackend::Assembly(AssemblyBackendMode::Binary)
+    let sanitizer_friendly = matches.is_present(FLAG_SANITIZER_FRIENDLY);
+
+    if sanitizer_friendly && matches.is_present(FLAG_DEV) {
+        user_error!("--{FLAG_SANITIZER_FRIENDLY} is only supported by the LLVM backend, so it cannot be used with --{FLAG_DEV}");
+    }
+
+    let stack_traces = matches.is_present(FLAG_STACK_TRACES);
+
+    if stack_traces && matches.is_present(FLAG_DEV) {
+        user_error!("--{FLAG_STACK_TRACES} is only supported by the LLVM backend, so it cannot be used with --{FLAG_DEV}");
+    }
+
+    let stack_limit_checks = matches.is_present(FLAG_STACK_LIMIT_CHECKS);
+
+    if stack_limit_checks && matches.is_present(FLAG_DEV) {
+        user_error!("--{FLAG_STACK_LIMIT_CHECKS} is only supported by the LLVM backend, so it cannot be used with --{FLAG_DEV}");
+    }
+
+    // only `roc build` has the profile flags
+    let profile_generate = config == BuildOnly && matches.is_present(FLAG_PROFILE_GENERATE);
+    let profile_use = config == BuildOnly && matches.is_present(FLAG_PROFILE_USE);
+
+    for (used, flag) in [
+        (profile_generate, FLAG_PROFILE_GENERATE),
+        (profile_use, FLAG_PROFILE_USE),
+    ] {
+        if used && matches.is_present(FLAG_DEV) {
+            user_error!("--{flag} is only supported by the LLVM backend, so it cannot be used with --{FLAG_DEV}");
         }
-    } else {
-        let backend_mode = match opt_level {
-            OptLevel::Development => LlvmBackendMode::BinaryDev,
-            OptLevel::Normal | OptLevel::Size | OptLevel::Optimize => LlvmBackendMode::Binary,
-        };
+    }
 
-        CodeGenBackend::Llvm(backend_mode)
-    };
+    // Note: This allows using `--dev` with `--optimize`.
+    // This means frontend optimizations and dev backend.
+    let code_gen_backend =
+        roc_compile::code_gen_backend(matches.is_present(FLAG_DEV), &triple, opt_level);
 
     let emit_debug_info = matches.is_present(FLAG_DEBUG);
     let emit_timings = matches.is_present(FLAG_TIME);
+    let time_trace_path = matches
+        .try_get_one::<String>(FLAG_TIME_TRACE)
+        .ok()
+        .flatten()
+        .map(PathBuf::from);
 
     let threading = match matches
         .value_of(FLAG_MAX_THREADS)
@@ -652,25 +1317,20 @@ pub fn build(
         Some(n) => Threading::AtMost(n),
     };
 
-    let wasm_dev_backend = matches!(code_gen_backend, CodeGenBackend::Wasm);
-
-    let linking_strategy = if wasm_dev_backend {
-        LinkingStrategy::Additive
-    } else if !roc_linker::supported(link_type, &triple)
-        || matches.value_of(FLAG_LINKER) == Some("legacy")
-    {
-        LinkingStrategy::Legacy
-    } else {
-        LinkingStrategy::Surgical
-    };
+    let linking_strategy = roc_compile::linking_strategy(
+        code_gen_backend,
+        link_type,
+        &triple,
+        // the counts are written by a destructor, which the surgical linker doesn't run
+        matches.value_of(FLAG_LINKER) == Some("legacy") || sanitizer_friendly || profile_generate,
+    );
 
-    let prebuilt = if matches.is_present(FLAG_PREBUILT) {
+    let prebuilt = if host_rebuilt {
+        true
+    } else if matches.is_present(FLAG_PREBUILT) {
         matches.value_of(FLAG_PREBUILT) == Some("true")
     } else {
-        // When compiling for a different target, default to assuming a prebuilt platform.
-        // Otherwise compilation would most likely fail because many toolchains assume you're compiling for the current machine.
-        // We make an exception for Wasm, because cross-compiling is the norm in that case.
-        triple != Triple::host() && !matches!(triple.architecture, Architecture::Wasm32)
+        roc_compile::assume_prebuilt(&triple)
     };
 
     let wasm_dev_stack_bytes: Option<u32> = matches
@@ -680,6 +1340,13 @@ pub fn build(
         .and_then(|s| s.parse::<u32>().ok())
         .map(|x| x * 1024);
 
+    let wasm_dev_features: WasmFeatures = matches
+        .try_get_one::<String>(FLAG_WASM_FEATURES)
+        .ok()
+        .flatten()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or_default();
+
     let build_ordering = match config {
         BuildAndRunIfNoErrors => BuildOrdering::BuildIfChecks,
         _ => BuildOrdering::AlwaysBuild,
@@ -689,9 +1356,77 @@ pub fn build(
         backend: code_gen_backend,
         opt_level,
         emit_debug_info,
+        trace_calls: matches.is_present(FLAG_TRACE_CALLS),
+        arena_allocation: matches.is_present(FLAG_ARENA_ALLOCATION),
+        overflow: match matches.value_of(FLAG_OVERFLOW) {
+            Some(mode) => mode.parse().unwrap(),
+            None => OverflowMode::Panic,
+        },
+        heap_profile: matches.is_present(FLAG_HEAP_PROFILE),
+        stack_traces,
+        stack_limit_checks,
+        sanitizer_friendly,
+        // only `roc build` has the --emit flag
+        emit_host_layouts: config == BuildOnly
+            && matches.value_of(FLAG_EMIT) == Some("host-layouts"),
+        emit_llvm_ir: config == BuildOnly && matches.value_of(FLAG_EMIT) == Some("llvm-ir"),
+        emit_call_graph: config == BuildOnly && matches.value_of(FLAG_EMIT) == Some("call-graph"),
+        emit_c_header: config == BuildOnly && matches.value_of(FLAG_EMIT) == Some("c-header"),
+        profile_generate,
+        profile_use,
+        // the embedded WASI runtime that runs wasm32 programs has no host functions of its own
+        wasi_hooks: config != BuildOnly && triple.architecture == Architecture::Wasm32,
     };
 
-    let load_config = standard_load_config(&triple, build_ordering, threading);
+    let standard_config = standard_load_config(&triple, build_ordering, threading);
+    let optimized = matches!(opt_level, OptLevel::Optimize | OptLevel::Size);
+    let load_config = LoadConfig {
+        target_info: if sanitizer_friendly {
+            standard_config.target_info.without_pointer_tagging()
+        } else {
+            standard_config.target_info
+        },
+        max_specializations: matches
+            .value_of(FLAG_MAX_SPECIALIZATIONS)
+            .and_then(|s| s.parse::<usize>().ok()),
+        fold_constants: optimized,
+        hoist_loop_invariants: optimized,
+        strip_expects: optimized,
+        cancelled: None,
+        // both profile builds count the same regions, so the counts can be matched up
+        coverage: profile_generate || profile_use,
+        ..standard_config
+    };
+    let severity_config = warnings::severity_config(matches, &path_buf);
+
+    // only reuse builds whose output is just the executable; a profile isn't one of its inputs
+    let build_cache = if config == BuildOnly
+        && link_type == LinkType::Executable
+        && matches.value_of(FLAG_EMIT).is_none()
+        && !emit_timings
+        && time_trace_path.is_none()
+        && !code_gen_options.profile_use
+    {
+        BuildCache::new(
+            roc_cache_dir,
+            &path_buf,
+            &triple,
+            &code_gen_options,
+            link_type,
+            linking_strategy,
+            prebuilt,
+        )
+    } else {
+        None
+    };
+
+    let lookup_start = Instant::now();
+
+    if let Some(binary_path) = build_cache.as_ref().and_then(BuildCache::lookup) {
+        print_built(&binary_path, &Problems::default(), lookup_start.elapsed());
+
+        return Ok(0);
+    }
 
     let res_binary_path = build_file(
         &arena,
@@ -699,12 +1434,15 @@ pub fn build(
         path_buf,
         code_gen_options,
         emit_timings,
+        time_trace_path,
         link_type,
         linking_strategy,
         prebuilt,
         wasm_dev_stack_bytes,
+        wasm_dev_features,
         roc_cache_dir,
         load_config,
+        &severity_config,
     );
 
     match res_binary_path {
@@ -713,22 +1451,26 @@ pub fn build(
             problems,
             total_time,
             expect_metadata,
+            inputs,
         }) => {
             match config {
                 BuildOnly => {
-                    // If possible, report the generated executable name relative to the current dir.
-                    let generated_filename = binary_path
-                        .strip_prefix(env::current_dir().unwrap())
-                        .unwrap_or(&binary_path)
-                        .to_str()
-                        .unwrap();
-
                     // No need to waste time freeing this memory,
                     // since the process is about to exit anyway.
                     // std::mem::forget(arena);
 
-                    problems.print_to_stdout(total_time);
-                    println!(" while successfully building:\n\n    {generated_filename}");
+                    print_built(&binary_path, &problems, total_time);
+
+                    if let Some(build_cache) = build_cache {
+                        if !inputs.is_empty() && problems.errors == 0 && problems.warnings == 0 {
+                            // the build succeeded either way, so there's nothing to report
+                            let _ = build_cache.store(&inputs, &binary_path);
+                        }
+                    }
+
+                    if matches.value_of(FLAG_EMIT) == Some("size-report") {
+                        print_size_report(&binary_path);
+                    }
 
                     // Return a nonzero exit code if there were problems
                     Ok(problems.exit_code())
@@ -771,10 +1513,17 @@ pub fn build(
                         // Return a nonzero exit code due to fatal problem
                         return Ok(problems.exit_code());
                     }
-                    debug_assert_eq!(
-                        problems.errors, 0,
-                        "if there are non-fatal errors, they should have been returned as an error variant"
-                    );
+                    if problems.errors > 0 {
+                        // Warnings that were configured to be errors only show up here,
+                        // since the loader itself still considers them warnings.
+                        problems.print_to_stdout(total_time);
+                        println!(
+                            ".\n\nCannot run program due to warnings being treated as errors…\n\n\x1B[36m{}\x1B[39m",
+                            "─".repeat(80)
+                        );
+
+                        return Ok(problems.exit_code());
+                    }
 
                     if problems.warnings > 0 {
                         problems.print_to_stdout(total_time);
@@ -795,12 +1544,93 @@ pub fn build(
             }
         }
         Err(BuildFileError::ErrorModule { module, total_time }) => {
-            handle_error_module(module, total_time, filename, true)
+            handle_error_module(module, total_time, filename, true, &severity_config)
         }
         Err(BuildFileError::LoadingProblem(problem)) => handle_loading_problem(problem),
     }
 }
 
+/// `roc dev --watch`: run `roc dev` (without `--watch`) in a child process, and start it over
+/// whenever a .roc file in the app's directory changes. Changes are found by polling
+/// modification times, so this works the same on every OS.
+pub fn watch_and_rerun(roc_file: &Path) -> io::Result<i32> {
+    use std::time::Duration;
+
+    const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+    let roc_exe = env::current_exe()?;
+
+    // Drop our own --watch flag, but leave the app's arguments (after the .roc file) untouched.
+    let child_args = {
+        let mut reached_roc_file = false;
+
+        env::args_os()
+            .skip(1)
+            .filter(|arg| {
+                reached_roc_file |= Path::new(arg) == roc_file;
+
+                reached_roc_file || arg != "--watch"
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let watched_dir = match roc_file.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+
+    loop {
+        let snapshot = roc_file_mtimes(&watched_dir)?;
+        let mut child = process::Command::new(&roc_exe).args(&child_args).spawn()?;
+        let mut exited = false;
+
+        while roc_file_mtimes(&watched_dir)? == snapshot {
+            std::thread::sleep(POLL_INTERVAL);
+
+            if !exited && child.try_wait()?.is_some() {
+                exited = true;
+
+                println!(
+                    "\nWaiting for changes to .roc files in {}…",
+                    watched_dir.display()
+                );
+            }
+        }
+
+        if !exited {
+            // The app may have exited in between our last check and now, so ignore errors.
+            let _ = child.kill();
+            child.wait()?;
+        }
+
+        println!("\nChange detected, rebuilding…\n");
+    }
+}
+
+/// The modification times of all the .roc files under `dir`, in a stable order.
+fn roc_file_mtimes(dir: &Path) -> io::Result<Vec<(PathBuf, std::time::SystemTime)>> {
+    fn visit(dir: &Path, mtimes: &mut Vec<(PathBuf, std::time::SystemTime)>) -> io::Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+
+            if path.is_dir() {
+                visit(&path, mtimes)?;
+            } else if path.extension() == Some(OsStr::new("roc")) {
+                mtimes.push((path.clone(), path.metadata()?.modified()?));
+            }
+        }
+
+        Ok(())
+    }
+
+    let mut mtimes = Vec::new();
+
+    visit(dir, &mut mtimes)?;
+    mtimes.sort_unstable();
+
+    Ok(mtimes)
+}
+
 fn roc_run<'a, I: IntoIterator<Item = &'a OsStr>>(
     arena: &Bump,
     opt_level: OptLevel,
@@ -819,28 +1649,26 @@ fn roc_run<'a, I: IntoIterator<Item = &'a OsStr>>(
                 .unwrap_or(path);
 
             #[cfg(target_family = "unix")]
-            {
+            let exit_code = {
                 use std::os::unix::ffi::OsStrExt;
 
                 run_wasm(
                     generated_filename,
                     args.into_iter().map(|os_str| os_str.as_bytes()),
-                );
-            }
+                )
+            };
 
             #[cfg(not(target_family = "unix"))]
-            {
-                run_wasm(
-                    generated_filename,
-                    args.into_iter().map(|os_str| {
-                        os_str.to_str().expect(
-                            "Roc does not currently support passing non-UTF8 arguments to Wasm.",
-                        )
-                    }),
-                );
-            }
+            let exit_code = run_wasm(
+                generated_filename,
+                args.into_iter().map(|os_str| {
+                    os_str.to_str().expect(
+                        "Roc does not currently support passing non-UTF8 arguments to Wasm.",
+                    )
+                }),
+            );
 
-            Ok(0)
+            Ok(exit_code)
         }
         _ => roc_run_native(arena, opt_level, args, binary_bytes, expect_metadata),
     }
@@ -852,11 +1680,7 @@ fn os_str_as_utf8_bytes(os_str: &OsStr) -> &[u8] {
     os_str.as_bytes()
 }
 
-#[cfg(not(target_family = "unix"))]
-fn os_str_as_utf8_bytes(os_str: &OsStr) -> &[u8] {
-    os_str.to_str().unwrap().as_bytes()
-}
-
+#[cfg(target_family = "unix")]
 fn make_argv_envp<'a, I: IntoIterator<Item = S>, S: AsRef<OsStr>>(
     arena: &'a Bump,
     executable: &ExecutableFile,
@@ -940,6 +1764,7 @@ fn roc_run_native<I: IntoIterator<Item = S>, S: AsRef<OsStr>>(
     Ok(1)
 }
 
+#[cfg(target_family = "unix")]
 unsafe fn roc_run_native_fast(
     executable: ExecutableFile,
     argv: &[*const c_char],
@@ -967,7 +1792,7 @@ impl ExecutableFile {
     #[cfg(target_os = "linux")]
     const SYSCALL: &'static str = "fexecve";
 
-    #[cfg(not(target_os = "linux"))]
+    #[cfg(all(target_family = "unix", not(target_os = "linux")))]
     const SYSCALL: &'static str = "execve";
 
     fn as_path(&self) -> &Path {
@@ -979,6 +1804,7 @@ impl ExecutableFile {
         }
     }
 
+    #[cfg(target_family = "unix")]
     unsafe fn execve(&self, argv: &[*const c_char], envp: &[*const c_char]) -> c_int {
         match self {
             #[cfg(target_os = "linux")]
@@ -991,13 +1817,6 @@ impl ExecutableFile {
                 let path_cstring = CString::new(path.as_os_str().as_bytes()).unwrap();
                 libc::execve(path_cstring.as_ptr().cast(), argv.as_ptr(), envp.as_ptr())
             }
-
-            #[cfg(target_family = "windows")]
-            ExecutableFile::OnDisk(_, path) => {
-                let path_cstring = CString::new(path.to_str().unwrap()).unwrap();
-
-                libc::execve(path_cstring.as_ptr().cast(), argv.as_ptr(), envp.as_ptr())
-            }
         }
     }
 }
@@ -1029,6 +1848,10 @@ fn roc_dev_native(
 
     let mut writer = std::io::stdout();
 
+    // Register before forking, so we can't miss the signal when the child exits right away
+    let sigchld = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register(signal_hook::consts::SIGCHLD, Arc::clone(&sigchld)).unwrap();
+
     match unsafe { libc::fork() } {
         0 => unsafe {
             // we are the child
@@ -1048,11 +1871,7 @@ fn roc_dev_native(
 
             std::process::exit(1)
         }
-        1.. => {
-            let sigchld = Arc::new(AtomicBool::new(false));
-            signal_hook::flag::register(signal_hook::consts::SIGCHLD, Arc::clone(&sigchld))
-                .unwrap();
-
+        child_pid @ 1.. => {
             loop {
                 match memory.wait_for_child(sigchld.clone()) {
                     ChildProcessMsg::Terminate => break,
@@ -1085,7 +1904,20 @@ fn roc_dev_native(
                 }
             }
 
-            std::process::exit(0)
+            // Exit the way the app did, so whatever ran `roc` sees the app's exit code
+            let mut status = 0;
+            unsafe { libc::waitpid(child_pid, &mut status, 0) };
+
+            let exit_code = if libc::WIFEXITED(status) {
+                libc::WEXITSTATUS(status)
+            } else if libc::WIFSIGNALED(status) {
+                // the convention shells use for a process that was killed by a signal
+                128 + libc::WTERMSIG(status)
+            } else {
+                1
+            };
+
+            std::process::exit(exit_code)
         }
         _ => unreachable!(),
     }
@@ -1167,52 +1999,45 @@ fn roc_run_executable_file_path(binary_bytes: &[u8]) -> std::io::Result<Executab
 /// Run on the native OS (not on wasm)
 #[cfg(not(target_family = "unix"))]
 fn roc_run_native<I: IntoIterator<Item = S>, S: AsRef<OsStr>>(
-    arena: &Bump, // This should be passed an owned value, not a reference, so we can usefully mem::forget it!
+    _arena: &Bump,
     opt_level: OptLevel,
     args: I,
     binary_bytes: &[u8],
     _expect_metadata: ExpectMetadata,
 ) -> io::Result<i32> {
-    use bumpalo::collections::CollectIn;
-
-    unsafe {
-        let executable = roc_run_executable_file_path(binary_bytes)?;
+    let executable = roc_run_executable_file_path(binary_bytes)?;
 
-        // TODO forward the arguments
-        let (argv_cstrings, envp_cstrings) = make_argv_envp(&arena, &executable, args);
-
-        let argv: bumpalo::collections::Vec<*const c_char> = argv_cstrings
-            .iter()
-            .map(|s| s.as_ptr())
-            .chain([std::ptr::null()])
-            .collect_in(arena);
-
-        let envp: bumpalo::collections::Vec<*const c_char> = envp_cstrings
-            .iter()
-            .map(|s| s.as_ptr())
-            .chain([std::ptr::null()])
-            .collect_in(arena);
-
-        match opt_level {
-            OptLevel::Development => {
-                // roc_run_native_debug(executable, &argv, &envp, expectations, interns)
-                internal_error!("running `expect`s does not currently work on windows")
-            }
-            OptLevel::Normal | OptLevel::Size | OptLevel::Optimize => {
-                roc_run_native_fast(executable, &argv, &envp);
-            }
+    match opt_level {
+        OptLevel::Development => {
+            // roc_run_native_debug(executable, &argv, &envp, expectations, interns)
+            internal_error!("running `expect`s does not currently work on windows")
+        }
+        OptLevel::Normal | OptLevel::Size | OptLevel::Optimize => {
+            // Windows can't replace the current process with another one, so run the app as a
+            // child that inherits our stdin, stdout, and stderr, and exit with its exit code.
+            let status = std::process::Command::new(executable.as_path())
+                .args(args)
+                .status()?;
+
+            Ok(status.code().unwrap_or(1))
         }
     }
-
-    Ok(1)
 }
 
+/// Run a WASI program in the embedded interpreter and return its exit code.
+/// A program that exits with a nonzero code calls `proc_exit`, which exits the `roc` process too.
 #[cfg(feature = "run-wasm32")]
-fn run_wasm<I: Iterator<Item = S>, S: AsRef<[u8]>>(wasm_path: &std::path::Path, args: I) {
+fn run_wasm<I: Iterator<Item = S>, S: AsRef<[u8]>>(wasm_path: &std::path::Path, args: I) -> i32 {
     use bumpalo::collections::Vec;
     use roc_wasm_interp::{DefaultImportDispatcher, Instance};
 
-    let bytes = std::fs::read(wasm_path).unwrap();
+    let bytes = match std::fs::read(wasm_path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            eprintln!("I could not read {} to run it: {}", wasm_path.display(), e);
+            return 1;
+        }
+    };
     let arena = Bump::new();
 
     let mut argv = Vec::<&[u8]>::new_in(&arena);
@@ -1223,19 +2048,29 @@ fn run_wasm<I: Iterator<Item = S>, S: AsRef<[u8]>>(wasm_path: &std::path::Path,
     }
     let import_dispatcher = DefaultImportDispatcher::new(&argv);
 
-    let mut instance = Instance::from_bytes(&arena, &bytes, import_dispatcher, false).unwrap();
+    let mut instance = match Instance::from_bytes(&arena, &bytes, import_dispatcher, false) {
+        Ok(instance) => instance,
+        Err(e) => {
+            eprintln!("I could not load {} to run it: {}", wasm_path.display(), e);
+            return 1;
+        }
+    };
 
-    instance
-        .call_export("_start", [])
-        .unwrap()
-        .unwrap()
-        .expect_i32()
-        .unwrap();
+    // The WASI `_start` has type `() -> nil`, but some hosts export a `main`-like `_start` instead.
+    match instance.call_export("_start", []) {
+        Ok(None) => 0,
+        Ok(Some(value)) => value.expect_i32().unwrap_or(0),
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
 }
 
 #[cfg(not(feature = "run-wasm32"))]
-fn run_wasm<I: Iterator<Item = S>, S: AsRef<[u8]>>(_wasm_path: &std::path::Path, _args: I) {
+fn run_wasm<I: Iterator<Item = S>, S: AsRef<[u8]>>(_wasm_path: &std::path::Path, _args: I) -> i32 {
     println!("Running wasm files is not supported on this target.");
+    1
 }
 
 #[derive(Debug, Copy, Clone, EnumIter, IntoStaticStr, PartialEq, Eq, Default)]
@@ -1247,6 +2082,12 @@ pub enum Target {
     Linux32,
     #[strum(serialize = "linux64")]
     Linux64,
+    #[strum(serialize = "linux-arm64")]
+    LinuxArm64,
+    #[strum(serialize = "macos64")]
+    Macos64,
+    #[strum(serialize = "macos-arm64")]
+    MacosArm64,
     #[strum(serialize = "windows64")]
     Windows64,
     #[strum(serialize = "wasm32")]
@@ -1273,6 +2114,27 @@ impl Target {
                 environment: Environment::Musl,
                 binary_format: BinaryFormat::Elf,
             },
+            LinuxArm64 => Triple {
+                architecture: Architecture::Aarch64(Aarch64Architecture::Aarch64),
+                vendor: Vendor::Unknown,
+                operating_system: OperatingSystem::Linux,
+                environment: Environment::Gnu,
+                binary_format: BinaryFormat::Elf,
+            },
+            Macos64 => Triple {
+                architecture: Architecture::X86_64,
+                vendor: Vendor::Apple,
+                operating_system: OperatingSystem::Darwin,
+                environment: Environment::Unknown,
+                binary_format: BinaryFormat::Macho,
+            },
+            MacosArm64 => Triple {
+                architecture: Architecture::Aarch64(Aarch64Architecture::Aarch64),
+                vendor: Vendor::Apple,
+                operating_system: OperatingSystem::Darwin,
+                environment: Environment::Unknown,
+                binary_format: BinaryFormat::Macho,
+            },
             Windows64 => Triple {
                 architecture: Architecture::X86_64,
                 vendor: Vendor::Unknown,
@@ -1311,9 +2173,38 @@ impl std::str::FromStr for Target {
             "system" => Ok(Target::System),
             "linux32" => Ok(Target::Linux32),
             "linux64" => Ok(Target::Linux64),
+            "linux-arm64" => Ok(Target::LinuxArm64),
+            "macos64" => Ok(Target::Macos64),
+            "macos-arm64" => Ok(Target::MacosArm64),
             "windows64" => Ok(Target::Windows64),
             "wasm32" => Ok(Target::Wasm32),
             _ => Err(format!("Roc does not know how to compile to {}", string)),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn target_round_trips_through_its_name() {
+        for target in Target::iter() {
+            let name: &'static str = target.into();
+
+            assert_eq!(Target::from_str(name), Ok(target));
+        }
+    }
+
+    #[test]
+    fn every_target_has_builtins_bitcode() {
+        for target in Target::iter() {
+            assert!(
+                roc_bitcode_bc::builtins_bitcode(&target.to_triple()).is_some(),
+                "no builtins bitcode for {:?}",
+                target
+            );
+        }
+    }
+}
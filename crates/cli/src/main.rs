@@ -2,10 +2,14 @@
 use roc_build::link::LinkType;
 use roc_build::program::{check_file, CodeGenBackend};
 use roc_cli::{
-    build_app, format, test, BuildConfig, FormatMode, Target, CMD_BUILD, CMD_CHECK, CMD_DEV,
-    CMD_DOCS, CMD_EDIT, CMD_FORMAT, CMD_GEN_STUB_LIB, CMD_GLUE, CMD_REPL, CMD_RUN, CMD_TEST,
-    CMD_VERSION, DIRECTORY_OR_FILES, FLAG_CHECK, FLAG_DEV, FLAG_LIB, FLAG_NO_LINK, FLAG_TARGET,
-    FLAG_TIME, GLUE_DIR, GLUE_SPEC, ROC_FILE,
+    annotate, bench, build_app, check_compat, diff, format, init, refs, rename, test,
+    watch_and_rerun, BuildConfig, FormatMode, ProjectKind, Target, CMD_ANNOTATE, CMD_BENCH,
+    CMD_BUILD, CMD_CHECK, CMD_CHECK_COMPAT, CMD_DEV, CMD_DIFF, CMD_DOCS, CMD_EDIT, CMD_EXPLAIN,
+    CMD_FORMAT, CMD_GEN_STUB_LIB, CMD_GLUE, CMD_GLUE_JS, CMD_INIT, CMD_REFS, CMD_RENAME, CMD_REPL,
+    CMD_RUN, CMD_TEST, CMD_VERSION, DIRECTORY_OR_FILES, ERROR_CODE, FLAG_BACKEND, FLAG_CHECK,
+    FLAG_DEV, FLAG_FORMAT, FLAG_LIB, FLAG_NO_LINK, FLAG_PACKAGE, FLAG_PLATFORM, FLAG_STATIC_LIB,
+    FLAG_TARGET, FLAG_TIME, FLAG_WATCH, GLUE_DIR, GLUE_SPEC, NEW_NAME, NEW_ROC_FILE, OLD_ROC_FILE,
+    PROJECT_NAME, ROC_FILE, VALUE_NAME,
 };
 use roc_docs::generate_docs_html;
 use roc_error_macros::user_error;
@@ -13,7 +17,8 @@ use roc_gen_dev::AssemblyBackendMode;
 use roc_gen_llvm::llvm::build::LlvmBackendMode;
 use roc_load::{LoadingProblem, Threading};
 use roc_packaging::cache::{self, RocCacheDir};
-use std::fs::{self, FileType};
+use roc_repl_cli::repl_state::ReplBackend;
+use roc_reporting::report::ErrorCode;
 use std::io;
 use std::path::{Path, PathBuf};
 use target_lexicon::Triple;
@@ -24,8 +29,6 @@ extern crate const_format;
 #[global_allocator]
 static ALLOC: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
-use std::ffi::{OsStr, OsString};
-
 use roc_cli::build;
 
 fn main() -> io::Result<()> {
@@ -51,10 +54,12 @@ fn main() -> io::Result<()> {
         }
         Some((CMD_RUN, matches)) => {
             if matches.is_present(ROC_FILE) {
+                let target: Target = matches.value_of_t(FLAG_TARGET).unwrap_or_default();
+
                 build(
                     matches,
                     BuildConfig::BuildAndRun,
-                    Triple::host(),
+                    target.to_triple(),
                     RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
                     LinkType::Executable,
                 )
@@ -73,8 +78,19 @@ fn main() -> io::Result<()> {
                 Ok(1)
             }
         }
-        Some((CMD_DEV, matches)) => {
+        Some((CMD_BENCH, matches)) => {
             if matches.is_present(ROC_FILE) {
+                bench(matches, Triple::host())
+            } else {
+                eprintln!("What .roc file do you want to bench? Specify it at the end of the `roc bench` command.");
+
+                Ok(1)
+            }
+        }
+        Some((CMD_DEV, matches)) => {
+            if matches.is_present(FLAG_WATCH) && matches.is_present(ROC_FILE) {
+                watch_and_rerun(Path::new(matches.value_of_os(ROC_FILE).unwrap()))
+            } else if matches.is_present(ROC_FILE) {
                 build(
                     matches,
                     BuildConfig::BuildAndRunIfNoErrors,
@@ -107,6 +123,18 @@ fn main() -> io::Result<()> {
                 Ok(1)
             }
         }
+        Some((CMD_GLUE_JS, matches)) => {
+            let input_path = Path::new(matches.value_of_os(ROC_FILE).unwrap());
+            let output_path = Path::new(matches.value_of_os(GLUE_DIR).unwrap());
+
+            if !output_path.exists() || output_path.is_dir() {
+                roc_glue::generate_js(input_path, output_path)
+            } else {
+                eprintln!("`roc glue-js` must be given a directory to output into, because the glue generates multiple files.");
+
+                Ok(1)
+            }
+        }
         Some((CMD_GEN_STUB_LIB, matches)) => {
             let input_path = Path::new(matches.value_of_os(ROC_FILE).unwrap());
             let target: Target = matches.value_of_t(FLAG_TARGET).unwrap_or_default();
@@ -125,6 +153,7 @@ fn main() -> io::Result<()> {
                 (true, false) => LinkType::Dylib,
                 (true, true) => user_error!("build can only be one of `--lib` or `--no-link`"),
                 (false, true) => LinkType::None,
+                (false, false) if matches.is_present(FLAG_STATIC_LIB) => LinkType::StaticLib,
                 (false, false) => LinkType::Executable,
             };
 
@@ -152,55 +181,76 @@ fn main() -> io::Result<()> {
                 Some(n) => Threading::AtMost(n),
             };
 
-            match check_file(
-                &arena,
-                roc_file_path,
-                emit_timings,
-                RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
-                threading,
-            ) {
-                Ok((problems, total_time)) => {
-                    println!(
-                        "\x1B[{}m{}\x1B[39m {} and \x1B[{}m{}\x1B[39m {} found in {} ms.",
-                        if problems.errors == 0 {
-                            32 // green
-                        } else {
-                            33 // yellow
-                        },
-                        problems.errors,
-                        if problems.errors == 1 {
-                            "error"
-                        } else {
-                            "errors"
-                        },
-                        if problems.warnings == 0 {
-                            32 // green
-                        } else {
-                            33 // yellow
-                        },
-                        problems.warnings,
-                        if problems.warnings == 1 {
-                            "warning"
-                        } else {
-                            "warnings"
-                        },
-                        total_time.as_millis(),
-                    );
+            let severity_config = roc_cli::warnings::severity_config(matches, &roc_file_path);
 
-                    Ok(problems.exit_code())
-                }
+            if matches.value_of(FLAG_FORMAT) == Some("json") {
+                roc_cli::check_json(
+                    roc_file_path,
+                    RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
+                    threading,
+                    &severity_config,
+                )
+            } else {
+                match check_file(
+                    &arena,
+                    roc_file_path,
+                    emit_timings,
+                    RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
+                    threading,
+                    &severity_config,
+                ) {
+                    Ok((problems, total_time)) => {
+                        println!(
+                            "\x1B[{}m{}\x1B[39m {} and \x1B[{}m{}\x1B[39m {} found in {} ms.",
+                            if problems.errors == 0 {
+                                32 // green
+                            } else {
+                                33 // yellow
+                            },
+                            problems.errors,
+                            if problems.errors == 1 {
+                                "error"
+                            } else {
+                                "errors"
+                            },
+                            if problems.warnings == 0 {
+                                32 // green
+                            } else {
+                                33 // yellow
+                            },
+                            problems.warnings,
+                            if problems.warnings == 1 {
+                                "warning"
+                            } else {
+                                "warnings"
+                            },
+                            total_time.as_millis(),
+                        );
+
+                        problems.print_by_module_to_stdout();
+
+                        Ok(problems.exit_code())
+                    }
 
-                Err(LoadingProblem::FormattedReport(report)) => {
-                    print!("{}", report);
+                    Err(LoadingProblem::FormattedReport(report)) => {
+                        print!("{}", report);
 
-                    Ok(1)
-                }
-                Err(other) => {
-                    panic!("build_file failed with error:\n{:?}", other);
+                        Ok(1)
+                    }
+                    Err(other) => {
+                        panic!("build_file failed with error:\n{:?}", other);
+                    }
                 }
             }
         }
-        Some((CMD_REPL, _)) => Ok(roc_repl_cli::main()),
+        Some((CMD_REPL, matches)) => {
+            let backend = match matches.value_of(FLAG_BACKEND) {
+                Some("wasm") => ReplBackend::Wasm,
+                _ => ReplBackend::Llvm,
+            };
+
+            Ok(roc_repl_cli::main(backend))
+        }
         Some((CMD_EDIT, matches)) => {
             match matches
                 .values_of_os(DIRECTORY_OR_FILES)
@@ -225,42 +275,17 @@ fn main() -> io::Result<()> {
             Ok(0)
         }
         Some((CMD_FORMAT, matches)) => {
-            let maybe_values = matches.values_of_os(DIRECTORY_OR_FILES);
-
-            let mut values: Vec<OsString> = Vec::new();
-
-            match maybe_values {
-                None => {
-                    let mut os_string_values: Vec<OsString> = Vec::new();
-                    read_all_roc_files(
-                        &std::env::current_dir()?.as_os_str().to_os_string(),
-                        &mut os_string_values,
-                    )?;
-                    for os_string in os_string_values {
-                        values.push(os_string);
-                    }
-                }
-                Some(os_values) => {
-                    for os_str in os_values {
-                        values.push(os_str.to_os_string());
-                    }
-                }
-            }
-
-            let mut roc_files = Vec::new();
-
-            // Populate roc_files
-            for os_str in values {
-                let metadata = fs::metadata(os_str.clone())?;
-                roc_files_recursive(os_str.as_os_str(), metadata.file_type(), &mut roc_files)?;
-            }
+            let paths = match matches.values_of_os(DIRECTORY_OR_FILES) {
+                Some(os_values) => os_values.map(PathBuf::from).collect(),
+                None => vec![std::env::current_dir()?],
+            };
 
             let format_mode = match matches.is_present(FLAG_CHECK) {
                 true => FormatMode::CheckOnly,
                 false => FormatMode::Format,
             };
 
-            let format_exit_code = match format(roc_files, format_mode) {
+            let format_exit_code = match format(paths, format_mode) {
                 Ok(_) => 0,
                 Err(message) => {
                     eprintln!("{}", message);
@@ -270,6 +295,76 @@ fn main() -> io::Result<()> {
 
             Ok(format_exit_code)
         }
+        Some((CMD_EXPLAIN, matches)) => {
+            let code = matches.value_of(ERROR_CODE).unwrap();
+
+            match ErrorCode::parse(code) {
+                Some(code) => {
+                    print!("{}", code.explanation());
+
+                    Ok(0)
+                }
+                None => {
+                    eprintln!(
+                        "`{}` is not a Roc error code. Error codes look like E0301 and are shown in the header of each report.",
+                        code
+                    );
+
+                    Ok(1)
+                }
+            }
+        }
+        Some((CMD_RENAME, matches)) => {
+            let roc_file_path = PathBuf::from(matches.value_of_os(ROC_FILE).unwrap());
+
+            rename(
+                roc_file_path,
+                matches.value_of(VALUE_NAME).unwrap(),
+                matches.value_of(NEW_NAME).unwrap(),
+                RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
+            )
+        }
+        Some((CMD_REFS, matches)) => {
+            let roc_file_path = PathBuf::from(matches.value_of_os(ROC_FILE).unwrap());
+
+            refs(
+                roc_file_path,
+                matches.value_of(VALUE_NAME).unwrap(),
+                RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
+            )
+        }
+        Some((CMD_ANNOTATE, matches)) => {
+            let roc_file_path = PathBuf::from(matches.value_of_os(ROC_FILE).unwrap());
+
+            annotate(
+                roc_file_path,
+                RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
+            )
+        }
+        Some((CMD_DIFF, matches)) => diff(
+            Path::new(matches.value_of_os(OLD_ROC_FILE).unwrap()),
+            Path::new(matches.value_of_os(NEW_ROC_FILE).unwrap()),
+        ),
+        Some((CMD_CHECK_COMPAT, matches)) => check_compat(
+            Path::new(matches.value_of_os(OLD_ROC_FILE).unwrap()),
+            Path::new(matches.value_of_os(NEW_ROC_FILE).unwrap()),
+            RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
+        ),
+        Some((CMD_INIT, matches)) => {
+            let kind = if matches.is_present(FLAG_PACKAGE) {
+                ProjectKind::Package
+            } else {
+                ProjectKind::App {
+                    platform: matches.value_of(FLAG_PLATFORM).unwrap(),
+                }
+            };
+
+            init(
+                matches.value_of(PROJECT_NAME).unwrap(),
+                kind,
+                RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
+            )
+        }
         Some((CMD_VERSION, _)) => {
             print!(
                 "{}",
@@ -284,44 +379,6 @@ fn main() -> io::Result<()> {
     std::process::exit(exit_code);
 }
 
-fn read_all_roc_files(
-    dir: &OsString,
-    roc_file_paths: &mut Vec<OsString>,
-) -> Result<(), std::io::Error> {
-    let entries = fs::read_dir(dir)?;
-
-    for entry in entries {
-        let path = entry?.path();
-
-        if path.is_dir() {
-            read_all_roc_files(&path.into_os_string(), roc_file_paths)?;
-        } else if path.extension().and_then(OsStr::to_str) == Some("roc") {
-            let file_path = path.into_os_string();
-            roc_file_paths.push(file_path);
-        }
-    }
-
-    Ok(())
-}
-
-fn roc_files_recursive<P: AsRef<Path>>(
-    path: P,
-    file_type: FileType,
-    roc_files: &mut Vec<PathBuf>,
-) -> io::Result<()> {
-    if file_type.is_dir() {
-        for entry_res in fs::read_dir(path)? {
-            let entry = entry_res?;
-
-            roc_files_recursive(entry.path(), entry.file_type()?, roc_files)?;
-        }
-    } else {
-        roc_files.push(path.as_ref().to_path_buf());
-    }
-
-    Ok(())
-}
-
 #[cfg(feature = "editor")]
 fn launch_editor(project_dir_path: Option<&Path>) -> io::Result<()> {
     roc_editor::launch(project_dir_path)
@@ -0,0 +1,85 @@
+use std::io;
+use std::path::PathBuf;
+
+use bumpalo::Bump;
+use roc_build::program::check_load_config;
+use roc_can::references::ReferenceKind;
+use roc_collections::MutMap;
+use roc_language_server::rename::find_top_level;
+use roc_load::{LoadingProblem, Threading};
+use roc_packaging::cache::RocCacheDir;
+use roc_region::all::LineInfo;
+use roc_reporting::report::{Palette, RenderTarget};
+
+use crate::rename::split_qualified;
+
+/// Print every reference to a top-level value in the modules loaded from `roc_file_path`, one
+/// `path:line:column` per line. `name` is either unqualified, for a value defined in the module
+/// at `roc_file_path`, or qualified like `Json.parse`.
+pub fn refs(roc_file_path: PathBuf, name: &str, roc_cache_dir: RocCacheDir<'_>) -> io::Result<i32> {
+    let arena = Bump::new();
+    let load_config = check_load_config(
+        RenderTarget::ColorTerminal,
+        Palette::from_env(),
+        Threading::AllAvailable,
+    );
+
+    let loaded =
+        match roc_load::load_and_typecheck(&arena, roc_file_path, roc_cache_dir, load_config) {
+            Ok(loaded) => loaded,
+            Err(LoadingProblem::FormattedReport(report)) => {
+                print!("{}", report);
+
+                return Ok(1);
+            }
+            Err(other) => {
+                panic!("loading failed with error:\n{:?}", other);
+            }
+        };
+
+    let (module_id, ident) = match split_qualified(&loaded, name) {
+        Ok(found) => found,
+        Err(module_name) => {
+            eprintln!("The app doesn't import a module named `{}`.", module_name);
+
+            return Ok(1);
+        }
+    };
+
+    let symbol = match find_top_level(&loaded, module_id, ident) {
+        Some(symbol) => symbol,
+        None => {
+            eprintln!("There's no top-level value named `{}`.", name);
+
+            return Ok(1);
+        }
+    };
+
+    let mut line_infos = MutMap::default();
+
+    for (module_id, reference) in loaded.references(symbol) {
+        let (path, source) = match loaded.sources.get(&module_id) {
+            Some(found) => found,
+            None => continue,
+        };
+
+        let line_info = line_infos
+            .entry(module_id)
+            .or_insert_with(|| LineInfo::new(source));
+        let start = line_info.convert_pos(reference.region.start());
+        let kind = match reference.kind {
+            ReferenceKind::Definition => " (definition)",
+            ReferenceKind::Usage => "",
+        };
+
+        println!(
+            "{}:{}:{}{}",
+            path.display(),
+            start.line + 1,
+            start.column + 1,
+            kind
+        );
+    }
+
+    Ok(0)
+}
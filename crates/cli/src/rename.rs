@@ -0,0 +1,115 @@
+use std::io;
+use std::path::PathBuf;
+
+use bumpalo::Bump;
+use roc_build::program::check_load_config;
+use roc_language_server::rename::{apply_edits, find_top_level};
+use roc_load::{LoadedModule, LoadingProblem, Threading};
+use roc_module::symbol::ModuleId;
+use roc_packaging::cache::RocCacheDir;
+use roc_reporting::report::{Palette, RenderTarget};
+
+/// Rename a top-level value in every module loaded from `roc_file_path`, and write the
+/// changed modules back to disk. `name` is either unqualified, for a value defined in the
+/// module at `roc_file_path`, or qualified like `Json.parse`.
+pub fn rename(
+    roc_file_path: PathBuf,
+    name: &str,
+    new_name: &str,
+    roc_cache_dir: RocCacheDir<'_>,
+) -> io::Result<i32> {
+    let arena = Bump::new();
+    let load_config = check_load_config(
+        RenderTarget::ColorTerminal,
+        Palette::from_env(),
+        Threading::AllAvailable,
+    );
+
+    let loaded =
+        match roc_load::load_and_typecheck(&arena, roc_file_path, roc_cache_dir, load_config) {
+            Ok(loaded) => loaded,
+            Err(LoadingProblem::FormattedReport(report)) => {
+                print!("{}", report);
+
+                return Ok(1);
+            }
+            Err(other) => {
+                panic!("loading failed with error:\n{:?}", other);
+            }
+        };
+
+    let (module_id, ident) = match split_qualified(&loaded, name) {
+        Ok(found) => found,
+        Err(module_name) => {
+            eprintln!("The app doesn't import a module named `{}`.", module_name);
+
+            return Ok(1);
+        }
+    };
+
+    let symbol = match find_top_level(&loaded, module_id, ident) {
+        Some(symbol) => symbol,
+        None => {
+            eprintln!("There's no top-level value named `{}` to rename.", name);
+
+            return Ok(1);
+        }
+    };
+
+    let files = match roc_language_server::rename::rename(&loaded, symbol, new_name) {
+        Ok(files) => files,
+        Err(problem) => {
+            eprintln!("{}", problem);
+
+            return Ok(1);
+        }
+    };
+
+    let mut total_edits = 0;
+
+    for file in files.iter() {
+        let (_, source) = &loaded.sources[&file.module_id];
+
+        std::fs::write(&file.path, apply_edits(source, &file.edits))?;
+
+        total_edits += file.edits.len();
+    }
+
+    println!(
+        "Renamed `{}` to `{}`: changed {} {} in {} {}.",
+        name,
+        new_name,
+        total_edits,
+        if total_edits == 1 {
+            "reference"
+        } else {
+            "references"
+        },
+        files.len(),
+        if files.len() == 1 { "file" } else { "files" },
+    );
+
+    Ok(0)
+}
+
+/// The module and unqualified name of a value named like `parse` or `Json.parse`, or the name
+/// of the module if it isn't loaded.
+pub(crate) fn split_qualified<'n>(
+    loaded: &LoadedModule,
+    name: &'n str,
+) -> Result<(ModuleId, &'n str), &'n str> {
+    match name.rsplit_once('.') {
+        Some((module_name, ident)) => {
+            let module_id = loaded.sources.keys().copied().find(|module_id| {
+                let interned = loaded.interns.module_name(*module_id).as_str();
+
+                interned == module_name || interned.ends_with(&format!(".{}", module_name))
+            });
+
+            module_id
+                .map(|module_id| (module_id, ident))
+                .ok_or(module_name)
+        }
+        None => Ok((loaded.module_id, name)),
+    }
+}
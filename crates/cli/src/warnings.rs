@@ -0,0 +1,118 @@
+//! Decides how warnings get reported, from the `[warnings]` section of a project's
+//! `roc.toml` and the `--warning` and `--fail-on-warnings` flags.
+//!
+//! ```toml
+//! [warnings]
+//! unused-import = "off"
+//! unused-def = "error"
+//! fail-on-warnings = true
+//! ```
+use clap::ArgMatches;
+use roc_error_macros::user_error;
+use roc_packaging::manifest::{settings, MANIFEST_FILENAME};
+use roc_problem::lint::{Level, Lint, SeverityConfig};
+use std::path::Path;
+
+use crate::{FLAG_FAIL_ON_WARNINGS, FLAG_WARNING};
+
+/// Parses a `--warning` value like `unused-import=off`.
+pub fn parse_lint_level(arg: &str) -> Result<(Lint, Level), String> {
+    match arg.split_once('=') {
+        Some((lint, level)) => Ok((lint.trim().parse()?, level.trim().parse()?)),
+        None => Err(format!(
+            "`{}` should look like <warning>=<level>, e.g. unused-import=off",
+            arg
+        )),
+    }
+}
+
+/// Reads the project config next to `roc_file` (if there is one), then applies
+/// the command line flags on top of it.
+pub fn severity_config(matches: &ArgMatches, roc_file: &Path) -> SeverityConfig {
+    let config_path = roc_file
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(MANIFEST_FILENAME);
+
+    let mut config = match std::fs::read_to_string(&config_path) {
+        Ok(contents) => match parse_project_config(&contents) {
+            Ok(config) => config,
+            Err(problem) => user_error!("{}: {}", config_path.display(), problem),
+        },
+        Err(_) => SeverityConfig::default(),
+    };
+
+    for arg in matches.values_of(FLAG_WARNING).into_iter().flatten() {
+        match parse_lint_level(arg) {
+            Ok((lint, level)) => config.set(lint, level),
+            Err(problem) => user_error!("{}", problem),
+        }
+    }
+
+    if matches.is_present(FLAG_FAIL_ON_WARNINGS) {
+        config.fail_on_warnings = true;
+    }
+
+    config
+}
+
+/// Only the `[warnings]` table is read; every other section belongs to someone else.
+fn parse_project_config(contents: &str) -> Result<SeverityConfig, String> {
+    let mut config = SeverityConfig::default();
+
+    for setting in settings(contents, "warnings")? {
+        if setting.key == FLAG_FAIL_ON_WARNINGS {
+            config.fail_on_warnings = match setting.value {
+                "true" => true,
+                "false" => false,
+                value => return Err(setting.problem(format!("`{}` is not true or false", value))),
+            };
+        } else {
+            let lint = setting
+                .key
+                .parse()
+                .map_err(|problem| setting.problem(problem))?;
+            let level = setting
+                .unquoted_value()
+                .parse()
+                .map_err(|problem| setting.problem(problem))?;
+
+            config.set(lint, level);
+        }
+    }
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn project_config_warnings_section() {
+        let config = parse_project_config(
+            "[package]\nunused-def = \"off\"\n\n[warnings]\nunused-import = \"off\" # noisy\nredundant-pattern = \"error\"\nfail-on-warnings = true\n",
+        )
+        .unwrap();
+
+        assert_eq!(config.level(Lint::UnusedImport), Some(Level::Off));
+        assert_eq!(config.level(Lint::RedundantPattern), Some(Level::Error));
+        assert_eq!(config.level(Lint::UnusedDef), None);
+        assert!(config.fail_on_warnings);
+    }
+
+    #[test]
+    fn project_config_unknown_lint() {
+        assert!(parse_project_config("[warnings]\nunused-everything = \"off\"\n").is_err());
+    }
+
+    #[test]
+    fn warning_flag() {
+        assert_eq!(
+            parse_lint_level("unused-argument=error"),
+            Ok((Lint::UnusedArgument, Level::Error))
+        );
+        assert!(parse_lint_level("unused-argument").is_err());
+        assert!(parse_lint_level("unused-argument=loud").is_err());
+    }
+}
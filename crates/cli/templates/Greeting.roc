@@ -0,0 +1,9 @@
+interface Greeting
+    exposes [greet]
+    imports []
+
+greet : Str -> Str
+greet = \name -> "Hello, \(name)!"
+
+# Run these with `roc test`
+expect greet "Roc" == "Hello, Roc!"
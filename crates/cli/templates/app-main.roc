@@ -0,0 +1,7 @@
+app "{{name}}"
+    packages { pf: "{{platform}}" }
+    imports [pf.Stdout, Greeting]
+    provides [main] to pf
+
+main =
+    Stdout.line (Greeting.greet "World")
@@ -0,0 +1,3 @@
+package "{{name}}"
+    exposes [Greeting]
+    packages {}
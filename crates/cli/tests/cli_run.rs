@@ -1399,14 +1399,15 @@ mod cli_run {
             &[],
             indoc!(
                 r#"
-                ── TYPE MISMATCH ─────────────────────────────── tests/known_bad/TypeError.roc ─
+                ── TYPE MISMATCH [E0301] ─────────────────────── tests/known_bad/TypeError.roc ─
 
                 Something is off with the body of the main definition:
 
                 6│  main : Str -> Task {} []
+                           ----------------- expected because of this annotation
                 7│  main = /_ ->
                 8│      "this is a string, not a Task {} [] function like the platform expects."
-                        ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+                        ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ but this doesn't match it
 
                 The body is a string of type:
 
@@ -1435,7 +1436,7 @@ mod cli_run {
             &[],
             indoc!(
                 r#"
-                ── MISSING DEFINITION ────────────────── tests/known_bad/ExposedNotDefined.roc ─
+                ── MISSING DEFINITION [E0607] ────────── tests/known_bad/ExposedNotDefined.roc ─
 
                 bar is listed as exposed, but it isn't defined in this module.
 
@@ -1456,7 +1457,7 @@ mod cli_run {
             &[],
             indoc!(
                 r#"
-                ── UNUSED IMPORT ──────────────────────────── tests/known_bad/UnusedImport.roc ─
+                ── UNUSED IMPORT [E0702] ──────────────────── tests/known_bad/UnusedImport.roc ─
 
                 Nothing from Symbol is used in this module.
 
@@ -1479,7 +1480,7 @@ mod cli_run {
             &[],
             indoc!(
                 r#"
-                ── UNKNOWN GENERATES FUNCTION ─────── tests/known_bad/UnknownGeneratesWith.roc ─
+                ── UNKNOWN GENERATES FUNCTION [E0228] ─ tests/known_bad/UnknownGeneratesWith.roc ─
 
                 I don't know how to generate the foobar function.
 
@@ -1514,6 +1515,17 @@ mod cli_run {
         // This doesn't fail, since only "Formatted.roc" and non-roc files are present in this folder
         check_format_check_as_expected(&fixtures_dir("format/formatted_directory"), true);
     }
+
+    #[test]
+    fn format_check_globs() {
+        // Matches "Formatted.roc" here and in "formatted_directory", but not "NotFormatted.roc"
+        check_format_check_as_expected(&fixtures_dir("format").join("**/Formatted.roc"), true);
+
+        check_format_check_as_expected(&fixtures_dir("format").join("*Formatted.roc"), false);
+
+        // No files match, which is an error
+        check_format_check_as_expected(&fixtures_dir("format").join("*.rock"), false);
+    }
 }
 
 #[cfg(feature = "wasm32-cli-run")]
@@ -987,6 +987,20 @@ fn call_spec<'a>(
 
                     let init_state = new_list(builder, block, output_element_type)?;
 
+                    add_loop(builder, block, state_type, init_state, loop_body)
+                }
+                StrWalkUtf8 { state, .. } => {
+                    let state = env.symbols[state];
+
+                    let loop_body = |builder: &mut FuncDefBuilder, block, state| {
+                        let byte = builder.add_make_tuple(block, &[])?;
+
+                        Ok(call_function!(builder, block, [state, byte]))
+                    };
+
+                    let state_type = layout_spec(env, builder, interner, *return_layout)?;
+                    let init_state = state;
+
                     add_loop(builder, block, state_type, init_state, loop_body)
                 }
             }
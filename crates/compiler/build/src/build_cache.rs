@@ -0,0 +1,146 @@
+//! Reuse the executable of an earlier `roc build` when none of the files it was built from
+//! changed.
+//!
+//! An app on a prebuilt platform is built from the source files of its modules and packages and
+//! from the preprocessed host, by a given compiler with given options. After a build without
+//! problems, [BuildCache::store] records a hash of each of those files next to a copy of the
+//! executable, in a directory named after the compiler, the app, the target and the options.
+//! The next build of the app checks those hashes with [BuildCache::lookup] before loading
+//! anything, and if they all match, copies the executable to where the build would write it.
+//!
+//! Only whole builds are reused: when any module changed, the app is built from scratch.
+//! Keeping the typechecked or specialized modules of an app between runs would need their
+//! symbols to mean the same thing on every run. They don't: the loader numbers the app's modules
+//! in the order its worker threads discover them, and every symbol carries its module's number.
+
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use roc_packaging::cache::RocCacheDir;
+use target_lexicon::Triple;
+
+use crate::link::{LinkType, LinkingStrategy};
+use crate::program::CodeGenOptions;
+
+const VERSION: &str = include_str!("../../../../version.txt");
+
+/// The path the executable was written to, then a line of `<hash> <path>` for every input
+const MANIFEST: &str = "manifest";
+const BINARY: &str = "binary";
+
+pub struct BuildCache {
+    dir: PathBuf,
+}
+
+impl BuildCache {
+    /// The cache for building the app at `app_module_path` with these options, or `None` if
+    /// nothing may be written to `roc_cache_dir`
+    pub fn new(
+        roc_cache_dir: RocCacheDir<'_>,
+        app_module_path: &Path,
+        target: &Triple,
+        code_gen_options: &CodeGenOptions,
+        link_type: LinkType,
+        linking_strategy: LinkingStrategy,
+        prebuilt_requested: bool,
+    ) -> Option<Self> {
+        let packages_dir = match roc_cache_dir {
+            RocCacheDir::Persistent(dir) => dir,
+            _ => return None,
+        };
+
+        // compilers built from source all have the same version, so tell them apart by their
+        // contents: a modification time can stay the same across a rebuild, e.g. after `cp -p`
+        let compiler_hash = hash_file(&std::env::current_exe().ok()?).ok()?;
+
+        // the debug flags, the sanitizers, the linker flags and the zig to link with are all
+        // read from `ROC_*` variables, and a build with any of them set must not be replaced by
+        // one without
+        let mut roc_env_vars: Vec<_> = std::env::vars_os()
+            .filter(|(name, _)| name.to_string_lossy().starts_with("ROC_"))
+            .collect();
+        roc_env_vars.sort();
+
+        let key = format!(
+            "{}\n{}\n{:?}\n{}\n{}\n{:?}\n{:?}\n{:?}\n{}",
+            VERSION.trim(),
+            compiler_hash,
+            roc_env_vars,
+            app_module_path.canonicalize().ok()?.display(),
+            target,
+            code_gen_options,
+            link_type,
+            linking_strategy,
+            prebuilt_requested,
+        );
+
+        // the downloaded packages are in a `packages` directory of the roc cache
+        let dir = packages_dir
+            .with_file_name("builds")
+            .join(blake3::hash(key.as_bytes()).to_hex().as_str());
+
+        Some(Self { dir })
+    }
+
+    /// If none of the files the last build read changed since, copy its executable to where
+    /// that build wrote it, and return that path
+    pub fn lookup(&self) -> Option<PathBuf> {
+        let manifest = fs::read_to_string(self.dir.join(MANIFEST)).ok()?;
+        let mut lines = manifest.lines();
+        let binary_path = PathBuf::from(lines.next()?);
+
+        for line in lines {
+            let (hash, path) = line.split_once(' ')?;
+
+            if hash_file(Path::new(path)).ok()? != hash {
+                return None;
+            }
+        }
+
+        fs::copy(self.dir.join(BINARY), &binary_path).ok()?;
+
+        Some(binary_path)
+    }
+
+    /// Remember that the executable at `binary_path` was built from the files at `inputs`
+    pub fn store(&self, inputs: &[PathBuf], binary_path: &Path) -> io::Result<()> {
+        let mut manifest = String::new();
+
+        for path in std::iter::once(binary_path).chain(inputs.iter().map(PathBuf::as_path)) {
+            let path = path.canonicalize()?;
+
+            match path.to_str() {
+                Some(path) if !path.contains('\n') => {
+                    if manifest.is_empty() {
+                        writeln!(manifest, "{}", path).unwrap();
+                    } else {
+                        writeln!(manifest, "{} {}", hash_file(Path::new(path))?, path).unwrap();
+                    }
+                }
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("cannot record the path {}", path.display()),
+                    ))
+                }
+            }
+        }
+
+        fs::create_dir_all(&self.dir)?;
+
+        // the manifest goes last, so that a store that stops halfway is never looked up
+        match fs::remove_file(self.dir.join(MANIFEST)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+
+        fs::copy(binary_path, self.dir.join(BINARY))?;
+        fs::write(self.dir.join(MANIFEST), manifest)
+    }
+}
+
+fn hash_file(path: &Path) -> io::Result<String> {
+    Ok(blake3::hash(&fs::read(path)?).to_hex().to_string())
+}
@@ -0,0 +1,267 @@
+//! Writes a C header that declares what a Roc app exposes to its host, so hosts and programs
+//! written in C, or in any language that can read C headers, don't have to mirror Roc's
+//! layouts by hand.
+//!
+//! Every layout the exposed functions use gets a C type with the same size and alignment.
+//! Records become structs with a field per Roc field, in the order the layout stores them.
+//! Tag unions and closure captures become byte arrays, and boxes and recursive unions become
+//! pointers, since the host shouldn't look inside them. The header then declares
+//!
+//! * `roc__{name}_1_exposed_generic`, which writes the result of an exposed function through
+//!   its first argument
+//! * the `caller`, `size` and `result_size` functions for the closures the host gets, see
+//!   [roc_mono::host_exposed]
+use roc_collections::MutMap;
+use roc_load::MonomorphizedModule;
+use roc_mono::host_exposed::{caller_name, host_exposed_name, result_size_name, size_name};
+use roc_mono::ir::{HostExposedLayouts, ProcLayout};
+use roc_mono::layout::{
+    Builtin, InLayout, Layout, LayoutIds, LayoutInterner, LayoutRepr, RawFunctionLayout,
+    STLayoutInterner, UnionLayout,
+};
+use std::fmt::Write;
+
+/// Renders the types and functions of every host-exposed proc in `loaded` as a C header.
+///
+/// Procs are sorted by name, so the output is stable from one build to the next.
+pub fn c_header(loaded: &MonomorphizedModule<'_>) -> String {
+    let interner = &loaded.layout_interner;
+    let interns = &loaded.interns;
+    let mut layout_ids = LayoutIds::default();
+    let mut types = Types {
+        interner,
+        names: MutMap::default(),
+        definitions: String::new(),
+    };
+
+    let mut procs: Vec<_> = loaded
+        .procedures
+        .iter()
+        .filter(|(_, proc)| {
+            matches!(
+                proc.host_exposed_layouts,
+                HostExposedLayouts::HostExposed { .. }
+            )
+        })
+        .collect();
+
+    procs.sort_by_key(|((symbol, _), _)| symbol.as_str(interns));
+
+    let mut declarations = String::new();
+
+    for ((symbol, proc_layout), proc) in procs {
+        let generic_name = layout_ids
+            .get_toplevel(*symbol, proc_layout)
+            .to_exposed_generic_symbol_string(*symbol, interns);
+
+        writeln!(declarations).unwrap();
+        declarations.push_str(&exposed_generic(&mut types, &generic_name, proc_layout));
+
+        let aliases = match &proc.host_exposed_layouts {
+            HostExposedLayouts::HostExposed { aliases, .. } => aliases,
+            HostExposedLayouts::NotHostExposed => unreachable!(),
+        };
+
+        let mut closures: Vec<_> = aliases.values().collect();
+        closures.sort_by_key(|hels| hels.id.0);
+
+        for hels in closures {
+            let name = host_exposed_name(interns, proc.name.name(), hels);
+
+            if let RawFunctionLayout::Function(..) = hels.raw_function_layout {
+                let ProcLayout {
+                    arguments, result, ..
+                } = hels.proc_layout;
+
+                let mut params: Vec<String> = arguments
+                    .iter()
+                    .enumerate()
+                    .map(|(index, argument)| {
+                        let c_type = types.c_type(*argument);
+                        // the closure data comes after the arguments
+                        let param = if index + 1 == arguments.len() {
+                            "closure".to_string()
+                        } else {
+                            format!("arg{}", index)
+                        };
+
+                        format!("const {} *{}", c_type.as_deref().unwrap_or("void"), param)
+                    })
+                    .collect();
+
+                params.push(format!(
+                    "{} *output",
+                    types.c_type(result).as_deref().unwrap_or("void")
+                ));
+
+                writeln!(
+                    declarations,
+                    "void {}({});",
+                    caller_name(&name),
+                    params.join(", ")
+                )
+                .unwrap();
+                writeln!(declarations, "int64_t {}(void);", size_name(&name)).unwrap();
+            }
+
+            writeln!(declarations, "int64_t {}(void);", result_size_name(&name)).unwrap();
+        }
+    }
+
+    let wide_alignment = interner.alignment_bytes(Layout::I128);
+    let mut header = String::new();
+
+    writeln!(
+        header,
+        "// Generated by `roc build --emit c-header`, for {:?}. Rebuild it when the exposed types change.",
+        interner.target_info().architecture
+    )
+    .unwrap();
+    header.push_str(
+        "#pragma once\n\
+         \n\
+         #include <stdbool.h>\n\
+         #include <stddef.h>\n\
+         #include <stdint.h>\n\
+         \n\
+         typedef struct RocStr { uint8_t *bytes; size_t length; size_t capacity; } RocStr;\n\
+         typedef struct RocList { void *elements; size_t length; size_t capacity; } RocList;\n",
+    );
+
+    for name in ["RocDec", "RocI128", "RocU128"] {
+        writeln!(
+            header,
+            "typedef struct {} {{ _Alignas({}) uint64_t low; uint64_t high; }} {};",
+            name, wide_alignment, name
+        )
+        .unwrap();
+    }
+
+    header.push_str(&types.definitions);
+    header.push_str(&declarations);
+
+    header
+}
+
+/// The declaration of the exposed function, or a comment saying why there is none: arguments
+/// other than numbers, strings and lists are passed in ways C can't declare.
+fn exposed_generic<'a>(
+    types: &mut Types<'a, '_>,
+    name: &str,
+    proc_layout: &ProcLayout<'a>,
+) -> String {
+    let mut params = vec![format!(
+        "{} *output",
+        types
+            .c_type(proc_layout.result)
+            .as_deref()
+            .unwrap_or("void")
+    )];
+
+    for (index, argument) in proc_layout.arguments.iter().enumerate() {
+        let param = match types.interner.get(*argument).repr {
+            LayoutRepr::Builtin(Builtin::Str | Builtin::List(_)) => {
+                format!("{} *arg{}", types.c_type(*argument).unwrap(), index)
+            }
+            LayoutRepr::Builtin(_) => format!("{} arg{}", types.c_type(*argument).unwrap(), index),
+            _ => return format!("// {} takes arguments C can't declare\n", name),
+        };
+
+        params.push(param);
+    }
+
+    format!("void {}({});\n", name, params.join(", "))
+}
+
+struct Types<'a, 'r> {
+    interner: &'r STLayoutInterner<'a>,
+    /// The C types of the layouts that needed a definition
+    names: MutMap<InLayout<'a>, String>,
+    definitions: String,
+}
+
+impl<'a, 'r> Types<'a, 'r> {
+    /// The C type with the size and alignment of `layout`, or `None` if it has no size, because
+    /// C has no such types
+    fn c_type(&mut self, layout: InLayout<'a>) -> Option<String> {
+        let (size, alignment) = self.interner.stack_size_and_alignment(layout);
+
+        if size == 0 {
+            return None;
+        }
+
+        if let Some(name) = self.names.get(&layout) {
+            return Some(name.clone());
+        }
+
+        let c_type = match self.interner.get(layout).repr {
+            LayoutRepr::Builtin(builtin) => builtin_type(builtin).to_string(),
+            LayoutRepr::Struct { field_layouts } => {
+                let fields: Vec<String> = field_layouts
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, field)| {
+                        let c_type = self.c_type(*field)?;
+
+                        Some(format!("{} f{};", c_type, index))
+                    })
+                    .collect();
+
+                self.define(layout, "RocStruct", &fields.join(" "))
+            }
+            LayoutRepr::LambdaSet(lambda_set) => {
+                return self.c_type(lambda_set.runtime_representation())
+            }
+            LayoutRepr::Union(UnionLayout::NonRecursive(_)) => {
+                let bytes = format!("_Alignas({}) uint8_t bytes[{}];", alignment, size);
+
+                self.define(layout, "RocUnion", &bytes)
+            }
+            LayoutRepr::Union(_) | LayoutRepr::Boxed(_) | LayoutRepr::RecursivePointer(_) => {
+                "void *".to_string()
+            }
+        };
+
+        Some(c_type)
+    }
+
+    fn define(&mut self, layout: InLayout<'a>, kind: &str, fields: &str) -> String {
+        let name = format!("{}{}", kind, self.names.len());
+
+        writeln!(
+            self.definitions,
+            "typedef struct {} {{ {} }} {};",
+            name, fields, name
+        )
+        .unwrap();
+
+        self.names.insert(layout, name.clone());
+
+        name
+    }
+}
+
+fn builtin_type(builtin: Builtin<'_>) -> &'static str {
+    match builtin {
+        Builtin::Int(width) => match (width.is_signed(), width.stack_size()) {
+            (true, 1) => "int8_t",
+            (true, 2) => "int16_t",
+            (true, 4) => "int32_t",
+            (true, 8) => "int64_t",
+            (true, _) => "RocI128",
+            (false, 1) => "uint8_t",
+            (false, 2) => "uint16_t",
+            (false, 4) => "uint32_t",
+            (false, 8) => "uint64_t",
+            (false, _) => "RocU128",
+        },
+        Builtin::Float(width) => match width.stack_size() {
+            4 => "float",
+            _ => "double",
+        },
+        Builtin::Bool => "bool",
+        Builtin::Decimal => "RocDec",
+        Builtin::Str => "RocStr",
+        Builtin::List(_) => "RocList",
+    }
+}
@@ -0,0 +1,369 @@
+//! Describes which procs call which after specialization, as JSON and as a Graphviz DOT graph,
+//! so users can see why code ends up in their app, and so compiler developers can debug
+//! dead-code elimination.
+//!
+//! Besides the specialized procs, the graph has a node for every refcounting helper the backends
+//! will generate (one per operation and layout), every lowlevel builtin, and every function the
+//! host provides.
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use roc_collections::all::MutMap;
+use roc_load::MonomorphizedModule;
+use roc_module::symbol::{Interns, Symbol};
+use roc_mono::ir::{Call, CallType, Expr, HostExposedLayouts, ModifyRc, ProcLayout, Stmt};
+use roc_mono::layout::{InLayout, LayoutInterner, STLayoutInterner};
+use roc_mono::low_level::HigherOrder;
+use serde_json::{json, Value};
+
+#[derive(Clone, Copy)]
+enum NodeKind {
+    Proc,
+    Refcount,
+    LowLevel,
+    Foreign,
+}
+
+impl NodeKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            NodeKind::Proc => "proc",
+            NodeKind::Refcount => "refcount",
+            NodeKind::LowLevel => "lowlevel",
+            NodeKind::Foreign => "foreign",
+        }
+    }
+}
+
+struct Node {
+    kind: NodeKind,
+    /// Exposed to the host, so it is kept no matter what calls it
+    root: bool,
+    /// The first caller on a shortest path from a root, which is why this node is kept
+    retained_by: Option<String>,
+}
+
+/// Nodes and edges are kept in maps sorted by name, so the output is stable from one build to
+/// the next.
+#[derive(Default)]
+struct CallGraph {
+    nodes: BTreeMap<String, Node>,
+    /// How often each caller calls each callee
+    edges: BTreeMap<(String, String), usize>,
+}
+
+/// Renders the call graph of `loaded` as a pretty-printed JSON document.
+pub fn call_graph_json(loaded: &MonomorphizedModule<'_>) -> String {
+    let graph = call_graph(loaded);
+
+    let nodes: Vec<Value> = graph
+        .nodes
+        .iter()
+        .map(|(name, node)| {
+            json!({
+                "name": name,
+                "kind": node.kind.as_str(),
+                "root": node.root,
+                "retained_by": node.retained_by,
+            })
+        })
+        .collect();
+
+    let edges: Vec<Value> = graph
+        .edges
+        .iter()
+        .map(|((caller, callee), calls)| {
+            json!({
+                "caller": caller,
+                "callee": callee,
+                "calls": calls,
+            })
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&json!({
+        "nodes": nodes,
+        "edges": edges,
+    }))
+    .unwrap()
+}
+
+/// Renders the call graph of `loaded` in the DOT language of Graphviz.
+pub fn call_graph_dot(loaded: &MonomorphizedModule<'_>) -> String {
+    let graph = call_graph(loaded);
+    let mut dot = String::from("digraph calls {\n    node [fontname=monospace];\n");
+
+    for (name, node) in graph.nodes.iter() {
+        let shape = match node.kind {
+            NodeKind::Proc => "box",
+            NodeKind::Refcount => "ellipse",
+            NodeKind::LowLevel => "diamond",
+            NodeKind::Foreign => "hexagon",
+        };
+        let style = if node.root { ", style=bold" } else { "" };
+
+        dot.push_str(&format!("    {:?} [shape={shape}{style}];\n", name));
+    }
+
+    for ((caller, callee), calls) in graph.edges.iter() {
+        if *calls == 1 {
+            dot.push_str(&format!("    {:?} -> {:?};\n", caller, callee));
+        } else {
+            dot.push_str(&format!(
+                "    {:?} -> {:?} [label=\"{calls}\"];\n",
+                caller, callee
+            ));
+        }
+    }
+
+    dot.push_str("}\n");
+
+    dot
+}
+
+fn call_graph(loaded: &MonomorphizedModule<'_>) -> CallGraph {
+    let interner = &loaded.layout_interner;
+    let interns = &loaded.interns;
+    let mut graph = CallGraph::default();
+
+    for ((symbol, proc_layout), proc) in loaded.procedures.iter() {
+        let name = proc_name(interner, interns, *symbol, proc_layout);
+        let root = loaded.exposed_to_host.top_level_values.contains_key(symbol)
+            || matches!(
+                proc.host_exposed_layouts,
+                HostExposedLayouts::HostExposed { .. }
+            );
+
+        graph.add_node(name.clone(), NodeKind::Proc).root |= root;
+
+        let mut walker = Walker {
+            interner,
+            interns,
+            caller: name,
+            layouts: proc
+                .args
+                .iter()
+                .map(|(layout, symbol)| (*symbol, *layout))
+                .collect(),
+            graph: &mut graph,
+        };
+
+        walker.walk_stmt(&proc.body);
+    }
+
+    graph.find_retainers();
+
+    graph
+}
+
+fn proc_name<'a>(
+    interner: &STLayoutInterner<'a>,
+    interns: &Interns,
+    symbol: Symbol,
+    proc_layout: &ProcLayout<'a>,
+) -> String {
+    let arguments: Vec<String> = proc_layout
+        .arguments
+        .iter()
+        .map(|layout| interner.dbg(*layout))
+        .collect();
+
+    format!(
+        "{}.{} : ({}) -> {}",
+        interns.module_name(symbol.module_id()).as_str(),
+        symbol.as_str(interns),
+        arguments.join(", "),
+        interner.dbg(proc_layout.result)
+    )
+}
+
+impl CallGraph {
+    fn add_node(&mut self, name: String, kind: NodeKind) -> &mut Node {
+        self.nodes.entry(name).or_insert(Node {
+            kind,
+            root: false,
+            retained_by: None,
+        })
+    }
+
+    fn add_edge(&mut self, caller: &str, callee: String, kind: NodeKind) {
+        self.add_node(callee.clone(), kind);
+
+        *self.edges.entry((caller.to_string(), callee)).or_insert(0) += 1;
+    }
+
+    /// Walk breadth-first from the roots, so every node is retained by a caller on a shortest
+    /// path from a root.
+    fn find_retainers(&mut self) {
+        let mut callees: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+
+        for (caller, callee) in self.edges.keys() {
+            callees
+                .entry(caller.as_str())
+                .or_default()
+                .push(callee.as_str());
+        }
+
+        let mut seen: BTreeSet<&str> = BTreeSet::new();
+        let mut queue: VecDeque<&str> = VecDeque::new();
+        let mut retainers: Vec<(String, String)> = Vec::new();
+
+        for (name, node) in self.nodes.iter() {
+            if node.root {
+                seen.insert(name.as_str());
+                queue.push_back(name.as_str());
+            }
+        }
+
+        while let Some(caller) = queue.pop_front() {
+            for callee in callees.get(caller).into_iter().flatten() {
+                if seen.insert(*callee) {
+                    retainers.push((callee.to_string(), caller.to_string()));
+                    queue.push_back(*callee);
+                }
+            }
+        }
+
+        for (callee, caller) in retainers {
+            self.nodes.get_mut(&callee).unwrap().retained_by = Some(caller);
+        }
+    }
+}
+
+struct Walker<'a, 'r> {
+    interner: &'r STLayoutInterner<'a>,
+    interns: &'r Interns,
+    caller: String,
+    /// The layout of every symbol in scope, to find the refcounting helper a statement uses
+    layouts: MutMap<Symbol, InLayout<'a>>,
+    graph: &'r mut CallGraph,
+}
+
+impl<'a, 'r> Walker<'a, 'r> {
+    fn walk_stmt(&mut self, stmt: &Stmt<'a>) {
+        use Stmt::*;
+
+        match stmt {
+            Let(symbol, expr, layout, cont) => {
+                self.layouts.insert(*symbol, *layout);
+                self.walk_expr(expr);
+                self.walk_stmt(cont);
+            }
+            Switch {
+                branches,
+                default_branch,
+                ..
+            } => {
+                for (_, _, branch) in branches.iter() {
+                    self.walk_stmt(branch);
+                }
+
+                self.walk_stmt(default_branch.1);
+            }
+            Refcounting(modify_rc, cont) => {
+                let (operation, symbol) = match modify_rc {
+                    ModifyRc::Inc(symbol, _) => ("inc", symbol),
+                    ModifyRc::Dec(symbol) => ("dec", symbol),
+                    ModifyRc::DecRef(symbol) => ("decref", symbol),
+                };
+
+                self.refcount_helper(operation, *symbol);
+                self.walk_stmt(cont);
+            }
+            Expect { remainder, .. } | ExpectFx { remainder, .. } | Dbg { remainder, .. } => {
+                self.walk_stmt(remainder)
+            }
+            Join {
+                parameters,
+                body,
+                remainder,
+                ..
+            } => {
+                for param in parameters.iter() {
+                    self.layouts.insert(param.symbol, param.layout);
+                }
+
+                self.walk_stmt(body);
+                self.walk_stmt(remainder);
+            }
+            Ret(_) | Jump(..) | Crash(..) => {}
+        }
+    }
+
+    fn walk_expr(&mut self, expr: &Expr<'a>) {
+        match expr {
+            Expr::Call(Call { call_type, .. }) => match call_type {
+                CallType::ByName {
+                    name,
+                    ret_layout,
+                    arg_layouts,
+                    ..
+                } => {
+                    let proc_layout = ProcLayout {
+                        arguments: arg_layouts,
+                        result: *ret_layout,
+                        niche: name.niche(),
+                    };
+
+                    self.call_proc(name.name(), &proc_layout);
+                }
+                CallType::Foreign { foreign_symbol, .. } => {
+                    let callee = foreign_symbol.as_str().to_string();
+
+                    self.graph.add_edge(&self.caller, callee, NodeKind::Foreign);
+                }
+                CallType::LowLevel { op, .. } => {
+                    let callee = format!("{:?}", op);
+
+                    self.graph
+                        .add_edge(&self.caller, callee, NodeKind::LowLevel);
+                }
+                CallType::HigherOrder(higher_order) => {
+                    let callee = higher_order_name(&higher_order.op).to_string();
+                    self.graph
+                        .add_edge(&self.caller, callee, NodeKind::LowLevel);
+
+                    // the builtin calls this, but it is only kept because this proc passes it
+                    let passed = &higher_order.passed_function;
+                    let proc_layout = ProcLayout {
+                        arguments: passed.argument_layouts,
+                        result: passed.return_layout,
+                        niche: passed.name.niche(),
+                    };
+
+                    self.call_proc(passed.name.name(), &proc_layout);
+                }
+            },
+            Expr::Reset { symbol, .. } => self.refcount_helper("reset", *symbol),
+            Expr::ResetRef { symbol, .. } => self.refcount_helper("resetref", *symbol),
+            _ => {}
+        }
+    }
+
+    fn call_proc(&mut self, symbol: Symbol, proc_layout: &ProcLayout<'a>) {
+        let callee = proc_name(self.interner, self.interns, symbol, proc_layout);
+
+        self.graph.add_edge(&self.caller, callee, NodeKind::Proc);
+    }
+
+    fn refcount_helper(&mut self, operation: &str, symbol: Symbol) {
+        let layout = match self.layouts.get(&symbol) {
+            Some(layout) => *layout,
+            None => return,
+        };
+        let callee = format!("{operation} {}", self.interner.dbg(layout));
+
+        self.graph
+            .add_edge(&self.caller, callee, NodeKind::Refcount);
+    }
+}
+
+fn higher_order_name(op: &HigherOrder) -> &'static str {
+    match op {
+        HigherOrder::ListMap { .. } => "ListMap",
+        HigherOrder::ListMap2 { .. } => "ListMap2",
+        HigherOrder::ListMap3 { .. } => "ListMap3",
+        HigherOrder::ListMap4 { .. } => "ListMap4",
+        HigherOrder::ListSortWith { .. } => "ListSortWith",
+        HigherOrder::StrWalkUtf8 { .. } => "StrWalkUtf8",
+    }
+}
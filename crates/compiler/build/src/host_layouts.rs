@@ -0,0 +1,227 @@
+//! Describes the layouts of everything a Roc app exposes to its host as JSON, so hosts written
+//! in languages other than Rust can generate their own bindings, and so that changes to those
+//! layouts show up as a diff.
+use roc_load::MonomorphizedModule;
+use roc_mono::ir::{HostExposedLayouts, ProcLayout};
+use roc_mono::layout::{
+    Builtin, InLayout, LayoutIds, LayoutInterner, LayoutRepr, STLayoutInterner, UnionLayout,
+};
+use roc_target::TargetInfo;
+use serde_json::{json, Value};
+
+/// The name of the custom section in Wasm apps that describes what the app exposes to the host
+pub const WASM_METADATA_SECTION: &str = "roc_metadata";
+
+/// Renders the layouts of every host-exposed proc in `loaded`, and of the closures those procs
+/// hand to the host, as a pretty-printed JSON document.
+///
+/// Procs and closures are sorted by name, so the output is stable from one build to the next.
+pub fn host_layouts_json(loaded: &MonomorphizedModule<'_>) -> String {
+    serde_json::to_string_pretty(&host_layouts(loaded)).unwrap()
+}
+
+/// The contents of the [WASM_METADATA_SECTION]: the version of the compiler that built the app,
+/// and the same description of its entry points as [host_layouts_json], so JS glue can check
+/// that it matches the app before calling into it.
+pub fn wasm_metadata_json(loaded: &MonomorphizedModule<'_>, compiler_version: &str) -> String {
+    let mut metadata = host_layouts(loaded);
+    metadata["compiler_version"] = json!(compiler_version);
+
+    metadata.to_string()
+}
+
+fn host_layouts(loaded: &MonomorphizedModule<'_>) -> Value {
+    let interner = &loaded.layout_interner;
+    let interns = &loaded.interns;
+    let mut layout_ids = LayoutIds::default();
+
+    let mut procs: Vec<(&str, Value)> = loaded
+        .procedures
+        .iter()
+        .filter_map(
+            |((symbol, proc_layout), proc)| match &proc.host_exposed_layouts {
+                HostExposedLayouts::NotHostExposed => None,
+                HostExposedLayouts::HostExposed { aliases, .. } => {
+                    let mut closures: Vec<(&str, Value)> = aliases
+                        .iter()
+                        .map(|(name, hels)| {
+                            let name = name.as_str(interns);
+                            let mut closure = proc_layout_json(interner, &hels.proc_layout);
+                            closure["name"] = json!(name);
+                            closure["lambda_set_id"] = json!(hels.id.0);
+
+                            (name, closure)
+                        })
+                        .collect();
+
+                    closures.sort_by(|a, b| a.0.cmp(b.0));
+
+                    let name = symbol.as_str(interns);
+                    let exposed_symbol = layout_ids
+                        .get_toplevel(*symbol, proc_layout)
+                        .to_exposed_symbol_string(*symbol, interns);
+                    let mut value = proc_layout_json(interner, proc_layout);
+                    value["name"] = json!(name);
+                    value["exposed_symbol"] = json!(exposed_symbol);
+                    value["closures"] =
+                        Value::Array(closures.into_iter().map(|(_, value)| value).collect());
+
+                    Some((name, value))
+                }
+            },
+        )
+        .collect();
+
+    procs.sort_by(|a, b| a.0.cmp(b.0));
+
+    json!({
+        "target": target_json(interner.target_info()),
+        "procs": procs.into_iter().map(|(_, value)| value).collect::<Vec<_>>(),
+    })
+}
+
+fn target_json(target_info: TargetInfo) -> Value {
+    json!({
+        "architecture": format!("{:?}", target_info.architecture),
+        "pointer_width": target_info.ptr_width() as u32,
+        "pointer_tagging": target_info.pointer_tagging,
+    })
+}
+
+fn proc_layout_json<'a>(interner: &STLayoutInterner<'a>, proc_layout: &ProcLayout<'a>) -> Value {
+    json!({
+        "arguments": proc_layout
+            .arguments
+            .iter()
+            .map(|argument| layout_json(interner, *argument))
+            .collect::<Vec<_>>(),
+        "result": layout_json(interner, proc_layout.result),
+    })
+}
+
+fn layout_json<'a>(interner: &STLayoutInterner<'a>, layout: InLayout<'a>) -> Value {
+    let (size, alignment) = interner.stack_size_and_alignment(layout);
+
+    let mut value = match interner.get(layout).repr {
+        LayoutRepr::Builtin(builtin) => builtin_json(interner, builtin),
+        LayoutRepr::Struct { field_layouts } => json!({
+            "kind": "struct",
+            "fields": fields_json(interner, field_layouts),
+        }),
+        LayoutRepr::Boxed(inner) => json!({
+            "kind": "box",
+            "inner": layout_json(interner, inner),
+        }),
+        LayoutRepr::Union(union_layout) => union_json(interner, union_layout),
+        LayoutRepr::LambdaSet(lambda_set) => json!({
+            "kind": "closure",
+            "captures": layout_json(interner, lambda_set.runtime_representation()),
+        }),
+        // The layout this points back to is the enclosing recursive union, which is already
+        // being described; following it would never terminate.
+        LayoutRepr::RecursivePointer(_) => json!({ "kind": "recursive_pointer" }),
+    };
+
+    value["size"] = json!(size);
+    value["alignment"] = json!(alignment);
+
+    value
+}
+
+fn builtin_json<'a>(interner: &STLayoutInterner<'a>, builtin: Builtin<'a>) -> Value {
+    match builtin {
+        Builtin::Int(width) => json!({ "kind": width.type_name() }),
+        Builtin::Float(width) => json!({ "kind": format!("f{}", width.stack_size() * 8) }),
+        Builtin::Bool => json!({ "kind": "bool" }),
+        Builtin::Decimal => json!({ "kind": "dec" }),
+        Builtin::Str => json!({ "kind": "str" }),
+        Builtin::List(element) => json!({
+            "kind": "list",
+            "element": layout_json(interner, element),
+        }),
+    }
+}
+
+/// Fields are laid out in order with no padding between them; the layout code already sorted
+/// them by alignment.
+fn fields_json<'a>(interner: &STLayoutInterner<'a>, field_layouts: &[InLayout<'a>]) -> Value {
+    let mut offset = 0;
+
+    field_layouts
+        .iter()
+        .map(|field| {
+            let value = json!({
+                "offset": offset,
+                "layout": layout_json(interner, *field),
+            });
+
+            offset += interner.stack_size(*field);
+
+            value
+        })
+        .collect()
+}
+
+fn union_json<'a>(interner: &STLayoutInterner<'a>, union_layout: UnionLayout<'a>) -> Value {
+    let target_info = interner.target_info();
+
+    let (representation, tags): (&str, Vec<Option<&[InLayout<'a>]>>) = match union_layout {
+        UnionLayout::NonRecursive(tags) => {
+            ("non_recursive", tags.iter().copied().map(Some).collect())
+        }
+        UnionLayout::Recursive(tags) => ("recursive", tags.iter().copied().map(Some).collect()),
+        UnionLayout::NonNullableUnwrapped(fields) => ("non_nullable_unwrapped", vec![Some(fields)]),
+        UnionLayout::NullableWrapped {
+            nullable_id,
+            other_tags,
+        } => {
+            let mut tags: Vec<_> = other_tags.iter().copied().map(Some).collect();
+            tags.insert(nullable_id as usize, None);
+
+            ("nullable_wrapped", tags)
+        }
+        UnionLayout::NullableUnwrapped {
+            nullable_id,
+            other_fields,
+        } => {
+            let tags = if nullable_id {
+                vec![Some(other_fields), None]
+            } else {
+                vec![None, Some(other_fields)]
+            };
+
+            ("nullable_unwrapped", tags)
+        }
+    };
+
+    let tag_id = if union_layout.stores_tag_id_in_pointer(target_info) {
+        let (bits, mask) = UnionLayout::tag_id_pointer_bits_and_mask(target_info);
+
+        json!({ "location": "pointer", "bits": bits, "mask": mask })
+    } else if union_layout.stores_tag_id_as_data(target_info) {
+        json!({
+            "location": "data",
+            "offset": union_layout.tag_id_offset(interner, target_info),
+            "size": interner.stack_size(union_layout.tag_id_layout()),
+        })
+    } else {
+        json!({ "location": "implicit" })
+    };
+
+    let tags: Vec<Value> = tags
+        .into_iter()
+        .enumerate()
+        .map(|(id, fields)| match fields {
+            Some(fields) => json!({ "id": id, "fields": fields_json(interner, fields) }),
+            None => json!({ "id": id, "null": true }),
+        })
+        .collect();
+
+    json!({
+        "kind": "tag_union",
+        "representation": representation,
+        "heap_allocated": !matches!(union_layout, UnionLayout::NonRecursive(_)),
+        "tag_id": tag_id,
+        "tags": tags,
+    })
+}
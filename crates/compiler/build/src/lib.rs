@@ -2,6 +2,12 @@
 #![warn(clippy::dbg_macro)]
 // See github.com/roc-lang/roc/issues/800 for discussion of the large_enum_variant check.
 #![allow(clippy::large_enum_variant)]
+pub mod build_cache;
+pub mod c_header;
+pub mod call_graph;
+pub mod host_layouts;
 pub mod link;
 pub mod program;
 pub mod target;
+pub mod timings;
+pub mod wasm_source_map;
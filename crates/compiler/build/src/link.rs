@@ -32,10 +32,16 @@ pub fn link(
     link_type: LinkType,
 ) -> io::Result<(Child, PathBuf)> {
     match target {
+        _ if link_type == LinkType::StaticLib => link_static_lib(target, output_path, input_paths),
         Triple {
             architecture: Architecture::Wasm32,
             ..
         } => link_wasm32(target, output_path, input_paths, link_type),
+        // The system linkers below only know how to link for the machine they're running on,
+        // but zig can link an executable for any target it knows about.
+        _ if needs_cross_linker(target) && link_type == LinkType::Executable => {
+            link_cross_target(target, output_path, input_paths)
+        }
         Triple {
             operating_system: OperatingSystem::Linux,
             ..
@@ -1077,7 +1083,12 @@ fn link_linux(
                 output_path,
             )
         }
-        LinkType::None => internal_error!("link_linux should not be called with link type of none"),
+        LinkType::None | LinkType::StaticLib => {
+            internal_error!(
+                "link_linux should not be called with link type {:?}",
+                link_type
+            )
+        }
     };
 
     let env_path = env::var("PATH").unwrap_or_else(|_| "".to_string());
@@ -1147,7 +1158,12 @@ fn link_macos(
 
             (vec!["-dylib", "-undefined", "dynamic_lookup"], output_path)
         }
-        LinkType::None => internal_error!("link_macos should not be called with link type of none"),
+        LinkType::None | LinkType::StaticLib => {
+            internal_error!(
+                "link_macos should not be called with link type {:?}",
+                link_type
+            )
+        }
     };
 
     let arch = match target.architecture {
@@ -1295,6 +1311,71 @@ fn link_wasm32(
     Ok((child, output_path))
 }
 
+/// Whether linking for `target` needs a linker that can target other machines.
+/// The system linker can still handle 32-bit x86 on a 64-bit x86 host, and
+/// Windows always links with zig anyway.
+fn needs_cross_linker(target: &Triple) -> bool {
+    let host = Triple::host();
+
+    if target.operating_system == OperatingSystem::Windows {
+        return false;
+    }
+
+    let same_arch = target.architecture == host.architecture
+        || matches!(
+            (target.architecture, host.architecture),
+            (Architecture::X86_32(_), Architecture::X86_64)
+        );
+
+    target.operating_system != host.operating_system || !same_arch
+}
+
+fn link_cross_target(
+    target: &Triple,
+    output_path: PathBuf,
+    input_paths: &[&str],
+) -> io::Result<(Child, PathBuf)> {
+    let child = zig()
+        .args(["build-exe"])
+        .args(input_paths)
+        .args([
+            "-target",
+            target_zig_str(target),
+            "-lc",
+            &format!("-femit-bin={}", output_path.to_str().unwrap()),
+        ])
+        .spawn()?;
+
+    Ok((child, output_path))
+}
+
+/// Archive the object files, for linking into programs that aren't Roc platforms
+fn link_static_lib(
+    target: &Triple,
+    mut output_path: PathBuf,
+    input_paths: &[&str],
+) -> io::Result<(Child, PathBuf)> {
+    let extension = match target.operating_system {
+        OperatingSystem::Windows => "lib",
+        _ => "a",
+    };
+
+    output_path.set_extension(extension);
+
+    // without -dynamic, zig build-lib archives the objects it's given
+    let child = zig()
+        .args(["build-lib"])
+        .args(input_paths)
+        .args([
+            "-target",
+            target_zig_str(target),
+            &format!("-femit-bin={}", output_path.to_str().unwrap()),
+        ])
+        .spawn()?;
+
+    Ok((child, output_path))
+}
+
 fn link_windows(
     target: &Triple,
     output_path: PathBuf,
@@ -1341,6 +1422,12 @@ fn link_windows(
             Ok((child, output_path))
         }
         LinkType::None => todo!(),
+        LinkType::StaticLib => {
+            internal_error!(
+                "link_windows should not be called with link type {:?}",
+                link_type
+            )
+        }
     }
 }
 
@@ -1,21 +1,28 @@
 use crate::link::{
     legacy_host_filename, link, preprocess_host_wasm32, rebuild_host, LinkType, LinkingStrategy,
 };
+use crate::timings::BuildTimings;
 use bumpalo::Bump;
 use inkwell::memory_buffer::MemoryBuffer;
-use roc_error_macros::internal_error;
+use roc_error_macros::{internal_error, user_error};
 use roc_gen_dev::AssemblyBackendMode;
 use roc_gen_llvm::llvm::build::{module_from_builtins, LlvmBackendMode};
-use roc_gen_llvm::llvm::externs::add_default_roc_externs;
+use roc_gen_llvm::llvm::externs::{
+    add_coverage_counters, add_default_roc_externs, add_profile_writer,
+};
+use roc_gen_wasm::WasmFeatures;
 use roc_load::{
     EntryPoint, ExecutionMode, ExpectMetadata, LoadConfig, LoadMonomorphizedError, LoadedModule,
     LoadingProblem, MonomorphizedModule, Threading,
 };
 use roc_mono::ir::{OptLevel, SingleEntryPoint};
+use roc_mono::overflow::OverflowMode;
+use roc_mono::profile::{apply_profile, profile_header, Profile, ProfileHints, PROFILE_EXTENSION};
 use roc_packaging::cache::RocCacheDir;
+use roc_problem::lint::SeverityConfig;
 use roc_reporting::{
-    cli::{report_problems, Problems},
-    report::{RenderTarget, DEFAULT_PALETTE},
+    cli::{render_problems, report_problems, Problems},
+    report::{Palette, RenderTarget},
 };
 use roc_target::TargetInfo;
 use std::ffi::OsStr;
@@ -32,28 +39,85 @@ use roc_collections::all::MutSet;
 
 pub const DEFAULT_ROC_FILENAME: &str = "main.roc";
 
+const VERSION: &str = include_str!("../../../../version.txt");
+
 #[derive(Debug, Clone, Copy, Default)]
 pub struct CodeGenTiming {
     pub code_gen: Duration,
 }
 
-pub fn report_problems_monomorphized(loaded: &mut MonomorphizedModule) -> Problems {
+pub fn report_problems_monomorphized(
+    loaded: &mut MonomorphizedModule,
+    severity_config: &SeverityConfig,
+) -> Problems {
+    let palette = Palette::from_env();
+    let problems =
+        render_problems_monomorphized(loaded, severity_config, RenderTarget::ColorTerminal);
+
+    problems.print_reports_to_stdout(&palette);
+
+    problems
+}
+
+/// Like [report_problems_monomorphized], but without printing the reports
+pub fn render_problems_monomorphized(
+    loaded: &mut MonomorphizedModule,
+    severity_config: &SeverityConfig,
+    render: RenderTarget,
+) -> Problems {
+    let mut problems = render_problems(
+        loaded.total_problems(),
+        &loaded.sources,
+        &loaded.interns,
+        &mut loaded.can_problems,
+        &mut loaded.type_problems,
+        severity_config,
+        render,
+        &Palette::from_env(),
+    );
+
+    // These are found during monomorphization rather than checking, so they're reported
+    // separately; like other warnings, only when there are no errors.
+    let specialization_warnings = std::mem::take(&mut loaded.specialization_warnings);
+
+    if problems.errors == 0 {
+        problems.warnings += specialization_warnings.len();
+        problems.reports.extend(specialization_warnings);
+    }
+
+    problems
+}
+
+pub fn report_problems_typechecked(
+    loaded: &mut LoadedModule,
+    severity_config: &SeverityConfig,
+) -> Problems {
     report_problems(
         loaded.total_problems(),
         &loaded.sources,
         &loaded.interns,
         &mut loaded.can_problems,
         &mut loaded.type_problems,
+        severity_config,
+        &Palette::from_env(),
     )
 }
 
-pub fn report_problems_typechecked(loaded: &mut LoadedModule) -> Problems {
-    report_problems(
+/// Like [report_problems_typechecked], but without printing the reports
+pub fn render_problems_typechecked(
+    loaded: &mut LoadedModule,
+    severity_config: &SeverityConfig,
+    render: RenderTarget,
+) -> Problems {
+    render_problems(
         loaded.total_problems(),
         &loaded.sources,
         &loaded.interns,
         &mut loaded.can_problems,
         &mut loaded.type_problems,
+        severity_config,
+        render,
+        &Palette::from_env(),
     )
 }
 
@@ -85,6 +149,52 @@ pub struct CodeGenOptions {
     pub backend: CodeGenBackend,
     pub opt_level: OptLevel,
     pub emit_debug_info: bool,
+    /// Call the host's `roc_trace_enter` and `roc_trace_exit` around every proc
+    pub trace_calls: bool,
+    /// Don't decrement what a value refers to when it's freed, for platforms that free
+    /// everything at once when the program exits. The LLVM backend also carves allocations out
+    /// of large chunks from `roc_alloc`, and never calls `roc_dealloc`.
+    pub arena_allocation: bool,
+    /// Whether integer `+`, `-` and `*` crash or wrap around when they overflow
+    pub overflow: OverflowMode,
+    /// Call the host's `roc_heap_profile_alloc` and `roc_heap_profile_dealloc` for every box and
+    /// recursive tag union. Only the LLVM backend supports this.
+    pub heap_profile: bool,
+    /// Add the Roc procs being run, and where they are, to every crash message. Only the LLVM
+    /// backend supports this; see [roc_mono::stack_trace].
+    pub stack_traces: bool,
+    /// Call the host's `roc_stack_limit` at the start of every proc, and crash with a "stack
+    /// overflow" message if the proc's frame is past it. Only the LLVM backend supports this;
+    /// see [roc_gen_llvm::llvm::stack_limit].
+    pub stack_limit_checks: bool,
+    /// Keep frame pointers, so memory checkers can show where memory was allocated and freed.
+    /// The loaded module must also be built without pointer tagging; see
+    /// [TargetInfo::without_pointer_tagging].
+    pub sanitizer_friendly: bool,
+    /// Write the layouts of everything exposed to the host to `<app>.host-layouts.json`, next
+    /// to the built executable.
+    pub emit_host_layouts: bool,
+    /// Write which procs call which procs, refcounting helpers, builtins, and host functions to
+    /// `<app>.call-graph.json` and `<app>.call-graph.dot`, next to the built executable.
+    pub emit_call_graph: bool,
+    /// Write the types and functions the app exposes to its host to `<app>.h`, next to the built
+    /// executable, so hosts written in C don't have to mirror Roc's layouts by hand.
+    pub emit_c_header: bool,
+    /// Write the app's LLVM IR before and after optimization to `<app>.unoptimized.ll` and
+    /// `<app>.optimized.ll`, and the optimized bitcode to `<app>.bc`, next to the built
+    /// executable. Only the LLVM backend supports this.
+    pub emit_llvm_ir: bool,
+    /// Write how often every function body and branch ran to `<app>.rocprof`, next to the built
+    /// executable, when the program exits. The module must be loaded with coverage. Only the
+    /// LLVM backend supports this; see [roc_mono::profile].
+    pub profile_generate: bool,
+    /// Optimize using the counts in `<app>.rocprof`, next to the built executable. The module
+    /// must be loaded with coverage. Only the LLVM backend supports this.
+    pub profile_use: bool,
+    /// Define the allocator, `roc_memcpy` and `roc_panic` hooks that the host leaves undefined,
+    /// on top of WASI imports, so the app runs in any WASI runtime. Only the Wasm backend
+    /// supports this.
+    pub wasi_hooks: bool,
 }
 
 type GenFromMono<'a> = (CodeObject, CodeGenTiming, ExpectMetadata<'a>);
@@ -92,17 +202,105 @@ type GenFromMono<'a> = (CodeObject, CodeGenTiming, ExpectMetadata<'a>);
 #[allow(clippy::too_many_arguments)]
 pub fn gen_from_mono_module<'a>(
     arena: &'a bumpalo::Bump,
-    loaded: MonomorphizedModule<'a>,
+    mut loaded: MonomorphizedModule<'a>,
     roc_file_path: &Path,
     target: &target_lexicon::Triple,
     code_gen_options: CodeGenOptions,
     preprocessed_host_path: &Path,
     wasm_dev_stack_bytes: Option<u32>,
+    wasm_dev_features: WasmFeatures,
 ) -> GenFromMono<'a> {
     let path = roc_file_path;
     let debug = code_gen_options.emit_debug_info;
     let opt = code_gen_options.opt_level;
 
+    // The profile is named after the app's output path. It must be applied before the passes
+    // below add statements to the start of procs, where its counters are.
+    let profile_path = path
+        .with_file_name(&*loaded.output_path)
+        .with_extension(PROFILE_EXTENSION);
+
+    let profile_hints = if code_gen_options.profile_use {
+        let profile = std::fs::read(&profile_path)
+            .map_err(|error| error.to_string())
+            .and_then(|bytes| Profile::parse(&bytes));
+
+        match profile {
+            Ok(profile) => Some(apply_profile(
+                arena,
+                &mut loaded.procedures,
+                &loaded.coverage_points,
+                &loaded.interns,
+                &profile,
+            )),
+            Err(problem) => user_error!(
+                "I could not read the profile at {}: {}\n\nBuild with --profile-generate and run the program to write it.",
+                profile_path.display(),
+                problem
+            ),
+        }
+    } else {
+        None
+    };
+
+    if code_gen_options.trace_calls {
+        roc_mono::trace_calls::insert_trace_calls(
+            arena,
+            &mut loaded.interns,
+            loaded.module_id,
+            &mut loaded.procedures,
+        );
+    }
+
+    if code_gen_options.stack_traces {
+        roc_mono::stack_trace::insert_stack_trace_calls(
+            arena,
+            &mut loaded.interns,
+            loaded.module_id,
+            &loaded.body_regions,
+            &loaded.sources,
+            &mut loaded.procedures,
+        );
+    }
+
+    if code_gen_options.arena_allocation {
+        roc_mono::arena_allocation::skip_recursive_decrements(arena, &mut loaded.procedures);
+    }
+
+    roc_mono::overflow::apply_overflow_mode(
+        arena,
+        &loaded.layout_interner,
+        &mut loaded.procedures,
+        code_gen_options.overflow,
+    );
+
+    // written next to the .wasm file, which is named after the app's output path
+    let wasm_source_map_path = if debug {
+        Some(
+            path.with_file_name(&*loaded.output_path)
+                .with_extension("wasm.map"),
+        )
+    } else {
+        None
+    };
+
+    // also named after the app's output path, with the extension depending on the stage
+    let llvm_ir_path = if code_gen_options.emit_llvm_ir {
+        Some(path.with_file_name(&*loaded.output_path))
+    } else {
+        None
+    };
+
+    // the program writes the profile wherever it is run from, so the path must be absolute
+    let profile_output = if code_gen_options.profile_generate {
+        let header = profile_header(&loaded.coverage_points, &loaded.interns);
+        let profile_path = std::env::current_dir().unwrap().join(&profile_path);
+
+        Some((header, profile_path))
+    } else {
+        None
+    };
+
     match code_gen_options.backend {
         CodeGenBackend::Wasm => gen_from_mono_module_dev(
             arena,
@@ -110,6 +308,9 @@ pub fn gen_from_mono_module<'a>(
             target,
             preprocessed_host_path,
             wasm_dev_stack_bytes,
+            wasm_dev_features,
+            code_gen_options.wasi_hooks,
+            wasm_source_map_path.as_deref(),
             AssemblyBackendMode::Binary, // dummy value, unused in practice
         ),
         CodeGenBackend::Assembly(backend_mode) => gen_from_mono_module_dev(
@@ -118,17 +319,34 @@ pub fn gen_from_mono_module<'a>(
             target,
             preprocessed_host_path,
             wasm_dev_stack_bytes,
+            wasm_dev_features,
+            code_gen_options.wasi_hooks,
+            wasm_source_map_path.as_deref(),
             backend_mode,
         ),
-        CodeGenBackend::Llvm(backend_mode) => {
-            gen_from_mono_module_llvm(arena, loaded, path, target, opt, backend_mode, debug)
-        }
+        CodeGenBackend::Llvm(backend_mode) => gen_from_mono_module_llvm(
+            arena,
+            loaded,
+            path,
+            target,
+            opt,
+            backend_mode,
+            debug,
+            code_gen_options.heap_profile,
+            code_gen_options.stack_limit_checks,
+            code_gen_options.arena_allocation,
+            code_gen_options.sanitizer_friendly,
+            llvm_ir_path.as_deref(),
+            profile_hints,
+            profile_output,
+        ),
     }
 }
 
 // TODO how should imported modules factor into this? What if those use builtins too?
 // TODO this should probably use more helper functions
 // TODO make this polymorphic in the llvm functions so it can be reused for another backend.
+#[allow(clippy::too_many_arguments)]
 fn gen_from_mono_module_llvm<'a>(
     arena: &'a bumpalo::Bump,
     mut loaded: MonomorphizedModule<'a>,
@@ -137,17 +355,26 @@ fn gen_from_mono_module_llvm<'a>(
     opt_level: OptLevel,
     backend_mode: LlvmBackendMode,
     emit_debug_info: bool,
+    heap_profile: bool,
+    stack_limit_checks: bool,
+    arena_allocation: bool,
+    sanitizer_friendly: bool,
+    llvm_ir_path: Option<&Path>,
+    profile_hints: Option<ProfileHints>,
+    profile_output: Option<(String, PathBuf)>,
 ) -> GenFromMono<'a> {
     use crate::target::{self, convert_opt_level};
     use inkwell::attributes::{Attribute, AttributeLoc};
     use inkwell::context::Context;
     use inkwell::module::Linkage;
     use inkwell::targets::{FileType, RelocMode};
+    use roc_mono::layout::LayoutInterner;
 
     let code_gen_start = Instant::now();
 
-    // Generate the binary
-    let target_info = roc_target::TargetInfo::from(target);
+    // Generate the binary. The layouts were made for this target info, which may differ
+    // from the triple's defaults, e.g. in whether pointers are tagged.
+    let target_info = loaded.layout_interner.target_info();
     let context = Context::create();
     let module = arena.alloc(module_from_builtins(target, &context, "app"));
 
@@ -190,6 +417,15 @@ fn gen_from_mono_module_llvm<'a>(
     let (dibuilder, compile_unit) = roc_gen_llvm::llvm::build::Env::new_debug_info(module);
     let (mpm, _fpm) = roc_gen_llvm::llvm::build::construct_optimization_passes(module, opt_level);
 
+    let procedures = roc_gen_llvm::llvm::build::expand_refcounting_procs(
+        arena,
+        target_info,
+        loaded.module_id,
+        &mut loaded.interns,
+        &mut loaded.layout_interner,
+        loaded.procedures,
+    );
+
     // Compile and add all the Procs before adding main
     let env = roc_gen_llvm::llvm::build::Env {
         arena,
@@ -208,6 +444,11 @@ fn gen_from_mono_module_llvm<'a>(
             .keys()
             .copied()
             .collect(),
+        heap_profile,
+        stack_limit_checks,
+        arena_allocation,
+        profile_hints,
+        func_spec_numbers: Default::default(),
     };
 
     // does not add any externs for this mode (we have a host) but cleans up some functions around
@@ -232,20 +473,43 @@ fn gen_from_mono_module_llvm<'a>(
         &env,
         &mut loaded.layout_interner,
         opt_level,
-        loaded.procedures,
+        procedures,
         entry_point,
         Some(&app_ll_file),
         &loaded.glue_layouts,
     );
 
+    // the counters are incremented by the calls that coverage added to the procs
+    if let Some((header, profile_path)) = profile_output {
+        let counters = loaded.coverage_points.len();
+
+        add_coverage_counters(&env, counters);
+        add_profile_writer(&env, &header, counters, &profile_path.to_string_lossy());
+    }
+
     env.dibuilder.finalize();
 
     // we don't use the debug info, and it causes weird errors.
     module.strip_debug_info();
 
+    if sanitizer_friendly {
+        // memory checkers use frame pointers to record where memory was allocated and freed
+        let frame_pointer = context.create_string_attribute("frame-pointer", "all");
+
+        for function in module.get_functions() {
+            function.add_attribute(AttributeLoc::Function, frame_pointer);
+        }
+    }
+
     // Uncomment this to see the module's optimized LLVM instruction output:
     // env.module.print_to_stderr();
 
+    if let Some(llvm_ir_path) = llvm_ir_path {
+        env.module
+            .print_to_file(llvm_ir_path.with_extension("unoptimized.ll"))
+            .unwrap();
+    }
+
     mpm.run_on(module);
 
     // Verify the module
@@ -260,6 +524,14 @@ fn gen_from_mono_module_llvm<'a>(
         );
     }
 
+    if let Some(llvm_ir_path) = llvm_ir_path {
+        env.module
+            .print_to_file(llvm_ir_path.with_extension("optimized.ll"))
+            .unwrap();
+        env.module
+            .write_bitcode_to_path(&llvm_ir_path.with_extension("bc"));
+    }
+
     // Uncomment this to see the module's optimized LLVM instruction output:
     // env.module.print_to_stderr();
 
@@ -459,6 +731,9 @@ fn gen_from_mono_module_dev<'a>(
     target: &target_lexicon::Triple,
     preprocessed_host_path: &Path,
     wasm_dev_stack_bytes: Option<u32>,
+    wasm_dev_features: WasmFeatures,
+    wasi_hooks: bool,
+    wasm_source_map_path: Option<&Path>,
     backend_mode: AssemblyBackendMode,
 ) -> GenFromMono<'a> {
     use target_lexicon::Architecture;
@@ -469,6 +744,9 @@ fn gen_from_mono_module_dev<'a>(
             loaded,
             preprocessed_host_path,
             wasm_dev_stack_bytes,
+            wasm_dev_features,
+            wasi_hooks,
+            wasm_source_map_path,
         ),
         Architecture::X86_64 | Architecture::Aarch64(_) => {
             gen_from_mono_module_dev_assembly(arena, loaded, target, backend_mode)
@@ -484,6 +762,9 @@ pub fn gen_from_mono_module_dev<'a>(
     target: &target_lexicon::Triple,
     _host_input_path: &Path,
     _wasm_dev_stack_bytes: Option<u32>,
+    _wasm_dev_features: WasmFeatures,
+    _wasi_hooks: bool,
+    _wasm_source_map_path: Option<&Path>,
     backend_mode: AssemblyBackendMode,
 ) -> GenFromMono<'a> {
     use target_lexicon::Architecture;
@@ -502,6 +783,9 @@ fn gen_from_mono_module_dev_wasm32<'a>(
     loaded: MonomorphizedModule<'a>,
     preprocessed_host_path: &Path,
     wasm_dev_stack_bytes: Option<u32>,
+    wasm_dev_features: WasmFeatures,
+    wasi_hooks: bool,
+    source_map_path: Option<&Path>,
 ) -> GenFromMono<'a> {
     let code_gen_start = Instant::now();
     let MonomorphizedModule {
@@ -509,6 +793,8 @@ fn gen_from_mono_module_dev_wasm32<'a>(
         procedures,
         mut interns,
         mut layout_interner,
+        body_regions,
+        sources,
         ..
     } = loaded;
 
@@ -524,6 +810,8 @@ fn gen_from_mono_module_dev_wasm32<'a>(
         module_id,
         exposed_to_host,
         stack_bytes: wasm_dev_stack_bytes.unwrap_or(roc_gen_wasm::Env::DEFAULT_STACK_BYTES),
+        features: wasm_dev_features,
+        wasi_hooks,
     };
 
     let host_bytes = std::fs::read(preprocessed_host_path).unwrap_or_else(|_| {
@@ -542,7 +830,7 @@ fn gen_from_mono_module_dev_wasm32<'a>(
         )
     });
 
-    let final_binary_bytes = roc_gen_wasm::build_app_binary(
+    let (mut final_binary_bytes, proc_offsets) = roc_gen_wasm::build_app_binary_with_offsets(
         &env,
         &mut layout_interner,
         &mut interns,
@@ -550,6 +838,22 @@ fn gen_from_mono_module_dev_wasm32<'a>(
         procedures,
     );
 
+    if let Some(source_map_path) = source_map_path {
+        let source_map =
+            crate::wasm_source_map::source_map_json(&proc_offsets, &body_regions, &sources);
+
+        std::fs::write(source_map_path, source_map).unwrap();
+
+        use roc_wasm_module::Serialize;
+
+        // devtools look for the source map at this URL, relative to the .wasm file
+        let source_map_url = source_map_path.file_name().unwrap().to_string_lossy();
+        let mut payload = Vec::new();
+        source_map_url.as_ref().serialize(&mut payload);
+
+        append_wasm_custom_section(&mut final_binary_bytes, "sourceMappingURL", &payload);
+    }
+
     let code_gen = code_gen_start.elapsed();
 
     (
@@ -610,6 +914,16 @@ fn gen_from_mono_module_dev_assembly<'a>(
     )
 }
 
+/// Custom sections may go anywhere in a Wasm module, including after all the others, so this works
+/// on modules we didn't serialize ourselves.
+fn append_wasm_custom_section(module_bytes: &mut Vec<u8>, name: &str, payload: &[u8]) {
+    use roc_wasm_module::sections::{update_section_size, write_custom_section_header};
+
+    let header_indices = write_custom_section_header(module_bytes, name);
+    module_bytes.extend_from_slice(payload);
+    update_section_size(module_bytes, header_indices);
+}
+
 fn report_timing(buf: &mut String, label: &str, duration: Duration) {
     use std::fmt::Write;
 
@@ -627,6 +941,9 @@ pub struct BuiltFile<'a> {
     pub problems: Problems,
     pub total_time: Duration,
     pub expect_metadata: ExpectMetadata<'a>,
+    /// The files the binary was built from, for [crate::build_cache]. Empty if the host was
+    /// rebuilt, because we don't know which files a host is built from.
+    pub inputs: Vec<PathBuf>,
 }
 
 pub enum BuildOrdering {
@@ -665,10 +982,11 @@ pub fn handle_error_module(
     total_time: std::time::Duration,
     filename: &OsStr,
     print_run_anyway_hint: bool,
+    severity_config: &SeverityConfig,
 ) -> std::io::Result<i32> {
     debug_assert!(module.total_problems() > 0);
 
-    let problems = report_problems_typechecked(&mut module);
+    let problems = report_problems_typechecked(&mut module, severity_config);
 
     problems.print_to_stdout(total_time);
 
@@ -717,9 +1035,15 @@ pub fn standard_load_config(
     LoadConfig {
         target_info,
         render: RenderTarget::ColorTerminal,
-        palette: DEFAULT_PALETTE,
+        palette: Palette::from_env(),
         threading,
         exec_mode,
+        max_specializations: None,
+        coverage: false,
+        fold_constants: false,
+        hoist_loop_invariants: false,
+        strip_expects: false,
+        cancelled: None,
     }
 }
 
@@ -730,48 +1054,65 @@ pub fn build_file<'a>(
     app_module_path: PathBuf,
     code_gen_options: CodeGenOptions,
     emit_timings: bool,
+    time_trace_path: Option<PathBuf>,
     link_type: LinkType,
     linking_strategy: LinkingStrategy,
     prebuilt_requested: bool,
     wasm_dev_stack_bytes: Option<u32>,
+    wasm_dev_features: WasmFeatures,
     roc_cache_dir: RocCacheDir<'_>,
     load_config: LoadConfig,
+    severity_config: &SeverityConfig,
 ) -> Result<BuiltFile<'a>, BuildFileError<'a>> {
     let compilation_start = Instant::now();
+    let render = load_config.render;
+    let palette = load_config.palette;
 
     // Step 1: compile the app and generate the .o file
     let loaded =
         roc_load::load_and_monomorphize(arena, app_module_path.clone(), roc_cache_dir, load_config)
             .map_err(|e| BuildFileError::from_mono_error(e, compilation_start))?;
 
-    build_loaded_file(
+    let built = build_loaded_file(
         arena,
         target,
         app_module_path,
         code_gen_options,
         emit_timings,
+        time_trace_path,
         link_type,
         linking_strategy,
         prebuilt_requested,
         wasm_dev_stack_bytes,
+        wasm_dev_features,
         loaded,
         compilation_start,
-    )
+        severity_config,
+        render,
+    )?;
+
+    built.problems.print_reports_to_stdout(&palette);
+
+    Ok(built)
 }
 
 #[allow(clippy::too_many_arguments)]
-fn build_loaded_file<'a>(
+pub fn build_loaded_file<'a>(
     arena: &'a Bump,
     target: &Triple,
     app_module_path: PathBuf,
     code_gen_options: CodeGenOptions,
     emit_timings: bool,
+    time_trace_path: Option<PathBuf>,
     link_type: LinkType,
     linking_strategy: LinkingStrategy,
     prebuilt_requested: bool,
     wasm_dev_stack_bytes: Option<u32>,
+    wasm_dev_features: WasmFeatures,
     loaded: roc_load::MonomorphizedModule<'a>,
     compilation_start: Instant,
+    severity_config: &SeverityConfig,
+    render: RenderTarget,
 ) -> Result<BuiltFile<'a>, BuildFileError<'a>> {
     let operating_system = roc_target::OperatingSystem::from(target.operating_system);
 
@@ -805,7 +1146,10 @@ fn build_loaded_file<'a>(
     }
 
     // We don't need to spawn a rebuild thread when using a prebuilt host.
-    let rebuild_thread = if matches!(link_type, LinkType::Dylib | LinkType::None) {
+    let rebuild_thread = if matches!(
+        link_type,
+        LinkType::Dylib | LinkType::None | LinkType::StaticLib
+    ) {
         None
     } else if is_platform_prebuilt {
         if !preprocessed_host_path.exists() {
@@ -846,25 +1190,29 @@ fn build_loaded_file<'a>(
     };
 
     let buf = &mut String::with_capacity(1024);
+    let mut build_timings = BuildTimings::new(compilation_start);
 
     let mut it = loaded.timings.iter().peekable();
     while let Some((module_id, module_timing)) = it.next() {
         let module_name = loaded.interns.module_name(*module_id);
-
-        buf.push_str("    ");
-
-        if module_name.is_empty() {
+        let row_name = if module_name.is_empty() {
             // the App module
-            buf.push_str("Application Module");
+            "Application Module"
         } else {
-            buf.push_str(module_name);
-        }
+            module_name
+        };
 
+        buf.push_str("    ");
+        buf.push_str(row_name);
         buf.push('\n');
 
         use std::fmt::Write;
         write!(buf, "{}", module_timing).unwrap();
 
+        for span in module_timing.spans.iter() {
+            build_timings.add(row_name, *span);
+        }
+
         if it.peek().is_some() {
             buf.push('\n');
         }
@@ -873,7 +1221,7 @@ fn build_loaded_file<'a>(
     // This only needs to be mutable for report_problems. This can't be done
     // inside a nested scope without causing a borrow error!
     let mut loaded = loaded;
-    let problems = report_problems_monomorphized(&mut loaded);
+    let problems = render_problems_monomorphized(&mut loaded, severity_config, render);
     let loaded = loaded;
 
     enum HostRebuildTiming {
@@ -902,6 +1250,63 @@ fn build_loaded_file<'a>(
         None
     };
 
+    if code_gen_options.emit_host_layouts {
+        let host_layouts_path = output_exe_path.with_extension("host-layouts.json");
+        let json = crate::host_layouts::host_layouts_json(&loaded);
+
+        std::fs::write(host_layouts_path, json).unwrap();
+    }
+
+    // libraries are called from C, so they always come with a header
+    if code_gen_options.emit_c_header || matches!(link_type, LinkType::Dylib | LinkType::StaticLib)
+    {
+        let header = crate::c_header::c_header(&loaded);
+
+        std::fs::write(output_exe_path.with_extension("h"), header).unwrap();
+    }
+
+    if code_gen_options.emit_call_graph {
+        let json = crate::call_graph::call_graph_json(&loaded);
+        let dot = crate::call_graph::call_graph_dot(&loaded);
+
+        std::fs::write(output_exe_path.with_extension("call-graph.json"), json).unwrap();
+        std::fs::write(output_exe_path.with_extension("call-graph.dot"), dot).unwrap();
+    }
+
+    let inputs = if is_platform_prebuilt && link_type == LinkType::Executable {
+        // the builtins are part of the compiler, and have no file
+        let mut inputs: Vec<PathBuf> = loaded
+            .sources
+            .values()
+            .map(|(path, _)| path.clone())
+            .filter(|path| path.is_file())
+            .collect();
+
+        inputs.push(preprocessed_host_path.clone());
+
+        if linking_strategy == LinkingStrategy::Surgical {
+            inputs.push(platform_main_roc.with_file_name(roc_linker::metadata_file_name(target)));
+        }
+
+        inputs.sort();
+        inputs
+    } else {
+        Vec::new()
+    };
+
+    // code gen consumes the module, so describe what the app exposes now
+    let wasm_metadata = if matches!(operating_system, roc_target::OperatingSystem::Wasi)
+        && matches!(link_type, LinkType::Executable)
+    {
+        Some(crate::host_layouts::wasm_metadata_json(
+            &loaded,
+            VERSION.trim(),
+        ))
+    } else {
+        None
+    };
+
+    let code_gen_start = Instant::now();
     let (roc_app_bytes, code_gen_timing, expect_metadata) = gen_from_mono_module(
         arena,
         loaded,
@@ -910,6 +1315,7 @@ fn build_loaded_file<'a>(
         code_gen_options,
         &preprocessed_host_path,
         wasm_dev_stack_bytes,
+        wasm_dev_features,
     );
 
     buf.push('\n');
@@ -922,6 +1328,7 @@ fn build_loaded_file<'a>(
         "Generate Assembly from Mono IR",
         code_gen_timing.code_gen,
     );
+    build_timings.add_phase("Code Generation", "Code Generation", code_gen_start);
 
     let compilation_end = compilation_start.elapsed();
     let size = roc_app_bytes.len();
@@ -990,7 +1397,10 @@ fn build_loaded_file<'a>(
 
             let mut inputs = vec![app_o_file.to_str().unwrap()];
 
-            if !matches!(link_type, LinkType::Dylib | LinkType::None) {
+            if !matches!(
+                link_type,
+                LinkType::Dylib | LinkType::None | LinkType::StaticLib
+            ) {
                 // the host has been compiled into a .o or .obj file
                 inputs.push(preprocessed_host_path.as_path().to_str().unwrap());
             }
@@ -999,8 +1409,9 @@ fn build_loaded_file<'a>(
                 inputs.push(builtins_host_tempfile.path().to_str().unwrap());
             }
 
-            let (mut child, _) = link(target, output_exe_path.clone(), &inputs, link_type)
-                .map_err(|_| todo!("gracefully handle `ld` failing to spawn."))?;
+            let (mut child, linked_path) =
+                link(target, output_exe_path.clone(), &inputs, link_type)
+                    .map_err(|_| todo!("gracefully handle `ld` failing to spawn."))?;
 
             let exit_status = child
                 .wait()
@@ -1016,13 +1427,45 @@ fn build_loaded_file<'a>(
                     exit_status.code()
                 );
             }
+
+            // a static library gets the extension the host's linker expects
+            if link_type == LinkType::StaticLib {
+                output_exe_path = linked_path;
+            }
         }
     }
 
+    if let Some(metadata) = wasm_metadata {
+        let mut module_bytes = std::fs::read(&output_exe_path).unwrap();
+        append_wasm_custom_section(
+            &mut module_bytes,
+            crate::host_layouts::WASM_METADATA_SECTION,
+            metadata.as_bytes(),
+        );
+        std::fs::write(&output_exe_path, module_bytes).unwrap();
+    }
+
     let linking_time = link_start.elapsed();
+    build_timings.add_phase("Link", "Link", link_start);
 
     if emit_timings {
         println!("Finished linking in {} ms\n", linking_time.as_millis());
+
+        println!(
+            "Here's how long each phase took, summed over all modules:\n\n{}",
+            build_timings.summary()
+        );
+    }
+
+    if let Some(path) = time_trace_path {
+        match build_timings.write_trace(&path) {
+            Ok(()) => println!("Wrote a timing trace to {}\n", path.display()),
+            Err(err) => eprintln!(
+                "Failed to write a timing trace to {}: {}",
+                path.display(),
+                err
+            ),
+        }
     }
 
     let total_time = compilation_start.elapsed();
@@ -1032,6 +1475,7 @@ fn build_loaded_file<'a>(
         problems,
         total_time,
         expect_metadata,
+        inputs,
     })
 }
 
@@ -1164,6 +1608,29 @@ fn build_and_preprocess_host_lowlevel(
     )
 }
 
+/// Settings for loading modules only to typecheck them, like `roc check` does.
+pub fn check_load_config(
+    render: RenderTarget,
+    palette: Palette,
+    threading: Threading,
+) -> LoadConfig {
+    LoadConfig {
+        // only used for generating errors. We don't do code generation, so hardcoding should be fine
+        // we need monomorphization for when exhaustiveness checking
+        target_info: TargetInfo::default_x86_64(),
+        render,
+        palette,
+        threading,
+        exec_mode: ExecutionMode::Check,
+        max_specializations: None,
+        coverage: false,
+        fold_constants: false,
+        hoist_loop_invariants: false,
+        strip_expects: false,
+        cancelled: None,
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn check_file<'a>(
     arena: &'a Bump,
@@ -1171,23 +1638,15 @@ pub fn check_file<'a>(
     emit_timings: bool,
     roc_cache_dir: RocCacheDir<'_>,
     threading: Threading,
+    severity_config: &SeverityConfig,
 ) -> Result<(Problems, Duration), LoadingProblem<'a>> {
     let compilation_start = Instant::now();
 
-    // only used for generating errors. We don't do code generation, so hardcoding should be fine
-    // we need monomorphization for when exhaustiveness checking
-    let target_info = TargetInfo::default_x86_64();
-
     // Step 1: compile the app and generate the .o file
 
-    let load_config = LoadConfig {
-        target_info,
-        // TODO: expose this from CLI?
-        render: RenderTarget::ColorTerminal,
-        palette: DEFAULT_PALETTE,
-        threading,
-        exec_mode: ExecutionMode::Check,
-    };
+    // TODO: expose the render target from CLI?
+    let load_config =
+        check_load_config(RenderTarget::ColorTerminal, Palette::from_env(), threading);
     let mut loaded =
         roc_load::load_and_typecheck(arena, roc_file_path, roc_cache_dir, load_config)?;
 
@@ -1234,7 +1693,10 @@ pub fn check_file<'a>(
         println!("Finished checking in {} ms\n", compilation_end.as_millis(),);
     }
 
-    Ok((report_problems_typechecked(&mut loaded), compilation_end))
+    Ok((
+        report_problems_typechecked(&mut loaded, severity_config),
+        compilation_end,
+    ))
 }
 
 pub fn build_str_test<'a>(
@@ -1249,12 +1711,27 @@ pub fn build_str_test<'a>(
         backend: CodeGenBackend::Llvm(LlvmBackendMode::Binary),
         opt_level: OptLevel::Normal,
         emit_debug_info: false,
+        trace_calls: false,
+        arena_allocation: false,
+        overflow: OverflowMode::Panic,
+        heap_profile: false,
+        stack_traces: false,
+        stack_limit_checks: false,
+        sanitizer_friendly: false,
+        emit_host_layouts: false,
+        emit_call_graph: false,
+        emit_c_header: false,
+        emit_llvm_ir: false,
+        profile_generate: false,
+        profile_use: false,
+        wasi_hooks: false,
     };
 
     let emit_timings = false;
     let link_type = LinkType::Executable;
     let linking_strategy = LinkingStrategy::Surgical;
     let wasm_dev_stack_bytes = None;
+    let wasm_dev_features = WasmFeatures::default();
 
     let roc_cache_dir = roc_packaging::cache::RocCacheDir::Disallowed;
     let build_ordering = BuildOrdering::AlwaysBuild;
@@ -1275,17 +1752,25 @@ pub fn build_str_test<'a>(
     )
     .map_err(|e| BuildFileError::from_mono_error(e, compilation_start))?;
 
-    build_loaded_file(
+    let built = build_loaded_file(
         arena,
         &triple,
         app_module_path.to_path_buf(),
         code_gen_options,
         emit_timings,
+        None,
         link_type,
         linking_strategy,
         assume_prebuild,
         wasm_dev_stack_bytes,
+        wasm_dev_features,
         loaded,
         compilation_start,
-    )
+        &SeverityConfig::default(),
+        RenderTarget::ColorTerminal,
+    )?;
+
+    built.problems.print_reports_to_stdout(&Palette::from_env());
+
+    Ok(built)
 }
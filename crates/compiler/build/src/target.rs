@@ -93,6 +93,11 @@ pub fn target_zig_str(target: &Triple) -> &'static str {
             operating_system: OperatingSystem::Darwin,
             ..
         } => "aarch64-apple-darwin",
+        Triple {
+            architecture: Architecture::X86_64,
+            operating_system: OperatingSystem::Windows,
+            ..
+        } => "x86_64-windows-gnu",
         _ => internal_error!("TODO gracefully handle unsupported target: {:?}", target),
     }
 }
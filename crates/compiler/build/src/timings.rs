@@ -0,0 +1,120 @@
+//! Collects the phase timings of a build, so `--time` can print a summary of where the time
+//! went and `--time-trace` can write them out in the Chrome trace-event format.
+//!
+//! The trace can be opened in `chrome://tracing` or <https://ui.perfetto.dev>.
+//! Each module gets its own row, so phases that ran in parallel on different threads show up
+//! side by side.
+use roc_load::TimingSpan;
+use std::fmt::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct Event {
+    /// Index into `BuildTimings::rows`
+    row: usize,
+    span: TimingSpan,
+}
+
+#[derive(Debug)]
+pub struct BuildTimings {
+    origin: Instant,
+    rows: Vec<String>,
+    events: Vec<Event>,
+}
+
+impl BuildTimings {
+    pub fn new(origin: Instant) -> Self {
+        BuildTimings {
+            origin,
+            rows: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, row_name: &str, span: TimingSpan) {
+        let row = match self.rows.iter().position(|name| name == row_name) {
+            Some(row) => row,
+            None => {
+                self.rows.push(row_name.to_string());
+                self.rows.len() - 1
+            }
+        };
+
+        self.events.push(Event { row, span });
+    }
+
+    pub fn add_phase(&mut self, row_name: &str, phase: &'static str, start: Instant) {
+        let duration = start.elapsed();
+
+        self.add(
+            row_name,
+            TimingSpan {
+                phase,
+                start,
+                duration,
+            },
+        );
+    }
+
+    /// Total time spent in each phase, summed over all modules, in the order the phases first ran.
+    pub fn phase_totals(&self) -> Vec<(&'static str, Duration)> {
+        let mut totals: Vec<(&'static str, Duration)> = Vec::new();
+
+        for Event { span, .. } in self.events.iter() {
+            match totals.iter_mut().find(|(phase, _)| *phase == span.phase) {
+                Some((_, total)) => *total += span.duration,
+                None => totals.push((span.phase, span.duration)),
+            }
+        }
+
+        totals
+    }
+
+    pub fn summary(&self) -> String {
+        let mut buf = String::new();
+
+        for (phase, total) in self.phase_totals() {
+            writeln!(
+                buf,
+                "        {:9.3} ms   {}",
+                total.as_secs_f64() * 1000.0,
+                phase
+            )
+            .unwrap();
+        }
+
+        buf
+    }
+
+    /// Render the timings as a Chrome trace-event JSON array.
+    /// Timestamps and durations are in microseconds, relative to the start of the build.
+    pub fn to_trace_json(&self) -> String {
+        let thread_names = self.rows.iter().enumerate().map(|(tid, name)| {
+            format!(
+                r#"{{"name": "thread_name", "ph": "M", "pid": 0, "tid": {}, "args": {{"name": {:?}}}}}"#,
+                tid, name
+            )
+        });
+
+        let events = self.events.iter().map(|Event { row, span }| {
+            format!(
+                r#"{{"name": {:?}, "ph": "X", "pid": 0, "tid": {}, "ts": {}, "dur": {}}}"#,
+                span.phase,
+                row,
+                span.start
+                    .saturating_duration_since(self.origin)
+                    .as_micros(),
+                span.duration.as_micros(),
+            )
+        });
+
+        let entries: Vec<String> = thread_names.chain(events).collect();
+
+        format!("[\n  {}\n]\n", entries.join(",\n  "))
+    }
+
+    pub fn write_trace(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, self.to_trace_json())
+    }
+}
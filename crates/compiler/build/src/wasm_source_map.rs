@@ -0,0 +1,101 @@
+//! Source maps for the Wasm dev backend, so browser devtools can show the Roc source of the
+//! function being stepped through.
+//!
+//! The output follows version 3 of the source map format. For Wasm, all generated code is on
+//! line 0, and the column is the byte offset into the module.
+use roc_collections::all::MutMap;
+use roc_module::symbol::{ModuleId, Symbol};
+use roc_region::all::{LineInfo, Region};
+use serde_json::json;
+use std::path::PathBuf;
+
+/// Map the start of each Roc proc's code to the start of its body in the Roc source.
+///
+/// Procs without a known body region (generated helpers and wrappers) are left out, so stepping
+/// into them keeps showing the last Roc function.
+pub fn source_map_json(
+    proc_offsets: &[(Symbol, u32)],
+    body_regions: &MutMap<Symbol, Region>,
+    sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+) -> String {
+    let mut proc_offsets = proc_offsets.to_vec();
+    proc_offsets.sort_by_key(|(_, offset)| *offset);
+
+    let mut source_paths = Vec::new();
+    let mut source_contents = Vec::new();
+    let mut source_indices: MutMap<ModuleId, (i64, LineInfo)> = MutMap::default();
+
+    let mut mappings = String::new();
+    let mut previous = [0i64; 4];
+
+    for (symbol, offset) in proc_offsets {
+        let module_id = symbol.module_id();
+        let (region, (path, src)) = match (body_regions.get(&symbol), sources.get(&module_id)) {
+            (Some(region), Some(source)) => (region, source),
+            _ => continue,
+        };
+
+        let (source_index, line_info) = source_indices.entry(module_id).or_insert_with(|| {
+            source_paths.push(path.to_string_lossy().into_owned());
+            source_contents.push(src.to_string());
+
+            (source_paths.len() as i64 - 1, LineInfo::new(src))
+        });
+
+        let start = line_info.convert_pos(region.start());
+        let segment = [
+            offset as i64,
+            *source_index,
+            start.line as i64,
+            start.column as i64,
+        ];
+
+        if !mappings.is_empty() {
+            mappings.push(',');
+        }
+
+        // each field is relative to the same field of the previous segment
+        for (field, previous_field) in segment.iter().zip(previous.iter()) {
+            push_vlq(&mut mappings, field - previous_field);
+        }
+
+        previous = segment;
+    }
+
+    let source_map = json!({
+        "version": 3,
+        "sources": source_paths,
+        "sourcesContent": source_contents,
+        "names": [],
+        "mappings": mappings,
+    });
+
+    source_map.to_string()
+}
+
+/// Append a base64 variable-length quantity, least significant 5 bits first, with the sign in
+/// the lowest bit.
+fn push_vlq(buffer: &mut String, value: i64) {
+    const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut vlq = if value < 0 {
+        ((-value) << 1) | 1
+    } else {
+        value << 1
+    };
+
+    loop {
+        let mut digit = vlq & 0b1_1111;
+        vlq >>= 5;
+
+        if vlq > 0 {
+            digit |= 0b10_0000;
+        }
+
+        buffer.push(BASE64[digit as usize] as char);
+
+        if vlq == 0 {
+            break;
+        }
+    }
+}
@@ -1,7 +1,9 @@
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use roc_command_utils::{pretty_command_string, zig};
 use roc_error_macros::internal_error;
 use std::fs;
-use std::io;
+use std::io::{self, Write};
 use std::path::Path;
 use std::str;
 use std::{env, path::PathBuf, process::Command};
@@ -35,6 +37,9 @@ fn main() {
 
     generate_bc_file(&bitcode_path, "ir-i386", "builtins-i386");
     generate_bc_file(&bitcode_path, "ir-x86_64", "builtins-x86_64");
+    generate_bc_file(&bitcode_path, "ir-aarch64", "builtins-aarch64");
+    generate_bc_file(&bitcode_path, "ir-macos-x86_64", "builtins-macos-x86_64");
+    generate_bc_file(&bitcode_path, "ir-macos-aarch64", "builtins-macos-aarch64");
     generate_bc_file(
         &bitcode_path,
         "ir-windows-x86_64",
@@ -79,6 +84,26 @@ fn generate_bc_file(bitcode_path: &Path, zig_object: &str, file_name: &str) {
         .args(["build", zig_object, "-Drelease=true"]);
 
     run_command(zig_cmd, 0);
+
+    compress_bc_file(&bc_path, file_name);
+}
+
+/// The compiler embeds the bitcode gzipped, and only decompresses the targets it needs.
+fn compress_bc_file(bc_path: &Path, file_name: &str) {
+    let bitcode = fs::read(bc_path)
+        .unwrap_or_else(|err| internal_error!("Failed to read {}: {}", bc_path.display(), err));
+
+    let gz_path = get_lib_dir().join(format!("{}.bc.gz", file_name));
+    println!("Compressing bitcode to: {}", gz_path.display());
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    let gzipped = encoder
+        .write_all(&bitcode)
+        .and_then(|()| encoder.finish())
+        .unwrap_or_else(|err| internal_error!("Failed to compress {}: {}", bc_path.display(), err));
+
+    fs::write(&gz_path, gzipped)
+        .unwrap_or_else(|err| internal_error!("Failed to write {}: {}", gz_path.display(), err));
 }
 
 pub fn get_lib_dir() -> PathBuf {
@@ -1 +1,132 @@
+//! The Zig builtins, compiled to LLVM bitcode for each target we can build them for.
+//!
+//! The bitcode is embedded gzipped, and a target's bitcode is only decompressed the first time
+//! it's asked for. Commands that never generate LLVM IR, like `roc check`, never pay for it, and
+//! building for one target doesn't decompress the others.
 
+use flate2::read::GzDecoder;
+use std::io::Read;
+use std::sync::Mutex;
+use target_lexicon::{Aarch64Architecture, Architecture, OperatingSystem, Triple};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BitcodeTarget {
+    Host,
+    Wasm32,
+    I386,
+    X86_64,
+    Aarch64,
+    MacosX86_64,
+    MacosAarch64,
+    WindowsX86_64,
+}
+
+impl BitcodeTarget {
+    fn from_triple(target: &Triple) -> Option<Self> {
+        if target == &Triple::host() {
+            return Some(BitcodeTarget::Host);
+        }
+
+        match target {
+            Triple {
+                architecture: Architecture::Wasm32,
+                ..
+            } => Some(BitcodeTarget::Wasm32),
+            Triple {
+                architecture: Architecture::X86_32(_),
+                operating_system: OperatingSystem::Linux,
+                ..
+            } => Some(BitcodeTarget::I386),
+            Triple {
+                architecture: Architecture::X86_64,
+                operating_system: OperatingSystem::Linux,
+                ..
+            } => Some(BitcodeTarget::X86_64),
+            Triple {
+                architecture: Architecture::Aarch64(Aarch64Architecture::Aarch64),
+                operating_system: OperatingSystem::Linux,
+                ..
+            } => Some(BitcodeTarget::Aarch64),
+            Triple {
+                architecture: Architecture::X86_64,
+                operating_system: OperatingSystem::Darwin | OperatingSystem::MacOSX { .. },
+                ..
+            } => Some(BitcodeTarget::MacosX86_64),
+            Triple {
+                architecture: Architecture::Aarch64(Aarch64Architecture::Aarch64),
+                operating_system: OperatingSystem::Darwin | OperatingSystem::MacOSX { .. },
+                ..
+            } => Some(BitcodeTarget::MacosAarch64),
+            Triple {
+                architecture: Architecture::X86_64,
+                operating_system: OperatingSystem::Windows,
+                ..
+            } => Some(BitcodeTarget::WindowsX86_64),
+            _ => None,
+        }
+    }
+
+    fn gzipped(self) -> &'static [u8] {
+        match self {
+            BitcodeTarget::Host => {
+                include_bytes!(concat!(env!("OUT_DIR"), "/builtins-host.bc.gz"))
+            }
+            BitcodeTarget::Wasm32 => {
+                include_bytes!(concat!(env!("OUT_DIR"), "/builtins-wasm32.bc.gz"))
+            }
+            BitcodeTarget::I386 => {
+                include_bytes!(concat!(env!("OUT_DIR"), "/builtins-i386.bc.gz"))
+            }
+            BitcodeTarget::X86_64 => {
+                include_bytes!(concat!(env!("OUT_DIR"), "/builtins-x86_64.bc.gz"))
+            }
+            BitcodeTarget::Aarch64 => {
+                include_bytes!(concat!(env!("OUT_DIR"), "/builtins-aarch64.bc.gz"))
+            }
+            BitcodeTarget::MacosX86_64 => {
+                include_bytes!(concat!(env!("OUT_DIR"), "/builtins-macos-x86_64.bc.gz"))
+            }
+            BitcodeTarget::MacosAarch64 => {
+                include_bytes!(concat!(env!("OUT_DIR"), "/builtins-macos-aarch64.bc.gz"))
+            }
+            BitcodeTarget::WindowsX86_64 => {
+                include_bytes!(concat!(env!("OUT_DIR"), "/builtins-windows-x86_64.bc.gz"))
+            }
+        }
+    }
+}
+
+/// The bitcode of every target that's been asked for so far.
+///
+/// It's leaked on purpose: LLVM borrows it rather than copying it, for as long as the modules
+/// parsed from it are alive, and in the REPL that's the rest of the process anyway.
+static DECOMPRESSED: Mutex<Vec<(BitcodeTarget, &'static [u8])>> = Mutex::new(Vec::new());
+
+/// The builtins bitcode for `target`, or `None` if the builtins aren't built for that target.
+///
+/// It's decompressed on the first call for each target, and the same bytes are returned after that.
+pub fn builtins_bitcode(target: &Triple) -> Option<&'static [u8]> {
+    let target = BitcodeTarget::from_triple(target)?;
+    let mut decompressed = DECOMPRESSED.lock().unwrap();
+
+    if let Some((_, bitcode)) = decompressed.iter().find(|(cached, _)| *cached == target) {
+        return Some(bitcode);
+    }
+
+    let mut bitcode = Vec::new();
+
+    GzDecoder::new(target.gzipped())
+        .read_to_end(&mut bitcode)
+        .unwrap_or_else(|err| {
+            panic!(
+                "Unable to decompress the builtins bitcode for {:?}: {}",
+                target, err
+            )
+        });
+
+    let bitcode: &'static [u8] = Vec::leak(bitcode);
+
+    decompressed.push((target, bitcode));
+
+    Some(bitcode)
+}
@@ -30,6 +30,9 @@ pub fn build(b: *Builder) void {
     });
     const linux32_target = makeLinux32Target();
     const linux64_target = makeLinux64Target();
+    const linux_aarch64_target = makeLinuxAarch64Target();
+    const macos64_target = makeMacos64Target();
+    const macos_aarch64_target = makeMacosAarch64Target();
     const windows64_target = makeWindows64Target();
     const wasm32_target = makeWasm32Target();
 
@@ -37,6 +40,9 @@ pub fn build(b: *Builder) void {
     generateLlvmIrFile(b, mode, host_target, main_path, "ir", "builtins-host");
     generateLlvmIrFile(b, mode, linux32_target, main_path, "ir-i386", "builtins-i386");
     generateLlvmIrFile(b, mode, linux64_target, main_path, "ir-x86_64", "builtins-x86_64");
+    generateLlvmIrFile(b, mode, linux_aarch64_target, main_path, "ir-aarch64", "builtins-aarch64");
+    generateLlvmIrFile(b, mode, macos64_target, main_path, "ir-macos-x86_64", "builtins-macos-x86_64");
+    generateLlvmIrFile(b, mode, macos_aarch64_target, main_path, "ir-macos-aarch64", "builtins-macos-aarch64");
     generateLlvmIrFile(b, mode, windows64_target, main_path, "ir-windows-x86_64", "builtins-windows-x86_64");
     generateLlvmIrFile(b, mode, wasm32_target, main_path, "ir-wasm32", "builtins-wasm32");
 
@@ -113,6 +119,36 @@ fn makeLinux64Target() CrossTarget {
     return target;
 }
 
+fn makeLinuxAarch64Target() CrossTarget {
+    var target = CrossTarget.parse(.{}) catch unreachable;
+
+    target.cpu_arch = std.Target.Cpu.Arch.aarch64;
+    target.os_tag = std.Target.Os.Tag.linux;
+    target.abi = std.Target.Abi.musl;
+
+    return target;
+}
+
+fn makeMacos64Target() CrossTarget {
+    var target = CrossTarget.parse(.{}) catch unreachable;
+
+    target.cpu_arch = std.Target.Cpu.Arch.x86_64;
+    target.os_tag = std.Target.Os.Tag.macos;
+    target.abi = std.Target.Abi.none;
+
+    return target;
+}
+
+fn makeMacosAarch64Target() CrossTarget {
+    var target = CrossTarget.parse(.{}) catch unreachable;
+
+    target.cpu_arch = std.Target.Cpu.Arch.aarch64;
+    target.os_tag = std.Target.Os.Tag.macos;
+    target.abi = std.Target.Abi.none;
+
+    return target;
+}
+
 fn makeWindows64Target() CrossTarget {
     var target = CrossTarget.parse(.{}) catch unreachable;
 
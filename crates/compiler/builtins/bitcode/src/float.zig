@@ -0,0 +1,851 @@
+const std = @import("std");
+const math = std.math;
+
+// Formatting and parsing of F32 and F64.
+//
+// `format` prints the shortest decimal that parses back to exactly the same float, and `parse`
+// rounds correctly no matter how many digits it is given, so the two round-trip.
+
+fn FloatInfo(comptime T: type) type {
+    return struct {
+        const Bits = std.meta.Int(.unsigned, @bitSizeOf(T));
+
+        const mantissa_bits: u6 = switch (T) {
+            f32 => 23,
+            f64 => 52,
+            else => @compileError("only f32 and f64 are supported"),
+        };
+        const exponent_bits: u6 = switch (T) {
+            f32 => 8,
+            f64 => 11,
+            else => @compileError("only f32 and f64 are supported"),
+        };
+        const bias: i32 = (1 << (exponent_bits - 1)) - 1;
+        const infinite_power: i32 = (1 << exponent_bits) - 1;
+    };
+}
+
+// -------- Formatting
+
+/// Enough for any F64 written out without an exponent, e.g. the smallest subnormal takes
+/// 323 zeroes after the decimal point before its one significant digit.
+pub const max_format_len = 400;
+
+/// Write `value` to `buf` as the shortest decimal that round-trips, and return its length.
+///
+/// The digits are the ones Ryu (https://github.com/ulfjack/ryu) picks. They are written out in
+/// full without an exponent, so `1e21` becomes `1000000000000000000000` and a whole number has
+/// no decimal point. Non-finite values are written as `NaN`, `∞` and `-∞`.
+pub fn format(comptime T: type, value: T, buf: *[max_format_len]u8) usize {
+    const info = FloatInfo(T);
+
+    const bits = @bitCast(info.Bits, value);
+    const sign = (bits >> (@bitSizeOf(T) - 1)) != 0;
+    const ieee_mantissa = @as(u64, bits & ((@as(info.Bits, 1) << info.mantissa_bits) - 1));
+    const ieee_exponent = @intCast(i32, (bits >> info.mantissa_bits) & ((1 << info.exponent_bits) - 1));
+
+    if (ieee_exponent == info.infinite_power) {
+        const special = if (ieee_mantissa != 0) "NaN" else if (sign) "-∞" else "∞";
+        std.mem.copy(u8, buf, special);
+
+        return special.len;
+    }
+
+    var len: usize = 0;
+    if (sign) {
+        buf[0] = '-';
+        len = 1;
+    }
+
+    if (ieee_exponent == 0 and ieee_mantissa == 0) {
+        buf[len] = '0';
+
+        return len + 1;
+    }
+
+    const decimal = shortestDecimal(T, ieee_mantissa, ieee_exponent);
+
+    return len + writeDecimal(decimal, buf[len..]);
+}
+
+const ShortestDecimal = struct {
+    mantissa: u64,
+    exponent: i32,
+};
+
+fn writeDecimal(decimal: ShortestDecimal, buf: []u8) usize {
+    var mantissa = decimal.mantissa;
+    var exponent = decimal.exponent;
+    while (mantissa % 10 == 0) {
+        mantissa /= 10;
+        exponent += 1;
+    }
+
+    var digits_buf: [20]u8 = undefined;
+    const num_digits = std.fmt.formatIntBuf(&digits_buf, mantissa, 10, .lower, .{});
+    const digits = digits_buf[0..num_digits];
+
+    var len: usize = 0;
+    if (exponent >= 0) {
+        // 1234000
+        std.mem.copy(u8, buf, digits);
+        len = num_digits;
+
+        std.mem.set(u8, buf[len .. len + @intCast(usize, exponent)], '0');
+        len += @intCast(usize, exponent);
+    } else if (@intCast(i32, num_digits) + exponent > 0) {
+        // 12.34
+        const point = @intCast(usize, @intCast(i32, num_digits) + exponent);
+        std.mem.copy(u8, buf, digits[0..point]);
+        buf[point] = '.';
+        std.mem.copy(u8, buf[point + 1 ..], digits[point..]);
+        len = num_digits + 1;
+    } else {
+        // 0.001234
+        const leading_zeros = @intCast(usize, -(@intCast(i32, num_digits) + exponent));
+        buf[0] = '0';
+        buf[1] = '.';
+        std.mem.set(u8, buf[2 .. 2 + leading_zeros], '0');
+        len = 2 + leading_zeros;
+
+        std.mem.copy(u8, buf[len..], digits);
+        len += num_digits;
+    }
+
+    return len;
+}
+
+// F32 reuses the F64 tables; they are more precise than it needs, which does no harm.
+const pow5_inv_bitcount = 125;
+const pow5_bitcount = 125;
+
+const pow5_inv_split = pow5InvSplitTable(292);
+const pow5_split = pow5SplitTable(326);
+
+fn bitLength(x: u1024) u32 {
+    return 1024 - @as(u32, @clz(u1024, x));
+}
+
+// pow5_split[i] is 5^i, shifted to be exactly pow5_bitcount bits long
+fn pow5SplitTable(comptime len: usize) [len]u128 {
+    @setEvalBranchQuota(100_000);
+
+    var table: [len]u128 = undefined;
+    var pow: u1024 = 1;
+    for (table) |*entry| {
+        const pow_len = bitLength(pow);
+        if (pow_len > pow5_bitcount) {
+            entry.* = @intCast(u128, pow >> @intCast(u10, pow_len - pow5_bitcount));
+        } else {
+            entry.* = @intCast(u128, pow << @intCast(u10, pow5_bitcount - pow_len));
+        }
+
+        pow *= 5;
+    }
+
+    return table;
+}
+
+// pow5_inv_split[i] is 2^k / 5^i rounded up, with k chosen to make it pow5_inv_bitcount bits long
+fn pow5InvSplitTable(comptime len: usize) [len]u128 {
+    @setEvalBranchQuota(100_000);
+
+    var table: [len]u128 = undefined;
+    var pow: u1024 = 1;
+    for (table) |*entry| {
+        const shift = bitLength(pow) - 1 + pow5_inv_bitcount;
+        entry.* = @intCast(u128, (@as(u1024, 1) << @intCast(u10, shift)) / pow + 1);
+
+        pow *= 5;
+    }
+
+    return table;
+}
+
+// ceil(log2(5^e)), or 1 when e is 0
+fn pow5bits(e: i32) i32 {
+    return @intCast(i32, (@intCast(u32, e) * 1217359) >> 19) + 1;
+}
+
+// floor(log10(2^e))
+fn log10Pow2(e: i32) u32 {
+    return (@intCast(u32, e) * 78913) >> 18;
+}
+
+// floor(log10(5^e))
+fn log10Pow5(e: i32) u32 {
+    return (@intCast(u32, e) * 732923) >> 20;
+}
+
+fn multipleOfPowerOf5(value: u64, p: u32) bool {
+    var v = value;
+    var count: u32 = 0;
+    while (v % 5 == 0) {
+        v /= 5;
+        count += 1;
+    }
+
+    return count >= p;
+}
+
+fn multipleOfPowerOf2(value: u64, p: u32) bool {
+    return (value & ((@as(u64, 1) << @intCast(u6, p)) - 1)) == 0;
+}
+
+// (m * mul) >> j, where the full product is up to 192 bits but the result fits in 64
+fn mulShift64(m: u64, mul: u128, j: i32) u64 {
+    const low = @as(u128, m) * @truncate(u64, mul);
+    const high = @as(u128, m) * @truncate(u64, mul >> 64);
+
+    return @truncate(u64, ((low >> 64) + high) >> @intCast(u7, j - 64));
+}
+
+fn shortestDecimal(comptime T: type, ieee_mantissa: u64, ieee_exponent: i32) ShortestDecimal {
+    const info = FloatInfo(T);
+
+    // Work with the value as m2 * 2^e2, with two extra bits so the halfway points to the
+    // neighbouring floats are whole numbers as well
+    var e2: i32 = undefined;
+    var m2: u64 = undefined;
+    if (ieee_exponent == 0) {
+        e2 = 1 - info.bias - info.mantissa_bits - 2;
+        m2 = ieee_mantissa;
+    } else {
+        e2 = ieee_exponent - info.bias - info.mantissa_bits - 2;
+        m2 = (@as(u64, 1) << info.mantissa_bits) | ieee_mantissa;
+    }
+
+    // Round-to-even means an even float also owns the halfway points on either side of it
+    const accept_bounds = (m2 & 1) == 0;
+
+    const mv = 4 * m2;
+    // The gap to the float below is half as wide when that float has a smaller exponent
+    const mm_shift = @boolToInt(ieee_mantissa != 0 or ieee_exponent <= 1);
+
+    // vr is the value, and vp and vm are the halfway points to the floats above and below it,
+    // all scaled by 10^-e10
+    var vr: u64 = undefined;
+    var vp: u64 = undefined;
+    var vm: u64 = undefined;
+    var e10: i32 = undefined;
+    var vm_is_trailing_zeros = false;
+    var vr_is_trailing_zeros = false;
+
+    if (e2 >= 0) {
+        const q = log10Pow2(e2) - @boolToInt(e2 > 3);
+        e10 = @intCast(i32, q);
+
+        const k = pow5_inv_bitcount + pow5bits(@intCast(i32, q)) - 1;
+        const i = -e2 + @intCast(i32, q) + k;
+        const mul = pow5_inv_split[q];
+
+        vr = mulShift64(mv, mul, i);
+        vp = mulShift64(mv + 2, mul, i);
+        vm = mulShift64(mv - 1 - mm_shift, mul, i);
+
+        // Only these can be divisible by 10^q, which would make the digits we drop exact zeroes
+        if (q <= 21) {
+            if (mv % 5 == 0) {
+                vr_is_trailing_zeros = multipleOfPowerOf5(mv, q);
+            } else if (accept_bounds) {
+                vm_is_trailing_zeros = multipleOfPowerOf5(mv - 1 - mm_shift, q);
+            } else {
+                vp -= @boolToInt(multipleOfPowerOf5(mv + 2, q));
+            }
+        }
+    } else {
+        const q = log10Pow5(-e2) - @boolToInt(-e2 > 1);
+        e10 = @intCast(i32, q) + e2;
+
+        const i = -e2 - @intCast(i32, q);
+        const k = pow5bits(i) - pow5_bitcount;
+        const j = @intCast(i32, q) - k;
+        const mul = pow5_split[@intCast(usize, i)];
+
+        vr = mulShift64(mv, mul, j);
+        vp = mulShift64(mv + 2, mul, j);
+        vm = mulShift64(mv - 1 - mm_shift, mul, j);
+
+        if (q <= 1) {
+            // mv has at least q trailing zero bits, and so do the bounds
+            vr_is_trailing_zeros = true;
+            if (accept_bounds) {
+                vm_is_trailing_zeros = mm_shift == 1;
+            } else {
+                vp -= 1;
+            }
+        } else if (q < 63) {
+            vr_is_trailing_zeros = multipleOfPowerOf2(mv, q);
+        }
+    }
+
+    // Drop digits for as long as the result stays strictly between the two halfway points
+    var removed: i32 = 0;
+    var last_removed_digit: u64 = 0;
+    var output: u64 = undefined;
+
+    if (vm_is_trailing_zeros or vr_is_trailing_zeros) {
+        // Rare: the exact values matter for rounding and for whether vm itself is allowed
+        while (vp / 10 > vm / 10) {
+            vm_is_trailing_zeros = vm_is_trailing_zeros and vm % 10 == 0;
+            vr_is_trailing_zeros = vr_is_trailing_zeros and last_removed_digit == 0;
+            last_removed_digit = vr % 10;
+            vr /= 10;
+            vp /= 10;
+            vm /= 10;
+            removed += 1;
+        }
+
+        if (vm_is_trailing_zeros) {
+            while (vm % 10 == 0) {
+                vr_is_trailing_zeros = vr_is_trailing_zeros and last_removed_digit == 0;
+                last_removed_digit = vr % 10;
+                vr /= 10;
+                vp /= 10;
+                vm /= 10;
+                removed += 1;
+            }
+        }
+
+        if (vr_is_trailing_zeros and last_removed_digit == 5 and vr % 2 == 0) {
+            // Exactly halfway, so round to even
+            last_removed_digit = 4;
+        }
+
+        const round_up = (vr == vm and (!accept_bounds or !vm_is_trailing_zeros)) or last_removed_digit >= 5;
+        output = vr + @boolToInt(round_up);
+    } else {
+        var round_up = false;
+        while (vp / 10 > vm / 10) {
+            round_up = vr % 10 >= 5;
+            vr /= 10;
+            vp /= 10;
+            vm /= 10;
+            removed += 1;
+        }
+
+        output = vr + @boolToInt(vr == vm or round_up);
+    }
+
+    return .{ .mantissa = output, .exponent = e10 + removed };
+}
+
+// -------- Parsing
+
+pub const ParseError = error{
+    InvalidCharacter,
+    Overflow,
+};
+
+/// Parse a decimal like `-12.5`, `.5`, `5.` or `1.25e-3`, rounding correctly to the nearest `T`.
+/// `NaN`, `∞`, `inf` and `infinity` (with an optional sign) are accepted too, so that everything
+/// `format` writes can be read back in.
+///
+/// A finite number too large for `T` is an `Overflow` rather than becoming infinity, and anything
+/// else that isn't a number, including surrounding whitespace, is an `InvalidCharacter`.
+pub fn parse(comptime T: type, string: []const u8) ParseError!T {
+    var rest = string;
+    var negative = false;
+    if (rest.len > 0 and (rest[0] == '-' or rest[0] == '+')) {
+        negative = rest[0] == '-';
+        rest = rest[1..];
+    }
+
+    const value = parseSpecial(T, rest) orelse try parseFinite(T, rest);
+
+    return if (negative) -value else value;
+}
+
+fn parseSpecial(comptime T: type, string: []const u8) ?T {
+    if (std.ascii.eqlIgnoreCase(string, "nan")) {
+        return math.nan(T);
+    } else if (std.mem.eql(u8, string, "∞") or std.ascii.eqlIgnoreCase(string, "inf") or std.ascii.eqlIgnoreCase(string, "infinity")) {
+        return math.inf(T);
+    } else {
+        return null;
+    }
+}
+
+fn isDigit(char: u8) bool {
+    return char >= '0' and char <= '9';
+}
+
+fn parseFinite(comptime T: type, string: []const u8) ParseError!T {
+    var decimal = Decimal{};
+    var seen_digit = false;
+    var i: usize = 0;
+
+    while (i < string.len and isDigit(string[i])) : (i += 1) {
+        seen_digit = true;
+        decimal.pushDigit(string[i] - '0', true);
+    }
+
+    if (i < string.len and string[i] == '.') {
+        i += 1;
+
+        while (i < string.len and isDigit(string[i])) : (i += 1) {
+            seen_digit = true;
+            decimal.pushDigit(string[i] - '0', false);
+        }
+    }
+
+    if (!seen_digit) {
+        return error.InvalidCharacter;
+    }
+
+    if (i < string.len and (string[i] == 'e' or string[i] == 'E')) {
+        i += 1;
+
+        var exponent_negative = false;
+        if (i < string.len and (string[i] == '-' or string[i] == '+')) {
+            exponent_negative = string[i] == '-';
+            i += 1;
+        }
+
+        if (i == string.len or !isDigit(string[i])) {
+            return error.InvalidCharacter;
+        }
+
+        // Anything past this is far out of range already, so stop before the i32 overflows
+        var exponent: i32 = 0;
+        while (i < string.len and isDigit(string[i])) : (i += 1) {
+            if (exponent < 0x10000) {
+                exponent = 10 * exponent + (string[i] - '0');
+            }
+        }
+
+        decimal.decimal_point += if (exponent_negative) -exponent else exponent;
+    }
+
+    if (i != string.len) {
+        return error.InvalidCharacter;
+    }
+
+    decimal.finishDigits();
+
+    if (decimal.num_digits == 0) {
+        return 0;
+    }
+
+    if (fastPath(T, &decimal)) |value| {
+        return value;
+    }
+
+    return slowPath(T, &decimal) orelse error.Overflow;
+}
+
+// Clinger's fast path: when both the digits and the power of ten are exact floats, a single
+// multiplication or division rounds correctly.
+fn fastPath(comptime T: type, decimal: *const Decimal) ?T {
+    const max_digits_exact = switch (T) {
+        f32 => 7,
+        f64 => 15,
+        else => unreachable,
+    };
+    const max_exponent_exact = switch (T) {
+        f32 => 10,
+        f64 => 22,
+        else => unreachable,
+    };
+    const powers_of_10 = comptime blk: {
+        var powers: [max_exponent_exact + 1]T = undefined;
+        var power: T = 1;
+        for (powers) |*entry| {
+            entry.* = power;
+            power *= 10;
+        }
+        break :blk powers;
+    };
+
+    if (decimal.truncated or decimal.num_digits > max_digits_exact) {
+        return null;
+    }
+
+    var mantissa: u64 = 0;
+    for (decimal.digits[0..decimal.num_digits]) |digit| {
+        mantissa = 10 * mantissa + digit;
+    }
+
+    const exponent = decimal.decimal_point - @intCast(i32, decimal.num_digits);
+    if (exponent >= 0 and exponent <= max_exponent_exact) {
+        return @intToFloat(T, mantissa) * powers_of_10[@intCast(usize, exponent)];
+    } else if (exponent < 0 and exponent >= -max_exponent_exact) {
+        return @intToFloat(T, mantissa) / powers_of_10[@intCast(usize, -exponent)];
+    } else {
+        return null;
+    }
+}
+
+// The simple decimal conversion also used by Go's and Rust's standard libraries: scale the decimal
+// by powers of two until it lies in [1/2, 1), then read off the mantissa bits. Slow but exact.
+fn slowPath(comptime T: type, decimal: *Decimal) ?T {
+    const info = FloatInfo(T);
+    const minimum_exponent = -info.bias;
+
+    if (decimal.decimal_point < -324) {
+        return 0;
+    } else if (decimal.decimal_point >= 310) {
+        return null;
+    }
+
+    var exp2: i32 = 0;
+
+    while (decimal.decimal_point > 0) {
+        const shift = decimalShift(@intCast(usize, decimal.decimal_point));
+        decimal.rightShift(shift);
+        if (decimal.decimal_point < -Decimal.decimal_point_range) {
+            return 0;
+        }
+        exp2 += shift;
+    }
+
+    while (decimal.decimal_point <= 0) {
+        const shift: u6 = if (decimal.decimal_point == 0) switch (decimal.digits[0]) {
+            0, 1 => 2,
+            2...4 => 1,
+            else => break,
+        } else decimalShift(@intCast(usize, -decimal.decimal_point));
+
+        decimal.leftShift(shift);
+        if (decimal.decimal_point > Decimal.decimal_point_range) {
+            return null;
+        }
+        exp2 -= shift;
+    }
+
+    // The decimal is now in [1/2, 1), but the mantissa is in [1, 2)
+    exp2 -= 1;
+
+    while (minimum_exponent + 1 > exp2) {
+        const shift = math.min(minimum_exponent + 1 - exp2, Decimal.max_shift);
+        decimal.rightShift(@intCast(u6, shift));
+        exp2 += shift;
+    }
+
+    if (exp2 - minimum_exponent >= info.infinite_power) {
+        return null;
+    }
+
+    // Move the hidden bit to the left of the decimal point, and round what's left of it off
+    decimal.leftShift(info.mantissa_bits + 1);
+    var mantissa = decimal.round();
+    if (mantissa >= (@as(u64, 1) << (info.mantissa_bits + 1))) {
+        // Rounding carried into a new bit, so shift it back out
+        decimal.rightShift(1);
+        exp2 += 1;
+        mantissa = decimal.round();
+
+        if (exp2 - minimum_exponent >= info.infinite_power) {
+            return null;
+        }
+    }
+
+    var biased_exponent = exp2 - minimum_exponent;
+    if (mantissa < (@as(u64, 1) << info.mantissa_bits)) {
+        // No hidden bit, so this is a subnormal
+        biased_exponent -= 1;
+    }
+    mantissa &= (@as(u64, 1) << info.mantissa_bits) - 1;
+
+    const bits = (@intCast(u64, biased_exponent) << info.mantissa_bits) | mantissa;
+
+    return @bitCast(T, @truncate(info.Bits, bits));
+}
+
+// How far to shift to move the decimal point by about `n` digits, without overflowing a u64
+fn decimalShift(n: usize) u6 {
+    const shifts = [_]u6{ 0, 3, 6, 9, 13, 16, 19, 23, 26, 29, 33, 36, 39, 43, 46, 49, 53, 56, 59 };
+
+    return if (n < shifts.len) shifts[n] else Decimal.max_shift;
+}
+
+/// A big decimal 0.d1d2d3... * 10^decimal_point. Digits beyond `max_digits` can't change how
+/// the value rounds except by being nonzero, which `truncated` records.
+const Decimal = struct {
+    const max_digits = 768;
+    const decimal_point_range = 2047;
+    const max_shift = 60;
+
+    num_digits: usize = 0,
+    decimal_point: i32 = 0,
+    truncated: bool = false,
+    digits: [max_digits]u8 = undefined,
+
+    fn pushDigit(self: *Decimal, digit: u8, before_point: bool) void {
+        if (self.num_digits == 0 and digit == 0) {
+            // A leading zero only matters for where the point is
+            if (!before_point) {
+                self.decimal_point -= 1;
+            }
+            return;
+        }
+
+        if (self.num_digits < max_digits) {
+            self.digits[self.num_digits] = digit;
+        } else if (digit != 0) {
+            self.truncated = true;
+        }
+
+        self.num_digits += 1;
+        if (before_point) {
+            self.decimal_point += 1;
+        }
+    }
+
+    fn finishDigits(self: *Decimal) void {
+        self.num_digits = math.min(self.num_digits, max_digits);
+        self.trim();
+    }
+
+    fn trim(self: *Decimal) void {
+        while (self.num_digits != 0 and self.digits[self.num_digits - 1] == 0) {
+            self.num_digits -= 1;
+        }
+    }
+
+    // The whole part of the decimal, rounded half to even
+    fn round(self: *const Decimal) u64 {
+        if (self.num_digits == 0 or self.decimal_point < 0) {
+            return 0;
+        } else if (self.decimal_point > 18) {
+            return math.maxInt(u64);
+        }
+
+        const point = @intCast(usize, self.decimal_point);
+
+        var n: u64 = 0;
+        var i: usize = 0;
+        while (i < point) : (i += 1) {
+            n *= 10;
+            if (i < self.num_digits) {
+                n += self.digits[i];
+            }
+        }
+
+        var round_up = false;
+        if (point < self.num_digits) {
+            round_up = self.digits[point] >= 5;
+
+            if (self.digits[point] == 5 and point + 1 == self.num_digits) {
+                round_up = self.truncated or (point != 0 and (self.digits[point - 1] & 1) != 0);
+            }
+        }
+
+        return n + @boolToInt(round_up);
+    }
+
+    // Multiply by 2^shift
+    fn leftShift(self: *Decimal, shift: u6) void {
+        if (self.num_digits == 0) {
+            return;
+        }
+
+        const num_new_digits = self.numNewDigits(shift);
+        var read_index = self.num_digits;
+        var write_index = self.num_digits + num_new_digits;
+        var n: u64 = 0;
+
+        while (read_index != 0) {
+            read_index -= 1;
+            write_index -= 1;
+
+            n += @as(u64, self.digits[read_index]) << shift;
+            self.writeDigit(write_index, n % 10);
+            n /= 10;
+        }
+
+        while (n > 0) {
+            write_index -= 1;
+
+            self.writeDigit(write_index, n % 10);
+            n /= 10;
+        }
+
+        self.num_digits = math.min(self.num_digits + num_new_digits, max_digits);
+        self.decimal_point += @intCast(i32, num_new_digits);
+        self.trim();
+    }
+
+    fn writeDigit(self: *Decimal, index: usize, digit: u64) void {
+        if (index < max_digits) {
+            self.digits[index] = @intCast(u8, digit);
+        } else if (digit > 0) {
+            self.truncated = true;
+        }
+    }
+
+    // Multiplying by 2^shift adds as many digits as 2^shift has, or one fewer when the
+    // decimal's digits are less than those of 5^shift
+    fn numNewDigits(self: *const Decimal, shift: u6) usize {
+        const entry = left_shift_table[shift];
+
+        for (entry.pow5) |char, i| {
+            if (i >= self.num_digits) {
+                return entry.num_new_digits - 1;
+            } else if (self.digits[i] + '0' != char) {
+                return if (self.digits[i] + '0' < char) entry.num_new_digits - 1 else entry.num_new_digits;
+            }
+        }
+
+        return entry.num_new_digits;
+    }
+
+    // Divide by 2^shift
+    fn rightShift(self: *Decimal, shift: u6) void {
+        var read_index: usize = 0;
+        var write_index: usize = 0;
+        var n: u64 = 0;
+
+        while ((n >> shift) == 0) {
+            if (read_index < self.num_digits) {
+                n = 10 * n + self.digits[read_index];
+                read_index += 1;
+            } else if (n == 0) {
+                return;
+            } else {
+                while ((n >> shift) == 0) {
+                    n *= 10;
+                    read_index += 1;
+                }
+                break;
+            }
+        }
+
+        self.decimal_point -= @intCast(i32, read_index) - 1;
+        if (self.decimal_point < -decimal_point_range) {
+            self.* = Decimal{};
+            return;
+        }
+
+        const mask = (@as(u64, 1) << shift) - 1;
+
+        while (read_index < self.num_digits) {
+            const new_digit = @intCast(u8, n >> shift);
+            n = 10 * (n & mask) + self.digits[read_index];
+            read_index += 1;
+
+            self.digits[write_index] = new_digit;
+            write_index += 1;
+        }
+
+        while (n > 0) {
+            const new_digit = @intCast(u8, n >> shift);
+            n = 10 * (n & mask);
+
+            if (write_index < max_digits) {
+                self.digits[write_index] = new_digit;
+                write_index += 1;
+            } else if (new_digit > 0) {
+                self.truncated = true;
+            }
+        }
+
+        self.num_digits = write_index;
+        self.trim();
+    }
+};
+
+const LeftShiftEntry = struct {
+    num_new_digits: usize,
+    pow5: []const u8,
+};
+
+const left_shift_table = leftShiftTable();
+
+fn leftShiftTable() [Decimal.max_shift + 1]LeftShiftEntry {
+    @setEvalBranchQuota(100_000);
+
+    var table: [Decimal.max_shift + 1]LeftShiftEntry = undefined;
+    var pow2: u256 = 1;
+    var pow5: u256 = 1;
+    for (table) |*entry| {
+        entry.* = .{
+            .num_new_digits = std.fmt.comptimePrint("{d}", .{pow2}).len,
+            .pow5 = std.fmt.comptimePrint("{d}", .{pow5}),
+        };
+
+        pow2 *= 2;
+        pow5 *= 5;
+    }
+
+    return table;
+}
+
+const testing = std.testing;
+
+fn expectFormat(comptime T: type, value: T, expected: []const u8) !void {
+    var buf: [max_format_len]u8 = undefined;
+    const len = format(T, value, &buf);
+
+    try testing.expectEqualStrings(expected, buf[0..len]);
+}
+
+test "format: shortest round-trip" {
+    var tenth: f64 = 0.1;
+    var fifth: f64 = 0.2;
+
+    try expectFormat(f64, tenth + fifth, "0.30000000000000004");
+    try expectFormat(f64, 0.1, "0.1");
+    try expectFormat(f64, 1.0, "1");
+    try expectFormat(f64, -1.75, "-1.75");
+    try expectFormat(f64, 1e21, "1000000000000000000000");
+    try expectFormat(f64, 1e-7, "0.0000001");
+    try expectFormat(f64, 9007199254740993.0, "9007199254740992");
+    try expectFormat(f64, 5e-324, "0." ++ "0" ** 323 ++ "5");
+
+    try expectFormat(f32, 0.1, "0.1");
+    try expectFormat(f32, 16777216.0, "16777216");
+    try expectFormat(f32, math.f32_max, "340282350000000000000000000000000000000");
+    try expectFormat(f32, math.f32_true_min, "0." ++ "0" ** 44 ++ "1");
+}
+
+test "format: special values" {
+    try expectFormat(f64, 0.0, "0");
+    try expectFormat(f64, -0.0, "-0");
+    try expectFormat(f64, math.inf(f64), "∞");
+    try expectFormat(f64, -math.inf(f64), "-∞");
+    try expectFormat(f32, math.nan(f32), "NaN");
+}
+
+test "parse: correctly rounded" {
+    try testing.expectEqual(@as(f64, 0.1), try parse(f64, "0.1"));
+    try testing.expectEqual(@as(f64, 0.1), try parse(f64, "0.1000000000000000055511151231257827021181583404541015625"));
+    try testing.expectEqual(@as(f64, 2.2250738585072014e-308), try parse(f64, "2.2250738585072014e-308"));
+    try testing.expectEqual(@as(f64, 5e-324), try parse(f64, "4.9406564584124654e-324"));
+    try testing.expectEqual(@as(f64, 0), try parse(f64, "1e-400"));
+    try testing.expectEqual(@as(f64, 9007199254740992), try parse(f64, "9007199254740993"));
+    try testing.expectEqual(@as(f64, 1.5), try parse(f64, ".15e1"));
+    try testing.expectEqual(@as(f64, -5), try parse(f64, "-5."));
+    try testing.expectEqual(math.f32_max, try parse(f32, "340282350000000000000000000000000000000"));
+    try testing.expectEqual(@as(f32, 0.1), try parse(f32, "0.1"));
+}
+
+test "parse: special values" {
+    try testing.expect(math.isNan(try parse(f64, "NaN")));
+    try testing.expectEqual(math.inf(f64), try parse(f64, "∞"));
+    try testing.expectEqual(-math.inf(f64), try parse(f64, "-∞"));
+    try testing.expectEqual(-math.inf(f32), try parse(f32, "-Infinity"));
+}
+
+test "parse: errors" {
+    try testing.expectError(error.InvalidCharacter, parse(f64, ""));
+    try testing.expectError(error.InvalidCharacter, parse(f64, "-"));
+    try testing.expectError(error.InvalidCharacter, parse(f64, "."));
+    try testing.expectError(error.InvalidCharacter, parse(f64, "1e"));
+    try testing.expectError(error.InvalidCharacter, parse(f64, "1e+"));
+    try testing.expectError(error.InvalidCharacter, parse(f64, "1.2.3"));
+    try testing.expectError(error.InvalidCharacter, parse(f64, " 1"));
+    try testing.expectError(error.InvalidCharacter, parse(f64, "0x10"));
+    try testing.expectError(error.Overflow, parse(f64, "1e400"));
+    try testing.expectError(error.Overflow, parse(f32, "3.5e38"));
+}
+
+test "format and parse round-trip" {
+    const values = [_]f64{ 0.1, 1.0 / 3.0, math.pi, 123456.789e200, 2.5e-310, math.f64_max, -math.f64_min };
+    for (values) |value| {
+        var buf: [max_format_len]u8 = undefined;
+        const len = format(f64, value, &buf);
+
+        try testing.expectEqual(value, try parse(f64, buf[0..len]));
+    }
+}
@@ -73,13 +73,17 @@ comptime {
     exportNumFn(num.bytesToU64C, "bytes_to_u64");
     exportNumFn(num.bytesToU128C, "bytes_to_u128");
 
-    exportNumFn(num.shiftRightZeroFillI128, "shift_right_zero_fill.i128");
-    exportNumFn(num.shiftRightZeroFillU128, "shift_right_zero_fill.u128");
-
     inline for (INTEGERS) |T, i| {
         num.exportPow(T, ROC_BUILTINS ++ "." ++ NUM ++ ".pow_int.");
         num.exportDivCeil(T, ROC_BUILTINS ++ "." ++ NUM ++ ".div_ceil.");
 
+        num.exportBitwiseAnd(T, ROC_BUILTINS ++ "." ++ NUM ++ ".bitwise_and.");
+        num.exportBitwiseOr(T, ROC_BUILTINS ++ "." ++ NUM ++ ".bitwise_or.");
+        num.exportBitwiseXor(T, ROC_BUILTINS ++ "." ++ NUM ++ ".bitwise_xor.");
+        num.exportShiftLeftBy(T, ROC_BUILTINS ++ "." ++ NUM ++ ".shift_left_by.");
+        num.exportShiftRightBy(T, ROC_BUILTINS ++ "." ++ NUM ++ ".shift_right_by.");
+        num.exportShiftRightZeroFillBy(T, ROC_BUILTINS ++ "." ++ NUM ++ ".shift_right_zero_fill.");
+
         num.exportRoundF32(T, ROC_BUILTINS ++ "." ++ NUM ++ ".round_f32.");
         num.exportRoundF64(T, ROC_BUILTINS ++ "." ++ NUM ++ ".round_f64.");
 
@@ -166,6 +170,9 @@ comptime {
     exportStrFn(str.strGraphemes, "graphemes");
     exportStrFn(str.strRefcountPtr, "refcount_ptr");
     exportStrFn(str.strReleaseExcessCapacity, "release_excess_capacity");
+    exportStrFn(str.strReplaceEachUnsafe, "replace_each_unsafe");
+    exportStrFn(str.strSplitOn, "split_on");
+    exportStrFn(str.strWalkUtf8, "walk_utf8");
 
     inline for (INTEGERS) |T| {
         str.exportFromInt(T, ROC_BUILTINS ++ "." ++ STR ++ ".from_int.");
@@ -188,8 +195,13 @@ comptime {
     exportUtilsFn(utils.isUnique, "is_unique");
     exportUtilsFn(utils.decrefCheckNullC, "decref_check_null");
     exportUtilsFn(utils.allocateWithRefcountC, "allocate_with_refcount");
+    exportUtilsFn(utils.allocC, "alloc");
+    exportUtilsFn(utils.deallocC, "dealloc");
+    @export(utils.arena_allocation, .{ .name = "roc_builtins.utils." ++ "arena_allocation", .linkage = .Strong });
 
     @export(panic_utils.panic, .{ .name = "roc_builtins.utils." ++ "panic", .linkage = .Weak });
+    exportUtilsFn(panic_utils.stackTracePush, "stack_trace_push");
+    exportUtilsFn(panic_utils.stackTracePop, "stack_trace_pop");
 
     if (builtin.target.cpu.arch != .wasm32) {
         exportUtilsFn(expect.expectFailedStartSharedBuffer, "expect_failed_start_shared_buffer");
@@ -3,6 +3,7 @@ const always_inline = std.builtin.CallOptions.Modifier.always_inline;
 const math = std.math;
 const RocList = @import("list.zig").RocList;
 const RocStr = @import("str.zig").RocStr;
+const float = @import("float.zig");
 const WithOverflow = @import("utils.zig").WithOverflow;
 const roc_panic = @import("panic.zig").panic_help;
 
@@ -76,7 +77,7 @@ pub fn exportParseInt(comptime T: type, comptime name: []const u8) void {
 pub fn exportParseFloat(comptime T: type, comptime name: []const u8) void {
     comptime var f = struct {
         fn func(buf: RocStr) callconv(.C) NumParseResult(T) {
-            if (std.fmt.parseFloat(T, buf.asSlice())) |success| {
+            if (float.parse(T, buf.asSlice())) |success| {
                 return .{ .errorcode = 0, .value = success };
             } else |_| {
                 return .{ .errorcode = 1, .value = 0 };
@@ -515,20 +516,75 @@ pub fn exportMulWrappedInt(comptime T: type, comptime name: []const u8) void {
     @export(f, .{ .name = name ++ @typeName(T), .linkage = .Strong });
 }
 
-pub fn shiftRightZeroFillI128(self: i128, other: u8) callconv(.C) i128 {
-    if (other & 0b1000_0000 > 0) {
-        return 0;
-    } else {
-        return self >> @intCast(u7, other);
-    }
+pub fn exportBitwiseAnd(comptime T: type, comptime name: []const u8) void {
+    comptime var f = struct {
+        fn func(self: T, other: T) callconv(.C) T {
+            return self & other;
+        }
+    }.func;
+    @export(f, .{ .name = name ++ @typeName(T), .linkage = .Strong });
 }
 
-pub fn shiftRightZeroFillU128(self: u128, other: u8) callconv(.C) u128 {
-    if (other & 0b1000_0000 > 0) {
-        return 0;
-    } else {
-        return self >> @intCast(u7, other);
-    }
+pub fn exportBitwiseOr(comptime T: type, comptime name: []const u8) void {
+    comptime var f = struct {
+        fn func(self: T, other: T) callconv(.C) T {
+            return self | other;
+        }
+    }.func;
+    @export(f, .{ .name = name ++ @typeName(T), .linkage = .Strong });
+}
+
+pub fn exportBitwiseXor(comptime T: type, comptime name: []const u8) void {
+    comptime var f = struct {
+        fn func(self: T, other: T) callconv(.C) T {
+            return self ^ other;
+        }
+    }.func;
+    @export(f, .{ .name = name ++ @typeName(T), .linkage = .Strong });
+}
+
+// Shifting by the integer's width or more shifts every bit out, rather than being undefined.
+
+pub fn exportShiftLeftBy(comptime T: type, comptime name: []const u8) void {
+    comptime var f = struct {
+        fn func(self: T, other: u8) callconv(.C) T {
+            if (other >= @bitSizeOf(T)) {
+                return 0;
+            } else {
+                return self << @intCast(math.Log2Int(T), other);
+            }
+        }
+    }.func;
+    @export(f, .{ .name = name ++ @typeName(T), .linkage = .Strong });
+}
+
+pub fn exportShiftRightBy(comptime T: type, comptime name: []const u8) void {
+    comptime var f = struct {
+        fn func(self: T, other: u8) callconv(.C) T {
+            // Roc treats every integer as signed here, so copies of the top bit are shifted in.
+            const Signed = std.meta.Int(.signed, @bitSizeOf(T));
+            const amount = if (other >= @bitSizeOf(T)) @bitSizeOf(T) - 1 else other;
+
+            return @bitCast(T, @bitCast(Signed, self) >> @intCast(math.Log2Int(Signed), amount));
+        }
+    }.func;
+    @export(f, .{ .name = name ++ @typeName(T), .linkage = .Strong });
+}
+
+pub fn exportShiftRightZeroFillBy(comptime T: type, comptime name: []const u8) void {
+    comptime var f = struct {
+        fn func(self: T, other: u8) callconv(.C) T {
+            // Roc treats every integer as unsigned here, so zeroes are shifted in.
+            const Unsigned = std.meta.Int(.unsigned, @bitSizeOf(T));
+
+            if (other >= @bitSizeOf(T)) {
+                return 0;
+            } else {
+                return @bitCast(T, @bitCast(Unsigned, self) >> @intCast(math.Log2Int(Unsigned), other));
+            }
+        }
+    }.func;
+    @export(f, .{ .name = name ++ @typeName(T), .linkage = .Strong });
 }
 
 pub fn exportMulOrPanic(comptime T: type, comptime W: type, comptime name: []const u8) void {
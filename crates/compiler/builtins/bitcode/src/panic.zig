@@ -7,10 +7,105 @@ extern fn roc_panic(msg: *const RocStr, tag_id: u32) callconv(.C) void;
 
 pub fn panic_help(msg: []const u8, tag_id: u32) void {
     var str = RocStr.init(msg.ptr, msg.len);
-    roc_panic(&str, tag_id);
+    panic(&str, tag_id);
 }
 
-// must export this explicitly because right now it is not used from zig code
-pub fn panic(msg: *const RocStr, alignment: u32) callconv(.C) void {
-    return @call(.{ .modifier = always_inline }, roc_panic, .{ msg, alignment });
+// must export this explicitly, because the LLVM backend calls it for every crash
+pub fn panic(msg: *const RocStr, tag_id: u32) callconv(.C) void {
+    if (stack_depth == 0) {
+        return @call(.{ .modifier = always_inline }, roc_panic, .{ msg, tag_id });
+    }
+
+    var with_stack_trace = withStackTrace(msg);
+    roc_panic(&with_stack_trace, tag_id);
+}
+
+// The Roc procs being run in a `--stack-traces` build, pushed and popped by the procs
+// themselves. Procs deeper than this are counted, but their names are not kept.
+const MAX_STACK_DEPTH = 1024;
+var stack: [MAX_STACK_DEPTH]RocStr = undefined;
+var stack_depth: usize = 0;
+
+// The name is a string literal, so keeping a copy of the struct keeps it alive
+pub fn stackTracePush(name: *const RocStr) callconv(.C) void {
+    if (stack_depth < MAX_STACK_DEPTH) {
+        stack[stack_depth] = name.*;
+    }
+
+    stack_depth += 1;
+}
+
+pub fn stackTracePop(name: *const RocStr) callconv(.C) void {
+    _ = name;
+
+    stack_depth -= 1;
+}
+
+// A crash message lists at most this many procs, most recent first
+const MAX_LISTED_PROCS = 64;
+
+// The message with its stack trace is written here rather than allocated, because the crash
+// may be that the program is out of memory. The first word is the refcount; 0 marks the
+// string as static, so it is never freed.
+var message_buffer: [16 * 1024]u8 align(@alignOf(usize)) = undefined;
+
+fn withStackTrace(msg: *const RocStr) RocStr {
+    const refcount_bytes = @sizeOf(usize);
+    std.mem.set(u8, message_buffer[0..refcount_bytes], 0);
+
+    var stream = std.io.fixedBufferStream(message_buffer[refcount_bytes..]);
+
+    // if the buffer fills up, the message is cut short, which is better than no message
+    writeStackTrace(stream.writer(), msg.asSlice()) catch {};
+
+    const written = stream.getWritten();
+    return RocStr{
+        .str_bytes = written.ptr,
+        .str_len = written.len,
+        .str_capacity = written.len,
+    };
+}
+
+fn writeStackTrace(writer: anytype, msg: []const u8) !void {
+    try writer.writeAll(msg);
+    try writer.writeAll("\n\nRoc stack trace, most recent call first:\n");
+
+    const named: usize = std.math.min(stack_depth, MAX_STACK_DEPTH);
+    if (stack_depth > named) {
+        try writer.print("    ... {d} calls too deep to name\n", .{stack_depth - named});
+    }
+
+    var i: usize = named;
+    var listed: usize = 0;
+    while (i > 0 and listed < MAX_LISTED_PROCS) : (listed += 1) {
+        i -= 1;
+        try writer.print("    {s}\n", .{stack[i].asSlice()});
+    }
+
+    if (i > 0) {
+        try writer.print("    ... {d} more\n", .{i});
+    }
+}
+
+test "the stack trace is added to the crash message" {
+    const outer = RocStr.fromSlice("Main.main (main.roc:3:5)");
+    const inner = RocStr.fromSlice("Main.f (main.roc:7:5)");
+    defer RocStr.decref(outer);
+    defer RocStr.decref(inner);
+
+    stackTracePush(&outer);
+    stackTracePush(&inner);
+    defer stackTracePop(&outer);
+    defer stackTracePop(&inner);
+
+    const msg = RocStr.fromSlice("oops");
+    defer RocStr.decref(msg);
+
+    const actual = withStackTrace(&msg);
+    defer RocStr.decref(actual);
+
+    try std.testing.expectEqualStrings(
+        "oops\n\nRoc stack trace, most recent call first:\n    Main.f (main.roc:7:5)\n    Main.main (main.roc:3:5)\n",
+        actual.asSlice(),
+    );
 }
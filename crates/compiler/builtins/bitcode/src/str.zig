@@ -1,6 +1,7 @@
 const utils = @import("utils.zig");
 const RocList = @import("list.zig").RocList;
 const grapheme = @import("helpers/grapheme.zig");
+const float_ = @import("float.zig");
 const UpdateMode = utils.UpdateMode;
 const std = @import("std");
 const mem = std.mem;
@@ -852,10 +853,10 @@ pub fn exportFromFloat(comptime T: type, comptime name: []const u8) void {
 }
 
 fn strFromFloatHelp(comptime T: type, float: T) RocStr {
-    var buf: [400]u8 = undefined;
-    const result = std.fmt.bufPrint(&buf, "{d}", .{float}) catch unreachable;
+    var buf: [float_.max_format_len]u8 = undefined;
+    const len = float_.format(T, float, &buf);
 
-    return RocStr.init(&buf, result.len);
+    return RocStr.init(&buf, len);
 }
 
 // Str.split
@@ -871,6 +872,42 @@ pub fn strSplit(string: RocStr, delimiter: RocStr) callconv(.C) RocList {
     return list;
 }
 
+// Str.splitOn
+// Like Str.split, but the string is owned: the pieces are slices of its buffer,
+// and they take over its reference instead of each adding one more.
+pub fn strSplitOn(string: RocStr, delimiter: RocStr) callconv(.C) RocList {
+    const list = strSplit(string, delimiter);
+
+    // strSplit gave every piece its own reference, so ours isn't needed anymore.
+    // The pieces still hold the buffer, so this never frees it.
+    string.decref();
+
+    return list;
+}
+
+test "strSplitOn: the pieces take over the reference" {
+    const str = RocStr.fromSlice("one, two, three, four, five, six, seven");
+    const delimiter = RocStr.fromSlice(", ");
+    defer delimiter.decref();
+
+    const list = strSplitOn(str, delimiter);
+    const strings = @ptrCast([*]RocStr, @alignCast(@alignOf(RocStr), list.bytes));
+
+    try expectEqual(list.len(), 7);
+    try expect(strings[0].eq(RocStr.fromSlice("one")));
+    try expect(strings[6].eq(RocStr.fromSlice("seven")));
+
+    var i: usize = 0;
+    while (i < 6) : (i += 1) {
+        strings[i].decref();
+    }
+
+    // the last piece holds the only reference left
+    try expect(strings[6].isUnique());
+    strings[6].decref();
+    list.decref(@alignOf(RocStr));
+}
+
 fn initFromSmallStr(slice_bytes: [*]u8, len: usize, _: usize) RocStr {
     return RocStr.init(slice_bytes, len);
 }
@@ -1542,6 +1579,72 @@ pub fn getUnsafe(string: RocStr, index: usize) callconv(.C) u8 {
     return string.getUnchecked(index);
 }
 
+const Opaque = ?[*]u8;
+const Caller2 = fn (?[*]u8, ?[*]u8, ?[*]u8, ?[*]u8) callconv(.C) void;
+const IncN = fn (?[*]u8, usize) callconv(.C) void;
+
+// Str.walkUtf8
+// The state starts out in `state`, and is left there at the end. Each call writes the next
+// state to `scratch`, which has the same width, and it is copied back from there.
+pub fn strWalkUtf8(
+    string: RocStr,
+    caller: Caller2,
+    data: Opaque,
+    inc_n_data: IncN,
+    data_is_owned: bool,
+    state: Opaque,
+    scratch: Opaque,
+    state_width: usize,
+) callconv(.C) void {
+    const size = string.len();
+
+    if (size == 0) {
+        return;
+    }
+
+    if (data_is_owned) {
+        inc_n_data(data, size);
+    }
+
+    const bytes = string.asU8ptr();
+    const state_ptr = state orelse unreachable;
+    const scratch_ptr = scratch orelse unreachable;
+
+    var i: usize = 0;
+    while (i < size) : (i += 1) {
+        var byte: u8 = bytes[i];
+
+        caller(data, state_ptr, @ptrCast(?[*]u8, &byte), scratch_ptr);
+        @memcpy(state_ptr, scratch_ptr, state_width);
+    }
+}
+
+fn testSumBytes(_: ?[*]u8, state: ?[*]u8, byte: ?[*]u8, output: ?[*]u8) callconv(.C) void {
+    const sum = @ptrCast(*u64, @alignCast(@alignOf(u64), state));
+    const next = @ptrCast(*u64, @alignCast(@alignOf(u64), output));
+
+    next.* = sum.* + (byte orelse unreachable)[0];
+}
+
+fn testIncN(_: ?[*]u8, _: usize) callconv(.C) void {}
+
+test "strWalkUtf8: visits every byte in order" {
+    const str = RocStr.fromSlice("a string that is too long to be small");
+    defer str.decref();
+
+    var expected: u64 = 0;
+    for (str.asSlice()) |byte| {
+        expected += byte;
+    }
+
+    var state: u64 = 0;
+    var scratch: u64 = undefined;
+
+    strWalkUtf8(str, testSumBytes, null, testIncN, false, @ptrCast(?[*]u8, &state), @ptrCast(?[*]u8, &scratch), @sizeOf(u64));
+
+    try expectEqual(expected, state);
+}
+
 test "substringUnsafe: start" {
     const str = RocStr.fromSlice("abcdef");
     defer str.decref();
@@ -2936,3 +3039,160 @@ pub fn strReleaseExcessCapacity(
         return output;
     }
 }
+
+// Str.replaceEachUnsafe
+// The needle must not be empty.
+pub fn strReplaceEachUnsafe(string: RocStr, needle: RocStr, flower: RocStr) callconv(.C) RocStr {
+    const str_len = string.len();
+    const needle_len = needle.len();
+    const flower_len = flower.len();
+    const match_count = countMatches(string.asU8ptr(), str_len, needle);
+
+    if (match_count == 0) {
+        return string;
+    }
+
+    const new_len = str_len - match_count * needle_len + match_count * flower_len;
+
+    if (flower_len <= needle_len and !string.isSeamlessSlice() and string.isUnique()) {
+        // Each replacement is no longer than what it replaces, so writing from left to right
+        // never overwrites bytes that haven't been read yet, and the buffer can be reused.
+        var output = string;
+        const bytes_ptr = output.asU8ptrMut();
+
+        replaceEachHelp(bytes_ptr, bytes_ptr, str_len, needle, flower);
+        output.setLen(new_len);
+
+        return output;
+    } else {
+        var output = RocStr.allocate(new_len);
+
+        replaceEachHelp(output.asU8ptrMut(), string.asU8ptr(), str_len, needle, flower);
+        string.decref();
+
+        return output;
+    }
+}
+
+fn matchesAt(bytes_ptr: [*]const u8, length: usize, index: usize, needle: RocStr) bool {
+    const needle_len = needle.len();
+
+    if (index + needle_len > length) {
+        return false;
+    }
+
+    const needle_ptr = needle.asU8ptr();
+    var i: usize = 0;
+    while (i < needle_len) : (i += 1) {
+        if (bytes_ptr[index + i] != needle_ptr[i]) {
+            return false;
+        }
+    }
+
+    return true;
+}
+
+fn countMatches(bytes_ptr: [*]const u8, length: usize, needle: RocStr) usize {
+    const needle_len = needle.len();
+
+    if (needle_len == 0) {
+        return 0;
+    }
+
+    var count: usize = 0;
+    var index: usize = 0;
+    while (index < length) {
+        if (matchesAt(bytes_ptr, length, index, needle)) {
+            count += 1;
+            index += needle_len;
+        } else {
+            index += 1;
+        }
+    }
+
+    return count;
+}
+
+// Copy `length` bytes from `source` to `dest`, replacing each needle with the flower.
+// `dest` may be `source`, as long as the flower is no longer than the needle.
+fn replaceEachHelp(dest: [*]u8, source: [*]const u8, length: usize, needle: RocStr, flower: RocStr) void {
+    const needle_len = needle.len();
+    const flower_len = flower.len();
+    const flower_ptr = flower.asU8ptr();
+
+    var read_index: usize = 0;
+    var write_index: usize = 0;
+    while (read_index < length) {
+        if (matchesAt(source, length, read_index, needle)) {
+            var i: usize = 0;
+            while (i < flower_len) : (i += 1) {
+                dest[write_index + i] = flower_ptr[i];
+            }
+
+            read_index += needle_len;
+            write_index += flower_len;
+        } else {
+            dest[write_index] = source[read_index];
+
+            read_index += 1;
+            write_index += 1;
+        }
+    }
+}
+
+test "strReplaceEachUnsafe: same length reuses the buffer" {
+    const str = RocStr.fromSlice("one/two/three/four/five/six/seven");
+    const original_ptr = str.str_bytes;
+
+    const needle = RocStr.fromSlice("/");
+    const flower = RocStr.fromSlice("_");
+    defer {
+        needle.decref();
+        flower.decref();
+    }
+
+    const expected = RocStr.fromSlice("one_two_three_four_five_six_seven");
+    defer expected.decref();
+
+    const actual = strReplaceEachUnsafe(str, needle, flower);
+    defer actual.decref();
+
+    try expect(RocStr.eq(actual, expected));
+    try expectEqual(original_ptr, actual.str_bytes);
+}
+
+test "strReplaceEachUnsafe: shorter flower" {
+    const str = RocStr.fromSlice("one, two, three, four, five, six");
+    const needle = RocStr.fromSlice(", ");
+    const flower = RocStr.fromSlice(",");
+    defer {
+        needle.decref();
+        flower.decref();
+    }
+
+    const expected = RocStr.fromSlice("one,two,three,four,five,six");
+    defer expected.decref();
+
+    const actual = strReplaceEachUnsafe(str, needle, flower);
+    defer actual.decref();
+
+    try expect(RocStr.eq(actual, expected));
+}
+
+test "strReplaceEachUnsafe: longer flower" {
+    const str = RocStr.fromSlice("aXbXc");
+    const needle = RocStr.fromSlice("X");
+    const flower = RocStr.fromSlice("---");
+    defer {
+        needle.decref();
+        flower.decref();
+    }
+
+    const expected = RocStr.fromSlice("a---b---c");
+    defer expected.decref();
+
+    const actual = strReplaceEachUnsafe(str, needle, flower);
+    defer actual.decref();
+
+    try expect(RocStr.eq(actual, expected));
+}
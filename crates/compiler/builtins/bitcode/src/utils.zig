@@ -2,6 +2,7 @@ const std = @import("std");
 const builtin = @import("builtin");
 const always_inline = std.builtin.CallOptions.Modifier.always_inline;
 const Monotonic = std.builtin.AtomicOrder.Monotonic;
+const panic_help = @import("panic.zig").panic_help;
 
 const DEBUG_INCDEC = false;
 
@@ -9,11 +10,11 @@ pub fn WithOverflow(comptime T: type) type {
     return extern struct { value: T, has_overflowed: bool };
 }
 
-// If allocation fails, this must cxa_throw - it must not return a null pointer!
+// Returns null if allocation fails, which crashes the program with OUT_OF_MEMORY.
 extern fn roc_alloc(size: usize, alignment: u32) callconv(.C) ?*anyopaque;
 
 // This should never be passed a null pointer.
-// If allocation fails, this must cxa_throw - it must not return a null pointer!
+// Returns null if allocation fails, which crashes the program with OUT_OF_MEMORY.
 extern fn roc_realloc(c_ptr: *anyopaque, new_size: usize, old_size: usize, alignment: u32) callconv(.C) ?*anyopaque;
 
 // This should never be passed a null pointer.
@@ -91,18 +92,86 @@ fn testing_roc_memcpy(dest: *anyopaque, src: *anyopaque, bytes: usize) callconv(
     return dest;
 }
 
-pub fn alloc(size: usize, alignment: u32) ?[*]u8 {
-    return @ptrCast(?[*]u8, roc_alloc(size, alignment));
+// The crash tag passed to roc_panic when allocation fails; CrashTag::OutOfMemory in roc_mono
+pub const OUT_OF_MEMORY: u32 = 2;
+
+// Set to 1 by the LLVM backend in `--arena-allocation` builds, for platforms that free
+// everything when the program exits. Small allocations are then carved out of large chunks
+// from roc_alloc, and nothing is ever handed back to roc_dealloc.
+pub var arena_allocation: u8 = 0;
+
+// The size of the chunks requested from roc_alloc. Allocations bigger than a quarter of this
+// get a roc_alloc call of their own, so a chunk never wastes more than a quarter of its space.
+const ARENA_CHUNK_SIZE: usize = 64 * 1024;
+const ARENA_CHUNK_ALIGNMENT: u32 = 16;
+
+// The unused part of the current chunk. Roc code runs on one thread, so these aren't atomic.
+var arena_next: usize = 0;
+var arena_end: usize = 0;
+
+fn arenaAlloc(size: usize, alignment: u32) ?[*]u8 {
+    if (size > ARENA_CHUNK_SIZE / 4) {
+        return @ptrCast(?[*]u8, roc_alloc(size, alignment));
+    }
+
+    var start = std.mem.alignForward(arena_next, alignment);
+
+    if (arena_next == 0 or start + size > arena_end) {
+        const chunk = @ptrCast(?[*]u8, roc_alloc(ARENA_CHUNK_SIZE, ARENA_CHUNK_ALIGNMENT)) orelse return null;
+
+        arena_next = @ptrToInt(chunk);
+        arena_end = arena_next + ARENA_CHUNK_SIZE;
+        start = std.mem.alignForward(arena_next, alignment);
+    }
+
+    arena_next = start + size;
+
+    return @intToPtr([*]u8, start);
+}
+
+pub fn alloc(size: usize, alignment: u32) [*]u8 {
+    const ptr = if (arena_allocation != 0)
+        arenaAlloc(size, alignment)
+    else
+        @ptrCast(?[*]u8, roc_alloc(size, alignment));
+
+    return ptr orelse outOfMemory();
+}
+
+pub fn allocC(size: usize, alignment: u32) callconv(.C) [*]u8 {
+    return alloc(size, alignment);
 }
 
 pub fn realloc(c_ptr: [*]u8, new_size: usize, old_size: usize, alignment: u32) [*]u8 {
-    return @ptrCast([*]u8, roc_realloc(c_ptr, new_size, old_size, alignment));
+    if (arena_allocation != 0) {
+        const new_ptr = alloc(new_size, alignment);
+
+        memcpy(new_ptr, c_ptr, std.math.min(old_size, new_size));
+
+        return new_ptr;
+    }
+
+    return @ptrCast(?[*]u8, roc_realloc(c_ptr, new_size, old_size, alignment)) orelse outOfMemory();
+}
+
+fn outOfMemory() noreturn {
+    // short enough to be a small string, so reporting it doesn't allocate
+    panic_help("Out of memory", OUT_OF_MEMORY);
+    unreachable;
 }
 
 pub fn dealloc(c_ptr: [*]u8, alignment: u32) void {
+    if (arena_allocation != 0) {
+        return;
+    }
+
     return roc_dealloc(c_ptr, alignment);
 }
 
+pub fn deallocC(c_ptr: [*]u8, alignment: u32) callconv(.C) void {
+    return dealloc(c_ptr, alignment);
+}
+
 pub fn memcpy(dst: [*]u8, src: [*]u8, size: usize) void {
     roc_memcpy(dst, src, size);
 }
@@ -373,7 +442,7 @@ pub fn allocateWithRefcount(
     const alignment = std.math.max(ptr_width, element_alignment);
     const length = alignment + data_bytes;
 
-    var new_bytes: [*]u8 = alloc(length, alignment) orelse unreachable;
+    var new_bytes: [*]u8 = alloc(length, alignment);
 
     const data_ptr = new_bytes + alignment;
     const refcount_ptr = @ptrCast([*]usize, @alignCast(ptr_width, data_ptr) - ptr_width);
@@ -403,7 +472,6 @@ pub fn unsafeReallocate(
         return source_ptr;
     }
 
-    // TODO handle out of memory
     // NOTE realloc will dealloc the original allocation
     const old_allocation = source_ptr - align_width;
     const new_allocation = realloc(old_allocation, new_width, old_width, alignment);
@@ -436,3 +504,23 @@ test "increfC, static data" {
     increfRcPtrC(ptr_to_refcount, 2);
     try std.testing.expectEqual(mock_rc, REFCOUNT_MAX_ISIZE);
 }
+
+test "arena allocation" {
+    arena_allocation = 1;
+    defer arena_allocation = 0;
+
+    const first = alloc(3, 1);
+    const second = alloc(8, 8);
+
+    try std.testing.expectEqual(@ptrToInt(first) + 8, @ptrToInt(second));
+
+    // freeing is a no-op, so the next allocation still comes after the last one
+    dealloc(second, 8);
+    const third = alloc(1, 1);
+    try std.testing.expectEqual(@ptrToInt(second) + 8, @ptrToInt(third));
+
+    const chunk = @intToPtr([*]u8, arena_end - ARENA_CHUNK_SIZE);
+    std.testing.allocator.free(chunk[0..ARENA_CHUNK_SIZE]);
+    arena_next = 0;
+    arena_end = 0;
+}
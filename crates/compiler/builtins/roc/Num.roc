@@ -505,6 +505,10 @@ Dec : Num (FloatingPoint Decimal)
 ## Num.toStr 4.2
 ## Num.toStr 4.0
 ## ```
+## [F64] and [F32] values are written with the fewest digits that [Str.toF64] or
+## [Str.toF32] will read back as exactly the same number, so `Num.toStr (0.1f64 + 0.2)`
+## is `"0.30000000000000004"` rather than `"0.3"`.
+##
 ## When this function is given a non-[finite](Num.isFinite)
 ## [F64] or [F32] value, the returned string will be `"NaN"`, `"∞"`, or `"-∞"`.
 ##
@@ -1376,5 +1380,25 @@ toU32Checked : Int * -> Result U32 [OutOfBounds]
 toU64Checked : Int * -> Result U64 [OutOfBounds]
 toU128Checked : Int * -> Result U128 [OutOfBounds]
 toNatChecked : Int * -> Result Nat [OutOfBounds]
+
+## Converts a [Num] to an [F32].
+## If the given number is finite but too big or too small to fit in an [F32], returns
+## `Err OutOfBounds`. Otherwise, the number is rounded to the nearest [F32].
+## ```
+## Num.toF32Checked 1.5f64 == Ok 1.5f32
+## Num.toF32Checked 1e300f64 == Err OutOfBounds
+## ```
 toF32Checked : Num * -> Result F32 [OutOfBounds]
+toF32Checked = \num ->
+    asF64 = toF64 num
+
+    if isFinite asF64 && (asF64 > toF64 maxF32 || asF64 < toF64 minF32) then
+        Err OutOfBounds
+    else
+        Ok (toF32 num)
+
+## Converts a [Num] to an [F64].
+## Every [Num] fits in an [F64], though it may be rounded to the nearest [F64],
+## so this never returns `Err OutOfBounds`.
 toF64Checked : Num * -> Result F64 [OutOfBounds]
+toF64Checked = \num -> Ok (toF64 num)
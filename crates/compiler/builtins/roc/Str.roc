@@ -93,6 +93,7 @@ interface Str
         isEmpty,
         joinWith,
         split,
+        splitOn,
         repeat,
         countGraphemes,
         countUtf8Bytes,
@@ -272,6 +273,15 @@ joinWith : List Str, Str -> Str
 ## ```
 split : Str, Str -> List Str
 
+## Like [Str.split], but consumes the string. The pieces are slices of its
+## buffer, and they take over its reference, so when the string isn't used
+## again this doesn't touch the refcount for each piece.
+## ```
+## expect Str.splitOn "1,2,3" "," == ["1","2","3"]
+## expect Str.splitOn "1,2,3" "" == ["1,2,3"]
+## ```
+splitOn : Str, Str -> List Str
+
 ## Repeats a string the given number of times.
 ## ```
 ## expect Str.repeat "z" 3 == "zzz"
@@ -471,8 +481,14 @@ toDec = \string -> strToNumHelp string
 ## Encode a [Str] to a [F64]. A [F64] value is a 64-bit
 ## [floating-point number](https://en.wikipedia.org/wiki/IEEE_754) and can be
 ## specified with a `f64` suffix.
+##
+## The result is the [F64] closest to the given number, however many digits it has,
+## so this gives back exactly what [Num.toStr] was given. A number too large for [F64]
+## is an `InvalidNumStr` rather than infinity.
 ## ```
 ## expect Str.toF64 "0.10" == Ok 0.10f64
+## expect Str.toF64 "1.5e-3" == Ok 0.0015f64
+## expect Str.toF64 "1e400" == Err InvalidNumStr
 ## expect Str.toF64 "not a number" == Err InvalidNumStr
 ## ```
 toF64 : Str -> Result F64 [InvalidNumStr]
@@ -481,6 +497,8 @@ toF64 = \string -> strToNumHelp string
 ## Encode a [Str] to a [F32].A [F32] value is a 32-bit
 ## [floating-point number](https://en.wikipedia.org/wiki/IEEE_754) and can be
 ## specified with a `f32` suffix.
+##
+## Like [Str.toF64], this rounds to the closest [F32] and rejects numbers too large for it.
 ## ```
 ## expect Str.toF32 "0.10" == Ok 0.10f32
 ## expect Str.toF32 "not a number" == Err InvalidNumStr
@@ -645,30 +663,21 @@ substringUnsafe : Str, Nat, Nat -> Str
 ## ```
 replaceEach : Str, Str, Str -> Result Str [NotFound]
 replaceEach = \haystack, needle, flower ->
-    when splitFirst haystack needle is
-        Ok { before, after } ->
-            # We found at least one needle, so start the buffer off with
-            # `before` followed by the first replacement flower.
-            Str.withCapacity (Str.countUtf8Bytes haystack)
-            |> Str.concat before
-            |> Str.concat flower
-            |> replaceEachHelp after needle flower
-            |> Ok
-
-        Err err -> Err err
-
-replaceEachHelp : Str, Str, Str, Str -> Str
-replaceEachHelp = \buf, haystack, needle, flower ->
-    when splitFirst haystack needle is
-        Ok { before, after } ->
-            buf
-            |> Str.concat before
-            |> Str.concat flower
-            |> replaceEachHelp after needle flower
+    if Str.isEmpty needle then
+        Err NotFound
+    else
+        when firstMatch haystack needle is
+            Some _ -> Ok (replaceEachUnsafe haystack needle flower)
+            None -> Err NotFound
 
-        Err NotFound -> Str.concat buf haystack
+## Replace each occurrence of a non-empty needle. If the string is unique and the flower
+## is no longer than the needle, this reuses the string's buffer instead of allocating.
+replaceEachUnsafe : Str, Str, Str -> Str
 
 expect Str.replaceEach "abXdeXghi" "X" "_" == Ok "ab_de_ghi"
+expect Str.replaceEach "a, b, c" ", " "," == Ok "a,b,c"
+expect Str.replaceEach "a/b" "/" " / " == Ok "a / b"
+expect Str.replaceEach "abc" "" "x" == Err NotFound
 
 ## Returns the given [Str] with the first occurrence of a substring replaced.
 ## Returns [Err NotFound] if the substring is not found.
@@ -878,18 +887,6 @@ walkUtf8WithIndexHelp = \string, state, step, index, length ->
 ## expect result == Ok "hello, world!"
 ## ```
 walkUtf8 : Str, state, (state, U8 -> state) -> state
-walkUtf8 = \str, initial, step ->
-    walkUtf8Help str initial step 0 (Str.countUtf8Bytes str)
-
-walkUtf8Help : Str, state, (state, U8 -> state), Nat, Nat -> state
-walkUtf8Help = \str, state, step, index, length ->
-    if index < length then
-        byte = Str.getUnsafe str index
-        newState = step state byte
-
-        walkUtf8Help str newState step (index + 1) length
-    else
-        state
 
 expect (walkUtf8 "ABC" [] List.append) == [65, 66, 67]
 expect (walkUtf8 "鹏" [] List.append) == [233, 185, 143]
@@ -0,0 +1,57 @@
+## Information about the target a program is being compiled for.
+##
+## Use this to pick between implementations that only work on some targets,
+## for example one for WebAssembly and one for native code:
+## ```
+## readBytes = \path ->
+##     when Target.targetOs is
+##         Wasi -> wasiReadBytes path
+##         _ -> nativeReadBytes path
+## ```
+## The compiler replaces `Target.targetOs` and `Target.targetArchitecture` with
+## the tag for the target, and removes the branches that can't be taken, so
+## this choice has no cost at runtime.
+interface Target
+    exposes [
+        Target,
+        OperatingSystem,
+        Architecture,
+        targetOs,
+        targetArchitecture,
+    ]
+    imports []
+
+## A target a program can be compiled for.
+Target : {
+    architecture : Architecture,
+    operatingSystem : OperatingSystem,
+}
+
+## The operating system family of a target. Linux, macOS and targets without
+## an operating system are all `Unix`.
+OperatingSystem : [
+    Windows,
+    Unix,
+    Wasi,
+]
+
+## The instruction set of a target.
+Architecture : [
+    Aarch32,
+    Aarch64,
+    Wasm32,
+    X86x32,
+    X86x64,
+]
+
+## The operating system of the target being compiled for.
+targetOs : OperatingSystem
+targetOs =
+    # Every use is replaced with the target's tag when the program is compiled.
+    Unix
+
+## The architecture of the target being compiled for.
+targetArchitecture : Architecture
+targetArchitecture =
+    # Every use is replaced with the target's tag when the program is compiled.
+    X86x64
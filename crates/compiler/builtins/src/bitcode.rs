@@ -294,6 +294,11 @@ pub const NUM_MUL_CHECKED_FLOAT: IntrinsicName =
 
 pub const NUM_IS_MULTIPLE_OF: IntrinsicName = int_intrinsic!("roc_builtins.num.is_multiple_of");
 
+pub const NUM_BITWISE_AND: IntrinsicName = int_intrinsic!("roc_builtins.num.bitwise_and");
+pub const NUM_BITWISE_OR: IntrinsicName = int_intrinsic!("roc_builtins.num.bitwise_or");
+pub const NUM_BITWISE_XOR: IntrinsicName = int_intrinsic!("roc_builtins.num.bitwise_xor");
+pub const NUM_SHIFT_LEFT_BY: IntrinsicName = int_intrinsic!("roc_builtins.num.shift_left_by");
+pub const NUM_SHIFT_RIGHT_BY: IntrinsicName = int_intrinsic!("roc_builtins.num.shift_right_by");
 pub const NUM_SHIFT_RIGHT_ZERO_FILL: IntrinsicName =
     int_intrinsic!("roc_builtins.num.shift_right_zero_fill");
 
@@ -344,6 +349,9 @@ pub const STR_WITH_CAPACITY: &str = "roc_builtins.str.with_capacity";
 pub const STR_GRAPHEMES: &str = "roc_builtins.str.graphemes";
 pub const STR_REFCOUNT_PTR: &str = "roc_builtins.str.refcount_ptr";
 pub const STR_RELEASE_EXCESS_CAPACITY: &str = "roc_builtins.str.release_excess_capacity";
+pub const STR_REPLACE_EACH_UNSAFE: &str = "roc_builtins.str.replace_each_unsafe";
+pub const STR_SPLIT_ON: &str = "roc_builtins.str.split_on";
+pub const STR_WALK_UTF8: &str = "roc_builtins.str.walk_utf8";
 
 pub const LIST_MAP: &str = "roc_builtins.list.map";
 pub const LIST_MAP2: &str = "roc_builtins.list.map2";
@@ -383,7 +391,14 @@ pub const DEC_MUL_OR_PANIC: &str = "roc_builtins.dec.mul_or_panic";
 pub const DEC_MUL_SATURATED: &str = "roc_builtins.dec.mul_saturated";
 
 pub const UTILS_TEST_PANIC: &str = "roc_builtins.utils.test_panic";
+pub const UTILS_PANIC: &str = "roc_builtins.utils.panic";
+pub const UTILS_STACK_TRACE_PUSH: &str = "roc_builtins.utils.stack_trace_push";
+pub const UTILS_STACK_TRACE_POP: &str = "roc_builtins.utils.stack_trace_pop";
 pub const UTILS_ALLOCATE_WITH_REFCOUNT: &str = "roc_builtins.utils.allocate_with_refcount";
+pub const UTILS_ALLOC: &str = "roc_builtins.utils.alloc";
+pub const UTILS_DEALLOC: &str = "roc_builtins.utils.dealloc";
+/// A global that is 1 in `--arena-allocation` builds; see `alloc` in utils.zig
+pub const UTILS_ARENA_ALLOCATION: &str = "roc_builtins.utils.arena_allocation";
 pub const UTILS_INCREF_RC_PTR: &str = "roc_builtins.utils.incref_rc_ptr";
 pub const UTILS_DECREF_RC_PTR: &str = "roc_builtins.utils.decref_rc_ptr";
 pub const UTILS_INCREF_DATA_PTR: &str = "roc_builtins.utils.incref_data_ptr";
@@ -59,8 +59,17 @@ fn print_declarations_help<'a>(
                 let function_def = &declarations.function_bodies[f_index.index()].value;
                 toplevel_function(c, f, symbol, function_def, &body.value)
             }
-            DeclarationTag::Expectation => todo!(),
-            DeclarationTag::ExpectationFx => todo!(),
+            DeclarationTag::Expectation => keyword_help(c, f, "expect", &body.value),
+            DeclarationTag::ExpectationFx => keyword_help(c, f, "expect-fx", &body.value),
+            DeclarationTag::Bench => {
+                // the body was wrapped as `result = body` followed by `{}`; print what was written
+                let body = match &body.value {
+                    LetNonRec(def, _) => &def.loc_expr.value,
+                    other => other,
+                };
+
+                keyword_help(c, f, "bench", body)
+            }
             DeclarationTag::Destructure(_) => todo!(),
             DeclarationTag::MutualRecursion { .. } => {
                 // the defs will be printed next
@@ -113,6 +122,19 @@ fn def_symbol_help<'a>(
         .group()
 }
 
+fn keyword_help<'a>(
+    c: &Ctx,
+    f: &'a Arena<'a>,
+    keyword: &'a str,
+    body: &'a Expr,
+) -> DocBuilder<'a, Arena<'a>> {
+    f.text(keyword)
+        .append(f.line())
+        .append(expr(c, EPrec::Free, f, body))
+        .nest(2)
+        .group()
+}
+
 fn def_help<'a>(
     c: &Ctx,
     f: &'a Arena<'a>,
@@ -94,6 +94,7 @@ pub(crate) struct CanDefs {
     dbgs: ExpectsOrDbgs,
     expects: ExpectsOrDbgs,
     expects_fx: ExpectsOrDbgs,
+    benches: ExpectsOrDbgs,
     def_ordering: DefOrdering,
     aliases: VecMap<Symbol, Alias>,
 }
@@ -1024,6 +1025,7 @@ fn canonicalize_value_defs<'a>(
     let mut pending_dbgs = Vec::with_capacity(value_defs.len());
     let mut pending_expects = Vec::with_capacity(value_defs.len());
     let mut pending_expect_fx = Vec::with_capacity(value_defs.len());
+    let mut pending_benches = Vec::with_capacity(value_defs.len());
 
     for loc_pending_def in value_defs {
         match loc_pending_def.value {
@@ -1044,6 +1046,16 @@ fn canonicalize_value_defs<'a>(
             PendingValue::ExpectFx(pending_expect) => {
                 pending_expect_fx.push(pending_expect);
             }
+            PendingValue::Bench(pending_bench) => {
+                // only top-level benches can be run by `roc bench`
+                if let PatternType::TopLevelDef = pattern_type {
+                    pending_benches.push(pending_bench);
+                } else {
+                    env.problem(Problem::BenchNotOnToplevel {
+                        region: loc_pending_def.region,
+                    });
+                }
+            }
         }
     }
 
@@ -1102,6 +1114,7 @@ fn canonicalize_value_defs<'a>(
     let mut dbgs = ExpectsOrDbgs::with_capacity(pending_dbgs.len());
     let mut expects = ExpectsOrDbgs::with_capacity(pending_expects.len());
     let mut expects_fx = ExpectsOrDbgs::with_capacity(pending_expects.len());
+    let mut benches = ExpectsOrDbgs::with_capacity(pending_benches.len());
 
     for pending in pending_dbgs {
         let (loc_can_condition, can_output) = canonicalize_expr(
@@ -1145,11 +1158,26 @@ fn canonicalize_value_defs<'a>(
         output.union(can_output);
     }
 
+    for pending in pending_benches {
+        let (loc_can_condition, can_output) = canonicalize_expr(
+            env,
+            var_store,
+            scope,
+            pending.condition.region,
+            &pending.condition.value,
+        );
+
+        benches.push(loc_can_condition, pending.preceding_comment);
+
+        output.union(can_output);
+    }
+
     let can_defs = CanDefs {
         defs,
         dbgs,
         expects,
         expects_fx,
+        benches,
         def_ordering,
         aliases,
     };
@@ -1560,6 +1588,7 @@ pub(crate) fn sort_can_defs_new(
         dbgs: _,
         expects,
         expects_fx,
+        benches,
         def_ordering,
         aliases,
     } = defs;
@@ -1603,6 +1632,34 @@ pub(crate) fn sort_can_defs_new(
         declarations.push_expect_fx(preceding_comment, name, Loc::at(region, condition));
     }
 
+    let it = benches
+        .conditions
+        .into_iter()
+        .zip(benches.regions)
+        .zip(benches.preceding_comment);
+
+    for ((body, region), preceding_comment) in it {
+        // a `bench` can have any type, so it becomes `result = body` followed by `{}`, which
+        // can be called like an expectation
+        let name = scope.gen_unique_symbol();
+        let result = scope.gen_unique_symbol();
+        let expr_var = var_store.fresh();
+
+        let mut pattern_vars = SendMap::default();
+        pattern_vars.insert(result, expr_var);
+
+        let def = Def {
+            loc_pattern: Loc::at(region, Pattern::Identifier(result)),
+            loc_expr: Loc::at(region, body),
+            expr_var,
+            pattern_vars,
+            annotation: None,
+        };
+        let wrapped = Expr::LetNonRec(Box::new(def), Box::new(Loc::at(region, Expr::EmptyRecord)));
+
+        declarations.push_bench(preceding_comment, name, Loc::at(region, wrapped));
+    }
+
     for (symbol, alias) in aliases.into_iter() {
         output.aliases.insert(symbol, alias);
     }
@@ -1797,6 +1854,7 @@ pub(crate) fn sort_can_defs(
         dbgs,
         expects,
         expects_fx,
+        benches: _,
         def_ordering,
         aliases,
     } = defs;
@@ -2683,6 +2741,7 @@ enum PendingValue<'a> {
     Dbg(PendingExpectOrDbg<'a>),
     Expect(PendingExpectOrDbg<'a>),
     ExpectFx(PendingExpectOrDbg<'a>),
+    Bench(PendingExpectOrDbg<'a>),
     SignatureDefMismatch,
 }
 
@@ -2807,6 +2866,14 @@ fn to_pending_value_def<'a>(
             condition,
             preceding_comment: *preceding_comment,
         }),
+
+        Bench {
+            condition,
+            preceding_comment,
+        } => PendingValue::Bench(PendingExpectOrDbg {
+            condition,
+            preceding_comment: *preceding_comment,
+        }),
     }
 }
 
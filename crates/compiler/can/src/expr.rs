@@ -1375,6 +1375,14 @@ pub fn canonicalize_expr<'a>(
 
             (RuntimeError(problem), Output::default())
         }
+        ast::Expr::TaskAwaitBang(_) => {
+            use roc_problem::can::RuntimeError::*;
+
+            let problem = MisplacedTaskAwaitBang(region);
+            env.problem(Problem::RuntimeError(problem.clone()));
+
+            (RuntimeError(problem), Output::default())
+        }
         &ast::Expr::NonBase10Int {
             string,
             base,
@@ -1420,6 +1428,12 @@ pub fn canonicalize_expr<'a>(
                 bad_expr
             );
         }
+        bad_expr @ ast::Expr::WhenConditions(_) => {
+            internal_error!(
+                "The conditions of a when did not get desugared somehow: {:#?}",
+                bad_expr
+            );
+        }
         bad_expr @ ast::Expr::UnaryOp(_, _) => {
             internal_error!(
                 "A unary operator did not get desugared somehow: {:#?}",
@@ -2686,6 +2700,24 @@ impl Declarations {
         index
     }
 
+    pub fn push_bench(
+        &mut self,
+        preceding_comment: Region,
+        name: Symbol,
+        loc_expr: Loc<Expr>,
+    ) -> usize {
+        let index = self.declarations.len();
+
+        self.declarations.push(DeclarationTag::Bench);
+        self.variables.push(Variable::EMPTY_RECORD);
+        self.symbols.push(Loc::at(preceding_comment, name));
+        self.annotations.push(None);
+
+        self.expressions.push(loc_expr);
+
+        index
+    }
+
     pub fn push_value_def(
         &mut self,
         symbol: Loc<Symbol>,
@@ -2852,7 +2884,7 @@ impl Declarations {
             use crate::expr::DeclarationTag::*;
 
             match self.declarations[index] {
-                Value | Function(_) | Recursive(_) | TailRecursive(_) | Destructure(_) => {
+                Value | Function(_) | Recursive(_) | TailRecursive(_) | Destructure(_) | Bench => {
                     // def pattern has no default expressions, so skip
                     let loc_expr = &self.expressions[index];
 
@@ -2887,6 +2919,8 @@ pub enum DeclarationTag {
     Value,
     Expectation,
     ExpectationFx,
+    /// A top-level `bench`, whose body is wrapped so that it evaluates to `{}`
+    Bench,
     Function(Index<Loc<FunctionDef>>),
     Recursive(Index<Loc<FunctionDef>>),
     TailRecursive(Index<Loc<FunctionDef>>),
@@ -2904,7 +2938,7 @@ impl DeclarationTag {
         match self {
             Function(_) | Recursive(_) | TailRecursive(_) => 1,
             Value => 1,
-            Expectation | ExpectationFx => 1,
+            Expectation | ExpectationFx | Bench => 1,
             Destructure(_) => 1,
             MutualRecursion { length, .. } => length as usize + 1,
         }
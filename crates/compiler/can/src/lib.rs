@@ -23,6 +23,7 @@ pub mod num;
 pub mod operator;
 pub mod pattern;
 pub mod procedure;
+pub mod references;
 pub mod scope;
 pub mod string;
 pub mod traverse;
@@ -7,18 +7,21 @@ use crate::expr::{
     ClosureData, DbgLookup, Declarations, ExpectLookup, Expr, Output, PendingDerives,
 };
 use crate::pattern::{BindingsFromPattern, Pattern};
+use crate::references::ReferenceIndex;
 use crate::scope::Scope;
 use bumpalo::Bump;
 use roc_collections::{MutMap, SendMap, VecMap, VecSet};
 use roc_error_macros::internal_error;
 use roc_module::ident::Ident;
 use roc_module::ident::Lowercase;
+use roc_module::ident::ModuleName;
 use roc_module::symbol::{IdentIds, IdentIdsByModule, ModuleId, ModuleIds, Symbol};
 use roc_parse::ast::{Defs, TypeAnnotation};
 use roc_parse::header::HeaderType;
 use roc_parse::pattern::PatternType;
 use roc_problem::can::{Problem, RuntimeError};
 use roc_region::all::{Loc, Region};
+use roc_serialize::bytes;
 use roc_types::subs::{ExposedTypesStorageSubs, Subs, VarStore, Variable};
 use roc_types::types::{AbilitySet, Alias, AliasKind, AliasVar, Type};
 
@@ -126,6 +129,8 @@ pub struct Module {
     pub module_id: ModuleId,
     pub exposed_imports: MutMap<Symbol, Region>,
     pub exposed_symbols: VecSet<Symbol>,
+    /// aliases exposed as `opaque Name`, which other modules see as opaque types
+    pub opaque_exposes: VecSet<Symbol>,
     pub referenced_values: VecSet<Symbol>,
     pub referenced_types: VecSet<Symbol>,
     /// all aliases. `bool` indicates whether it is exposed
@@ -158,6 +163,7 @@ pub struct ModuleOutput {
     pub scope: Scope,
     pub loc_expects: VecMap<Region, Vec<ExpectLookup>>,
     pub loc_dbgs: VecMap<Symbol, DbgLookup>,
+    pub references: ReferenceIndex,
 }
 
 fn validate_generate_with<'a>(
@@ -310,7 +316,20 @@ pub fn canonicalize_module_defs<'a>(
     // visited a BinOp node we'd recursively try to apply this to each of its nested
     // operators, and then again on *their* nested operators, ultimately applying the
     // rules multiple times unnecessarily.
-    crate::operator::desugar_defs(arena, loc_defs);
+    //
+    // The `!` suffix awaits a Task when this module can refer to `Task.await`,
+    // and a Result otherwise.
+    let imports_task = module_ids
+        .get_id(&ModuleName::from("Task"))
+        .map_or(false, |task_id| dep_idents.get(&task_id).is_some());
+
+    let await_fn = if imports_task {
+        crate::operator::AwaitFn::TaskAwait
+    } else {
+        crate::operator::AwaitFn::ResultTry
+    };
+
+    crate::operator::desugar_defs(arena, loc_defs, await_fn);
 
     let mut rigid_variables = RigidVariables::default();
 
@@ -633,6 +652,7 @@ pub fn canonicalize_module_defs<'a>(
             }
             Expectation => { /* ignore */ }
             ExpectationFx => { /* ignore */ }
+            Bench => { /* ignore */ }
         }
     }
 
@@ -783,7 +803,7 @@ pub fn canonicalize_module_defs<'a>(
                     &mut fix_closures_closure_captures,
                 );
             }
-            ExpectationFx => {
+            ExpectationFx | Bench => {
                 let loc_expr = &mut declarations.expressions[index];
                 fix_values_captured_in_closure_expr(
                     &mut loc_expr.value,
@@ -795,6 +815,7 @@ pub fn canonicalize_module_defs<'a>(
     }
 
     let collected = declarations.expects();
+    let references = ReferenceIndex::build(&declarations);
 
     ModuleOutput {
         scope,
@@ -810,6 +831,7 @@ pub fn canonicalize_module_defs<'a>(
         loc_expects: collected.expects,
         loc_dbgs: collected.dbgs,
         exposed_symbols,
+        references,
     }
 }
 
@@ -1254,6 +1276,16 @@ pub struct TypeState {
     pub solved_implementations: ResolvedImplementations,
 }
 
+/// Bump this whenever the serialized form of [Subs], [AbilitiesStore], or the solved
+/// implementations changes, so that snapshots written by older compilers aren't read.
+const TYPE_SNAPSHOT_VERSION: u32 = 1;
+
+const TYPE_SNAPSHOT_HEADER_SIZE: usize = 24;
+
+/// Every part of a snapshot is read in place, so each one starts at this alignment, which is the
+/// largest alignment of anything in them.
+const TYPE_SNAPSHOT_ALIGNMENT: usize = std::mem::align_of::<u64>();
+
 impl TypeState {
     pub fn serialize(&self, writer: &mut impl std::io::Write) -> std::io::Result<usize> {
         let Self {
@@ -1263,25 +1295,27 @@ impl TypeState {
             solved_implementations,
         } = self;
 
-        let written_subs = subs.serialize(exposed_vars_by_symbol, writer)?;
-        let written_ab = abilities.serialize(writer)?;
+        let written = subs.serialize(exposed_vars_by_symbol, writer)?;
+        let written = bytes::serialize_padding(writer, written, TYPE_SNAPSHOT_ALIGNMENT)?;
+
+        let written = written + abilities.serialize(writer)?;
+        let written = bytes::serialize_padding(writer, written, TYPE_SNAPSHOT_ALIGNMENT)?;
+
         let written_solved_impls =
             crate::abilities::serialize_solved_implementations(solved_implementations, writer)?;
 
-        Ok(written_subs + written_ab + written_solved_impls)
+        Ok(written + written_solved_impls)
     }
 
     pub fn deserialize(bytes: &[u8]) -> (Self, usize) {
         let ((subs, exposed_vars_by_symbol), len_subs) = Subs::deserialize(bytes);
-        let bytes = &bytes[len_subs..];
+        let offset = bytes::next_multiple_of(len_subs, TYPE_SNAPSHOT_ALIGNMENT);
 
-        let (abilities, len_abilities) = AbilitiesStore::deserialize(bytes);
-        let bytes = &bytes[len_abilities..];
+        let (abilities, len_abilities) = AbilitiesStore::deserialize(&bytes[offset..]);
+        let offset = bytes::next_multiple_of(offset + len_abilities, TYPE_SNAPSHOT_ALIGNMENT);
 
         let (solved_implementations, len_solved_impls) =
-            crate::abilities::deserialize_solved_implementations(bytes);
-
-        let total_offset = len_subs + len_abilities + len_solved_impls;
+            crate::abilities::deserialize_solved_implementations(&bytes[offset..]);
 
         (
             Self {
@@ -1290,7 +1324,57 @@ impl TypeState {
                 abilities,
                 solved_implementations,
             },
-            total_offset,
+            offset + len_solved_impls,
         )
     }
+
+    /// Writes these types as a snapshot that [TypeState::deserialize_snapshot] can read back
+    /// without solving them again, e.g. to embed the builtin modules' types in the compiler.
+    pub fn serialize_snapshot(&self, writer: &mut impl std::io::Write) -> std::io::Result<usize> {
+        let mut body = Vec::new();
+        let body_len = self.serialize(&mut body)?;
+
+        writer.write_all(&type_snapshot_header(body_len))?;
+        writer.write_all(&body)?;
+
+        Ok(TYPE_SNAPSHOT_HEADER_SIZE + body_len)
+    }
+
+    /// Reads a snapshot written by [TypeState::serialize_snapshot], in place.
+    ///
+    /// Returns `None` if the snapshot was written by a different version of the compiler, for a
+    /// machine with a different word size or byte order, or if it isn't aligned to 8 bytes.
+    /// The types then have to be solved from source instead.
+    pub fn deserialize_snapshot(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < TYPE_SNAPSHOT_HEADER_SIZE
+            || bytes.as_ptr() as usize % TYPE_SNAPSHOT_ALIGNMENT != 0
+        {
+            return None;
+        }
+
+        let (header, body) = bytes.split_at(TYPE_SNAPSHOT_HEADER_SIZE);
+
+        if header != type_snapshot_header(body.len()) {
+            return None;
+        }
+
+        let (state, len) = Self::deserialize(body);
+        debug_assert_eq!(len, body.len());
+
+        Some(state)
+    }
+}
+
+/// The magic bytes, the snapshot version, the word size, and the length of the rest of the
+/// snapshot. These are all written in native byte order, so a snapshot from a machine with a
+/// different byte order won't match either.
+fn type_snapshot_header(body_len: usize) -> [u8; TYPE_SNAPSHOT_HEADER_SIZE] {
+    let mut header = [0; TYPE_SNAPSHOT_HEADER_SIZE];
+
+    header[..8].copy_from_slice(b"rocsubs\0");
+    header[8..12].copy_from_slice(&TYPE_SNAPSHOT_VERSION.to_ne_bytes());
+    header[12..16].copy_from_slice(&(std::mem::size_of::<usize>() as u32).to_ne_bytes());
+    header[16..].copy_from_slice(&(body_len as u64).to_ne_bytes());
+
+    header
 }
@@ -64,11 +64,37 @@ fn new_op_call_expr<'a>(
     Loc { region, value }
 }
 
-fn desugar_value_def<'a>(arena: &'a Bump, def: &'a ValueDef<'a>) -> ValueDef<'a> {
+/// What the `!` await suffix desugars into: backpassing on `Task.await` in modules
+/// that import a `Task` module, and on `Result.try` everywhere else.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AwaitFn {
+    TaskAwait,
+    ResultTry,
+}
+
+impl AwaitFn {
+    fn module_and_ident(self) -> (&'static str, &'static str) {
+        match self {
+            AwaitFn::TaskAwait => ("Task", "await"),
+            AwaitFn::ResultTry => (ModuleName::RESULT, "try"),
+        }
+    }
+}
+
+fn desugar_value_def<'a>(
+    arena: &'a Bump,
+    def: &'a ValueDef<'a>,
+    await_fn: AwaitFn,
+) -> ValueDef<'a> {
     use ValueDef::*;
 
     match def {
-        Body(loc_pattern, loc_expr) => Body(loc_pattern, desugar_expr(arena, loc_expr)),
+        // Inside a block, `x = f! args` defs have already been turned into backpassing,
+        // so a `!` left here is on a module-level def and its whole body is the tail.
+        Body(loc_pattern, loc_expr) => Body(
+            loc_pattern,
+            desugar_expr(arena, strip_trailing_await(arena, loc_expr), await_fn),
+        ),
         ann @ Annotation(_, _) => *ann,
         AnnotatedBody {
             ann_pattern,
@@ -81,13 +107,13 @@ fn desugar_value_def<'a>(arena: &'a Bump, def: &'a ValueDef<'a>) -> ValueDef<'a>
             ann_type,
             comment: *comment,
             body_pattern,
-            body_expr: desugar_expr(arena, body_expr),
+            body_expr: desugar_expr(arena, body_expr, await_fn),
         },
         Dbg {
             condition,
             preceding_comment,
         } => {
-            let desugared_condition = &*arena.alloc(desugar_expr(arena, condition));
+            let desugared_condition = &*arena.alloc(desugar_expr(arena, condition, await_fn));
             Dbg {
                 condition: desugared_condition,
                 preceding_comment: *preceding_comment,
@@ -97,7 +123,7 @@ fn desugar_value_def<'a>(arena: &'a Bump, def: &'a ValueDef<'a>) -> ValueDef<'a>
             condition,
             preceding_comment,
         } => {
-            let desugared_condition = &*arena.alloc(desugar_expr(arena, condition));
+            let desugared_condition = &*arena.alloc(desugar_expr(arena, condition, await_fn));
             Expect {
                 condition: desugared_condition,
                 preceding_comment: *preceding_comment,
@@ -107,24 +133,39 @@ fn desugar_value_def<'a>(arena: &'a Bump, def: &'a ValueDef<'a>) -> ValueDef<'a>
             condition,
             preceding_comment,
         } => {
-            let desugared_condition = &*arena.alloc(desugar_expr(arena, condition));
+            let desugared_condition = &*arena.alloc(desugar_expr(arena, condition, await_fn));
             ExpectFx {
                 condition: desugared_condition,
                 preceding_comment: *preceding_comment,
             }
         }
+        Bench {
+            condition,
+            preceding_comment,
+        } => {
+            let desugared_condition = &*arena.alloc(desugar_expr(arena, condition, await_fn));
+            Bench {
+                condition: desugared_condition,
+                preceding_comment: *preceding_comment,
+            }
+        }
     }
 }
 
-pub fn desugar_defs<'a>(arena: &'a Bump, defs: &mut roc_parse::ast::Defs<'a>) {
+pub fn desugar_defs<'a>(arena: &'a Bump, defs: &mut roc_parse::ast::Defs<'a>, await_fn: AwaitFn) {
     for value_def in defs.value_defs.iter_mut() {
-        *value_def = desugar_value_def(arena, arena.alloc(*value_def));
+        *value_def = desugar_value_def(arena, arena.alloc(*value_def), await_fn);
     }
 }
 
 /// Reorder the expression tree based on operator precedence and associativity rules,
-/// then replace the BinOp nodes with Apply nodes. Also drop SpaceBefore and SpaceAfter nodes.
-pub fn desugar_expr<'a>(arena: &'a Bump, loc_expr: &'a Loc<Expr<'a>>) -> &'a Loc<Expr<'a>> {
+/// then replace the BinOp nodes with Apply nodes. Also drop SpaceBefore and SpaceAfter nodes,
+/// and turn `x = f! arg` defs into backpassing on `await_fn`.
+pub fn desugar_expr<'a>(
+    arena: &'a Bump,
+    loc_expr: &'a Loc<Expr<'a>>,
+    await_fn: AwaitFn,
+) -> &'a Loc<Expr<'a>> {
     match &loc_expr.value {
         Float(..)
         | Num(..)
@@ -139,6 +180,7 @@ pub fn desugar_expr<'a>(arena: &'a Bump, loc_expr: &'a Loc<Expr<'a>>) -> &'a Loc
         | PrecedenceConflict { .. }
         | MultipleRecordBuilders { .. }
         | UnappliedRecordBuilder { .. }
+        | TaskAwaitBang(_)
         | Tag(_)
         | OpaqueRef(_)
         | IngestedFile(_, _)
@@ -150,7 +192,10 @@ pub fn desugar_expr<'a>(arena: &'a Bump, loc_expr: &'a Loc<Expr<'a>>) -> &'a Loc
                 region,
                 value: **sub_expr,
             };
-            let value = TupleAccess(&desugar_expr(arena, arena.alloc(loc_sub_expr)).value, paths);
+            let value = TupleAccess(
+                &desugar_expr(arena, arena.alloc(loc_sub_expr), await_fn).value,
+                paths,
+            );
 
             arena.alloc(Loc { region, value })
         }
@@ -160,7 +205,10 @@ pub fn desugar_expr<'a>(arena: &'a Bump, loc_expr: &'a Loc<Expr<'a>>) -> &'a Loc
                 region,
                 value: **sub_expr,
             };
-            let value = RecordAccess(&desugar_expr(arena, arena.alloc(loc_sub_expr)).value, paths);
+            let value = RecordAccess(
+                &desugar_expr(arena, arena.alloc(loc_sub_expr), await_fn).value,
+                paths,
+            );
 
             arena.alloc(Loc { region, value })
         }
@@ -168,7 +216,7 @@ pub fn desugar_expr<'a>(arena: &'a Bump, loc_expr: &'a Loc<Expr<'a>>) -> &'a Loc
             let mut new_items = Vec::with_capacity_in(items.len(), arena);
 
             for item in items.iter() {
-                new_items.push(desugar_expr(arena, item));
+                new_items.push(desugar_expr(arena, item, await_fn));
             }
             let new_items = new_items.into_bump_slice();
             let value: Expr<'a> = List(items.replace_items(new_items));
@@ -181,7 +229,7 @@ pub fn desugar_expr<'a>(arena: &'a Bump, loc_expr: &'a Loc<Expr<'a>>) -> &'a Loc
         Record(fields) => arena.alloc(Loc {
             region: loc_expr.region,
             value: Record(fields.map_items(arena, |field| {
-                let value = desugar_field(arena, &field.value);
+                let value = desugar_field(arena, &field.value, await_fn);
                 Loc {
                     value,
                     region: field.region,
@@ -190,15 +238,29 @@ pub fn desugar_expr<'a>(arena: &'a Bump, loc_expr: &'a Loc<Expr<'a>>) -> &'a Loc
         }),
         Tuple(fields) => arena.alloc(Loc {
             region: loc_expr.region,
-            value: Tuple(fields.map_items(arena, |field| desugar_expr(arena, field))),
+            value: Tuple(fields.map_items(arena, |field| desugar_expr(arena, field, await_fn))),
         }),
+        WhenConditions(conditions) => {
+            // `when a, b is` matches on the tuple `(a, b)`
+            let conditions = Vec::from_iter_in(
+                conditions
+                    .iter()
+                    .map(|condition| &*desugar_expr(arena, condition, await_fn)),
+                arena,
+            );
+
+            arena.alloc(Loc {
+                region: loc_expr.region,
+                value: Tuple(Collection::with_items(conditions.into_bump_slice())),
+            })
+        }
         RecordUpdate { fields, update } => {
             // NOTE the `update` field is always a `Var { .. }`, we only desugar it to get rid of
             // any spaces before/after
-            let new_update = desugar_expr(arena, update);
+            let new_update = desugar_expr(arena, update, await_fn);
 
             let new_fields = fields.map_items(arena, |field| {
-                let value = desugar_field(arena, &field.value);
+                let value = desugar_field(arena, &field.value, await_fn);
                 Loc {
                     value,
                     region: field.region,
@@ -215,7 +277,10 @@ pub fn desugar_expr<'a>(arena: &'a Bump, loc_expr: &'a Loc<Expr<'a>>) -> &'a Loc
         }
         Closure(loc_patterns, loc_ret) => arena.alloc(Loc {
             region: loc_expr.region,
-            value: Closure(loc_patterns, desugar_expr(arena, loc_ret)),
+            value: Closure(
+                loc_patterns,
+                desugar_expr(arena, strip_trailing_await(arena, loc_ret), await_fn),
+            ),
         }),
         Backpassing(loc_patterns, loc_body, loc_ret) => {
             // loc_patterns <- loc_body
@@ -223,9 +288,9 @@ pub fn desugar_expr<'a>(arena: &'a Bump, loc_expr: &'a Loc<Expr<'a>>) -> &'a Loc
             // loc_ret
 
             // first desugar the body, because it may contain |>
-            let desugared_body = desugar_expr(arena, loc_body);
+            let desugared_body = desugar_expr(arena, loc_body, await_fn);
 
-            let desugared_ret = desugar_expr(arena, loc_ret);
+            let desugared_ret = desugar_expr(arena, strip_trailing_await(arena, loc_ret), await_fn);
             let closure = Expr::Closure(loc_patterns, desugared_ret);
             let loc_closure = Loc::at(loc_expr.region, closure);
 
@@ -258,12 +323,18 @@ pub fn desugar_expr<'a>(arena: &'a Bump, loc_expr: &'a Loc<Expr<'a>>) -> &'a Loc
             value: UnappliedRecordBuilder(loc_expr),
             region: loc_expr.region,
         }),
-        BinOps(lefts, right) => desugar_bin_ops(arena, loc_expr.region, lefts, right),
+        BinOps(lefts, right) => desugar_bin_ops(arena, loc_expr.region, lefts, right, await_fn),
         Defs(defs, loc_ret) => {
+            if let Some(awaited) =
+                split_first_await(arena, loc_expr.region, defs, loc_ret, await_fn)
+            {
+                return desugar_expr(arena, awaited, await_fn);
+            }
+
             let mut defs = (*defs).clone();
-            desugar_defs(arena, &mut defs);
+            desugar_defs(arena, &mut defs, await_fn);
 
-            let loc_ret = desugar_expr(arena, loc_ret);
+            let loc_ret = desugar_expr(arena, strip_trailing_await(arena, loc_ret), await_fn);
 
             arena.alloc(Loc::at(loc_expr.region, Defs(arena.alloc(defs), loc_ret)))
         }
@@ -295,13 +366,17 @@ pub fn desugar_expr<'a>(arena: &'a Bump, loc_expr: &'a Loc<Expr<'a>>) -> &'a Loc
                     }
                 };
 
-                desugared_args.push(desugar_expr(arena, arg));
+                desugared_args.push(desugar_expr(arena, arg, await_fn));
             }
 
             let desugared_args = desugared_args.into_bump_slice();
 
             let mut apply: &Loc<Expr> = arena.alloc(Loc {
-                value: Apply(desugar_expr(arena, loc_fn), desugared_args, *called_via),
+                value: Apply(
+                    desugar_expr(arena, loc_fn, await_fn),
+                    desugared_args,
+                    *called_via,
+                ),
                 region: loc_expr.region,
             });
 
@@ -310,7 +385,7 @@ pub fn desugar_expr<'a>(arena: &'a Bump, loc_expr: &'a Loc<Expr<'a>>) -> &'a Loc
 
                 Some(apply_exprs) => {
                     for expr in apply_exprs {
-                        let desugared_expr = desugar_expr(arena, expr);
+                        let desugared_expr = desugar_expr(arena, expr, await_fn);
 
                         let args = std::slice::from_ref(arena.alloc(apply));
 
@@ -325,17 +400,17 @@ pub fn desugar_expr<'a>(arena: &'a Bump, loc_expr: &'a Loc<Expr<'a>>) -> &'a Loc
             apply
         }
         When(loc_cond_expr, branches) => {
-            let loc_desugared_cond = &*arena.alloc(desugar_expr(arena, loc_cond_expr));
+            let loc_desugared_cond = &*arena.alloc(desugar_expr(arena, loc_cond_expr, await_fn));
             let mut desugared_branches = Vec::with_capacity_in(branches.len(), arena);
 
             for branch in branches.iter() {
-                let desugared = desugar_expr(arena, &branch.value);
+                let desugared = desugar_expr(arena, &branch.value, await_fn);
 
                 let mut alternatives = Vec::with_capacity_in(branch.patterns.len(), arena);
                 alternatives.extend(branch.patterns.iter().copied());
 
                 let desugared_guard = if let Some(guard) = &branch.guard {
-                    Some(*desugar_expr(arena, guard))
+                    Some(*desugar_expr(arena, guard, await_fn))
                 } else {
                     None
                 };
@@ -375,7 +450,7 @@ pub fn desugar_expr<'a>(arena: &'a Bump, loc_expr: &'a Loc<Expr<'a>>) -> &'a Loc
                 },
             };
             let loc_fn_var = arena.alloc(Loc { region, value });
-            let desugared_args = arena.alloc([desugar_expr(arena, loc_arg)]);
+            let desugared_args = arena.alloc([desugar_expr(arena, loc_arg, await_fn)]);
 
             arena.alloc(Loc {
                 value: Apply(loc_fn_var, desugared_args, CalledVia::UnaryOp(op)),
@@ -391,18 +466,20 @@ pub fn desugar_expr<'a>(arena: &'a Bump, loc_expr: &'a Loc<Expr<'a>>) -> &'a Loc
                     value: **expr,
                     region: loc_expr.region,
                 }),
+                await_fn,
             )
         }
         If(if_thens, final_else_branch) => {
             // If does not get desugared into `when` so we can give more targeted error messages during type checking.
-            let desugared_final_else = &*arena.alloc(desugar_expr(arena, final_else_branch));
+            let desugared_final_else =
+                &*arena.alloc(desugar_expr(arena, final_else_branch, await_fn));
 
             let mut desugared_if_thens = Vec::with_capacity_in(if_thens.len(), arena);
 
             for (condition, then_branch) in if_thens.iter() {
                 desugared_if_thens.push((
-                    *desugar_expr(arena, condition),
-                    *desugar_expr(arena, then_branch),
+                    *desugar_expr(arena, condition, await_fn),
+                    *desugar_expr(arena, then_branch, await_fn),
                 ));
             }
 
@@ -412,16 +489,16 @@ pub fn desugar_expr<'a>(arena: &'a Bump, loc_expr: &'a Loc<Expr<'a>>) -> &'a Loc
             })
         }
         Expect(condition, continuation) => {
-            let desugared_condition = &*arena.alloc(desugar_expr(arena, condition));
-            let desugared_continuation = &*arena.alloc(desugar_expr(arena, continuation));
+            let desugared_condition = &*arena.alloc(desugar_expr(arena, condition, await_fn));
+            let desugared_continuation = &*arena.alloc(desugar_expr(arena, continuation, await_fn));
             arena.alloc(Loc {
                 value: Expect(desugared_condition, desugared_continuation),
                 region: loc_expr.region,
             })
         }
         Dbg(condition, continuation) => {
-            let desugared_condition = &*arena.alloc(desugar_expr(arena, condition));
-            let desugared_continuation = &*arena.alloc(desugar_expr(arena, continuation));
+            let desugared_condition = &*arena.alloc(desugar_expr(arena, condition, await_fn));
+            let desugared_continuation = &*arena.alloc(desugar_expr(arena, continuation, await_fn));
             arena.alloc(Loc {
                 value: Dbg(desugared_condition, desugared_continuation),
                 region: loc_expr.region,
@@ -433,6 +510,7 @@ pub fn desugar_expr<'a>(arena: &'a Bump, loc_expr: &'a Loc<Expr<'a>>) -> &'a Loc
 fn desugar_field<'a>(
     arena: &'a Bump,
     field: &'a AssignedField<'a, Expr<'a>>,
+    await_fn: AwaitFn,
 ) -> AssignedField<'a, Expr<'a>> {
     use roc_parse::ast::AssignedField::*;
 
@@ -443,7 +521,7 @@ fn desugar_field<'a>(
                 region: loc_str.region,
             },
             spaces,
-            desugar_expr(arena, loc_expr),
+            desugar_expr(arena, loc_expr, await_fn),
         ),
         OptionalValue(loc_str, spaces, loc_expr) => OptionalValue(
             Loc {
@@ -451,7 +529,7 @@ fn desugar_field<'a>(
                 region: loc_str.region,
             },
             spaces,
-            desugar_expr(arena, loc_expr),
+            desugar_expr(arena, loc_expr, await_fn),
         ),
         LabelOnly(loc_str) => {
             // Desugar { x } into { x: x }
@@ -469,16 +547,141 @@ fn desugar_field<'a>(
                     region: loc_str.region,
                 },
                 &[],
-                desugar_expr(arena, arena.alloc(loc_expr)),
+                desugar_expr(arena, arena.alloc(loc_expr), await_fn),
             )
         }
-        SpaceBefore(field, _spaces) => desugar_field(arena, field),
-        SpaceAfter(field, _spaces) => desugar_field(arena, field),
+        SpaceBefore(field, _spaces) => desugar_field(arena, field, await_fn),
+        SpaceAfter(field, _spaces) => desugar_field(arena, field, await_fn),
 
         Malformed(string) => Malformed(string),
     }
 }
 
+/// If `expr` is `f!` or `f! args`, returns the call without the `!`, i.e. `f` or `f args`.
+fn without_await<'a>(arena: &'a Bump, expr: &'a Expr<'a>) -> Option<Expr<'a>> {
+    match expr {
+        TaskAwaitBang(sub_expr) => Some(**sub_expr),
+        Apply(loc_fn, loc_args, called_via) => match without_await(arena, &loc_fn.value) {
+            Some(function) => {
+                let loc_fn = arena.alloc(Loc::at(loc_fn.region, function));
+
+                Some(Apply(loc_fn, *loc_args, *called_via))
+            }
+            None => None,
+        },
+        SpaceBefore(sub_expr, _) | SpaceAfter(sub_expr, _) | ParensAround(sub_expr) => {
+            without_await(arena, sub_expr)
+        }
+        _ => None,
+    }
+}
+
+/// The last expression of a block is returned as-is, so awaiting it would only
+/// unwrap and rewrap its result. Drop the `!` there: `f! args` becomes `f args`.
+fn strip_trailing_await<'a>(arena: &'a Bump, loc_expr: &'a Loc<Expr<'a>>) -> &'a Loc<Expr<'a>> {
+    match without_await(arena, &loc_expr.value) {
+        Some(value) => arena.alloc(Loc::at(loc_expr.region, value)),
+        None => loc_expr,
+    }
+}
+
+/// Finds the first `pattern = f! args` def and rewrites the block around it into
+///
+/// ```text
+/// <defs before it>
+/// pattern <- await_fn (f args)
+/// <defs after it>
+/// loc_ret
+/// ```
+///
+/// so that the rest of the block runs in the continuation of `f args`.
+fn split_first_await<'a>(
+    arena: &'a Bump,
+    region: Region,
+    defs: &'a roc_parse::ast::Defs<'a>,
+    loc_ret: &'a Loc<Expr<'a>>,
+    await_fn: AwaitFn,
+) -> Option<&'a Loc<Expr<'a>>> {
+    let (split_index, loc_pattern, loc_call) =
+        defs.tags.iter().enumerate().find_map(|(index, tag)| {
+            let value_def = &defs.value_defs[tag.split().err()?.index()];
+
+            match value_def {
+                ValueDef::Body(loc_pattern, loc_body) => {
+                    let call = without_await(arena, &loc_body.value)?;
+
+                    Some((index, *loc_pattern, Loc::at(loc_body.region, call)))
+                }
+                _ => None,
+            }
+        })?;
+
+    let mut before = roc_parse::ast::Defs::default();
+    let mut after = roc_parse::ast::Defs::default();
+
+    for (index, tag) in defs.tags.iter().enumerate() {
+        let target = match index.cmp(&split_index) {
+            std::cmp::Ordering::Less => &mut before,
+            std::cmp::Ordering::Equal => continue,
+            std::cmp::Ordering::Greater => &mut after,
+        };
+
+        let def_region = defs.regions[index];
+        let spaces_before = &defs.spaces[defs.space_before[index].indices()];
+        let spaces_after = &defs.spaces[defs.space_after[index].indices()];
+
+        match tag.split() {
+            Ok(type_index) => target.push_type_def(
+                defs.type_defs[type_index.index()],
+                def_region,
+                spaces_before,
+                spaces_after,
+            ),
+            Err(value_index) => target.push_value_def(
+                defs.value_defs[value_index.index()],
+                def_region,
+                spaces_before,
+                spaces_after,
+            ),
+        }
+    }
+
+    let continuation = if after.is_empty() {
+        loc_ret
+    } else {
+        let continuation_region = Region::span_across(&after.regions[0], &loc_ret.region);
+
+        arena.alloc(Loc::at(
+            continuation_region,
+            Defs(arena.alloc(after), loc_ret),
+        ))
+    };
+
+    let (module_name, ident) = await_fn.module_and_ident();
+    let loc_await = arena.alloc(Loc::at(loc_call.region, Var { module_name, ident }));
+    let loc_body = arena.alloc(Loc::at(
+        loc_call.region,
+        Apply(
+            loc_await,
+            arena.alloc([&*arena.alloc(loc_call)]),
+            CalledVia::Space,
+        ),
+    ));
+
+    let backpassing = Backpassing(std::slice::from_ref(loc_pattern), loc_body, continuation);
+
+    if before.is_empty() {
+        Some(arena.alloc(Loc::at(region, backpassing)))
+    } else {
+        let loc_backpassing = arena.alloc(Loc::at(
+            Region::span_across(&loc_pattern.region, &continuation.region),
+            backpassing,
+        ));
+
+        Some(arena.alloc(Loc::at(region, Defs(arena.alloc(before), loc_backpassing))))
+    }
+}
+
 struct RecordBuilderArg<'a> {
     closure: &'a Loc<Expr<'a>>,
     apply_exprs: Vec<'a, &'a Loc<Expr<'a>>>,
@@ -595,19 +798,20 @@ fn desugar_bin_ops<'a>(
     whole_region: Region,
     lefts: &'a [(Loc<Expr<'_>>, Loc<BinOp>)],
     right: &'a Loc<Expr<'_>>,
+    await_fn: AwaitFn,
 ) -> &'a Loc<Expr<'a>> {
     let mut arg_stack: Vec<&'a Loc<Expr>> = Vec::with_capacity_in(lefts.len() + 1, arena);
     let mut op_stack: Vec<Loc<BinOp>> = Vec::with_capacity_in(lefts.len(), arena);
 
     for (loc_expr, loc_op) in lefts {
-        arg_stack.push(desugar_expr(arena, loc_expr));
+        arg_stack.push(desugar_expr(arena, loc_expr, await_fn));
         match run_binop_step(arena, whole_region, &mut arg_stack, &mut op_stack, *loc_op) {
             Err(problem) => return problem,
             Ok(()) => continue,
         }
     }
 
-    let mut expr = desugar_expr(arena, right);
+    let mut expr = desugar_expr(arena, right, await_fn);
 
     for (left, loc_op) in arg_stack.into_iter().zip(op_stack.into_iter()).rev() {
         expr = arena.alloc(new_op_call_expr(arena, left, loc_op, expr));
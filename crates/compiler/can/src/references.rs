@@ -0,0 +1,118 @@
+//! Where every symbol is mentioned in a module, built once canonicalization is done so that
+//! "find all references" doesn't have to walk the canonical IR of every module again.
+use roc_collections::MutMap;
+use roc_module::symbol::Symbol;
+use roc_region::all::{Position, Region};
+use roc_types::subs::Variable;
+
+use crate::expr::{Declarations, Expr};
+use crate::pattern::{DestructType, Pattern, RecordDestruct};
+use crate::traverse::{walk_expr, walk_pattern, walk_record_destruct, Visitor};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceKind {
+    /// The symbol is bound here, by a def, an argument, or a pattern.
+    Definition,
+    /// The symbol is looked up here.
+    Usage,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reference {
+    pub region: Region,
+    pub kind: ReferenceKind,
+}
+
+/// The references to every symbol mentioned in one module, including symbols of other modules.
+#[derive(Debug, Clone, Default)]
+pub struct ReferenceIndex {
+    references: MutMap<Symbol, Vec<Reference>>,
+}
+
+impl ReferenceIndex {
+    pub fn build(decls: &Declarations) -> Self {
+        let mut collector = Collector {
+            index: ReferenceIndex::default(),
+        };
+
+        collector.visit_decls(decls);
+
+        let mut index = collector.index;
+
+        for references in index.references.values_mut() {
+            references.sort_by_key(|reference| reference.region);
+            references.dedup();
+        }
+
+        index
+    }
+
+    /// The references to `symbol` in this module, in source order.
+    pub fn references(&self, symbol: Symbol) -> &[Reference] {
+        self.references
+            .get(&symbol)
+            .map(|references| references.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// The symbol defined or used at `position`, if any.
+    pub fn symbol_at(&self, position: Position) -> Option<Symbol> {
+        let at = Region::new(position, position);
+
+        self.references.iter().find_map(|(symbol, references)| {
+            references
+                .iter()
+                .any(|reference| reference.region.contains(&at))
+                .then_some(*symbol)
+        })
+    }
+
+    pub fn symbols(&self) -> impl Iterator<Item = Symbol> + '_ {
+        self.references.keys().copied()
+    }
+
+    fn add(&mut self, symbol: Symbol, region: Region, kind: ReferenceKind) {
+        self.references
+            .entry(symbol)
+            .or_default()
+            .push(Reference { region, kind });
+    }
+}
+
+struct Collector {
+    index: ReferenceIndex,
+}
+
+impl Visitor for Collector {
+    fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+        if let Expr::Var(symbol, _) | Expr::AbilityMember(symbol, _, _) = expr {
+            self.index.add(*symbol, region, ReferenceKind::Usage);
+        }
+
+        walk_expr(self, expr, var);
+    }
+
+    fn visit_pattern(&mut self, pattern: &Pattern, region: Region, _opt_var: Option<Variable>) {
+        match pattern {
+            Pattern::Identifier(symbol)
+            | Pattern::Shadowed(_, _, symbol)
+            | Pattern::AbilityMemberSpecialization { ident: symbol, .. } => {
+                self.index.add(*symbol, region, ReferenceKind::Definition);
+            }
+            _ => walk_pattern(self, pattern),
+        }
+    }
+
+    fn visit_record_destruct(&mut self, destruct: &RecordDestruct, region: Region) {
+        match destruct.typ {
+            // with a guard, the symbol is bound by the guard pattern instead
+            DestructType::Guard(..) => {}
+            DestructType::Required | DestructType::Optional(..) => {
+                self.index
+                    .add(destruct.symbol, region, ReferenceKind::Definition);
+            }
+        }
+
+        walk_record_destruct(self, destruct);
+    }
+}
@@ -89,13 +89,14 @@ impl Scope {
         match self.scope_contains_ident(ident) {
             InScope(symbol, _) => Ok(symbol),
             NotInScope(_) | NotPresent => {
-                let error = RuntimeError::LookupNotInScope(
-                    Loc {
+                let error = RuntimeError::LookupNotInScope {
+                    loc_name: Loc {
                         region,
                         value: Ident::from(ident),
                     },
-                    self.idents_in_scope().map(|v| v.as_ref().into()).collect(),
-                );
+                    suggestion_options: self.idents_in_scope().map(|v| v.as_ref().into()).collect(),
+                    exposed_by: Vec::new(),
+                };
 
                 Err(error)
             }
@@ -53,7 +53,7 @@ pub fn can_expr_with(arena: &Bump, home: ModuleId, expr_str: &str) -> CanExprOut
     // visited a BinOp node we'd recursively try to apply this to each of its nested
     // operators, and then again on *their* nested operators, ultimately applying the
     // rules multiple times unnecessarily.
-    let loc_expr = operator::desugar_expr(arena, &loc_expr);
+    let loc_expr = operator::desugar_expr(arena, &loc_expr, operator::AwaitFn::ResultTry);
 
     let mut scope = Scope::new(home, IdentIds::default(), Default::default());
     scope.add_alias(
@@ -332,7 +332,7 @@ mod test_can {
             matches!(
                 problem,
                 Problem::SignatureDefMismatch { .. }
-                    | Problem::RuntimeError(RuntimeError::LookupNotInScope(_, _))
+                    | Problem::RuntimeError(RuntimeError::LookupNotInScope { .. })
             )
         }));
     }
@@ -360,7 +360,7 @@ mod test_can {
             matches!(
                 problem,
                 Problem::SignatureDefMismatch { .. }
-                    | Problem::RuntimeError(RuntimeError::LookupNotInScope(_, _))
+                    | Problem::RuntimeError(RuntimeError::LookupNotInScope { .. })
             )
         }));
     }
@@ -823,6 +823,92 @@ mod test_can {
         ));
     }
 
+    // AWAIT SUFFIX
+    #[test]
+    fn task_await_bang_desugar() {
+        let src = indoc!(
+            r#"
+                f = \_ -> crash "f"
+
+                x = f! 1
+                y = f! x
+
+                f! y
+            "#
+        );
+        let arena = Bump::new();
+        let out = can_expr_with(&arena, test_home(), src);
+
+        assert_eq!(out.problems.len(), 0);
+
+        // Assert that we desugar to:
+        //
+        // Result.try (f 1) \x -> Result.try (f x) \y -> f y
+
+        // Result.try (f 1) ..
+        let (f_one, x_closure) = simplify_await_call(&out.loc_expr.value);
+        match simplify_curried_call(f_one) {
+            (Var(sym, _), Num(_, num_str, _, _)) => {
+                assert_eq!(sym.as_str(&out.interns), "f");
+                assert_eq!(num_str.to_string(), "1");
+            }
+            call => panic!("Not a valid (f 1) call: {:?}", call),
+        }
+
+        // \x -> Result.try (f x) ..
+        let (x_sym, x_body) = simplify_builder_closure(x_closure);
+        let (f_x, y_closure) = simplify_await_call(x_body);
+        match simplify_curried_call(f_x) {
+            (Var(sym, _), Var(arg, _)) => {
+                assert_eq!(sym.as_str(&out.interns), "f");
+                assert_eq!(*arg, x_sym);
+            }
+            call => panic!("Not a valid (f x) call: {:?}", call),
+        }
+
+        // \y -> f y
+        let (y_sym, y_body) = simplify_builder_closure(y_closure);
+        match simplify_curried_call(y_body) {
+            (Var(sym, _), Var(arg, _)) => {
+                assert_eq!(sym.as_str(&out.interns), "f");
+                assert_eq!(*arg, y_sym);
+            }
+            call => panic!("Not a valid (f y) call: {:?}", call),
+        }
+    }
+
+    fn simplify_await_call(expr: &Expr) -> (&Expr, &Expr) {
+        match expr {
+            LetNonRec(_, loc_expr) | LetRec(_, loc_expr, _) => simplify_await_call(&loc_expr.value),
+            Call(fun, args, _) => {
+                match &fun.1.value {
+                    Var(sym, _) => assert_eq!(*sym, Symbol::RESULT_TRY),
+                    fun => panic!("Not calling Result.try: {:?}", fun),
+                }
+
+                (&args[0].1.value, &args[1].1.value)
+            }
+            _ => panic!("Final Expr is not a Call: {:?}", expr),
+        }
+    }
+
+    #[test]
+    fn misplaced_task_await_bang() {
+        let src = indoc!(
+            r#"
+                Num.add (Num.abs! 1) 2
+            "#
+        );
+        let arena = Bump::new();
+        let CanExprOut { problems, .. } = can_expr_with(&arena, test_home(), src);
+
+        assert_eq!(problems.len(), 1);
+        assert!(problems.iter().all(|problem| matches!(
+            problem,
+            Problem::RuntimeError(roc_problem::can::RuntimeError::MisplacedTaskAwaitBang { .. })
+        )));
+    }
+
     // TAIL CALLS
     fn get_closure(expr: &Expr, i: usize) -> roc_can::expr::Recursive {
         match expr {
@@ -0,0 +1,373 @@
+//! A library interface to building Roc apps, for build tools, editors, and applications that
+//! embed the compiler, so they don't have to run the `roc` binary.
+//!
+//! ```no_run
+//! use roc_compile::{compile, CancellationToken, CompileRequest, Emit};
+//!
+//! let request = CompileRequest::new("main.roc".into(), Emit::Executable);
+//! let cancellation = CancellationToken::new();
+//!
+//! match compile(request, |progress| eprintln!("{:?}", progress), &cancellation) {
+//!     Ok(result) => {
+//!         for report in result.problems.reports.iter() {
+//!             eprintln!("{}", report);
+//!         }
+//!     }
+//!     Err(error) => eprintln!("{:?}", error),
+//! }
+//! ```
+//!
+//! Nothing is printed. Problems in the Roc code are rendered without colors, into
+//! [Problems::reports] of the result or of [CompileError::Problems].
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bumpalo::Bump;
+use roc_build::link::{LinkType, LinkingStrategy};
+use roc_build::program::{
+    build_loaded_file, check_load_config, render_problems_typechecked, standard_load_config,
+    BuildFileError, BuildOrdering, CodeGenBackend, CodeGenOptions,
+};
+use roc_gen_dev::AssemblyBackendMode;
+use roc_gen_llvm::llvm::build::LlvmBackendMode;
+use roc_gen_wasm::WasmFeatures;
+use roc_load::{LoadConfig, LoadMonomorphizedError, LoadingProblem, Threading};
+use roc_mono::ir::OptLevel;
+use roc_mono::overflow::OverflowMode;
+use roc_packaging::cache::{self, RocCacheDir};
+use roc_problem::lint::SeverityConfig;
+use roc_reporting::cli::Problems;
+use roc_reporting::report::{RenderTarget, DEFAULT_PALETTE};
+use target_lexicon::{Architecture, Triple};
+
+#[derive(Debug, Clone)]
+pub struct CompileRequest {
+    /// The app module to build, e.g. `main.roc`
+    pub root: PathBuf,
+    pub target: Triple,
+    pub opt: OptLevel,
+    pub emit: Emit,
+}
+
+impl CompileRequest {
+    /// Build `root` for this machine, with the same optimizations as a plain `roc build`.
+    pub fn new(root: PathBuf, emit: Emit) -> Self {
+        Self {
+            root,
+            target: Triple::host(),
+            opt: OptLevel::Normal,
+            emit,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Emit {
+    /// Only report problems, like `roc check`
+    Check,
+    /// An object file, like `roc build --no-link`
+    Object,
+    /// A shared library, like `roc build --lib`
+    Library,
+    /// An executable, like `roc build`
+    Executable,
+}
+
+/// The stage a build is starting, reported to the progress callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Progress {
+    /// Parsing, canonicalizing, type checking, and specializing every module
+    Loading,
+    /// Generating code, and linking it with the platform's host
+    Generating,
+    Done,
+}
+
+/// Cancels a build from another thread, which then returns [CompileError::Cancelled]. While
+/// loading, the build stops as soon as the modules being worked on are done. Code generation and
+/// linking can't be interrupted, so a build that's cancelled during them stops when they finish.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug)]
+pub struct CompileResult {
+    /// The file that was generated, or `None` for [Emit::Check]
+    pub output: Option<PathBuf>,
+    pub problems: Problems,
+    pub total_time: Duration,
+}
+
+#[derive(Debug)]
+pub enum CompileError {
+    Cancelled,
+    /// A module couldn't be loaded, e.g. because a file is missing or has a syntax error.
+    /// This is the rendered report.
+    Loading(String),
+    /// The app has errors, so nothing was generated
+    Problems(Problems),
+}
+
+/// Build the app in the request, calling `progress` as each stage starts.
+pub fn compile(
+    request: CompileRequest,
+    mut progress: impl FnMut(Progress),
+    cancellation: &CancellationToken,
+) -> Result<CompileResult, CompileError> {
+    let CompileRequest {
+        root,
+        target,
+        opt,
+        emit,
+    } = request;
+
+    let arena = Bump::new();
+    let cache_dir = cache::roc_cache_dir();
+    let roc_cache_dir = RocCacheDir::Persistent(cache_dir.as_path());
+    let severity_config = SeverityConfig::default();
+    let compilation_start = Instant::now();
+
+    check_cancelled(cancellation)?;
+    progress(Progress::Loading);
+
+    let link_type = match emit {
+        Emit::Check => {
+            let load_config = LoadConfig {
+                cancelled: Some(Arc::clone(&cancellation.0)),
+                ..check_load_config(
+                    RenderTarget::Generic,
+                    DEFAULT_PALETTE,
+                    Threading::AllAvailable,
+                )
+            };
+            let mut loaded = roc_load::load_and_typecheck(&arena, root, roc_cache_dir, load_config)
+                .map_err(loading_error)?;
+            let problems =
+                render_problems_typechecked(&mut loaded, &severity_config, RenderTarget::Generic);
+
+            progress(Progress::Done);
+
+            return Ok(CompileResult {
+                output: None,
+                problems,
+                total_time: compilation_start.elapsed(),
+            });
+        }
+        Emit::Object => LinkType::None,
+        Emit::Library => LinkType::Dylib,
+        Emit::Executable => LinkType::Executable,
+    };
+
+    let load_config = LoadConfig {
+        render: RenderTarget::Generic,
+        cancelled: Some(Arc::clone(&cancellation.0)),
+        ..standard_load_config(
+            &target,
+            BuildOrdering::BuildIfChecks,
+            Threading::AllAvailable,
+        )
+    };
+    let loaded = roc_load::load_and_monomorphize(&arena, root.clone(), roc_cache_dir, load_config)
+        .map_err(|error| match error {
+            LoadMonomorphizedError::LoadingProblem(problem) => loading_error(problem),
+            LoadMonomorphizedError::ErrorModule(mut module) => CompileError::Problems(
+                render_problems_typechecked(&mut module, &severity_config, RenderTarget::Generic),
+            ),
+        })?;
+
+    check_cancelled(cancellation)?;
+    progress(Progress::Generating);
+
+    let backend = code_gen_backend(false, &target, opt);
+    let code_gen_options = CodeGenOptions {
+        backend,
+        opt_level: opt,
+        emit_debug_info: false,
+        trace_calls: false,
+        arena_allocation: false,
+        overflow: OverflowMode::Panic,
+        heap_profile: false,
+        stack_traces: false,
+        stack_limit_checks: false,
+        sanitizer_friendly: false,
+        emit_host_layouts: false,
+        emit_call_graph: false,
+        emit_c_header: false,
+        emit_llvm_ir: false,
+        profile_generate: false,
+        profile_use: false,
+        wasi_hooks: false,
+    };
+
+    let built = build_loaded_file(
+        &arena,
+        &target,
+        root,
+        code_gen_options,
+        false,
+        None,
+        link_type,
+        linking_strategy(backend, link_type, &target, false),
+        assume_prebuilt(&target),
+        None,
+        WasmFeatures::default(),
+        loaded,
+        compilation_start,
+        &severity_config,
+        RenderTarget::Generic,
+    )
+    .map_err(|error| match error {
+        BuildFileError::LoadingProblem(problem) => loading_error(problem),
+        BuildFileError::ErrorModule { mut module, .. } => CompileError::Problems(
+            render_problems_typechecked(&mut module, &severity_config, RenderTarget::Generic),
+        ),
+    })?;
+
+    check_cancelled(cancellation)?;
+    progress(Progress::Done);
+
+    Ok(CompileResult {
+        output: Some(built.binary_path),
+        problems: built.problems,
+        total_time: built.total_time,
+    })
+}
+
+/// The backend `roc build` uses: a dev backend for `--dev`, and LLVM otherwise.
+pub fn code_gen_backend(dev: bool, target: &Triple, opt: OptLevel) -> CodeGenBackend {
+    if dev {
+        if matches!(target.architecture, Architecture::Wasm32) {
+            CodeGenBackend::Wasm
+        } else {
+            CodeGenBackend::Assembly(AssemblyBackendMode::Binary)
+        }
+    } else {
+        let backend_mode = match opt {
+            OptLevel::Development => LlvmBackendMode::BinaryDev,
+            OptLevel::Normal | OptLevel::Size | OptLevel::Optimize => LlvmBackendMode::Binary,
+        };
+
+        CodeGenBackend::Llvm(backend_mode)
+    }
+}
+
+/// The surgical linker where it's supported, unless the legacy linker was requested.
+pub fn linking_strategy(
+    backend: CodeGenBackend,
+    link_type: LinkType,
+    target: &Triple,
+    legacy_requested: bool,
+) -> LinkingStrategy {
+    if matches!(backend, CodeGenBackend::Wasm) {
+        LinkingStrategy::Additive
+    } else if legacy_requested || !roc_linker::supported(link_type, target) {
+        LinkingStrategy::Legacy
+    } else {
+        LinkingStrategy::Surgical
+    }
+}
+
+/// When compiling for a different target, assume the platform is prebuilt. Otherwise
+/// compilation would most likely fail, because many toolchains assume you're compiling
+/// for the current machine. Wasm is the exception, because cross-compiling is the norm there.
+pub fn assume_prebuilt(target: &Triple) -> bool {
+    *target != Triple::host() && !matches!(target.architecture, Architecture::Wasm32)
+}
+
+fn check_cancelled(cancellation: &CancellationToken) -> Result<(), CompileError> {
+    if cancellation.is_cancelled() {
+        Err(CompileError::Cancelled)
+    } else {
+        Ok(())
+    }
+}
+
+fn loading_error(problem: LoadingProblem) -> CompileError {
+    match problem {
+        LoadingProblem::Cancelled => CompileError::Cancelled,
+        LoadingProblem::FormattedReport(report) => CompileError::Loading(report),
+        other => CompileError::Loading(format!("{:?}", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn cancelled_before_loading() {
+        let cancellation = CancellationToken::new();
+        let mut stages = Vec::new();
+
+        cancellation.clone().cancel();
+
+        let result = compile(
+            CompileRequest::new(PathBuf::from("does-not-exist.roc"), Emit::Check),
+            |progress| stages.push(progress),
+            &cancellation,
+        );
+
+        assert!(matches!(result, Err(CompileError::Cancelled)));
+        assert!(stages.is_empty());
+    }
+
+    #[test]
+    fn check_returns_rendered_reports() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Main.roc");
+
+        std::fs::write(
+            &path,
+            indoc!(
+                r#"
+                interface Main exposes [x] imports []
+
+                x : Str
+                x = 1
+                "#
+            ),
+        )
+        .unwrap();
+
+        let mut stages = Vec::new();
+        let result = compile(
+            CompileRequest::new(path, Emit::Check),
+            |progress| stages.push(progress),
+            &CancellationToken::new(),
+        )
+        .unwrap();
+
+        assert_eq!(stages, [Progress::Loading, Progress::Done]);
+        assert_eq!(result.output, None);
+        assert_eq!(result.problems.errors, 1);
+        assert_eq!(result.problems.reports.len(), 1);
+
+        let report = &result.problems.reports[0];
+
+        assert!(report.contains("TYPE MISMATCH"), "{}", report);
+        // rendered for a library user, not a terminal
+        assert!(!report.contains('\u{1b}'), "{}", report);
+    }
+
+    #[test]
+    fn prebuilt_only_when_cross_compiling() {
+        assert!(!assume_prebuilt(&Triple::host()));
+        assert!(!assume_prebuilt(&"wasm32-unknown-unknown".parse().unwrap()));
+    }
+}
@@ -2566,6 +2566,30 @@ pub fn constrain_decls(
                     Generalizable(false),
                 )
             }
+            Bench => {
+                let loc_expr = &declarations.expressions[index];
+
+                let record_type = constraints.push_variable(Variable::EMPTY_RECORD);
+                let expected = constraints.push_expected_type(Expected::NoExpectation(record_type));
+
+                let bench_constraint = constrain_expr(
+                    types,
+                    constraints,
+                    &mut env,
+                    loc_expr.region,
+                    &loc_expr.value,
+                    expected,
+                );
+
+                constraint = constraints.let_constraint(
+                    [],
+                    [],
+                    [],
+                    bench_constraint,
+                    constraint,
+                    Generalizable(false),
+                )
+            }
             Function(function_def_index) => {
                 constraint = constrain_function_def(
                     types,
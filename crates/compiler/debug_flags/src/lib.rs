@@ -124,6 +124,12 @@ flags! {
     /// Writes a pretty-printed mono IR to stderr after function specialization.
     ROC_PRINT_IR_AFTER_SPECIALIZATION
 
+    /// Writes a pretty-printed mono IR to stderr after top-level constants are evaluated.
+    ROC_PRINT_IR_AFTER_FOLD_CONSTANTS
+
+    /// Prints how many list bounds checks were found to always pass, and removed.
+    ROC_PRINT_BOUNDS_CHECKS_ELIMINATED
+
     /// Writes a pretty-printed mono IR to stderr after insertion of reset/reuse
     /// instructions.
     ROC_PRINT_IR_AFTER_RESET_REUSE
@@ -151,6 +157,10 @@ flags! {
     /// Prints LLVM function verification output.
     ROC_PRINT_LLVM_FN_VERIFICATION
 
+    /// Use the LLVM backend's own refcounting code, rather than the helper procs that are shared
+    /// with the other backends. Unlike other flags, this is also read in release builds.
+    ROC_LLVM_INLINE_REFCOUNTING
+
     // ===WASM Gen===
 
     /// Writes a `final.wasm` file to /tmp
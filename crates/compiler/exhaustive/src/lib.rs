@@ -7,7 +7,7 @@ use roc_module::{
     ident::{Lowercase, TagIdIntType, TagName},
     symbol::Symbol,
 };
-use roc_problem::Severity;
+use roc_problem::{lint::Lint, Severity};
 use roc_region::all::Region;
 
 use self::Pattern::*;
@@ -160,6 +160,13 @@ impl Error {
             Error::Unmatchable { .. } => Warning,
         }
     }
+
+    pub fn lint(&self) -> Option<Lint> {
+        match self {
+            Error::Incomplete(..) => None,
+            Error::Redundant { .. } | Error::Unmatchable { .. } => Some(Lint::RedundantPattern),
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -195,6 +195,7 @@ impl<'a> Formattable for ValueDef<'a> {
             AnnotatedBody { .. } => true,
             Expect { condition, .. } => condition.is_multiline(),
             ExpectFx { condition, .. } => condition.is_multiline(),
+            Bench { condition, .. } => condition.is_multiline(),
             Dbg { condition, .. } => condition.is_multiline(),
         }
     }
@@ -220,6 +221,7 @@ impl<'a> Formattable for ValueDef<'a> {
             ExpectFx { condition, .. } => {
                 fmt_expect_fx(buf, condition, self.is_multiline(), indent)
             }
+            Bench { condition, .. } => fmt_bench(buf, condition, self.is_multiline(), indent),
             AnnotatedBody {
                 ann_pattern,
                 ann_type,
@@ -355,6 +357,22 @@ fn fmt_expect_fx<'a>(buf: &mut Buf, condition: &'a Loc<Expr<'a>>, is_multiline:
     condition.format(buf, return_indent);
 }
 
+fn fmt_bench<'a>(buf: &mut Buf, condition: &'a Loc<Expr<'a>>, is_multiline: bool, indent: u16) {
+    buf.ensure_ends_with_newline();
+    buf.indent(indent);
+    buf.push_str("bench");
+
+    let return_indent = if is_multiline {
+        buf.newline();
+        indent + INDENT
+    } else {
+        buf.spaces(1);
+        indent
+    };
+
+    condition.format(buf, return_indent);
+}
+
 pub fn fmt_value_def(buf: &mut Buf, def: &roc_parse::ast::ValueDef, indent: u16) {
     def.format(buf, indent);
 }
@@ -52,6 +52,7 @@ impl<'a> Formattable for Expr<'a> {
             Defs(_, _) | When(_, _) => true,
 
             List(items) => is_collection_multiline(items),
+            WhenConditions(conditions) => conditions.iter().any(|loc_expr| loc_expr.is_multiline()),
 
             Str(literal) => is_str_multiline(literal),
             Apply(loc_expr, args, _) => {
@@ -82,7 +83,7 @@ impl<'a> Formattable for Expr<'a> {
             | MultipleRecordBuilders(loc_subexpr)
             | UnappliedRecordBuilder(loc_subexpr) => loc_subexpr.is_multiline(),
 
-            ParensAround(subexpr) => subexpr.is_multiline(),
+            ParensAround(subexpr) | TaskAwaitBang(subexpr) => subexpr.is_multiline(),
 
             Closure(loc_patterns, loc_body) => {
                 // check the body first because it's more likely to be multiline
@@ -436,6 +437,16 @@ impl<'a> Formattable for Expr<'a> {
                 fmt_if(buf, branches, final_else, self.is_multiline(), indent);
             }
             When(loc_condition, branches) => fmt_when(buf, loc_condition, branches, indent),
+            WhenConditions(conditions) => {
+                for (index, loc_condition) in conditions.iter().enumerate() {
+                    if index > 0 {
+                        buf.push_str(",");
+                        buf.spaces(1);
+                    }
+
+                    loc_condition.format(buf, indent);
+                }
+            }
             Tuple(items) => fmt_collection(buf, indent, Braces::Round, *items, Newlines::No),
             List(items) => fmt_collection(buf, indent, Braces::Square, *items, Newlines::No),
             BinOps(lefts, right) => fmt_binops(buf, lefts, right, false, indent),
@@ -494,6 +505,10 @@ impl<'a> Formattable for Expr<'a> {
                 buf.push('.');
                 buf.push_str(key);
             }
+            TaskAwaitBang(expr) => {
+                expr.format_with_options(buf, Parens::InApply, Newlines::Yes, indent);
+                buf.push('!');
+            }
             MalformedIdent(str, _) => {
                 buf.indent(indent);
                 buf.push_str(str)
@@ -764,6 +779,10 @@ fn fmt_when<'a>(
     indent: u16,
 ) {
     let is_multiline_condition = loc_condition.is_multiline();
+    let num_conditions = match loc_condition.value {
+        Expr::WhenConditions(conditions) => conditions.len(),
+        _ => 1,
+    };
     buf.ensure_ends_with_newline();
     buf.indent(indent);
     buf.push_str("when");
@@ -864,7 +883,7 @@ fn fmt_when<'a>(
                             }
                         }
 
-                        fmt_pattern(buf, sub_pattern, indent + INDENT, Parens::NotNeeded);
+                        fmt_when_pattern(buf, sub_pattern, indent + INDENT, num_conditions);
                     }
                     other => {
                         if branch_index > 0 {
@@ -876,7 +895,7 @@ fn fmt_when<'a>(
                             }
                         }
 
-                        fmt_pattern(buf, other, indent + INDENT, Parens::NotNeeded);
+                        fmt_when_pattern(buf, other, indent + INDENT, num_conditions);
                     }
                 }
             } else {
@@ -890,7 +909,7 @@ fn fmt_when<'a>(
 
                 buf.spaces(1);
 
-                fmt_pattern(buf, &pattern.value, indent + INDENT, Parens::NotNeeded);
+                fmt_when_pattern(buf, &pattern.value, indent + INDENT, num_conditions);
             }
         }
 
@@ -939,6 +958,31 @@ fn fmt_when<'a>(
     }
 }
 
+/// The branches of a `when a, b is` have one pattern per condition, which are parsed as a tuple
+/// pattern. They're written back without the parens, like `Ok x, Ok y ->`.
+fn fmt_when_pattern<'a>(
+    buf: &mut Buf,
+    pattern: &'a Pattern<'a>,
+    indent: u16,
+    num_conditions: usize,
+) {
+    match pattern {
+        Pattern::Tuple(loc_patterns)
+            if num_conditions > 1 && loc_patterns.len() == num_conditions =>
+        {
+            for (index, loc_pattern) in loc_patterns.iter().enumerate() {
+                if index > 0 {
+                    buf.push_str(",");
+                    buf.spaces(1);
+                }
+
+                loc_pattern.format(buf, indent);
+            }
+        }
+        _ => fmt_pattern(buf, pattern, indent, Parens::NotNeeded),
+    }
+}
+
 fn fmt_dbg<'a>(
     buf: &mut Buf,
     condition: &'a Loc<Expr<'a>>,
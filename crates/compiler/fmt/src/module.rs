@@ -412,12 +412,17 @@ impl<'a> Formattable for ExposedName<'a> {
         indent: u16,
     ) {
         buf.indent(indent);
-        buf.push_str(self.as_str());
+        FormatName::format(self, buf);
     }
 }
 
 impl<'a> FormatName for ExposedName<'a> {
     fn format(&self, buf: &mut Buf) {
+        if self.is_opaque() {
+            buf.push_str("opaque");
+            buf.spaces(1);
+        }
+
         buf.push_str(self.as_str());
     }
 }
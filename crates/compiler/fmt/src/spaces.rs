@@ -565,6 +565,13 @@ impl<'a> RemoveSpaces<'a> for ValueDef<'a> {
                 condition: arena.alloc(condition.remove_spaces(arena)),
                 preceding_comment: Region::zero(),
             },
+            Bench {
+                condition,
+                preceding_comment: _,
+            } => Bench {
+                condition: arena.alloc(condition.remove_spaces(arena)),
+                preceding_comment: Region::zero(),
+            },
         }
     }
 }
@@ -740,10 +747,12 @@ impl<'a> RemoveSpaces<'a> for Expr<'a> {
             Expr::When(a, b) => {
                 Expr::When(arena.alloc(a.remove_spaces(arena)), b.remove_spaces(arena))
             }
+            Expr::WhenConditions(a) => Expr::WhenConditions(a.remove_spaces(arena)),
             Expr::ParensAround(a) => {
                 // The formatter can remove redundant parentheses, so also remove these when normalizing for comparison.
                 a.remove_spaces(arena)
             }
+            Expr::TaskAwaitBang(a) => Expr::TaskAwaitBang(arena.alloc(a.remove_spaces(arena))),
             Expr::MalformedIdent(a, b) => Expr::MalformedIdent(a, remove_spaces_bad_ident(b)),
             Expr::MalformedClosure => Expr::MalformedClosure,
             Expr::PrecedenceConflict(a) => Expr::PrecedenceConflict(a),
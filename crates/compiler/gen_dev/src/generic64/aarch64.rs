@@ -1,11 +1,14 @@
 use crate::generic64::{storage::StorageManager, Assembler, CallConv, RegTrait};
-use crate::Relocation;
+use crate::{
+    pointer_layouts, single_register_floats, single_register_int_builtins,
+    single_register_integers, single_register_layouts, Relocation,
+};
 use bumpalo::collections::Vec;
 use packed_struct::prelude::*;
 use roc_builtins::bitcode::FloatWidth;
 use roc_error_macros::internal_error;
 use roc_module::symbol::Symbol;
-use roc_mono::layout::{InLayout, STLayoutInterner};
+use roc_mono::layout::{InLayout, LayoutInterner, LayoutRepr, STLayoutInterner, UnionLayout};
 
 use super::{CompareOperation, RegisterWidth};
 
@@ -203,6 +206,10 @@ pub struct AArch64Call {}
 
 const STACK_ALIGNMENT: u8 = 16;
 
+/// The caller's frame pointer and the link register, saved at the top of every frame.
+/// Arguments passed on the stack start right above it.
+const FRAME_RECORD_SIZE: i32 = 16;
+
 impl CallConv<AArch64GeneralReg, AArch64FloatReg, AArch64Assembler> for AArch64Call {
     const BASE_PTR_REG: AArch64GeneralReg = AArch64GeneralReg::FP;
     const STACK_PTR_REG: AArch64GeneralReg = AArch64GeneralReg::ZRSP;
@@ -227,6 +234,7 @@ impl CallConv<AArch64GeneralReg, AArch64FloatReg, AArch64Assembler> for AArch64C
         // Don't use platform register: AArch64GeneralReg::PR,
         // Don't use link register: AArch64GeneralReg::LR,
         // Don't use zero register/stack pointer: AArch64GeneralReg::ZRSP,
+        // Don't use the assembler's scratch register: AArch64GeneralReg::IP1,
 
         // Use callee saved regs last.
         AArch64GeneralReg::X19,
@@ -256,11 +264,57 @@ impl CallConv<AArch64GeneralReg, AArch64FloatReg, AArch64Assembler> for AArch64C
         AArch64GeneralReg::X14,
         AArch64GeneralReg::X15,
         AArch64GeneralReg::IP0,
-        AArch64GeneralReg::IP1,
     ];
-    const FLOAT_PARAM_REGS: &'static [AArch64FloatReg] = &[];
+    const FLOAT_PARAM_REGS: &'static [AArch64FloatReg] = &[
+        AArch64FloatReg::V0,
+        AArch64FloatReg::V1,
+        AArch64FloatReg::V2,
+        AArch64FloatReg::V3,
+        AArch64FloatReg::V4,
+        AArch64FloatReg::V5,
+        AArch64FloatReg::V6,
+        AArch64FloatReg::V7,
+    ];
     const FLOAT_RETURN_REGS: &'static [AArch64FloatReg] = Self::FLOAT_PARAM_REGS;
-    const FLOAT_DEFAULT_FREE_REGS: &'static [AArch64FloatReg] = &[];
+    const FLOAT_DEFAULT_FREE_REGS: &'static [AArch64FloatReg] = &[
+        // The regs we want to use first should be at the end of this vec.
+        // We will use pop to get which reg to use next
+
+        // Use callee saved regs last.
+        AArch64FloatReg::V8,
+        AArch64FloatReg::V9,
+        AArch64FloatReg::V10,
+        AArch64FloatReg::V11,
+        AArch64FloatReg::V12,
+        AArch64FloatReg::V13,
+        AArch64FloatReg::V14,
+        AArch64FloatReg::V15,
+        // Use caller saved regs first.
+        AArch64FloatReg::V0,
+        AArch64FloatReg::V1,
+        AArch64FloatReg::V2,
+        AArch64FloatReg::V3,
+        AArch64FloatReg::V4,
+        AArch64FloatReg::V5,
+        AArch64FloatReg::V6,
+        AArch64FloatReg::V7,
+        AArch64FloatReg::V16,
+        AArch64FloatReg::V17,
+        AArch64FloatReg::V18,
+        AArch64FloatReg::V19,
+        AArch64FloatReg::V20,
+        AArch64FloatReg::V21,
+        AArch64FloatReg::V22,
+        AArch64FloatReg::V23,
+        AArch64FloatReg::V24,
+        AArch64FloatReg::V25,
+        AArch64FloatReg::V26,
+        AArch64FloatReg::V27,
+        AArch64FloatReg::V28,
+        AArch64FloatReg::V29,
+        AArch64FloatReg::V30,
+        AArch64FloatReg::V31,
+    ];
 
     const SHADOW_SPACE_SIZE: u8 = 0;
 
@@ -281,8 +335,20 @@ impl CallConv<AArch64GeneralReg, AArch64FloatReg, AArch64Assembler> for AArch64C
         )
     }
     #[inline(always)]
-    fn float_callee_saved(_reg: &AArch64FloatReg) -> bool {
-        todo!("AArch64 FloatRegs");
+    fn float_callee_saved(reg: &AArch64FloatReg) -> bool {
+        // Only the bottom 64 bits of these need to be preserved,
+        // which is all we ever save and restore.
+        matches!(
+            reg,
+            AArch64FloatReg::V8
+                | AArch64FloatReg::V9
+                | AArch64FloatReg::V10
+                | AArch64FloatReg::V11
+                | AArch64FloatReg::V12
+                | AArch64FloatReg::V13
+                | AArch64FloatReg::V14
+                | AArch64FloatReg::V15
+        )
     }
 
     #[inline(always)]
@@ -295,7 +361,8 @@ impl CallConv<AArch64GeneralReg, AArch64FloatReg, AArch64Assembler> for AArch64C
     ) -> i32 {
         // Full size is upcast to i64 to make sure we don't overflow here.
         let full_stack_size = match requested_stack_size
-            .checked_add(8 * (saved_general_regs.len() + saved_float_regs.len()) as i32 + 8) // The extra 8 is space to store the frame pointer.
+            .checked_add(8 * (saved_general_regs.len() + saved_float_regs.len()) as i32)
+            .and_then(|size| size.checked_add(FRAME_RECORD_SIZE))
             .and_then(|size| size.checked_add(fn_call_stack_size))
         {
             Some(size) => size,
@@ -313,11 +380,6 @@ impl CallConv<AArch64GeneralReg, AArch64FloatReg, AArch64Assembler> for AArch64C
         };
         if let Some(aligned_stack_size) = full_stack_size.checked_add(offset as i32) {
             if aligned_stack_size > 0 {
-                AArch64Assembler::mov_reg64_reg64(
-                    buf,
-                    AArch64GeneralReg::FP,
-                    AArch64GeneralReg::ZRSP,
-                );
                 AArch64Assembler::sub_reg64_reg64_imm32(
                     buf,
                     AArch64GeneralReg::ZRSP,
@@ -325,21 +387,29 @@ impl CallConv<AArch64GeneralReg, AArch64FloatReg, AArch64Assembler> for AArch64C
                     aligned_stack_size,
                 );
 
+                // The frame record (the caller's frame pointer and our return address) sits at the
+                // top of the frame, and the frame pointer points at it.
                 // All the following stores could be optimized by using `STP` to store pairs.
-                let mut offset = aligned_stack_size;
-                offset -= 8;
-                AArch64Assembler::mov_stack32_reg64(buf, offset, AArch64GeneralReg::LR);
-                offset -= 8;
-                AArch64Assembler::mov_stack32_reg64(buf, offset, AArch64GeneralReg::FP);
+                let frame_record = aligned_stack_size - FRAME_RECORD_SIZE;
+                AArch64Assembler::mov_stack32_reg64(buf, frame_record, AArch64GeneralReg::FP);
+                AArch64Assembler::mov_stack32_reg64(buf, frame_record + 8, AArch64GeneralReg::LR);
+                AArch64Assembler::add_reg64_reg64_imm32(
+                    buf,
+                    AArch64GeneralReg::FP,
+                    AArch64GeneralReg::ZRSP,
+                    frame_record,
+                );
 
-                offset = aligned_stack_size - fn_call_stack_size;
+                // Put values at the bottom of the frame, just above the space for function call
+                // arguments, to avoid conflicts with previously saved variables.
+                let mut offset = frame_record - fn_call_stack_size;
                 for reg in saved_general_regs {
+                    AArch64Assembler::mov_base32_reg64(buf, -offset, *reg);
                     offset -= 8;
-                    AArch64Assembler::mov_base32_reg64(buf, offset, *reg);
                 }
                 for reg in saved_float_regs {
+                    AArch64Assembler::mov_base32_freg64(buf, -offset, *reg);
                     offset -= 8;
-                    AArch64Assembler::mov_base32_freg64(buf, offset, *reg);
                 }
                 aligned_stack_size
             } else {
@@ -359,22 +429,21 @@ impl CallConv<AArch64GeneralReg, AArch64FloatReg, AArch64Assembler> for AArch64C
         fn_call_stack_size: i32,
     ) {
         if aligned_stack_size > 0 {
-            // All the following stores could be optimized by using `STP` to store pairs.
-            let mut offset = aligned_stack_size;
-            offset -= 8;
-            AArch64Assembler::mov_reg64_stack32(buf, AArch64GeneralReg::LR, offset);
-            offset -= 8;
-            AArch64Assembler::mov_reg64_stack32(buf, AArch64GeneralReg::FP, offset);
-
-            offset = aligned_stack_size - fn_call_stack_size;
+            let frame_record = aligned_stack_size - FRAME_RECORD_SIZE;
+
+            let mut offset = frame_record - fn_call_stack_size;
             for reg in saved_general_regs {
+                AArch64Assembler::mov_reg64_base32(buf, *reg, -offset);
                 offset -= 8;
-                AArch64Assembler::mov_reg64_base32(buf, *reg, offset);
             }
             for reg in saved_float_regs {
+                AArch64Assembler::mov_freg64_base32(buf, *reg, -offset);
                 offset -= 8;
-                AArch64Assembler::mov_freg64_base32(buf, *reg, offset);
             }
+
+            // All the following loads could be optimized by using `LDP` to load pairs.
+            AArch64Assembler::mov_reg64_stack32(buf, AArch64GeneralReg::FP, frame_record);
+            AArch64Assembler::mov_reg64_stack32(buf, AArch64GeneralReg::LR, frame_record + 8);
             AArch64Assembler::add_reg64_reg64_imm32(
                 buf,
                 AArch64GeneralReg::ZRSP,
@@ -386,77 +455,477 @@ impl CallConv<AArch64GeneralReg, AArch64FloatReg, AArch64Assembler> for AArch64C
 
     #[inline(always)]
     fn load_args<'a>(
-        _buf: &mut Vec<'a, u8>,
-        _storage_manager: &mut StorageManager<
-            'a,
-            '_,
-            AArch64GeneralReg,
-            AArch64FloatReg,
-            AArch64Assembler,
-            AArch64Call,
-        >,
-        _layout_interner: &mut STLayoutInterner<'a>,
-        _args: &'a [(InLayout<'a>, Symbol)],
-        _ret_layout: &InLayout<'a>,
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut AArch64StorageManager<'a, '_>,
+        layout_interner: &mut STLayoutInterner<'a>,
+        args: &'a [(InLayout<'a>, Symbol)],
+        ret_layout: &InLayout<'a>,
     ) {
-        todo!("Loading args for AArch64");
+        if AArch64Call::returns_via_arg_pointer(layout_interner, ret_layout) {
+            // The indirect result location register is not one of the param regs,
+            // so it does not shift the other arguments.
+            storage_manager.ret_pointer_arg(AArch64GeneralReg::XR);
+        }
+
+        let mut state = AArch64CallLoadArgs {
+            general_i: 0,
+            float_i: 0,
+            argument_offset: FRAME_RECORD_SIZE,
+        };
+
+        for (in_layout, sym) in args.iter() {
+            state.load_arg(buf, storage_manager, layout_interner, *sym, *in_layout);
+        }
     }
 
     #[inline(always)]
     fn store_args<'a>(
-        _buf: &mut Vec<'a, u8>,
-        _storage_manager: &mut StorageManager<
-            'a,
-            '_,
-            AArch64GeneralReg,
-            AArch64FloatReg,
-            AArch64Assembler,
-            AArch64Call,
-        >,
-        _layout_interner: &mut STLayoutInterner<'a>,
-        _dst: &Symbol,
-        _args: &[Symbol],
-        _arg_layouts: &[InLayout<'a>],
-        _ret_layout: &InLayout<'a>,
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut AArch64StorageManager<'a, '_>,
+        layout_interner: &mut STLayoutInterner<'a>,
+        dst: &Symbol,
+        args: &[Symbol],
+        arg_layouts: &[InLayout<'a>],
+        ret_layout: &InLayout<'a>,
     ) {
-        todo!("Storing args for AArch64");
+        if Self::returns_via_arg_pointer(layout_interner, ret_layout) {
+            // Save space on the stack for the result we will be return.
+            let base_offset =
+                storage_manager.claim_stack_area(dst, layout_interner.stack_size(*ret_layout));
+            // Set the indirect result location to the address base + offset.
+            AArch64Assembler::add_reg64_reg64_imm32(
+                buf,
+                AArch64GeneralReg::XR,
+                AArch64GeneralReg::FP,
+                base_offset,
+            );
+        }
+
+        let mut state = AArch64CallStoreArgs {
+            general_i: 0,
+            float_i: 0,
+            tmp_stack_offset: Self::SHADOW_SPACE_SIZE as i32,
+        };
+
+        for (sym, in_layout) in args.iter().zip(arg_layouts.iter()) {
+            state.store_arg(buf, storage_manager, layout_interner, *sym, *in_layout);
+        }
+
+        storage_manager.update_fn_call_stack_size(state.tmp_stack_offset as u32);
     }
 
     fn return_complex_symbol<'a>(
-        _buf: &mut Vec<'a, u8>,
-        _storage_manager: &mut StorageManager<
-            'a,
-            '_,
-            AArch64GeneralReg,
-            AArch64FloatReg,
-            AArch64Assembler,
-            AArch64Call,
-        >,
-        _layout_interner: &mut STLayoutInterner<'a>,
-        _sym: &Symbol,
-        _layout: &InLayout<'a>,
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut AArch64StorageManager<'a, '_>,
+        layout_interner: &mut STLayoutInterner<'a>,
+        sym: &Symbol,
+        layout: &InLayout<'a>,
     ) {
-        todo!("Returning complex symbols for AArch64");
+        match layout_interner.get(*layout).repr {
+            single_register_layouts!() => {
+                internal_error!("single register layouts are not complex symbols");
+            }
+            _ if layout_interner.stack_size(*layout) == 0 => {}
+            _ if !Self::returns_via_arg_pointer(layout_interner, layout) => {
+                let (base_offset, size) = storage_manager.stack_offset_and_size(sym);
+                debug_assert_eq!(base_offset % 8, 0);
+                if size <= 8 {
+                    AArch64Assembler::mov_reg64_base32(
+                        buf,
+                        Self::GENERAL_RETURN_REGS[0],
+                        base_offset,
+                    );
+                } else if size <= 16 {
+                    AArch64Assembler::mov_reg64_base32(
+                        buf,
+                        Self::GENERAL_RETURN_REGS[0],
+                        base_offset,
+                    );
+                    AArch64Assembler::mov_reg64_base32(
+                        buf,
+                        Self::GENERAL_RETURN_REGS[1],
+                        base_offset + 8,
+                    );
+                } else {
+                    internal_error!(
+                        "types that don't return via arg pointer must be less than 16 bytes"
+                    );
+                }
+            }
+            _ => {
+                // This is a large type returned via the indirect result location.
+                // Unlike x86_64, the callee does not have to hand the pointer back.
+                storage_manager.copy_symbol_to_arg_pointer(buf, sym, layout);
+            }
+        }
     }
 
     fn load_returned_complex_symbol<'a>(
-        _buf: &mut Vec<'a, u8>,
-        _storage_manager: &mut StorageManager<
-            'a,
-            '_,
-            AArch64GeneralReg,
-            AArch64FloatReg,
-            AArch64Assembler,
-            AArch64Call,
-        >,
-        _layout_interner: &mut STLayoutInterner<'a>,
-        _sym: &Symbol,
-        _layout: &InLayout<'a>,
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut AArch64StorageManager<'a, '_>,
+        layout_interner: &mut STLayoutInterner<'a>,
+        sym: &Symbol,
+        layout: &InLayout<'a>,
+    ) {
+        match layout_interner.get(*layout).repr {
+            single_register_layouts!() => {
+                internal_error!("single register layouts are not complex symbols");
+            }
+            _ if layout_interner.stack_size(*layout) == 0 => {
+                storage_manager.no_data(sym);
+            }
+            _ if !Self::returns_via_arg_pointer(layout_interner, layout) => {
+                let size = layout_interner.stack_size(*layout);
+                let offset = storage_manager.claim_stack_area(sym, size);
+                if size <= 8 {
+                    AArch64Assembler::mov_base32_reg64(buf, offset, Self::GENERAL_RETURN_REGS[0]);
+                } else if size <= 16 {
+                    AArch64Assembler::mov_base32_reg64(buf, offset, Self::GENERAL_RETURN_REGS[0]);
+                    AArch64Assembler::mov_base32_reg64(
+                        buf,
+                        offset + 8,
+                        Self::GENERAL_RETURN_REGS[1],
+                    );
+                } else {
+                    internal_error!(
+                        "types that don't return via arg pointer must be less than 16 bytes"
+                    );
+                }
+            }
+            _ => {
+                // This should have been recieved via the indirect result location.
+                // That means the value is already loaded onto the stack area we allocated before the call.
+                // Nothing to do.
+            }
+        }
+    }
+}
+
+impl AArch64Call {
+    fn returns_via_arg_pointer<'a>(
+        interner: &STLayoutInterner<'a>,
+        ret_layout: &InLayout<'a>,
+    ) -> bool {
+        // TODO: Homogeneous floating-point aggregates should be returned in float registers.
+        // details here: https://github.com/ARM-software/abi-aa/blob/main/aapcs64/aapcs64.rst
+        interner.stack_size(*ret_layout) > 16
+    }
+}
+
+type AArch64StorageManager<'a, 'r> =
+    StorageManager<'a, 'r, AArch64GeneralReg, AArch64FloatReg, AArch64Assembler, AArch64Call>;
+
+/// Caller saved and never used for arguments, so it is free to use as a buffer
+/// while arguments are being moved in or out of place.
+const ARG_TMP_REG: AArch64GeneralReg = AArch64GeneralReg::X9;
+/// A second buffer, for when `ARG_TMP_REG` is holding a pointer.
+const ARG_TMP_REG2: AArch64GeneralReg = AArch64GeneralReg::X10;
+const ARG_FLOAT_TMP_REG: AArch64FloatReg = AArch64FloatReg::V16;
+
+struct AArch64CallStoreArgs {
+    general_i: usize,
+    float_i: usize,
+    tmp_stack_offset: i32,
+}
+
+impl AArch64CallStoreArgs {
+    const GENERAL_PARAM_REGS: &'static [AArch64GeneralReg] = AArch64Call::GENERAL_PARAM_REGS;
+    const FLOAT_PARAM_REGS: &'static [AArch64FloatReg] = AArch64Call::FLOAT_PARAM_REGS;
+
+    fn store_arg<'a>(
+        &mut self,
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut AArch64StorageManager<'a, '_>,
+        layout_interner: &mut STLayoutInterner<'a>,
+        sym: Symbol,
+        in_layout: InLayout<'a>,
+    ) {
+        let stack_size = layout_interner.stack_size(in_layout);
+        match layout_interner.get(in_layout).repr {
+            single_register_integers!() => self.store_arg_general(buf, storage_manager, sym),
+            single_register_floats!() => self.store_arg_float(buf, storage_manager, sym),
+            _ if stack_size == 0 => {}
+            _ if stack_size > 16 => self.store_arg_by_reference(buf, storage_manager, sym),
+            other => match other {
+                pointer_layouts!() => {
+                    // treat pointers like a 64-bit integer
+                    self.store_arg_general(buf, storage_manager, sym)
+                }
+                LayoutRepr::LambdaSet(lambda_set) => self.store_arg(
+                    buf,
+                    storage_manager,
+                    layout_interner,
+                    sym,
+                    lambda_set.runtime_representation(),
+                ),
+                LayoutRepr::I128 | LayoutRepr::U128 | LayoutRepr::DEC => {
+                    // 16 byte aligned values start at an even register, or a 16 byte aligned slot.
+                    self.general_i += self.general_i % 2;
+                    self.store_arg_composite(buf, storage_manager, sym, 16);
+                }
+                LayoutRepr::Struct { .. } | LayoutRepr::Union(UnionLayout::NonRecursive(_)) => {
+                    self.store_arg_composite(buf, storage_manager, sym, 8);
+                }
+                _ => {
+                    // every layout of 16 bytes or less is handled above
+                    internal_error!(
+                        "unsupported argument layout for AArch64: {:?}",
+                        layout_interner.dbg(in_layout)
+                    );
+                }
+            },
+        }
+    }
+
+    fn store_arg_general<'a>(
+        &mut self,
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut AArch64StorageManager<'a, '_>,
+        sym: Symbol,
+    ) {
+        match Self::GENERAL_PARAM_REGS.get(self.general_i) {
+            Some(reg) => {
+                storage_manager.load_to_specified_general_reg(buf, &sym, *reg);
+                self.general_i += 1;
+            }
+            None => {
+                storage_manager.load_to_specified_general_reg(buf, &sym, ARG_TMP_REG);
+                AArch64Assembler::mov_stack32_reg64(buf, self.tmp_stack_offset, ARG_TMP_REG);
+
+                self.tmp_stack_offset += 8;
+            }
+        }
+    }
+
+    fn store_arg_float<'a>(
+        &mut self,
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut AArch64StorageManager<'a, '_>,
+        sym: Symbol,
+    ) {
+        match Self::FLOAT_PARAM_REGS.get(self.float_i) {
+            Some(reg) => {
+                storage_manager.load_to_specified_float_reg(buf, &sym, *reg);
+                self.float_i += 1;
+            }
+            None => {
+                storage_manager.load_to_specified_float_reg(buf, &sym, ARG_FLOAT_TMP_REG);
+                AArch64Assembler::mov_stack32_freg64(buf, self.tmp_stack_offset, ARG_FLOAT_TMP_REG);
+
+                self.tmp_stack_offset += 8;
+            }
+        }
+    }
+
+    /// Composites of up to 16 bytes are passed in consecutive general registers,
+    /// or entirely on the stack if there are not enough registers left.
+    fn store_arg_composite<'a>(
+        &mut self,
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut AArch64StorageManager<'a, '_>,
+        sym: Symbol,
+        stack_alignment: i32,
+    ) {
+        let (base_offset, size) = storage_manager.stack_offset_and_size(&sym);
+        debug_assert_eq!(base_offset % 8, 0);
+        let regs_needed = (size as usize + 7) / 8;
+
+        if self.general_i + regs_needed <= Self::GENERAL_PARAM_REGS.len() {
+            let regs = &Self::GENERAL_PARAM_REGS[self.general_i..][..regs_needed];
+            for (i, reg) in regs.iter().enumerate() {
+                AArch64Assembler::mov_reg64_base32(buf, *reg, base_offset + 8 * i as i32);
+            }
+            self.general_i += regs_needed;
+        } else {
+            // Once a composite goes on the stack, so do all the remaining general arguments.
+            self.general_i = Self::GENERAL_PARAM_REGS.len();
+            self.tmp_stack_offset += self.tmp_stack_offset % stack_alignment;
+
+            for i in (0..size as i32).step_by(8) {
+                AArch64Assembler::mov_reg64_base32(buf, ARG_TMP_REG, base_offset + i);
+                AArch64Assembler::mov_stack32_reg64(buf, self.tmp_stack_offset + i, ARG_TMP_REG);
+            }
+            self.tmp_stack_offset += 8 * regs_needed as i32;
+        }
+    }
+
+    /// Composites over 16 bytes are passed as a pointer to a copy.
+    /// Values are immutable, so the original can be used as the copy.
+    fn store_arg_by_reference<'a>(
+        &mut self,
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut AArch64StorageManager<'a, '_>,
+        sym: Symbol,
     ) {
-        todo!("Loading returned complex symbols for AArch64");
+        let (base_offset, _) = storage_manager.stack_offset_and_size(&sym);
+
+        match Self::GENERAL_PARAM_REGS.get(self.general_i) {
+            Some(reg) => {
+                AArch64Assembler::add_reg64_reg64_imm32(
+                    buf,
+                    *reg,
+                    AArch64GeneralReg::FP,
+                    base_offset,
+                );
+                self.general_i += 1;
+            }
+            None => {
+                AArch64Assembler::add_reg64_reg64_imm32(
+                    buf,
+                    ARG_TMP_REG,
+                    AArch64GeneralReg::FP,
+                    base_offset,
+                );
+                AArch64Assembler::mov_stack32_reg64(buf, self.tmp_stack_offset, ARG_TMP_REG);
+
+                self.tmp_stack_offset += 8;
+            }
+        }
     }
 }
 
+struct AArch64CallLoadArgs {
+    general_i: usize,
+    float_i: usize,
+    argument_offset: i32,
+}
+
+impl AArch64CallLoadArgs {
+    const GENERAL_PARAM_REGS: &'static [AArch64GeneralReg] = AArch64Call::GENERAL_PARAM_REGS;
+    const FLOAT_PARAM_REGS: &'static [AArch64FloatReg] = AArch64Call::FLOAT_PARAM_REGS;
+
+    fn load_arg<'a>(
+        &mut self,
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut AArch64StorageManager<'a, '_>,
+        layout_interner: &mut STLayoutInterner<'a>,
+        sym: Symbol,
+        in_layout: InLayout<'a>,
+    ) {
+        let stack_size = layout_interner.stack_size(in_layout);
+        match layout_interner.get(in_layout).repr {
+            single_register_integers!() => self.load_arg_general(storage_manager, sym),
+            single_register_floats!() => self.load_arg_float(storage_manager, sym),
+            _ if stack_size == 0 => {
+                storage_manager.no_data(&sym);
+            }
+            _ if stack_size > 16 => {
+                self.load_arg_by_reference(buf, storage_manager, sym, stack_size)
+            }
+            other => match other {
+                pointer_layouts!() => {
+                    // these layouts are pointers, which we treat as 64-bit integers
+                    self.load_arg_general(storage_manager, sym)
+                }
+                LayoutRepr::LambdaSet(lambda_set) => self.load_arg(
+                    buf,
+                    storage_manager,
+                    layout_interner,
+                    sym,
+                    lambda_set.runtime_representation(),
+                ),
+                LayoutRepr::I128 | LayoutRepr::U128 | LayoutRepr::DEC => {
+                    // 16 byte aligned values start at an even register, or a 16 byte aligned slot.
+                    self.general_i += self.general_i % 2;
+                    self.load_arg_composite(buf, storage_manager, sym, stack_size, 16);
+                }
+                LayoutRepr::Struct { .. } | LayoutRepr::Union(UnionLayout::NonRecursive(_)) => {
+                    self.load_arg_composite(buf, storage_manager, sym, stack_size, 8);
+                }
+                _ => {
+                    // every layout of 16 bytes or less is handled above
+                    internal_error!(
+                        "unsupported argument layout for AArch64: {:?}",
+                        layout_interner.dbg(in_layout)
+                    );
+                }
+            },
+        }
+    }
+
+    fn load_arg_general(
+        &mut self,
+        storage_manager: &mut AArch64StorageManager<'_, '_>,
+        sym: Symbol,
+    ) {
+        if let Some(reg) = Self::GENERAL_PARAM_REGS.get(self.general_i) {
+            storage_manager.general_reg_arg(&sym, *reg);
+            self.general_i += 1;
+        } else {
+            storage_manager.primitive_stack_arg(&sym, self.argument_offset);
+            self.argument_offset += 8;
+        }
+    }
+
+    fn load_arg_float(&mut self, storage_manager: &mut AArch64StorageManager<'_, '_>, sym: Symbol) {
+        if let Some(reg) = Self::FLOAT_PARAM_REGS.get(self.float_i) {
+            storage_manager.float_reg_arg(&sym, *reg);
+            self.float_i += 1;
+        } else {
+            storage_manager.primitive_stack_arg(&sym, self.argument_offset);
+            self.argument_offset += 8;
+        }
+    }
+
+    fn load_arg_composite<'a>(
+        &mut self,
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut AArch64StorageManager<'a, '_>,
+        sym: Symbol,
+        size: u32,
+        stack_alignment: i32,
+    ) {
+        let regs_needed = (size as usize + 7) / 8;
+
+        if self.general_i + regs_needed <= Self::GENERAL_PARAM_REGS.len() {
+            let base_offset = storage_manager.claim_stack_area(&sym, size);
+            let regs = &Self::GENERAL_PARAM_REGS[self.general_i..][..regs_needed];
+            for (i, reg) in regs.iter().enumerate() {
+                AArch64Assembler::mov_base32_reg64(buf, base_offset + 8 * i as i32, *reg);
+            }
+            self.general_i += regs_needed;
+        } else {
+            // Once a composite goes on the stack, so do all the remaining general arguments.
+            self.general_i = Self::GENERAL_PARAM_REGS.len();
+            self.argument_offset += self.argument_offset % stack_alignment;
+
+            storage_manager.complex_stack_arg(&sym, self.argument_offset, size);
+            self.argument_offset += 8 * regs_needed as i32;
+        }
+    }
+
+    fn load_arg_by_reference<'a>(
+        &mut self,
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut AArch64StorageManager<'a, '_>,
+        sym: Symbol,
+        size: u32,
+    ) {
+        let ptr_reg = match Self::GENERAL_PARAM_REGS.get(self.general_i) {
+            Some(reg) => {
+                self.general_i += 1;
+                *reg
+            }
+            None => {
+                AArch64Assembler::mov_reg64_base32(buf, ARG_TMP_REG2, self.argument_offset);
+                self.argument_offset += 8;
+                ARG_TMP_REG2
+            }
+        };
+
+        // Copy the value into our own frame, so it is stored like every other complex symbol.
+        let base_offset = storage_manager.claim_stack_area(&sym, size);
+        for i in (0..size as i32).step_by(8) {
+            AArch64Assembler::mov_reg64_mem64_offset32(buf, ARG_TMP_REG, ptr_reg, i);
+            AArch64Assembler::mov_base32_reg64(buf, base_offset + i, ARG_TMP_REG);
+        }
+    }
+}
+
+/// The assembler uses IP1 as a scratch register for values that don't fit in an instruction,
+/// like large stack offsets, so it is never handed out by the storage manager.
+const SCRATCH_REG: AArch64GeneralReg = AArch64GeneralReg::IP1;
+
 impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
     #[inline(always)]
     fn abs_reg64_reg64(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, src: AArch64GeneralReg) {
@@ -481,13 +950,7 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         src: AArch64GeneralReg,
         imm32: i32,
     ) {
-        if imm32 < 0 {
-            todo!("immediate addition with values less than 0");
-        } else if imm32 < 0xFFF {
-            add_reg64_reg64_imm12(buf, dst, src, imm32 as u16);
-        } else {
-            todo!("immediate additions with values greater than 12bits");
-        }
+        Self::add_sub_reg64_reg64_imm32(buf, false, dst, src, imm32);
     }
     #[inline(always)]
     fn add_reg64_reg64_reg64(
@@ -496,7 +959,8 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         src1: AArch64GeneralReg,
         src2: AArch64GeneralReg,
     ) {
-        add_reg64_reg64_reg64(buf, dst, src1, src2);
+        // Set the flags, so that set_if_overflow can follow this.
+        adds_reg64_reg64_reg64(buf, dst, src1, src2);
     }
     #[inline(always)]
     fn add_freg32_freg32_freg32(
@@ -518,18 +982,48 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
     }
 
     #[inline(always)]
-    fn call(_buf: &mut Vec<'_, u8>, _relocs: &mut Vec<'_, Relocation>, _fn_name: String) {
-        todo!("calling functions literal for AArch64");
+    fn call(buf: &mut Vec<'_, u8>, relocs: &mut Vec<'_, Relocation>, fn_name: String) {
+        bl_imm26(buf, 0);
+        // Unlike x86, the relocation covers the whole instruction.
+        relocs.push(Relocation::LinkedFunction {
+            offset: buf.len() as u64 - 4,
+            name: fn_name,
+        });
     }
 
     #[inline(always)]
     fn function_pointer(
-        _buf: &mut Vec<'_, u8>,
-        _relocs: &mut Vec<'_, Relocation>,
-        _fn_name: String,
-        _dst: AArch64GeneralReg,
+        buf: &mut Vec<'_, u8>,
+        relocs: &mut Vec<'_, Relocation>,
+        fn_name: String,
+        dst: AArch64GeneralReg,
+    ) {
+        // Like data_pointer: the linker fills in the page and the offset within it.
+        relocs.push(Relocation::FunctionPointer {
+            offset: buf.len() as u64,
+            name: fn_name,
+        });
+
+        adrp_reg64(buf, dst);
+        add_reg64_reg64_imm12(buf, dst, dst, 0);
+    }
+
+    #[inline(always)]
+    fn data_pointer(
+        buf: &mut Vec<'_, u8>,
+        relocs: &mut Vec<'_, Relocation>,
+        data: std::vec::Vec<u8>,
+        dst: AArch64GeneralReg,
     ) {
-        todo!("calling functions literal for AArch64");
+        // ADRP loads the 4KB page the data is in, then ADD adds the data's offset in that page.
+        // The linker fills in both, starting from the relocation on the ADRP.
+        relocs.push(Relocation::LocalData {
+            offset: buf.len() as u64,
+            data,
+        });
+
+        adrp_reg64(buf, dst);
+        add_reg64_reg64_imm12(buf, dst, dst, 0);
     }
 
     #[inline(always)]
@@ -582,29 +1076,33 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
     }
 
     fn irem_reg64_reg64_reg64<'a, ASM, CC>(
-        _buf: &mut Vec<'a, u8>,
+        buf: &mut Vec<'a, u8>,
         _storage_manager: &mut StorageManager<'a, '_, AArch64GeneralReg, AArch64FloatReg, ASM, CC>,
-        _dst: AArch64GeneralReg,
-        _src1: AArch64GeneralReg,
-        _src2: AArch64GeneralReg,
+        dst: AArch64GeneralReg,
+        src1: AArch64GeneralReg,
+        src2: AArch64GeneralReg,
     ) where
         ASM: Assembler<AArch64GeneralReg, AArch64FloatReg>,
         CC: CallConv<AArch64GeneralReg, AArch64FloatReg, ASM>,
     {
-        todo!()
+        // There is no remainder instruction: src1 - (src1 / src2) * src2
+        sdiv_reg64_reg64_reg64(buf, SCRATCH_REG, src1, src2);
+        msub_reg64_reg64_reg64_reg64(buf, dst, SCRATCH_REG, src2, src1);
     }
 
     fn urem_reg64_reg64_reg64<'a, ASM, CC>(
-        _buf: &mut Vec<'a, u8>,
+        buf: &mut Vec<'a, u8>,
         _storage_manager: &mut StorageManager<'a, '_, AArch64GeneralReg, AArch64FloatReg, ASM, CC>,
-        _dst: AArch64GeneralReg,
-        _src1: AArch64GeneralReg,
-        _src2: AArch64GeneralReg,
+        dst: AArch64GeneralReg,
+        src1: AArch64GeneralReg,
+        src2: AArch64GeneralReg,
     ) where
         ASM: Assembler<AArch64GeneralReg, AArch64FloatReg>,
         CC: CallConv<AArch64GeneralReg, AArch64FloatReg, ASM>,
     {
-        todo!()
+        // There is no remainder instruction: src1 - (src1 / src2) * src2
+        udiv_reg64_reg64_reg64(buf, SCRATCH_REG, src1, src2);
+        msub_reg64_reg64_reg64_reg64(buf, dst, SCRATCH_REG, src2, src1);
     }
 
     #[inline(always)]
@@ -647,19 +1145,24 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
 
     #[inline(always)]
     fn jmp_imm32(buf: &mut Vec<'_, u8>, offset: i32) -> usize {
-        if (-(1 << 27)..(1 << 27)).contains(&offset) {
-            b_imm26(buf, offset);
-        } else {
-            todo!("jump offsets over 27 bits for AArch64: {:#x}", offset);
+        // The backend measures offsets from the end of the jump, like x86 does,
+        // but AArch64 branches are relative to the branch instruction itself.
+        let offset = offset + 4;
+        if !(-(1 << 27)..(1 << 27)).contains(&offset) {
+            // That's 128MB either way, which is far more than one procedure's code
+            internal_error!("jump offsets over 27 bits for AArch64: {:#x}", offset);
         }
 
+        b_imm26(buf, offset);
+
         buf.len()
     }
 
     #[inline(always)]
     fn tail_call(buf: &mut Vec<'_, u8>) -> u64 {
-        Self::jmp_imm32(buf, 0);
-        buf.len() as u64 - 4 // TODO is 4 the correct offset in ARM?
+        Self::jmp_imm32(buf, -4);
+        // Unlike x86, the relocation covers the whole instruction.
+        buf.len() as u64 - 4
     }
 
     #[inline(always)]
@@ -672,19 +1175,14 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         if imm < (1 << 12) {
             cmp_reg64_imm12(buf, reg, imm as u16);
         } else {
-            todo!(
-                "cmp immediate with value over 12 bits for AArch64: {:#x}",
-                imm
-            );
-        }
-
-        if (-(1 << 20)..(1 << 20)).contains(&offset) {
-            b_cond_imm19(buf, ConditionCode::NE, offset);
-        } else {
-            todo!("jump offsets over 20 bits for AArch64: {:#x}", offset);
+            Self::mov_reg64_imm64(buf, SCRATCH_REG, imm as i64);
+            cmp_reg64_reg64(buf, reg, SCRATCH_REG);
         }
 
-        buf.len()
+        // B.NE only reaches 1MB, which a big procedure can exceed. So this skips over a B
+        // instead, which keeps the size the same whatever the offset is patched to later.
+        b_cond_imm19(buf, ConditionCode::EQ, 8);
+        Self::jmp_imm32(buf, offset)
     }
 
     #[inline(always)]
@@ -704,7 +1202,8 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
                 fmov_freg_imm8(buf, FloatWidth::F32, dst, imm8);
             }
             None => {
-                todo!("loading f32 literal over 8 bits for AArch64");
+                Self::mov_reg64_imm64(buf, SCRATCH_REG, imm.to_bits() as i64);
+                fmov_freg_reg(buf, FloatWidth::F32, dst, SCRATCH_REG);
             }
         }
     }
@@ -725,7 +1224,8 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
                 fmov_freg_imm8(buf, FloatWidth::F64, dst, imm8);
             }
             None => {
-                todo!("loading f64 literal over 8 bits for AArch64");
+                Self::mov_reg64_imm64(buf, SCRATCH_REG, imm.to_bits() as i64);
+                fmov_freg_reg(buf, FloatWidth::F64, dst, SCRATCH_REG);
             }
         }
     }
@@ -752,12 +1252,12 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
     }
 
     #[inline(always)]
-    fn mov_reg32_freg32(_buf: &mut Vec<'_, u8>, _dst: AArch64GeneralReg, _src: AArch64FloatReg) {
-        unimplemented!();
+    fn mov_reg32_freg32(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, src: AArch64FloatReg) {
+        fmov_reg_freg(buf, FloatWidth::F32, dst, src);
     }
     #[inline(always)]
-    fn mov_reg64_freg64(_buf: &mut Vec<'_, u8>, _dst: AArch64GeneralReg, _src: AArch64FloatReg) {
-        unimplemented!();
+    fn mov_reg64_freg64(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, src: AArch64FloatReg) {
+        fmov_reg_freg(buf, FloatWidth::F64, dst, src);
     }
 
     #[inline(always)]
@@ -768,87 +1268,77 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         src: AArch64GeneralReg,
     ) {
         match register_width {
-            RegisterWidth::W8 => todo!(),
-            RegisterWidth::W16 => todo!(),
-            RegisterWidth::W32 => todo!(),
+            RegisterWidth::W8 | RegisterWidth::W16 => {
+                uxt_reg32_reg32(buf, register_width, dst, src)
+            }
+            RegisterWidth::W32 => mov_reg32_reg32(buf, dst, src),
             RegisterWidth::W64 => mov_reg64_reg64(buf, dst, src),
         }
     }
 
     #[inline(always)]
     fn movsx_reg_reg(
-        _buf: &mut Vec<'_, u8>,
-        _input_width: RegisterWidth,
-        _dst: AArch64GeneralReg,
-        _src: AArch64GeneralReg,
+        buf: &mut Vec<'_, u8>,
+        input_width: RegisterWidth,
+        dst: AArch64GeneralReg,
+        src: AArch64GeneralReg,
     ) {
-        todo!("move with sign extension");
+        match input_width {
+            RegisterWidth::W64 => mov_reg64_reg64(buf, dst, src),
+            _ => sxt_reg64_reg(buf, input_width, dst, src),
+        }
     }
 
     #[inline(always)]
-    fn mov_freg64_base32(_buf: &mut Vec<'_, u8>, _dst: AArch64FloatReg, _offset: i32) {
-        todo!("loading floating point reg from base offset for AArch64");
+    fn mov_freg64_base32(buf: &mut Vec<'_, u8>, dst: AArch64FloatReg, offset: i32) {
+        Self::load_freg(buf, FloatWidth::F64, dst, AArch64GeneralReg::FP, offset);
     }
     #[inline(always)]
     fn mov_reg64_base32(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, offset: i32) {
-        if offset < 0 {
-            todo!("negative base offsets for AArch64");
-        } else if offset < (0xFFF << 8) {
-            debug_assert!(offset % 8 == 0);
-            ldr_reg64_reg64_imm12(buf, dst, AArch64GeneralReg::FP, (offset as u16) >> 3);
-        } else {
-            todo!("base offsets over 32k for AArch64");
-        }
+        Self::load_reg(buf, RegisterWidth::W64, dst, AArch64GeneralReg::FP, offset);
     }
     #[inline(always)]
-    fn mov_reg32_base32(_buf: &mut Vec<'_, u8>, _dst: AArch64GeneralReg, _offset: i32) {
-        todo!()
+    fn mov_reg32_base32(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, offset: i32) {
+        Self::load_reg(buf, RegisterWidth::W32, dst, AArch64GeneralReg::FP, offset);
     }
     #[inline(always)]
-    fn mov_reg16_base32(_buf: &mut Vec<'_, u8>, _dst: AArch64GeneralReg, _offset: i32) {
-        todo!()
+    fn mov_reg16_base32(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, offset: i32) {
+        Self::load_reg(buf, RegisterWidth::W16, dst, AArch64GeneralReg::FP, offset);
     }
     #[inline(always)]
-    fn mov_reg8_base32(_buf: &mut Vec<'_, u8>, _dst: AArch64GeneralReg, _offset: i32) {
-        todo!()
+    fn mov_reg8_base32(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, offset: i32) {
+        Self::load_reg(buf, RegisterWidth::W8, dst, AArch64GeneralReg::FP, offset);
     }
     #[inline(always)]
-    fn mov_base32_freg64(_buf: &mut Vec<'_, u8>, _offset: i32, _src: AArch64FloatReg) {
-        todo!("saving floating point reg to base offset for AArch64");
+    fn mov_base32_freg64(buf: &mut Vec<'_, u8>, offset: i32, src: AArch64FloatReg) {
+        Self::store_freg(buf, FloatWidth::F64, src, AArch64GeneralReg::FP, offset);
     }
     #[inline(always)]
     fn movesd_mem64_offset32_freg64(
-        _buf: &mut Vec<'_, u8>,
-        _ptr: AArch64GeneralReg,
-        _offset: i32,
-        _src: AArch64FloatReg,
+        buf: &mut Vec<'_, u8>,
+        ptr: AArch64GeneralReg,
+        offset: i32,
+        src: AArch64FloatReg,
     ) {
-        todo!()
+        Self::store_freg(buf, FloatWidth::F64, src, ptr, offset);
     }
 
     #[inline(always)]
     fn mov_base32_reg64(buf: &mut Vec<'_, u8>, offset: i32, src: AArch64GeneralReg) {
-        if offset < 0 {
-            todo!("negative base offsets for AArch64");
-        } else if offset < (0xFFF << 8) {
-            debug_assert!(offset % 8 == 0);
-            str_reg64_reg64_imm12(buf, src, AArch64GeneralReg::FP, (offset as u16) >> 3);
-        } else {
-            todo!("base offsets over 32k for AArch64");
-        }
+        Self::store_reg(buf, RegisterWidth::W64, src, AArch64GeneralReg::FP, offset);
     }
 
     #[inline(always)]
-    fn mov_base32_reg32(_buf: &mut Vec<'_, u8>, _offset: i32, _src: AArch64GeneralReg) {
-        todo!()
+    fn mov_base32_reg32(buf: &mut Vec<'_, u8>, offset: i32, src: AArch64GeneralReg) {
+        Self::store_reg(buf, RegisterWidth::W32, src, AArch64GeneralReg::FP, offset);
     }
     #[inline(always)]
-    fn mov_base32_reg16(_buf: &mut Vec<'_, u8>, _offset: i32, _src: AArch64GeneralReg) {
-        todo!()
+    fn mov_base32_reg16(buf: &mut Vec<'_, u8>, offset: i32, src: AArch64GeneralReg) {
+        Self::store_reg(buf, RegisterWidth::W16, src, AArch64GeneralReg::FP, offset);
     }
     #[inline(always)]
-    fn mov_base32_reg8(_buf: &mut Vec<'_, u8>, _offset: i32, _src: AArch64GeneralReg) {
-        todo!()
+    fn mov_base32_reg8(buf: &mut Vec<'_, u8>, offset: i32, src: AArch64GeneralReg) {
+        Self::store_reg(buf, RegisterWidth::W8, src, AArch64GeneralReg::FP, offset);
     }
 
     #[inline(always)]
@@ -858,14 +1348,7 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         src: AArch64GeneralReg,
         offset: i32,
     ) {
-        if offset < 0 {
-            todo!("negative mem offsets for AArch64");
-        } else if offset < (0xFFF << 8) {
-            debug_assert!(offset % 8 == 0);
-            ldr_reg64_reg64_imm12(buf, dst, src, (offset as u16) >> 3);
-        } else {
-            todo!("mem offsets over 32k for AArch64");
-        }
+        Self::load_reg(buf, RegisterWidth::W64, dst, src, offset);
     }
     #[inline(always)]
     fn mov_reg32_mem32_offset32(
@@ -874,32 +1357,25 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         src: AArch64GeneralReg,
         offset: i32,
     ) {
-        if offset < 0 {
-            todo!("negative mem offsets for AArch64");
-        } else if offset < (0xFFF << 8) {
-            debug_assert!(offset % 8 == 0);
-            ldr_reg64_reg64_imm12(buf, dst, src, (offset as u16) >> 3);
-        } else {
-            todo!("mem offsets over 32k for AArch64");
-        }
+        Self::load_reg(buf, RegisterWidth::W32, dst, src, offset);
     }
     #[inline(always)]
     fn mov_reg16_mem16_offset32(
-        _buf: &mut Vec<'_, u8>,
-        _dst: AArch64GeneralReg,
-        _src: AArch64GeneralReg,
-        _offset: i32,
+        buf: &mut Vec<'_, u8>,
+        dst: AArch64GeneralReg,
+        src: AArch64GeneralReg,
+        offset: i32,
     ) {
-        todo!()
+        Self::load_reg(buf, RegisterWidth::W16, dst, src, offset);
     }
     #[inline(always)]
     fn mov_reg8_mem8_offset32(
-        _buf: &mut Vec<'_, u8>,
-        _dst: AArch64GeneralReg,
-        _src: AArch64GeneralReg,
-        _offset: i32,
+        buf: &mut Vec<'_, u8>,
+        dst: AArch64GeneralReg,
+        src: AArch64GeneralReg,
+        offset: i32,
     ) {
-        todo!()
+        Self::load_reg(buf, RegisterWidth::W8, dst, src, offset);
     }
 
     #[inline(always)]
@@ -909,95 +1385,82 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         offset: i32,
         src: AArch64GeneralReg,
     ) {
-        if offset < 0 {
-            todo!("negative mem offsets for AArch64");
-        } else if offset < (0xFFF << 8) {
-            debug_assert!(offset % 8 == 0);
-            str_reg64_reg64_imm12(buf, src, dst, (offset as u16) >> 3);
-        } else {
-            todo!("mem offsets over 32k for AArch64");
-        }
+        Self::store_reg(buf, RegisterWidth::W64, src, dst, offset);
     }
 
     #[inline(always)]
     fn mov_mem32_offset32_reg32(
-        _buf: &mut Vec<'_, u8>,
-        _dst: AArch64GeneralReg,
-        _offset: i32,
-        _src: AArch64GeneralReg,
+        buf: &mut Vec<'_, u8>,
+        dst: AArch64GeneralReg,
+        offset: i32,
+        src: AArch64GeneralReg,
     ) {
-        todo!()
+        Self::store_reg(buf, RegisterWidth::W32, src, dst, offset);
     }
 
     #[inline(always)]
     fn mov_mem16_offset32_reg16(
-        _buf: &mut Vec<'_, u8>,
-        _dst: AArch64GeneralReg,
-        _offset: i32,
-        _src: AArch64GeneralReg,
+        buf: &mut Vec<'_, u8>,
+        dst: AArch64GeneralReg,
+        offset: i32,
+        src: AArch64GeneralReg,
     ) {
-        todo!()
+        Self::store_reg(buf, RegisterWidth::W16, src, dst, offset);
     }
 
     #[inline(always)]
     fn mov_mem8_offset32_reg8(
-        _buf: &mut Vec<'_, u8>,
-        _dst: AArch64GeneralReg,
-        _offset: i32,
-        _src: AArch64GeneralReg,
+        buf: &mut Vec<'_, u8>,
+        dst: AArch64GeneralReg,
+        offset: i32,
+        src: AArch64GeneralReg,
     ) {
-        todo!()
+        Self::store_reg(buf, RegisterWidth::W8, src, dst, offset);
     }
 
     #[inline(always)]
     fn movsx_reg64_base32(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, offset: i32, size: u8) {
         debug_assert!(size <= 8);
-        if size == 8 {
-            Self::mov_reg64_base32(buf, dst, offset);
-        } else if size == 4 {
-            todo!("sign extending 4 byte values");
-        } else if size == 2 {
-            todo!("sign extending 2 byte values");
-        } else if size == 1 {
-            todo!("sign extending 1 byte values");
-        } else {
-            internal_error!("Invalid size for sign extension: {}", size);
-        }
+        let width = match size {
+            8 => return Self::mov_reg64_base32(buf, dst, offset),
+            4 => RegisterWidth::W32,
+            2 => RegisterWidth::W16,
+            1 => RegisterWidth::W8,
+            _ => internal_error!("Invalid size for sign extension: {}", size),
+        };
+        Self::load_reg_signed(buf, width, dst, AArch64GeneralReg::FP, offset);
     }
     #[inline(always)]
     fn movzx_reg64_base32(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, offset: i32, size: u8) {
         debug_assert!(size <= 8);
-        if size == 8 {
-            Self::mov_reg64_base32(buf, dst, offset);
-        } else if size == 4 {
-            todo!("zero extending 4 byte values");
-        } else if size == 2 {
-            todo!("zero extending 2 byte values");
-        } else if size == 1 {
-            todo!("zero extending 1 byte values");
-        } else {
-            internal_error!("Invalid size for zero extension: {}", size);
-        }
+        let width = match size {
+            8 => RegisterWidth::W64,
+            4 => RegisterWidth::W32,
+            2 => RegisterWidth::W16,
+            1 => RegisterWidth::W8,
+            _ => internal_error!("Invalid size for zero extension: {}", size),
+        };
+        // Loads of less than 64 bits always zero extend.
+        Self::load_reg(buf, width, dst, AArch64GeneralReg::FP, offset);
     }
 
     #[inline(always)]
-    fn mov_freg64_stack32(_buf: &mut Vec<'_, u8>, _dst: AArch64FloatReg, _offset: i32) {
-        todo!("loading floating point reg from stack for AArch64");
+    fn mov_freg64_stack32(buf: &mut Vec<'_, u8>, dst: AArch64FloatReg, offset: i32) {
+        Self::load_freg(buf, FloatWidth::F64, dst, AArch64GeneralReg::ZRSP, offset);
     }
     #[inline(always)]
-    fn mov_reg64_stack32(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, offset: i32) {
-        if offset < 0 {
-            todo!("negative stack offsets for AArch64");
-        } else if offset < (0xFFF << 8) {
-            debug_assert!(offset % 8 == 0);
-            ldr_reg64_reg64_imm12(buf, dst, AArch64GeneralReg::ZRSP, (offset as u16) >> 3);
-        } else {
-            todo!("stack offsets over 32k for AArch64");
-        }
+    fn mov_reg64_stack32(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, offset: i32) {
+        Self::load_reg(
+            buf,
+            RegisterWidth::W64,
+            dst,
+            AArch64GeneralReg::ZRSP,
+            offset,
+        );
     }
     #[inline(always)]
-    fn mov_stack32_freg64(_buf: &mut Vec<'_, u8>, _offset: i32, _src: AArch64FloatReg) {
-        todo!("saving floating point reg to stack for AArch64");
+    fn mov_stack32_freg64(buf: &mut Vec<'_, u8>, offset: i32, src: AArch64FloatReg) {
+        Self::store_freg(buf, FloatWidth::F64, src, AArch64GeneralReg::ZRSP, offset);
     }
     #[inline(always)]
     fn mov_stack32_reg(
@@ -1006,21 +1469,7 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         offset: i32,
         src: AArch64GeneralReg,
     ) {
-        match register_width {
-            RegisterWidth::W8 => todo!(),
-            RegisterWidth::W16 => todo!(),
-            RegisterWidth::W32 => todo!(),
-            RegisterWidth::W64 => {
-                if offset < 0 {
-                    todo!("negative stack offsets for AArch64");
-                } else if offset < (0xFFF << 8) {
-                    debug_assert!(offset % 8 == 0);
-                    str_reg64_reg64_imm12(buf, src, AArch64GeneralReg::ZRSP, (offset as u16) >> 3);
-                } else {
-                    todo!("stack offsets over 32k for AArch64");
-                }
-            }
-        }
+        Self::store_reg(buf, register_width, src, AArch64GeneralReg::ZRSP, offset);
     }
     #[inline(always)]
     fn neg_reg64_reg64(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, src: AArch64GeneralReg) {
@@ -1034,13 +1483,7 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         src: AArch64GeneralReg,
         imm32: i32,
     ) {
-        if imm32 < 0 {
-            todo!("immediate subtractions with values less than 0");
-        } else if imm32 < 0xFFF {
-            sub_reg64_reg64_imm12(buf, dst, src, imm32 as u16);
-        } else {
-            todo!("immediate subtractions with values greater than 12bits");
-        }
+        Self::add_sub_reg64_reg64_imm32(buf, true, dst, src, imm32);
     }
     #[inline(always)]
     fn sub_reg64_reg64_reg64(
@@ -1127,8 +1570,8 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         fcvt_freg64_freg32(buf, dst, src);
     }
 
-    fn set_if_overflow(_buf: &mut Vec<'_, u8>, _dst: AArch64GeneralReg) {
-        todo!("set if overflow for AArch64");
+    fn set_if_overflow(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg) {
+        cset_reg64_cond(buf, dst, ConditionCode::VS);
     }
 
     #[inline(always)]
@@ -1247,25 +1690,156 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
     }
 
     fn mov_freg64_mem64_offset32(
-        _buf: &mut Vec<'_, u8>,
-        _dst: AArch64FloatReg,
-        _src: AArch64GeneralReg,
-        _offset: i32,
+        buf: &mut Vec<'_, u8>,
+        dst: AArch64FloatReg,
+        src: AArch64GeneralReg,
+        offset: i32,
     ) {
-        todo!()
+        Self::load_freg(buf, FloatWidth::F64, dst, src, offset);
     }
 
     fn mov_freg32_mem32_offset32(
-        _buf: &mut Vec<'_, u8>,
-        _dst: AArch64FloatReg,
-        _src: AArch64GeneralReg,
-        _offset: i32,
+        buf: &mut Vec<'_, u8>,
+        dst: AArch64FloatReg,
+        src: AArch64GeneralReg,
+        offset: i32,
     ) {
-        todo!()
+        Self::load_freg(buf, FloatWidth::F32, dst, src, offset);
     }
 }
 
-impl AArch64Assembler {}
+/// How a load or store addresses `base + offset`.
+enum Address {
+    /// An unsigned offset, in multiples of the access size.
+    Scaled(AArch64GeneralReg, u16),
+    /// A signed offset in bytes, from -256 to 255.
+    Unscaled(AArch64GeneralReg, i16),
+}
+
+impl AArch64Assembler {
+    /// Adds (or subtracts) an immediate 24 bits at a time, using the shifted form for the upper 12.
+    /// Unlike going through a register, this also works when `src` is the stack pointer.
+    fn add_sub_reg64_reg64_imm32(
+        buf: &mut Vec<'_, u8>,
+        subtract: bool,
+        dst: AArch64GeneralReg,
+        src: AArch64GeneralReg,
+        imm32: i32,
+    ) {
+        let (subtract, mut imm) = if imm32 < 0 {
+            (!subtract, imm32.unsigned_abs())
+        } else {
+            (subtract, imm32 as u32)
+        };
+
+        let mut src = src;
+        loop {
+            let chunk = imm.min(0xFF_FFFF);
+            let high = (chunk >> 12) as u16;
+            let low = (chunk & 0xFFF) as u16;
+
+            if high != 0 {
+                if subtract {
+                    sub_reg64_reg64_imm12_lsl12(buf, dst, src, high);
+                } else {
+                    add_reg64_reg64_imm12_lsl12(buf, dst, src, high);
+                }
+                src = dst;
+            }
+            if low != 0 || high == 0 {
+                if subtract {
+                    sub_reg64_reg64_imm12(buf, dst, src, low);
+                } else {
+                    add_reg64_reg64_imm12(buf, dst, src, low);
+                }
+                src = dst;
+            }
+
+            imm -= chunk;
+            if imm == 0 {
+                break;
+            }
+        }
+    }
+
+    /// Picks an addressing mode for an access of `size` bytes at `base + offset`.
+    /// If neither immediate form fits, the address is computed into the scratch register.
+    fn address(buf: &mut Vec<'_, u8>, base: AArch64GeneralReg, offset: i32, size: i32) -> Address {
+        if offset >= 0 && offset % size == 0 && offset / size <= 0xFFF {
+            Address::Scaled(base, (offset / size) as u16)
+        } else if (-256..256).contains(&offset) {
+            Address::Unscaled(base, offset as i16)
+        } else {
+            Self::add_reg64_reg64_imm32(buf, SCRATCH_REG, base, offset);
+            Address::Scaled(SCRATCH_REG, 0)
+        }
+    }
+
+    fn load_reg(
+        buf: &mut Vec<'_, u8>,
+        width: RegisterWidth,
+        dst: AArch64GeneralReg,
+        base: AArch64GeneralReg,
+        offset: i32,
+    ) {
+        match Self::address(buf, base, offset, 1 << encode_register_width(width)) {
+            Address::Scaled(base, imm12) => ldr_reg_reg64_imm12(buf, width, dst, base, imm12),
+            Address::Unscaled(base, imm9) => ldur_reg_reg64_imm9(buf, width, dst, base, imm9),
+        }
+    }
+
+    fn load_reg_signed(
+        buf: &mut Vec<'_, u8>,
+        width: RegisterWidth,
+        dst: AArch64GeneralReg,
+        base: AArch64GeneralReg,
+        offset: i32,
+    ) {
+        match Self::address(buf, base, offset, 1 << encode_register_width(width)) {
+            Address::Scaled(base, imm12) => ldrs_reg64_reg64_imm12(buf, width, dst, base, imm12),
+            Address::Unscaled(base, imm9) => ldurs_reg64_reg64_imm9(buf, width, dst, base, imm9),
+        }
+    }
+
+    fn store_reg(
+        buf: &mut Vec<'_, u8>,
+        width: RegisterWidth,
+        src: AArch64GeneralReg,
+        base: AArch64GeneralReg,
+        offset: i32,
+    ) {
+        match Self::address(buf, base, offset, 1 << encode_register_width(width)) {
+            Address::Scaled(base, imm12) => str_reg_reg64_imm12(buf, width, src, base, imm12),
+            Address::Unscaled(base, imm9) => stur_reg_reg64_imm9(buf, width, src, base, imm9),
+        }
+    }
+
+    fn load_freg(
+        buf: &mut Vec<'_, u8>,
+        width: FloatWidth,
+        dst: AArch64FloatReg,
+        base: AArch64GeneralReg,
+        offset: i32,
+    ) {
+        match Self::address(buf, base, offset, 1 << encode_float_register_width(width)) {
+            Address::Scaled(base, imm12) => ldr_freg_reg64_imm12(buf, width, dst, base, imm12),
+            Address::Unscaled(base, imm9) => ldur_freg_reg64_imm9(buf, width, dst, base, imm9),
+        }
+    }
+
+    fn store_freg(
+        buf: &mut Vec<'_, u8>,
+        width: FloatWidth,
+        src: AArch64FloatReg,
+        base: AArch64GeneralReg,
+        offset: i32,
+    ) {
+        match Self::address(buf, base, offset, 1 << encode_float_register_width(width)) {
+            Address::Scaled(base, imm12) => str_freg_reg64_imm12(buf, width, src, base, imm12),
+            Address::Unscaled(base, imm9) => stur_freg_reg64_imm9(buf, width, src, base, imm9),
+        }
+    }
+}
 
 // Instructions
 // ARM manual section C3
@@ -1387,6 +1961,39 @@ impl ArithmeticImmediate {
     }
 }
 
+#[derive(PackedStruct, Debug)]
+#[packed_struct(endian = "msb")]
+pub struct PcRelativeAddressing {
+    op: bool, // false=ADR, true=ADRP
+    immlo: Integer<u8, packed_bits::Bits<2>>,
+    fixed: Integer<u8, packed_bits::Bits<5>>, // = 0b10000,
+    immhi: Integer<u32, packed_bits::Bits<19>>,
+    reg_d: Integer<u8, packed_bits::Bits<5>>,
+}
+
+impl Aarch64Bytes for PcRelativeAddressing {}
+
+pub struct PcRelativeAddressingParams {
+    op: bool,
+    rd: AArch64GeneralReg,
+    imm21: u32,
+}
+
+impl PcRelativeAddressing {
+    #[inline(always)]
+    fn new(PcRelativeAddressingParams { op, rd, imm21 }: PcRelativeAddressingParams) -> Self {
+        debug_assert!(imm21 <= 0x1F_FFFF);
+
+        Self {
+            op,
+            immlo: ((imm21 & 0b11) as u8).into(),
+            fixed: 0b10000.into(),
+            immhi: (imm21 >> 2).into(),
+            reg_d: rd.id().into(),
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 #[allow(dead_code)]
 enum ShiftType {
@@ -1704,6 +2311,7 @@ impl Aarch64Bytes for DataProcessingThreeSource {}
 pub struct DataProcessingThreeSourceParams {
     op31: u8,
     rm: AArch64GeneralReg,
+    o0: bool,
     ra: AArch64GeneralReg,
     rn: AArch64GeneralReg,
     rd: AArch64GeneralReg,
@@ -1715,6 +2323,7 @@ impl DataProcessingThreeSource {
         DataProcessingThreeSourceParams {
             op31,
             rm,
+            o0,
             ra,
             rn,
             rd,
@@ -1728,7 +2337,7 @@ impl DataProcessingThreeSource {
             fixed: 0b011011.into(),
             op31: op31.into(),
             rm: rm.id().into(),
-            o0: false,
+            o0,
             ra: ra.id().into(),
             rn: rn.id().into(),
             rd: rd.id().into(),
@@ -1766,6 +2375,7 @@ pub struct LogicalShiftedRegister {
 impl Aarch64Bytes for LogicalShiftedRegister {}
 
 pub struct LogicalShiftedRegisterParams {
+    sf: bool,
     op: LogicalOp,
     shift: ShiftType,
     imm6: u8,
@@ -1778,6 +2388,7 @@ impl LogicalShiftedRegister {
     #[inline(always)]
     fn new(
         LogicalShiftedRegisterParams {
+            sf,
             op,
             shift,
             imm6,
@@ -1808,9 +2419,9 @@ impl LogicalShiftedRegister {
             shift: shift.id().into(),
             fixed: 0b01010.into(),
             op: op.into(),
-            // true for 64 bit addition
-            // false for 32 bit addition
-            sf: true,
+            // true for 64 bit operands
+            // false for 32 bit operands
+            sf,
         }
     }
 }
@@ -1890,12 +2501,14 @@ impl UnconditionalBranchImmediate {
 // Uses unsigned Offset
 // opc = 0b01 means load
 // opc = 0b00 means store
+// opc = 0b10 means load and sign extend to 64 bits
+// v = true means the register is a floating-point register
 #[derive(PackedStruct, Debug)]
 #[packed_struct(endian = "msb")]
 pub struct LoadStoreRegisterImmediate {
     size: Integer<u8, packed_bits::Bits<2>>,
     fixed: Integer<u8, packed_bits::Bits<3>>, // = 0b111,
-    fixed2: bool,
+    v: bool,
     fixed3: Integer<u8, packed_bits::Bits<2>>,
     opc: Integer<u8, packed_bits::Bits<2>>,
     imm12: Integer<u16, packed_bits::Bits<12>>,
@@ -1905,47 +2518,220 @@ pub struct LoadStoreRegisterImmediate {
 
 impl Aarch64Bytes for LoadStoreRegisterImmediate {}
 
-pub struct LoadStoreRegisterImmediateParams {
+pub struct LoadStoreRegisterImmediateParams<R: RegTrait> {
     size: u8,
     imm12: u16,
     rn: AArch64GeneralReg,
-    rt: AArch64GeneralReg,
+    rt: R,
 }
 
 impl LoadStoreRegisterImmediate {
     #[inline(always)]
-    fn new(
+    fn new<R: RegTrait>(
         opc: u8,
+        v: bool,
         LoadStoreRegisterImmediateParams {
             size,
             imm12,
             rn,
             rt,
-        }: LoadStoreRegisterImmediateParams,
+        }: LoadStoreRegisterImmediateParams<R>,
     ) -> Self {
         debug_assert!(size <= 0b11);
         debug_assert!(imm12 <= 0xFFF);
 
         Self {
-            rt: rt.id().into(),
+            rt: rt.value().into(),
             rn: rn.id().into(),
             imm12: imm12.into(),
             opc: opc.into(),
             fixed3: 0b01.into(),
-            fixed2: false,
+            v,
+            fixed: 0b111.into(),
+            size: size.into(),
+        }
+    }
+
+    #[inline(always)]
+    fn new_load(params: LoadStoreRegisterImmediateParams<AArch64GeneralReg>) -> Self {
+        Self::new(0b01, false, params)
+    }
+
+    #[inline(always)]
+    fn new_load_signed(params: LoadStoreRegisterImmediateParams<AArch64GeneralReg>) -> Self {
+        Self::new(0b10, false, params)
+    }
+
+    #[inline(always)]
+    fn new_store(params: LoadStoreRegisterImmediateParams<AArch64GeneralReg>) -> Self {
+        Self::new(0b00, false, params)
+    }
+
+    #[inline(always)]
+    fn new_float_load(params: LoadStoreRegisterImmediateParams<AArch64FloatReg>) -> Self {
+        Self::new(0b01, true, params)
+    }
+
+    #[inline(always)]
+    fn new_float_store(params: LoadStoreRegisterImmediateParams<AArch64FloatReg>) -> Self {
+        Self::new(0b00, true, params)
+    }
+}
+
+// Uses a signed, unscaled offset.
+// The opc and v fields mean the same as for LoadStoreRegisterImmediate.
+#[derive(PackedStruct, Debug)]
+#[packed_struct(endian = "msb")]
+pub struct LoadStoreRegisterUnscaledImmediate {
+    size: Integer<u8, packed_bits::Bits<2>>,
+    fixed: Integer<u8, packed_bits::Bits<3>>, // = 0b111,
+    v: bool,
+    fixed2: Integer<u8, packed_bits::Bits<2>>, // = 0b00,
+    opc: Integer<u8, packed_bits::Bits<2>>,
+    fixed3: bool,
+    imm9: Integer<u16, packed_bits::Bits<9>>,
+    fixed4: Integer<u8, packed_bits::Bits<2>>, // = 0b00,
+    rn: Integer<u8, packed_bits::Bits<5>>,
+    rt: Integer<u8, packed_bits::Bits<5>>,
+}
+
+impl Aarch64Bytes for LoadStoreRegisterUnscaledImmediate {}
+
+pub struct LoadStoreRegisterUnscaledImmediateParams<R: RegTrait> {
+    size: u8,
+    imm9: i16,
+    rn: AArch64GeneralReg,
+    rt: R,
+}
+
+impl LoadStoreRegisterUnscaledImmediate {
+    #[inline(always)]
+    fn new<R: RegTrait>(
+        opc: u8,
+        v: bool,
+        LoadStoreRegisterUnscaledImmediateParams {
+            size,
+            imm9,
+            rn,
+            rt,
+        }: LoadStoreRegisterUnscaledImmediateParams<R>,
+    ) -> Self {
+        debug_assert!(size <= 0b11);
+        debug_assert!((-256..256).contains(&imm9));
+
+        Self {
+            rt: rt.value().into(),
+            rn: rn.id().into(),
+            fixed4: 0b00.into(),
+            imm9: ((imm9 as u16) & 0x1FF).into(),
+            fixed3: false,
+            opc: opc.into(),
+            fixed2: 0b00.into(),
+            v,
             fixed: 0b111.into(),
             size: size.into(),
         }
     }
 
     #[inline(always)]
-    fn new_load(params: LoadStoreRegisterImmediateParams) -> Self {
-        Self::new(0b01, params)
+    fn new_load(params: LoadStoreRegisterUnscaledImmediateParams<AArch64GeneralReg>) -> Self {
+        Self::new(0b01, false, params)
+    }
+
+    #[inline(always)]
+    fn new_load_signed(
+        params: LoadStoreRegisterUnscaledImmediateParams<AArch64GeneralReg>,
+    ) -> Self {
+        Self::new(0b10, false, params)
+    }
+
+    #[inline(always)]
+    fn new_store(params: LoadStoreRegisterUnscaledImmediateParams<AArch64GeneralReg>) -> Self {
+        Self::new(0b00, false, params)
+    }
+
+    #[inline(always)]
+    fn new_float_load(params: LoadStoreRegisterUnscaledImmediateParams<AArch64FloatReg>) -> Self {
+        Self::new(0b01, true, params)
+    }
+
+    #[inline(always)]
+    fn new_float_store(params: LoadStoreRegisterUnscaledImmediateParams<AArch64FloatReg>) -> Self {
+        Self::new(0b00, true, params)
+    }
+}
+
+/// The size field of a load or store of the given register width.
+fn encode_register_width(width: RegisterWidth) -> u8 {
+    match width {
+        RegisterWidth::W8 => 0b00,
+        RegisterWidth::W16 => 0b01,
+        RegisterWidth::W32 => 0b10,
+        RegisterWidth::W64 => 0b11,
+    }
+}
+
+/// The size field of a load or store of the given float width.
+fn encode_float_register_width(width: FloatWidth) -> u8 {
+    match width {
+        FloatWidth::F32 => 0b10,
+        FloatWidth::F64 => 0b11,
     }
+}
+
+// opc = 0b00 means SBFM
+// opc = 0b10 means UBFM
+#[derive(PackedStruct)]
+#[packed_struct(endian = "msb")]
+pub struct Bitfield {
+    sf: bool,
+    opc: Integer<u8, packed_bits::Bits<2>>,
+    fixed: Integer<u8, packed_bits::Bits<6>>, // = 0b100110,
+    n: bool,
+    immr: Integer<u8, packed_bits::Bits<6>>,
+    imms: Integer<u8, packed_bits::Bits<6>>,
+    rn: Integer<u8, packed_bits::Bits<5>>,
+    rd: Integer<u8, packed_bits::Bits<5>>,
+}
+
+impl Aarch64Bytes for Bitfield {}
+
+pub struct BitfieldParams {
+    sf: bool,
+    opc: u8,
+    immr: u8,
+    imms: u8,
+    rn: AArch64GeneralReg,
+    rd: AArch64GeneralReg,
+}
 
+impl Bitfield {
     #[inline(always)]
-    fn new_store(params: LoadStoreRegisterImmediateParams) -> Self {
-        Self::new(0b00, params)
+    fn new(
+        BitfieldParams {
+            sf,
+            opc,
+            immr,
+            imms,
+            rn,
+            rd,
+        }: BitfieldParams,
+    ) -> Self {
+        debug_assert!(opc <= 0b11);
+        debug_assert!(immr <= 0b111111);
+        debug_assert!(imms <= 0b111111);
+
+        Self {
+            sf,
+            opc: opc.into(),
+            fixed: 0b100110.into(),
+            // N must match sf
+            n: sf,
+            immr: immr.into(),
+            imms: imms.into(),
+            rn: rn.id().into(),
+            rd: rd.id().into(),
+        }
     }
 }
 
@@ -2021,30 +2807,32 @@ pub struct ConversionBetweenFloatingPointAndInteger {
 
 impl Aarch64Bytes for ConversionBetweenFloatingPointAndInteger {}
 
-pub struct ConversionBetweenFloatingPointAndIntegerParams {
+pub struct ConversionBetweenFloatingPointAndIntegerParams<N: RegTrait, D: RegTrait> {
+    sf: bool,
     ptype: FloatWidth,
     rmode: u8,
     opcode: u8,
-    rn: AArch64GeneralReg,
-    rd: AArch64FloatReg,
+    rn: N,
+    rd: D,
 }
 
 impl ConversionBetweenFloatingPointAndInteger {
     #[inline(always)]
-    fn new(
+    fn new<N: RegTrait, D: RegTrait>(
         ConversionBetweenFloatingPointAndIntegerParams {
+            sf,
             ptype,
             rmode,
             opcode,
             rn,
             rd,
-        }: ConversionBetweenFloatingPointAndIntegerParams,
+        }: ConversionBetweenFloatingPointAndIntegerParams<N, D>,
     ) -> Self {
         debug_assert!(rmode <= 0b11);
         debug_assert!(opcode <= 0b111);
 
         Self {
-            sf: true,
+            sf,
             fixed: false,
             s: false,
             fixed2: 0b11110.into(),
@@ -2053,8 +2841,8 @@ impl ConversionBetweenFloatingPointAndInteger {
             rmode: rmode.into(),
             opcode: opcode.into(),
             fixed4: 0b000000.into(),
-            rn: rn.id().into(),
-            rd: rd.id().into(),
+            rn: rn.value().into(),
+            rd: rd.value().into(),
         }
     }
 }
@@ -2288,9 +3076,50 @@ fn add_reg64_reg64_imm12(
     buf.extend(inst.bytes());
 }
 
-/// `ADD Xd, Xm, Xn` -> Add Xm and Xn and place the result into Xd.
+/// `ADD Xd, Xn, imm12, LSL #12` -> Add Xn and (imm12 << 12) and place the result into Xd.
+#[inline(always)]
+fn add_reg64_reg64_imm12_lsl12(
+    buf: &mut Vec<'_, u8>,
+    dst: AArch64GeneralReg,
+    src: AArch64GeneralReg,
+    imm12: u16,
+) {
+    let inst = ArithmeticImmediate::new(ArithmeticImmediateParams {
+        op: false,
+        s: false,
+        rd: dst,
+        rn: src,
+        imm12,
+        sh: true,
+    });
+
+    buf.extend(inst.bytes());
+}
+
+/// `ADD Xd, Xm, Xn` -> Add Xm and Xn and place the result into Xd.
+#[inline(always)]
+fn add_reg64_reg64_reg64(
+    buf: &mut Vec<'_, u8>,
+    dst: AArch64GeneralReg,
+    src1: AArch64GeneralReg,
+    src2: AArch64GeneralReg,
+) {
+    let inst = ArithmeticShifted::new(ArithmeticShiftedParams {
+        op: false,
+        s: false,
+        shift: ShiftType::LSL,
+        imm6: 0,
+        rm: src2,
+        rn: src1,
+        rd: dst,
+    });
+
+    buf.extend(inst.bytes());
+}
+
+/// `ADDS Xd, Xm, Xn` -> Add Xm and Xn and place the result into Xd. Set condition flags.
 #[inline(always)]
-fn add_reg64_reg64_reg64(
+fn adds_reg64_reg64_reg64(
     buf: &mut Vec<'_, u8>,
     dst: AArch64GeneralReg,
     src1: AArch64GeneralReg,
@@ -2298,7 +3127,7 @@ fn add_reg64_reg64_reg64(
 ) {
     let inst = ArithmeticShifted::new(ArithmeticShiftedParams {
         op: false,
-        s: false,
+        s: true,
         shift: ShiftType::LSL,
         imm6: 0,
         rm: src2,
@@ -2309,6 +3138,19 @@ fn add_reg64_reg64_reg64(
     buf.extend(inst.bytes());
 }
 
+/// `ADRP Xd, 0` -> Place the address of the 4KB page containing PC into Xd.
+/// The page is filled in by a relocation, which is why there's no immediate.
+#[inline(always)]
+fn adrp_reg64(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg) {
+    let inst = PcRelativeAddressing::new(PcRelativeAddressingParams {
+        op: true,
+        rd: dst,
+        imm21: 0,
+    });
+
+    buf.extend(inst.bytes());
+}
+
 /// `AND Xd, Xn, Xm` -> Bitwise AND Xn and Xm and place the result into Xd.
 #[inline(always)]
 fn and_reg64_reg64_reg64(
@@ -2318,6 +3160,7 @@ fn and_reg64_reg64_reg64(
     src2: AArch64GeneralReg,
 ) {
     let inst = LogicalShiftedRegister::new(LogicalShiftedRegisterParams {
+        sf: true,
         op: LogicalOp::AND,
         shift: ShiftType::LSL,
         imm6: 0,
@@ -2376,6 +3219,28 @@ fn b_cond_imm19(buf: &mut Vec<'_, u8>, cond: ConditionCode, imm19: i32) {
 /// `B imm26` -> Jump to PC + imm26.
 #[inline(always)]
 fn b_imm26(buf: &mut Vec<'_, u8>, imm26: i32) {
+    let inst = UnconditionalBranchImmediate::new(UnconditionalBranchImmediateParams {
+        op: false,
+        imm26: encode_imm26(imm26),
+    });
+
+    buf.extend(inst.bytes());
+}
+
+/// `BL imm26` -> Jump to PC + imm26, storing the return address in LR.
+#[inline(always)]
+fn bl_imm26(buf: &mut Vec<'_, u8>, imm26: i32) {
+    let inst = UnconditionalBranchImmediate::new(UnconditionalBranchImmediateParams {
+        op: true,
+        imm26: encode_imm26(imm26),
+    });
+
+    buf.extend(inst.bytes());
+}
+
+/// Encode a byte offset for B and BL.
+#[inline(always)]
+fn encode_imm26(imm26: i32) -> u32 {
     // Since instructions are 4 bytes, the branch instructions assume the last 2 bits are 0
     debug_assert!(imm26 & 0b11 == 0, "branch location must be 4-byte aligned");
     let shifted = imm26 >> 2;
@@ -2391,12 +3256,7 @@ fn b_imm26(buf: &mut Vec<'_, u8>, imm26: i32) {
         debug_assert!(left_removed | 0b1111_1110_0000_0000_0000_0000_0000_0000 == unsigned);
     }
 
-    let inst = UnconditionalBranchImmediate::new(UnconditionalBranchImmediateParams {
-        op: false,
-        imm26: left_removed,
-    });
-
-    buf.extend(inst.bytes());
+    left_removed
 }
 
 /// `CMP Xn, imm12` -> Compare Xn and imm12, setting condition flags.
@@ -2487,6 +3347,7 @@ fn eor_reg64_reg64_reg64(
     src2: AArch64GeneralReg,
 ) {
     let inst = LogicalShiftedRegister::new(LogicalShiftedRegisterParams {
+        sf: true,
         op: LogicalOp::EOR,
         shift: ShiftType::LSL,
         imm6: 0,
@@ -2498,17 +3359,39 @@ fn eor_reg64_reg64_reg64(
     buf.extend(inst.bytes());
 }
 
-/// `LDR Xt, [Xn, #offset]` -> Load Xn + Offset Xt. ZRSP is SP.
-/// Note: imm12 is the offest divided by 8.
+/// `LDR(B/H) Wt/Xt, [Xn, #offset]` -> Load Xn + Offset into Wt/Xt, zero extending. ZRSP is SP.
+/// Note: imm12 is the offset divided by the width in bytes.
 #[inline(always)]
-fn ldr_reg64_reg64_imm12(
+fn ldr_reg_reg64_imm12(
     buf: &mut Vec<'_, u8>,
+    width: RegisterWidth,
     dst: AArch64GeneralReg,
     base: AArch64GeneralReg,
     imm12: u16,
 ) {
     let inst = LoadStoreRegisterImmediate::new_load(LoadStoreRegisterImmediateParams {
-        size: 0b11,
+        size: encode_register_width(width),
+        imm12,
+        rn: base,
+        rt: dst,
+    });
+
+    buf.extend(inst.bytes());
+}
+
+/// `LDRS(B/H/W) Xt, [Xn, #offset]` -> Load Xn + Offset into Xt, sign extending. ZRSP is SP.
+/// Note: imm12 is the offset divided by the width in bytes.
+#[inline(always)]
+fn ldrs_reg64_reg64_imm12(
+    buf: &mut Vec<'_, u8>,
+    width: RegisterWidth,
+    dst: AArch64GeneralReg,
+    base: AArch64GeneralReg,
+    imm12: u16,
+) {
+    debug_assert!(!matches!(width, RegisterWidth::W64));
+    let inst = LoadStoreRegisterImmediate::new_load_signed(LoadStoreRegisterImmediateParams {
+        size: encode_register_width(width),
         imm12,
         rn: base,
         rt: dst,
@@ -2517,6 +3400,50 @@ fn ldr_reg64_reg64_imm12(
     buf.extend(inst.bytes());
 }
 
+/// `LDUR(B/H) Wt/Xt, [Xn, #offset]` -> Load Xn + Offset into Wt/Xt, zero extending. ZRSP is SP.
+/// Note: imm9 is the offset in bytes and may be negative.
+#[inline(always)]
+fn ldur_reg_reg64_imm9(
+    buf: &mut Vec<'_, u8>,
+    width: RegisterWidth,
+    dst: AArch64GeneralReg,
+    base: AArch64GeneralReg,
+    imm9: i16,
+) {
+    let inst =
+        LoadStoreRegisterUnscaledImmediate::new_load(LoadStoreRegisterUnscaledImmediateParams {
+            size: encode_register_width(width),
+            imm9,
+            rn: base,
+            rt: dst,
+        });
+
+    buf.extend(inst.bytes());
+}
+
+/// `LDURS(B/H/W) Xt, [Xn, #offset]` -> Load Xn + Offset into Xt, sign extending. ZRSP is SP.
+/// Note: imm9 is the offset in bytes and may be negative.
+#[inline(always)]
+fn ldurs_reg64_reg64_imm9(
+    buf: &mut Vec<'_, u8>,
+    width: RegisterWidth,
+    dst: AArch64GeneralReg,
+    base: AArch64GeneralReg,
+    imm9: i16,
+) {
+    debug_assert!(!matches!(width, RegisterWidth::W64));
+    let inst = LoadStoreRegisterUnscaledImmediate::new_load_signed(
+        LoadStoreRegisterUnscaledImmediateParams {
+            size: encode_register_width(width),
+            imm9,
+            rn: base,
+            rt: dst,
+        },
+    );
+
+    buf.extend(inst.bytes());
+}
+
 /// `LSL Xd, Xn, Xm` -> Logical shift Xn left by Xm and place the result into Xd.
 #[inline(always)]
 fn lsl_reg64_reg64_reg64(
@@ -2565,6 +3492,7 @@ fn madd_reg64_reg64_reg64_reg64(
     let inst = DataProcessingThreeSource::new(DataProcessingThreeSourceParams {
         op31: 0b000000,
         rm: src2,
+        o0: false,
         ra: src3,
         rn: src1,
         rd: dst,
@@ -2573,6 +3501,23 @@ fn madd_reg64_reg64_reg64_reg64(
     buf.extend(inst.bytes());
 }
 
+/// `MOV Wd, Wm` -> Move Wm to Wd, zeroing the upper 32 bits of Xd.
+#[inline(always)]
+fn mov_reg32_reg32(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, src: AArch64GeneralReg) {
+    // MOV is equivalent to `ORR Wd, WZR, Wm` in AARCH64.
+    let inst = LogicalShiftedRegister::new(LogicalShiftedRegisterParams {
+        sf: false,
+        op: LogicalOp::ORR,
+        shift: ShiftType::LSL,
+        imm6: 0,
+        rm: src,
+        rn: AArch64GeneralReg::ZRSP,
+        rd: dst,
+    });
+
+    buf.extend(inst.bytes());
+}
+
 /// `MOV Xd, Xm` -> Move Xm to Xd.
 #[inline(always)]
 fn mov_reg64_reg64(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, src: AArch64GeneralReg) {
@@ -2608,6 +3553,27 @@ fn movz_reg64_imm16(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, imm16: u16, h
     buf.extend(inst.bytes());
 }
 
+/// `MSUB Xd, Xn, Xm, Xa` -> Multiply Xn and Xm, subtract the result from Xa, and place the result into Xd.
+#[inline(always)]
+fn msub_reg64_reg64_reg64_reg64(
+    buf: &mut Vec<'_, u8>,
+    dst: AArch64GeneralReg,
+    src1: AArch64GeneralReg,
+    src2: AArch64GeneralReg,
+    src3: AArch64GeneralReg,
+) {
+    let inst = DataProcessingThreeSource::new(DataProcessingThreeSourceParams {
+        op31: 0b000000,
+        rm: src2,
+        o0: true,
+        ra: src3,
+        rn: src1,
+        rd: dst,
+    });
+
+    buf.extend(inst.bytes());
+}
+
 /// `MUL Xd, Xn, Xm` -> Multiply Xn and Xm and place the result into Xd.
 #[inline(always)]
 fn mul_reg64_reg64_reg64(
@@ -2634,6 +3600,7 @@ fn orr_reg64_reg64_reg64(
     src2: AArch64GeneralReg,
 ) {
     let inst = LogicalShiftedRegister::new(LogicalShiftedRegisterParams {
+        sf: true,
         op: LogicalOp::ORR,
         shift: ShiftType::LSL,
         imm6: 0,
@@ -2664,17 +3631,18 @@ fn sdiv_reg64_reg64_reg64(
     buf.extend(inst.bytes());
 }
 
-/// `STR Xt, [Xn, #offset]` -> Store Xt to Xn + Offset. ZRSP is SP.
-/// Note: imm12 is the offest divided by 8.
+/// `STR(B/H) Wt/Xt, [Xn, #offset]` -> Store the low bits of Wt/Xt to Xn + Offset. ZRSP is SP.
+/// Note: imm12 is the offset divided by the width in bytes.
 #[inline(always)]
-fn str_reg64_reg64_imm12(
+fn str_reg_reg64_imm12(
     buf: &mut Vec<'_, u8>,
+    width: RegisterWidth,
     src: AArch64GeneralReg,
     base: AArch64GeneralReg,
     imm12: u16,
 ) {
     let inst = LoadStoreRegisterImmediate::new_store(LoadStoreRegisterImmediateParams {
-        size: 0b11,
+        size: encode_register_width(width),
         imm12,
         rn: base,
         rt: src,
@@ -2683,6 +3651,47 @@ fn str_reg64_reg64_imm12(
     buf.extend(inst.bytes());
 }
 
+/// `STUR(B/H) Wt/Xt, [Xn, #offset]` -> Store the low bits of Wt/Xt to Xn + Offset. ZRSP is SP.
+/// Note: imm9 is the offset in bytes and may be negative.
+#[inline(always)]
+fn stur_reg_reg64_imm9(
+    buf: &mut Vec<'_, u8>,
+    width: RegisterWidth,
+    src: AArch64GeneralReg,
+    base: AArch64GeneralReg,
+    imm9: i16,
+) {
+    let inst =
+        LoadStoreRegisterUnscaledImmediate::new_store(LoadStoreRegisterUnscaledImmediateParams {
+            size: encode_register_width(width),
+            imm9,
+            rn: base,
+            rt: src,
+        });
+
+    buf.extend(inst.bytes());
+}
+
+/// `SUB Xd, Xn, imm12, LSL #12` -> Subtract Xn and (imm12 << 12) and place the result into Xd.
+#[inline(always)]
+fn sub_reg64_reg64_imm12_lsl12(
+    buf: &mut Vec<'_, u8>,
+    dst: AArch64GeneralReg,
+    src: AArch64GeneralReg,
+    imm12: u16,
+) {
+    let inst = ArithmeticImmediate::new(ArithmeticImmediateParams {
+        op: true,
+        s: false,
+        rd: dst,
+        rn: src,
+        imm12,
+        sh: true,
+    });
+
+    buf.extend(inst.bytes());
+}
+
 /// `SUB Xd, Xn, imm12` -> Subtract Xn and imm12 and place the result into Xd.
 #[inline(always)]
 fn sub_reg64_reg64_imm12(
@@ -2765,6 +3774,33 @@ fn subs_reg64_reg64_reg64(
     buf.extend(inst.bytes());
 }
 
+/// `SXT(B/H/W) Xd, Wn` -> Sign extend the low 8/16/32 bits of Wn and place the result into Xd.
+#[inline(always)]
+fn sxt_reg64_reg(
+    buf: &mut Vec<'_, u8>,
+    input_width: RegisterWidth,
+    dst: AArch64GeneralReg,
+    src: AArch64GeneralReg,
+) {
+    // SXT is equivalent to `SBFM Xd, Xn, #0, #(bits - 1)` in AARCH64.
+    let imms = match input_width {
+        RegisterWidth::W8 => 7,
+        RegisterWidth::W16 => 15,
+        RegisterWidth::W32 => 31,
+        RegisterWidth::W64 => internal_error!("Cannot sign extend a 64 bit value"),
+    };
+    let inst = Bitfield::new(BitfieldParams {
+        sf: true,
+        opc: 0b00,
+        immr: 0,
+        imms,
+        rn: src,
+        rd: dst,
+    });
+
+    buf.extend(inst.bytes());
+}
+
 /// `RET Xn` -> Return to the address stored in Xn.
 #[inline(always)]
 fn ret_reg64(buf: &mut Vec<'_, u8>, xn: AArch64GeneralReg) {
@@ -2793,6 +3829,35 @@ fn udiv_reg64_reg64_reg64(
     buf.extend(inst.bytes());
 }
 
+/// `UXT(B/H) Wd, Wn` -> Zero extend the low 8/16 bits of Wn and place the result into Wd.
+/// Writing Wd zeros the upper 32 bits of Xd.
+#[inline(always)]
+fn uxt_reg32_reg32(
+    buf: &mut Vec<'_, u8>,
+    input_width: RegisterWidth,
+    dst: AArch64GeneralReg,
+    src: AArch64GeneralReg,
+) {
+    // UXT is equivalent to `UBFM Wd, Wn, #0, #(bits - 1)` in AARCH64.
+    let imms = match input_width {
+        RegisterWidth::W8 => 7,
+        RegisterWidth::W16 => 15,
+        RegisterWidth::W32 | RegisterWidth::W64 => {
+            internal_error!("UXT only zero extends 8 and 16 bit values")
+        }
+    };
+    let inst = Bitfield::new(BitfieldParams {
+        sf: false,
+        opc: 0b10,
+        immr: 0,
+        imms,
+        rn: src,
+        rd: dst,
+    });
+
+    buf.extend(inst.bytes());
+}
+
 // Floating point (and advanced SIMD) instructions
 // ARM manual section C7
 
@@ -3029,6 +4094,50 @@ fn fmov_freg_imm8(buf: &mut Vec<'_, u8>, ftype: FloatWidth, dst: AArch64FloatReg
     buf.extend(inst.bytes());
 }
 
+/// `FMOV Sd/Dd, Wn/Xn` -> Move the bits of Wn/Xn to Sd/Dd.
+#[inline(always)]
+fn fmov_freg_reg(
+    buf: &mut Vec<'_, u8>,
+    ftype: FloatWidth,
+    dst: AArch64FloatReg,
+    src: AArch64GeneralReg,
+) {
+    let inst = ConversionBetweenFloatingPointAndInteger::new(
+        ConversionBetweenFloatingPointAndIntegerParams {
+            sf: matches!(ftype, FloatWidth::F64),
+            opcode: 0b111,
+            rmode: 0b00,
+            ptype: ftype,
+            rd: dst,
+            rn: src,
+        },
+    );
+
+    buf.extend(inst.bytes());
+}
+
+/// `FMOV Wd/Xd, Sn/Dn` -> Move the bits of Sn/Dn to Wd/Xd.
+#[inline(always)]
+fn fmov_reg_freg(
+    buf: &mut Vec<'_, u8>,
+    ftype: FloatWidth,
+    dst: AArch64GeneralReg,
+    src: AArch64FloatReg,
+) {
+    let inst = ConversionBetweenFloatingPointAndInteger::new(
+        ConversionBetweenFloatingPointAndIntegerParams {
+            sf: matches!(ftype, FloatWidth::F64),
+            opcode: 0b110,
+            rmode: 0b00,
+            ptype: ftype,
+            rd: dst,
+            rn: src,
+        },
+    );
+
+    buf.extend(inst.bytes());
+}
+
 /// `FMUL Sd/Dd, Sn/Dn, Sm/Dm` -> Multiply Sn/Dn by Sm/Dm and store the result in Sd/Dd.
 #[inline(always)]
 fn fmul_freg_freg_freg(
@@ -3069,6 +4178,48 @@ fn fsqrt_freg_freg(
     buf.extend(inst.bytes());
 }
 
+/// `LDR St/Dt, [Xn, #offset]` -> Load Xn + Offset into St/Dt. ZRSP is SP.
+/// Note: imm12 is the offset divided by the width in bytes.
+#[inline(always)]
+fn ldr_freg_reg64_imm12(
+    buf: &mut Vec<'_, u8>,
+    ftype: FloatWidth,
+    dst: AArch64FloatReg,
+    base: AArch64GeneralReg,
+    imm12: u16,
+) {
+    let inst = LoadStoreRegisterImmediate::new_float_load(LoadStoreRegisterImmediateParams {
+        size: encode_float_register_width(ftype),
+        imm12,
+        rn: base,
+        rt: dst,
+    });
+
+    buf.extend(inst.bytes());
+}
+
+/// `LDUR St/Dt, [Xn, #offset]` -> Load Xn + Offset into St/Dt. ZRSP is SP.
+/// Note: imm9 is the offset in bytes and may be negative.
+#[inline(always)]
+fn ldur_freg_reg64_imm9(
+    buf: &mut Vec<'_, u8>,
+    ftype: FloatWidth,
+    dst: AArch64FloatReg,
+    base: AArch64GeneralReg,
+    imm9: i16,
+) {
+    let inst = LoadStoreRegisterUnscaledImmediate::new_float_load(
+        LoadStoreRegisterUnscaledImmediateParams {
+            size: encode_float_register_width(ftype),
+            imm9,
+            rn: base,
+            rt: dst,
+        },
+    );
+
+    buf.extend(inst.bytes());
+}
+
 /// Currently, we're only using MOVI to set a float register to 0.0.
 /// `MOVI Dd, #0.0` -> Move 0.0 to Dd
 #[inline(always)]
@@ -3088,6 +4239,7 @@ fn scvtf_freg_reg64(
 ) {
     let inst = ConversionBetweenFloatingPointAndInteger::new(
         ConversionBetweenFloatingPointAndIntegerParams {
+            sf: true,
             opcode: 0b010,
             rmode: 0b00,
             ptype: ftype,
@@ -3099,6 +4251,48 @@ fn scvtf_freg_reg64(
     buf.extend(inst.bytes());
 }
 
+/// `STR St/Dt, [Xn, #offset]` -> Store St/Dt to Xn + Offset. ZRSP is SP.
+/// Note: imm12 is the offset divided by the width in bytes.
+#[inline(always)]
+fn str_freg_reg64_imm12(
+    buf: &mut Vec<'_, u8>,
+    ftype: FloatWidth,
+    src: AArch64FloatReg,
+    base: AArch64GeneralReg,
+    imm12: u16,
+) {
+    let inst = LoadStoreRegisterImmediate::new_float_store(LoadStoreRegisterImmediateParams {
+        size: encode_float_register_width(ftype),
+        imm12,
+        rn: base,
+        rt: src,
+    });
+
+    buf.extend(inst.bytes());
+}
+
+/// `STUR St/Dt, [Xn, #offset]` -> Store St/Dt to Xn + Offset. ZRSP is SP.
+/// Note: imm9 is the offset in bytes and may be negative.
+#[inline(always)]
+fn stur_freg_reg64_imm9(
+    buf: &mut Vec<'_, u8>,
+    ftype: FloatWidth,
+    src: AArch64FloatReg,
+    base: AArch64GeneralReg,
+    imm9: i16,
+) {
+    let inst = LoadStoreRegisterUnscaledImmediate::new_float_store(
+        LoadStoreRegisterUnscaledImmediateParams {
+            size: encode_float_register_width(ftype),
+            imm9,
+            rn: base,
+            rt: src,
+        },
+    );
+
+    buf.extend(inst.bytes());
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -3127,6 +4321,23 @@ mod tests {
                 _ => format!("{}", self),
             }
         }
+
+        fn capstone_string_32(&self, zrsp_kind: ZRSPKind) -> String {
+            match self {
+                AArch64GeneralReg::ZRSP => match zrsp_kind {
+                    UsesZR => "wzr".to_owned(),
+                    UsesSP => "wsp".to_owned(),
+                },
+                _ => format!("w{}", self.id()),
+            }
+        }
+
+        fn capstone_string_width(&self, width: RegisterWidth, zrsp_kind: ZRSPKind) -> String {
+            match width {
+                RegisterWidth::W64 => self.capstone_string(zrsp_kind),
+                _ => self.capstone_string_32(zrsp_kind),
+            }
+        }
     }
 
     impl AArch64FloatReg {
@@ -3214,6 +4425,36 @@ mod tests {
 
     const ALL_FLOAT_TYPES: &[FloatWidth] = &[FloatWidth::F32, FloatWidth::F64];
 
+    const ALL_REGISTER_WIDTHS: &[RegisterWidth] = &[
+        RegisterWidth::W8,
+        RegisterWidth::W16,
+        RegisterWidth::W32,
+        RegisterWidth::W64,
+    ];
+
+    const EXTENDABLE_REGISTER_WIDTHS: &[RegisterWidth] =
+        &[RegisterWidth::W8, RegisterWidth::W16, RegisterWidth::W32];
+
+    fn load_store_suffix(width: RegisterWidth) -> &'static str {
+        match width {
+            RegisterWidth::W8 => "b",
+            RegisterWidth::W16 => "h",
+            RegisterWidth::W32 => "w",
+            RegisterWidth::W64 => "",
+        }
+    }
+
+    /// Capstone prints immediates between -9 and 9 in decimal, and the rest in hex.
+    fn signed_hex(imm: i16) -> String {
+        if (-9..=9).contains(&imm) {
+            format!("{}", imm)
+        } else if imm < 0 {
+            format!("-0x{:x}", -imm)
+        } else {
+            format!("0x{:x}", imm)
+        }
+    }
+
     const ALL_CONDITIONS: &[ConditionCode] = &[
         ConditionCode::EQ,
         ConditionCode::NE,
@@ -3282,6 +4523,81 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_add_reg64_reg64_imm12_lsl12() {
+        disassembler_test!(
+            add_reg64_reg64_imm12_lsl12,
+            |reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm| format!(
+                "add {}, {}, #0x{:x}, lsl #12",
+                reg1.capstone_string(UsesSP),
+                reg2.capstone_string(UsesSP),
+                imm
+            ),
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS,
+            [0x123]
+        );
+    }
+
+    #[test]
+    fn test_adds_reg64_reg64_reg64() {
+        disassembler_test!(
+            adds_reg64_reg64_reg64,
+            |reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, reg3: AArch64GeneralReg| {
+                if reg1 == AArch64GeneralReg::ZRSP {
+                    // When the first register is ZR, it gets disassembled as cmn,
+                    // which is an alias for adds.
+                    format!(
+                        "cmn {}, {}",
+                        reg2.capstone_string(UsesZR),
+                        reg3.capstone_string(UsesZR)
+                    )
+                } else {
+                    format!(
+                        "adds {}, {}, {}",
+                        reg1.capstone_string(UsesZR),
+                        reg2.capstone_string(UsesZR),
+                        reg3.capstone_string(UsesZR)
+                    )
+                }
+            },
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS
+        );
+    }
+
+    #[test]
+    fn test_adrp_reg64() {
+        disassembler_test!(
+            adrp_reg64,
+            |reg1: AArch64GeneralReg| format!("adrp {}, #0", reg1.capstone_string(UsesZR)),
+            ALL_GENERAL_REGS
+        );
+    }
+
+    #[test]
+    fn test_add_reg64_reg64_imm32_over_24_bits() {
+        disassembler_test!(
+            |buf| AArch64Assembler::add_reg64_reg64_imm32(
+                buf,
+                AArch64GeneralReg::X0,
+                AArch64GeneralReg::ZRSP,
+                0x100_0000
+            ),
+            || "add x0, sp, #0xfff, lsl #12\nadd x0, x0, #0xfff\nadd x0, x0, #1".to_string()
+        );
+    }
+
+    #[test]
+    fn test_jne_reg64_imm64_imm32() {
+        // The B.EQ skips the B, which jumps 0x100 bytes past the end of the sequence
+        disassembler_test!(
+            |buf| AArch64Assembler::jne_reg64_imm64_imm32(buf, AArch64GeneralReg::X0, 1, 0x100),
+            || "cmp x0, #1\nb.eq #0xc\nb #0x10c".to_string()
+        );
+    }
+
     #[test]
     fn test_and_reg64_reg64_reg64() {
         disassembler_test!(
@@ -3333,6 +4649,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_bl_imm26() {
+        disassembler_test!(
+            bl_imm26,
+            |imm| format!("bl #0x{:x}", imm as i64),
+            [0x120, -0x120, (1 << 27) - 4, -(1 << 27)]
+        );
+    }
+
     #[test]
     fn test_cmp_reg64_imm12() {
         disassembler_test!(
@@ -3499,21 +4824,92 @@ mod tests {
     }
 
     #[test]
-    fn test_ldr_reg64_reg64_imm12() {
+    fn test_ldr_reg_reg64_imm12() {
         disassembler_test!(
-            ldr_reg64_reg64_imm12,
-            |reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm| format!(
-                "ldr {}, [{}, #0x{:x}]",
-                reg1.capstone_string(UsesZR),
-                reg2.capstone_string(UsesSP),
-                imm << 3
-            ),
+            ldr_reg_reg64_imm12,
+            |width: RegisterWidth, reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm: u16| {
+                format!(
+                    "ldr{} {}, [{}, #0x{:x}]",
+                    // Loads of 32 bits don't need a suffix, since they use a W register.
+                    match width {
+                        RegisterWidth::W32 => "",
+                        _ => load_store_suffix(width),
+                    },
+                    reg1.capstone_string_width(width, UsesZR),
+                    reg2.capstone_string(UsesSP),
+                    imm << encode_register_width(width)
+                )
+            },
+            ALL_REGISTER_WIDTHS,
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS,
+            [0x123]
+        );
+    }
+
+    #[test]
+    fn test_ldrs_reg64_reg64_imm12() {
+        disassembler_test!(
+            ldrs_reg64_reg64_imm12,
+            |width: RegisterWidth, reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm: u16| {
+                format!(
+                    "ldrs{} {}, [{}, #0x{:x}]",
+                    load_store_suffix(width),
+                    reg1.capstone_string(UsesZR),
+                    reg2.capstone_string(UsesSP),
+                    imm << encode_register_width(width)
+                )
+            },
+            EXTENDABLE_REGISTER_WIDTHS,
             ALL_GENERAL_REGS,
             ALL_GENERAL_REGS,
             [0x123]
         );
     }
 
+    #[test]
+    fn test_ldur_reg_reg64_imm9() {
+        disassembler_test!(
+            ldur_reg_reg64_imm9,
+            |width: RegisterWidth, reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm: i16| {
+                format!(
+                    "ldur{} {}, [{}, #{}]",
+                    match width {
+                        RegisterWidth::W32 => "",
+                        _ => load_store_suffix(width),
+                    },
+                    reg1.capstone_string_width(width, UsesZR),
+                    reg2.capstone_string(UsesSP),
+                    signed_hex(imm)
+                )
+            },
+            ALL_REGISTER_WIDTHS,
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS,
+            [0xFF, -0x100, -0x8]
+        );
+    }
+
+    #[test]
+    fn test_ldurs_reg64_reg64_imm9() {
+        disassembler_test!(
+            ldurs_reg64_reg64_imm9,
+            |width: RegisterWidth, reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm: i16| {
+                format!(
+                    "ldurs{} {}, [{}, #{}]",
+                    load_store_suffix(width),
+                    reg1.capstone_string(UsesZR),
+                    reg2.capstone_string(UsesSP),
+                    signed_hex(imm)
+                )
+            },
+            EXTENDABLE_REGISTER_WIDTHS,
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS,
+            [0xFF, -0x100, -0x8]
+        );
+    }
+
     #[test]
     fn test_lsl_reg64_reg64_reg64() {
         disassembler_test!(
@@ -3578,6 +4974,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_mov_reg32_reg32() {
+        disassembler_test!(
+            mov_reg32_reg32,
+            |reg1: AArch64GeneralReg, reg2: AArch64GeneralReg| format!(
+                "mov {}, {}",
+                reg1.capstone_string_32(UsesZR),
+                reg2.capstone_string_32(UsesZR)
+            ),
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS
+        );
+    }
+
     #[test]
     fn test_mov_reg64_reg64() {
         disassembler_test!(
@@ -3628,6 +5038,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_msub_reg64_reg64_reg64_reg64() {
+        disassembler_test!(
+            msub_reg64_reg64_reg64_reg64,
+            |reg1: AArch64GeneralReg,
+             reg2: AArch64GeneralReg,
+             reg3: AArch64GeneralReg,
+             reg4: AArch64GeneralReg| {
+                if reg4 == AArch64GeneralReg::ZRSP {
+                    format!(
+                        "mneg {}, {}, {}",
+                        reg1.capstone_string(UsesZR),
+                        reg2.capstone_string(UsesZR),
+                        reg3.capstone_string(UsesZR)
+                    )
+                } else {
+                    format!(
+                        "msub {}, {}, {}, {}",
+                        reg1.capstone_string(UsesZR),
+                        reg2.capstone_string(UsesZR),
+                        reg3.capstone_string(UsesZR),
+                        reg4.capstone_string(UsesZR)
+                    )
+                }
+            },
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS
+        );
+    }
+
     #[test]
     fn test_mul_reg64_reg64_reg64() {
         disassembler_test!(
@@ -3701,21 +5143,52 @@ mod tests {
     }
 
     #[test]
-    fn test_str_reg64_reg64_imm12() {
+    fn test_str_reg_reg64_imm12() {
         disassembler_test!(
-            str_reg64_reg64_imm12,
-            |reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm| format!(
-                "str {}, [{}, #0x{:x}]",
-                reg1.capstone_string(UsesZR),
-                reg2.capstone_string(UsesSP),
-                imm << 3
-            ),
+            str_reg_reg64_imm12,
+            |width: RegisterWidth, reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm: u16| {
+                format!(
+                    "str{} {}, [{}, #0x{:x}]",
+                    // Stores of 32 bits don't need a suffix, since they use a W register.
+                    match width {
+                        RegisterWidth::W32 => "",
+                        _ => load_store_suffix(width),
+                    },
+                    reg1.capstone_string_width(width, UsesZR),
+                    reg2.capstone_string(UsesSP),
+                    imm << encode_register_width(width)
+                )
+            },
+            ALL_REGISTER_WIDTHS,
             ALL_GENERAL_REGS,
             ALL_GENERAL_REGS,
             [0x123]
         );
     }
 
+    #[test]
+    fn test_stur_reg_reg64_imm9() {
+        disassembler_test!(
+            stur_reg_reg64_imm9,
+            |width: RegisterWidth, reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm: i16| {
+                format!(
+                    "stur{} {}, [{}, #{}]",
+                    match width {
+                        RegisterWidth::W32 => "",
+                        _ => load_store_suffix(width),
+                    },
+                    reg1.capstone_string_width(width, UsesZR),
+                    reg2.capstone_string(UsesSP),
+                    signed_hex(imm)
+                )
+            },
+            ALL_REGISTER_WIDTHS,
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS,
+            [0xFF, -0x100, -0x8]
+        );
+    }
+
     #[test]
     fn test_sub_reg64_reg64_imm12() {
         disassembler_test!(
@@ -3732,6 +5205,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_sub_reg64_reg64_imm12_lsl12() {
+        disassembler_test!(
+            sub_reg64_reg64_imm12_lsl12,
+            |reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm| format!(
+                "sub {}, {}, #0x{:x}, lsl #12",
+                reg1.capstone_string(UsesSP),
+                reg2.capstone_string(UsesSP),
+                imm
+            ),
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS,
+            [0x123]
+        );
+    }
+
     #[test]
     fn test_sub_reg64_reg64_reg64() {
         disassembler_test!(
@@ -3821,6 +5310,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_sxt_reg64_reg() {
+        disassembler_test!(
+            sxt_reg64_reg,
+            |width: RegisterWidth, reg1: AArch64GeneralReg, reg2: AArch64GeneralReg| format!(
+                "sxt{} {}, {}",
+                load_store_suffix(width),
+                reg1.capstone_string(UsesZR),
+                reg2.capstone_string_32(UsesZR)
+            ),
+            EXTENDABLE_REGISTER_WIDTHS,
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS
+        );
+    }
+
     #[test]
     fn test_ret_reg64() {
         disassembler_test!(
@@ -3852,6 +5357,22 @@ mod tests {
 
     // Float instructions
 
+    #[test]
+    fn test_uxt_reg32_reg32() {
+        disassembler_test!(
+            uxt_reg32_reg32,
+            |width: RegisterWidth, reg1: AArch64GeneralReg, reg2: AArch64GeneralReg| format!(
+                "uxt{} {}, {}",
+                load_store_suffix(width),
+                reg1.capstone_string_32(UsesZR),
+                reg2.capstone_string_32(UsesZR)
+            ),
+            [RegisterWidth::W8, RegisterWidth::W16],
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS
+        );
+    }
+
     #[test]
     fn test_fabs_freg_freg() {
         disassembler_test!(
@@ -4115,6 +5636,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_fmov_freg_reg() {
+        disassembler_test!(
+            fmov_freg_reg,
+            |ftype: FloatWidth, reg1: AArch64FloatReg, reg2: AArch64GeneralReg| format!(
+                "fmov {}, {}",
+                reg1.capstone_string(ftype),
+                match ftype {
+                    FloatWidth::F32 => reg2.capstone_string_32(UsesZR),
+                    FloatWidth::F64 => reg2.capstone_string(UsesZR),
+                }
+            ),
+            ALL_FLOAT_TYPES,
+            ALL_FLOAT_REGS,
+            ALL_GENERAL_REGS
+        );
+    }
+
+    #[test]
+    fn test_fmov_reg_freg() {
+        disassembler_test!(
+            fmov_reg_freg,
+            |ftype: FloatWidth, reg1: AArch64GeneralReg, reg2: AArch64FloatReg| format!(
+                "fmov {}, {}",
+                match ftype {
+                    FloatWidth::F32 => reg1.capstone_string_32(UsesZR),
+                    FloatWidth::F64 => reg1.capstone_string(UsesZR),
+                },
+                reg2.capstone_string(ftype)
+            ),
+            ALL_FLOAT_TYPES,
+            ALL_GENERAL_REGS,
+            ALL_FLOAT_REGS
+        );
+    }
+
     #[test]
     fn test_fmul_freg_freg_freg() {
         disassembler_test!(
@@ -4150,6 +5707,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_ldr_freg_reg64_imm12() {
+        disassembler_test!(
+            ldr_freg_reg64_imm12,
+            |ftype: FloatWidth, reg1: AArch64FloatReg, reg2: AArch64GeneralReg, imm: u16| format!(
+                "ldr {}, [{}, #0x{:x}]",
+                reg1.capstone_string(ftype),
+                reg2.capstone_string(UsesSP),
+                imm << encode_float_register_width(ftype)
+            ),
+            ALL_FLOAT_TYPES,
+            ALL_FLOAT_REGS,
+            ALL_GENERAL_REGS,
+            [0x123]
+        );
+    }
+
+    #[test]
+    fn test_ldur_freg_reg64_imm9() {
+        disassembler_test!(
+            ldur_freg_reg64_imm9,
+            |ftype: FloatWidth, reg1: AArch64FloatReg, reg2: AArch64GeneralReg, imm: i16| format!(
+                "ldur {}, [{}, #{}]",
+                reg1.capstone_string(ftype),
+                reg2.capstone_string(UsesSP),
+                signed_hex(imm)
+            ),
+            ALL_FLOAT_TYPES,
+            ALL_FLOAT_REGS,
+            ALL_GENERAL_REGS,
+            [0xFF, -0x100, -0x8]
+        );
+    }
+
     #[test]
     fn test_movi_freg_zero() {
         disassembler_test!(
@@ -4176,4 +5767,38 @@ mod tests {
             ALL_GENERAL_REGS
         );
     }
+
+    #[test]
+    fn test_str_freg_reg64_imm12() {
+        disassembler_test!(
+            str_freg_reg64_imm12,
+            |ftype: FloatWidth, reg1: AArch64FloatReg, reg2: AArch64GeneralReg, imm: u16| format!(
+                "str {}, [{}, #0x{:x}]",
+                reg1.capstone_string(ftype),
+                reg2.capstone_string(UsesSP),
+                imm << encode_float_register_width(ftype)
+            ),
+            ALL_FLOAT_TYPES,
+            ALL_FLOAT_REGS,
+            ALL_GENERAL_REGS,
+            [0x123]
+        );
+    }
+
+    #[test]
+    fn test_stur_freg_reg64_imm9() {
+        disassembler_test!(
+            stur_freg_reg64_imm9,
+            |ftype: FloatWidth, reg1: AArch64FloatReg, reg2: AArch64GeneralReg, imm: i16| format!(
+                "stur {}, [{}, #{}]",
+                reg1.capstone_string(ftype),
+                reg2.capstone_string(UsesSP),
+                signed_hex(imm)
+            ),
+            ALL_FLOAT_TYPES,
+            ALL_FLOAT_REGS,
+            ALL_GENERAL_REGS,
+            [0xFF, -0x100, -0x8]
+        );
+    }
 }
@@ -1,20 +1,20 @@
 use crate::{
-    single_register_floats, single_register_int_builtins, single_register_integers, Backend, Env,
-    Relocation,
+    pointer_layouts, single_register_floats, single_register_int_builtins,
+    single_register_integers, Backend, Env, Relocation, UsePositions,
 };
-use bumpalo::collections::{CollectIn, Vec};
-use roc_builtins::bitcode::{self, FloatWidth, IntWidth};
+use bumpalo::collections::Vec;
+use roc_builtins::bitcode::{self, FloatWidth, IntWidth, IntrinsicName};
 use roc_collections::all::MutMap;
 use roc_error_macros::internal_error;
 use roc_module::symbol::{Interns, ModuleId, Symbol};
-use roc_mono::code_gen_help::{CallerProc, CodeGenHelp, HelperOp};
+use roc_mono::code_gen_help::{CallerProc, CodeGenHelp, HelperOp, REFCOUNT_MAX};
 use roc_mono::ir::{
     BranchInfo, HigherOrderLowLevel, JoinPointId, ListLiteralElement, Literal, Param, ProcLayout,
     SelfRecursive, Stmt,
 };
 use roc_mono::layout::{
-    Builtin, InLayout, Layout, LayoutIds, LayoutInterner, LayoutRepr, STLayoutInterner,
-    TagIdIntType, UnionLayout,
+    Builtin, Discriminant, InLayout, Layout, LayoutIds, LayoutInterner, LayoutRepr,
+    STLayoutInterner, TagIdIntType, UnionLayout,
 };
 use roc_mono::low_level::HigherOrder;
 use roc_target::TargetInfo;
@@ -238,6 +238,14 @@ pub trait Assembler<GeneralReg: RegTrait, FloatReg: RegTrait>: Sized + Copy {
         dst: GeneralReg,
     );
 
+    /// Loads the address of `data`, which is placed in the read-only data of the object file.
+    fn data_pointer(
+        buf: &mut Vec<'_, u8>,
+        relocs: &mut Vec<'_, Relocation>,
+        data: std::vec::Vec<u8>,
+        dst: GeneralReg,
+    );
+
     /// Jumps by an offset of offset bytes unconditionally.
     /// It should always generate the same number of bytes to enable replacement if offset changes.
     /// It returns the base offset to calculate the jump from (generally the instruction after the jump).
@@ -586,6 +594,7 @@ pub struct Backend64Bit<
     phantom_asm: PhantomData<ASM>,
     phantom_cc: PhantomData<CC>,
     env: &'r Env<'a>,
+    target_info: TargetInfo,
     layout_interner: &'r mut STLayoutInterner<'a>,
     interns: &'r mut Interns,
     helper_proc_gen: CodeGenHelp<'a>,
@@ -624,6 +633,7 @@ pub fn new_backend_64bit<
         phantom_asm: PhantomData,
         phantom_cc: PhantomData,
         env,
+        target_info,
         interns,
         layout_interner,
         helper_proc_gen: CodeGenHelp::new(env.arena, target_info, env.module_id),
@@ -737,6 +747,14 @@ impl<
         &mut self.free_map
     }
 
+    fn set_use_positions(&mut self, use_positions: UsePositions<'a>) {
+        self.storage_manager.set_use_positions(use_positions);
+    }
+
+    fn enter_stmt(&mut self, stmt: &Stmt<'a>) {
+        self.storage_manager.enter_stmt(stmt);
+    }
+
     fn finalize(&mut self) -> (Vec<u8>, Vec<Relocation>) {
         let mut out = bumpalo::vec![in self.env.arena];
 
@@ -825,6 +843,10 @@ impl<
                         offset: offset + setup_offset as u64,
                         name,
                     },
+                    Relocation::FunctionPointer { offset, name } => Relocation::FunctionPointer {
+                        offset: offset + setup_offset as u64,
+                        name,
+                    },
                     Relocation::JmpToReturn { .. } => unreachable!(),
                 }),
         );
@@ -908,7 +930,7 @@ impl<
             other => {
                 //
                 match other {
-                    LayoutRepr::Boxed(_) => {
+                    pointer_layouts!() => {
                         let dst_reg = self.storage_manager.claim_general_reg(&mut self.buf, dst);
                         ASM::mov_reg64_reg64(&mut self.buf, dst_reg, CC::GENERAL_RETURN_REGS[0]);
                     }
@@ -969,7 +991,7 @@ impl<
             // Build unconditional jump to the end of this switch.
             // Since we don't know the offset yet, set it to 0 and overwrite later.
             let jmp_location = self.buf.len();
-            let jmp_offset = ASM::jmp_imm32(&mut self.buf, 0x1234_5678);
+            let jmp_offset = ASM::jmp_imm32(&mut self.buf, 0);
             ret_jumps.push((jmp_location, jmp_offset));
 
             // Overwrite the original jne with the correct offset.
@@ -1055,7 +1077,7 @@ impl<
             .setup_jump(self.layout_interner, &mut self.buf, id, args, arg_layouts);
 
         let jmp_location = self.buf.len();
-        let start_offset = ASM::jmp_imm32(&mut self.buf, 0x1234_5678);
+        let start_offset = ASM::jmp_imm32(&mut self.buf, 0);
 
         if let Some(vec) = self.join_map.get_mut(id) {
             vec.push((jmp_location as u64, start_offset as u64))
@@ -1842,14 +1864,24 @@ impl<
             .get_mut(&self.env.module_id)
             .unwrap();
 
-        let caller_proc = CallerProc::new(
-            self.env.arena,
-            self.env.module_id,
-            ident_ids,
-            self.layout_interner,
-            &higher_order.passed_function,
-            higher_order.closure_env_layout,
-        );
+        let caller_proc = match higher_order.op {
+            HigherOrder::ListSortWith { .. } => CallerProc::new_compare(
+                self.env.arena,
+                self.env.module_id,
+                ident_ids,
+                self.layout_interner,
+                &higher_order.passed_function,
+                higher_order.closure_env_layout,
+            ),
+            _ => CallerProc::new(
+                self.env.arena,
+                self.env.module_id,
+                ident_ids,
+                self.layout_interner,
+                &higher_order.passed_function,
+                higher_order.closure_env_layout,
+            ),
+        };
 
         match higher_order.op {
             HigherOrder::ListMap { xs } => {
@@ -1986,7 +2018,260 @@ impl<
             HigherOrder::ListMap2 { .. } => todo!(),
             HigherOrder::ListMap3 { .. } => todo!(),
             HigherOrder::ListMap4 { .. } => todo!(),
-            HigherOrder::ListSortWith { .. } => todo!(),
+            HigherOrder::ListSortWith { xs } => {
+                let element_layout = higher_order.passed_function.argument_layouts[0];
+
+                let input_list_layout = LayoutRepr::Builtin(Builtin::List(element_layout));
+                let input_list_in_layout =
+                    self.layout_interner.insert_no_semantic(input_list_layout);
+
+                let caller = self.debug_symbol("caller");
+                let data = self.debug_symbol("data");
+                let alignment = self.debug_symbol("alignment");
+                let element_width = self.debug_symbol("element_width");
+
+                self.load_layout_alignment(element_layout, alignment);
+                self.load_layout_stack_size(element_layout, element_width);
+
+                let caller_string = self.function_symbol_to_string(
+                    caller_proc.proc_symbol,
+                    std::iter::empty(),
+                    None,
+                    higher_order.passed_function.return_layout,
+                );
+
+                self.caller_procs.push(caller_proc);
+
+                // function pointer to a function that takes a pointer, and increments
+                let inc_n_data = if let Some(closure_env_layout) = higher_order.closure_env_layout {
+                    self.increment_fn_pointer(closure_env_layout)
+                } else {
+                    // null pointer
+                    self.load_literal_i64(&Symbol::DEV_TMP, 0);
+                    Symbol::DEV_TMP
+                };
+
+                self.build_fn_pointer(&caller, caller_string);
+
+                if let Some(_closure_data_layout) = higher_order.closure_env_layout {
+                    let data_symbol = higher_order.passed_function.captured_environment;
+                    self.storage_manager
+                        .ensure_symbol_on_stack(&mut self.buf, &data_symbol);
+                    let (data_offset, _) = self.storage_manager.stack_offset_and_size(&data_symbol);
+
+                    // Load address of the captured environment into register.
+                    let reg = self.storage_manager.claim_general_reg(&mut self.buf, &data);
+                    ASM::add_reg64_reg64_imm32(&mut self.buf, reg, CC::BASE_PTR_REG, data_offset);
+                } else {
+                    // use a null pointer
+                    self.load_literal(&data, &Layout::U64, &Literal::Int(0u128.to_be_bytes()));
+                }
+
+                // we pass a null pointer when the data is not owned. the zig code must not call this!
+                let data_is_owned = higher_order.closure_env_layout.is_some()
+                    && higher_order.passed_function.owns_captured_environment;
+
+                self.load_literal(
+                    &Symbol::DEV_TMP2,
+                    &Layout::BOOL,
+                    &Literal::Bool(data_is_owned),
+                );
+
+                //    input: RocList,
+                //    caller: CompareFn,
+                //    data: Opaque,
+                //    inc_n_data: IncN,
+                //    data_is_owned: bool,
+                //    alignment: u32,
+                //    element_width: usize,
+
+                let arguments = [
+                    xs,
+                    caller,
+                    data,
+                    inc_n_data,
+                    Symbol::DEV_TMP2,
+                    alignment,
+                    element_width,
+                ];
+
+                let ptr = Layout::U64;
+                let usize_ = Layout::U64;
+
+                let layouts = [
+                    input_list_in_layout,
+                    ptr,
+                    ptr,
+                    ptr,
+                    Layout::BOOL,
+                    Layout::U32,
+                    usize_,
+                ];
+
+                // Setup the return location.
+                let base_offset = self
+                    .storage_manager
+                    .claim_stack_area(dst, self.layout_interner.stack_size(ret_layout));
+
+                self.build_fn_call(
+                    &Symbol::DEV_TMP3,
+                    bitcode::LIST_SORT_WITH.to_string(),
+                    &arguments,
+                    &layouts,
+                    &ret_layout,
+                );
+
+                self.free_symbol(&Symbol::DEV_TMP);
+                self.free_symbol(&Symbol::DEV_TMP2);
+
+                // Return list value from fn call
+                self.storage_manager.copy_symbol_to_stack_offset(
+                    self.layout_interner,
+                    &mut self.buf,
+                    base_offset,
+                    &Symbol::DEV_TMP3,
+                    &ret_layout,
+                );
+
+                self.free_symbol(&Symbol::DEV_TMP3);
+            }
+            HigherOrder::StrWalkUtf8 { string, state } => {
+                let state_size = self.layout_interner.stack_size(ret_layout);
+
+                let caller = self.debug_symbol("caller");
+                let data = self.debug_symbol("data");
+                let state_area = self.debug_symbol("state_area");
+                let scratch_area = self.debug_symbol("scratch_area");
+                let state_ptr = self.debug_symbol("state_ptr");
+                let scratch_ptr = self.debug_symbol("scratch_ptr");
+                let state_width = self.debug_symbol("state_width");
+
+                self.load_layout_stack_size(ret_layout, state_width);
+
+                let caller_string = self.function_symbol_to_string(
+                    caller_proc.proc_symbol,
+                    std::iter::empty(),
+                    None,
+                    Layout::UNIT,
+                );
+
+                self.caller_procs.push(caller_proc);
+
+                // function pointer to a function that takes a pointer, and increments
+                let inc_n_data = if let Some(closure_env_layout) = higher_order.closure_env_layout {
+                    self.increment_fn_pointer(closure_env_layout)
+                } else {
+                    // null pointer
+                    self.load_literal_i64(&Symbol::DEV_TMP, 0);
+                    Symbol::DEV_TMP
+                };
+
+                self.build_fn_pointer(&caller, caller_string);
+
+                if let Some(_closure_data_layout) = higher_order.closure_env_layout {
+                    let data_symbol = higher_order.passed_function.captured_environment;
+                    self.storage_manager
+                        .ensure_symbol_on_stack(&mut self.buf, &data_symbol);
+                    let (data_offset, _) = self.storage_manager.stack_offset_and_size(&data_symbol);
+
+                    // Load address of the captured environment into register.
+                    let reg = self.storage_manager.claim_general_reg(&mut self.buf, &data);
+                    ASM::add_reg64_reg64_imm32(&mut self.buf, reg, CC::BASE_PTR_REG, data_offset);
+                } else {
+                    // use a null pointer
+                    self.load_literal(&data, &Layout::U64, &Literal::Int(0u128.to_be_bytes()));
+                }
+
+                // we pass a null pointer when the data is not owned. the zig code must not call this!
+                let data_is_owned = higher_order.closure_env_layout.is_some()
+                    && higher_order.passed_function.owns_captured_environment;
+
+                self.load_literal(
+                    &Symbol::DEV_TMP2,
+                    &Layout::BOOL,
+                    &Literal::Bool(data_is_owned),
+                );
+
+                // The state lives on the stack while we walk. The caller writes each new state
+                // to the scratch area, and zig copies it back over the old one.
+                let state_offset = self
+                    .storage_manager
+                    .claim_stack_area(&state_area, state_size);
+                self.storage_manager.copy_symbol_to_stack_offset(
+                    self.layout_interner,
+                    &mut self.buf,
+                    state_offset,
+                    &state,
+                    &ret_layout,
+                );
+                let scratch_offset = self
+                    .storage_manager
+                    .claim_stack_area(&scratch_area, state_size);
+
+                let reg = self
+                    .storage_manager
+                    .claim_general_reg(&mut self.buf, &state_ptr);
+                ASM::add_reg64_reg64_imm32(&mut self.buf, reg, CC::BASE_PTR_REG, state_offset);
+                let reg = self
+                    .storage_manager
+                    .claim_general_reg(&mut self.buf, &scratch_ptr);
+                ASM::add_reg64_reg64_imm32(&mut self.buf, reg, CC::BASE_PTR_REG, scratch_offset);
+
+                //    string: RocStr,
+                //    caller: Caller2,
+                //    data: Opaque,
+                //    inc_n_data: IncN,
+                //    data_is_owned: bool,
+                //    state: Opaque,
+                //    scratch: Opaque,
+                //    state_width: usize,
+
+                let arguments = [
+                    string,
+                    caller,
+                    data,
+                    inc_n_data,
+                    Symbol::DEV_TMP2,
+                    state_ptr,
+                    scratch_ptr,
+                    state_width,
+                ];
+
+                let ptr = Layout::U64;
+                let usize_ = Layout::U64;
+
+                let layouts = [Layout::STR, ptr, ptr, ptr, Layout::BOOL, ptr, ptr, usize_];
+
+                self.build_fn_call(
+                    &Symbol::DEV_TMP3,
+                    bitcode::STR_WALK_UTF8.to_string(),
+                    &arguments,
+                    &layouts,
+                    &Layout::UNIT,
+                );
+
+                self.free_symbol(&Symbol::DEV_TMP);
+                self.free_symbol(&Symbol::DEV_TMP2);
+                self.free_symbol(&Symbol::DEV_TMP3);
+
+                // Read the final state out of the stack area
+                let ptr_reg = self
+                    .storage_manager
+                    .load_to_general_reg(&mut self.buf, &state_ptr);
+                Self::ptr_read(
+                    &mut self.buf,
+                    &mut self.storage_manager,
+                    self.layout_interner,
+                    ptr_reg,
+                    ret_layout,
+                    *dst,
+                );
+
+                self.free_symbol(&state_ptr);
+                self.free_symbol(&scratch_ptr);
+                self.free_symbol(&state_area);
+                self.free_symbol(&scratch_area);
+            }
         }
     }
 
@@ -2601,7 +2886,7 @@ impl<
         union_layout: &UnionLayout<'a>,
     ) {
         match union_layout {
-            UnionLayout::NonRecursive(tag_layouts) | UnionLayout::Recursive(tag_layouts) => {
+            UnionLayout::NonRecursive(tag_layouts) => {
                 self.storage_manager.load_field_at_index(
                     self.layout_interner,
                     sym,
@@ -2611,13 +2896,54 @@ impl<
                 );
             }
             _ => {
-                let union_in_layout = self
-                    .layout_interner
-                    .insert_no_semantic(LayoutRepr::Union(*union_layout));
-                todo!(
-                    "loading from union type: {:?}",
-                    self.layout_interner.dbg(union_in_layout)
-                )
+                // the payload is on the heap, behind the pointer
+                debug_assert!(!union_layout.tag_is_null(tag_id));
+
+                let field_layouts = Self::union_field_layouts(union_layout, tag_id);
+                let field_offset: u32 = field_layouts
+                    .iter()
+                    .take(index as usize)
+                    .map(|field_layout| self.layout_interner.stack_size(*field_layout))
+                    .sum();
+                let field_layout =
+                    union_layout.layout_at(self.layout_interner, tag_id, index as usize);
+
+                let ptr_reg = self
+                    .storage_manager
+                    .load_to_general_reg(&mut self.buf, structure);
+                let field_ptr_reg = self
+                    .storage_manager
+                    .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP);
+
+                if union_layout.stores_tag_id_in_pointer(self.target_info) {
+                    let tag_id_mask = self.target_info.ptr_width() as i64 - 1;
+                    ASM::mov_reg64_imm64(&mut self.buf, field_ptr_reg, !tag_id_mask);
+                    ASM::and_reg64_reg64_reg64(
+                        &mut self.buf,
+                        field_ptr_reg,
+                        field_ptr_reg,
+                        ptr_reg,
+                    );
+                } else {
+                    ASM::mov_reg64_reg64(&mut self.buf, field_ptr_reg, ptr_reg);
+                }
+                ASM::add_reg64_reg64_imm32(
+                    &mut self.buf,
+                    field_ptr_reg,
+                    field_ptr_reg,
+                    field_offset as i32,
+                );
+
+                Self::ptr_read(
+                    &mut self.buf,
+                    &mut self.storage_manager,
+                    self.layout_interner,
+                    field_ptr_reg,
+                    field_layout,
+                    *sym,
+                );
+
+                self.free_symbol(&Symbol::DEV_TMP);
             }
         }
     }
@@ -2696,13 +3022,95 @@ impl<
     }
 
     fn get_tag_id(&mut self, sym: &Symbol, structure: &Symbol, union_layout: &UnionLayout<'a>) {
-        self.storage_manager.load_union_tag_id(
-            self.layout_interner,
-            &mut self.buf,
-            sym,
-            structure,
-            union_layout,
-        );
+        match union_layout {
+            UnionLayout::NonRecursive(_) => {
+                self.storage_manager.load_union_tag_id(
+                    self.layout_interner,
+                    &mut self.buf,
+                    sym,
+                    structure,
+                    union_layout,
+                );
+            }
+            UnionLayout::NonNullableUnwrapped(_) => {
+                // there is only one tag
+                self.load_literal(sym, &Layout::U8, &Literal::Int(0i128.to_ne_bytes()));
+            }
+            UnionLayout::NullableUnwrapped { nullable_id, .. } => {
+                // the tag id is whether the pointer is null (or the opposite)
+                let dst_reg = self.storage_manager.claim_general_reg(&mut self.buf, sym);
+                let ptr_reg = self
+                    .storage_manager
+                    .load_to_general_reg(&mut self.buf, structure);
+                let tmp_reg = self
+                    .storage_manager
+                    .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP);
+
+                ASM::mov_reg64_imm64(&mut self.buf, tmp_reg, 0);
+                ASM::eq_reg64_reg64_reg64(&mut self.buf, dst_reg, ptr_reg, tmp_reg);
+                if !nullable_id {
+                    ASM::mov_reg64_imm64(&mut self.buf, tmp_reg, 1);
+                    ASM::xor_reg64_reg64_reg64(&mut self.buf, dst_reg, dst_reg, tmp_reg);
+                }
+
+                self.free_symbol(&Symbol::DEV_TMP);
+            }
+            UnionLayout::Recursive(_) | UnionLayout::NullableWrapped { .. } => {
+                // Claim all registers up front: the code after the null check is skipped at
+                // runtime for a null pointer, so it must not move any symbols around.
+                let dst_reg = self.storage_manager.claim_general_reg(&mut self.buf, sym);
+                let ptr_reg = self
+                    .storage_manager
+                    .load_to_general_reg(&mut self.buf, structure);
+                let tmp_reg = self
+                    .storage_manager
+                    .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP);
+
+                let null_check = match union_layout {
+                    UnionLayout::NullableWrapped { nullable_id, .. } => {
+                        ASM::mov_reg64_imm64(&mut self.buf, dst_reg, *nullable_id as i64);
+                        ASM::mov_reg64_imm64(&mut self.buf, tmp_reg, 0);
+                        ASM::eq_reg64_reg64_reg64(&mut self.buf, tmp_reg, ptr_reg, tmp_reg);
+
+                        // Since we don't know the offset yet, set it to 0 and overwrite later.
+                        let jne_location = self.buf.len();
+                        let start_offset = ASM::jne_reg64_imm64_imm32(&mut self.buf, tmp_reg, 0, 0);
+                        Some((jne_location, start_offset))
+                    }
+                    _ => None,
+                };
+
+                if union_layout.stores_tag_id_in_pointer(self.target_info) {
+                    let tag_id_mask = self.target_info.ptr_width() as i64 - 1;
+                    ASM::mov_reg64_imm64(&mut self.buf, tmp_reg, tag_id_mask);
+                    ASM::and_reg64_reg64_reg64(&mut self.buf, dst_reg, ptr_reg, tmp_reg);
+                } else {
+                    let id_offset = union_layout
+                        .tag_id_offset(self.layout_interner, self.target_info)
+                        .unwrap() as i32;
+
+                    // the loads below only write the lower bits of the register
+                    ASM::mov_reg64_imm64(&mut self.buf, dst_reg, 0);
+                    match union_layout.discriminant() {
+                        Discriminant::U0 | Discriminant::U1 | Discriminant::U8 => {
+                            ASM::mov_reg8_mem8_offset32(&mut self.buf, dst_reg, ptr_reg, id_offset)
+                        }
+                        Discriminant::U16 => ASM::mov_reg16_mem16_offset32(
+                            &mut self.buf,
+                            dst_reg,
+                            ptr_reg,
+                            id_offset,
+                        ),
+                    }
+                }
+
+                if let Some((jne_location, start_offset)) = null_check {
+                    self.update_jne_imm32_offset(tmp_reg, 0, jne_location, start_offset);
+                }
+
+                self.free_symbol(&Symbol::DEV_TMP);
+            }
+        }
     }
 
     fn tag(
@@ -2711,15 +3119,122 @@ impl<
         fields: &'a [Symbol],
         union_layout: &UnionLayout<'a>,
         tag_id: TagIdIntType,
+        reuse: Option<Symbol>,
     ) {
-        self.storage_manager.create_union(
-            self.layout_interner,
-            &mut self.buf,
-            sym,
-            union_layout,
-            fields,
-            tag_id,
-        )
+        if let UnionLayout::NonRecursive(_) = union_layout {
+            return self.storage_manager.create_union(
+                self.layout_interner,
+                &mut self.buf,
+                sym,
+                union_layout,
+                fields,
+                tag_id,
+            );
+        }
+
+        // We always make a fresh allocation. A reset token is unique, and its children have
+        // already been handled by the reset, so releasing it only frees the memory.
+        let allocation_alignment =
+            union_layout.allocation_alignment_bytes(self.layout_interner, self.target_info);
+        if let Some(reused) = reuse {
+            self.load_literal(
+                &Symbol::DEV_TMP,
+                &Layout::U32,
+                &Literal::Int((allocation_alignment as i128).to_ne_bytes()),
+            );
+            self.build_fn_call(
+                &Symbol::DEV_TMP2,
+                bitcode::UTILS_DECREF_DATA_PTR.to_string(),
+                &[reused, Symbol::DEV_TMP],
+                &[Layout::U64, Layout::U32],
+                &Layout::UNIT,
+            );
+            self.free_symbol(&Symbol::DEV_TMP);
+            self.free_symbol(&Symbol::DEV_TMP2);
+        }
+
+        if union_layout.tag_is_null(tag_id) {
+            let dst_reg = self.storage_manager.claim_general_reg(&mut self.buf, sym);
+            ASM::mov_reg64_imm64(&mut self.buf, dst_reg, 0);
+            return;
+        }
+
+        let (data_size, _) =
+            union_layout.data_size_and_alignment(self.layout_interner, self.target_info);
+        self.load_literal(
+            &Symbol::DEV_TMP,
+            &Layout::U64,
+            &Literal::Int((data_size as i128).to_ne_bytes()),
+        );
+        self.load_literal(
+            &Symbol::DEV_TMP2,
+            &Layout::U32,
+            &Literal::Int((allocation_alignment as i128).to_ne_bytes()),
+        );
+        self.allocate_with_refcount(Symbol::DEV_TMP3, Symbol::DEV_TMP, Symbol::DEV_TMP2);
+        self.free_symbol(&Symbol::DEV_TMP);
+        self.free_symbol(&Symbol::DEV_TMP2);
+
+        let ptr_reg = self
+            .storage_manager
+            .load_to_general_reg(&mut self.buf, &Symbol::DEV_TMP3);
+
+        let field_layouts = Self::union_field_layouts(union_layout, tag_id);
+        let mut field_offset = 0;
+        for (field, field_layout) in fields.iter().zip(field_layouts.iter()) {
+            let field_width = self.layout_interner.stack_size(*field_layout);
+            let layout = self.layout_interner.get(*field_layout);
+
+            Self::ptr_write(
+                &mut self.buf,
+                &mut self.storage_manager,
+                self.layout_interner,
+                ptr_reg,
+                field_offset as i32,
+                field_width as u64,
+                layout,
+                *field,
+            );
+
+            field_offset += field_width;
+        }
+
+        if union_layout.stores_tag_id_as_data(self.target_info) {
+            let id_offset = union_layout
+                .tag_id_offset(self.layout_interner, self.target_info)
+                .unwrap() as i32;
+            let discriminant = union_layout.discriminant();
+
+            self.storage_manager.with_tmp_general_reg(
+                &mut self.buf,
+                |_storage_manager, buf, tmp_reg| {
+                    ASM::mov_reg64_imm64(buf, tmp_reg, tag_id as i64);
+                    match discriminant {
+                        Discriminant::U0 | Discriminant::U1 | Discriminant::U8 => {
+                            ASM::mov_mem8_offset32_reg8(buf, ptr_reg, id_offset, tmp_reg)
+                        }
+                        Discriminant::U16 => {
+                            ASM::mov_mem16_offset32_reg16(buf, ptr_reg, id_offset, tmp_reg)
+                        }
+                    }
+                },
+            );
+        }
+
+        let dst_reg = self.storage_manager.claim_general_reg(&mut self.buf, sym);
+        ASM::mov_reg64_reg64(&mut self.buf, dst_reg, ptr_reg);
+
+        if union_layout.stores_tag_id_in_pointer(self.target_info) && tag_id != 0 {
+            self.storage_manager.with_tmp_general_reg(
+                &mut self.buf,
+                |_storage_manager, buf, tmp_reg| {
+                    ASM::mov_reg64_imm64(buf, tmp_reg, tag_id as i64);
+                    ASM::or_reg64_reg64_reg64(buf, dst_reg, dst_reg, tmp_reg);
+                },
+            );
+        }
+
+        self.free_symbol(&Symbol::DEV_TMP3);
     }
 
     fn load_literal(&mut self, sym: &Symbol, layout: &InLayout<'a>, lit: &Literal<'a>) {
@@ -2836,15 +3351,29 @@ impl<
                         },
                     );
                 } else {
-                    // load large string (pretend it's a `List U8`). We should move this data into
-                    // the binary eventually because our RC algorithm won't free this value
-                    let elements: Vec<_> = x
-                        .as_bytes()
-                        .iter()
-                        .map(|b| ListLiteralElement::Literal(Literal::Byte(*b)))
-                        .collect_in(self.storage_manager.env.arena);
-
-                    self.create_array(sym, &Layout::U8, elements.into_bump_slice())
+                    // Large strings are placed in read-only data, after a refcount that marks
+                    // them as constants, so they're never incremented, decremented or freed.
+                    let mut data = std::vec::Vec::with_capacity(8 + x.len());
+                    data.extend_from_slice(&(REFCOUNT_MAX as u64).to_le_bytes());
+                    data.extend_from_slice(x.as_bytes());
+
+                    let relocs = &mut self.relocs;
+
+                    self.storage_manager.with_tmp_general_reg(
+                        &mut self.buf,
+                        |storage_manager, buf, reg| {
+                            let base_offset = storage_manager.claim_stack_area(sym, 24);
+
+                            // The string's bytes start right after the refcount.
+                            ASM::data_pointer(buf, relocs, data, reg);
+                            ASM::add_reg64_reg64_imm32(buf, reg, reg, 8);
+                            ASM::mov_base32_reg64(buf, base_offset, reg);
+
+                            ASM::mov_reg64_imm64(buf, reg, x.len() as i64);
+                            ASM::mov_base32_reg64(buf, base_offset + 8, reg);
+                            ASM::mov_base32_reg64(buf, base_offset + 16, reg);
+                        },
+                    );
                 }
             }
             _ => todo!("loading literal {:?} with layout {:?}", lit, layout),
@@ -2876,7 +3405,7 @@ impl<
                     );
                 }
                 other => match other {
-                    LayoutRepr::Boxed(_) => {
+                    pointer_layouts!() => {
                         // treat like a 64-bit integer
                         self.storage_manager.load_to_specified_general_reg(
                             &mut self.buf,
@@ -2902,7 +3431,7 @@ impl<
             )
         }
         let inst_loc = self.buf.len() as u64;
-        let offset = ASM::jmp_imm32(&mut self.buf, 0x1234_5678) as u64;
+        let offset = ASM::jmp_imm32(&mut self.buf, 0) as u64;
         self.relocs.push(Relocation::JmpToReturn {
             inst_loc,
             inst_size: self.buf.len() as u64 - inst_loc,
@@ -2920,7 +3449,17 @@ impl<
         let buf = &mut self.buf;
 
         match int_width {
-            IntWidth::U128 | IntWidth::I128 => todo!(),
+            IntWidth::U128 | IntWidth::I128 => {
+                let layout = Layout::int_width(int_width);
+
+                self.build_fn_call(
+                    dst,
+                    bitcode::NUM_BITWISE_AND[int_width].to_string(),
+                    &[*src1, *src2],
+                    &[layout, layout],
+                    &layout,
+                );
+            }
             _ => {
                 let dst_reg = self.storage_manager.claim_general_reg(buf, dst);
                 let src1_reg = self.storage_manager.load_to_general_reg(buf, src1);
@@ -2940,7 +3479,17 @@ impl<
         let buf = &mut self.buf;
 
         match int_width {
-            IntWidth::U128 | IntWidth::I128 => todo!(),
+            IntWidth::U128 | IntWidth::I128 => {
+                let layout = Layout::int_width(int_width);
+
+                self.build_fn_call(
+                    dst,
+                    bitcode::NUM_BITWISE_OR[int_width].to_string(),
+                    &[*src1, *src2],
+                    &[layout, layout],
+                    &layout,
+                );
+            }
             _ => {
                 let dst_reg = self.storage_manager.claim_general_reg(buf, dst);
                 let src1_reg = self.storage_manager.load_to_general_reg(buf, src1);
@@ -2960,7 +3509,17 @@ impl<
         let buf = &mut self.buf;
 
         match int_width {
-            IntWidth::U128 | IntWidth::I128 => todo!(),
+            IntWidth::U128 | IntWidth::I128 => {
+                let layout = Layout::int_width(int_width);
+
+                self.build_fn_call(
+                    dst,
+                    bitcode::NUM_BITWISE_XOR[int_width].to_string(),
+                    &[*src1, *src2],
+                    &[layout, layout],
+                    &layout,
+                );
+            }
             _ => {
                 let dst_reg = self.storage_manager.claim_general_reg(buf, dst);
                 let src1_reg = self.storage_manager.load_to_general_reg(buf, src1);
@@ -2977,24 +3536,7 @@ impl<
         src2: &Symbol,
         int_width: IntWidth,
     ) {
-        let buf = &mut self.buf;
-
-        match int_width {
-            IntWidth::U128 | IntWidth::I128 => todo!(),
-            _ => {
-                let dst_reg = self.storage_manager.claim_general_reg(buf, dst);
-                let src1_reg = self.storage_manager.load_to_general_reg(buf, src1);
-                let src2_reg = self.storage_manager.load_to_general_reg(buf, src2);
-
-                ASM::shl_reg64_reg64_reg64(
-                    buf,
-                    &mut self.storage_manager,
-                    dst_reg,
-                    src1_reg,
-                    src2_reg,
-                );
-            }
-        }
+        self.build_int_shift_call(dst, src1, src2, int_width, &bitcode::NUM_SHIFT_LEFT_BY)
     }
 
     fn build_int_shift_right(
@@ -3004,64 +3546,7 @@ impl<
         src2: &Symbol,
         int_width: IntWidth,
     ) {
-        let buf = &mut self.buf;
-
-        match int_width {
-            IntWidth::U128 | IntWidth::I128 => todo!(),
-            _ => {
-                let dst_reg = self.storage_manager.claim_general_reg(buf, dst);
-                let src1_reg = self.storage_manager.load_to_general_reg(buf, src1);
-                let src2_reg = self.storage_manager.load_to_general_reg(buf, src2);
-
-                // to get sign extension "for free", we move our bits to the left
-                // so the integers sign bit is stored in the register's sign bit.
-                // Then we arithmetic shift right, getting the correct sign extension behavior,
-                // then shift logical right to get the bits back into the position they should
-                // be for our particular integer width
-                let sign_extend_shift_amount = 64 - (int_width.stack_size() as i64 * 8);
-
-                if sign_extend_shift_amount > 0 {
-                    self.storage_manager.with_tmp_general_reg(
-                        buf,
-                        |storage_manager, buf, tmp_reg| {
-                            ASM::mov_reg64_imm64(buf, tmp_reg, sign_extend_shift_amount);
-                            ASM::shl_reg64_reg64_reg64(
-                                buf,
-                                storage_manager,
-                                src1_reg,
-                                src1_reg,
-                                tmp_reg,
-                            );
-                        },
-                    )
-                }
-
-                ASM::sar_reg64_reg64_reg64(
-                    buf,
-                    &mut self.storage_manager,
-                    dst_reg,
-                    src1_reg,
-                    src2_reg,
-                );
-
-                if sign_extend_shift_amount > 0 {
-                    // shift back if needed
-                    self.storage_manager.with_tmp_general_reg(
-                        &mut self.buf,
-                        |storage_manager, buf, tmp_reg| {
-                            ASM::mov_reg64_imm64(buf, tmp_reg, sign_extend_shift_amount);
-                            ASM::shr_reg64_reg64_reg64(
-                                buf,
-                                storage_manager,
-                                dst_reg,
-                                dst_reg,
-                                tmp_reg,
-                            );
-                        },
-                    )
-                }
-            }
-        }
+        self.build_int_shift_call(dst, src1, src2, int_width, &bitcode::NUM_SHIFT_RIGHT_BY)
     }
 
     fn build_int_shift_right_zero_fill(
@@ -3071,38 +3556,13 @@ impl<
         src2: &Symbol,
         int_width: IntWidth,
     ) {
-        let buf = &mut self.buf;
-
-        match int_width {
-            IntWidth::U128 | IntWidth::I128 => {
-                let layout = match int_width {
-                    IntWidth::I128 => Layout::I128,
-                    IntWidth::U128 => Layout::U128,
-                    _ => unreachable!(),
-                };
-
-                self.build_fn_call(
-                    dst,
-                    bitcode::NUM_SHIFT_RIGHT_ZERO_FILL[int_width].to_string(),
-                    &[*src1, *src2],
-                    &[layout, layout],
-                    &layout,
-                );
-            }
-            _ => {
-                let dst_reg = self.storage_manager.claim_general_reg(buf, dst);
-                let src1_reg = self.storage_manager.load_to_general_reg(buf, src1);
-                let src2_reg = self.storage_manager.load_to_general_reg(buf, src2);
-
-                ASM::shr_reg64_reg64_reg64(
-                    buf,
-                    &mut self.storage_manager,
-                    dst_reg,
-                    src1_reg,
-                    src2_reg,
-                );
-            }
-        }
+        self.build_int_shift_call(
+            dst,
+            src1,
+            src2,
+            int_width,
+            &bitcode::NUM_SHIFT_RIGHT_ZERO_FILL,
+        )
     }
 
     fn build_num_sqrt(&mut self, dst: Symbol, src: Symbol, float_width: FloatWidth) {
@@ -3228,6 +3688,27 @@ impl<
         CC: CallConv<GeneralReg, FloatReg, ASM>,
     > Backend64Bit<'a, 'r, GeneralReg, FloatReg, ASM, CC>
 {
+    /// Shift instructions take the amount modulo the register width, so shifting by the bit width
+    /// of the integer or more is handled by the zig builtins instead.
+    fn build_int_shift_call(
+        &mut self,
+        dst: &Symbol,
+        src1: &Symbol,
+        src2: &Symbol,
+        int_width: IntWidth,
+        intrinsic: &IntrinsicName,
+    ) {
+        let layout = Layout::int_width(int_width);
+
+        self.build_fn_call(
+            dst,
+            intrinsic[int_width].to_string(),
+            &[*src1, *src2],
+            &[layout, Layout::U8],
+            &layout,
+        );
+    }
+
     fn compare(
         &mut self,
         op: CompareOperation,
@@ -3427,7 +3908,7 @@ impl<
                 }
             },
 
-            LayoutRepr::Boxed(_) => {
+            pointer_layouts!() => {
                 // the same as 64-bit integer (for 64-bit targets)
                 let dst_reg = storage_manager.claim_general_reg(buf, &dst);
                 ASM::mov_reg64_mem64_offset32(buf, dst_reg, ptr_reg, 0);
@@ -3498,7 +3979,7 @@ impl<
                 let sym_reg = storage_manager.load_to_float_reg(buf, &value);
                 ASM::movesd_mem64_offset32_freg64(buf, ptr_reg, element_offset, sym_reg);
             }
-            LayoutRepr::Boxed(_) => {
+            pointer_layouts!() => {
                 let sym_reg = storage_manager.load_to_general_reg(buf, &value);
                 ASM::mov_mem64_offset32_reg64(buf, ptr_reg, element_offset, sym_reg);
             }
@@ -3589,6 +4070,45 @@ impl<
         }
     }
 
+    /// Points a `jne` that was written with a placeholder offset at the end of the buffer.
+    fn update_jne_imm32_offset(
+        &mut self,
+        reg: GeneralReg,
+        imm: u64,
+        jne_location: usize,
+        start_offset: usize,
+    ) {
+        let mut tmp = bumpalo::vec![in self.env.arena];
+        let jne_offset = self.buf.len() - start_offset;
+        ASM::jne_reg64_imm64_imm32(&mut tmp, reg, imm, jne_offset as i32);
+        for (i, byte) in tmp.iter().enumerate() {
+            self.buf[jne_location + i] = *byte;
+        }
+    }
+
+    /// The fields of a non-null tag in a union that lives on the heap
+    fn union_field_layouts(
+        union_layout: &UnionLayout<'a>,
+        tag_id: TagIdIntType,
+    ) -> &'a [InLayout<'a>] {
+        match union_layout {
+            UnionLayout::NonRecursive(tags) | UnionLayout::Recursive(tags) => tags[tag_id as usize],
+            UnionLayout::NonNullableUnwrapped(fields) => *fields,
+            UnionLayout::NullableWrapped {
+                nullable_id,
+                other_tags,
+            } => {
+                let index = if tag_id > *nullable_id {
+                    tag_id - 1
+                } else {
+                    tag_id
+                };
+                other_tags[index as usize]
+            }
+            UnionLayout::NullableUnwrapped { other_fields, .. } => *other_fields,
+        }
+    }
+
     /// Updates a jump instruction to a new offset and returns the number of bytes written.
     fn update_jmp_imm32_offset(
         &mut self,
@@ -3672,3 +4192,18 @@ macro_rules! single_register_layouts {
         single_register_integers!() | single_register_floats!()
     };
 }
+
+/// Layouts whose values are a single pointer, which we treat as 64-bit integers
+#[macro_export]
+macro_rules! pointer_layouts {
+    () => {
+        LayoutRepr::Boxed(_)
+            | LayoutRepr::RecursivePointer(_)
+            | LayoutRepr::Union(
+                UnionLayout::Recursive(_)
+                    | UnionLayout::NonNullableUnwrapped(_)
+                    | UnionLayout::NullableWrapped { .. }
+                    | UnionLayout::NullableUnwrapped { .. },
+            )
+    };
+}
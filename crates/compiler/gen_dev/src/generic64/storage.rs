@@ -1,7 +1,8 @@
 use crate::{
     generic64::{Assembler, CallConv, RegTrait},
-    sign_extended_int_builtins, single_register_floats, single_register_int_builtins,
-    single_register_integers, single_register_layouts, Env,
+    pointer_layouts, sign_extended_int_builtins, single_register_floats,
+    single_register_int_builtins, single_register_integers, single_register_layouts, Env,
+    UsePositions,
 };
 use bumpalo::collections::Vec;
 use roc_builtins::bitcode::{FloatWidth, IntWidth};
@@ -9,7 +10,7 @@ use roc_collections::all::{MutMap, MutSet};
 use roc_error_macros::internal_error;
 use roc_module::symbol::Symbol;
 use roc_mono::{
-    ir::{JoinPointId, Param},
+    ir::{JoinPointId, Param, Stmt},
     layout::{
         Builtin, InLayout, Layout, LayoutInterner, LayoutRepr, STLayoutInterner, TagIdIntType,
         UnionLayout,
@@ -79,6 +80,17 @@ enum Storage<GeneralReg: RegTrait, FloatReg: RegTrait> {
     NoData,
 }
 
+/// Tracks where every symbol of the proc being built lives, moving values between registers
+/// and the stack as its statements are built.
+///
+/// Registers are handed out in a single pass, in the order build_stmt visits the statements,
+/// rather than by a linear scan over precomputed live intervals. A symbol gets a register when it
+/// is defined or first loaded, and gives it back after its last use. When every register is taken,
+/// the symbol whose next use is the furthest away is spilled, and it's reloaded at that use.
+/// Symbols that are live across a call prefer callee saved registers.
+///
+/// Join point parameters are always passed on the stack, so a loop still stores and reloads its
+/// parameters on every iteration.
 #[derive(Clone)]
 pub struct StorageManager<
     'a,
@@ -111,12 +123,15 @@ pub struct StorageManager<
     general_free_regs: Vec<'a, GeneralReg>,
     float_free_regs: Vec<'a, FloatReg>,
 
-    // The last major thing we need is a way to decide what reg to free when all of them are full.
-    // Theoretically we want a basic lru cache for the currently loaded symbols.
-    // For now just a vec of used registers and the symbols they contain.
+    // The registers currently holding symbols.
+    // When all of them are full, the symbol whose next use is the furthest away gets spilled.
     general_used_regs: Vec<'a, (GeneralReg, Symbol)>,
     float_used_regs: Vec<'a, (FloatReg, Symbol)>,
 
+    // Where every symbol is used, and the position of the statement currently being built.
+    use_positions: Rc<UsePositions<'a>>,
+    position: u32,
+
     // TODO: it probably would be faster to make these a list that linearly scans rather than hashing.
     // used callee saved regs must be tracked for pushing and popping at the beginning/end of the function.
     general_used_callee_saved_regs: MutSet<GeneralReg>,
@@ -154,6 +169,8 @@ pub fn new_storage_manager<
         float_free_regs: bumpalo::vec![in env.arena],
         float_used_regs: bumpalo::vec![in env.arena],
         float_used_callee_saved_regs: MutSet::default(),
+        use_positions: Rc::default(),
+        position: 0,
         free_stack_chunks: bumpalo::vec![in env.arena],
         stack_size: 0,
         fn_call_stack_size: 0,
@@ -183,6 +200,8 @@ impl<
         self.float_used_regs.clear();
         self.float_free_regs
             .extend_from_slice(CC::FLOAT_DEFAULT_FREE_REGS);
+        self.use_positions = Rc::default();
+        self.position = 0;
         self.free_stack_chunks.clear();
         self.stack_size = 0;
         self.fn_call_stack_size = 0;
@@ -216,16 +235,58 @@ impl<
         )
     }
 
+    pub fn set_use_positions(&mut self, use_positions: UsePositions<'a>) {
+        self.use_positions = Rc::new(use_positions);
+        self.position = 0;
+    }
+
+    /// Moves to the position of `stmt`.
+    /// Statements without a position were generated while building, so they keep the current one.
+    pub fn enter_stmt(&mut self, stmt: &Stmt<'a>) {
+        if let Some(position) = self.use_positions.stmt_position(stmt) {
+            self.position = position;
+        }
+    }
+
+    /// Values that are still needed after a call are better off in callee saved registers.
+    /// Otherwise, they get pushed to the stack before the call and reloaded after it.
+    fn live_across_call(&self, sym: &Symbol) -> bool {
+        self.use_positions.live_across_call(sym, self.position)
+    }
+
+    /// Picks which of the used registers to spill: the one whose symbol is needed again the latest.
+    /// Symbols that are never used again go first. Ties spill the register that was loaded first.
+    fn spill_index<'s>(&self, syms: impl Iterator<Item = &'s Symbol>) -> usize {
+        let mut spill = (0, 0);
+        for (index, sym) in syms.enumerate() {
+            let next_use = self
+                .use_positions
+                .next_use(sym, self.position)
+                .unwrap_or(u32::MAX);
+            if index == 0 || next_use > spill.1 {
+                spill = (index, next_use);
+            }
+        }
+        spill.0
+    }
+
     /// Get a general register from the free list.
     /// Will free data to the stack if necessary to get the register.
-    fn get_general_reg(&mut self, buf: &mut Vec<'a, u8>) -> GeneralReg {
-        if let Some(reg) = self.general_free_regs.pop() {
+    fn get_general_reg(&mut self, buf: &mut Vec<'a, u8>, prefer_callee_saved: bool) -> GeneralReg {
+        let free_index = self
+            .general_free_regs
+            .iter()
+            .rposition(|reg| CC::general_callee_saved(reg) == prefer_callee_saved)
+            .or_else(|| self.general_free_regs.len().checked_sub(1));
+        if let Some(index) = free_index {
+            let reg = self.general_free_regs.remove(index);
             if CC::general_callee_saved(&reg) {
                 self.general_used_callee_saved_regs.insert(reg);
             }
             reg
         } else if !self.general_used_regs.is_empty() {
-            let (reg, sym) = self.general_used_regs.remove(0);
+            let index = self.spill_index(self.general_used_regs.iter().map(|(_, sym)| sym));
+            let (reg, sym) = self.general_used_regs.remove(index);
             self.free_to_stack(buf, &sym, General(reg));
             reg
         } else {
@@ -235,14 +296,21 @@ impl<
 
     /// Get a float register from the free list.
     /// Will free data to the stack if necessary to get the register.
-    fn get_float_reg(&mut self, buf: &mut Vec<'a, u8>) -> FloatReg {
-        if let Some(reg) = self.float_free_regs.pop() {
+    fn get_float_reg(&mut self, buf: &mut Vec<'a, u8>, prefer_callee_saved: bool) -> FloatReg {
+        let free_index = self
+            .float_free_regs
+            .iter()
+            .rposition(|reg| CC::float_callee_saved(reg) == prefer_callee_saved)
+            .or_else(|| self.float_free_regs.len().checked_sub(1));
+        if let Some(index) = free_index {
+            let reg = self.float_free_regs.remove(index);
             if CC::float_callee_saved(&reg) {
                 self.float_used_callee_saved_regs.insert(reg);
             }
             reg
         } else if !self.float_used_regs.is_empty() {
-            let (reg, sym) = self.float_used_regs.remove(0);
+            let index = self.spill_index(self.float_used_regs.iter().map(|(_, sym)| sym));
+            let (reg, sym) = self.float_used_regs.remove(index);
             self.free_to_stack(buf, &sym, Float(reg));
             reg
         } else {
@@ -258,7 +326,7 @@ impl<
             None,
             "Symbol {sym:?} is already in the storage map!"
         );
-        let reg = self.get_general_reg(buf);
+        let reg = self.get_general_reg(buf, self.live_across_call(sym));
         self.general_used_regs.push((reg, *sym));
         self.symbol_storage_map.insert(*sym, Reg(General(reg)));
         reg
@@ -268,7 +336,7 @@ impl<
     /// They symbol should not already have storage.
     pub fn claim_float_reg(&mut self, buf: &mut Vec<'a, u8>, sym: &Symbol) -> FloatReg {
         debug_assert_eq!(self.symbol_storage_map.get(sym), None);
-        let reg = self.get_float_reg(buf);
+        let reg = self.get_float_reg(buf, self.live_across_call(sym));
         self.float_used_regs.push((reg, *sym));
         self.symbol_storage_map.insert(*sym, Reg(Float(reg)));
         reg
@@ -281,7 +349,7 @@ impl<
         buf: &mut Vec<'a, u8>,
         callback: F,
     ) {
-        let reg = self.get_general_reg(buf);
+        let reg = self.get_general_reg(buf, false);
         callback(self, buf, reg);
         self.general_free_regs.push(reg);
     }
@@ -294,7 +362,7 @@ impl<
         buf: &mut Vec<'a, u8>,
         callback: F,
     ) {
-        let reg = self.get_float_reg(buf);
+        let reg = self.get_float_reg(buf, false);
         callback(self, buf, reg);
         self.float_free_regs.push(reg);
     }
@@ -326,7 +394,7 @@ impl<
                 base_offset,
             }) => {
                 debug_assert_eq!(base_offset % 8, 0);
-                let reg = self.get_general_reg(buf);
+                let reg = self.get_general_reg(buf, self.live_across_call(sym));
                 ASM::mov_reg64_base32(buf, reg, base_offset);
                 self.general_used_regs.push((reg, *sym));
                 self.symbol_storage_map.insert(
@@ -343,7 +411,7 @@ impl<
                 size,
                 sign_extend,
             }) => {
-                let reg = self.get_general_reg(buf);
+                let reg = self.get_general_reg(buf, self.live_across_call(sym));
                 if sign_extend {
                     ASM::movsx_reg64_base32(buf, reg, base_offset, size as u8);
                 } else {
@@ -392,7 +460,7 @@ impl<
                 base_offset,
             }) => {
                 debug_assert_eq!(base_offset % 8, 0);
-                let reg = self.get_float_reg(buf);
+                let reg = self.get_float_reg(buf, self.live_across_call(sym));
                 ASM::mov_freg64_base32(buf, reg, base_offset);
                 self.float_used_regs.push((reg, *sym));
                 self.symbol_storage_map.insert(
@@ -408,7 +476,7 @@ impl<
                 base_offset, size, ..
             }) if base_offset % 8 == 0 && size == 8 => {
                 // The primitive is aligned and the data is exactly 8 bytes, treat it like regular stack.
-                let reg = self.get_float_reg(buf);
+                let reg = self.get_float_reg(buf, self.live_across_call(sym));
                 ASM::mov_freg64_base32(buf, reg, base_offset);
                 self.float_used_regs.push((reg, *sym));
                 self.symbol_storage_map.insert(*sym, Reg(Float(reg)));
@@ -845,7 +913,7 @@ impl<
                     self.copy_to_stack_offset(buf, size, from_offset, to_offset)
                 }
             },
-            LayoutRepr::Boxed(_) => {
+            pointer_layouts!() => {
                 // like a 64-bit integer
                 debug_assert_eq!(to_offset % 8, 0);
                 let reg = self.load_to_general_reg(buf, sym);
@@ -1160,7 +1228,7 @@ impl<
         layout: InLayout<'a>,
     ) {
         match layout_interner.get(layout).repr {
-            single_register_layouts!() => {
+            single_register_layouts!() | pointer_layouts!() => {
                 let base_offset = self.claim_stack_size(8);
                 self.symbol_storage_map.insert(
                     symbol,
@@ -1246,7 +1314,7 @@ impl<
                         base_offset,
                     );
                 }
-                LayoutRepr::Boxed(_) => {
+                pointer_layouts!() => {
                     let reg = self.load_to_general_reg(buf, &symbol);
                     ASM::mov_base32_reg64(buf, base_offset, reg);
                 }
@@ -1542,7 +1610,7 @@ fn is_primitive(layout_interner: &mut STLayoutInterner<'_>, layout: InLayout<'_>
     match layout_interner.get(layout).repr {
         single_register_layouts!() => true,
         _ => match layout_interner.get(layout).repr {
-            LayoutRepr::Boxed(_) => true,
+            pointer_layouts!() => true,
             LayoutRepr::LambdaSet(lambda_set) => {
                 is_primitive(layout_interner, lambda_set.runtime_representation())
             }
@@ -1,7 +1,7 @@
 use crate::generic64::{storage::StorageManager, Assembler, CallConv, RegTrait};
 use crate::{
-    single_register_floats, single_register_int_builtins, single_register_integers,
-    single_register_layouts, Relocation,
+    pointer_layouts, single_register_floats, single_register_int_builtins,
+    single_register_integers, single_register_layouts, Relocation,
 };
 use bumpalo::collections::Vec;
 use roc_builtins::bitcode::{FloatWidth, IntWidth};
@@ -510,8 +510,8 @@ impl X64_64SystemVStoreArgs {
             other => {
                 // look at the layout in more detail
                 match other {
-                    LayoutRepr::Boxed(_) => {
-                        // treat boxed like a 64-bit integer
+                    pointer_layouts!() => {
+                        // treat pointers like a 64-bit integer
                         self.store_arg_general(buf, storage_manager, sym)
                     }
                     LayoutRepr::LambdaSet(lambda_set) => self.store_arg(
@@ -674,8 +674,8 @@ impl X64_64SystemVLoadArgs {
                 self.argument_offset += stack_size as i32;
             }
             other => match other {
-                LayoutRepr::Boxed(_) => {
-                    // boxed layouts are pointers, which we treat as 64-bit integers
+                pointer_layouts!() => {
+                    // these layouts are pointers, which we treat as 64-bit integers
                     self.load_arg_general(storage_manager, sym)
                 }
                 LayoutRepr::LambdaSet(lambda_set) => self.load_arg(
@@ -797,7 +797,7 @@ impl CallConv<X86_64GeneralReg, X86_64FloatReg, X86_64Assembler> for X86_64Windo
         // We will use pop to get which reg to use next
         // Use callee saved regs last.
         X86_64FloatReg::XMM15,
-        X86_64FloatReg::XMM15,
+        X86_64FloatReg::XMM14,
         X86_64FloatReg::XMM13,
         X86_64FloatReg::XMM12,
         X86_64FloatReg::XMM11,
@@ -836,12 +836,16 @@ impl CallConv<X86_64GeneralReg, X86_64FloatReg, X86_64Assembler> for X86_64Windo
     fn float_callee_saved(reg: &X86_64FloatReg) -> bool {
         matches!(
             reg,
-            X86_64FloatReg::XMM0
-                | X86_64FloatReg::XMM1
-                | X86_64FloatReg::XMM2
-                | X86_64FloatReg::XMM3
-                | X86_64FloatReg::XMM4
-                | X86_64FloatReg::XMM5
+            X86_64FloatReg::XMM6
+                | X86_64FloatReg::XMM7
+                | X86_64FloatReg::XMM8
+                | X86_64FloatReg::XMM9
+                | X86_64FloatReg::XMM10
+                | X86_64FloatReg::XMM11
+                | X86_64FloatReg::XMM12
+                | X86_64FloatReg::XMM13
+                | X86_64FloatReg::XMM14
+                | X86_64FloatReg::XMM15
         )
     }
 
@@ -881,7 +885,7 @@ impl CallConv<X86_64GeneralReg, X86_64FloatReg, X86_64Assembler> for X86_64Windo
 
     #[inline(always)]
     fn load_args<'a>(
-        _buf: &mut Vec<'a, u8>,
+        buf: &mut Vec<'a, u8>,
         storage_manager: &mut X86_64StorageManager<'a, '_, X86_64WindowsFastcall>,
         layout_interner: &mut STLayoutInterner<'a>,
         args: &'a [(InLayout<'a>, Symbol)],
@@ -889,45 +893,66 @@ impl CallConv<X86_64GeneralReg, X86_64FloatReg, X86_64Assembler> for X86_64Windo
     ) {
         let mut arg_offset = Self::SHADOW_SPACE_SIZE as i32 + 16; // 16 is the size of the pushed return address and base pointer.
 
-        let mut general_registers_used = 0;
-        let mut float_registers_used = 0;
+        // Unlike SystemV, every argument takes up the next slot, and slot `i` is either
+        // the `i`th general or the `i`th float param reg, never both.
+        let mut slot = 0;
 
         if X86_64WindowsFastcall::returns_via_arg_pointer(layout_interner, ret_layout) {
             storage_manager.ret_pointer_arg(Self::GENERAL_PARAM_REGS[0]);
-            general_registers_used += 1;
+            slot += 1;
         }
 
         for (layout, sym) in args.iter() {
+            let size = layout_interner.stack_size(*layout);
             match layout_interner.get(*layout).repr {
-                single_register_integers!() => {
-                    match Self::GENERAL_PARAM_REGS.get(general_registers_used) {
-                        Some(reg) => {
-                            storage_manager.general_reg_arg(sym, *reg);
-                            general_registers_used += 1;
-                        }
-                        None => {
-                            storage_manager.primitive_stack_arg(sym, arg_offset);
-                            arg_offset += 8;
-                        }
-                    }
+                _ if size == 0 => {
+                    storage_manager.no_data(sym);
+                    continue;
                 }
-                single_register_floats!() => {
-                    match Self::FLOAT_PARAM_REGS.get(float_registers_used) {
-                        Some(reg) => {
-                            storage_manager.float_reg_arg(sym, *reg);
-                            float_registers_used += 1;
-                        }
+                single_register_integers!() => match Self::GENERAL_PARAM_REGS.get(slot) {
+                    Some(reg) => storage_manager.general_reg_arg(sym, *reg),
+                    None => {
+                        storage_manager.primitive_stack_arg(sym, arg_offset);
+                        arg_offset += 8;
+                    }
+                },
+                single_register_floats!() => match Self::FLOAT_PARAM_REGS.get(slot) {
+                    Some(reg) => storage_manager.float_reg_arg(sym, *reg),
+                    None => {
+                        storage_manager.primitive_stack_arg(sym, arg_offset);
+                        arg_offset += 8;
+                    }
+                },
+                _ if Self::passes_by_value(size) => match Self::GENERAL_PARAM_REGS.get(slot) {
+                    Some(reg) => {
+                        let base_offset = storage_manager.claim_stack_area(sym, size);
+                        X86_64Assembler::mov_base32_reg64(buf, base_offset, *reg);
+                    }
+                    None => {
+                        storage_manager.complex_stack_arg(sym, arg_offset, size);
+                        arg_offset += 8;
+                    }
+                },
+                _ => {
+                    // Everything else is passed as a pointer to a copy made by the caller.
+                    // R10 and R11 are never param regs, so they are free to use here.
+                    let ptr_reg = match Self::GENERAL_PARAM_REGS.get(slot) {
+                        Some(reg) => *reg,
                         None => {
-                            storage_manager.primitive_stack_arg(sym, arg_offset);
+                            X86_64Assembler::mov_reg64_base32(
+                                buf,
+                                X86_64GeneralReg::R10,
+                                arg_offset,
+                            );
                             arg_offset += 8;
+                            X86_64GeneralReg::R10
                         }
-                    }
-                }
-                _ if layout_interner.stack_size(*layout) == 0 => {}
-                x => {
-                    todo!("Loading args with layout {:?}", x);
+                    };
+                    let base_offset = storage_manager.claim_stack_area(sym, size);
+                    Self::copy_from_pointer(buf, base_offset, ptr_reg, size);
                 }
             }
+            slot += 1;
         }
     }
 
@@ -949,63 +974,86 @@ impl CallConv<X86_64GeneralReg, X86_64FloatReg, X86_64Assembler> for X86_64Windo
         ret_layout: &InLayout<'a>,
     ) {
         let mut tmp_stack_offset = Self::SHADOW_SPACE_SIZE as i32;
+        let mut slot = 0;
+
         if Self::returns_via_arg_pointer(layout_interner, ret_layout) {
             // Save space on the stack for the arg we will return.
-            storage_manager.claim_stack_area(dst, layout_interner.stack_size(*ret_layout));
-            todo!("claim first parama reg for the address");
+            let base_offset =
+                storage_manager.claim_stack_area(dst, layout_interner.stack_size(*ret_layout));
+            // Set the first reg to the address base + offset.
+            X86_64Assembler::add_reg64_reg64_imm32(
+                buf,
+                Self::GENERAL_PARAM_REGS[0],
+                X86_64GeneralReg::RBP,
+                base_offset,
+            );
+            slot += 1;
         }
 
-        let mut general_registers_used = 0;
-        let mut float_registers_used = 0;
-
         for (sym, layout) in args.iter().zip(arg_layouts.iter()) {
+            let size = layout_interner.stack_size(*layout);
             match layout_interner.get(*layout).repr {
-                single_register_integers!() => {
-                    match Self::GENERAL_PARAM_REGS.get(general_registers_used) {
-                        Some(reg) => {
-                            storage_manager.load_to_specified_general_reg(buf, sym, *reg);
-                            general_registers_used += 1;
-                        }
-                        None => {
-                            // Copy to stack using return reg as buffer.
-                            let tmp = Self::GENERAL_RETURN_REGS[0];
+                _ if size == 0 => continue,
+                single_register_integers!() => match Self::GENERAL_PARAM_REGS.get(slot) {
+                    Some(reg) => {
+                        storage_manager.load_to_specified_general_reg(buf, sym, *reg);
+                    }
+                    None => {
+                        // Copy to stack using return reg as buffer.
+                        let tmp = Self::GENERAL_RETURN_REGS[0];
 
-                            storage_manager.load_to_specified_general_reg(buf, sym, tmp);
-                            X86_64Assembler::mov_stack32_reg64(buf, tmp_stack_offset, tmp);
+                        storage_manager.load_to_specified_general_reg(buf, sym, tmp);
+                        X86_64Assembler::mov_stack32_reg64(buf, tmp_stack_offset, tmp);
 
-                            tmp_stack_offset += 8;
-                        }
+                        tmp_stack_offset += 8;
                     }
-                }
-                single_register_floats!() => {
-                    match Self::FLOAT_PARAM_REGS.get(float_registers_used) {
-                        Some(reg) => {
-                            storage_manager.load_to_specified_float_reg(buf, sym, *reg);
-                            float_registers_used += 1;
-                        }
-                        None => {
-                            // Copy to stack using return reg as buffer.
-                            let tmp = Self::FLOAT_RETURN_REGS[0];
+                },
+                single_register_floats!() => match Self::FLOAT_PARAM_REGS.get(slot) {
+                    Some(reg) => {
+                        storage_manager.load_to_specified_float_reg(buf, sym, *reg);
+                    }
+                    None => {
+                        // Copy to stack using return reg as buffer.
+                        let tmp = Self::FLOAT_RETURN_REGS[0];
 
-                            storage_manager.load_to_specified_float_reg(buf, sym, tmp);
-                            X86_64Assembler::mov_stack32_freg64(buf, tmp_stack_offset, tmp);
+                        storage_manager.load_to_specified_float_reg(buf, sym, tmp);
+                        X86_64Assembler::mov_stack32_freg64(buf, tmp_stack_offset, tmp);
 
-                            tmp_stack_offset += 8;
-                        }
+                        tmp_stack_offset += 8;
+                    }
+                },
+                _ => {
+                    // Small aggregates are passed by value, everything else as a pointer to a copy.
+                    // The copy we pass is the symbol's own stack area: callees never write to it.
+                    let (base_offset, _) = storage_manager.stack_offset_and_size(sym);
+                    let reg = match Self::GENERAL_PARAM_REGS.get(slot) {
+                        Some(reg) => *reg,
+                        None => Self::GENERAL_RETURN_REGS[0],
+                    };
+                    if Self::passes_by_value(size) {
+                        X86_64Assembler::mov_reg64_base32(buf, reg, base_offset);
+                    } else {
+                        X86_64Assembler::add_reg64_reg64_imm32(
+                            buf,
+                            reg,
+                            X86_64GeneralReg::RBP,
+                            base_offset,
+                        );
+                    }
+                    if slot >= Self::GENERAL_PARAM_REGS.len() {
+                        X86_64Assembler::mov_stack32_reg64(buf, tmp_stack_offset, reg);
+                        tmp_stack_offset += 8;
                     }
-                }
-                _ if layout_interner.stack_size(*layout) == 0 => {}
-                x => {
-                    todo!("calling with arg type, {:?}", x);
                 }
             }
+            slot += 1;
         }
         storage_manager.update_fn_call_stack_size(tmp_stack_offset as u32);
     }
 
     fn return_complex_symbol<'a>(
-        _buf: &mut Vec<'a, u8>,
-        _storage_manager: &mut StorageManager<
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut StorageManager<
             'a,
             '_,
             X86_64GeneralReg,
@@ -1013,16 +1061,36 @@ impl CallConv<X86_64GeneralReg, X86_64FloatReg, X86_64Assembler> for X86_64Windo
             X86_64Assembler,
             X86_64WindowsFastcall,
         >,
-        _layout_interner: &mut STLayoutInterner<'a>,
-        _sym: &Symbol,
-        _layout: &InLayout<'a>,
+        layout_interner: &mut STLayoutInterner<'a>,
+        sym: &Symbol,
+        layout: &InLayout<'a>,
     ) {
-        todo!("Returning complex symbols for X86_64");
+        match layout_interner.get(*layout).repr {
+            single_register_layouts!() => {
+                internal_error!("single register layouts are not complex symbols");
+            }
+            _ if layout_interner.stack_size(*layout) == 0 => {}
+            _ if !Self::returns_via_arg_pointer(layout_interner, layout) => {
+                let (base_offset, _) = storage_manager.stack_offset_and_size(sym);
+                debug_assert_eq!(base_offset % 8, 0);
+                X86_64Assembler::mov_reg64_base32(buf, Self::GENERAL_RETURN_REGS[0], base_offset);
+            }
+            _ => {
+                // This is a large type returned via the arg pointer.
+                storage_manager.copy_symbol_to_arg_pointer(buf, sym, layout);
+                // Also set the return reg to the arg pointer.
+                storage_manager.load_to_specified_general_reg(
+                    buf,
+                    &Symbol::RET_POINTER,
+                    Self::GENERAL_RETURN_REGS[0],
+                );
+            }
+        }
     }
 
     fn load_returned_complex_symbol<'a>(
-        _buf: &mut Vec<'a, u8>,
-        _storage_manager: &mut StorageManager<
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut StorageManager<
             'a,
             '_,
             X86_64GeneralReg,
@@ -1030,11 +1098,28 @@ impl CallConv<X86_64GeneralReg, X86_64FloatReg, X86_64Assembler> for X86_64Windo
             X86_64Assembler,
             X86_64WindowsFastcall,
         >,
-        _layout_interner: &mut STLayoutInterner<'a>,
-        _sym: &Symbol,
-        _layout: &InLayout<'a>,
+        layout_interner: &mut STLayoutInterner<'a>,
+        sym: &Symbol,
+        layout: &InLayout<'a>,
     ) {
-        todo!("Loading returned complex symbols for X86_64");
+        match layout_interner.get(*layout).repr {
+            single_register_layouts!() => {
+                internal_error!("single register layouts are not complex symbols");
+            }
+            _ if layout_interner.stack_size(*layout) == 0 => {
+                storage_manager.no_data(sym);
+            }
+            _ if !Self::returns_via_arg_pointer(layout_interner, layout) => {
+                let size = layout_interner.stack_size(*layout);
+                let offset = storage_manager.claim_stack_area(sym, size);
+                X86_64Assembler::mov_base32_reg64(buf, offset, Self::GENERAL_RETURN_REGS[0]);
+            }
+            _ => {
+                // This should have been recieved via an arg pointer.
+                // That means the value is already loaded onto the stack area we allocated before the call.
+                // Nothing to do.
+            }
+        }
     }
 }
 
@@ -1047,6 +1132,46 @@ impl X86_64WindowsFastcall {
         // details here: https://docs.microsoft.com/en-us/cpp/build/x64-calling-convention?view=msvc-160#return-values
         interner.stack_size(*ret_layout) > 8
     }
+
+    /// Aggregates of exactly 1, 2, 4 or 8 bytes are passed in a register (or stack slot) directly.
+    /// Any other size is passed as a pointer to a copy.
+    fn passes_by_value(size: u32) -> bool {
+        matches!(size, 1 | 2 | 4 | 8)
+    }
+
+    /// Copies `size` bytes pointed to by `ptr_reg` to the stack at `base_offset`.
+    /// Uses R11 as a scratch register, so `ptr_reg` must not be R11.
+    fn copy_from_pointer(
+        buf: &mut Vec<'_, u8>,
+        base_offset: i32,
+        ptr_reg: X86_64GeneralReg,
+        size: u32,
+    ) {
+        debug_assert!(!matches!(ptr_reg, X86_64GeneralReg::R11));
+        let tmp = X86_64GeneralReg::R11;
+        let size = size as i32;
+        let mut copied = 0;
+
+        while size - copied >= 8 {
+            X86_64Assembler::mov_reg64_mem64_offset32(buf, tmp, ptr_reg, copied);
+            X86_64Assembler::mov_base32_reg64(buf, base_offset + copied, tmp);
+            copied += 8;
+        }
+        if size - copied >= 4 {
+            X86_64Assembler::mov_reg32_mem32_offset32(buf, tmp, ptr_reg, copied);
+            X86_64Assembler::mov_base32_reg32(buf, base_offset + copied, tmp);
+            copied += 4;
+        }
+        if size - copied >= 2 {
+            X86_64Assembler::mov_reg16_mem16_offset32(buf, tmp, ptr_reg, copied);
+            X86_64Assembler::mov_base32_reg16(buf, base_offset + copied, tmp);
+            copied += 2;
+        }
+        if size - copied >= 1 {
+            X86_64Assembler::mov_reg8_mem8_offset32(buf, tmp, ptr_reg, copied);
+            X86_64Assembler::mov_base32_reg8(buf, base_offset + copied, tmp);
+        }
+    }
 }
 
 #[inline(always)]
@@ -1252,6 +1377,21 @@ impl Assembler<X86_64GeneralReg, X86_64FloatReg> for X86_64Assembler {
         });
     }
 
+    #[inline(always)]
+    fn data_pointer(
+        buf: &mut Vec<'_, u8>,
+        relocs: &mut Vec<'_, Relocation>,
+        data: std::vec::Vec<u8>,
+        dst: X86_64GeneralReg,
+    ) {
+        lea_reg64(buf, dst);
+
+        relocs.push(Relocation::LocalData {
+            offset: buf.len() as u64 - 4,
+            data,
+        });
+    }
+
     #[inline(always)]
     fn imul_reg64_reg64_reg64(
         buf: &mut Vec<'_, u8>,
@@ -24,6 +24,7 @@ use roc_mono::layout::{
     TagIdIntType, UnionLayout,
 };
 use roc_mono::list_element_layout;
+use roc_mono::low_level::LowLevelImpl;
 
 mod generic64;
 mod object_builder;
@@ -70,6 +71,12 @@ pub enum Relocation {
         offset: u64,
         name: String,
     },
+    /// Loads the address of a function, rather than calling it.
+    /// Only AArch64 needs this; x86-64 loads it with a `lea` and a `LinkedFunction`.
+    FunctionPointer {
+        offset: u64,
+        name: String,
+    },
     LinkedData {
         offset: u64,
         name: String,
@@ -111,24 +118,77 @@ struct ListArgument<'a> {
 struct LastSeenMap<'a> {
     last_seen: MutMap<Symbol, *const Stmt<'a>>,
     join_map: MutMap<JoinPointId, &'a [Param<'a>]>,
+    position: u32,
+    uses: UsePositions<'a>,
+}
+
+/// Statements are numbered in the order build_stmt visits them.
+/// For every symbol, this records the positions it is used at,
+/// so the register allocator can tell how soon a value is needed again.
+#[derive(Debug, Default)]
+pub struct UsePositions<'a> {
+    stmt_positions: MutMap<*const Stmt<'a>, u32>,
+    symbol_uses: MutMap<Symbol, std::vec::Vec<u32>>,
+    call_positions: std::vec::Vec<u32>,
+}
+
+impl<'a> UsePositions<'a> {
+    /// The position of a statement from the original proc.
+    /// Statements generated while building (like expanded refcounting) don't have one.
+    pub fn stmt_position(&self, stmt: &Stmt<'a>) -> Option<u32> {
+        self.stmt_positions.get(&(stmt as *const _)).copied()
+    }
+
+    /// The first use of `sym` at or after `position`.
+    pub fn next_use(&self, sym: &Symbol, position: u32) -> Option<u32> {
+        let uses = self.symbol_uses.get(sym)?;
+        let index = uses.partition_point(|use_position| *use_position < position);
+
+        uses.get(index).copied()
+    }
+
+    /// Whether a call happens after `position`, but before the last use of `sym`.
+    pub fn live_across_call(&self, sym: &Symbol, position: u32) -> bool {
+        match self.symbol_uses.get(sym).and_then(|uses| uses.last()) {
+            Some(last_use) => {
+                let index = self
+                    .call_positions
+                    .partition_point(|call_position| *call_position <= position);
+
+                matches!(self.call_positions.get(index), Some(call_position) if call_position < last_use)
+            }
+            None => false,
+        }
+    }
+
+    fn add_use(&mut self, symbol: Symbol, position: u32) {
+        let uses = self.symbol_uses.entry(symbol).or_default();
+        if uses.last() != Some(&position) {
+            uses.push(position);
+        }
+    }
 }
 
 impl<'a> LastSeenMap<'a> {
     fn set_last_seen(&mut self, symbol: Symbol, stmt: &'a Stmt<'a>) {
         self.last_seen.insert(symbol, stmt);
+        self.uses.add_use(symbol, self.position);
     }
 
     /// scan_ast runs through the ast and fill the last seen map.
     /// This must iterate through the ast in the same way that build_stmt does. i.e. then before else.
-    fn scan_ast(root: &'a Stmt<'a>) -> MutMap<Symbol, *const Stmt<'a>> {
+    fn scan_ast(root: &'a Stmt<'a>) -> (MutMap<Symbol, *const Stmt<'a>>, UsePositions<'a>) {
         let mut this: Self = Default::default();
 
         this.scan_ast_help(root);
 
-        this.last_seen
+        (this.last_seen, this.uses)
     }
 
     fn scan_ast_help(&mut self, stmt: &'a Stmt<'a>) {
+        self.position += 1;
+        self.uses.stmt_positions.insert(stmt, self.position);
+
         match stmt {
             Stmt::Let(sym, expr, _, following) => {
                 self.set_last_seen(*sym, stmt);
@@ -206,6 +266,8 @@ impl<'a> LastSeenMap<'a> {
                 let sym = modify.get_symbol();
 
                 self.set_last_seen(sym, stmt);
+                // Refcounting is expanded into a call to a helper proc.
+                self.uses.call_positions.push(self.position);
                 self.scan_ast_help(following);
             }
             Stmt::Join {
@@ -219,7 +281,18 @@ impl<'a> LastSeenMap<'a> {
                 self.join_map.insert(JoinPointId(*sym), parameters);
                 self.scan_ast_help(remainder);
 
-                for (symbol, symbol_stmt) in Self::scan_ast(continuation) {
+                // The continuation gets its own last seen map, but keeps numbering positions
+                // where the remainder stopped, because it is built after the remainder.
+                let mut continuation_scan = Self {
+                    position: self.position,
+                    uses: std::mem::take(&mut self.uses),
+                    ..Default::default()
+                };
+                continuation_scan.scan_ast_help(continuation);
+                self.position = continuation_scan.position;
+                self.uses = continuation_scan.uses;
+
+                for (symbol, symbol_stmt) in continuation_scan.last_seen {
                     match self.last_seen.entry(symbol) {
                         Entry::Occupied(mut occupied) => {
                             // lives for the joinpoint
@@ -269,10 +342,10 @@ impl<'a> LastSeenMap<'a> {
         }
 
         match call_type {
-            CallType::ByName { .. } => {}
+            CallType::ByName { .. } | CallType::HigherOrder { .. } | CallType::Foreign { .. } => {
+                self.uses.call_positions.push(self.position);
+            }
             CallType::LowLevel { .. } => {}
-            CallType::HigherOrder { .. } => {}
-            CallType::Foreign { .. } => {}
         }
     }
 }
@@ -501,6 +574,7 @@ trait Backend<'a> {
         stmt: &Stmt<'a>,
         ret_layout: &InLayout<'a>,
     ) {
+        self.enter_stmt(stmt);
         match stmt {
             Stmt::Let(sym, expr, layout, following) => {
                 self.build_expr(sym, expr, layout);
@@ -798,7 +872,26 @@ trait Backend<'a> {
                 ..
             } => {
                 self.load_literal_symbols(arguments);
-                self.tag(sym, arguments, tag_layout, *tag_id);
+                self.tag(sym, arguments, tag_layout, *tag_id, None);
+            }
+            Expr::Reuse {
+                symbol: reused,
+                tag_layout,
+                tag_id,
+                arguments,
+                ..
+            } => {
+                self.load_literal_symbols(arguments);
+                self.tag(sym, arguments, tag_layout, *tag_id, Some(*reused));
+            }
+            Expr::Reset { symbol, .. } => {
+                self.build_reset(sym, *symbol, *layout, false);
+            }
+            Expr::ResetRef { symbol, .. } => {
+                self.build_reset(sym, *symbol, *layout, true);
+            }
+            Expr::NullPointer => {
+                self.load_literal(sym, &Layout::U64, &Literal::Int(0i128.to_ne_bytes()));
             }
             Expr::ExprBox { symbol: value } => {
                 let element_layout = match self.interner().get(*layout).repr {
@@ -831,6 +924,9 @@ trait Backend<'a> {
     ) {
         // Now that the arguments are needed, load them if they are literals.
         self.load_literal_symbols(args);
+        if let LowLevelImpl::ZigBuiltin(intrinsic) = LowLevelImpl::of(*lowlevel) {
+            return self.build_fn_call(sym, intrinsic.to_string(), args, arg_layouts, ret_layout);
+        }
         match lowlevel {
             LowLevel::NumAbs => {
                 debug_assert_eq!(
@@ -1025,20 +1121,25 @@ trait Backend<'a> {
                     internal_error!("bitwise xor on a non-integer")
                 }
             }
-            LowLevel::And => {
-                if let LayoutRepr::Builtin(Builtin::Bool) = self.interner().get(*ret_layout).repr {
+            LowLevel::And => match self.interner().get(*ret_layout).repr {
+                LayoutRepr::Builtin(Builtin::Bool) => {
                     self.build_int_bitwise_and(sym, &args[0], &args[1], IntWidth::U8)
-                } else {
-                    internal_error!("bitwise and on a non-integer")
                 }
-            }
-            LowLevel::Or => {
-                if let LayoutRepr::Builtin(Builtin::Bool) = self.interner().get(*ret_layout).repr {
+                // refcounting helpers use this to mask tag ids out of pointers
+                LayoutRepr::Builtin(Builtin::Int(int_width)) => {
+                    self.build_int_bitwise_and(sym, &args[0], &args[1], int_width)
+                }
+                _ => internal_error!("bitwise and on a non-integer"),
+            },
+            LowLevel::Or => match self.interner().get(*ret_layout).repr {
+                LayoutRepr::Builtin(Builtin::Bool) => {
                     self.build_int_bitwise_or(sym, &args[0], &args[1], IntWidth::U8)
-                } else {
-                    internal_error!("bitwise or on a non-integer")
                 }
-            }
+                LayoutRepr::Builtin(Builtin::Int(int_width)) => {
+                    self.build_int_bitwise_or(sym, &args[0], &args[1], int_width)
+                }
+                _ => internal_error!("bitwise or on a non-integer"),
+            },
             LowLevel::NumShiftLeftBy => {
                 if let LayoutRepr::Builtin(Builtin::Int(int_width)) =
                     self.interner().get(*ret_layout).repr
@@ -1325,83 +1426,6 @@ trait Backend<'a> {
                 );
                 self.build_list_prepend(sym, args, arg_layouts, ret_layout)
             }
-            LowLevel::StrConcat => self.build_fn_call(
-                sym,
-                bitcode::STR_CONCAT.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
-            LowLevel::StrJoinWith => self.build_fn_call(
-                sym,
-                bitcode::STR_JOIN_WITH.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
-            LowLevel::StrSplit => self.build_fn_call(
-                sym,
-                bitcode::STR_SPLIT.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
-            LowLevel::StrStartsWith => self.build_fn_call(
-                sym,
-                bitcode::STR_STARTS_WITH.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
-            LowLevel::StrStartsWithScalar => self.build_fn_call(
-                sym,
-                bitcode::STR_STARTS_WITH_SCALAR.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
-            LowLevel::StrAppendScalar => self.build_fn_call(
-                sym,
-                bitcode::STR_APPEND_SCALAR.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
-            LowLevel::StrEndsWith => self.build_fn_call(
-                sym,
-                bitcode::STR_ENDS_WITH.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
-            LowLevel::StrCountGraphemes => self.build_fn_call(
-                sym,
-                bitcode::STR_COUNT_GRAPEHEME_CLUSTERS.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
-            LowLevel::StrSubstringUnsafe => self.build_fn_call(
-                sym,
-                bitcode::STR_SUBSTRING_UNSAFE.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
-            LowLevel::StrToUtf8 => self.build_fn_call(
-                sym,
-                bitcode::STR_TO_UTF8.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
-            LowLevel::StrCountUtf8Bytes => self.build_fn_call(
-                sym,
-                bitcode::STR_COUNT_UTF8_BYTES.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
             LowLevel::StrFromUtf8Range => self.build_fn_call(
                 sym,
                 bitcode::STR_FROM_UTF8_RANGE.to_string(),
@@ -1409,76 +1433,6 @@ trait Backend<'a> {
                 arg_layouts,
                 ret_layout,
             ),
-            //            LowLevel::StrToUtf8 => self.build_fn_call(
-            //                sym,
-            //                bitcode::STR_TO_UTF8.to_string(),
-            //                args,
-            //                arg_layouts,
-            //                ret_layout,
-            //            ),
-            LowLevel::StrRepeat => self.build_fn_call(
-                sym,
-                bitcode::STR_REPEAT.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
-            LowLevel::StrTrim => self.build_fn_call(
-                sym,
-                bitcode::STR_TRIM.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
-            LowLevel::StrTrimLeft => self.build_fn_call(
-                sym,
-                bitcode::STR_TRIM_LEFT.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
-            LowLevel::StrTrimRight => self.build_fn_call(
-                sym,
-                bitcode::STR_TRIM_RIGHT.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
-            LowLevel::StrReserve => self.build_fn_call(
-                sym,
-                bitcode::STR_RESERVE.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
-            LowLevel::StrWithCapacity => self.build_fn_call(
-                sym,
-                bitcode::STR_WITH_CAPACITY.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
-            LowLevel::StrToScalars => self.build_fn_call(
-                sym,
-                bitcode::STR_TO_SCALARS.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
-            LowLevel::StrGetUnsafe => self.build_fn_call(
-                sym,
-                bitcode::STR_GET_UNSAFE.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
-            LowLevel::StrGetScalarUnsafe => self.build_fn_call(
-                sym,
-                bitcode::STR_GET_SCALAR_UNSAFE.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
             LowLevel::StrToNum => {
                 let number_layout = match self.interner().get(*ret_layout).repr {
                     LayoutRepr::Struct { field_layouts, .. } => field_layouts[0], // TODO: why is it sometimes a struct?
@@ -1513,41 +1467,6 @@ trait Backend<'a> {
 
                 self.build_ptr_write(*sym, args[0], args[1], element_layout);
             }
-            LowLevel::RefCountDecRcPtr => self.build_fn_call(
-                sym,
-                bitcode::UTILS_DECREF_RC_PTR.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
-            LowLevel::RefCountIncRcPtr => self.build_fn_call(
-                sym,
-                bitcode::UTILS_INCREF_RC_PTR.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
-            LowLevel::RefCountDecDataPtr => self.build_fn_call(
-                sym,
-                bitcode::UTILS_DECREF_DATA_PTR.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
-            LowLevel::RefCountIncDataPtr => self.build_fn_call(
-                sym,
-                bitcode::UTILS_INCREF_DATA_PTR.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
-            LowLevel::RefCountIsUnique => self.build_fn_call(
-                sym,
-                bitcode::UTILS_IS_UNIQUE.to_string(),
-                args,
-                arg_layouts,
-                ret_layout,
-            ),
             LowLevel::NumToStr => {
                 let arg_layout = arg_layouts[0];
                 let intrinsic = match self.interner().get(arg_layout).repr {
@@ -2203,14 +2122,50 @@ trait Backend<'a> {
     fn get_tag_id(&mut self, sym: &Symbol, structure: &Symbol, union_layout: &UnionLayout<'a>);
 
     /// tag sets the tag for a union.
+    /// `reuse` is a reset token from an earlier `Reset`; it may be null at runtime.
     fn tag(
         &mut self,
         sym: &Symbol,
         args: &'a [Symbol],
         tag_layout: &UnionLayout<'a>,
         tag_id: TagIdIntType,
+        reuse: Option<Symbol>,
     );
 
+    /// build_reset calls the reset (or resetref) helper for `argument`, which gives back
+    /// its allocation if it was unique, and null otherwise.
+    fn build_reset(&mut self, sym: &Symbol, argument: Symbol, layout: InLayout<'a>, is_ref: bool) {
+        let argument_layout = *self.layout_map().get(&argument).unwrap();
+
+        let (reset_expr, new_specializations) = {
+            let (module_id, layout_interner, interns, rc_proc_gen, _) =
+                self.module_interns_helpers_mut();
+            let ident_ids = interns.all_ident_ids.get_mut(&module_id).unwrap();
+
+            if is_ref {
+                rc_proc_gen.call_resetref_refcount(
+                    ident_ids,
+                    layout_interner,
+                    argument_layout,
+                    argument,
+                )
+            } else {
+                rc_proc_gen.call_reset_refcount(
+                    ident_ids,
+                    layout_interner,
+                    argument_layout,
+                    argument,
+                )
+            }
+        };
+
+        for spec in new_specializations.into_iter() {
+            self.helper_proc_symbols_mut().push(spec);
+        }
+
+        self.build_expr(sym, &reset_expr, &layout);
+    }
+
     /// load a value from a pointer
     fn expr_unbox(&mut self, sym: Symbol, ptr: Symbol, element_layout: InLayout<'a>);
 
@@ -2273,9 +2228,17 @@ trait Backend<'a> {
     /// set_free_map sets the free map to the given map.
     fn set_free_map(&mut self, map: MutMap<*const Stmt<'a>, Vec<'a, Symbol>>);
 
+    /// set_use_positions hands the use positions found by scan_ast to the register allocator.
+    fn set_use_positions(&mut self, use_positions: UsePositions<'a>);
+
+    /// enter_stmt tells the register allocator which statement is being built.
+    fn enter_stmt(&mut self, stmt: &Stmt<'a>);
+
     /// scan_ast runs through the ast and fill the last seen map.
     /// This must iterate through the ast in the same way that build_stmt does. i.e. then before else.
     fn scan_ast(&mut self, stmt: &'a Stmt<'a>) {
-        *self.last_seen_map() = LastSeenMap::scan_ast(stmt);
+        let (last_seen, use_positions) = LastSeenMap::scan_ast(stmt);
+        *self.last_seen_map() = last_seen;
+        self.set_use_positions(use_positions);
     }
 }
@@ -1,5 +1,5 @@
 use crate::generic64::{aarch64, new_backend_64bit, x86_64};
-use crate::{Backend, Env, Relocation};
+use crate::{AssemblyBackendMode, Backend, Env, Relocation};
 use bumpalo::collections::Vec;
 use object::write::{self, SectionId, SymbolId};
 use object::write::{Object, StandardSection, StandardSegment, Symbol, SymbolSection};
@@ -11,6 +11,7 @@ use roc_collections::all::MutMap;
 use roc_error_macros::internal_error;
 use roc_module::symbol;
 use roc_module::symbol::Interns;
+use roc_mono::host_exposed::{host_exposed_procs, HostExposedProc};
 use roc_mono::ir::{Call, CallSpecId, Expr, UpdateModeId};
 use roc_mono::ir::{Proc, ProcLayout, Stmt};
 use roc_mono::layout::{LambdaName, Layout, LayoutIds, LayoutInterner, STLayoutInterner};
@@ -42,16 +43,7 @@ pub fn build_module<'a, 'r>(
                 x86_64::X86_64Assembler,
                 x86_64::X86_64SystemV,
             >(env, TargetInfo::default_x86_64(), interns, layout_interner);
-            // Newer version of `ld` require `.note.GNU-stack` for security reasons.
-            // It specifies that we will not execute code stored on the stack.
-            let mut object =
-                Object::new(BinaryFormat::Elf, Architecture::X86_64, Endianness::Little);
-            object.add_section(
-                vec![],
-                b".note.GNU-stack".to_vec(),
-                SectionKind::Elf(object::elf::SHT_PROGBITS),
-            );
-            build_object(procedures, backend, object)
+            build_object(procedures, backend, elf_object(Architecture::X86_64))
         }
         Triple {
             architecture: TargetArch::X86_64,
@@ -74,6 +66,23 @@ pub fn build_module<'a, 'r>(
                 ),
             )
         }
+        Triple {
+            architecture: TargetArch::X86_64,
+            binary_format: TargetBF::Coff,
+            ..
+        } if cfg!(feature = "target-x86_64") => {
+            let backend = new_backend_64bit::<
+                x86_64::X86_64GeneralReg,
+                x86_64::X86_64FloatReg,
+                x86_64::X86_64Assembler,
+                x86_64::X86_64WindowsFastcall,
+            >(env, TargetInfo::default_x86_64(), interns, layout_interner);
+            build_object(
+                procedures,
+                backend,
+                Object::new(BinaryFormat::Coff, Architecture::X86_64, Endianness::Little),
+            )
+        }
         Triple {
             architecture: TargetArch::Aarch64(_),
             binary_format: TargetBF::Elf,
@@ -86,11 +95,7 @@ pub fn build_module<'a, 'r>(
                     aarch64::AArch64Assembler,
                     aarch64::AArch64Call,
                 >(env, TargetInfo::default_aarch64(), interns, layout_interner);
-            build_object(
-                procedures,
-                backend,
-                Object::new(BinaryFormat::Elf, Architecture::Aarch64, Endianness::Little),
-            )
+            build_object(procedures, backend, elf_object(Architecture::Aarch64))
         }
         Triple {
             architecture: TargetArch::Aarch64(_),
@@ -118,6 +123,39 @@ pub fn build_module<'a, 'r>(
     }
 }
 
+fn elf_object<'a>(architecture: Architecture) -> Object<'a> {
+    let mut object = Object::new(BinaryFormat::Elf, architecture, Endianness::Little);
+
+    // Newer version of `ld` require `.note.GNU-stack` for security reasons.
+    // It specifies that we will not execute code stored on the stack.
+    object.add_section(
+        vec![],
+        b".note.GNU-stack".to_vec(),
+        SectionKind::Elf(object::elf::SHT_PROGBITS),
+    );
+
+    object
+}
+
+/// The scope of procs that only Roc code calls, so that the host can't accidentally link against them.
+/// ELF objects keep them local, which leaves just the host-exposed entry points and the wrappers
+/// around libc in the global symbol table. Mach-O and COFF hide them from everything outside the link.
+fn internal_scope(output: &Object) -> SymbolScope {
+    match output.format() {
+        BinaryFormat::Elf => SymbolScope::Compilation,
+        _ => SymbolScope::Linkage,
+    }
+}
+
+/// A section of its own for each proc, so linkers can garbage collect the ones the host never reaches.
+fn proc_section(output: &mut Object, sym: symbol::Symbol) -> SectionId {
+    output.add_section(
+        output.segment_name(StandardSegment::Text).to_vec(),
+        format!(".text.{:x}", sym.as_u64()).as_bytes().to_vec(),
+        SectionKind::Text,
+    )
+}
+
 fn generate_wrapper<'a, B: Backend<'a>>(
     backend: &mut B,
     output: &mut Object,
@@ -153,14 +191,7 @@ fn generate_wrapper<'a, B: Backend<'a>>(
     };
     output.add_symbol(symbol);
     if let Some(sym_id) = output.symbol_id(name) {
-        let reloc = write::Relocation {
-            offset: offset + proc_offset,
-            size: 32,
-            kind: RelocationKind::PltRelative,
-            encoding: RelocationEncoding::X86Branch,
-            symbol: sym_id,
-            addend: -4,
-        };
+        let reloc = branch_relocation(output, offset + proc_offset, sym_id);
 
         match output.add_relocation(text_section, reloc) {
             Ok(obj) => obj,
@@ -171,12 +202,85 @@ fn generate_wrapper<'a, B: Backend<'a>>(
     }
 }
 
+/// A relocation for the call or jump to `symbol` at `offset`.
+/// On x86-64 `offset` points at the rel32 operand; on AArch64 it points at the `BL`/`B` instruction.
+/// COFF has no PLT, so there the branch is a plain pc-relative relocation.
+fn branch_relocation(output: &Object, offset: u64, symbol: SymbolId) -> write::Relocation {
+    match output.architecture() {
+        Architecture::Aarch64 => write::Relocation {
+            offset,
+            size: 26,
+            kind: RelocationKind::Relative,
+            encoding: RelocationEncoding::AArch64Call,
+            symbol,
+            addend: 0,
+        },
+        _ if output.format() == BinaryFormat::Coff => write::Relocation {
+            offset,
+            size: 32,
+            kind: RelocationKind::Relative,
+            encoding: RelocationEncoding::Generic,
+            symbol,
+            addend: -4,
+        },
+        _ => write::Relocation {
+            offset,
+            size: 32,
+            kind: RelocationKind::PltRelative,
+            encoding: RelocationEncoding::X86Branch,
+            symbol,
+            addend: -4,
+        },
+    }
+}
+
+/// The relocations for an AArch64 `ADRP` at `offset` and the `ADD` right after it, which
+/// together load the address of `symbol`: the first one fills in the 4KB page, and the second
+/// one the offset within that page.
+fn page_relocations(
+    output: &Object,
+    offset: u64,
+    symbol: SymbolId,
+) -> (write::Relocation, write::Relocation) {
+    let (page, page_offset) = match output.format() {
+        BinaryFormat::MachO => (
+            RelocationKind::MachO {
+                value: object::macho::ARM64_RELOC_PAGE21,
+                relative: true,
+            },
+            RelocationKind::MachO {
+                value: object::macho::ARM64_RELOC_PAGEOFF12,
+                relative: false,
+            },
+        ),
+        _ => (
+            RelocationKind::Elf(object::elf::R_AARCH64_ADR_PREL_PG_HI21),
+            RelocationKind::Elf(object::elf::R_AARCH64_ADD_ABS_LO12_NC),
+        ),
+    };
+
+    let relocation = |offset, kind| write::Relocation {
+        offset,
+        size: 32,
+        kind,
+        encoding: RelocationEncoding::Generic,
+        symbol,
+        addend: 0,
+    };
+
+    (
+        relocation(offset, page),
+        relocation(offset + 4, page_offset),
+    )
+}
+
 fn build_object<'a, B: Backend<'a>>(
     procedures: MutMap<(symbol::Symbol, ProcLayout<'a>), Proc<'a>>,
     mut backend: B,
     mut output: Object<'a>,
 ) -> Object<'a> {
-    let data_section = output.section_id(StandardSection::Data);
+    // Local data is only ever constants, like floats and string literals.
+    let data_section = output.section_id(StandardSection::ReadOnlyData);
 
     let arena = backend.env().arena;
 
@@ -237,6 +341,13 @@ fn build_object<'a, B: Backend<'a>>(
     let mut layout_ids = LayoutIds::default();
     let mut procs = Vec::with_capacity_in(procedures.len(), arena);
 
+    // Generate the procs in an order that doesn't depend on how they were numbered,
+    // so that the same program always produces the same object file
+    let mut procedures: std::vec::Vec<_> = procedures.into_iter().collect();
+    procedures.sort_by_cached_key(|((symbol, layout), _)| {
+        roc_mono::ir::proc_sort_key(backend.interns(), backend.interner(), *symbol, layout)
+    });
+
     // Names and linker data for user procedures
     for ((sym, layout), proc) in procedures {
         debug_assert_eq!(sym, proc.name.name());
@@ -277,6 +388,29 @@ fn build_object<'a, B: Backend<'a>>(
             );
         }
 
+        // Callers and sizes for the closures the host gets
+        if let AssemblyBackendMode::Binary = backend.env().mode {
+            let (module_id, interner, interns, _, _) = backend.module_interns_helpers_mut();
+            let host_exposed = host_exposed_procs(arena, interner, interns, module_id, &proc);
+
+            for HostExposedProc {
+                name,
+                proc_layout,
+                proc,
+            } in host_exposed
+            {
+                build_proc_symbol(
+                    &mut output,
+                    &mut layout_ids,
+                    &mut procs,
+                    &mut backend,
+                    proc_layout,
+                    proc,
+                    Exposed::HostExposed(name),
+                );
+            }
+        }
+
         build_proc_symbol(
             &mut output,
             &mut layout_ids,
@@ -357,18 +491,14 @@ fn build_object<'a, B: Backend<'a>>(
             }
         } else {
             // The symbol isn't defined yet and will just be used by other rc procs.
-            let section_id = output.add_section(
-                output.segment_name(StandardSegment::Text).to_vec(),
-                format!(".text.{:x}", sym.as_u64()).as_bytes().to_vec(),
-                SectionKind::Text,
-            );
+            let section_id = proc_section(&mut output, sym);
 
             let rc_symbol = Symbol {
                 name: fn_name.as_bytes().to_vec(),
                 value: 0,
                 size: 0,
                 kind: SymbolKind::Text,
-                scope: SymbolScope::Linkage,
+                scope: internal_scope(&output),
                 weak: false,
                 section: SymbolSection::Section(section_id),
                 flags: SymbolFlags::None,
@@ -532,6 +662,8 @@ fn build_exposed_generic_proc<'a, B: Backend<'a>>(backend: &mut B, proc: &Proc<'
 enum Exposed {
     ExposedGeneric,
     Exposed,
+    /// Exported under the given name, see [roc_mono::host_exposed]
+    HostExposed(String),
     NotExposed,
 }
 
@@ -546,19 +678,16 @@ fn build_proc_symbol<'a, B: Backend<'a>>(
 ) {
     let sym = proc.name.name();
 
-    let section_id = output.add_section(
-        output.segment_name(StandardSegment::Text).to_vec(),
-        format!(".text.{:x}", sym.as_u64()).as_bytes().to_vec(),
-        SectionKind::Text,
-    );
+    let section_id = proc_section(output, sym);
 
-    let fn_name = match exposed {
+    let fn_name = match &exposed {
         Exposed::ExposedGeneric => layout_ids
             .get_toplevel(sym, &layout)
             .to_exposed_generic_symbol_string(sym, backend.interns()),
         Exposed::Exposed => layout_ids
             .get_toplevel(sym, &layout)
             .to_exposed_symbol_string(sym, backend.interns()),
+        Exposed::HostExposed(name) => name.clone(),
         Exposed::NotExposed => backend.function_symbol_to_string(
             sym,
             layout.arguments.iter().copied(),
@@ -572,11 +701,11 @@ fn build_proc_symbol<'a, B: Backend<'a>>(
         value: 0,
         size: 0,
         kind: SymbolKind::Text,
-        // TODO: Depending on whether we are building a static or dynamic lib, this should change.
-        // We should use Dynamic -> anyone, Linkage -> static link, Compilation -> this module only.
         scope: match exposed {
-            Exposed::ExposedGeneric | Exposed::Exposed => SymbolScope::Dynamic,
-            Exposed::NotExposed => SymbolScope::Linkage,
+            Exposed::ExposedGeneric | Exposed::Exposed | Exposed::HostExposed(_) => {
+                SymbolScope::Dynamic
+            }
+            Exposed::NotExposed => internal_scope(output),
         },
         weak: false,
         section: SymbolSection::Section(section_id),
@@ -618,14 +747,25 @@ fn build_proc<'a, B: Backend<'a>>(
                 };
                 local_data_index += 1;
                 let data_id = output.add_symbol(data_symbol);
-                output.add_symbol_data(data_id, data_section, data, 4);
-                write::Relocation {
-                    offset: offset + proc_offset,
-                    size: 32,
-                    kind: RelocationKind::Relative,
-                    encoding: RelocationEncoding::Generic,
-                    symbol: data_id,
-                    addend: -4,
+                // Aligned for the refcount in front of string literals
+                output.add_symbol_data(data_id, data_section, data, 8);
+
+                match output.architecture() {
+                    Architecture::Aarch64 => {
+                        let (page, page_offset) =
+                            page_relocations(output, offset + proc_offset, data_id);
+                        relocations.push((section_id, page));
+
+                        page_offset
+                    }
+                    _ => write::Relocation {
+                        offset: offset + proc_offset,
+                        size: 32,
+                        kind: RelocationKind::Relative,
+                        encoding: RelocationEncoding::Generic,
+                        symbol: data_id,
+                        addend: -4,
+                    },
                 }
             }
             Relocation::LinkedData { offset, name } => {
@@ -642,15 +782,18 @@ fn build_proc<'a, B: Backend<'a>>(
                     internal_error!("failed to find data symbol for {:?}", name);
                 }
             }
-            Relocation::LinkedFunction { offset, name } => {
+            Relocation::LinkedFunction { offset, name }
+            | Relocation::FunctionPointer { offset, name } => {
                 // If the symbol is an undefined roc function, we need to add it here.
+                // It's defined by the host or the builtins, so it mustn't be hidden: a hidden
+                // undefined symbol can only resolve to a definition in this same object.
                 if output.symbol_id(name.as_bytes()).is_none() && name.starts_with("roc_") {
                     let builtin_symbol = Symbol {
                         name: name.as_bytes().to_vec(),
                         value: 0,
                         size: 0,
                         kind: SymbolKind::Text,
-                        scope: SymbolScope::Linkage,
+                        scope: SymbolScope::Unknown,
                         weak: false,
                         section: SymbolSection::Undefined,
                         flags: SymbolFlags::None,
@@ -662,18 +805,14 @@ fn build_proc<'a, B: Backend<'a>>(
                 if output.symbol_id(name.as_bytes()).is_none() {
                     for (sym, rc_name) in rc_proc_names.iter() {
                         if name == rc_name {
-                            let section_id = output.add_section(
-                                output.segment_name(StandardSegment::Text).to_vec(),
-                                format!(".text.{:x}", sym.as_u64()).as_bytes().to_vec(),
-                                SectionKind::Text,
-                            );
+                            let section_id = proc_section(output, *sym);
 
                             let rc_symbol = Symbol {
                                 name: name.as_bytes().to_vec(),
                                 value: 0,
                                 size: 0,
                                 kind: SymbolKind::Text,
-                                scope: SymbolScope::Linkage,
+                                scope: internal_scope(output),
                                 weak: false,
                                 section: SymbolSection::Section(section_id),
                                 flags: SymbolFlags::None,
@@ -684,13 +823,15 @@ fn build_proc<'a, B: Backend<'a>>(
                 }
 
                 if let Some(sym_id) = output.symbol_id(name.as_bytes()) {
-                    write::Relocation {
-                        offset: offset + proc_offset,
-                        size: 32,
-                        kind: RelocationKind::PltRelative,
-                        encoding: RelocationEncoding::X86Branch,
-                        symbol: sym_id,
-                        addend: -4,
+                    match reloc {
+                        Relocation::FunctionPointer { .. } => {
+                            let (page, page_offset) =
+                                page_relocations(output, offset + proc_offset, sym_id);
+                            relocations.push((section_id, page));
+
+                            page_offset
+                        }
+                        _ => branch_relocation(output, offset + proc_offset, sym_id),
                     }
                 } else {
                     internal_error!("failed to find fn symbol for {:?}", name);
@@ -948,6 +948,36 @@ pub(crate) fn call_str_bitcode_fn<'ctx>(
     }
 }
 
+/// Like [call_str_bitcode_fn], for a builtin that returns nothing
+pub(crate) fn call_void_str_bitcode_fn<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    strings: &[BasicValueEnum<'ctx>],
+    other_arguments: &[BasicValueEnum<'ctx>],
+    fn_name: &str,
+) {
+    use bumpalo::collections::Vec;
+
+    let mut arguments: Vec<BasicValueEnum> =
+        Vec::with_capacity_in(other_arguments.len() + 2 * strings.len(), env.arena);
+
+    for string in strings {
+        match env.target_info.ptr_width() {
+            roc_target::PtrWidth::Bytes4 => {
+                let (a, b) = pass_list_or_string_to_zig_32bit(env, string.into_struct_value());
+                arguments.push(a.into());
+                arguments.push(b.into());
+            }
+            roc_target::PtrWidth::Bytes8 => {
+                arguments.push(pass_string_to_zig_64bit(env, *string).into());
+            }
+        }
+    }
+
+    arguments.extend(other_arguments);
+
+    call_void_bitcode_fn(env, &arguments, fn_name);
+}
+
 pub(crate) fn call_list_bitcode_fn<'ctx>(
     env: &Env<'_, 'ctx, '_>,
     lists: &[StructValue<'ctx>],
@@ -1,4 +1,4 @@
-use crate::llvm::bitcode::call_bitcode_fn;
+use crate::llvm::bitcode::{call_bitcode_fn, call_void_bitcode_fn};
 use crate::llvm::build_list::{self, allocate_list, empty_polymorphic_list};
 use crate::llvm::convert::{
     argument_type_from_layout, basic_type_from_builtin, basic_type_from_layout, zig_str_type,
@@ -31,33 +31,40 @@ use inkwell::OptimizationLevel;
 use inkwell::{AddressSpace, IntPredicate};
 use morphic_lib::{
     CalleeSpecVar, FuncName, FuncSpec, FuncSpecSolutions, ModSolutions, UpdateMode, UpdateModeVar,
+    SPEC_HASH_BYTES,
 };
 use roc_builtins::bitcode::{self, FloatWidth, IntWidth};
 use roc_collections::all::{ImMap, MutMap, MutSet};
 use roc_debug_flags::dbg_do;
+use roc_debug_flags::ROC_LLVM_INLINE_REFCOUNTING;
 #[cfg(debug_assertions)]
 use roc_debug_flags::ROC_PRINT_LLVM_FN_VERIFICATION;
 use roc_module::symbol::{Interns, ModuleId, Symbol};
+use roc_mono::code_gen_help::ExpandedRefcounting;
+use roc_mono::host_exposed::{caller_name, host_exposed_name, result_size_name, size_name};
 use roc_mono::ir::{
-    BranchInfo, CallType, CrashTag, EntryPoint, GlueLayouts, HostExposedLambdaSet, JoinPointId,
-    ListLiteralElement, ModifyRc, OptLevel, ProcLayout, SingleEntryPoint,
+    BranchInfo, CallSpecId, CallType, CrashTag, EntryPoint, GlueLayouts, HostExposedLambdaSet,
+    JoinPointId, ListLiteralElement, ModifyRc, OptLevel, ProcLayout, SingleEntryPoint,
 };
 use roc_mono::layout::{
     Builtin, InLayout, LambdaName, LambdaSet, Layout, LayoutIds, LayoutInterner, LayoutRepr, Niche,
     RawFunctionLayout, STLayoutInterner, TagIdIntType, UnionLayout,
 };
+use roc_mono::profile::ProfileHints;
 use roc_std::RocDec;
 use roc_target::{PtrWidth, TargetInfo};
+use std::cell::RefCell;
 use std::convert::TryInto;
 use std::path::Path;
-use target_lexicon::{Architecture, OperatingSystem, Triple};
 
 use super::convert::{struct_type_from_union_layout, RocUnion};
+use super::heap_profile;
 use super::intrinsics::{
     add_intrinsics, LLVM_FRAME_ADDRESS, LLVM_MEMSET_I32, LLVM_MEMSET_I64, LLVM_SETJMP,
     LLVM_STACK_SAVE,
 };
 use super::lowlevel::run_higher_order_low_level;
+use super::stack_limit;
 
 pub(crate) trait BuilderExt<'ctx> {
     fn new_build_struct_gep(
@@ -253,6 +260,18 @@ pub struct Env<'a, 'ctx, 'env> {
     pub target_info: TargetInfo,
     pub mode: LlvmBackendMode,
     pub exposed_to_host: MutSet<Symbol>,
+    /// Call the host's heap profiling hooks; see [crate::llvm::heap_profile]
+    pub heap_profile: bool,
+    /// Crash with a message when a proc's frame is past the host's stack limit; see
+    /// [crate::llvm::stack_limit]
+    pub stack_limit_checks: bool,
+    /// Carve small allocations out of large chunks from `roc_alloc`, and never call
+    /// `roc_dealloc`, for `--arena-allocation` builds; see `alloc` in the builtins' utils.zig
+    pub arena_allocation: bool,
+    /// Procs to inline or keep out of the way in a `--profile-use` build; see [roc_mono::profile]
+    pub profile_hints: Option<ProfileHints>,
+    /// The number in the name of each proc specialization; see [func_spec_name]
+    pub func_spec_numbers: RefCell<MutMap<(Symbol, FuncSpec), usize>>,
 }
 
 impl<'a, 'ctx, 'env> Env<'a, 'ctx, 'env> {
@@ -351,8 +370,19 @@ impl<'a, 'ctx, 'env> Env<'a, 'ctx, 'env> {
         number_of_bytes: IntValue<'ctx>,
         alignment: u32,
     ) -> PointerValue<'ctx> {
-        let function = self.module.get_function("roc_alloc").unwrap();
         let alignment = self.alignment_const(alignment);
+
+        if self.arena_allocation {
+            // the builtins crash themselves if they can't get a chunk
+            return call_bitcode_fn(
+                self,
+                &[number_of_bytes.into(), alignment.into()],
+                bitcode::UTILS_ALLOC,
+            )
+            .into_pointer_value();
+        }
+
+        let function = self.module.get_function("roc_alloc").unwrap();
         let call = self.builder.build_call(
             function,
             &[number_of_bytes.into(), alignment.into()],
@@ -361,16 +391,47 @@ impl<'a, 'ctx, 'env> Env<'a, 'ctx, 'env> {
 
         call.set_call_convention(C_CALL_CONV);
 
-        call.try_as_basic_value()
+        let ptr = call
+            .try_as_basic_value()
             .left()
             .unwrap()
-            .into_pointer_value()
-        // TODO check if alloc returned null; if so, runtime error for OOM!
+            .into_pointer_value();
+
+        // crash, like the builtins do, rather than write through a null pointer
+        let parent = self
+            .builder
+            .get_insert_block()
+            .and_then(|block| block.get_parent())
+            .unwrap();
+        let out_of_memory = self.context.append_basic_block(parent, "out_of_memory");
+        let allocated = self.context.append_basic_block(parent, "allocated");
+
+        let is_null = self.builder.build_is_null(ptr, "is_null");
+        self.builder
+            .build_conditional_branch(is_null, out_of_memory, allocated);
+
+        self.builder.position_at_end(out_of_memory);
+        let message = build_string_literal(self, parent, "Out of memory");
+        self.call_panic(self, message, CrashTag::OutOfMemory);
+        self.builder.build_unreachable();
+
+        self.builder.position_at_end(allocated);
+
+        ptr
     }
 
     pub fn call_dealloc(&self, ptr: PointerValue<'ctx>, alignment: u32) -> InstructionValue<'ctx> {
-        let function = self.module.get_function("roc_dealloc").unwrap();
         let alignment = self.alignment_const(alignment);
+
+        if self.arena_allocation {
+            return call_void_bitcode_fn(
+                self,
+                &[ptr.into(), alignment.into()],
+                bitcode::UTILS_DEALLOC,
+            );
+        }
+
+        let function = self.module.get_function("roc_dealloc").unwrap();
         let call =
             self.builder
                 .build_call(function, &[ptr.into(), alignment.into()], "roc_dealloc");
@@ -410,7 +471,8 @@ impl<'a, 'ctx, 'env> Env<'a, 'ctx, 'env> {
         message: BasicValueEnum<'ctx>,
         tag: CrashTag,
     ) {
-        let function = self.module.get_function("roc_panic").unwrap();
+        // goes to the host's `roc_panic`, adding the stack trace in `--stack-traces` builds
+        let function = self.module.get_function(bitcode::UTILS_PANIC).unwrap();
         let tag_id = self.context.i32_type().const_int(tag as u32 as u64, false);
 
         let msg = match env.target_info.ptr_width() {
@@ -497,45 +559,14 @@ pub fn module_from_builtins<'ctx>(
     module_name: &str,
 ) -> Module<'ctx> {
     // In the build script for the builtins module, we compile the builtins into LLVM bitcode
+    let bitcode_bytes = roc_bitcode_bc::builtins_bitcode(target).unwrap_or_else(|| {
+        panic!(
+            "The zig builtins are not currently built for this target: {:?}",
+            target
+        )
+    });
 
-    let bitcode_bytes: &[u8] = if target == &target_lexicon::Triple::host() {
-        include_bytes!("../../../builtins/bitcode/builtins-host.bc")
-    } else {
-        match target {
-            Triple {
-                architecture: Architecture::Wasm32,
-                ..
-            } => {
-                include_bytes!("../../../builtins/bitcode/builtins-wasm32.bc")
-            }
-            Triple {
-                architecture: Architecture::X86_32(_),
-                operating_system: OperatingSystem::Linux,
-                ..
-            } => {
-                include_bytes!("../../../builtins/bitcode/builtins-i386.bc")
-            }
-            Triple {
-                architecture: Architecture::X86_64,
-                operating_system: OperatingSystem::Linux,
-                ..
-            } => {
-                include_bytes!("../../../builtins/bitcode/builtins-x86_64.bc")
-            }
-            Triple {
-                architecture: Architecture::X86_64,
-                operating_system: OperatingSystem::Windows,
-                ..
-            } => {
-                include_bytes!("../../../builtins/bitcode/builtins-windows-x86_64.bc")
-            }
-            _ => panic!(
-                "The zig builtins are not currently built for this target: {:?}",
-                target
-            ),
-        }
-    };
-
+    // The bitcode lives for the rest of the process, so LLVM can use it without making a copy.
     let memory_buffer = MemoryBuffer::create_from_memory_range(bitcode_bytes, module_name);
 
     let module = Module::parse_bitcode_from_buffer(&memory_buffer, ctx)
@@ -922,7 +953,7 @@ pub fn build_exp_call<'a, 'ctx>(
     env: &Env<'a, 'ctx, '_>,
     layout_interner: &mut STLayoutInterner<'a>,
     layout_ids: &mut LayoutIds<'a>,
-    func_spec_solutions: &FuncSpecSolutions,
+    func_spec_solutions: Option<&FuncSpecSolutions>,
     scope: &mut Scope<'a, 'ctx>,
     parent: FunctionValue<'ctx>,
     layout: InLayout<'a>,
@@ -948,9 +979,16 @@ pub fn build_exp_call<'a, 'ctx>(
                 arg_tuples.push(load_symbol(scope, symbol));
             }
 
-            let bytes = specialization_id.to_bytes();
-            let callee_var = CalleeSpecVar(&bytes);
-            let func_spec = func_spec_solutions.callee_spec(callee_var).unwrap();
+            let func_spec = if *specialization_id == CallSpecId::BACKEND_DUMMY {
+                REFCOUNT_HELPER_SPEC
+            } else {
+                let bytes = specialization_id.to_bytes();
+                let callee_var = CalleeSpecVar(&bytes);
+                func_spec_solutions
+                    .unwrap()
+                    .callee_spec(callee_var)
+                    .unwrap()
+            };
 
             roc_call_with_args(
                 env,
@@ -967,7 +1005,7 @@ pub fn build_exp_call<'a, 'ctx>(
             let bytes = update_mode.to_bytes();
             let update_var = UpdateModeVar(&bytes);
             let update_mode = func_spec_solutions
-                .update_mode(update_var)
+                .and_then(|solutions| solutions.update_mode(update_var).ok())
                 .unwrap_or(UpdateMode::Immutable);
 
             crate::llvm::lowlevel::run_low_level(
@@ -986,7 +1024,10 @@ pub fn build_exp_call<'a, 'ctx>(
         CallType::HigherOrder(higher_order) => {
             let bytes = higher_order.passed_function.specialization_id.to_bytes();
             let callee_var = CalleeSpecVar(&bytes);
-            let func_spec = func_spec_solutions.callee_spec(callee_var).unwrap();
+            let func_spec = func_spec_solutions
+                .unwrap()
+                .callee_spec(callee_var)
+                .unwrap();
 
             run_higher_order_low_level(
                 env,
@@ -1069,7 +1110,7 @@ pub fn build_exp_expr<'a, 'ctx>(
     env: &Env<'a, 'ctx, '_>,
     layout_interner: &mut STLayoutInterner<'a>,
     layout_ids: &mut LayoutIds<'a>,
-    func_spec_solutions: &FuncSpecSolutions,
+    func_spec_solutions: Option<&FuncSpecSolutions>,
     scope: &mut Scope<'a, 'ctx>,
     parent: FunctionValue<'ctx>,
     layout: InLayout<'a>,
@@ -1145,6 +1186,9 @@ pub fn build_exp_expr<'a, 'ctx>(
                 layout_interner.alignment_bytes(layout),
             );
 
+            let boxed_layout = layout_interner.insert_no_semantic(LayoutRepr::Boxed(layout));
+            heap_profile::call_alloc_hook(env, layout_interner, boxed_layout);
+
             store_roc_value(env, layout_interner, layout, allocation, value);
 
             allocation.into()
@@ -1171,7 +1215,7 @@ pub fn build_exp_expr<'a, 'ctx>(
             let bytes = update_mode.to_bytes();
             let update_var = UpdateModeVar(&bytes);
             let update_mode = func_spec_solutions
-                .update_mode(update_var)
+                .and_then(|solutions| solutions.update_mode(update_var).ok())
                 .unwrap_or(UpdateMode::Immutable);
 
             let (tag_ptr, layout) = load_symbol_and_layout(scope, symbol);
@@ -1258,7 +1302,7 @@ pub fn build_exp_expr<'a, 'ctx>(
             let bytes = update_mode.to_bytes();
             let update_var = UpdateModeVar(&bytes);
             let update_mode = func_spec_solutions
-                .update_mode(update_var)
+                .and_then(|solutions| solutions.update_mode(update_var).ok())
                 .unwrap_or(UpdateMode::Immutable);
 
             let (tag_ptr, layout) = load_symbol_and_layout(scope, symbol);
@@ -1354,6 +1398,31 @@ pub fn build_exp_expr<'a, 'ctx>(
                         "struct_field_tag",
                     )
                 }
+                (value, LayoutRepr::Builtin(Builtin::Str | Builtin::List(_))) => {
+                    // refcounting helpers look inside strings and lists as if they were structs
+                    let argument = if value.is_pointer_value() {
+                        let struct_type = basic_type_from_layout(env, layout_interner, layout);
+
+                        env.builder
+                            .new_build_load(
+                                struct_type,
+                                value.into_pointer_value(),
+                                "load_builtin_struct",
+                            )
+                            .into_struct_value()
+                    } else {
+                        value.into_struct_value()
+                    };
+
+                    env.builder
+                        .build_extract_value(
+                            argument,
+                            *index as u32,
+                            env.arena
+                                .alloc(format!("struct_field_access_builtin_{}", index)),
+                        )
+                        .unwrap()
+                }
                 (other, layout) => {
                     // potential cause: indexing into an unwrapped 1-element record/tag?
                     unreachable!(
@@ -2193,12 +2262,17 @@ fn reserve_with_refcount_union_as_block_of_memory<'a, 'ctx>(
         RocUnion::untagged_from_slices(layout_interner, env.context, fields, env.target_info)
     };
 
-    reserve_with_refcount_help(
+    let data_ptr = reserve_with_refcount_help(
         env,
         roc_union.struct_type(),
         roc_union.tag_width(),
         roc_union.tag_alignment(),
-    )
+    );
+
+    let layout = layout_interner.insert_no_semantic(LayoutRepr::Union(union_layout));
+    heap_profile::call_alloc_hook(env, layout_interner, layout);
+
+    data_ptr
 }
 
 fn reserve_with_refcount_help<'a, 'ctx, 'env>(
@@ -2522,7 +2596,7 @@ pub fn build_exp_stmt<'a, 'ctx>(
     env: &Env<'a, 'ctx, '_>,
     layout_interner: &mut STLayoutInterner<'a>,
     layout_ids: &mut LayoutIds<'a>,
-    func_spec_solutions: &FuncSpecSolutions,
+    func_spec_solutions: Option<&FuncSpecSolutions>,
     scope: &mut Scope<'a, 'ctx>,
     parent: FunctionValue<'ctx>,
     stmt: &roc_mono::ir::Stmt<'a>,
@@ -2789,6 +2863,13 @@ pub fn build_exp_stmt<'a, 'ctx>(
             for (phi_value, argument) in argument_phi_values.iter().zip(arguments.iter()) {
                 let (value, _) = load_symbol_and_layout(scope, argument);
 
+                let phi_type = phi_value.as_basic_value().get_type();
+                let value = if value.get_type() == phi_type {
+                    value
+                } else {
+                    cast_pointer_or_int(env, value, phi_type, "jump_arg_cast")
+                };
+
                 phi_value.add_incoming(&[(&value, current_block)]);
             }
 
@@ -3160,6 +3241,31 @@ pub fn cast_basic_basic<'ctx>(
     complex_bitcast(builder, from_value, to_type, "cast_basic_basic")
 }
 
+/// The helper procs from `CodeGenHelp` are not picky about the difference between pointers
+/// and pointer-sized integers, so values passing between them may need converting.
+pub(crate) fn cast_pointer_or_int<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    value: BasicValueEnum<'ctx>,
+    to_type: BasicTypeEnum<'ctx>,
+    name: &str,
+) -> BasicValueEnum<'ctx> {
+    match (value, to_type) {
+        (PointerValue(ptr), BasicTypeEnum::PointerType(ptr_type)) => {
+            env.builder.build_pointer_cast(ptr, ptr_type, name).into()
+        }
+        (PointerValue(ptr), BasicTypeEnum::IntType(int_type)) => {
+            env.builder.build_ptr_to_int(ptr, int_type, name).into()
+        }
+        (IntValue(int), BasicTypeEnum::PointerType(ptr_type)) => {
+            env.builder.build_int_to_ptr(int, ptr_type, name).into()
+        }
+        (IntValue(int), BasicTypeEnum::IntType(int_type)) => {
+            env.builder.build_int_cast(int, int_type, name).into()
+        }
+        _ => value,
+    }
+}
+
 pub fn complex_bitcast_struct_struct<'ctx>(
     builder: &Builder<'ctx>,
     from_value: StructValue<'ctx>,
@@ -3394,7 +3500,7 @@ fn build_switch_ir<'a, 'ctx>(
     env: &Env<'a, 'ctx, '_>,
     layout_interner: &mut STLayoutInterner<'a>,
     layout_ids: &mut LayoutIds<'a>,
-    func_spec_solutions: &FuncSpecSolutions,
+    func_spec_solutions: Option<&FuncSpecSolutions>,
     scope: &Scope<'a, 'ctx>,
     parent: FunctionValue<'ctx>,
     switch_args: SwitchArgsIr<'a, 'ctx>,
@@ -4651,7 +4757,7 @@ pub fn build_proc_headers<'a, 'r, 'ctx>(
     env: &'r Env<'a, 'ctx, '_>,
     layout_interner: &'r mut STLayoutInterner<'a>,
     mod_solutions: &'a ModSolutions,
-    procedures: MutMap<(Symbol, ProcLayout<'a>), roc_mono::ir::Proc<'a>>,
+    procedures: std::vec::Vec<((Symbol, ProcLayout<'a>), roc_mono::ir::Proc<'a>)>,
     scope: &mut Scope<'a, 'ctx>,
     layout_ids: &mut LayoutIds<'a>,
     // alias_analysis_solutions: AliasAnalysisSolutions,
@@ -4695,15 +4801,75 @@ pub fn build_proc_headers<'a, 'r, 'ctx>(
     headers
 }
 
+/// Refcounting helper procs don't go through alias analysis, so they all get this spec.
+const REFCOUNT_HELPER_SPEC: FuncSpec = FuncSpec([0; SPEC_HASH_BYTES]);
+
+/// The procs to generate code for, in the order of [roc_mono::ir::proc_sort_key].
+pub struct Procedures<'a> {
+    procs: std::vec::Vec<((Symbol, ProcLayout<'a>), roc_mono::ir::Proc<'a>)>,
+    refcounting: Option<ExpandedRefcounting<'a>>,
+}
+
+/// Prepare `procedures` for code gen. Their `Refcounting` statements become calls to helper
+/// procs, which are generated by `CodeGenHelp` just like in the other backends. This has to
+/// happen before the `Env` is created, because the helpers need new symbols in `home`.
+///
+/// Set `ROC_LLVM_INLINE_REFCOUNTING` to keep the statements instead, and generate their code
+/// with `refcounting.rs`.
+///
+/// The procs are sorted first, so that the helpers, and the order everything is generated in,
+/// are the same however the modules were loaded.
+pub fn expand_refcounting_procs<'a>(
+    arena: &'a Bump,
+    target_info: TargetInfo,
+    home: ModuleId,
+    interns: &mut Interns,
+    layout_interner: &mut STLayoutInterner<'a>,
+    procedures: MutMap<(Symbol, ProcLayout<'a>), roc_mono::ir::Proc<'a>>,
+) -> Procedures<'a> {
+    let mut procedures: std::vec::Vec<_> = procedures.into_iter().collect();
+    procedures.sort_by_cached_key(|((symbol, layout), _)| {
+        roc_mono::ir::proc_sort_key(&*interns, &*layout_interner, *symbol, layout)
+    });
+
+    let inline_refcounting = std::env::var(ROC_LLVM_INLINE_REFCOUNTING);
+    let refcounting = if inline_refcounting.is_ok() && inline_refcounting.as_deref() != Ok("0") {
+        None
+    } else {
+        let ident_ids = interns.all_ident_ids.get_mut(&home).unwrap();
+
+        Some(roc_mono::code_gen_help::expand_refcounting(
+            arena,
+            target_info,
+            home,
+            ident_ids,
+            layout_interner,
+            &procedures,
+        ))
+    };
+
+    Procedures {
+        procs: procedures,
+        refcounting,
+    }
+}
+
 pub fn build_procedures<'a>(
     env: &Env<'a, '_, '_>,
     layout_interner: &mut STLayoutInterner<'a>,
     opt_level: OptLevel,
-    procedures: MutMap<(Symbol, ProcLayout<'a>), roc_mono::ir::Proc<'a>>,
+    procedures: Procedures<'a>,
     entry_point: EntryPoint<'a>,
     debug_output_file: Option<&Path>,
     glue_layouts: &GlueLayouts<'a>,
 ) {
+    if env.profile_hints.is_some() {
+        // Crashing is rare, so LLVM can move the code that leads to a crash out of the way of
+        // the code that the profile saw run.
+        let panic = env.module.get_function(bitcode::UTILS_PANIC).unwrap();
+        add_enum_attribute(env, panic, "cold");
+    }
+
     let mod_solutions = build_procedures_help(
         env,
         layout_interner,
@@ -4755,7 +4921,7 @@ pub fn build_wasm_test_wrapper<'a, 'ctx>(
     env: &Env<'a, 'ctx, '_>,
     layout_interner: &mut STLayoutInterner<'a>,
     opt_level: OptLevel,
-    procedures: MutMap<(Symbol, ProcLayout<'a>), roc_mono::ir::Proc<'a>>,
+    procedures: Procedures<'a>,
     entry_point: SingleEntryPoint<'a>,
 ) -> (&'static str, FunctionValue<'ctx>) {
     let mod_solutions = build_procedures_help(
@@ -4780,7 +4946,7 @@ pub fn build_procedures_return_main<'a, 'ctx>(
     env: &Env<'a, 'ctx, '_>,
     layout_interner: &mut STLayoutInterner<'a>,
     opt_level: OptLevel,
-    procedures: MutMap<(Symbol, ProcLayout<'a>), roc_mono::ir::Proc<'a>>,
+    procedures: Procedures<'a>,
     entry_point: SingleEntryPoint<'a>,
 ) -> (&'static str, FunctionValue<'ctx>) {
     let mod_solutions = build_procedures_help(
@@ -4806,7 +4972,7 @@ pub fn build_procedures_expose_expects<'a>(
     layout_interner: &mut STLayoutInterner<'a>,
     opt_level: OptLevel,
     expects: &'a [Symbol],
-    procedures: MutMap<(Symbol, ProcLayout<'a>), roc_mono::ir::Proc<'a>>,
+    procedures: Procedures<'a>,
 ) -> Vec<'a, &'a str> {
     let entry_point = EntryPoint::Expects { symbols: expects };
 
@@ -4876,14 +5042,19 @@ fn build_procedures_help<'a>(
     env: &Env<'a, '_, '_>,
     layout_interner: &mut STLayoutInterner<'a>,
     opt_level: OptLevel,
-    procedures: MutMap<(Symbol, ProcLayout<'a>), roc_mono::ir::Proc<'a>>,
+    procedures: Procedures<'a>,
     entry_point: EntryPoint<'a>,
     debug_output_file: Option<&Path>,
 ) -> &'a ModSolutions {
     let mut layout_ids = roc_mono::layout::LayoutIds::default();
     let mut scope = Scope::default();
 
-    let it = procedures.iter().map(|x| x.1);
+    let Procedures {
+        mut procs,
+        refcounting,
+    } = procedures;
+
+    let it = procs.iter().map(|(_, proc)| proc);
 
     let solutions = match roc_alias_analysis::spec_program(
         env.arena,
@@ -4902,17 +5073,57 @@ fn build_procedures_help<'a>(
         .mod_solutions(roc_alias_analysis::MOD_APP)
         .unwrap();
 
+    // Alias analysis saw the `Refcounting` statements, but the code calls helper procs instead
+    let helpers = match refcounting {
+        Some(ExpandedRefcounting {
+            mut bodies,
+            helpers,
+        }) => {
+            for (key, proc) in procs.iter_mut() {
+                if let Some(body) = bodies.remove(key) {
+                    proc.body = body;
+                }
+            }
+
+            helpers
+        }
+        None => Vec::new_in(env.arena),
+    };
+
     // Add all the Proc headers to the module.
     // We have to do this in a separate pass first,
     // because their bodies may reference each other.
-    let headers = build_proc_headers(
+    let mut headers: std::vec::Vec<_> = build_proc_headers(
         env,
         layout_interner,
         mod_solutions,
-        procedures,
+        procs,
         &mut scope,
         &mut layout_ids,
-    );
+    )
+    .into_iter()
+    .map(|(proc, fn_vals)| {
+        let fn_vals: std::vec::Vec<_> = fn_vals
+            .into_iter()
+            .map(|(func_spec_solutions, fn_val)| (Some(func_spec_solutions), fn_val))
+            .collect();
+
+        (proc, fn_vals)
+    })
+    .collect();
+
+    for helper in helpers {
+        let fn_val = build_proc_header(
+            env,
+            layout_interner,
+            REFCOUNT_HELPER_SPEC,
+            helper.name.name(),
+            &helper,
+            &mut layout_ids,
+        );
+
+        headers.push((helper, vec![(None, fn_val)]));
+    }
 
     let (_, function_pass) = construct_optimization_passes(env.module, opt_level);
 
@@ -4976,23 +5187,25 @@ fn build_procedures_help<'a>(
     mod_solutions
 }
 
+/// The LLVM name of a proc specialization. Specializations are numbered in the order their
+/// headers are built, rather than named after their [FuncSpec], because alias analysis derives
+/// that from symbol and layout numbers, which can change from run to run.
 fn func_spec_name<'a>(
-    arena: &'a Bump,
-    interns: &Interns,
+    env: &Env<'a, '_, '_>,
     symbol: Symbol,
     func_spec: FuncSpec,
 ) -> bumpalo::collections::String<'a> {
     use std::fmt::Write;
 
-    let mut buf = bumpalo::collections::String::with_capacity_in(1, arena);
+    let mut numbers = env.func_spec_numbers.borrow_mut();
+    let next = numbers.len();
+    let number = *numbers.entry((symbol, func_spec)).or_insert(next);
 
-    let ident_string = symbol.as_str(interns);
-    let module_string = interns.module_ids.get_name(symbol.module_id()).unwrap();
-    write!(buf, "{}_{}_", module_string, ident_string).unwrap();
+    let mut buf = bumpalo::collections::String::with_capacity_in(1, env.arena);
 
-    for byte in func_spec.0.iter() {
-        write!(buf, "{:x?}", byte).unwrap();
-    }
+    let ident_string = symbol.as_str(&env.interns);
+    let module_string = env.interns.module_ids.get_name(symbol.module_id()).unwrap();
+    write!(buf, "{}_{}_{}", module_string, ident_string, number).unwrap();
 
     buf
 }
@@ -5008,7 +5221,7 @@ fn build_proc_header<'a, 'ctx>(
     let args = proc.args;
     let arena = env.arena;
 
-    let fn_name = func_spec_name(env.arena, &env.interns, symbol, func_spec);
+    let fn_name = func_spec_name(env, symbol, func_spec);
 
     let ret_type = basic_type_from_layout(env, layout_interner, proc.ret_layout);
     let mut arg_basic_types = Vec::with_capacity_in(args.len(), arena);
@@ -5047,23 +5260,29 @@ fn build_proc_header<'a, 'ctx>(
         );
     }
 
-    if false {
-        let kind_id = Attribute::get_named_enum_kind_id("alwaysinline");
-        debug_assert!(kind_id > 0);
-        let enum_attr = env.context.create_enum_attribute(kind_id, 1);
-        fn_val.add_attribute(AttributeLoc::Function, enum_attr);
-    }
+    if let Some(hints) = &env.profile_hints {
+        // small, and run often in the profile
+        if hints.hot.contains(&symbol) {
+            add_enum_attribute(env, fn_val, "alwaysinline");
+        }
 
-    if false {
-        let kind_id = Attribute::get_named_enum_kind_id("noinline");
-        debug_assert!(kind_id > 0);
-        let enum_attr = env.context.create_enum_attribute(kind_id, 1);
-        fn_val.add_attribute(AttributeLoc::Function, enum_attr);
+        // never run in the profile; keep it out of the way of the code that did run
+        if hints.cold.contains(&symbol) {
+            add_enum_attribute(env, fn_val, "noinline");
+            add_enum_attribute(env, fn_val, "cold");
+        }
     }
 
     fn_val
 }
 
+fn add_enum_attribute(env: &Env, fn_val: FunctionValue, name: &str) {
+    let kind_id = Attribute::get_named_enum_kind_id(name);
+    debug_assert!(kind_id > 0);
+    let enum_attr = env.context.create_enum_attribute(kind_id, 1);
+    fn_val.add_attribute(AttributeLoc::Function, enum_attr);
+}
+
 fn expose_alias_to_host<'a>(
     env: &Env<'a, '_, '_>,
     layout_interner: &mut STLayoutInterner<'a>,
@@ -5136,9 +5355,8 @@ fn expose_alias_to_host<'a>(
 
             build_host_exposed_alias_size_help(
                 env,
-                fn_name,
+                &result_size_name(fn_name),
                 alias_symbol,
-                Some("result"),
                 result_type,
             );
         }
@@ -5184,7 +5402,7 @@ fn build_closure_caller<'a, 'ctx>(
     // STEP 1: build function header
 
     // e.g. `roc__mainForHost_0_caller` (def_name is `mainForHost_0`)
-    let function_name = format!("roc__{}_caller", def_name);
+    let function_name = caller_name(def_name);
 
     let function_spec = FunctionSpec::cconv(env, CCReturn::Void, None, &argument_types);
 
@@ -5264,7 +5482,7 @@ fn build_closure_caller<'a, 'ctx>(
     builder.build_return(None);
 
     // STEP 3: build a {} -> u64 function that gives the size of the return type
-    build_host_exposed_alias_size_help(env, def_name, alias_symbol, Some("result"), result_type);
+    build_host_exposed_alias_size_help(env, &result_size_name(def_name), alias_symbol, result_type);
 
     // STEP 4: build a {} -> u64 function that gives the size of the closure
     build_host_exposed_alias_size(
@@ -5285,18 +5503,16 @@ fn build_host_exposed_alias_size<'a, 'r>(
 ) {
     build_host_exposed_alias_size_help(
         env,
-        def_name,
+        &size_name(def_name),
         alias_symbol,
-        None,
         basic_type_from_layout(env, layout_interner, layout),
     )
 }
 
 fn build_host_exposed_alias_size_help<'a, 'ctx>(
     env: &'a Env<'a, 'ctx, '_>,
-    def_name: &str,
+    size_function_name: &str,
     _alias_symbol: Symbol,
-    opt_label: Option<&str>,
     basic_type: BasicTypeEnum<'ctx>,
 ) {
     let builder = env.builder;
@@ -5304,16 +5520,11 @@ fn build_host_exposed_alias_size_help<'a, 'ctx>(
 
     let i64 = env.context.i64_type().as_basic_type_enum();
     let size_function_spec = FunctionSpec::cconv(env, CCReturn::Return, Some(i64), &[]);
-    let size_function_name: String = if let Some(label) = opt_label {
-        format!("roc__{}_{}_size", def_name, label)
-    } else {
-        format!("roc__{}_size", def_name,)
-    };
 
     let size_function = add_func(
         env.context,
         env.module,
-        size_function_name.as_str(),
+        size_function_name,
         size_function_spec,
         Linkage::External,
     );
@@ -5331,7 +5542,7 @@ fn build_proc<'a, 'ctx>(
     layout_interner: &mut STLayoutInterner<'a>,
     mod_solutions: &'a ModSolutions,
     layout_ids: &mut LayoutIds<'a>,
-    func_spec_solutions: &FuncSpecSolutions,
+    func_spec_solutions: Option<&FuncSpecSolutions>,
     mut scope: Scope<'a, 'ctx>,
     proc: &roc_mono::ir::Proc<'a>,
     fn_val: FunctionValue<'ctx>,
@@ -5349,8 +5560,7 @@ fn build_proc<'a, 'ctx>(
                 }
                 Binary | BinaryDev | BinaryGlue => {
                     for (alias_name, hels) in aliases.iter() {
-                        let ident_string = proc.name.name().as_str(&env.interns);
-                        let fn_name: String = format!("{}_{}", ident_string, hels.id.0);
+                        let fn_name = host_exposed_name(&env.interns, proc.name.name(), hels);
 
                         expose_alias_to_host(
                             env,
@@ -5377,6 +5587,8 @@ fn build_proc<'a, 'ctx>(
 
     debug_info_init!(env, fn_val);
 
+    stack_limit::build_stack_limit_check(env, fn_val);
+
     // Add args to scope
     for (arg_val, (layout, arg_symbol)) in fn_val.get_param_iter().zip(args) {
         arg_val.set_name(arg_symbol.as_str(&env.interns));
@@ -5419,7 +5631,7 @@ pub(crate) fn function_value_by_func_spec<'a, 'ctx>(
     niche: Niche<'a>,
     result: InLayout<'a>,
 ) -> FunctionValue<'ctx> {
-    let fn_name = func_spec_name(env.arena, &env.interns, symbol, func_spec);
+    let fn_name = func_spec_name(env, symbol, func_spec);
     let fn_name = fn_name.as_str();
 
     function_value_by_name_help(env, arguments, niche, result, symbol, fn_name)
@@ -2,11 +2,15 @@ use crate::llvm::build::Env;
 use inkwell::values::{BasicValueEnum, PointerValue, StructValue};
 use inkwell::AddressSpace;
 use roc_builtins::bitcode;
-use roc_mono::layout::{InLayout, Layout};
+use roc_mono::layout::{InLayout, Layout, STLayoutInterner};
 use roc_target::PtrWidth;
 
-use super::bitcode::{call_str_bitcode_fn, BitcodeReturns};
-use super::build::BuilderExt;
+use super::bitcode::{call_str_bitcode_fn, call_void_str_bitcode_fn, BitcodeReturns};
+use super::build::{
+    create_entry_block_alloca, load_roc_value, store_roc_value, BuilderExt, RocFunctionCall,
+};
+use super::build_list::{layout_width, pass_as_opaque};
+use super::convert::basic_type_from_layout;
 
 pub static CHAR_LAYOUT: InLayout = Layout::U8;
 
@@ -79,3 +83,44 @@ pub(crate) fn str_refcount_ptr<'ctx>(
     )
     .into_pointer_value()
 }
+
+/// Str.walkUtf8 : Str, state, (state, U8 -> state) -> state
+pub(crate) fn str_walk_utf8<'a, 'ctx>(
+    env: &Env<'a, 'ctx, '_>,
+    layout_interner: &mut STLayoutInterner<'a>,
+    roc_function_call: RocFunctionCall<'ctx>,
+    string: BasicValueEnum<'ctx>,
+    state: BasicValueEnum<'ctx>,
+    state_layout: InLayout<'a>,
+) -> BasicValueEnum<'ctx> {
+    let parent = env
+        .builder
+        .get_insert_block()
+        .and_then(|b| b.get_parent())
+        .unwrap();
+
+    // the builtin keeps the state in `state_ptr`, and has the function write the next one to
+    // `scratch_ptr` before copying it back
+    let state_type = basic_type_from_layout(env, layout_interner, state_layout);
+    let state_ptr = create_entry_block_alloca(env, parent, state_type, "walk_state");
+    let scratch_ptr = create_entry_block_alloca(env, parent, state_type, "walk_scratch");
+
+    store_roc_value(env, layout_interner, state_layout, state_ptr, state);
+
+    call_void_str_bitcode_fn(
+        env,
+        &[string],
+        &[
+            roc_function_call.caller.into(),
+            pass_as_opaque(env, roc_function_call.data),
+            roc_function_call.inc_n_data.into(),
+            roc_function_call.data_is_owned.into(),
+            pass_as_opaque(env, state_ptr),
+            pass_as_opaque(env, scratch_ptr),
+            layout_width(env, layout_interner, state_layout),
+        ],
+        bitcode::STR_WALK_UTF8,
+    );
+
+    load_roc_value(env, layout_interner, state_layout, state_ptr, "walk_result")
+}
@@ -3,15 +3,19 @@ use crate::llvm::build::{add_func, get_panic_msg_ptr, get_panic_tag_ptr, Builder
 use crate::llvm::build::{CCReturn, Env, FunctionSpec};
 use crate::llvm::convert::zig_str_type;
 use inkwell::module::Linkage;
-use inkwell::types::BasicType;
+use inkwell::types::{BasicType, BasicTypeEnum};
+use inkwell::values::{FunctionValue, PointerValue};
 use inkwell::AddressSpace;
 use roc_builtins::bitcode;
+use roc_mono::coverage::{COVERAGE_COUNTS, COVERAGE_HIT};
 
 use super::build::get_sjlj_buffer;
 use super::intrinsics::LLVM_LONGJMP;
 
 /// Define functions for roc_alloc, roc_realloc, and roc_dealloc
 /// which use libc implementations (malloc, realloc, and free)
+///
+/// Also tells the builtins whether to batch allocations for `--arena-allocation`.
 pub fn add_default_roc_externs(env: &Env<'_, '_, '_>) {
     let ctx = env.context;
     let module = env.module;
@@ -20,6 +24,12 @@ pub fn add_default_roc_externs(env: &Env<'_, '_, '_>) {
     let usize_type = env.ptr_int();
     let i8_ptr_type = ctx.i8_type().ptr_type(AddressSpace::default());
 
+    if let Some(global) = module.get_global(bitcode::UTILS_ARENA_ALLOCATION) {
+        // internal, so LLVM can fold the checks in the builtins away
+        global.set_linkage(Linkage::Internal);
+        global.set_initializer(&ctx.i8_type().const_int(env.arena_allocation as u64, false));
+    }
+
     match env.mode {
         super::build::LlvmBackendMode::CliTest => {
             // expose this function
@@ -168,6 +178,214 @@ pub fn add_default_roc_externs(env: &Env<'_, '_, '_>) {
     }
 }
 
+/// Define the array of coverage counters, with `counters` zeroed entries, and define
+/// roc_coverage_hit to increment the counter at its argument's index.
+pub fn add_coverage_counters(env: &Env<'_, '_, '_>, counters: usize) {
+    let ctx = env.context;
+    let builder = env.builder;
+    let i64_type = ctx.i64_type();
+    let array_type = i64_type.array_type(counters as u32);
+
+    let global = env.module.add_global(array_type, None, COVERAGE_COUNTS);
+    global.set_initializer(&array_type.const_zero());
+    global.set_linkage(Linkage::External);
+
+    // The type of this function (but not the implementation) was defined by the calls to it.
+    // It isn't there if no counted region was ever specialized.
+    let fn_val = match env.module.get_function(COVERAGE_HIT) {
+        Some(f) => f,
+        None => return,
+    };
+    fn_val.set_linkage(Linkage::Internal);
+
+    let index_arg = fn_val.get_nth_param(0).unwrap().into_int_value();
+
+    // Add a basic block for the entry point
+    let entry = ctx.append_basic_block(fn_val, "entry");
+
+    builder.position_at_end(entry);
+
+    let counter_ptr = unsafe {
+        builder.new_build_in_bounds_gep(
+            array_type,
+            global.as_pointer_value(),
+            &[i64_type.const_zero(), index_arg],
+            "counter_ptr",
+        )
+    };
+    let count = builder
+        .new_build_load(i64_type, counter_ptr, "count")
+        .into_int_value();
+    let incremented = builder.build_int_add(count, i64_type.const_int(1, false), "incremented");
+
+    builder.build_store(counter_ptr, incremented);
+    builder.build_return(None);
+
+    if cfg!(debug_assertions) {
+        crate::llvm::build::verify_fn(fn_val);
+    }
+}
+
+/// Write `header`, then the `counters` coverage counts, to `path` when the program exits, for
+/// `roc build --profile-generate`; see [roc_mono::profile]. The counters must already have been
+/// added with [add_coverage_counters].
+pub fn add_profile_writer(env: &Env<'_, '_, '_>, header: &str, counters: usize, path: &str) {
+    let ctx = env.context;
+    let module = env.module;
+    let builder = env.builder;
+
+    let usize_type = env.ptr_int();
+    let i8_ptr_type = ctx.i8_type().ptr_type(AddressSpace::default());
+
+    let libc_fopen = libc_function(
+        env,
+        "fopen",
+        i8_ptr_type.into(),
+        &[i8_ptr_type.into(), i8_ptr_type.into()],
+    );
+    let libc_fwrite = libc_function(
+        env,
+        "fwrite",
+        usize_type.into(),
+        &[
+            i8_ptr_type.into(),
+            usize_type.into(),
+            usize_type.into(),
+            i8_ptr_type.into(),
+        ],
+    );
+    let libc_fclose = libc_function(env, "fclose", ctx.i32_type().into(), &[i8_ptr_type.into()]);
+
+    let fn_type = ctx.void_type().fn_type(&[], false);
+    let fn_val = module.add_function(PROFILE_WRITER, fn_type, Some(Linkage::Internal));
+
+    let entry = ctx.append_basic_block(fn_val, "entry");
+    let write = ctx.append_basic_block(fn_val, "write");
+    let done = ctx.append_basic_block(fn_val, "done");
+
+    builder.position_at_end(entry);
+
+    // fopen takes C strings
+    let path_ptr = constant_bytes(env, "roc_profile_path", format!("{path}\0").as_bytes());
+    let mode_ptr = constant_bytes(env, "roc_profile_mode", b"wb\0");
+
+    let call = builder.build_call(
+        libc_fopen,
+        &[path_ptr.into(), mode_ptr.into()],
+        "call_libc_fopen",
+    );
+    call.set_call_convention(C_CALL_CONV);
+    let file = call
+        .try_as_basic_value()
+        .left()
+        .unwrap()
+        .into_pointer_value();
+
+    // if the file can't be opened, there is nothing we can do about it this late
+    let is_null = builder.build_is_null(file, "is_null");
+    builder.build_conditional_branch(is_null, done, write);
+
+    builder.position_at_end(write);
+
+    let header_ptr = constant_bytes(env, "roc_profile_header", header.as_bytes());
+    let counts_ptr = builder.build_pointer_cast(
+        module
+            .get_global(COVERAGE_COUNTS)
+            .unwrap()
+            .as_pointer_value(),
+        i8_ptr_type,
+        "counts_ptr",
+    );
+
+    for (ptr, size, count) in [(header_ptr, 1, header.len()), (counts_ptr, 8, counters)] {
+        let call = builder.build_call(
+            libc_fwrite,
+            &[
+                ptr.into(),
+                usize_type.const_int(size, false).into(),
+                usize_type.const_int(count as u64, false).into(),
+                file.into(),
+            ],
+            "call_libc_fwrite",
+        );
+        call.set_call_convention(C_CALL_CONV);
+    }
+
+    let call = builder.build_call(libc_fclose, &[file.into()], "call_libc_fclose");
+    call.set_call_convention(C_CALL_CONV);
+
+    builder.build_unconditional_branch(done);
+
+    builder.position_at_end(done);
+    builder.build_return(None);
+
+    if cfg!(debug_assertions) {
+        crate::llvm::build::verify_fn(fn_val);
+    }
+
+    // Run it when the program exits, like a C++ static destructor.
+    let dtor_type = ctx.struct_type(
+        &[
+            ctx.i32_type().into(),
+            fn_type.ptr_type(AddressSpace::default()).into(),
+            i8_ptr_type.into(),
+        ],
+        false,
+    );
+    let dtor = dtor_type.const_named_struct(&[
+        ctx.i32_type().const_int(65535, false).into(),
+        fn_val.as_global_value().as_pointer_value().into(),
+        i8_ptr_type.const_null().into(),
+    ]);
+    let dtors = dtor_type.const_array(&[dtor]);
+
+    let global = module.add_global(dtors.get_type(), None, "llvm.global_dtors");
+    global.set_initializer(&dtors);
+    global.set_linkage(Linkage::Appending);
+}
+
+const PROFILE_WRITER: &str = "roc_profile_write";
+
+/// Declare a function of the C standard library
+fn libc_function<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    name: &str,
+    return_type: BasicTypeEnum<'ctx>,
+    argument_types: &[BasicTypeEnum<'ctx>],
+) -> FunctionValue<'ctx> {
+    match env.module.get_function(name) {
+        Some(fn_val) => fn_val,
+        None => {
+            let fn_spec =
+                FunctionSpec::cconv(env, CCReturn::Return, Some(return_type), argument_types);
+
+            add_func(env.context, env.module, name, fn_spec, Linkage::External)
+        }
+    }
+}
+
+/// A pointer to a private constant holding `bytes`
+fn constant_bytes<'ctx>(env: &Env<'_, 'ctx, '_>, name: &str, bytes: &[u8]) -> PointerValue<'ctx> {
+    let i8_type = env.context.i8_type();
+    let elements: Vec<_> = bytes
+        .iter()
+        .map(|byte| i8_type.const_int(*byte as u64, false))
+        .collect();
+    let value = i8_type.const_array(&elements);
+
+    let global = env.module.add_global(value.get_type(), None, name);
+
+    global.set_initializer(&value);
+    global.set_constant(true);
+    global.set_linkage(Linkage::Private);
+
+    env.builder.build_pointer_cast(
+        global.as_pointer_value(),
+        i8_type.ptr_type(AddressSpace::default()),
+        name,
+    )
+}
+
 fn unreachable_function(env: &Env, name: &str) {
     // The type of this function (but not the implementation) should have
     // already been defined by the builtins, which rely on it.
@@ -0,0 +1,163 @@
+//! Calls to the host's heap profiling hooks, for `--heap-profile`.
+//!
+//! Every allocation of a box or of a recursive tag union calls
+//!
+//! ```c
+//! void roc_heap_profile_alloc(uint32_t layout_id, const char *layout_name);
+//! ```
+//!
+//! and every decrement that frees one calls `roc_heap_profile_dealloc` with the same arguments.
+//! A layout's id is the same for every allocation of it, and its name is a NUL-terminated
+//! description of the layout. A host that counts allocations per id, minus deallocations, knows
+//! what is still alive, which finds leaks caused by missing decrements. Lists and strings are
+//! allocated by the builtins, which know nothing of layouts, so they are not tracked.
+use inkwell::module::Linkage;
+use inkwell::values::{FunctionValue, PointerValue};
+use inkwell::AddressSpace;
+use roc_mono::layout::{InLayout, LayoutInterner, LayoutRepr, STLayoutInterner, UnionLayout};
+
+use crate::llvm::build::{add_func, CCReturn, Env, FunctionSpec, C_CALL_CONV};
+use crate::llvm::refcounting::PointerToRefcount;
+
+pub const HEAP_PROFILE_ALLOC: &str = "roc_heap_profile_alloc";
+pub const HEAP_PROFILE_DEALLOC: &str = "roc_heap_profile_dealloc";
+
+/// The layout that identifies allocations of `layout`, if they are tracked
+fn tracked_layout<'a>(
+    layout_interner: &mut STLayoutInterner<'a>,
+    layout: InLayout<'a>,
+) -> Option<InLayout<'a>> {
+    match layout_interner.get(layout).repr {
+        repr @ LayoutRepr::Boxed(_) => Some(layout_interner.insert_no_semantic(repr)),
+        LayoutRepr::Union(UnionLayout::NonRecursive(_)) => None,
+        repr @ LayoutRepr::Union(_) => Some(layout_interner.insert_no_semantic(repr)),
+        _ => None,
+    }
+}
+
+/// Report an allocation of a value with this layout, which must be a box or a recursive union
+pub fn call_alloc_hook<'a>(
+    env: &Env<'a, '_, '_>,
+    layout_interner: &mut STLayoutInterner<'a>,
+    layout: InLayout<'a>,
+) {
+    if !env.heap_profile {
+        return;
+    }
+
+    if let Some(tracked) = tracked_layout(layout_interner, layout) {
+        call_hook(env, layout_interner, HEAP_PROFILE_ALLOC, tracked);
+    }
+}
+
+/// Report a deallocation if decrementing `refcount_ptr` is going to free it
+pub fn call_dealloc_hook_if_unique<'a, 'ctx>(
+    env: &Env<'a, 'ctx, '_>,
+    layout_interner: &mut STLayoutInterner<'a>,
+    layout: InLayout<'a>,
+    refcount_ptr: &PointerToRefcount<'ctx>,
+) {
+    if !env.heap_profile {
+        return;
+    }
+
+    let tracked = match tracked_layout(layout_interner, layout) {
+        Some(tracked) => tracked,
+        None => return,
+    };
+
+    let parent = env
+        .builder
+        .get_insert_block()
+        .and_then(|block| block.get_parent())
+        .expect("to be in a function");
+
+    let freed_block = env.context.append_basic_block(parent, "heap_profile_freed");
+    let cont_block = env.context.append_basic_block(parent, "heap_profile_cont");
+
+    env.builder
+        .build_conditional_branch(refcount_ptr.is_1(env), freed_block, cont_block);
+
+    env.builder.position_at_end(freed_block);
+    call_hook(env, layout_interner, HEAP_PROFILE_DEALLOC, tracked);
+    env.builder.build_unconditional_branch(cont_block);
+
+    env.builder.position_at_end(cont_block);
+}
+
+fn call_hook<'a>(
+    env: &Env<'a, '_, '_>,
+    layout_interner: &STLayoutInterner<'a>,
+    hook_name: &str,
+    layout: InLayout<'a>,
+) {
+    let hook = get_hook(env, hook_name);
+    let layout_id = env
+        .context
+        .i32_type()
+        .const_int(layout.index() as u64, false);
+    let layout_name = layout_name_ptr(env, layout_interner, layout);
+
+    let call = env
+        .builder
+        .build_call(hook, &[layout_id.into(), layout_name.into()], hook_name);
+
+    call.set_call_convention(C_CALL_CONV);
+}
+
+fn get_hook<'ctx>(env: &Env<'_, 'ctx, '_>, hook_name: &str) -> FunctionValue<'ctx> {
+    match env.module.get_function(hook_name) {
+        Some(function) => function,
+        None => {
+            let fn_spec = FunctionSpec::cconv(
+                env,
+                CCReturn::Void,
+                None,
+                &[
+                    env.context.i32_type().into(),
+                    env.context
+                        .i8_type()
+                        .ptr_type(AddressSpace::default())
+                        .into(),
+                ],
+            );
+
+            add_func(
+                env.context,
+                env.module,
+                hook_name,
+                fn_spec,
+                Linkage::External,
+            )
+        }
+    }
+}
+
+fn layout_name_ptr<'a, 'ctx>(
+    env: &Env<'a, 'ctx, '_>,
+    layout_interner: &STLayoutInterner<'a>,
+    layout: InLayout<'a>,
+) -> PointerValue<'ctx> {
+    let global_name = format!("roc_heap_profile_layout_{}", layout.index());
+
+    let global = match env.module.get_global(&global_name) {
+        Some(global) => global,
+        None => {
+            let name = layout_interner.dbg(layout);
+            let value = env.context.const_string(name.as_bytes(), true);
+            let global = env.module.add_global(value.get_type(), None, &global_name);
+
+            global.set_initializer(&value);
+            global.set_constant(true);
+            global.set_linkage(Linkage::Private);
+
+            global
+        }
+    };
+
+    env.builder.build_pointer_cast(
+        global.as_pointer_value(),
+        env.context.i8_type().ptr_type(AddressSpace::default()),
+        "layout_name",
+    )
+}
@@ -27,8 +27,9 @@ use crate::llvm::{
         BitcodeReturns,
     },
     build::{
-        complex_bitcast_check_size, create_entry_block_alloca, function_value_by_func_spec,
-        load_roc_value, roc_function_call, tag_pointer_clear_tag_id, BuilderExt, RocReturn,
+        cast_pointer_or_int, complex_bitcast_check_size, create_entry_block_alloca,
+        function_value_by_func_spec, load_roc_value, roc_function_call, tag_pointer_clear_tag_id,
+        BuilderExt, RocReturn,
     },
     build_list::{
         list_append_unsafe, list_concat, list_drop_at, list_get_unsafe, list_len, list_map,
@@ -36,6 +37,7 @@ use crate::llvm::{
         list_replace_unsafe, list_reserve, list_sort_with, list_sublist, list_swap,
         list_symbol_to_c_abi, list_with_capacity, pass_update_mode,
     },
+    build_str::str_walk_utf8,
     compare::{generic_eq, generic_neq},
     convert::{
         self, basic_type_from_layout, zig_num_parse_result_type, zig_to_int_checked_result_type,
@@ -435,6 +437,18 @@ pub(crate) fn run_low_level<'a, 'ctx>(
                 bitcode::STR_SPLIT,
             )
         }
+        StrSplitOn => {
+            // Str.splitOn : Str, Str -> List Str
+            arguments!(string, delimiter);
+
+            call_str_bitcode_fn(
+                env,
+                &[string, delimiter],
+                &[],
+                BitcodeReturns::List,
+                bitcode::STR_SPLIT_ON,
+            )
+        }
         StrIsEmpty => {
             // Str.isEmpty : Str -> Str
             arguments!(string);
@@ -579,6 +593,18 @@ pub(crate) fn run_low_level<'a, 'ctx>(
                 bitcode::STR_RELEASE_EXCESS_CAPACITY,
             )
         }
+        StrReplaceEachUnsafe => {
+            // Str.replaceEachUnsafe : Str, Str, Str -> Str
+            arguments!(string, needle, flower);
+
+            call_str_bitcode_fn(
+                env,
+                &[string, needle, flower],
+                &[],
+                BitcodeReturns::Str,
+                bitcode::STR_REPLACE_EACH_UNSAFE,
+            )
+        }
         StrAppendScalar => {
             // Str.appendScalar : Str, U32 -> Str
             arguments!(string, capacity);
@@ -1182,11 +1208,6 @@ pub(crate) fn run_low_level<'a, 'ctx>(
                 }
             }
         }
-        NumToFloatChecked => {
-            // NOTE: There's a NumToIntChecked implementation above,
-            // which could be useful to look at when implementing this.
-            todo!("implement checked float conversion");
-        }
         Eq => {
             arguments_with_layouts!((lhs_arg, lhs_layout), (rhs_arg, rhs_layout));
 
@@ -1244,11 +1265,7 @@ pub(crate) fn run_low_level<'a, 'ctx>(
             let bool_val = env.builder.build_not(arg.into_int_value(), "bool_not");
             BasicValueEnum::IntValue(bool_val)
         }
-        Hash => {
-            unimplemented!()
-        }
-
-        ListMap | ListMap2 | ListMap3 | ListMap4 | ListSortWith => {
+        ListMap | ListMap2 | ListMap3 | ListMap4 | ListSortWith | StrWalkUtf8 => {
             unreachable!("these are higher order, and are handled elsewhere")
         }
 
@@ -1259,18 +1276,42 @@ pub(crate) fn run_low_level<'a, 'ctx>(
         PtrCast => {
             arguments!(data_ptr);
 
-            let target_type =
-                basic_type_from_layout(env, layout_interner, layout).into_pointer_type();
+            let target_type = basic_type_from_layout(env, layout_interner, layout);
 
-            debug_assert!(data_ptr.is_pointer_value());
+            cast_pointer_or_int(env, data_ptr, target_type, "ptr_cast")
+        }
 
-            env.builder
-                .build_pointer_cast(data_ptr.into_pointer_value(), target_type, "ptr_cast")
-                .into()
+        RefCountIncRcPtr | RefCountIncDataPtr => {
+            arguments!(ptr, amount);
+
+            let ptr_type = env.ptr_int().ptr_type(AddressSpace::default());
+            let ptr = cast_pointer_or_int(env, ptr, ptr_type.into(), "to_isize_ptr");
+
+            let fn_name = match op {
+                RefCountIncRcPtr => bitcode::UTILS_INCREF_RC_PTR,
+                _ => bitcode::UTILS_INCREF_DATA_PTR,
+            };
+            call_void_bitcode_fn(env, &[ptr, amount], fn_name);
+
+            basic_type_from_layout(env, layout_interner, layout).const_zero()
+        }
+
+        RefCountDecRcPtr | RefCountDecDataPtr => {
+            arguments!(ptr, alignment);
+
+            let ptr_type = env.ptr_int().ptr_type(AddressSpace::default());
+            let ptr = cast_pointer_or_int(env, ptr, ptr_type.into(), "to_isize_ptr");
+
+            let fn_name = match op {
+                RefCountDecRcPtr => bitcode::UTILS_DECREF_RC_PTR,
+                _ => bitcode::UTILS_DECREF_DATA_PTR,
+            };
+            call_void_bitcode_fn(env, &[ptr, alignment], fn_name);
+
+            basic_type_from_layout(env, layout_interner, layout).const_zero()
         }
 
-        PtrWrite | RefCountIncRcPtr | RefCountDecRcPtr | RefCountIncDataPtr
-        | RefCountDecDataPtr => {
+        PtrWrite => {
             unreachable!("Not used in LLVM backend: {:?}", op);
         }
 
@@ -1524,13 +1565,41 @@ fn build_int_binop<'ctx>(
         NumBitwiseAnd => bd.build_and(lhs, rhs, "int_bitwise_and").into(),
         NumBitwiseXor => bd.build_xor(lhs, rhs, "int_bitwise_xor").into(),
         NumBitwiseOr => bd.build_or(lhs, rhs, "int_bitwise_or").into(),
-        NumShiftLeftBy => bd.build_left_shift(lhs, rhs, "int_shift_left").into(),
-        NumShiftRightBy => bd
-            .build_right_shift(lhs, rhs, true, "int_shift_right")
-            .into(),
-        NumShiftRightZfBy => bd
-            .build_right_shift(lhs, rhs, false, "int_shift_right_zf")
-            .into(),
+        NumShiftLeftBy | NumShiftRightBy | NumShiftRightZfBy => {
+            // LLVM leaves shifting by the bit width or more undefined, so clamp the amount:
+            // the logical shifts produce 0, and the arithmetic shift fills with the sign bit.
+            let int_type = lhs.get_type();
+            let bits = int_width.stack_size() as u64 * 8;
+            let width = int_type.const_int(bits, false);
+            let in_range = bd.build_int_compare(IntPredicate::ULT, rhs, width, "shift_in_range");
+
+            match op {
+                NumShiftLeftBy => {
+                    let shifted = bd.build_left_shift(lhs, rhs, "int_shift_left");
+
+                    bd.build_select(in_range, shifted, int_type.const_zero(), "int_shift_left")
+                }
+                NumShiftRightZfBy => {
+                    let shifted = bd.build_right_shift(lhs, rhs, false, "int_shift_right_zf");
+
+                    bd.build_select(
+                        in_range,
+                        shifted,
+                        int_type.const_zero(),
+                        "int_shift_right_zf",
+                    )
+                }
+                _ => {
+                    let max_amount = int_type.const_int(bits - 1, false);
+                    let amount = bd
+                        .build_select(in_range, rhs, max_amount, "shift_amount")
+                        .into_int_value();
+
+                    bd.build_right_shift(lhs, amount, true, "int_shift_right")
+                        .into()
+                }
+            }
+        }
 
         _ => {
             unreachable!("Unrecognized int binary operation: {:?}", op);
@@ -2682,6 +2751,36 @@ pub(crate) fn run_higher_order_low_level<'a, 'ctx>(
                 _ => unreachable!("invalid list layout"),
             }
         }
+        StrWalkUtf8 { string, state } => {
+            // Str.walkUtf8 : Str, state, (state, U8 -> state) -> state
+            let string = load_symbol(scope, string);
+            let (state, state_layout) = load_symbol_and_layout(scope, state);
+
+            let (function, closure, closure_layout) = function_details!();
+
+            let argument_layouts = &[state_layout, Layout::U8];
+
+            let roc_function_call = roc_function_call(
+                env,
+                layout_interner,
+                layout_ids,
+                function,
+                closure,
+                closure_layout,
+                function_owns_closure_data,
+                argument_layouts,
+                state_layout,
+            );
+
+            str_walk_utf8(
+                env,
+                layout_interner,
+                roc_function_call,
+                string,
+                state,
+                state_layout,
+            )
+        }
     }
 }
 
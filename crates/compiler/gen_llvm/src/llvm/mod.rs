@@ -6,6 +6,8 @@ pub mod compare;
 pub mod convert;
 mod expect;
 pub mod externs;
+pub mod heap_profile;
 mod intrinsics;
 mod lowlevel;
 pub mod refcounting;
+pub mod stack_limit;
@@ -24,6 +24,7 @@ use roc_mono::layout::{
 
 use super::build::{cast_if_necessary_for_opaque_recursive_pointers, load_roc_value, FunctionSpec};
 use super::convert::{argument_type_from_layout, argument_type_from_union_layout};
+use super::heap_profile;
 
 pub struct PointerToRefcount<'ctx> {
     value: PointerValue<'ctx>,
@@ -165,6 +166,8 @@ impl<'ctx> PointerToRefcount<'ctx> {
         env.builder.position_at_end(block);
         env.builder.set_current_debug_location(di_location);
 
+        heap_profile::call_dealloc_hook_if_unique(env, layout_interner, layout, self);
+
         let call = env
             .builder
             .build_call(function, &[refcount_ptr.into()], fn_name);
@@ -0,0 +1,83 @@
+//! Stack overflow checks, for `--stack-limit-checks`.
+//!
+//! Every proc starts by comparing the address of its stack frame with
+//!
+//! ```c
+//! uintptr_t roc_stack_limit(void);
+//! ```
+//!
+//! which the host defines to return the lowest address its stack may grow down to. A proc whose
+//! frame is below that crashes through `roc_panic` with a "stack overflow" message, instead of
+//! running into the guard page and segfaulting. Only proc entries are checked, so the limit
+//! should leave room for the frame of one proc and the builtins it calls.
+use inkwell::module::Linkage;
+use inkwell::values::FunctionValue;
+use inkwell::IntPredicate;
+
+use crate::llvm::build::{
+    add_func, throw_internal_exception, CCReturn, Env, FunctionSpec, C_CALL_CONV,
+};
+use crate::llvm::intrinsics::LLVM_FRAME_ADDRESS;
+
+pub const STACK_LIMIT: &str = "roc_stack_limit";
+
+/// Crash if the frame of `parent` is past the host's stack limit. The builder must be at the
+/// start of `parent`'s entry block, and is left at the start of the block that continues it.
+pub fn build_stack_limit_check<'ctx>(env: &Env<'_, 'ctx, '_>, parent: FunctionValue<'ctx>) {
+    if !env.stack_limit_checks {
+        return;
+    }
+
+    let builder = env.builder;
+
+    let frame_address = env.call_intrinsic(
+        LLVM_FRAME_ADDRESS,
+        &[env.context.i32_type().const_zero().into()],
+    );
+    let frame_address = builder.build_ptr_to_int(
+        frame_address.into_pointer_value(),
+        env.ptr_int(),
+        "frame_address",
+    );
+
+    let call = builder.build_call(get_hook(env), &[], STACK_LIMIT);
+    call.set_call_convention(C_CALL_CONV);
+
+    let limit = call
+        .try_as_basic_value()
+        .left()
+        .expect("roc_stack_limit returns the limit")
+        .into_int_value();
+
+    // the stack grows down on every target we support
+    let overflowed =
+        builder.build_int_compare(IntPredicate::ULT, frame_address, limit, "stack_overflowed");
+
+    let overflow_block = env.context.append_basic_block(parent, "stack_overflow");
+    let cont_block = env.context.append_basic_block(parent, "stack_ok");
+
+    builder.build_conditional_branch(overflowed, overflow_block, cont_block);
+
+    builder.position_at_end(overflow_block);
+    throw_internal_exception(env, parent, "stack overflow");
+
+    builder.position_at_end(cont_block);
+}
+
+fn get_hook<'ctx>(env: &Env<'_, 'ctx, '_>) -> FunctionValue<'ctx> {
+    match env.module.get_function(STACK_LIMIT) {
+        Some(function) => function,
+        None => {
+            let fn_spec =
+                FunctionSpec::cconv(env, CCReturn::Return, Some(env.ptr_int().into()), &[]);
+
+            add_func(
+                env.context,
+                env.module,
+                STACK_LIMIT,
+                fn_spec,
+                Linkage::External,
+            )
+        }
+    }
+}
@@ -31,8 +31,8 @@ use crate::layout::{CallConv, ReturnMethod, WasmLayout};
 use crate::low_level::{call_higher_order_lowlevel, LowLevelCall};
 use crate::storage::{AddressValue, Storage, StoredValue, StoredVarKind};
 use crate::{
-    copy_memory, CopyMemoryConfig, Env, DEBUG_SETTINGS, MEMORY_NAME, PTR_SIZE, PTR_TYPE,
-    TARGET_INFO,
+    copy_memory, zero_memory, CopyMemoryConfig, Env, DEBUG_SETTINGS, MEMORY_NAME, PTR_SIZE,
+    PTR_TYPE, TARGET_INFO, WASI_IMPORT_MODULE_NAME,
 };
 
 #[derive(Clone, Copy, Debug)]
@@ -42,6 +42,40 @@ pub enum ProcSource {
     /// Wrapper function for higher-order calls from Zig to Roc
     HigherOrderMapper(usize),
     HigherOrderCompare(usize),
+    /// Wrapper for a host-exposed Roc procedure, returning by pointer
+    ExposedGeneric(usize),
+    /// Definition of a hook that the host leaves undefined, for hosts that run under WASI
+    WasiHook(WasiHook),
+}
+
+/// Functions that every platform host must provide to the app
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WasiHook {
+    Alloc,
+    Realloc,
+    Dealloc,
+    Memcpy,
+    Panic,
+}
+
+impl WasiHook {
+    pub const ALL: [Self; 5] = [
+        Self::Alloc,
+        Self::Realloc,
+        Self::Dealloc,
+        Self::Memcpy,
+        Self::Panic,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Alloc => "roc_alloc",
+            Self::Realloc => "roc_realloc",
+            Self::Dealloc => "roc_dealloc",
+            Self::Memcpy => "roc_memcpy",
+            Self::Panic => "roc_panic",
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -99,8 +133,6 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
             )
         });
 
-        let host_lookup = module.get_host_function_lookup(env.arena);
-
         if module.names.function_names.is_empty() {
             module.names = NameSection::from_imports_and_linking_data(
                 env.arena,
@@ -110,6 +142,20 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
         }
 
         module.link_host_to_app_calls(env.arena, host_to_app_map);
+
+        // Linking moves imports around, so only look up host functions once it's done.
+        // Hooks we define ourselves come first, to take priority over the host's imports of them.
+        let mut host_lookup = Vec::from_iter_in(
+            proc_lookup
+                .iter()
+                .enumerate()
+                .filter_map(|(i, proc)| match proc.source {
+                    ProcSource::WasiHook(hook) => Some((hook.name(), fn_index_offset + i as u32)),
+                    _ => None,
+                }),
+            env.arena,
+        );
+        host_lookup.extend(module.get_host_function_lookup(env.arena));
         let import_fn_count = module.import.function_count();
         let host_function_count = import_fn_count
             + module.code.dead_import_dummy_count as usize
@@ -136,7 +182,7 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
             // Function-level data
             block_depth: 0,
             joinpoint_label_map: MutMap::default(),
-            code_builder: CodeBuilder::new(env.arena),
+            code_builder: CodeBuilder::new(env.arena, env.features),
             storage: Storage::new(env.arena),
         }
     }
@@ -493,7 +539,6 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
         wrapper_lookup_idx: usize,
         inner_lookup_idx: usize,
     ) {
-        use Align::*;
         use ValueType::*;
 
         let ProcLookupData {
@@ -584,17 +629,7 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
 
         // If the inner function returns a primitive, store it to the address we loaded at the very beginning
         if let Some((ty, size)) = ret_type_and_size {
-            match (ty, size) {
-                (I64, 8) => self.code_builder.i64_store(Bytes8, 0),
-                (I32, 4) => self.code_builder.i32_store(Bytes4, 0),
-                (I32, 2) => self.code_builder.i32_store16(Bytes2, 0),
-                (I32, 1) => self.code_builder.i32_store8(Bytes1, 0),
-                (F32, 4) => self.code_builder.f32_store(Bytes4, 0),
-                (F64, 8) => self.code_builder.f64_store(Bytes8, 0),
-                _ => {
-                    internal_error!("Cannot store {:?} with alignment of {:?}", ty, size);
-                }
-            }
+            self.store_returned_primitive(ty, size);
         }
 
         // Write empty function header (local variables array with zero length)
@@ -609,6 +644,362 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
         self.reset();
     }
 
+    /// Store a primitive return value to an address loaded before the call
+    fn store_returned_primitive(&mut self, ty: ValueType, size: u32) {
+        use Align::*;
+        use ValueType::*;
+
+        match (ty, size) {
+            (I64, 8) => self.code_builder.i64_store(Bytes8, 0),
+            (I32, 4) => self.code_builder.i32_store(Bytes4, 0),
+            (I32, 2) => self.code_builder.i32_store16(Bytes2, 0),
+            (I32, 1) => self.code_builder.i32_store8(Bytes1, 0),
+            (F32, 4) => self.code_builder.f32_store(Bytes4, 0),
+            (F64, 8) => self.code_builder.f64_store(Bytes8, 0),
+            _ => {
+                internal_error!("Cannot store {:?} with alignment of {:?}", ty, size);
+            }
+        }
+    }
+
+    /// Build the `_exposed_generic` wrapper for a host-exposed Roc procedure, like the LLVM and
+    /// dev backends do. It takes the same arguments, plus a pointer to write the result to.
+    ///
+    /// Under the C calling convention, small results are Wasm return values while larger ones
+    /// and 128-bit numbers are written to a pointer passed as the first argument. With this
+    /// wrapper, the host doesn't have to know which is which.
+    pub fn build_exposed_generic(&mut self, wrapper_lookup_idx: usize, inner_lookup_idx: usize) {
+        let ProcLookupData {
+            name: wrapper_name,
+            layout: proc_layout,
+            ..
+        } = self.proc_lookup[wrapper_lookup_idx];
+
+        let mut param_types =
+            Vec::with_capacity_in(proc_layout.arguments.len() + 1, self.env.arena);
+        for arg in proc_layout.arguments {
+            let arg_types = WasmLayout::new(self.layout_interner, *arg).arg_types(CallConv::C);
+            param_types.extend_from_slice(arg_types);
+        }
+        let return_ptr_id = LocalId(param_types.len() as u32);
+
+        let mut n_inner_wasm_args = param_types.len();
+        let ret_layout = WasmLayout::new(self.layout_interner, proc_layout.result);
+        let ret_type_and_size = match ret_layout.return_method(CallConv::C) {
+            ReturnMethod::NoReturnValue => None,
+            ReturnMethod::Primitive(ty, size) => {
+                // Load the address now, so it's under the return value after the call
+                self.code_builder.get_local(return_ptr_id);
+                Some((ty, size))
+            }
+            ReturnMethod::WriteToPointerArg => {
+                // The inner function can write straight to the host's pointer
+                self.code_builder.get_local(return_ptr_id);
+                n_inner_wasm_args += 1;
+                None
+            }
+            x => internal_error!("A Roc function should never use ReturnMethod {:?}", x),
+        };
+
+        for i in 0..param_types.len() {
+            self.code_builder.get_local(LocalId(i as u32));
+        }
+
+        let inner_wasm_fn_index = self.fn_index_offset + inner_lookup_idx as u32;
+        let has_return_val = ret_type_and_size.is_some();
+        self.code_builder
+            .call(inner_wasm_fn_index, n_inner_wasm_args, has_return_val);
+
+        if let Some((ty, size)) = ret_type_and_size {
+            self.store_returned_primitive(ty, size);
+        }
+
+        self.code_builder.build_fn_header_and_footer(&[], 0, None);
+
+        param_types.push(PTR_TYPE);
+        self.module.add_function_signature(Signature {
+            param_types,
+            ret_type: None,
+        });
+
+        self.append_proc_debug_name(wrapper_name);
+        self.reset();
+    }
+
+    /// Define a hook that the host doesn't, using WASI imports where we need the outside world
+    pub fn build_wasi_hook(&mut self, lookup_idx: usize, hook: WasiHook) {
+        use ValueType::*;
+
+        let hook_name = self.proc_lookup[lookup_idx].name;
+
+        let (param_types, ret_type, local_types): (&[ValueType], _, &[ValueType]) = match hook {
+            WasiHook::Alloc => {
+                // A bump allocator that never frees. Programs run by the CLI are short-lived.
+                // The heap starts wherever memory ends at the first allocation.
+                let (size, alignment) = (LocalId(0), LocalId(1));
+                let (ptr, new_top, end) = (LocalId(2), LocalId(3), LocalId(4));
+                let top_addr = self.reserve_data(PTR_SIZE) as i32;
+
+                self.code_builder.i32_const(top_addr);
+                self.code_builder.i32_load(Align::Bytes4, 0);
+                self.code_builder.set_local(ptr);
+                self.code_builder.get_local(ptr);
+                self.code_builder.i32_eqz();
+                self.code_builder.if_();
+                self.load_memory_end();
+                self.code_builder.set_local(ptr);
+                self.code_builder.end();
+
+                // ptr = (ptr + alignment - 1) & -alignment
+                self.code_builder.get_local(ptr);
+                self.code_builder.get_local(alignment);
+                self.code_builder.i32_add();
+                self.code_builder.i32_const(1);
+                self.code_builder.i32_sub();
+                self.code_builder.i32_const(0);
+                self.code_builder.get_local(alignment);
+                self.code_builder.i32_sub();
+                self.code_builder.i32_and();
+                self.code_builder.tee_local(ptr);
+                self.code_builder.get_local(size);
+                self.code_builder.i32_add();
+                self.code_builder.set_local(new_top);
+
+                self.load_memory_end();
+                self.code_builder.set_local(end);
+                self.code_builder.get_local(new_top);
+                self.code_builder.get_local(end);
+                self.code_builder.i32_gt_u();
+                self.code_builder.if_();
+                {
+                    // Grow by enough whole pages to fit the new top
+                    self.code_builder.get_local(new_top);
+                    self.code_builder.get_local(end);
+                    self.code_builder.i32_sub();
+                    self.code_builder
+                        .i32_const(MemorySection::PAGE_SIZE as i32 - 1);
+                    self.code_builder.i32_add();
+                    self.code_builder.i32_const(16);
+                    self.code_builder.i32_shr_u();
+                    self.code_builder.memory_grow();
+                    self.code_builder.i32_const(-1);
+                    self.code_builder.i32_eq();
+                    self.code_builder.if_();
+                    self.code_builder.unreachable_();
+                    self.code_builder.end();
+                }
+                self.code_builder.end();
+
+                self.code_builder.i32_const(top_addr);
+                self.code_builder.get_local(new_top);
+                self.code_builder.i32_store(Align::Bytes4, 0);
+                self.code_builder.get_local(ptr);
+
+                (&[I32, I32], Some(I32), &[I32, I32, I32])
+            }
+            WasiHook::Realloc => {
+                let (ptr, new_size, old_size, alignment) =
+                    (LocalId(0), LocalId(1), LocalId(2), LocalId(3));
+                let (new_ptr, n) = (LocalId(4), LocalId(5));
+
+                self.code_builder.get_local(new_size);
+                self.code_builder.get_local(alignment);
+                self.call_host_fn_after_loading_args(WasiHook::Alloc.name(), 2, true);
+                self.code_builder.set_local(new_ptr);
+
+                // n = min(new_size, old_size)
+                self.code_builder.get_local(new_size);
+                self.code_builder.get_local(old_size);
+                self.code_builder.get_local(new_size);
+                self.code_builder.get_local(old_size);
+                self.code_builder.i32_lt_u();
+                self.code_builder.select();
+                self.code_builder.set_local(n);
+
+                self.copy_bytes(new_ptr, ptr, n);
+                self.code_builder.get_local(new_ptr);
+
+                (&[I32, I32, I32, I32], Some(I32), &[I32, I32])
+            }
+            WasiHook::Dealloc => (&[I32, I32], None, &[]),
+            WasiHook::Memcpy => {
+                self.copy_bytes(LocalId(0), LocalId(1), LocalId(2));
+                (&[I32, I32, I32], None, &[])
+            }
+            WasiHook::Panic => {
+                self.build_wasi_panic();
+                (&[I32, I32], None, &[I32, I32])
+            }
+        };
+
+        self.code_builder
+            .build_fn_header_and_footer(local_types, 0, None);
+
+        self.module.add_function_signature(Signature {
+            param_types: Vec::from_iter_in(param_types.iter().copied(), self.env.arena),
+            ret_type,
+        });
+
+        self.append_proc_debug_name(hook_name);
+        self.reset();
+    }
+
+    /// Write the panic message to stderr and exit. Arguments are (msg: *RocStr, tag_id: u32)
+    fn build_wasi_panic(&mut self) {
+        use ValueType::*;
+
+        let msg = LocalId(0);
+        let (bytes, len) = (LocalId(2), LocalId(3));
+
+        let fd_write = self.wasi_import("fd_write", &[I32, I32, I32, I32], Some(I32));
+        let proc_exit = self.wasi_import("proc_exit", &[I32], None);
+
+        if let (Some(fd_write), Some(proc_exit)) = (fd_write, proc_exit) {
+            // A small string has the top bit of its capacity set, and its length in the last byte
+            self.code_builder.get_local(msg);
+            self.code_builder.i32_load(Align::Bytes4, 8);
+            self.code_builder.i32_const(0);
+            self.code_builder.i32_lt_s();
+            self.code_builder.if_();
+            {
+                self.code_builder.get_local(msg);
+                self.code_builder.set_local(bytes);
+                self.code_builder.get_local(msg);
+                self.code_builder.i32_load8_u(Align::Bytes1, 11);
+                self.code_builder.i32_const(0x7f);
+                self.code_builder.i32_and();
+                self.code_builder.set_local(len);
+            }
+            self.code_builder.else_();
+            {
+                self.code_builder.get_local(msg);
+                self.code_builder.i32_load(Align::Bytes4, 0);
+                self.code_builder.set_local(bytes);
+                self.code_builder.get_local(msg);
+                self.code_builder.i32_load(Align::Bytes4, 4);
+                self.code_builder.set_local(len);
+            }
+            self.code_builder.end();
+
+            // Two iovecs for the message and a newline, then the count of bytes written,
+            // then the newline itself
+            let scratch = self.reserve_data(24) as i32;
+            self.code_builder.i32_const(scratch);
+            self.code_builder.get_local(bytes);
+            self.code_builder.i32_store(Align::Bytes4, 0);
+            self.code_builder.i32_const(scratch);
+            self.code_builder.get_local(len);
+            self.code_builder.i32_store(Align::Bytes4, 4);
+            self.code_builder.i32_const(scratch);
+            self.code_builder.i32_const(scratch + 20);
+            self.code_builder.i32_store(Align::Bytes4, 8);
+            self.code_builder.i32_const(scratch);
+            self.code_builder.i32_const(1);
+            self.code_builder.i32_store(Align::Bytes4, 12);
+            self.code_builder.i32_const(scratch);
+            self.code_builder.i32_const(b'\n' as i32);
+            self.code_builder.i32_store8(Align::Bytes1, 20);
+
+            const STDERR: i32 = 2;
+            self.code_builder.i32_const(STDERR);
+            self.code_builder.i32_const(scratch);
+            self.code_builder.i32_const(2);
+            self.code_builder.i32_const(scratch + 16);
+            self.code_builder.call_import(fd_write, 4, true);
+            self.code_builder.drop_();
+
+            self.code_builder.i32_const(1);
+            self.code_builder.call_import(proc_exit, 1, false);
+        }
+
+        // Either proc_exit didn't return, or we had no way to report the panic
+        self.code_builder.unreachable_();
+    }
+
+    /// Copy `n` bytes from `src` to `dst`. Modifies all three locals.
+    fn copy_bytes(&mut self, dst: LocalId, src: LocalId, n: LocalId) {
+        if self.env.features.bulk_memory {
+            self.code_builder.get_local(dst);
+            self.code_builder.get_local(src);
+            self.code_builder.get_local(n);
+            self.code_builder.memory_copy();
+            return;
+        }
+
+        self.code_builder.block();
+        self.code_builder.loop_();
+        {
+            self.code_builder.get_local(n);
+            self.code_builder.i32_eqz();
+            self.code_builder.br_if(1);
+
+            self.code_builder.get_local(dst);
+            self.code_builder.get_local(src);
+            self.code_builder.i32_load8_u(Align::Bytes1, 0);
+            self.code_builder.i32_store8(Align::Bytes1, 0);
+
+            for (local, step) in [(dst, 1), (src, 1), (n, -1)] {
+                self.code_builder.get_local(local);
+                self.code_builder.i32_const(step);
+                self.code_builder.i32_add();
+                self.code_builder.set_local(local);
+            }
+            self.code_builder.br(0);
+        }
+        self.code_builder.end();
+        self.code_builder.end();
+    }
+
+    /// Push the address of the end of memory
+    fn load_memory_end(&mut self) {
+        self.code_builder.memory_size();
+        self.code_builder.i32_const(16); // log2(PAGE_SIZE)
+        self.code_builder.i32_shl();
+    }
+
+    /// Reserve some zeroed memory after the constant data, for a hook's own variables
+    fn reserve_data(&mut self, size: u32) -> u32 {
+        let addr = round_up_to_alignment!(self.module.data.end_addr, 8u32);
+        self.module.data.end_addr = addr + size;
+        addr
+    }
+
+    /// Find or create an import from WASI, if there's a free slot for it in the function index space
+    fn wasi_import(
+        &mut self,
+        name: &'a str,
+        param_types: &[ValueType],
+        ret_type: Option<ValueType>,
+    ) -> Option<u32> {
+        let existing = self
+            .module
+            .import
+            .imports
+            .iter()
+            .filter(|import| import.is_function())
+            .position(|import| import.module == WASI_IMPORT_MODULE_NAME && import.name == name);
+
+        let fn_index = match existing {
+            Some(index) => index as u32,
+            None => {
+                let signature = Signature {
+                    param_types: Vec::from_iter_in(param_types.iter().copied(), self.env.arena),
+                    ret_type,
+                };
+                let index = self.module.import_fn_in_dummy_slot(
+                    WASI_IMPORT_MODULE_NAME,
+                    name,
+                    signature,
+                )?;
+                self.import_fn_count += 1;
+                index
+            }
+        };
+
+        self.called_fns.set(fn_index as usize, true);
+        Some(fn_index)
+    }
+
     /// Build a wrapper around a Roc comparison proc so that it can be called from higher-order Zig builtins.
     /// Comparison procedure signature is: closure_data, a, b -> Order (u8)
     ///
@@ -1003,6 +1394,10 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
     }
 
     pub fn stmt_internal_error(&mut self, msg: &'a str) {
+        self.stmt_crash_with_message(msg, CrashTag::Roc);
+    }
+
+    fn stmt_crash_with_message(&mut self, msg: &'a str, tag: CrashTag) {
         let msg_sym = self.create_symbol("panic_str");
         let msg_storage = self.storage.allocate_var(
             self.layout_interner,
@@ -1020,7 +1415,7 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
         };
         self.expr_string_literal(msg, local_id, offset);
 
-        self.stmt_crash(msg_sym, CrashTag::Roc);
+        self.stmt_crash(msg_sym, tag);
     }
 
     pub fn stmt_crash(&mut self, msg: Symbol, tag: CrashTag) {
@@ -1607,12 +2002,7 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
 
             // Store 12 bytes of zeros { elements: null, length: 0, capacity: 0 }
             debug_assert_eq!(Builtin::LIST_WORDS, 3);
-            self.code_builder.get_local(local_id);
-            self.code_builder.i64_const(0);
-            self.code_builder.i64_store(Align::Bytes4, offset);
-            self.code_builder.get_local(local_id);
-            self.code_builder.i32_const(0);
-            self.code_builder.i32_store(Align::Bytes4, offset + 8);
+            zero_memory(&mut self.code_builder, local_id, offset, 12, 4);
         } else {
             internal_error!("Unexpected storage for {:?}", sym)
         }
@@ -1981,7 +2371,14 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
         let local_id = self.storage.create_anonymous_local(ValueType::I32);
         self.code_builder.tee_local(local_id);
 
+        // Crash, like the builtins do, rather than write the refcount to address 0
+        self.code_builder.i32_eqz();
+        self.code_builder.if_();
+        self.stmt_crash_with_message("Out of memory", CrashTag::OutOfMemory);
+        self.code_builder.end();
+
         // Write the initial refcount
+        self.code_builder.get_local(local_id);
         let refcount_offset = extra_bytes - PTR_SIZE;
         let encoded_refcount = (initial_refcount as i32) - 1 + i32::MIN;
         self.code_builder.i32_const(encoded_refcount);
@@ -5,14 +5,14 @@ use roc_wasm_module::linking::IndexRelocType;
 
 use roc_error_macros::internal_error;
 use roc_module::symbol::Symbol;
-use roc_wasm_module::opcodes::{OpCode, OpCode::*};
+use roc_wasm_module::opcodes::{MiscOpCode, OpCode, OpCode::*, SimdOpCode};
 use roc_wasm_module::serialize::SerialBuffer;
 use roc_wasm_module::{
     round_up_to_alignment, Align, LocalId, RelocationEntry, ValueType, WasmModule,
     FRAME_ALIGNMENT_BYTES, STACK_POINTER_GLOBAL_ID,
 };
 
-use crate::DEBUG_SETTINGS;
+use crate::{WasmFeatures, DEBUG_SETTINGS};
 
 macro_rules! log_instruction {
     ($($x: expr),+) => {
@@ -74,10 +74,22 @@ macro_rules! instruction_memargs {
     };
 }
 
+macro_rules! instruction_simd {
+    ($method_name: ident, $opcode: expr, $pops: expr, $push: expr) => {
+        pub fn $method_name(&mut self) {
+            debug_assert!(self.features.simd);
+            self.inst_prefixed(SIMDPREFIX, $opcode as u32, $pops, $push);
+        }
+    };
+}
+
 #[derive(Debug)]
 pub struct CodeBuilder<'a> {
     pub arena: &'a Bump,
 
+    /// Optional Wasm proposals we're allowed to generate instructions from
+    pub features: WasmFeatures,
+
     /// The main container for the instructions
     code: Vec<'a, u8>,
 
@@ -110,7 +122,7 @@ pub struct CodeBuilder<'a> {
 
 #[allow(clippy::new_without_default)]
 impl<'a> CodeBuilder<'a> {
-    pub fn new(arena: &'a Bump) -> Self {
+    pub fn new(arena: &'a Bump, features: WasmFeatures) -> Self {
         let mut vm_block_stack = Vec::with_capacity_in(8, arena);
         let function_block = VmBlock {
             opcode: BLOCK,
@@ -120,6 +132,7 @@ impl<'a> CodeBuilder<'a> {
 
         CodeBuilder {
             arena,
+            features,
             code: Vec::with_capacity_in(1024, arena),
             insertions: Vec::with_capacity_in(32, arena),
             insert_bytes: Vec::with_capacity_in(64, arena),
@@ -565,6 +578,18 @@ impl<'a> CodeBuilder<'a> {
         );
     }
 
+    /// Instruction from a post-MVP proposal: a prefix byte followed by a LEB-encoded sub-opcode
+    fn inst_prefixed(&mut self, prefix: OpCode, sub_opcode: u32, pops: usize, push: bool) {
+        self.inst_base(prefix, pops, push);
+        self.code.encode_u32(sub_opcode);
+        log_instruction!(
+            "{:10} {}\t{:?}",
+            format!("{:?}", prefix),
+            sub_opcode,
+            self.vm_block_stack
+        );
+    }
+
     /**********************************************************
 
         INSTRUCTION METHODS
@@ -718,6 +743,17 @@ impl<'a> CodeBuilder<'a> {
         self.inst(GROWMEMORY, 1, true);
         self.code.push(0);
     }
+    pub fn memory_copy(&mut self) {
+        debug_assert!(self.features.bulk_memory);
+        self.inst_prefixed(MISCPREFIX, MiscOpCode::MEMORYCOPY as u32, 3, false);
+        self.code.push(0); // destination memory index
+        self.code.push(0); // source memory index
+    }
+    pub fn memory_fill(&mut self) {
+        debug_assert!(self.features.bulk_memory);
+        self.inst_prefixed(MISCPREFIX, MiscOpCode::MEMORYFILL as u32, 3, false);
+        self.code.push(0); // memory index
+    }
 
     fn log_const<T>(&self, opcode: OpCode, x: T)
     where
@@ -876,4 +912,24 @@ impl<'a> CodeBuilder<'a> {
     instruction_no_args!(i64_reinterpret_f64, I64REINTERPRETF64, 1, true);
     instruction_no_args!(f32_reinterpret_i32, F32REINTERPRETI32, 1, true);
     instruction_no_args!(f64_reinterpret_i64, F64REINTERPRETI64, 1, true);
+
+    // SIMD proposal
+    pub fn v128_load(&mut self, align: Align, offset: u32) {
+        debug_assert!(self.features.simd);
+        self.inst_prefixed(SIMDPREFIX, SimdOpCode::V128LOAD as u32, 1, true);
+        self.code.push(align as u8);
+        self.code.encode_u32(offset);
+    }
+    pub fn v128_store(&mut self, align: Align, offset: u32) {
+        debug_assert!(self.features.simd);
+        self.inst_prefixed(SIMDPREFIX, SimdOpCode::V128STORE as u32, 2, false);
+        self.code.push(align as u8);
+        self.code.encode_u32(offset);
+    }
+    instruction_simd!(i8x16_eq, SimdOpCode::I8X16EQ, 2, true);
+    instruction_simd!(v128_not, SimdOpCode::V128NOT, 1, true);
+    instruction_simd!(v128_and, SimdOpCode::V128AND, 2, true);
+    instruction_simd!(v128_or, SimdOpCode::V128OR, 2, true);
+    instruction_simd!(v128_xor, SimdOpCode::V128XOR, 2, true);
+    instruction_simd!(i8x16_all_true, SimdOpCode::I8X16ALLTRUE, 1, true);
 }
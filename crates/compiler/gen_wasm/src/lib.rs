@@ -14,14 +14,17 @@ use bumpalo::collections::Vec;
 use bumpalo::{self, Bump};
 
 use roc_collections::all::{MutMap, MutSet};
+use roc_error_macros::internal_error;
 use roc_module::symbol::{Interns, ModuleId, Symbol};
 use roc_mono::code_gen_help::CodeGenHelp;
 use roc_mono::ir::{Proc, ProcLayout};
-use roc_mono::layout::{LayoutIds, STLayoutInterner};
+use roc_mono::layout::{Layout, LayoutIds, Niche, STLayoutInterner};
 use roc_target::TargetInfo;
+use roc_wasm_module::linking::{WasmObjectSymbol, WASM_SYM_UNDEFINED};
 use roc_wasm_module::parse::ParseError;
-use roc_wasm_module::{Align, LocalId, ValueType, WasmModule};
+use roc_wasm_module::{Align, LocalId, SymInfo, ValueType, WasmModule};
 
+pub use crate::backend::WasiHook;
 use crate::backend::{ProcLookupData, ProcSource, WasmBackend};
 use crate::code_builder::CodeBuilder;
 
@@ -39,6 +42,7 @@ const PTR_TYPE: ValueType = ValueType::I32;
 
 pub const MEMORY_NAME: &str = "memory";
 pub const BUILTINS_IMPORT_MODULE_NAME: &str = "env";
+pub const WASI_IMPORT_MODULE_NAME: &str = "wasi_snapshot_preview1";
 pub const STACK_POINTER_NAME: &str = "__stack_pointer";
 
 pub struct Env<'a> {
@@ -46,12 +50,55 @@ pub struct Env<'a> {
     pub module_id: ModuleId,
     pub exposed_to_host: MutSet<Symbol>,
     pub stack_bytes: u32,
+    pub features: WasmFeatures,
+    /// Define the hooks in [WasiHook] that the host leaves undefined, on top of WASI imports,
+    /// for hosts that run in a WASI runtime rather than next to JavaScript that provides them
+    pub wasi_hooks: bool,
 }
 
 impl Env<'_> {
     pub const DEFAULT_STACK_BYTES: u32 = 1024 * 1024;
 }
 
+/// Wasm proposals that the generated code is allowed to use, on top of the MVP instruction set.
+/// Everything has a fallback, so these are all off by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WasmFeatures {
+    /// `memory.copy` and `memory.fill`
+    pub bulk_memory: bool,
+    /// 128-bit vector instructions
+    pub simd: bool,
+}
+
+impl WasmFeatures {
+    pub const NAMES: [&'static str; 2] = ["bulk-memory", "simd"];
+}
+
+impl std::str::FromStr for WasmFeatures {
+    type Err = String;
+
+    /// Parses a comma-separated list like `bulk-memory,simd`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut features = WasmFeatures::default();
+
+        for name in s.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            match name {
+                "bulk-memory" => features.bulk_memory = true,
+                "simd" => features.simd = true,
+                _ => {
+                    return Err(format!(
+                        "`{}` is not a Wasm feature I know about. The options are: {}",
+                        name,
+                        Self::NAMES.join(", ")
+                    ))
+                }
+            }
+        }
+
+        Ok(features)
+    }
+}
+
 /// Parse the preprocessed host binary
 /// If successful, the module can be passed to build_app_binary
 pub fn parse_host<'a>(arena: &'a Bump, host_bytes: &[u8]) -> Result<WasmModule<'a>, ParseError> {
@@ -71,14 +118,47 @@ pub fn build_app_binary<'a, 'r>(
     host_module: WasmModule<'a>,
     procedures: MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
 ) -> std::vec::Vec<u8> {
+    build_app_binary_with_offsets(env, layout_interner, interns, host_module, procedures).0
+}
+
+/// Like [build_app_binary], but also returns where the code of each Roc proc starts, as a byte
+/// offset from the start of the binary. Used to map the binary back to Roc source for debuggers.
+pub fn build_app_binary_with_offsets<'a, 'r>(
+    env: &'r Env<'a>,
+    layout_interner: &'r mut STLayoutInterner<'a>,
+    interns: &'r mut Interns,
+    host_module: WasmModule<'a>,
+    procedures: MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) -> (std::vec::Vec<u8>, std::vec::Vec<(Symbol, u32)>) {
+    // build_sorted_app_module gives the Roc procs consecutive indices after the host's functions,
+    // in the order they are given
+    let procedures = sort_procedures(layout_interner, interns, procedures);
+    let fn_index_offset: u32 =
+        host_module.import.function_count() as u32 + host_module.code.function_count;
+    let proc_fn_indices: std::vec::Vec<(Symbol, u32)> = procedures
+        .iter()
+        .enumerate()
+        .map(|(i, ((sym, _), _))| (*sym, fn_index_offset + i as u32))
+        .collect();
+
     let (mut wasm_module, called_fns, _) =
-        build_app_module(env, layout_interner, interns, host_module, procedures);
+        build_sorted_app_module(env, layout_interner, interns, host_module, procedures);
 
     wasm_module.eliminate_dead_code(env.arena, called_fns);
 
     let mut buffer = std::vec::Vec::with_capacity(wasm_module.size());
     wasm_module.serialize(&mut buffer);
-    buffer
+
+    // Dead imports were replaced with dummy functions, so function indices are unchanged
+    let import_fn_count = wasm_module.import.function_count() as u32;
+    let body_offsets = WasmModule::function_body_offsets(&buffer)
+        .unwrap_or_else(|e| internal_error!("Generated an invalid Wasm module: {}", e.message));
+    let proc_offsets = proc_fn_indices
+        .into_iter()
+        .map(|(sym, fn_index)| (sym, body_offsets[(fn_index - import_fn_count) as usize]))
+        .collect();
+
+    (buffer, proc_offsets)
 }
 
 /// Generate an unserialized Wasm module
@@ -91,12 +171,40 @@ pub fn build_app_module<'a, 'r>(
     interns: &'r mut Interns,
     host_module: WasmModule<'a>,
     procedures: MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) -> (WasmModule<'a>, BitVec<usize>, u32) {
+    let procedures = sort_procedures(layout_interner, interns, procedures);
+
+    build_sorted_app_module(env, layout_interner, interns, host_module, procedures)
+}
+
+/// The procs in the order to generate them in, which doesn't depend on how they were numbered,
+/// so that the same program always produces the same binary
+fn sort_procedures<'a>(
+    layout_interner: &STLayoutInterner<'a>,
+    interns: &Interns,
+    procedures: MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) -> std::vec::Vec<((Symbol, ProcLayout<'a>), Proc<'a>)> {
+    let mut procedures: std::vec::Vec<_> = procedures.into_iter().collect();
+    procedures.sort_by_cached_key(|((symbol, layout), _)| {
+        roc_mono::ir::proc_sort_key(interns, layout_interner, *symbol, layout)
+    });
+
+    procedures
+}
+
+fn build_sorted_app_module<'a, 'r>(
+    env: &'r Env<'a>,
+    layout_interner: &'r mut STLayoutInterner<'a>,
+    interns: &'r mut Interns,
+    host_module: WasmModule<'a>,
+    procedures: std::vec::Vec<((Symbol, ProcLayout<'a>), Proc<'a>)>,
 ) -> (WasmModule<'a>, BitVec<usize>, u32) {
     let mut layout_ids = LayoutIds::default();
     let mut procs = Vec::with_capacity_in(procedures.len(), env.arena);
     let mut proc_lookup = Vec::with_capacity_in(procedures.len() * 2, env.arena);
     let mut host_to_app_map = Vec::with_capacity_in(env.exposed_to_host.len(), env.arena);
     let mut maybe_main_fn_index = None;
+    let mut exposed_generic = Vec::with_capacity_in(env.exposed_to_host.len(), env.arena);
 
     // Adjust Wasm function indices to account for functions from the object file
     let fn_index_offset: u32 =
@@ -117,6 +225,12 @@ pub fn build_app_module<'a, 'r>(
             let exposed_name_bump: &'a str = env.arena.alloc_str(&exposed_name);
 
             host_to_app_map.push((exposed_name_bump, fn_index));
+
+            let exposed_generic_name = layout_ids
+                .get_toplevel(sym, &proc_layout)
+                .to_exposed_generic_symbol_string(sym, interns);
+
+            exposed_generic.push((i, sym, proc_layout, exposed_generic_name));
         }
 
         proc_lookup.push(ProcLookupData {
@@ -126,6 +240,52 @@ pub fn build_app_module<'a, 'r>(
         });
     }
 
+    // Host-exposed procs also get a wrapper that returns by writing to a pointer, whatever the
+    // return type. They go after the Roc procs, since those are generated first, in order.
+    let ident_ids = interns.all_ident_ids.get_mut(&env.module_id).unwrap();
+    for (inner_idx, sym, proc_layout, exposed_generic_name) in exposed_generic {
+        let fn_index = fn_index_offset + proc_lookup.len() as u32;
+        let exposed_generic_name_bump: &'a str = env.arena.alloc_str(&exposed_generic_name);
+        host_to_app_map.push((exposed_generic_name_bump, fn_index));
+
+        let wrapper_ident = ident_ids.add_str(&format!("#exposed_generic#{:?}", sym));
+        proc_lookup.push(ProcLookupData {
+            name: Symbol::new(env.module_id, wrapper_ident),
+            layout: proc_layout,
+            source: ProcSource::ExposedGeneric(inner_idx),
+        });
+    }
+
+    // The hooks go last, since they don't depend on anything else
+    if env.wasi_hooks {
+        for hook in WasiHook::ALL {
+            if host_defines_function(&host_module, hook.name()) {
+                continue;
+            }
+
+            let fn_index = fn_index_offset + proc_lookup.len() as u32;
+            let host_calls_hook = host_module
+                .import
+                .imports
+                .iter()
+                .any(|import| import.is_function() && import.name == hook.name());
+            if host_calls_hook {
+                host_to_app_map.push((hook.name(), fn_index));
+            }
+
+            let hook_ident = ident_ids.add_str(&format!("#wasi_hook#{}", hook.name()));
+            proc_lookup.push(ProcLookupData {
+                name: Symbol::new(env.module_id, hook_ident),
+                layout: ProcLayout {
+                    arguments: &[],
+                    result: Layout::UNIT,
+                    niche: Niche::NONE,
+                },
+                source: ProcSource::WasiHook(hook),
+            });
+        }
+    }
+
     let mut backend = WasmBackend::new(
         env,
         layout_interner,
@@ -180,6 +340,8 @@ pub fn build_app_module<'a, 'r>(
             Helper => backend.build_proc(helper_iter.next().unwrap()),
             HigherOrderMapper(inner_idx) => backend.build_higher_order_mapper(idx, *inner_idx),
             HigherOrderCompare(inner_idx) => backend.build_higher_order_compare(idx, *inner_idx),
+            ExposedGeneric(inner_idx) => backend.build_exposed_generic(idx, *inner_idx),
+            WasiHook(hook) => backend.build_wasi_hook(idx, *hook),
         }
     }
 
@@ -190,6 +352,17 @@ pub fn build_app_module<'a, 'r>(
     (module, called_fns, main_function_index)
 }
 
+/// Whether the host has its own definition of the function `name`, rather than importing it
+fn host_defines_function(host_module: &WasmModule, name: &str) -> bool {
+    host_module.linking.symbol_table.iter().any(|sym| {
+        matches!(
+            sym,
+            SymInfo::Function(WasmObjectSymbol::ExplicitlyNamed { flags, name: sym_name, .. })
+                if *sym_name == name && flags & WASM_SYM_UNDEFINED == 0
+        )
+    })
+}
+
 pub struct CopyMemoryConfig {
     from_ptr: LocalId,
     from_offset: u32,
@@ -199,6 +372,9 @@ pub struct CopyMemoryConfig {
     alignment_bytes: u32,
 }
 
+/// Below this size, a few loads and stores are smaller and faster than `memory.copy` or `memory.fill`
+const BULK_MEMORY_MIN_BYTES: u32 = 64;
+
 pub fn copy_memory(code_builder: &mut CodeBuilder, config: CopyMemoryConfig) {
     if config.from_ptr == config.to_ptr && config.from_offset == config.to_offset {
         return;
@@ -207,8 +383,27 @@ pub fn copy_memory(code_builder: &mut CodeBuilder, config: CopyMemoryConfig) {
         return;
     }
 
+    let features = code_builder.features;
+
+    if features.bulk_memory && config.size >= BULK_MEMORY_MIN_BYTES {
+        load_address(code_builder, config.to_ptr, config.to_offset);
+        load_address(code_builder, config.from_ptr, config.from_offset);
+        code_builder.i32_const(config.size as i32);
+        code_builder.memory_copy();
+        return;
+    }
+
     let alignment = Align::from(config.alignment_bytes);
     let mut i = 0;
+    if features.simd {
+        while config.size - i >= 16 {
+            code_builder.get_local(config.to_ptr);
+            code_builder.get_local(config.from_ptr);
+            code_builder.v128_load(alignment, i + config.from_offset);
+            code_builder.v128_store(alignment, i + config.to_offset);
+            i += 16;
+        }
+    }
     while config.size - i >= 8 {
         code_builder.get_local(config.to_ptr);
         code_builder.get_local(config.from_ptr);
@@ -232,6 +427,53 @@ pub fn copy_memory(code_builder: &mut CodeBuilder, config: CopyMemoryConfig) {
     }
 }
 
+/// Set `size` bytes of memory to zero
+pub fn zero_memory(
+    code_builder: &mut CodeBuilder,
+    to_ptr: LocalId,
+    to_offset: u32,
+    size: u32,
+    alignment_bytes: u32,
+) {
+    if code_builder.features.bulk_memory && size >= BULK_MEMORY_MIN_BYTES {
+        load_address(code_builder, to_ptr, to_offset);
+        code_builder.i32_const(0);
+        code_builder.i32_const(size as i32);
+        code_builder.memory_fill();
+        return;
+    }
+
+    let alignment = Align::from(alignment_bytes);
+    let mut i = 0;
+    while size - i >= 8 {
+        code_builder.get_local(to_ptr);
+        code_builder.i64_const(0);
+        code_builder.i64_store(alignment, i + to_offset);
+        i += 8;
+    }
+    if size - i >= 4 {
+        code_builder.get_local(to_ptr);
+        code_builder.i32_const(0);
+        code_builder.i32_store(alignment, i + to_offset);
+        i += 4;
+    }
+    while size - i > 0 {
+        code_builder.get_local(to_ptr);
+        code_builder.i32_const(0);
+        code_builder.i32_store8(alignment, i + to_offset);
+        i += 1;
+    }
+}
+
+/// Bulk memory instructions take an address rather than a pointer and offset
+fn load_address(code_builder: &mut CodeBuilder, ptr: LocalId, offset: u32) {
+    code_builder.get_local(ptr);
+    if offset > 0 {
+        code_builder.i32_const(offset as i32);
+        code_builder.i32_add();
+    }
+}
+
 pub struct WasmDebugSettings {
     proc_start_end: bool,
     user_procs_ir: bool,
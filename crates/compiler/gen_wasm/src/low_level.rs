@@ -7,9 +7,10 @@ use roc_module::symbol::Symbol;
 use roc_mono::code_gen_help::HelperOp;
 use roc_mono::ir::{HigherOrderLowLevel, PassedFunction, ProcLayout};
 use roc_mono::layout::{Builtin, InLayout, Layout, LayoutInterner, LayoutRepr, UnionLayout};
-use roc_mono::low_level::HigherOrder;
+use roc_mono::low_level::{HigherOrder, LowLevelImpl};
 
 use crate::backend::{ProcLookupData, ProcSource, WasmBackend};
+use crate::code_builder::CodeBuilder;
 use crate::layout::{CallConv, StackMemoryFormat, WasmLayout};
 use crate::storage::{AddressValue, StackMemoryLocation, StoredValue};
 use crate::{PTR_TYPE, TARGET_INFO};
@@ -146,6 +147,32 @@ impl<'a> LowLevelCall<'a> {
         )
     }
 
+    /// Push whether shifting by `bits` keeps any of the bits of the return value.
+    /// Wasm takes shift amounts modulo 32 or 64, and the value may be narrower than that.
+    fn load_shift_in_range(&self, backend: &mut WasmBackend<'a, '_>, bits: Symbol) {
+        let bit_width = 8 * self
+            .ret_layout_raw
+            .stack_size(backend.layout_interner, TARGET_INFO) as i32;
+        backend
+            .storage
+            .load_symbols(&mut backend.code_builder, &[bits]);
+        backend.code_builder.i32_const(bit_width);
+        backend.code_builder.i32_lt_u();
+    }
+
+    /// Push `bits`, capped so that an arithmetic shift by it fills the value with its sign bit
+    fn load_clamped_shift_amount(&self, backend: &mut WasmBackend<'a, '_>, bits: Symbol) {
+        let bit_width = 8 * self
+            .ret_layout_raw
+            .stack_size(backend.layout_interner, TARGET_INFO) as i32;
+        backend
+            .storage
+            .load_symbols(&mut backend.code_builder, &[bits]);
+        backend.code_builder.i32_const(bit_width - 1);
+        self.load_shift_in_range(backend, bits);
+        backend.code_builder.select();
+    }
+
     fn load_args_and_call_zig(&self, backend: &mut WasmBackend<'a, '_>, name: &'a str) {
         let (num_wasm_args, has_return_val, ret_zig_packed_struct) = self.load_args(backend);
         backend.call_host_fn_after_loading_args(name, num_wasm_args, has_return_val);
@@ -206,12 +233,12 @@ impl<'a> LowLevelCall<'a> {
             )
         };
 
+        if let LowLevelImpl::ZigBuiltin(name) = LowLevelImpl::of(self.lowlevel) {
+            return self.load_args_and_call_zig(backend, name);
+        }
+
         match self.lowlevel {
             // Str
-            StrConcat => self.load_args_and_call_zig(backend, bitcode::STR_CONCAT),
-            StrToScalars => self.load_args_and_call_zig(backend, bitcode::STR_TO_SCALARS),
-            StrGetUnsafe => self.load_args_and_call_zig(backend, bitcode::STR_GET_UNSAFE),
-            StrJoinWith => self.load_args_and_call_zig(backend, bitcode::STR_JOIN_WITH),
             StrIsEmpty => match backend.storage.get(&self.arguments[0]) {
                 StoredValue::StackMemory { location, .. } => {
                     let (local_id, offset) =
@@ -223,19 +250,6 @@ impl<'a> LowLevelCall<'a> {
                 }
                 _ => internal_error!("invalid storage for Str"),
             },
-            StrStartsWith => self.load_args_and_call_zig(backend, bitcode::STR_STARTS_WITH),
-            StrStartsWithScalar => {
-                self.load_args_and_call_zig(backend, bitcode::STR_STARTS_WITH_SCALAR)
-            }
-            StrEndsWith => self.load_args_and_call_zig(backend, bitcode::STR_ENDS_WITH),
-            StrSplit => self.load_args_and_call_zig(backend, bitcode::STR_SPLIT),
-            StrCountGraphemes => {
-                self.load_args_and_call_zig(backend, bitcode::STR_COUNT_GRAPEHEME_CLUSTERS)
-            }
-            StrCountUtf8Bytes => {
-                self.load_args_and_call_zig(backend, bitcode::STR_COUNT_UTF8_BYTES)
-            }
-            StrGetCapacity => self.load_args_and_call_zig(backend, bitcode::STR_CAPACITY),
             StrToNum => {
                 let number_layout = match backend.layout_interner.get(self.ret_layout).repr {
                     LayoutRepr::Struct { field_layouts, .. } => field_layouts[0],
@@ -281,24 +295,6 @@ impl<'a> LowLevelCall<'a> {
                 backend.code_builder.i32_const(UPDATE_MODE_IMMUTABLE);
                 backend.call_host_fn_after_loading_args(bitcode::STR_FROM_UTF8_RANGE, 6, false);
             }
-            StrTrimLeft => self.load_args_and_call_zig(backend, bitcode::STR_TRIM_LEFT),
-            StrTrimRight => self.load_args_and_call_zig(backend, bitcode::STR_TRIM_RIGHT),
-            StrToUtf8 => self.load_args_and_call_zig(backend, bitcode::STR_TO_UTF8),
-            StrReserve => self.load_args_and_call_zig(backend, bitcode::STR_RESERVE),
-            StrReleaseExcessCapacity => {
-                self.load_args_and_call_zig(backend, bitcode::STR_RELEASE_EXCESS_CAPACITY)
-            }
-            StrRepeat => self.load_args_and_call_zig(backend, bitcode::STR_REPEAT),
-            StrAppendScalar => self.load_args_and_call_zig(backend, bitcode::STR_APPEND_SCALAR),
-            StrTrim => self.load_args_and_call_zig(backend, bitcode::STR_TRIM),
-            StrGetScalarUnsafe => {
-                self.load_args_and_call_zig(backend, bitcode::STR_GET_SCALAR_UNSAFE)
-            }
-            StrSubstringUnsafe => {
-                self.load_args_and_call_zig(backend, bitcode::STR_SUBSTRING_UNSAFE)
-            }
-            StrWithCapacity => self.load_args_and_call_zig(backend, bitcode::STR_WITH_CAPACITY),
-            StrGraphemes => self.load_args_and_call_zig(backend, bitcode::STR_GRAPHEMES),
 
             // List
             ListLen => match backend.storage.get(&self.arguments[0]) {
@@ -321,11 +317,7 @@ impl<'a> LowLevelCall<'a> {
                 _ => internal_error!("invalid storage for List"),
             },
 
-            ListGetCapacity => self.load_args_and_call_zig(backend, bitcode::LIST_CAPACITY),
-
-            ListIsUnique => self.load_args_and_call_zig(backend, bitcode::LIST_IS_UNIQUE),
-
-            ListMap | ListMap2 | ListMap3 | ListMap4 | ListSortWith => {
+            ListMap | ListMap2 | ListMap3 | ListMap4 | ListSortWith | StrWalkUtf8 => {
                 internal_error!("HigherOrder lowlevels should not be handled here")
             }
 
@@ -1644,50 +1636,71 @@ impl<'a> LowLevelCall<'a> {
                 }
                 _ => panic_ret_type(),
             },
-            NumBytesToU16 => self.load_args_and_call_zig(backend, bitcode::NUM_BYTES_TO_U16),
-            NumBytesToU32 => self.load_args_and_call_zig(backend, bitcode::NUM_BYTES_TO_U32),
-            NumBytesToU64 => self.load_args_and_call_zig(backend, bitcode::NUM_BYTES_TO_U64),
-            NumBytesToU128 => self.load_args_and_call_zig(backend, bitcode::NUM_BYTES_TO_U128),
-            NumBitwiseAnd => {
-                self.load_args(backend);
-                match CodeGenNumType::from(self.ret_layout) {
-                    I32 => backend.code_builder.i32_and(),
-                    I64 => backend.code_builder.i64_and(),
-                    I128 => todo!("{:?} for I128", self.lowlevel),
-                    _ => panic_ret_type(),
+            NumBitwiseAnd => match CodeGenNumType::from(self.ret_layout) {
+                I32 => {
+                    self.load_args(backend);
+                    backend.code_builder.i32_and();
                 }
-            }
-            NumBitwiseXor => {
-                self.load_args(backend);
-                match CodeGenNumType::from(self.ret_layout) {
-                    I32 => backend.code_builder.i32_xor(),
-                    I64 => backend.code_builder.i64_xor(),
-                    I128 => todo!("{:?} for I128", self.lowlevel),
-                    _ => panic_ret_type(),
+                I64 => {
+                    self.load_args(backend);
+                    backend.code_builder.i64_and();
                 }
-            }
-            NumBitwiseOr => {
-                self.load_args(backend);
-                match CodeGenNumType::from(self.ret_layout) {
-                    I32 => backend.code_builder.i32_or(),
-                    I64 => backend.code_builder.i64_or(),
-                    I128 => todo!("{:?} for I128", self.lowlevel),
-                    _ => panic_ret_type(),
+                I128 => self.bitwise_num128(backend, CodeBuilder::v128_and, CodeBuilder::i64_and),
+                _ => panic_ret_type(),
+            },
+            NumBitwiseXor => match CodeGenNumType::from(self.ret_layout) {
+                I32 => {
+                    self.load_args(backend);
+                    backend.code_builder.i32_xor();
                 }
-            }
+                I64 => {
+                    self.load_args(backend);
+                    backend.code_builder.i64_xor();
+                }
+                I128 => self.bitwise_num128(backend, CodeBuilder::v128_xor, CodeBuilder::i64_xor),
+                _ => panic_ret_type(),
+            },
+            NumBitwiseOr => match CodeGenNumType::from(self.ret_layout) {
+                I32 => {
+                    self.load_args(backend);
+                    backend.code_builder.i32_or();
+                }
+                I64 => {
+                    self.load_args(backend);
+                    backend.code_builder.i64_or();
+                }
+                I128 => self.bitwise_num128(backend, CodeBuilder::v128_or, CodeBuilder::i64_or),
+                _ => panic_ret_type(),
+            },
             NumShiftLeftBy => {
                 let num = self.arguments[0];
                 let bits = self.arguments[1];
-                backend
-                    .storage
-                    .load_symbols(&mut backend.code_builder, &[num, bits]);
                 match CodeGenNumType::from(self.ret_layout) {
-                    I32 => backend.code_builder.i32_shl(),
+                    I32 => {
+                        backend
+                            .storage
+                            .load_symbols(&mut backend.code_builder, &[num, bits]);
+                        backend.code_builder.i32_shl();
+                        backend.code_builder.i32_const(0);
+                        self.load_shift_in_range(backend, bits);
+                        backend.code_builder.select();
+                    }
                     I64 => {
+                        backend
+                            .storage
+                            .load_symbols(&mut backend.code_builder, &[num, bits]);
                         backend.code_builder.i64_extend_u_i32();
                         backend.code_builder.i64_shl();
+                        backend.code_builder.i64_const(0);
+                        self.load_shift_in_range(backend, bits);
+                        backend.code_builder.select();
                     }
-                    I128 => todo!("{:?} for I128", self.lowlevel),
+                    I128 => match self.ret_layout_raw.repr {
+                        LayoutRepr::Builtin(Builtin::Int(width)) => {
+                            self.load_args_and_call_zig(backend, &bitcode::NUM_SHIFT_LEFT_BY[width])
+                        }
+                        _ => panic_ret_type(),
+                    },
                     _ => panic_ret_type(),
                 }
             }
@@ -1712,9 +1725,7 @@ impl<'a> LowLevelCall<'a> {
                             backend.code_builder.i32_shl();
                             backend.code_builder.i32_const(32 - bit_width);
                             backend.code_builder.i32_shr_s();
-                            backend
-                                .storage
-                                .load_symbols(&mut backend.code_builder, &[bits]);
+                            self.load_clamped_shift_amount(backend, bits);
 
                             // Do the actual bitshift operation
                             backend.code_builder.i32_shr_s();
@@ -1725,18 +1736,24 @@ impl<'a> LowLevelCall<'a> {
                         } else {
                             backend
                                 .storage
-                                .load_symbols(&mut backend.code_builder, &[num, bits]);
+                                .load_symbols(&mut backend.code_builder, &[num]);
+                            self.load_clamped_shift_amount(backend, bits);
                             backend.code_builder.i32_shr_s();
                         }
                     }
                     I64 => {
                         backend
                             .storage
-                            .load_symbols(&mut backend.code_builder, &[num, bits]);
+                            .load_symbols(&mut backend.code_builder, &[num]);
+                        self.load_clamped_shift_amount(backend, bits);
                         backend.code_builder.i64_extend_u_i32();
                         backend.code_builder.i64_shr_s();
                     }
-                    I128 => todo!("{:?} for I128", self.lowlevel),
+                    I128 => match self.ret_layout_raw.repr {
+                        LayoutRepr::Builtin(Builtin::Int(width)) => self
+                            .load_args_and_call_zig(backend, &bitcode::NUM_SHIFT_RIGHT_BY[width]),
+                        _ => panic_ret_type(),
+                    },
                     _ => panic_ret_type(),
                 }
             }
@@ -1771,6 +1788,9 @@ impl<'a> LowLevelCall<'a> {
                         }
 
                         backend.code_builder.i32_shr_u();
+                        backend.code_builder.i32_const(0);
+                        self.load_shift_in_range(backend, bits);
+                        backend.code_builder.select();
                     }
                     I64 => {
                         backend
@@ -1778,8 +1798,17 @@ impl<'a> LowLevelCall<'a> {
                             .load_symbols(&mut backend.code_builder, &[num, bits]);
                         backend.code_builder.i64_extend_u_i32();
                         backend.code_builder.i64_shr_u();
+                        backend.code_builder.i64_const(0);
+                        self.load_shift_in_range(backend, bits);
+                        backend.code_builder.select();
                     }
-                    I128 => self.load_args_and_call_zig(backend, "__lshrti3"), // from compiler_rt
+                    I128 => match self.ret_layout_raw.repr {
+                        LayoutRepr::Builtin(Builtin::Int(width)) => self.load_args_and_call_zig(
+                            backend,
+                            &bitcode::NUM_SHIFT_RIGHT_ZERO_FILL[width],
+                        ),
+                        _ => panic_ret_type(),
+                    },
                     _ => panic_ret_type(),
                 }
             }
@@ -1944,9 +1973,6 @@ impl<'a> LowLevelCall<'a> {
                     )
                 }
             }
-            NumToFloatChecked => {
-                todo!("implement toF32Checked and toF64Checked");
-            }
             And => {
                 self.load_args(backend);
                 backend.code_builder.i32_and();
@@ -1959,15 +1985,6 @@ impl<'a> LowLevelCall<'a> {
                 self.load_args(backend);
                 backend.code_builder.i32_eqz();
             }
-            RefCountIncRcPtr => self.load_args_and_call_zig(backend, bitcode::UTILS_INCREF_RC_PTR),
-            RefCountDecRcPtr => self.load_args_and_call_zig(backend, bitcode::UTILS_DECREF_RC_PTR),
-            RefCountIncDataPtr => {
-                self.load_args_and_call_zig(backend, bitcode::UTILS_INCREF_DATA_PTR)
-            }
-            RefCountDecDataPtr => {
-                self.load_args_and_call_zig(backend, bitcode::UTILS_DECREF_DATA_PTR)
-            }
-            RefCountIsUnique => self.load_args_and_call_zig(backend, bitcode::UTILS_IS_UNIQUE),
 
             PtrCast => {
                 let code_builder = &mut backend.code_builder;
@@ -1976,8 +1993,6 @@ impl<'a> LowLevelCall<'a> {
 
             PtrWrite => todo!("{:?}", self.lowlevel),
 
-            Hash => todo!("{:?}", self.lowlevel),
-
             Eq | NotEq => self.eq_or_neq(backend),
 
             BoxExpr | UnboxExpr => {
@@ -1994,6 +2009,11 @@ impl<'a> LowLevelCall<'a> {
                 },
                 StoredValue::StackMemory { .. } => { /* do nothing */ }
             },
+
+            _ => internal_error!(
+                "{:?} is a call to a Zig builtin, which is handled above",
+                self.lowlevel
+            ),
         }
     }
 
@@ -2137,6 +2157,16 @@ impl<'a> LowLevelCall<'a> {
         let (local0, offset0) = locations[0].local_and_offset(backend.storage.stack_frame_pointer);
         let (local1, offset1) = locations[1].local_and_offset(backend.storage.stack_frame_pointer);
 
+        if backend.code_builder.features.simd {
+            backend.code_builder.get_local(local0);
+            backend.code_builder.v128_load(Align::Bytes8, offset0);
+            backend.code_builder.get_local(local1);
+            backend.code_builder.v128_load(Align::Bytes8, offset1);
+            backend.code_builder.i8x16_eq();
+            backend.code_builder.i8x16_all_true();
+            return;
+        }
+
         // Load & compare the first half of each argument
         backend.code_builder.get_local(local0);
         backend.code_builder.i64_load(Align::Bytes8, offset0);
@@ -2155,6 +2185,45 @@ impl<'a> LowLevelCall<'a> {
         backend.code_builder.i32_and();
     }
 
+    /// Bitwise operation on two 128-bit integers, which are always in stack memory.
+    /// Uses a single vector instruction if SIMD is enabled, or one per 64-bit half if not.
+    fn bitwise_num128(
+        &self,
+        backend: &mut WasmBackend<'a, '_>,
+        v128_op: fn(&mut CodeBuilder<'a>),
+        i64_op: fn(&mut CodeBuilder<'a>),
+    ) {
+        let fp = backend.storage.stack_frame_pointer;
+        let stack_memory_location = |storage: &StoredValue| match storage {
+            StoredValue::StackMemory { location, .. } => location.local_and_offset(fp),
+            _ => internal_error!("I128 should be in stack memory"),
+        };
+        let (ret_local, ret_offset) = stack_memory_location(&self.ret_storage);
+        let (local0, offset0) = stack_memory_location(backend.storage.get(&self.arguments[0]));
+        let (local1, offset1) = stack_memory_location(backend.storage.get(&self.arguments[1]));
+
+        let code_builder = &mut backend.code_builder;
+        if code_builder.features.simd {
+            code_builder.get_local(ret_local);
+            code_builder.get_local(local0);
+            code_builder.v128_load(Align::Bytes8, offset0);
+            code_builder.get_local(local1);
+            code_builder.v128_load(Align::Bytes8, offset1);
+            v128_op(code_builder);
+            code_builder.v128_store(Align::Bytes8, ret_offset);
+        } else {
+            for half in [0, 8] {
+                code_builder.get_local(ret_local);
+                code_builder.get_local(local0);
+                code_builder.i64_load(Align::Bytes8, offset0 + half);
+                code_builder.get_local(local1);
+                code_builder.i64_load(Align::Bytes8, offset1 + half);
+                i64_op(code_builder);
+                code_builder.i64_store(Align::Bytes8, ret_offset + half);
+            }
+        }
+    }
+
     fn num_to_str(&self, backend: &mut WasmBackend<'a, '_>) {
         let arg_layout = backend.storage.symbol_layouts[&self.arguments[0]];
         match backend.layout_interner.get(arg_layout).repr {
@@ -2446,9 +2515,11 @@ pub fn call_higher_order_lowlevel<'a>(
             .unwrap();
         match op {
             ListSortWith { .. } => ProcSource::HigherOrderCompare(passed_proc_index),
-            ListMap { .. } | ListMap2 { .. } | ListMap3 { .. } | ListMap4 { .. } => {
-                ProcSource::HigherOrderMapper(passed_proc_index)
-            }
+            ListMap { .. }
+            | ListMap2 { .. }
+            | ListMap3 { .. }
+            | ListMap4 { .. }
+            | StrWalkUtf8 { .. } => ProcSource::HigherOrderMapper(passed_proc_index),
         }
     };
     let wrapper_sym = backend.create_symbol(&format!("#wrap#{:?}", fn_name));
@@ -2491,7 +2562,10 @@ pub fn call_higher_order_lowlevel<'a>(
                 result: *result_layout,
                 niche: fn_name.niche(),
             },
-            ProcSource::Roc | ProcSource::Helper => {
+            ProcSource::Roc
+            | ProcSource::Helper
+            | ProcSource::ExposedGeneric(_)
+            | ProcSource::WasiHook(_) => {
                 internal_error!("Should never reach here for {:?}", helper_proc_source)
             }
         }
@@ -2604,6 +2678,68 @@ pub fn call_higher_order_lowlevel<'a>(
 
             backend.call_host_fn_after_loading_args(bitcode::LIST_SORT_WITH, 9, false);
         }
+
+        StrWalkUtf8 { string, state } => {
+            // The state lives in stack memory while we walk. The passed function writes each
+            // new state to the scratch space, and Zig copies it back over the old one.
+            let (state_width, state_alignment) = backend
+                .layout_interner
+                .stack_size_and_alignment(*return_layout);
+            let (frame_ptr, state_offset) = backend
+                .storage
+                .allocate_anonymous_stack_memory(state_width, state_alignment);
+            let (_, scratch_offset) = backend
+                .storage
+                .allocate_anonymous_stack_memory(state_width, state_alignment);
+
+            backend.storage.copy_value_to_memory(
+                &mut backend.code_builder,
+                frame_ptr,
+                state_offset,
+                *state,
+            );
+
+            let cb = &mut backend.code_builder;
+
+            // string: RocStr,       i64, i32
+            // caller: Caller2,      i32
+            // data: Opaque,         i32
+            // inc_n_data: IncN,     i32
+            // data_is_owned: bool,  i32
+            // state: Opaque,        i32
+            // scratch: Opaque,      i32
+            // state_width: usize,   i32
+
+            backend.storage.load_symbol_zig(cb, *string);
+            cb.i32_const(wrapper_fn_ptr);
+            if closure_data_exists {
+                backend
+                    .storage
+                    .load_symbols(cb, &[wrapped_captured_environment]);
+            } else {
+                // load_symbols assumes that a zero-size arg should be eliminated in code gen,
+                // but that's a specialization that our Zig code doesn't have! Pass a null pointer.
+                cb.i32_const(0);
+            }
+            cb.i32_const(inc_fn_ptr);
+            cb.i32_const(*owns_captured_environment as i32);
+            cb.get_local(frame_ptr);
+            cb.i32_const(state_offset as i32);
+            cb.i32_add();
+            cb.get_local(frame_ptr);
+            cb.i32_const(scratch_offset as i32);
+            cb.i32_add();
+            cb.i32_const(state_width as i32);
+
+            backend.call_host_fn_after_loading_args(bitcode::STR_WALK_UTF8, 9, false);
+
+            backend.storage.copy_value_from_memory(
+                &mut backend.code_builder,
+                return_sym,
+                AddressValue::NotLoaded(frame_ptr),
+                state_offset,
+            );
+        }
     }
 }
 
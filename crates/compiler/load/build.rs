@@ -26,6 +26,7 @@ const MODULES: &[(ModuleId, &str)] = &[
     (ModuleId::DECODE, "Decode.roc"),
     (ModuleId::HASH, "Hash.roc"),
     (ModuleId::JSON, "Json.roc"),
+    (ModuleId::TARGET, "Target.roc"),
 ];
 
 fn main() {
@@ -107,6 +108,8 @@ fn write_types_for_module_real(module_id: ModuleId, filename: &str, output_path:
         &module.interns,
         &mut module.can_problems,
         &mut module.type_problems,
+        &roc_problem::lint::SeverityConfig::default(),
+        &roc_reporting::report::DEFAULT_PALETTE,
     );
 
     if problems.errors + problems.warnings > 0 {
@@ -127,5 +130,5 @@ fn write_types_for_module_real(module_id: ModuleId, filename: &str, output_path:
         solved_implementations,
     };
 
-    type_state.serialize(&mut file).unwrap();
+    type_state.serialize_snapshot(&mut file).unwrap();
 }
@@ -19,7 +19,7 @@ const SKIP_SUBS_CACHE: bool = {
 pub use roc_load_internal::docs;
 pub use roc_load_internal::file::{
     EntryPoint, ExecutionMode, ExpectMetadata, Expectations, ExposedToHost, LoadConfig, LoadResult,
-    LoadStart, LoadedModule, LoadingProblem, MonomorphizedModule, Phase, Threading,
+    LoadStart, LoadedModule, LoadingProblem, MonomorphizedModule, Phase, Threading, TimingSpan,
 };
 
 #[allow(clippy::too_many_arguments)]
@@ -65,6 +65,12 @@ pub fn load_single_threaded<'a>(
         render,
         palette,
         exec_mode,
+        None,
+        false,
+        false,
+        false,
+        false,
+        None,
         roc_cache_dir,
     )
 }
@@ -192,48 +198,52 @@ pub fn load_and_typecheck_str<'a>(
     }
 }
 
+/// The type snapshots are read in place, so they need to be as aligned as what's in them.
+#[repr(C, align(8))]
+struct Aligned<Bytes: ?Sized>(Bytes);
+
 // IFTTT: crates/compiler/load/build.rs
-const BOOL: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/Bool.dat")) as &[_];
-const DICT: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/Dict.dat")) as &[_];
-const SET: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/Set.dat")) as &[_];
-const RESULT: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/Result.dat")) as &[_];
-const NUM: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/Num.dat")) as &[_];
-const LIST: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/List.dat")) as &[_];
-const STR: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/Str.dat")) as &[_];
-const BOX: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/Box.dat")) as &[_];
-const ENCODE: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/Encode.dat")) as &[_];
-const DECODE: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/Decode.dat")) as &[_];
-const HASH: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/Hash.dat")) as &[_];
-
-fn deserialize_help(bytes: &[u8]) -> TypeState {
-    let (state, _offset) = TypeState::deserialize(bytes);
-    debug_assert_eq!(bytes.len(), _offset);
-
-    state
-}
+const BOOL: &Aligned<[u8]> = &Aligned(*include_bytes!(concat!(env!("OUT_DIR"), "/Bool.dat")));
+const DICT: &Aligned<[u8]> = &Aligned(*include_bytes!(concat!(env!("OUT_DIR"), "/Dict.dat")));
+const SET: &Aligned<[u8]> = &Aligned(*include_bytes!(concat!(env!("OUT_DIR"), "/Set.dat")));
+const RESULT: &Aligned<[u8]> = &Aligned(*include_bytes!(concat!(env!("OUT_DIR"), "/Result.dat")));
+const NUM: &Aligned<[u8]> = &Aligned(*include_bytes!(concat!(env!("OUT_DIR"), "/Num.dat")));
+const LIST: &Aligned<[u8]> = &Aligned(*include_bytes!(concat!(env!("OUT_DIR"), "/List.dat")));
+const STR: &Aligned<[u8]> = &Aligned(*include_bytes!(concat!(env!("OUT_DIR"), "/Str.dat")));
+const BOX: &Aligned<[u8]> = &Aligned(*include_bytes!(concat!(env!("OUT_DIR"), "/Box.dat")));
+const ENCODE: &Aligned<[u8]> = &Aligned(*include_bytes!(concat!(env!("OUT_DIR"), "/Encode.dat")));
+const DECODE: &Aligned<[u8]> = &Aligned(*include_bytes!(concat!(env!("OUT_DIR"), "/Decode.dat")));
+const HASH: &Aligned<[u8]> = &Aligned(*include_bytes!(concat!(env!("OUT_DIR"), "/Hash.dat")));
 
 fn read_cached_types() -> MutMap<ModuleId, TypeState> {
     let mut output = MutMap::default();
 
+    // A snapshot that doesn't match this compiler is skipped, and its module solved from source.
+    let mut insert = |module_id, snapshot: &Aligned<[u8]>| {
+        if let Some(state) = TypeState::deserialize_snapshot(&snapshot.0) {
+            output.insert(module_id, state);
+        }
+    };
+
     // Wasm seems to re-order definitions between build time and runtime, but only in release mode.
     // That is very strange, but we can solve it separately
     if !cfg!(target_family = "wasm") && !cfg!(windows) && !SKIP_SUBS_CACHE {
-        output.insert(ModuleId::BOOL, deserialize_help(BOOL));
+        insert(ModuleId::BOOL, BOOL);
 
-        output.insert(ModuleId::RESULT, deserialize_help(RESULT));
-        output.insert(ModuleId::NUM, deserialize_help(NUM));
+        insert(ModuleId::RESULT, RESULT);
+        insert(ModuleId::NUM, NUM);
 
-        output.insert(ModuleId::LIST, deserialize_help(LIST));
-        output.insert(ModuleId::STR, deserialize_help(STR));
-        output.insert(ModuleId::BOX, deserialize_help(BOX));
+        insert(ModuleId::LIST, LIST);
+        insert(ModuleId::STR, STR);
+        insert(ModuleId::BOX, BOX);
 
-        output.insert(ModuleId::DICT, deserialize_help(DICT));
-        output.insert(ModuleId::SET, deserialize_help(SET));
+        insert(ModuleId::DICT, DICT);
+        insert(ModuleId::SET, SET);
 
-        output.insert(ModuleId::ENCODE, deserialize_help(ENCODE));
-        output.insert(ModuleId::DECODE, deserialize_help(DECODE));
+        insert(ModuleId::ENCODE, ENCODE);
+        insert(ModuleId::DECODE, DECODE);
 
-        output.insert(ModuleId::HASH, deserialize_help(HASH));
+        insert(ModuleId::HASH, HASH);
     }
 
     output
@@ -217,8 +217,22 @@ fn generate_entry_docs(
                     }
                 }
 
-                ValueDef::Body(_, _) => {
-                    // TODO generate docs for un-annotated bodies
+                ValueDef::Body(loc_pattern, _) => {
+                    if let Pattern::Identifier(identifier) = loc_pattern.value {
+                        // Check if this module exposes the def
+                        if let Some(ident_id) = ident_ids.get_id(identifier) {
+                            // There's no annotation to render, so the docs renderer
+                            // falls back on the inferred type, if it has one.
+                            let doc_def = DocDef {
+                                name: identifier.to_string(),
+                                type_annotation: NoTypeAnn,
+                                type_vars: Vec::new(),
+                                symbol: Symbol::new(home, ident_id),
+                                docs,
+                            };
+                            acc.push(DocEntry::DocDef(doc_def));
+                        }
+                    }
                 }
 
                 ValueDef::Dbg { .. } => {
@@ -232,6 +246,10 @@ fn generate_entry_docs(
                 ValueDef::ExpectFx { .. } => {
                     // Don't generate docs for `expect-fx`s
                 }
+
+                ValueDef::Bench { .. } => {
+                    // Don't generate docs for `bench`es
+                }
             },
             Ok(type_index) => match &defs.type_defs[type_index.index()] {
                 TypeDef::Alias {
@@ -14,13 +14,15 @@ use roc_can::module::{
     canonicalize_module_defs, ExposedByModule, ExposedForModule, ExposedModuleTypes, Module,
     ResolvedImplementations, TypeState,
 };
+use roc_can::references::{Reference, ReferenceIndex};
 use roc_collections::{default_hasher, BumpMap, MutMap, MutSet, VecMap, VecSet};
 use roc_constrain::module::constrain_module;
 use roc_debug_flags::dbg_do;
 #[cfg(debug_assertions)]
 use roc_debug_flags::{
-    ROC_CHECK_MONO_IR, ROC_PRINT_IR_AFTER_DROP_SPECIALIZATION, ROC_PRINT_IR_AFTER_REFCOUNT,
-    ROC_PRINT_IR_AFTER_RESET_REUSE, ROC_PRINT_IR_AFTER_SPECIALIZATION, ROC_PRINT_LOAD_LOG,
+    ROC_CHECK_MONO_IR, ROC_PRINT_IR_AFTER_DROP_SPECIALIZATION, ROC_PRINT_IR_AFTER_FOLD_CONSTANTS,
+    ROC_PRINT_IR_AFTER_REFCOUNT, ROC_PRINT_IR_AFTER_RESET_REUSE, ROC_PRINT_IR_AFTER_SPECIALIZATION,
+    ROC_PRINT_LOAD_LOG,
 };
 use roc_derive::SharedDerivedModule;
 use roc_error_macros::internal_error;
@@ -30,9 +32,11 @@ use roc_module::symbol::{
     IdentIds, IdentIdsByModule, Interns, ModuleId, ModuleIds, PQModuleName, PackageModuleIds,
     PackageQualified, Symbol,
 };
+use roc_mono::coverage::CoveragePoint;
 use roc_mono::ir::{
     CapturedSymbols, ExternalSpecializations, GlueLayouts, LambdaSetId, PartialProc, Proc,
-    ProcLayout, Procs, ProcsBase, UpdateModeIds,
+    ProcLayout, Procs, ProcsBase, SpecializationOrigin, UpdateModeIds,
+    SPECIALIZATION_WARNING_THRESHOLD,
 };
 use roc_mono::layout::LayoutInterner;
 use roc_mono::layout::{
@@ -41,6 +45,7 @@ use roc_mono::layout::{
 use roc_mono::reset_reuse;
 use roc_mono::{drop_specialization, inc_dec};
 use roc_packaging::cache::RocCacheDir;
+use roc_packaging::manifest::{self, ManifestProblem};
 use roc_parse::ast::{
     self, CommentOrNewline, Defs, Expr, ExtractSpaces, Pattern, Spaced, StrLiteral, TypeAnnotation,
     ValueDef,
@@ -52,13 +57,15 @@ use roc_parse::header::{HeaderType, PackageName};
 use roc_parse::module::module_defs;
 use roc_parse::parser::{FileError, Parser, SourceError, SyntaxError};
 use roc_problem::Severity;
-use roc_region::all::{LineInfo, Loc, Region};
-use roc_reporting::report::{to_file_problem_report_string, Palette, RenderTarget};
+use roc_region::all::{LineColumn, LineInfo, Loc, Region};
+use roc_reporting::report::{
+    to_file_problem_report_string, to_lockfile_problem_report_string, Palette, RenderTarget,
+};
 use roc_solve::module::{extract_module_owned_implementations, Solved, SolvedModule};
 use roc_solve_problem::TypeError;
 use roc_target::TargetInfo;
 use roc_types::subs::{CopiedImport, ExposedTypesStorageSubs, Subs, VarStore, Variable};
-use roc_types::types::{Alias, Types};
+use roc_types::types::{Alias, AliasKind, Types};
 use std::collections::hash_map::Entry::{Occupied, Vacant};
 use std::collections::HashMap;
 use std::env::current_dir;
@@ -67,6 +74,7 @@ use std::iter;
 use std::ops::ControlFlow;
 use std::path::{Path, PathBuf};
 use std::str::from_utf8_unchecked;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::{env, fs};
 #[cfg(not(target_family = "wasm"))]
@@ -105,6 +113,22 @@ pub struct LoadConfig {
     pub palette: Palette,
     pub threading: Threading,
     pub exec_mode: ExecutionMode,
+    /// Stop with an error if a function is specialized more times than this,
+    /// rather than running out of memory making ever more specializations.
+    pub max_specializations: Option<usize>,
+    /// Count how often each function body and branch runs, for `roc test --coverage` and
+    /// profile-guided optimization. See [roc_mono::coverage].
+    pub coverage: bool,
+    /// Evaluate top-level constants at compile time where possible.
+    /// See [roc_mono::fold_constants].
+    pub fold_constants: bool,
+    /// Move computations that are the same in every iteration of a loop out of the loop.
+    /// See [roc_mono::loop_invariants].
+    pub hoist_loop_invariants: bool,
+    /// Leave `expect`s inside function bodies out of the generated code, as optimized builds do.
+    pub strip_expects: bool,
+    /// Stop with [LoadingProblem::Cancelled] once this is set, e.g. by another thread.
+    pub cancelled: Option<Arc<AtomicBool>>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -151,10 +175,14 @@ struct ModuleCache<'a> {
 
     /// Various information
     imports: MutMap<ModuleId, MutSet<ModuleId>>,
+    /// The source of each module's header, and where in it each import is,
+    /// so that an import cycle can point at the imports that caused it
+    import_regions: MutMap<ModuleId, (&'a [u8], MutMap<ModuleId, Region>)>,
     top_level_thunks: MutMap<ModuleId, MutSet<Symbol>>,
     documentation: VecMap<ModuleId, ModuleDocumentation>,
     can_problems: MutMap<ModuleId, Vec<roc_problem::can::Problem>>,
     type_problems: MutMap<ModuleId, Vec<TypeError>>,
+    references: MutMap<ModuleId, ReferenceIndex>,
 
     sources: MutMap<ModuleId, (PathBuf, &'a str)>,
 }
@@ -205,6 +233,7 @@ impl Default for ModuleCache<'_> {
             DECODE,
             HASH,
             JSON,
+            TARGET,
         }
 
         Self {
@@ -219,10 +248,12 @@ impl Default for ModuleCache<'_> {
             late_specializations: Default::default(),
             external_specializations_requested: Default::default(),
             imports: Default::default(),
+            import_regions: Default::default(),
             top_level_thunks: Default::default(),
             documentation: Default::default(),
             can_problems: Default::default(),
             type_problems: Default::default(),
+            references: Default::default(),
             sources: Default::default(),
         }
     }
@@ -393,6 +424,8 @@ fn start_phase<'a>(
                     abilities_store,
                     skip_constraint_gen,
                     exposed_module_ids: state.exposed_modules,
+                    ident_ids_by_module: Arc::clone(&state.ident_ids_by_module),
+                    exposed_symbols_by_module: Arc::clone(&state.exposed_symbols_by_module),
                 }
             }
 
@@ -614,6 +647,9 @@ fn start_phase<'a>(
                     exposed_by_module: state.exposed_types.clone(),
                     derived_module,
                     expectations,
+                    max_specializations: state.max_specializations,
+                    coverage: state.coverage,
+                    strip_expects: state.strip_expects,
                 }
             }
         }
@@ -630,6 +666,8 @@ pub struct LoadedModule {
     pub can_problems: MutMap<ModuleId, Vec<roc_problem::can::Problem>>,
     pub type_problems: MutMap<ModuleId, Vec<TypeError>>,
     pub declarations_by_id: MutMap<ModuleId, Declarations>,
+    /// Where each module mentions each symbol, for "find all references"
+    pub references_by_id: MutMap<ModuleId, ReferenceIndex>,
     pub exposed_to_host: MutMap<Symbol, Variable>,
     pub dep_idents: IdentIdsByModule,
     pub exposed_aliases: MutMap<Symbol, Alias>,
@@ -657,6 +695,22 @@ impl LoadedModule {
         total
     }
 
+    /// Every reference to `symbol` in the loaded modules, by module and then in source order.
+    pub fn references(&self, symbol: Symbol) -> Vec<(ModuleId, Reference)> {
+        let mut module_ids: Vec<ModuleId> = self.references_by_id.keys().copied().collect();
+        module_ids.sort_by_key(|module_id| self.interns.module_name(*module_id).as_str());
+
+        module_ids
+            .into_iter()
+            .flat_map(|module_id| {
+                self.references_by_id[&module_id]
+                    .references(symbol)
+                    .iter()
+                    .map(move |reference| (module_id, *reference))
+            })
+            .collect()
+    }
+
     pub fn exposed_values_str(&self) -> Vec<&str> {
         self.exposed_values
             .iter()
@@ -688,6 +742,8 @@ struct ModuleHeader<'a> {
     imported_modules: MutMap<ModuleId, Region>,
     package_qualified_imported_modules: MutSet<PackageQualified<'a, ModuleId>>,
     exposes: Vec<Symbol>,
+    /// The type aliases exposed as `opaque Name`
+    opaque_exposes: VecSet<Symbol>,
     exposed_imports: MutMap<Ident, (Symbol, Region)>,
     parse_state: roc_parse::state::State<'a>,
     header_type: HeaderType<'a>,
@@ -751,6 +807,8 @@ struct LateSpecializationsModule<'a> {
 pub struct ToplevelExpects {
     pub pure: VecMap<Symbol, Region>,
     pub fx: VecMap<Symbol, Region>,
+    /// Top-level `bench`es, which `roc bench` runs
+    pub benches: VecMap<Symbol, Region>,
 }
 
 #[derive(Debug)]
@@ -771,6 +829,14 @@ pub struct MonomorphizedModule<'a> {
     pub expectations: VecMap<ModuleId, Expectations>,
     pub uses_prebuilt_platform: bool,
     pub glue_layouts: GlueLayouts<'a>,
+    /// Rendered reports for functions that were specialized suspiciously many times, or that call
+    /// themselves in tail position but did not become loops
+    pub specialization_warnings: Vec<String>,
+    /// The regions counted by `roc_coverage_hit`, in counter order. Empty unless
+    /// [LoadConfig::coverage] was set.
+    pub coverage_points: Vec<CoveragePoint>,
+    /// Where the body of each specialized function is, for debug info
+    pub body_regions: MutMap<Symbol, Region>,
 }
 
 /// Values used to render expect output
@@ -835,6 +901,7 @@ struct ParsedModule<'a> {
     imported_modules: MutMap<ModuleId, Region>,
     exposed_ident_ids: IdentIds,
     exposed_imports: MutMap<Ident, (Symbol, Region)>,
+    opaque_exposes: VecSet<Symbol>,
     parsed_defs: Defs<'a>,
     symbols_from_requires: Vec<(Loc<Symbol>, Loc<TypeAnnotation<'a>>)>,
     header_type: HeaderType<'a>,
@@ -896,6 +963,9 @@ enum Msg<'a> {
         module_timing: ModuleTiming,
         subs: Subs,
         expectations: Option<Expectations>,
+        specialization_origins: MutMap<Symbol, Vec<SpecializationOrigin<'a>>>,
+        tail_recursive: MutMap<Symbol, Region>,
+        body_regions: MutMap<Symbol, Region>,
     },
 
     /// The task is to only typecheck AND monomorphize modules
@@ -924,6 +994,7 @@ struct CanAndCon {
     constrained_module: ConstrainedModule,
     canonicalization_problems: Vec<roc_problem::can::Problem>,
     module_docs: Option<ModuleDocumentation>,
+    references: ReferenceIndex,
 }
 
 #[derive(Debug)]
@@ -988,6 +1059,20 @@ struct State<'a> {
     pub procedures: MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
     pub toplevel_expects: ToplevelExpects,
     pub exposed_to_host: ExposedToHost,
+    /// The distinct specializations made of each function, across all specialization passes
+    pub specialization_origins: MutMap<Symbol, Vec<SpecializationOrigin<'a>>>,
+    /// Functions that call themselves in tail position, and where their bodies are
+    pub tail_recursive: MutMap<Symbol, Region>,
+    /// The functions in `tail_recursive` that still call themselves once refcounts are inserted
+    pub not_loops: Vec<Symbol>,
+    /// Where the body of each specialized function is, across all specialization passes
+    pub body_regions: MutMap<Symbol, Region>,
+    pub max_specializations: Option<usize>,
+    pub coverage: bool,
+    pub fold_constants: bool,
+    pub hoist_loop_invariants: bool,
+    pub strip_expects: bool,
+    pub cancelled: Option<Arc<AtomicBool>>,
 
     /// This is the "final" list of IdentIds, after canonicalization and constraint gen
     /// have completed for a given module.
@@ -1002,7 +1087,8 @@ struct State<'a> {
 
     pub declarations_by_id: MutMap<ModuleId, Declarations>,
 
-    pub exposed_symbols_by_module: MutMap<ModuleId, VecSet<Symbol>>,
+    /// Shared with canonicalization, which looks up what other modules expose to suggest imports.
+    pub exposed_symbols_by_module: Arc<MutMap<ModuleId, VecSet<Symbol>>>,
 
     pub timings: MutMap<ModuleId, ModuleTiming>,
 
@@ -1047,6 +1133,12 @@ impl<'a> State<'a> {
         palette: Palette,
         number_of_workers: usize,
         exec_mode: ExecutionMode,
+        max_specializations: Option<usize>,
+        coverage: bool,
+        fold_constants: bool,
+        hoist_loop_invariants: bool,
+        strip_expects: bool,
+        cancelled: Option<Arc<AtomicBool>>,
     ) -> Self {
         let arc_shorthands = Arc::new(Mutex::new(MutMap::default()));
         let cache_dir = roc_packaging::cache::roc_cache_dir();
@@ -1066,6 +1158,16 @@ impl<'a> State<'a> {
             procedures: MutMap::default(),
             toplevel_expects: ToplevelExpects::default(),
             exposed_to_host: ExposedToHost::default(),
+            specialization_origins: MutMap::default(),
+            tail_recursive: MutMap::default(),
+            not_loops: Vec::new(),
+            body_regions: MutMap::default(),
+            max_specializations,
+            coverage,
+            fold_constants,
+            hoist_loop_invariants,
+            strip_expects,
+            cancelled,
             exposed_modules: &[],
             exposed_types,
             arc_modules,
@@ -1074,7 +1176,7 @@ impl<'a> State<'a> {
             constrained_ident_ids: IdentIds::exposed_builtins(0),
             ident_ids_by_module,
             declarations_by_id: MutMap::default(),
-            exposed_symbols_by_module: MutMap::default(),
+            exposed_symbols_by_module: Arc::default(),
             timings: MutMap::default(),
             layout_caches: std::vec::Vec::with_capacity(number_of_workers),
             cached_types: Arc::new(Mutex::new(cached_types)),
@@ -1099,13 +1201,25 @@ pub struct ModuleTiming {
     pub find_specializations: Duration,
     // indexed by make specializations pass
     pub make_specializations: Vec<Duration>,
+    /// Inserting refcounts, specializing drops and reset/reuse all happen once for the whole
+    /// program, so this is only set for the root module.
+    pub insert_refcounts: Duration,
     // TODO pub monomorphize: Duration,
+    /// When each phase started, in the order they ran. Used to export a trace of the build.
+    pub spans: Vec<TimingSpan>,
     /// Total duration will always be more than the sum of the other fields, due
     /// to things like state lookups in between phases, waiting on other threads, etc.
     start_time: Instant,
     end_time: Instant,
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct TimingSpan {
+    pub phase: &'static str,
+    pub start: Instant,
+    pub duration: Duration,
+}
+
 impl ModuleTiming {
     pub fn new(start_time: Instant) -> Self {
         ModuleTiming {
@@ -1117,6 +1231,8 @@ impl ModuleTiming {
             solve: Duration::default(),
             find_specializations: Duration::default(),
             make_specializations: Vec::with_capacity(2),
+            insert_refcounts: Duration::default(),
+            spans: Vec::with_capacity(8),
             start_time,
             end_time: start_time, // just for now; we'll overwrite this at the end
         }
@@ -1126,6 +1242,18 @@ impl ModuleTiming {
         self.end_time.duration_since(self.start_time)
     }
 
+    pub fn start_time(&self) -> Instant {
+        self.start_time
+    }
+
+    fn add_span(&mut self, phase: &'static str, start: Instant, duration: Duration) {
+        self.spans.push(TimingSpan {
+            phase,
+            start,
+            duration,
+        });
+    }
+
     /// Subtract all the other fields from total_start_to_finish
     pub fn other(&self) -> Duration {
         let Self {
@@ -1137,6 +1265,8 @@ impl ModuleTiming {
             solve,
             find_specializations,
             make_specializations,
+            insert_refcounts: _,
+            spans: _,
             start_time,
             end_time,
         } = self;
@@ -1195,6 +1325,9 @@ impl std::fmt::Display for ModuleTiming {
             };
             report_timing(f, &format!("Make Specializations{}", suffix), *pass_time)?;
         }
+        if module_timing.insert_refcounts != Duration::default() {
+            report_timing(f, "Insert Refcounts", module_timing.insert_refcounts)?;
+        }
         report_timing(f, "Other", module_timing.other())?;
         f.write_str("\n")?;
         report_timing(f, "Total", module_timing.total())?;
@@ -1225,6 +1358,8 @@ enum BuildTask<'a> {
         abilities_store: PendingAbilitiesStore,
         exposed_module_ids: &'a [ModuleId],
         skip_constraint_gen: bool,
+        ident_ids_by_module: SharedIdentIdsByModule,
+        exposed_symbols_by_module: Arc<MutMap<ModuleId, VecSet<Symbol>>>,
     },
     Solve {
         module: Module,
@@ -1268,6 +1403,9 @@ enum BuildTask<'a> {
         world_abilities: WorldAbilities,
         derived_module: SharedDerivedModule,
         expectations: Option<Expectations>,
+        max_specializations: Option<usize>,
+        coverage: bool,
+        strip_expects: bool,
     },
 }
 
@@ -1284,6 +1422,37 @@ pub struct IncorrectModuleName<'a> {
     pub expected: PQModuleName<'a>,
 }
 
+/// One of the imports that make up an import cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImportSite {
+    pub importer: ModuleId,
+    pub imported: ModuleId,
+    pub position: LineColumn,
+}
+
+/// Find the import behind each step of an import cycle, e.g. for the cycle A, B, A
+/// that's where A imports B, and where B imports A.
+fn import_sites(
+    import_regions: &MutMap<ModuleId, (&[u8], MutMap<ModuleId, Region>)>,
+    cycle: &[ModuleId],
+) -> Vec<ImportSite> {
+    cycle
+        .windows(2)
+        .filter_map(|pair| {
+            let (importer, imported) = (pair[0], pair[1]);
+            let (src, regions) = import_regions.get(&importer)?;
+            let region = regions.get(&imported)?;
+            let src = std::str::from_utf8(src).ok()?;
+
+            Some(ImportSite {
+                importer,
+                imported,
+                position: LineInfo::new(src).convert_pos(region.start()),
+            })
+        })
+        .collect()
+}
+
 #[derive(Debug)]
 pub enum LoadingProblem<'a> {
     FileProblem {
@@ -1299,10 +1468,16 @@ pub enum LoadingProblem<'a> {
 
     /// a formatted report
     FormattedReport(String),
+    /// [LoadConfig::cancelled] was set
+    Cancelled,
 
-    ImportCycle(PathBuf, Vec<ModuleId>),
+    ImportCycle(PathBuf, Vec<ModuleId>, Vec<ImportSite>),
     IncorrectModuleName(FileError<'a, IncorrectModuleName<'a>>),
     CouldNotFindCacheDir,
+    LockfileProblem {
+        filename: PathBuf,
+        problem: String,
+    },
 }
 
 pub enum Phases {
@@ -1357,6 +1532,12 @@ pub fn load_and_typecheck_str<'a>(
         palette,
         threading,
         exec_mode: ExecutionMode::Check,
+        max_specializations: None,
+        coverage: false,
+        fold_constants: false,
+        hoist_loop_invariants: false,
+        strip_expects: false,
+        cancelled: None,
     };
 
     match load(
@@ -1616,6 +1797,12 @@ pub fn load<'a>(
             load_config.render,
             load_config.palette,
             load_config.exec_mode,
+            load_config.max_specializations,
+            load_config.coverage,
+            load_config.fold_constants,
+            load_config.hoist_loop_invariants,
+            load_config.strip_expects,
+            load_config.cancelled,
             roc_cache_dir,
         ),
         Threads::Many(threads) => load_multi_threaded(
@@ -1628,6 +1815,12 @@ pub fn load<'a>(
             load_config.palette,
             threads,
             load_config.exec_mode,
+            load_config.max_specializations,
+            load_config.coverage,
+            load_config.fold_constants,
+            load_config.hoist_loop_invariants,
+            load_config.strip_expects,
+            load_config.cancelled,
             roc_cache_dir,
         ),
     }
@@ -1643,6 +1836,12 @@ pub fn load_single_threaded<'a>(
     render: RenderTarget,
     palette: Palette,
     exec_mode: ExecutionMode,
+    max_specializations: Option<usize>,
+    coverage: bool,
+    fold_constants: bool,
+    hoist_loop_invariants: bool,
+    strip_expects: bool,
+    cancelled: Option<Arc<AtomicBool>>,
     roc_cache_dir: RocCacheDir<'_>,
 ) -> Result<LoadResult<'a>, LoadingProblem<'a>> {
     let LoadStart {
@@ -1674,6 +1873,12 @@ pub fn load_single_threaded<'a>(
         palette,
         number_of_workers,
         exec_mode,
+        max_specializations,
+        coverage,
+        fold_constants,
+        hoist_loop_invariants,
+        strip_expects,
+        cancelled,
     );
 
     // We'll add tasks to this, and then worker threads will take tasks from it.
@@ -1737,6 +1942,12 @@ fn state_thread_step<'a>(
     msg_tx: &crossbeam::channel::Sender<Msg<'a>>,
     msg_rx: &crossbeam::channel::Receiver<Msg<'a>>,
 ) -> Result<ControlFlow<LoadResult<'a>, State<'a>>, LoadingProblem<'a>> {
+    if let Some(cancelled) = &state.cancelled {
+        if cancelled.load(Ordering::Relaxed) {
+            return Err(LoadingProblem::Cancelled);
+        }
+    }
+
     match msg_rx.try_recv() {
         Ok(msg) => {
             match msg {
@@ -1793,7 +2004,7 @@ fn state_thread_step<'a>(
                     Ok(ControlFlow::Break(LoadResult::Monomorphized(monomorphized)))
                 }
                 Msg::FailedToReadFile { filename, error } => {
-                    let buf = to_file_problem_report_string(&filename, error);
+                    let buf = to_file_problem_report_string(&filename, error, state.palette);
                     Err(LoadingProblem::FormattedReport(buf))
                 }
 
@@ -1820,6 +2031,7 @@ fn state_thread_step<'a>(
                         filename,
                         bytes,
                         state.render,
+                        state.palette,
                     );
                     Err(LoadingProblem::FormattedReport(buf))
                 }
@@ -1865,7 +2077,7 @@ fn state_thread_step<'a>(
                             );
                             Err(LoadingProblem::FormattedReport(buf))
                         }
-                        Err(LoadingProblem::ImportCycle(filename, cycle)) => {
+                        Err(LoadingProblem::ImportCycle(filename, cycle, sites)) => {
                             let module_ids = arc_modules.lock().clone().into_module_ids();
 
                             let root_exposed_ident_ids = IdentIds::exposed_builtins(0);
@@ -1873,8 +2085,10 @@ fn state_thread_step<'a>(
                                 module_ids,
                                 root_exposed_ident_ids,
                                 cycle,
+                                sites,
                                 filename,
                                 render,
+                                palette,
                             );
                             return Err(LoadingProblem::FormattedReport(buf));
                         }
@@ -1892,9 +2106,15 @@ fn state_thread_step<'a>(
                                 filename,
                                 bytes,
                                 render,
+                                palette,
                             );
                             return Err(LoadingProblem::FormattedReport(buf));
                         }
+                        Err(LoadingProblem::LockfileProblem { filename, problem }) => {
+                            let buf =
+                                to_lockfile_problem_report_string(&filename, &problem, palette);
+                            return Err(LoadingProblem::FormattedReport(buf));
+                        }
                         Err(e) => Err(e),
                     }
                 }
@@ -1920,10 +2140,18 @@ pub fn report_loading_problem(
 
             to_parse_problem_report(problem, module_ids, root_exposed_ident_ids, render, palette)
         }
-        LoadingProblem::ImportCycle(filename, cycle) => {
+        LoadingProblem::ImportCycle(filename, cycle, sites) => {
             let root_exposed_ident_ids = IdentIds::exposed_builtins(0);
 
-            to_import_cycle_report(module_ids, root_exposed_ident_ids, cycle, filename, render)
+            to_import_cycle_report(
+                module_ids,
+                root_exposed_ident_ids,
+                cycle,
+                sites,
+                filename,
+                render,
+                palette,
+            )
         }
         LoadingProblem::IncorrectModuleName(FileError {
             problem: SourceError { problem, bytes },
@@ -1938,11 +2166,15 @@ pub fn report_loading_problem(
                 filename,
                 bytes,
                 render,
+                palette,
             )
         }
         LoadingProblem::FormattedReport(report) => report,
         LoadingProblem::FileProblem { filename, error } => {
-            to_file_problem_report_string(&filename, error)
+            to_file_problem_report_string(&filename, error, palette)
+        }
+        LoadingProblem::LockfileProblem { filename, problem } => {
+            to_lockfile_problem_report_string(&filename, &problem, palette)
         }
         err => todo!("Loading error: {:?}", err),
     }
@@ -1958,6 +2190,12 @@ fn load_multi_threaded<'a>(
     palette: Palette,
     available_threads: usize,
     exec_mode: ExecutionMode,
+    max_specializations: Option<usize>,
+    coverage: bool,
+    fold_constants: bool,
+    hoist_loop_invariants: bool,
+    strip_expects: bool,
+    cancelled: Option<Arc<AtomicBool>>,
     roc_cache_dir: RocCacheDir<'_>,
 ) -> Result<LoadResult<'a>, LoadingProblem<'a>> {
     let LoadStart {
@@ -2004,6 +2242,12 @@ fn load_multi_threaded<'a>(
         palette,
         num_workers,
         exec_mode,
+        max_specializations,
+        coverage,
+        fold_constants,
+        hoist_loop_invariants,
+        strip_expects,
+        cancelled,
     );
 
     // an arena for every worker, stored in an arena-allocated bumpalo vec to make the lifetimes work
@@ -2594,9 +2838,7 @@ fn update<'a>(
             // We need a proper solution that marks a phase as in-progress so it's not repeated
             // debug_assert!(!state.exposed_symbols_by_module.contains_key(&home));
 
-            state
-                .exposed_symbols_by_module
-                .insert(home, exposed_symbols);
+            Arc::make_mut(&mut state.exposed_symbols_by_module).insert(home, exposed_symbols);
 
             // add the prelude
             let mut header = header;
@@ -2629,6 +2871,14 @@ fn update<'a>(
                         .map(|x| *x.as_inner()),
                 );
 
+            state.module_cache.import_regions.insert(
+                header.module_id,
+                (
+                    header.parse_state.original_bytes(),
+                    header.imported_modules.clone(),
+                ),
+            );
+
             let added_deps_result = state.dependencies.add_module(
                 header.module_id,
                 &header.package_qualified_imported_modules,
@@ -2638,9 +2888,12 @@ fn update<'a>(
             let new_work = match added_deps_result {
                 Ok(work) => work,
                 Err(DepCycle { cycle }) => {
+                    let sites = import_sites(&state.module_cache.import_regions, &cycle);
+
                     return Err(LoadingProblem::ImportCycle(
                         header.module_path.clone(),
                         cycle,
+                        sites,
                     ));
                 }
             };
@@ -2693,6 +2946,7 @@ fn update<'a>(
             constrained_module,
             canonicalization_problems,
             module_docs,
+            references,
         }) => {
             let module_id = constrained_module.module.module_id;
             log!("generated constraints for {:?}", module_id);
@@ -2700,6 +2954,7 @@ fn update<'a>(
                 .module_cache
                 .can_problems
                 .insert(module_id, canonicalization_problems);
+            state.module_cache.references.insert(module_id, references);
 
             if let Some(docs) = module_docs {
                 state.module_cache.documentation.insert(module_id, docs);
@@ -2710,7 +2965,7 @@ fn update<'a>(
             state
                 .module_cache
                 .aliases
-                .insert(module_id, constrained_module.module.aliases.clone());
+                .insert(module_id, aliases_for_importers(&constrained_module.module));
 
             state
                 .module_cache
@@ -2890,6 +3145,7 @@ fn update<'a>(
                         .typechecked
                         .insert(module_id, typechecked);
                 } else {
+                    state.declarations_by_id.insert(module_id, decls);
                     state.constrained_ident_ids.insert(module_id, ident_ids);
                     state.timings.insert(module_id, module_timing);
                 }
@@ -2930,6 +3186,10 @@ fn update<'a>(
 
             state.toplevel_expects.pure.extend(toplevel_expects.pure);
             state.toplevel_expects.fx.extend(toplevel_expects.fx);
+            state
+                .toplevel_expects
+                .benches
+                .extend(toplevel_expects.benches);
 
             state
                 .module_cache
@@ -2972,6 +3232,9 @@ fn update<'a>(
             module_timing,
             layout_cache,
             expectations,
+            specialization_origins,
+            tail_recursive,
+            body_regions,
             ..
         } => {
             debug_assert!(
@@ -2985,6 +3248,20 @@ fn update<'a>(
             let _ = layout_cache;
 
             state.procedures.extend(procedures);
+
+            for (symbol, origins) in specialization_origins {
+                let existing = state.specialization_origins.entry(symbol).or_default();
+
+                for origin in origins {
+                    if !existing.iter().any(|other| other.layout == origin.layout) {
+                        existing.push(origin);
+                    }
+                }
+            }
+
+            state.tail_recursive.extend(tail_recursive);
+            state.body_regions.extend(body_regions);
+
             state.module_cache.late_specializations.insert(
                 module_id,
                 LateSpecializationsModule {
@@ -3102,6 +3379,69 @@ fn update<'a>(
 
                     let ident_ids = state.constrained_ident_ids.get_mut(&module_id).unwrap();
 
+                    if state.fold_constants {
+                        let expects = &state.toplevel_expects;
+                        let top_level_values = state
+                            .module_cache
+                            .top_level_thunks
+                            .values()
+                            .flatten()
+                            .copied()
+                            .filter(|symbol| {
+                                !state.exposed_to_host.top_level_values.contains_key(symbol)
+                                    && !expects.pure.contains_key(symbol)
+                                    && !expects.fx.contains_key(symbol)
+                                    && !expects.benches.contains_key(symbol)
+                            })
+                            .collect();
+
+                        roc_mono::fold_constants::fold_constants(
+                            arena,
+                            &layout_interner,
+                            module_id,
+                            ident_ids,
+                            &mut state.procedures,
+                            &top_level_values,
+                        );
+
+                        debug_print_ir!(state, &layout_interner, ROC_PRINT_IR_AFTER_FOLD_CONSTANTS);
+                    }
+
+                    let roots = {
+                        let exposed = &state.exposed_to_host;
+                        let expects = &state.toplevel_expects;
+
+                        (exposed.top_level_values.keys())
+                            .chain(&exposed.closure_types)
+                            .chain(exposed.lambda_sets.iter().map(|(symbol, _)| symbol))
+                            .chain(&exposed.getters)
+                            .chain(expects.pure.keys())
+                            .chain(expects.fx.keys())
+                            .chain(expects.benches.keys())
+                            .copied()
+                            .collect()
+                    };
+
+                    roc_mono::prune_branches::prune_branches(arena, &mut state.procedures, &roots);
+
+                    roc_mono::bounds_checks::eliminate_bounds_checks(
+                        arena,
+                        &layout_interner,
+                        module_id,
+                        ident_ids,
+                        &mut update_mode_ids,
+                        &mut state.procedures,
+                    );
+
+                    if state.hoist_loop_invariants {
+                        roc_mono::loop_invariants::hoist_loop_invariants(
+                            arena,
+                            &mut state.procedures,
+                        );
+                    }
+
+                    let insert_refcounts_start = Instant::now();
+
                     inc_dec::insert_inc_dec_operations(
                         arena,
                         &layout_interner,
@@ -3110,6 +3450,11 @@ fn update<'a>(
 
                     debug_print_ir!(state, &layout_interner, ROC_PRINT_IR_AFTER_REFCOUNT);
 
+                    state.not_loops =
+                        roc_mono::tail_recursion::not_loops(&state.procedures, |symbol| {
+                            state.tail_recursive.contains_key(&symbol)
+                        });
+
                     drop_specialization::specialize_drops(
                         arena,
                         &mut layout_interner,
@@ -3137,6 +3482,12 @@ fn update<'a>(
 
                     debug_print_ir!(state, &layout_interner, ROC_PRINT_IR_AFTER_RESET_REUSE);
 
+                    if let Some(root_timing) = state.timings.get_mut(&state.root_id) {
+                        let duration = insert_refcounts_start.elapsed();
+                        root_timing.insert_refcounts = duration;
+                        root_timing.add_span("Insert Refcounts", insert_refcounts_start, duration);
+                    }
+
                     // This is not safe with the new non-recursive RC updates that we do for tag unions
                     //
                     // Proc::optimize_refcount_operations(
@@ -3338,7 +3689,7 @@ fn finish_specialization<'a>(
                     }
                     Valid(To::NewPackage(p_or_p)) => PathBuf::from(p_or_p.as_str()),
                     other => {
-                        let buf = to_missing_platform_report(state.root_id, other);
+                        let buf = to_missing_platform_report(state.root_id, other, state.palette);
                         return Err(LoadingProblem::FormattedReport(buf));
                     }
                 };
@@ -3390,13 +3741,28 @@ fn finish_specialization<'a>(
 
     let State {
         toplevel_expects,
-        procedures,
+        mut procedures,
         module_cache,
         output_path,
         platform_data,
+        specialization_origins,
+        tail_recursive,
+        not_loops,
+        body_regions,
+        max_specializations,
+        coverage,
+        render,
+        palette,
         ..
     } = state;
 
+    // every proc is specialized by now, so the counters can be numbered
+    let coverage_points = if coverage {
+        roc_mono::coverage::number_coverage_points(arena, &mut procedures)
+    } else {
+        Vec::new()
+    };
+
     let ModuleCache {
         type_problems,
         can_problems,
@@ -3458,6 +3824,58 @@ fn finish_specialization<'a>(
         }
     }
 
+    let mut specialization_warnings = Vec::new();
+    let mut specialization_errors = Vec::new();
+
+    for (symbol, origins) in specialization_origins.iter() {
+        let exceeded_limit = matches!(max_specializations, Some(max) if origins.len() > max);
+
+        if origins.len() <= SPECIALIZATION_WARNING_THRESHOLD && !exceeded_limit {
+            continue;
+        }
+
+        let filename = match sources.get(&symbol.module_id()) {
+            Some((path, _)) => path.clone(),
+            None => PathBuf::new(),
+        };
+        let report = to_too_many_specializations_report(
+            &interns,
+            &layout_interner,
+            *symbol,
+            origins,
+            max_specializations.filter(|_| exceeded_limit),
+            filename,
+            render,
+            palette,
+        );
+
+        if exceeded_limit {
+            specialization_errors.push(report);
+        } else {
+            specialization_warnings.push(report);
+        }
+    }
+
+    for symbol in not_loops {
+        if let Some((filename, src)) = sources.get(&symbol.module_id()) {
+            specialization_warnings.push(to_not_a_loop_report_string(
+                &interns,
+                symbol,
+                tail_recursive[&symbol],
+                filename.clone(),
+                src,
+                render,
+                palette,
+            ));
+        }
+    }
+
+    if !specialization_errors.is_empty() {
+        return Err(LoadingProblem::FormattedReport(
+            specialization_errors.join("\n\n"),
+        ));
+    }
+
     let output_path = match output_path {
         Some(path_str) => Path::new(path_str).into(),
         None => current_dir().unwrap().join(DEFAULT_APP_OUTPUT_PATH).into(),
@@ -3489,6 +3907,9 @@ fn finish_specialization<'a>(
             getters: glue_getters,
         },
         uses_prebuilt_platform,
+        specialization_warnings,
+        coverage_points,
+        body_regions,
     })
 }
 
@@ -3559,6 +3980,7 @@ fn finish(
         can_problems: state.module_cache.can_problems,
         type_problems: state.module_cache.type_problems,
         declarations_by_id: state.declarations_by_id,
+        references_by_id: state.module_cache.references,
         dep_idents,
         exposed_aliases: exposed_aliases_by_symbol,
         exposed_values,
@@ -3599,6 +4021,8 @@ fn load_package_from_disk<'a>(
 
             pkg_module_timing.read_roc_file = file_io_duration;
             pkg_module_timing.parse_header = parse_header_duration;
+            pkg_module_timing.add_span("Read .roc file from disk", file_io_start, file_io_duration);
+            pkg_module_timing.add_span("Parse header", parse_start, parse_header_duration);
 
             match parsed {
                 Ok((
@@ -3812,6 +4236,7 @@ fn load_module<'a>(
 
     module_timing.read_roc_file = Default::default();
     module_timing.parse_header = parse_header_duration;
+    module_timing.add_span("Parse header", parse_start, parse_header_duration);
 
     macro_rules! load_builtins {
         ($($name:literal, $module_id:path)*) => {
@@ -3847,6 +4272,7 @@ fn load_module<'a>(
         "Decode", ModuleId::DECODE
         "Hash", ModuleId::HASH
         "Json", ModuleId::JSON
+        "Target", ModuleId::TARGET
     }
 
     let (filename, opt_shorthand) = module_name_to_path(src_dir, &module_name, arc_shorthands);
@@ -4037,6 +4463,8 @@ fn parse_header<'a>(
 
     module_timing.read_roc_file = read_file_duration;
     module_timing.parse_header = parse_header_duration;
+    module_timing.add_span("Read .roc file from disk", start_time, read_file_duration);
+    module_timing.add_span("Parse header", parse_start, parse_header_duration);
 
     match parsed {
         Ok((
@@ -4319,6 +4747,27 @@ fn load_packages<'a>(
 
         let src = package_name.to_str();
 
+        // A bare package name (e.g. "basic-cli") stands for the version that
+        // roc.lock picked for it from the project's [dependencies].
+        let src: &str = match manifest::locked_url(&cwd, src) {
+            Ok(Some(url)) => arena.alloc_str(&url),
+            Ok(None) => src,
+            Err(problem) => {
+                let (filename, problem) = match problem {
+                    ManifestProblem::Syntax { path, problem } => (path, problem),
+                    ManifestProblem::Io { path, error } => (path, error.to_string()),
+                    other => (cwd.join(manifest::LOCKFILE_FILENAME), other.to_string()),
+                };
+
+                load_messages.push(Msg::FailedToLoad(LoadingProblem::LockfileProblem {
+                    filename,
+                    problem,
+                }));
+
+                continue;
+            }
+        };
+
         // find the `package` or `platform` module on disk,
         // downloading it into a cache dir first if necessary.
         let root_module_path = if src.starts_with("https://") {
@@ -4522,6 +4971,7 @@ fn build_header<'a>(
     }
 
     let mut exposed: Vec<Symbol> = Vec::with_capacity(num_exposes);
+    let mut opaque_exposes = VecSet::default();
 
     // Make sure the module_ids has ModuleIds for all our deps,
     // then record those ModuleIds in can_module_ids for later.
@@ -4638,6 +5088,10 @@ fn build_header<'a>(
             let ident_id = ident_ids.get_or_insert(loc_exposed.value.as_str());
             let symbol = Symbol::new(home, ident_id);
 
+            if loc_exposed.value.is_opaque() {
+                opaque_exposes.insert(symbol);
+            }
+
             exposed.push(symbol);
         }
 
@@ -4758,6 +5212,7 @@ fn build_header<'a>(
             package_qualified_imported_modules,
             deps_by_name,
             exposes: exposed,
+            opaque_exposes,
             parse_state,
             exposed_imports: scope,
             symbols_from_requires,
@@ -5190,6 +5645,7 @@ fn run_solve<'a>(
 
     // TODO remove when we write builtins in roc
     let aliases = module.aliases.clone();
+    let opaque_exposes = module.opaque_exposes.clone();
 
     let mut module = module;
     let loc_expects = std::mem::take(&mut module.loc_expects);
@@ -5236,6 +5692,13 @@ fn run_solve<'a>(
         }
     };
 
+    // builtins are written with these lints in mind, e.g. numbers with their types
+    let mut problems = problems;
+    if !module_id.is_builtin() {
+        roc_solve::precision::check_literal_precision(solved_subs.inner(), &decls, &mut problems);
+        roc_solve::discarded::check_discarded_results(solved_subs.inner(), &decls, &mut problems);
+    }
+
     let mut solved_subs = solved_subs;
     let exposed_types = roc_solve::module::exposed_types_storage_subs(
         module_id,
@@ -5243,6 +5706,7 @@ fn run_solve<'a>(
         &exposed_vars_by_symbol,
         &solved_implementations,
         &abilities_store,
+        &opaque_exposes,
     );
 
     let solved_module = SolvedModule {
@@ -5256,6 +5720,7 @@ fn run_solve<'a>(
     // Record the final timings
     let solve_end = Instant::now();
     module_timing.solve = solve_end.duration_since(solve_start);
+    module_timing.add_span("Solve", solve_start, module_timing.solve);
 
     // Send the subs to the main thread for processing,
     Msg::SolvedTypes {
@@ -5272,6 +5737,20 @@ fn run_solve<'a>(
     }
 }
 
+/// The aliases other modules see when they import this one. Those exposed as `opaque Name`
+/// are opaque to them, so they can't depend on (or construct) the alias's structure.
+fn aliases_for_importers(module: &Module) -> MutMap<Symbol, (bool, Alias)> {
+    let mut aliases = module.aliases.clone();
+
+    for symbol in module.opaque_exposes.iter() {
+        if let Some((true, alias)) = aliases.get_mut(symbol) {
+            alias.kind = AliasKind::Opaque;
+        }
+    }
+
+    aliases
+}
+
 fn unspace<'a, T: Copy>(arena: &'a Bump, items: &[Loc<Spaced<'a, T>>]) -> &'a [Loc<T>] {
     bumpalo::collections::Vec::from_iter_in(
         items
@@ -5409,6 +5888,8 @@ fn canonicalize_and_constrain<'a>(
     parsed: ParsedModule<'a>,
     skip_constraint_gen: bool,
     exposed_module_ids: &[ModuleId],
+    ident_ids_by_module: &SharedIdentIdsByModule,
+    exposed_symbols_by_module: &MutMap<ModuleId, VecSet<Symbol>>,
 ) -> CanAndCon {
     let canonicalize_start = Instant::now();
 
@@ -5418,6 +5899,7 @@ fn canonicalize_and_constrain<'a>(
         exposed_ident_ids,
         parsed_defs,
         exposed_imports,
+        opaque_exposes,
         imported_modules,
         mut module_timing,
         symbols_from_requires,
@@ -5431,7 +5913,7 @@ fn canonicalize_and_constrain<'a>(
     let parsed_defs = arena.alloc(parsed_defs);
 
     let mut var_store = VarStore::default();
-    let module_output = canonicalize_module_defs(
+    let mut module_output = canonicalize_module_defs(
         arena,
         parsed_defs,
         &header_type,
@@ -5448,6 +5930,15 @@ fn canonicalize_and_constrain<'a>(
     );
     let mut types = Types::new();
 
+    add_exposing_modules(
+        &mut module_output.problems,
+        module_id,
+        module_ids,
+        &dep_idents,
+        ident_ids_by_module,
+        exposed_symbols_by_module,
+    );
+
     // _after has an underscore because it's unused in --release builds
     let _after = roc_types::types::get_type_clone_count();
 
@@ -5462,6 +5953,11 @@ fn canonicalize_and_constrain<'a>(
     let canonicalize_end = Instant::now();
 
     module_timing.canonicalize = canonicalize_end.duration_since(canonicalize_start);
+    module_timing.add_span(
+        "Canonicalize",
+        canonicalize_start,
+        module_timing.canonicalize,
+    );
 
     // Generate documentation information
     // TODO: store timing information?
@@ -5560,6 +6056,7 @@ fn canonicalize_and_constrain<'a>(
         module_id,
         exposed_imports: module_output.exposed_imports,
         exposed_symbols: module_output.exposed_symbols,
+        opaque_exposes,
         referenced_values: module_output.referenced_values,
         referenced_types: module_output.referenced_types,
         aliases,
@@ -5587,6 +6084,72 @@ fn canonicalize_and_constrain<'a>(
         constrained_module,
         canonicalization_problems: module_output.problems,
         module_docs,
+        references: module_output.references,
+    }
+}
+
+/// Canonicalization only knows about the modules this one imports. For every name that
+/// was not in scope, find the other modules we know of that expose a value with that
+/// name, so the report can suggest qualifying the lookup or adding an import.
+fn add_exposing_modules(
+    problems: &mut [roc_problem::can::Problem],
+    home: ModuleId,
+    module_ids: &ModuleIds,
+    dep_idents: &IdentIdsByModule,
+    ident_ids_by_module: &SharedIdentIdsByModule,
+    exposed_symbols_by_module: &MutMap<ModuleId, VecSet<Symbol>>,
+) {
+    use roc_problem::can::{ExposingModule, Problem, RuntimeError};
+
+    let mut lookups = problems
+        .iter_mut()
+        .filter_map(|problem| match problem {
+            Problem::RuntimeError(RuntimeError::LookupNotInScope {
+                loc_name,
+                exposed_by,
+                ..
+            }) => Some((loc_name.value.as_str(), exposed_by)),
+            _ => None,
+        })
+        .peekable();
+
+    if lookups.peek().is_none() {
+        // Don't bother taking the lock if there's nothing to suggest.
+        return;
+    }
+
+    let ident_ids_by_module = ident_ids_by_module.lock();
+
+    for (name, exposed_by) in lookups {
+        for (module_id, exposed_symbols) in exposed_symbols_by_module.iter() {
+            if *module_id == home {
+                continue;
+            }
+
+            // The module's idents include everything it defines, exposed or not.
+            let is_exposed = match ident_ids_by_module.get(module_id) {
+                Some(ident_ids) => ident_ids.get_id(name).map_or(false, |ident_id| {
+                    exposed_symbols.contains(&Symbol::new(*module_id, ident_id))
+                }),
+                None => false,
+            };
+
+            if !is_exposed {
+                continue;
+            }
+
+            if let Some(module_name) = module_ids.get_name(*module_id) {
+                exposed_by.push(ExposingModule {
+                    module_name: module_name.clone(),
+                    imported: dep_idents.get(module_id).is_some(),
+                });
+            }
+        }
+
+        // Suggest the modules that are already imported first.
+        exposed_by.sort_by(|a, b| {
+            (!a.imported, a.module_name.as_str()).cmp(&(!b.imported, b.module_name.as_str()))
+        });
     }
 }
 
@@ -5613,6 +6176,7 @@ fn parse<'a>(arena: &'a Bump, header: ModuleHeader<'a>) -> Result<Msg<'a>, Loadi
     let parse_end = Instant::now();
 
     module_timing.parse_body = parse_end.duration_since(parse_start);
+    module_timing.add_span("Parse body", parse_start, module_timing.parse_body);
 
     let imported_modules = header.imported_modules;
 
@@ -5626,6 +6190,7 @@ fn parse<'a>(arena: &'a Bump, header: ModuleHeader<'a>) -> Result<Msg<'a>, Loadi
         deps_by_name,
         exposed_ident_ids,
         exposed_imports,
+        opaque_exposes,
         module_path,
         header_type,
         symbols_from_requires,
@@ -5642,6 +6207,7 @@ fn parse<'a>(arena: &'a Bump, header: ModuleHeader<'a>) -> Result<Msg<'a>, Loadi
         imported_modules,
         exposed_ident_ids,
         exposed_imports,
+        opaque_exposes,
         parsed_defs,
         symbols_from_requires,
         header_type,
@@ -5766,6 +6332,9 @@ fn make_specializations<'a>(
     exposed_by_module: &ExposedByModule,
     derived_module: SharedDerivedModule,
     mut expectations: Option<Expectations>,
+    max_specializations: Option<usize>,
+    coverage: bool,
+    strip_expects: bool,
 ) -> Msg<'a> {
     let make_specializations_start = Instant::now();
     let mut update_mode_ids = UpdateModeIds::new();
@@ -5798,6 +6367,9 @@ fn make_specializations<'a>(
     procs.module_thunks = procs_base.module_thunks;
     procs.runtime_errors = procs_base.runtime_errors;
     procs.imported_module_thunks = procs_base.imported_module_thunks;
+    procs.max_specializations = max_specializations;
+    procs.coverage = coverage;
+    procs.strip_expects = strip_expects;
 
     // TODO: for now this final specialization pass is sequential,
     // with no parallelization at all. We should try to parallelize
@@ -5811,15 +6383,25 @@ fn make_specializations<'a>(
     );
 
     let external_specializations_requested = procs.externals_we_need.clone();
+    let specialization_origins = std::mem::take(&mut procs.specialization_origins);
+    let tail_recursive = std::mem::take(&mut procs.tail_recursive);
+    let body_regions = std::mem::take(&mut procs.body_regions);
     let (procedures, restored_procs_base) = procs.get_specialized_procs_without_rc(&mut mono_env);
 
     // Turn `Bytes.Decode.IdentId(238)` into `Bytes.Decode.238`, we rely on this in mono tests
     mono_env.home.register_debug_idents(mono_env.ident_ids);
 
     let make_specializations_end = Instant::now();
+    let make_specializations_duration =
+        make_specializations_end.duration_since(make_specializations_start);
     module_timing
         .make_specializations
-        .push(make_specializations_end.duration_since(make_specializations_start));
+        .push(make_specializations_duration);
+    module_timing.add_span(
+        "Make Specializations",
+        make_specializations_start,
+        make_specializations_duration,
+    );
 
     Msg::MadeSpecializations {
         module_id: home,
@@ -5832,6 +6414,9 @@ fn make_specializations<'a>(
         expectations,
         external_specializations_requested,
         module_timing,
+        specialization_origins,
+        tail_recursive,
+        body_regions,
     }
 }
 
@@ -5952,7 +6537,7 @@ fn build_pending_specializations<'a>(
                             closure_data.arguments,
                             closure_data.return_type,
                             *closure_data.loc_body,
-                            false,
+                            roc_can::expr::Recursive::NotRecursive,
                         );
                     }
                     _ => {
@@ -5966,9 +6551,11 @@ fn build_pending_specializations<'a>(
                             // This is a top-level definition, so it cannot capture anything
                             captured_symbols: CapturedSymbols::None,
                             body: body.value,
+                            body_region: body.region,
                             body_var: expr_var,
                             // This is a 0-arity thunk, so it cannot be recursive
                             is_self_recursive: false,
+                            is_tail_recursive: false,
                         };
 
                         procs_base.partial_procs.insert(symbol, proc);
@@ -6021,7 +6608,11 @@ fn build_pending_specializations<'a>(
                     );
                 }
 
-                let is_recursive = matches!(tag, Recursive(_) | TailRecursive(_));
+                let recursive = match tag {
+                    Recursive(_) => roc_can::expr::Recursive::Recursive,
+                    TailRecursive(_) => roc_can::expr::Recursive::TailRecursive,
+                    _ => roc_can::expr::Recursive::NotRecursive,
+                };
 
                 register_toplevel_function_into_procs_base(
                     &mut mono_env,
@@ -6031,7 +6622,7 @@ fn build_pending_specializations<'a>(
                     function_def.arguments.clone(),
                     function_def.return_type,
                     body,
-                    is_recursive,
+                    recursive,
                 );
             }
             Destructure(d_index) => {
@@ -6105,9 +6696,11 @@ fn build_pending_specializations<'a>(
                     // This is a top-level definition, so it cannot capture anything
                     captured_symbols: CapturedSymbols::None,
                     body: body.value,
+                    body_region: body.region,
                     body_var: expr_var,
                     // This is a 0-arity thunk, so it cannot be recursive
                     is_self_recursive: false,
+                    is_tail_recursive: false,
                 };
 
                 procs_base.partial_procs.insert(symbol, proc);
@@ -6174,9 +6767,11 @@ fn build_pending_specializations<'a>(
                     // This is a top-level definition, so it cannot capture anything
                     captured_symbols: CapturedSymbols::None,
                     body: body.value,
+                    body_region: body.region,
                     body_var: expr_var,
                     // This is a 0-arity thunk, so it cannot be recursive
                     is_self_recursive: false,
+                    is_tail_recursive: false,
                 };
 
                 // extend the region of the expect expression with the region of the preceding
@@ -6247,9 +6842,11 @@ fn build_pending_specializations<'a>(
                     // This is a top-level definition, so it cannot capture anything
                     captured_symbols: CapturedSymbols::None,
                     body: body.value,
+                    body_region: body.region,
                     body_var: expr_var,
                     // This is a 0-arity thunk, so it cannot be recursive
                     is_self_recursive: false,
+                    is_tail_recursive: false,
                 };
 
                 // extend the region of the expect expression with the region of the preceding
@@ -6261,6 +6858,79 @@ fn build_pending_specializations<'a>(
                 toplevel_expects.fx.insert(symbol, region);
                 procs_base.partial_procs.insert(symbol, proc);
             }
+            Bench => {
+                // benches are built along with expectations, for `roc bench`
+                if !build_expects {
+                    continue;
+                }
+
+                // mark this symbol as a top-level thunk before any other work on the procs
+                module_thunks.push(symbol);
+
+                let expr_var = Variable::EMPTY_RECORD;
+
+                let is_host_exposed = true;
+
+                // If this is an exposed symbol, we need to
+                // register it as such. Otherwise, since it
+                // never gets called by Roc code, it will never
+                // get specialized!
+                if is_host_exposed {
+                    let layout_result =
+                        layout_cache.raw_from_var(mono_env.arena, expr_var, mono_env.subs);
+
+                    // cannot specialize when e.g. main's type contains type variables
+                    if let Err(e) = layout_result {
+                        match e {
+                            LayoutProblem::Erroneous => {
+                                let message = "top level function has erroneous type";
+                                procs_base.runtime_errors.insert(symbol, message);
+                                continue;
+                            }
+                            LayoutProblem::UnresolvedTypeVar(v) => {
+                                let message = format!(
+                                    "top level function has unresolved type variable {:?}",
+                                    v
+                                );
+                                procs_base
+                                    .runtime_errors
+                                    .insert(symbol, mono_env.arena.alloc(message));
+                                continue;
+                            }
+                        }
+                    }
+
+                    procs_base.host_specializations.insert_host_exposed(
+                        mono_env.subs,
+                        LambdaName::no_niche(symbol),
+                        annotation,
+                        expr_var,
+                    );
+                }
+
+                let proc = PartialProc {
+                    annotation: expr_var,
+                    // This is a 0-arity thunk, so it has no arguments.
+                    pattern_symbols: &[],
+                    // This is a top-level definition, so it cannot capture anything
+                    captured_symbols: CapturedSymbols::None,
+                    body: body.value,
+                    body_region: body.region,
+                    body_var: expr_var,
+                    // This is a 0-arity thunk, so it cannot be recursive
+                    is_self_recursive: false,
+                    is_tail_recursive: false,
+                };
+
+                // extend the region of the bench with the region of the preceding comment, which
+                // names it in the results
+                let name_region = declarations.symbols[index].region;
+                let expr_region = declarations.expressions[index].region;
+                let region = Region::span_across(&name_region, &expr_region);
+
+                toplevel_expects.benches.insert(symbol, region);
+                procs_base.partial_procs.insert(symbol, proc);
+            }
         }
     }
 
@@ -6271,6 +6941,11 @@ fn build_pending_specializations<'a>(
     let find_specializations_end = Instant::now();
     module_timing.find_specializations =
         find_specializations_end.duration_since(find_specializations_start);
+    module_timing.add_span(
+        "Find Specializations",
+        find_specializations_start,
+        module_timing.find_specializations,
+    );
 
     Msg::FoundSpecializations {
         module_id: home,
@@ -6297,7 +6972,7 @@ fn register_toplevel_function_into_procs_base<'a>(
     )>,
     return_type: Variable,
     body: Loc<roc_can::expr::Expr>,
-    is_recursive: bool,
+    recursive: roc_can::expr::Recursive,
 ) {
     let partial_proc = PartialProc::from_named_function(
         mono_env,
@@ -6305,7 +6980,7 @@ fn register_toplevel_function_into_procs_base<'a>(
         arguments,
         body,
         CapturedSymbols::None,
-        is_recursive,
+        recursive,
         return_type,
     );
 
@@ -6383,7 +7058,7 @@ fn load_derived_partial_procs<'a>(
                     arguments.clone(),
                     *loc_body,
                     CapturedSymbols::None,
-                    recursive.is_recursive(),
+                    recursive,
                     return_type,
                 )
             }
@@ -6398,9 +7073,11 @@ fn load_derived_partial_procs<'a>(
                     // This is a top-level definition, so it cannot capture anything
                     captured_symbols: CapturedSymbols::None,
                     body: derived_expr,
+                    body_region: Region::zero(),
                     body_var: derived_expr_var,
                     // This is a 0-arity thunk, so it cannot be recursive
                     is_self_recursive: false,
+                    is_tail_recursive: false,
                 }
             }
         };
@@ -6419,6 +7096,11 @@ fn load_derived_partial_procs<'a>(
 
     module_timing.find_specializations =
         load_derived_procs_end.duration_since(load_derived_procs_start);
+    module_timing.add_span(
+        "Find Specializations",
+        load_derived_procs_start,
+        module_timing.find_specializations,
+    );
 }
 
 fn run_task<'a>(
@@ -6457,6 +7139,8 @@ fn run_task<'a>(
             abilities_store,
             skip_constraint_gen,
             exposed_module_ids,
+            ident_ids_by_module,
+            exposed_symbols_by_module,
         } => {
             let can_and_con = canonicalize_and_constrain(
                 arena,
@@ -6468,6 +7152,8 @@ fn run_task<'a>(
                 parsed,
                 skip_constraint_gen,
                 exposed_module_ids,
+                &ident_ids_by_module,
+                &exposed_symbols_by_module,
             );
 
             Ok(Msg::CanonicalizedAndConstrained(can_and_con))
@@ -6544,6 +7230,9 @@ fn run_task<'a>(
             exposed_by_module,
             derived_module,
             expectations,
+            max_specializations,
+            coverage,
+            strip_expects,
         } => Ok(make_specializations(
             arena,
             module_id,
@@ -6558,6 +7247,9 @@ fn run_task<'a>(
             &exposed_by_module,
             derived_module,
             expectations,
+            max_specializations,
+            coverage,
+            strip_expects,
         )),
     }?;
 
@@ -6572,10 +7264,12 @@ fn to_import_cycle_report(
     module_ids: ModuleIds,
     all_ident_ids: IdentIdsByModule,
     import_cycle: Vec<ModuleId>,
+    import_sites: Vec<ImportSite>,
     filename: PathBuf,
     render: RenderTarget,
+    palette: Palette,
 ) -> String {
-    use roc_reporting::report::{Report, RocDocAllocator, DEFAULT_PALETTE};
+    use roc_reporting::report::{Report, RocDocAllocator};
     use ven_pretty::DocAllocator;
 
     // import_cycle looks like CycleModule, Import1, ..., ImportN, CycleModule
@@ -6584,7 +7278,8 @@ fn to_import_cycle_report(
     let source_of_cycle = import_cycle.first().unwrap();
 
     // We won't be printing any lines for this report, so this is okay.
-    // TODO: it would be nice to show how each module imports another in the cycle.
+    // The modules in the cycle are in different files, so instead of snippets
+    // we point at each import by its line and column.
     let src_lines = &[];
 
     let interns = Interns {
@@ -6593,7 +7288,7 @@ fn to_import_cycle_report(
     };
     let alloc = RocDocAllocator::new(src_lines, *source_of_cycle, &interns);
 
-    let doc = alloc.stack([
+    let mut docs = vec![
         alloc.concat([
             alloc.reflow("I can't compile "),
             alloc.module(*source_of_cycle),
@@ -6611,8 +7306,31 @@ fn to_import_cycle_report(
                 .map(|module| alloc.module(module))
                 .collect(),
         ),
-        alloc.reflow("Cyclic dependencies are not allowed in Roc! Can you restructure a module in this import chain so that it doesn't have to depend on itself?")
-    ]);
+    ];
+
+    if !import_sites.is_empty() {
+        docs.push(alloc.reflow("These are the imports that make up the cycle:"));
+        docs.push(
+            alloc
+                .vcat(import_sites.into_iter().map(|site| {
+                    alloc.concat([
+                        alloc.module(site.importer),
+                        alloc.reflow(" imports "),
+                        alloc.module(site.imported),
+                        alloc.text(format!(
+                            " on line {}, column {}",
+                            site.position.line + 1,
+                            site.position.column + 1
+                        )),
+                    ])
+                }))
+                .indent(4),
+        );
+    }
+
+    docs.push(alloc.reflow("Cyclic dependencies are not allowed in Roc! Can you restructure a module in this import chain so that it doesn't have to depend on itself?"));
+
+    let doc = alloc.stack(docs);
 
     let report = Report {
         filename,
@@ -6622,7 +7340,121 @@ fn to_import_cycle_report(
     };
 
     let mut buf = String::new();
-    let palette = DEFAULT_PALETTE;
+    report.render(render, &mut buf, &alloc, &palette);
+    buf
+}
+
+/// How many of a function's specializations to list in its report
+const LISTED_SPECIALIZATIONS: usize = 10;
+
+#[allow(clippy::too_many_arguments)]
+fn to_too_many_specializations_report(
+    interns: &Interns,
+    layout_interner: &STLayoutInterner,
+    symbol: Symbol,
+    origins: &[SpecializationOrigin],
+    exceeded_limit: Option<usize>,
+    filename: PathBuf,
+    render: RenderTarget,
+    palette: Palette,
+) -> String {
+    use roc_reporting::report::{Report, RocDocAllocator};
+    use ven_pretty::DocAllocator;
+
+    // We won't be printing any lines for this report, since specializations
+    // don't point back to the source.
+    let src_lines = &[];
+    let alloc = RocDocAllocator::new(src_lines, symbol.module_id(), interns);
+
+    let print_layout = |origin: &SpecializationOrigin| {
+        let arguments: Vec<_> = origin
+            .layout
+            .arguments
+            .iter()
+            .map(|argument| layout_interner.dbg(*argument))
+            .collect();
+
+        format!(
+            "{} -> {}",
+            arguments.join(", "),
+            layout_interner.dbg(origin.layout.result)
+        )
+    };
+
+    let mut docs = vec![alloc.concat([
+        alloc.symbol_qualified(symbol),
+        alloc.reflow(" was specialized "),
+        alloc.text(origins.len().to_string()),
+        alloc.reflow(
+            " times, once for each different set of types it was called with. These are the first:",
+        ),
+    ])];
+
+    docs.push(
+        alloc
+            .vcat(origins.iter().take(LISTED_SPECIALIZATIONS).map(|origin| {
+                let layout = alloc.type_str(&print_layout(origin));
+
+                match origin.requested_by {
+                    Some(caller) => alloc.concat([
+                        layout,
+                        alloc.reflow(", called from "),
+                        alloc.symbol_qualified(caller),
+                    ]),
+                    None => layout,
+                }
+            }))
+            .indent(4),
+    );
+
+    docs.push(alloc.reflow("This usually means the types grow with each call, for example in a function that calls itself with a bigger record or tuple than it was given. Adding a type annotation that fixes the types can stop it."));
+
+    let severity = match exceeded_limit {
+        Some(max) => {
+            docs.push(alloc.concat([
+                alloc.reflow("I stopped building, since that's more than the limit of "),
+                alloc.text(max.to_string()),
+                alloc.reflow(" given by "),
+                alloc.keyword("--max-specializations"),
+                alloc.reflow("."),
+            ]));
+
+            Severity::RuntimeError
+        }
+        None => Severity::Warning,
+    };
+
+    let doc = alloc.stack(docs);
+
+    let report = Report {
+        filename,
+        doc,
+        title: "TOO MANY SPECIALIZATIONS".to_string(),
+        severity,
+    };
+
+    let mut buf = String::new();
+    report.render(render, &mut buf, &alloc, &palette);
+    buf
+}
+
+fn to_not_a_loop_report_string(
+    interns: &Interns,
+    symbol: Symbol,
+    region: Region,
+    filename: PathBuf,
+    src: &str,
+    render: RenderTarget,
+    palette: Palette,
+) -> String {
+    use roc_reporting::report::{to_not_a_loop_report, RocDocAllocator};
+
+    let src_lines = src.lines().collect::<Vec<_>>();
+    let lines = LineInfo::new(src);
+    let alloc = RocDocAllocator::new(&src_lines, symbol.module_id(), interns);
+    let report = to_not_a_loop_report(&alloc, &lines, filename, symbol, region);
+
+    let mut buf = String::new();
     report.render(render, &mut buf, &alloc, &palette);
     buf
 }
@@ -6634,8 +7466,9 @@ fn to_incorrect_module_name_report<'a>(
     filename: PathBuf,
     src: &'a [u8],
     render: RenderTarget,
+    palette: Palette,
 ) -> String {
-    use roc_reporting::report::{Report, RocDocAllocator, DEFAULT_PALETTE};
+    use roc_reporting::report::{Report, RocDocAllocator};
     use ven_pretty::DocAllocator;
 
     let IncorrectModuleName {
@@ -6671,7 +7504,6 @@ fn to_incorrect_module_name_report<'a>(
     };
 
     let mut buf = String::new();
-    let palette = DEFAULT_PALETTE;
     report.render(render, &mut buf, &alloc, &palette);
     buf
 }
@@ -6720,8 +7552,12 @@ fn to_parse_problem_report<'a>(
     buf
 }
 
-fn to_missing_platform_report(module_id: ModuleId, other: &PlatformPath) -> String {
-    use roc_reporting::report::{Report, RocDocAllocator, DEFAULT_PALETTE};
+fn to_missing_platform_report(
+    module_id: ModuleId,
+    other: &PlatformPath,
+    palette: Palette,
+) -> String {
+    use roc_reporting::report::{Report, RocDocAllocator};
     use ven_pretty::DocAllocator;
     use PlatformPath::*;
 
@@ -6797,7 +7633,6 @@ fn to_missing_platform_report(module_id: ModuleId, other: &PlatformPath) -> Stri
         }
     };
 
-    let palette = DEFAULT_PALETTE;
     let mut buf = String::new();
     report.render_color_terminal(&mut buf, &alloc, &palette);
 
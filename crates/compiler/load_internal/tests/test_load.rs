@@ -54,6 +54,12 @@ fn load_and_typecheck(
         palette: DEFAULT_PALETTE,
         threading: Threading::Single,
         exec_mode: ExecutionMode::Check,
+        max_specializations: None,
+        coverage: false,
+        fold_constants: false,
+        hoist_loop_invariants: false,
+        strip_expects: false,
+        cancelled: None,
     };
 
     match roc_load_internal::file::load(
@@ -288,6 +294,10 @@ fn expect_types(mut loaded_module: LoadedModule, mut expected_types: HashMap<&st
                 // at least at the moment this does not happen
                 panic!("Unexpected expectation in module declarations");
             }
+            Bench => {
+                // at least at the moment this does not happen
+                panic!("Unexpected bench in module declarations");
+            }
         };
     }
 
@@ -339,6 +349,76 @@ fn import_transitive_alias() {
     assert!(multiple_modules("import_transitive_alias", modules).is_ok());
 }
 
+#[test]
+fn references_across_modules() {
+    use roc_can::references::ReferenceKind::*;
+
+    let modules = vec![
+        (
+            "Dep",
+            indoc!(
+                r#"
+                interface Dep exposes [double] imports []
+
+                double = \n -> n * 2
+                "#
+            ),
+        ),
+        (
+            "Main.roc",
+            indoc!(
+                r#"
+                interface Main exposes [quadruple] imports [Dep.{ double }]
+
+                quadruple = \n ->
+                    twice = double n
+                    Dep.double twice
+                "#
+            ),
+        ),
+    ];
+
+    let loaded = multiple_modules("references_across_modules", modules).unwrap();
+
+    let dep = loaded
+        .interns
+        .module_ids
+        .get_id(&ModuleName::from("Dep"))
+        .unwrap();
+    let double = loaded
+        .interns
+        .all_ident_ids
+        .get(&dep)
+        .and_then(|ident_ids| ident_ids.get_id("double"))
+        .unwrap();
+    let double = roc_module::symbol::Symbol::new(dep, double);
+
+    let references: Vec<_> = loaded
+        .references(double)
+        .into_iter()
+        .map(|(module_id, reference)| {
+            let (_, source) = &loaded.sources[&module_id];
+            let text = &source
+                [reference.region.start().offset as usize..reference.region.end().offset as usize];
+
+            (
+                loaded.interns.module_name(module_id).as_str(),
+                text,
+                reference.kind,
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        references,
+        vec![
+            ("Dep", "double", Definition),
+            ("Main", "double", Usage),
+            ("Main", "Dep.double", Usage),
+        ]
+    );
+}
+
 #[test]
 fn interface_with_deps() {
     let subs_by_module = Default::default();
@@ -373,7 +453,7 @@ fn interface_with_deps() {
                 def_count += 1;
             }
             MutualRecursion { .. } => { /* do nothing, not a def */ }
-            Expectation | ExpectationFx => { /* do nothing, not a def */ }
+            Expectation | ExpectationFx | Bench => { /* do nothing, not a def */ }
         }
     }
 
@@ -618,7 +698,7 @@ fn parse_problem() {
             report,
             indoc!(
                 "
-                    ── UNFINISHED LIST ──────────────────────────────────── tmp/parse_problem/Main ─
+                    ── UNFINISHED LIST [E0152] ──────────────────────────── tmp/parse_problem/Main ─
 
                     I am partway through started parsing a list, but I got stuck here:
 
@@ -711,6 +791,46 @@ fn platform_does_not_exist() {
     }
 }
 
+#[test]
+fn malformed_lockfile() {
+    use std::fs;
+
+    let dir = roc_test_utils::TmpDir::new("tmp/malformed_lockfile");
+    let app_path = dir.path().join("Main.roc");
+
+    fs::write(dir.path().join("roc.lock"), "basic-cli 0.5.0\n").unwrap();
+    fs::write(
+        &app_path,
+        indoc!(
+            r#"
+                app "example"
+                    packages { pf: "basic-cli" }
+                    imports []
+                    provides [main] to pf
+
+                main = ""
+                "#
+        ),
+    )
+    .unwrap();
+
+    let arena = Bump::new();
+
+    match load_and_typecheck(&arena, app_path, Default::default(), TARGET_INFO) {
+        Err(LoadingProblem::FormattedReport(report)) => {
+            assert!(report.contains("LOCKFILE PROBLEM"), "report=({})", report);
+            assert!(report.contains("roc.lock"), "report=({})", report);
+            assert!(
+                report.contains("line 1: expected `name version url`, found `basic-cli 0.5.0`"),
+                "report=({})",
+                report
+            );
+        }
+        Err(other) => panic!("expected a lockfile report, got {:?}", other),
+        Ok(_) => unreachable!("we expect failure here"),
+    }
+}
+
 #[test]
 fn platform_parse_error() {
     let modules = vec![
@@ -825,7 +945,7 @@ fn opaque_wrapped_unwrapped_outside_defining_module() {
         err,
         indoc!(
             r#"
-                ── OPAQUE TYPE DECLARED OUTSIDE SCOPE ─ ...rapped_outside_defining_module/Main ─
+                ── OPAQUE TYPE DECLARED OUTSIDE SCOPE [E0216] ─ ...rapped_outside_defining_module/Main ─
 
                 The unwrapped opaque type Age referenced here:
 
@@ -839,7 +959,7 @@ fn opaque_wrapped_unwrapped_outside_defining_module() {
 
                 Note: Opaque types can only be wrapped and unwrapped in the module they are defined in!
 
-                ── OPAQUE TYPE DECLARED OUTSIDE SCOPE ─ ...rapped_outside_defining_module/Main ─
+                ── OPAQUE TYPE DECLARED OUTSIDE SCOPE [E0216] ─ ...rapped_outside_defining_module/Main ─
 
                 The unwrapped opaque type Age referenced here:
 
@@ -853,7 +973,7 @@ fn opaque_wrapped_unwrapped_outside_defining_module() {
 
                 Note: Opaque types can only be wrapped and unwrapped in the module they are defined in!
 
-                ── UNUSED IMPORT ─── tmp/opaque_wrapped_unwrapped_outside_defining_module/Main ─
+                ── UNUSED IMPORT [E0702] ─ tmp/opaque_wrapped_unwrapped_outside_defining_module/Main ─
 
                 Nothing from Age is used in this module.
 
@@ -868,6 +988,94 @@ fn opaque_wrapped_unwrapped_outside_defining_module() {
     );
 }
 
+#[test]
+fn alias_exposed_opaquely_usable_by_name() {
+    let modules = vec![
+        (
+            "Token",
+            indoc!(
+                r#"
+                    interface Token exposes [opaque Token, fromStr, toStr] imports []
+
+                    Token : { text : Str }
+
+                    fromStr : Str -> Token
+                    fromStr = \text -> { text }
+
+                    toStr : Token -> Str
+                    toStr = \token -> token.text
+                    "#
+            ),
+        ),
+        (
+            "Main",
+            indoc!(
+                r#"
+                    interface Main exposes [roundTrip] imports [Token.{ Token }]
+
+                    roundTrip : Str -> Str
+                    roundTrip = \text ->
+                        token : Token
+                        token = Token.fromStr text
+
+                        Token.toStr token
+                    "#
+            ),
+        ),
+    ];
+
+    let result = multiple_modules("alias_exposed_opaquely_usable_by_name", modules);
+
+    assert!(result.is_ok(), "{}", result.unwrap_err());
+}
+
+#[test]
+fn alias_exposed_opaquely_structure_hidden_outside_defining_module() {
+    let modules = vec![
+        (
+            "Token",
+            indoc!(
+                r#"
+                    interface Token exposes [opaque Token, fromStr] imports []
+
+                    Token : { text : Str }
+
+                    fromStr : Str -> Token
+                    fromStr = \text -> { text }
+                    "#
+            ),
+        ),
+        (
+            "Main",
+            indoc!(
+                r#"
+                    interface Main exposes [text, forged] imports [Token]
+
+                    text = (Token.fromStr "hi").text
+
+                    forged : Token.Token
+                    forged = { text: "hi" }
+                    "#
+            ),
+        ),
+    ];
+
+    let arena = Bump::new();
+    let mut loaded_module = multiple_modules_help(
+        "alias_exposed_opaquely_structure_hidden_outside_defining_module",
+        &arena,
+        modules,
+    )
+    .unwrap()
+    .unwrap();
+
+    let home = loaded_module.module_id;
+    let type_problems = loaded_module.type_problems.remove(&home).unwrap_or_default();
+
+    // neither the field access nor the record literal may see through the alias
+    assert_eq!(type_problems.len(), 2, "{:?}", type_problems);
+}
+
 #[test]
 fn issue_2863_module_type_does_not_exist() {
     let modules = vec![
@@ -908,7 +1116,7 @@ fn issue_2863_module_type_does_not_exist() {
                 report,
                 indoc!(
                     "
-                        ── UNRECOGNIZED NAME ────────── tmp/issue_2863_module_type_does_not_exist/Main ─
+                        ── UNRECOGNIZED NAME [E0202] ── tmp/issue_2863_module_type_does_not_exist/Main ─
 
                         Nothing is named `DoesNotExist` in this scope.
 
@@ -984,7 +1192,7 @@ fn module_doesnt_match_file_path() {
         err,
         indoc!(
             r#"
-            ── WEIRD MODULE NAME ─────────────────── tmp/module_doesnt_match_file_path/Age ─
+            ── WEIRD MODULE NAME [E0170] ─────────── tmp/module_doesnt_match_file_path/Age ─
 
             This module name does not correspond with the file path it is defined
             in:
@@ -1017,7 +1225,7 @@ fn module_cyclic_import_itself() {
         err,
         indoc!(
             r#"
-            ── IMPORT CYCLE ────────────────────────── tmp/module_cyclic_import_itself/Age ─
+            ── IMPORT CYCLE [E0601] ────────────────── tmp/module_cyclic_import_itself/Age ─
 
             I can't compile Age because it depends on itself through the following
             chain of module imports:
@@ -1028,6 +1236,10 @@ fn module_cyclic_import_itself() {
                 │     Age
                 └─────┘
 
+            These are the imports that make up the cycle:
+
+                Age imports Age on line 1, column 35
+
             Cyclic dependencies are not allowed in Roc! Can you restructure a
             module in this import chain so that it doesn't have to depend on
             itself?"#
@@ -1063,7 +1275,7 @@ fn module_cyclic_import_transitive() {
         err,
         indoc!(
             r#"
-            ── IMPORT CYCLE ────────────────── tmp/module_cyclic_import_transitive/Age.roc ─
+            ── IMPORT CYCLE [E0601] ────────── tmp/module_cyclic_import_transitive/Age.roc ─
 
             I can't compile Age because it depends on itself through the following
             chain of module imports:
@@ -1076,6 +1288,11 @@ fn module_cyclic_import_transitive() {
                 │     Age
                 └─────┘
 
+            These are the imports that make up the cycle:
+
+                Age imports Person on line 1, column 35
+                Person imports Age on line 1, column 38
+
             Cyclic dependencies are not allowed in Roc! Can you restructure a
             module in this import chain so that it doesn't have to depend on
             itself?"#
@@ -1111,7 +1328,7 @@ fn nested_module_has_incorrect_name() {
         err,
         indoc!(
             r#"
-            ── INCORRECT MODULE NAME ──── tmp/nested_module_has_incorrect_name/Dep/Foo.roc ─
+            ── INCORRECT MODULE NAME [E0602] ─ tmp/nested_module_has_incorrect_name/Dep/Foo.roc ─
 
             This module has a different name than I expected:
 
@@ -1126,3 +1343,39 @@ fn nested_module_has_incorrect_name() {
         err
     );
 }
+
+#[test]
+fn unrecognized_name_only_suggests_exposed_values() {
+    let modules = vec![
+        (
+            "Dep.roc",
+            indoc!(
+                r#"
+                interface Dep exposes [shown] imports []
+
+                shown = 1
+
+                hidden = 2
+                "#
+            ),
+        ),
+        (
+            "Main.roc",
+            indoc!(
+                r#"
+                interface Main exposes [main] imports [Dep]
+
+                main = shown + hidden
+                "#
+            ),
+        ),
+    ];
+
+    let err =
+        multiple_modules("unrecognized_name_only_suggests_exposed_values", modules).unwrap_err();
+
+    assert_eq!(err.matches("── UNRECOGNIZED NAME").count(), 2, "\n{}", err);
+    assert!(err.contains("Dep.shown"), "\n{}", err);
+    // `hidden` is defined in Dep, but Main couldn't use it even if it qualified it
+    assert!(!err.contains("Dep.hidden"), "\n{}", err);
+}
@@ -33,6 +33,9 @@ pub enum LowLevel {
     StrWithCapacity,
     StrGraphemes,
     StrReleaseExcessCapacity,
+    StrReplaceEachUnsafe,
+    StrSplitOn,
+    StrWalkUtf8,
     ListLen,
     ListWithCapacity,
     ListReserve,
@@ -105,7 +108,6 @@ pub enum LowLevel {
     NumIntCast,
     NumToFloatCast,
     NumToIntChecked,
-    NumToFloatChecked,
     NumToStr,
     NumCountLeadingZeroBits,
     NumCountTrailingZeroBits,
@@ -115,7 +117,6 @@ pub enum LowLevel {
     And,
     Or,
     Not,
-    Hash,
     PtrCast,
     PtrWrite,
     RefCountIncRcPtr,
@@ -130,7 +131,7 @@ pub enum LowLevel {
 
 macro_rules! higher_order {
     () => {
-        ListMap | ListMap2 | ListMap3 | ListMap4 | ListSortWith
+        ListMap | ListMap2 | ListMap3 | ListMap4 | ListSortWith | StrWalkUtf8
     };
 }
 
@@ -152,6 +153,7 @@ impl LowLevel {
             ListMap3 => 3,
             ListMap4 => 4,
             ListSortWith => 1,
+            StrWalkUtf8 => 2,
             _ => unreachable!(),
         }
     }
@@ -211,6 +213,7 @@ macro_rules! map_symbol_to_lowlevel {
                 LowLevel::ListMap3 => unreachable!(),
                 LowLevel::ListMap4 => unreachable!(),
                 LowLevel::ListSortWith => unreachable!(),
+                LowLevel::StrWalkUtf8 => unreachable!(),
 
                 // (un)boxing is handled in a custom way
                 LowLevel::BoxExpr => unreachable!(),
@@ -220,11 +223,9 @@ macro_rules! map_symbol_to_lowlevel {
                 LowLevel::NumIntCast => unreachable!(),
                 LowLevel::NumToFloatCast => unreachable!(),
                 LowLevel::NumToIntChecked => unreachable!(),
-                LowLevel::NumToFloatChecked => unreachable!(),
 
 
                 // these are used internally and not tied to a symbol
-                LowLevel::Hash => unimplemented!(),
                 LowLevel::PtrCast => unimplemented!(),
                 LowLevel::PtrWrite => unimplemented!(),
                 LowLevel::RefCountIncRcPtr => unimplemented!(),
@@ -271,6 +272,8 @@ map_symbol_to_lowlevel! {
     StrWithCapacity <= STR_WITH_CAPACITY,
     StrGraphemes <= STR_GRAPHEMES,
     StrReleaseExcessCapacity <= STR_RELEASE_EXCESS_CAPACITY,
+    StrReplaceEachUnsafe <= STR_REPLACE_EACH_UNSAFE,
+    StrSplitOn <= STR_SPLIT_ON,
     ListLen <= LIST_LEN,
     ListGetCapacity <= LIST_CAPACITY,
     ListWithCapacity <= LIST_WITH_CAPACITY,
@@ -719,6 +719,10 @@ impl IdentIdsByModule {
         self.0.keys()
     }
 
+    pub fn iter(&self) -> impl Iterator<Item = (&ModuleId, &IdentIds)> {
+        self.0.iter()
+    }
+
     pub fn len(&self) -> usize {
         self.0.len()
     }
@@ -1338,6 +1342,8 @@ define_builtins! {
         56 STR_IS_VALID_SCALAR: "isValidScalar"
         57 STR_RELEASE_EXCESS_CAPACITY: "releaseExcessCapacity"
         58 STR_WALK_UTF8: "walkUtf8"
+        59 STR_REPLACE_EACH_UNSAFE: "replaceEachUnsafe"
+        60 STR_SPLIT_ON: "splitOn"
     }
     6 LIST: "List" => {
         0 LIST_LIST: "List" exposed_apply_type=true // the List.List type alias
@@ -1573,6 +1579,13 @@ define_builtins! {
     14 JSON: "Json" => {
         0 JSON_JSON: "Json"
     }
+    15 TARGET: "Target" => {
+        0 TARGET_TARGET: "Target"
+        1 TARGET_OPERATING_SYSTEM: "OperatingSystem"
+        2 TARGET_ARCHITECTURE: "Architecture"
+        3 TARGET_TARGET_OS: "targetOs"
+        4 TARGET_TARGET_ARCHITECTURE: "targetArchitecture"
+    }
 
-    num_modules: 15 // Keep this count up to date by hand! (TODO: see the mut_map! macro for how we could determine this count correctly in the macro)
+    num_modules: 16 // Keep this count up to date by hand! (TODO: see the mut_map! macro for how we could determine this count correctly in the macro)
 }
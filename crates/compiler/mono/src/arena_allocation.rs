@@ -0,0 +1,59 @@
+//! Make decrements non-recursive, for `--arena-allocation` builds.
+//!
+//! When a value's last reference is dropped, a `dec` frees it, and first decrements everything
+//! it refers to, which for a large structure means walking all of it. On a platform that
+//! allocates from an arena and frees it all at once when the program exits, that walk is wasted
+//! work, and for short-lived programs like CLI tools it can be most of the time spent tearing
+//! down. Here every `dec` becomes a `decref`, which only decrements the value itself.
+//!
+//! Values that are still referenced from the dropped value keep their refcounts, so they are
+//! never freed (which the arena takes care of), and may be copied rather than updated in place
+//! if they are later modified.
+//!
+//! The other half of `--arena-allocation` is in the builtins: with the LLVM backend, small
+//! allocations are carved out of large chunks from `roc_alloc`, and `roc_dealloc` is never
+//! called, so a `decref` that drops the last reference doesn't free anything either.
+
+use bumpalo::Bump;
+use roc_collections::MutMap;
+use roc_module::symbol::Symbol;
+
+use crate::ir::{ModifyRc, Proc, ProcLayout, Stmt};
+use crate::rewrite::{rewrite_children, Rewrite};
+
+/// This must run after refcounts are inserted.
+pub fn skip_recursive_decrements<'a>(
+    arena: &'a Bump,
+    procedures: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) {
+    let mut non_recursive = NonRecursive { arena };
+
+    for proc in procedures.values_mut() {
+        proc.body = non_recursive.rewrite(&proc.body);
+    }
+}
+
+struct NonRecursive<'a> {
+    arena: &'a Bump,
+}
+
+impl<'a> Rewrite<'a> for NonRecursive<'a> {
+    fn arena(&self) -> &'a Bump {
+        self.arena
+    }
+
+    fn rewrite(&mut self, stmt: &Stmt<'a>) -> Stmt<'a> {
+        match stmt {
+            Stmt::Refcounting(modify_rc, cont) => {
+                let cont = self.rewrite(cont);
+                let modify_rc = match modify_rc {
+                    ModifyRc::Dec(symbol) => ModifyRc::DecRef(*symbol),
+                    other => *other,
+                };
+
+                Stmt::Refcounting(modify_rc, self.arena.alloc(cont))
+            }
+            _ => rewrite_children(self, stmt),
+        }
+    }
+}
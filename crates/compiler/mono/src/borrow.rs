@@ -1,4 +1,3 @@
-use std::collections::HashMap;
 use std::hash::Hash;
 
 use crate::ir::{
@@ -7,7 +6,7 @@ use crate::ir::{
 use crate::layout::{InLayout, Layout, LayoutInterner, STLayoutInterner};
 use bumpalo::collections::Vec;
 use bumpalo::Bump;
-use roc_collections::all::{MutMap, MutSet};
+use roc_collections::all::{default_hasher, MutMap, MutSet};
 use roc_collections::ReferenceMatrix;
 use roc_module::low_level::LowLevel;
 use roc_module::symbol::Symbol;
@@ -155,12 +154,13 @@ impl<'a> Hash for Declaration<'a> {
 
 #[derive(Debug)]
 struct DeclarationToIndex<'a> {
-    elements: HashMap<Declaration<'a>, ParamOffset>,
+    elements: MutMap<Declaration<'a>, ParamOffset>,
 }
 
 impl<'a> DeclarationToIndex<'a> {
     fn new(procs: &MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>) -> (Self, usize) {
-        let mut declaration_to_index = HashMap::with_capacity(procs.len());
+        let mut declaration_to_index =
+            MutMap::with_capacity_and_hasher(procs.len(), default_hasher());
 
         let mut i = 0;
         for (symbol, layout) in procs.keys().copied() {
@@ -640,6 +640,10 @@ impl<'a> BorrowInfState<'a> {
                         // always own the input list
                         self.own_var(*xs);
                     }
+                    StrWalkUtf8 { state, .. } => {
+                        // the state is handed to the function, which owns it
+                        self.own_var(*state);
+                    }
                 }
 
                 // own the closure environment if the function needs to own it
@@ -980,6 +984,9 @@ pub fn lowlevel_borrow_signature(arena: &Bump, op: LowLevel) -> &[Ownership] {
         ListSwap => arena.alloc_slice_copy(&[owned, irrelevant, irrelevant]),
         ListReleaseExcessCapacity => arena.alloc_slice_copy(&[owned]),
         StrReleaseExcessCapacity => arena.alloc_slice_copy(&[owned]),
+        StrReplaceEachUnsafe => arena.alloc_slice_copy(&[owned, borrowed, borrowed]),
+        StrSplitOn => arena.alloc_slice_copy(&[owned, borrowed]),
+        StrWalkUtf8 => arena.alloc_slice_copy(&[borrowed, owned, function, closure_data]),
 
         Eq | NotEq => arena.alloc_slice_copy(&[borrowed, borrowed]),
 
@@ -1011,7 +1018,6 @@ pub fn lowlevel_borrow_signature(arena: &Bump, op: LowLevel) -> &[Ownership] {
         | NumIntCast
         | NumToIntChecked
         | NumToFloatCast
-        | NumToFloatChecked
         | NumCountLeadingZeroBits
         | NumCountTrailingZeroBits
         | NumCountOneBits => arena.alloc_slice_copy(&[irrelevant]),
@@ -1025,7 +1031,6 @@ pub fn lowlevel_borrow_signature(arena: &Bump, op: LowLevel) -> &[Ownership] {
         StrToUtf8 => arena.alloc_slice_copy(&[owned]),
         StrRepeat => arena.alloc_slice_copy(&[borrowed, irrelevant]),
         StrFromInt | StrFromFloat => arena.alloc_slice_copy(&[irrelevant]),
-        Hash => arena.alloc_slice_copy(&[borrowed, irrelevant]),
 
         ListIsUnique => arena.alloc_slice_copy(&[borrowed]),
 
@@ -0,0 +1,376 @@
+//! Remove list bounds checks that are known to pass.
+//!
+//! `List.get`, `List.set` and `List.replace` check that the index is below the length of the
+//! list. Inside `if index < List.len list then ...`, which is how loops over a list by index
+//! are usually written, that check always passes, so we call the unchecked lowlevels instead.
+//!
+//! The IR is in SSA form, so what we learn about a symbol holds in every scope nested under its
+//! definition, including the bodies of join points that tail-recursive loops are turned into.
+//! `List.set` doesn't change the length of a list, so a check on its input covers its output too.
+//!
+//! This must run before refcounts are inserted: the lowlevels borrow the list, where the calls
+//! they replace took ownership of it.
+
+use bumpalo::Bump;
+use roc_collections::{MutMap, MutSet};
+use roc_debug_flags::dbg_do;
+#[cfg(debug_assertions)]
+use roc_debug_flags::ROC_PRINT_BOUNDS_CHECKS_ELIMINATED;
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::{IdentIds, ModuleId, Symbol};
+
+use crate::ir::{Call, CallType, Expr, Proc, ProcLayout, Stmt, UpdateModeIds};
+use crate::layout::{InLayout, LayoutInterner, LayoutRepr, TagIdIntType, UnionLayout};
+use crate::rewrite::{rewrite_branches, rewrite_children, Rewrite};
+
+/// The tag id of `Ok` in `Result a [OutOfBounds]`
+const OK_TAG_ID: TagIdIntType = 1;
+
+pub fn eliminate_bounds_checks<'a, I>(
+    arena: &'a Bump,
+    interner: &I,
+    home: ModuleId,
+    ident_ids: &mut IdentIds,
+    update_mode_ids: &mut UpdateModeIds,
+    procedures: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) where
+    I: LayoutInterner<'a>,
+{
+    // `List.set` returns the list out of the `{ list, value }` record `List.replace` returns;
+    // the specialization of `List.replace` that it calls tells us that record's layout.
+    let replace_layouts = procedures
+        .keys()
+        .filter(|(symbol, _)| *symbol == Symbol::LIST_REPLACE)
+        .map(|(_, layout)| (layout.arguments, layout.result))
+        .collect();
+
+    let mut elimination = Elimination {
+        arena,
+        interner,
+        home,
+        ident_ids,
+        update_mode_ids,
+        replace_layouts,
+        lengths: MutMap::default(),
+        same_lists: MutMap::default(),
+        comparisons: MutMap::default(),
+        in_bounds: MutSet::default(),
+        eliminated: 0,
+    };
+
+    for proc in procedures.values_mut() {
+        // Specializations of the same function reuse its symbols, so facts about one proc's
+        // symbols must not leak into the next
+        elimination.lengths.clear();
+        elimination.same_lists.clear();
+        elimination.comparisons.clear();
+
+        proc.body = elimination.rewrite(&proc.body);
+    }
+
+    let _eliminated = elimination.eliminated;
+
+    dbg_do!(ROC_PRINT_BOUNDS_CHECKS_ELIMINATED, {
+        eprintln!("eliminated {} list bounds checks", _eliminated);
+    });
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Len,
+    Lt,
+    Gt,
+    Lte,
+    Gte,
+    Get,
+    Set,
+    Replace,
+}
+
+fn op_of(call_type: &CallType) -> Option<Op> {
+    match call_type {
+        CallType::ByName { name, .. } => match name.name() {
+            Symbol::LIST_LEN => Some(Op::Len),
+            Symbol::NUM_LT => Some(Op::Lt),
+            Symbol::NUM_GT => Some(Op::Gt),
+            Symbol::NUM_LTE => Some(Op::Lte),
+            Symbol::NUM_GTE => Some(Op::Gte),
+            Symbol::LIST_GET => Some(Op::Get),
+            Symbol::LIST_SET => Some(Op::Set),
+            Symbol::LIST_REPLACE => Some(Op::Replace),
+            _ => None,
+        },
+        CallType::LowLevel { op, .. } => match op {
+            LowLevel::ListLen => Some(Op::Len),
+            LowLevel::NumLt => Some(Op::Lt),
+            LowLevel::NumGt => Some(Op::Gt),
+            LowLevel::NumLte => Some(Op::Lte),
+            LowLevel::NumGte => Some(Op::Gte),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// What a boolean says about an index: it is below the length of a list when the boolean
+/// is `when_true`
+#[derive(Debug, Clone, Copy)]
+struct Comparison {
+    index: Symbol,
+    list: Symbol,
+    when_true: bool,
+}
+
+struct Elimination<'a, 'r, I> {
+    arena: &'a Bump,
+    interner: &'r I,
+    home: ModuleId,
+    ident_ids: &'r mut IdentIds,
+    update_mode_ids: &'r mut UpdateModeIds,
+    replace_layouts: MutMap<&'a [InLayout<'a>], InLayout<'a>>,
+    /// Symbols bound to the length of a list
+    lengths: MutMap<Symbol, Symbol>,
+    /// Lists that have the same length as an earlier list
+    same_lists: MutMap<Symbol, Symbol>,
+    comparisons: MutMap<Symbol, Comparison>,
+    /// (index, list) pairs known to be in bounds in the current scope
+    in_bounds: MutSet<(Symbol, Symbol)>,
+    eliminated: usize,
+}
+
+impl<'a, 'r, I> Elimination<'a, 'r, I>
+where
+    I: LayoutInterner<'a>,
+{
+    /// The earliest list known to have the same length as `list`
+    fn original(&self, mut list: Symbol) -> Symbol {
+        while let Some(earlier) = self.same_lists.get(&list) {
+            list = *earlier;
+        }
+
+        list
+    }
+
+    fn is_in_bounds(&self, list: Symbol, index: Symbol) -> bool {
+        self.in_bounds.contains(&(index, self.original(list)))
+    }
+
+    fn unique_symbol(&mut self) -> Symbol {
+        Symbol::new(self.home, self.ident_ids.gen_unique())
+    }
+
+    fn lowlevel(&mut self, op: LowLevel, arguments: &'a [Symbol]) -> Expr<'a> {
+        Expr::Call(Call {
+            call_type: CallType::LowLevel {
+                op,
+                update_mode: self.update_mode_ids.next_id(),
+            },
+            arguments,
+        })
+    }
+
+    /// Learn what binding `symbol` to `op` applied to `arguments` tells us
+    fn record(&mut self, symbol: Symbol, op: Op, arguments: &[Symbol]) {
+        match (op, arguments) {
+            (Op::Len, [list]) => {
+                let list = self.original(*list);
+
+                self.lengths.insert(symbol, list);
+            }
+            (Op::Set, [list, ..]) => {
+                self.same_lists.insert(symbol, *list);
+            }
+            (Op::Lt, [index, length]) | (Op::Gt, [length, index]) => {
+                self.record_comparison(symbol, *index, *length, true);
+            }
+            (Op::Gte, [index, length]) | (Op::Lte, [length, index]) => {
+                self.record_comparison(symbol, *index, *length, false);
+            }
+            _ => {}
+        }
+    }
+
+    fn record_comparison(
+        &mut self,
+        symbol: Symbol,
+        index: Symbol,
+        length: Symbol,
+        when_true: bool,
+    ) {
+        if let Some(list) = self.lengths.get(&length) {
+            let comparison = Comparison {
+                index,
+                list: *list,
+                when_true,
+            };
+
+            self.comparisons.insert(symbol, comparison);
+        }
+    }
+
+    /// Visit `stmt` knowing that the pair is in bounds
+    fn eliminate_with(&mut self, stmt: &Stmt<'a>, pair: Option<(Symbol, Symbol)>) -> Stmt<'a> {
+        // the pair may already be known, from an enclosing `if`
+        let added = pair.filter(|pair| self.in_bounds.insert(*pair));
+
+        let stmt = self.rewrite(stmt);
+
+        if let Some(pair) = added {
+            self.in_bounds.remove(&pair);
+        }
+
+        stmt
+    }
+
+    /// `let symbol = List.get list index` without the check, or None if the layouts are not
+    /// what we expect
+    fn unchecked_get(
+        &mut self,
+        symbol: Symbol,
+        arguments: &'a [Symbol],
+        layout: InLayout<'a>,
+        cont: &'a Stmt<'a>,
+    ) -> Option<Stmt<'a>> {
+        let tag_layout = match self.interner.get(layout).repr {
+            LayoutRepr::Union(tag_layout) => tag_layout,
+            _ => return None,
+        };
+        let element_layout = match tag_layout {
+            UnionLayout::NonRecursive([_, [element_layout]]) => *element_layout,
+            _ => return None,
+        };
+
+        let element = self.unique_symbol();
+        let get = self.lowlevel(LowLevel::ListGetUnsafe, arguments);
+        let ok = Expr::Tag {
+            tag_layout,
+            tag_id: OK_TAG_ID,
+            arguments: self.arena.alloc([element]),
+        };
+
+        Some(Stmt::Let(
+            element,
+            get,
+            element_layout,
+            self.arena.alloc(Stmt::Let(symbol, ok, layout, cont)),
+        ))
+    }
+
+    /// `let symbol = List.set list index value` without the check, or None if the layouts are
+    /// not what we expect
+    fn unchecked_set(
+        &mut self,
+        symbol: Symbol,
+        arguments: &'a [Symbol],
+        arg_layouts: &'a [InLayout<'a>],
+        layout: InLayout<'a>,
+        cont: &'a Stmt<'a>,
+    ) -> Option<Stmt<'a>> {
+        let record_layout = *self.replace_layouts.get(arg_layouts)?;
+        let field_layouts = match self.interner.get(record_layout).repr {
+            LayoutRepr::Struct { field_layouts } => field_layouts,
+            _ => return None,
+        };
+        let index = field_layouts.iter().position(|field| *field == layout)?;
+
+        let record = self.unique_symbol();
+        let replace = self.lowlevel(LowLevel::ListReplaceUnsafe, arguments);
+        let list = Expr::StructAtIndex {
+            index: index as u64,
+            field_layouts,
+            structure: record,
+        };
+
+        Some(Stmt::Let(
+            record,
+            replace,
+            record_layout,
+            self.arena.alloc(Stmt::Let(symbol, list, layout, cont)),
+        ))
+    }
+}
+
+impl<'a, 'r, I> Rewrite<'a> for Elimination<'a, 'r, I>
+where
+    I: LayoutInterner<'a>,
+{
+    fn arena(&self) -> &'a Bump {
+        self.arena
+    }
+
+    fn rewrite(&mut self, stmt: &Stmt<'a>) -> Stmt<'a> {
+        use Stmt::*;
+
+        let arena = self.arena;
+
+        match stmt {
+            Let(symbol, Expr::Call(call), layout, cont) => {
+                let op = op_of(&call.call_type);
+
+                if let Some(op) = op {
+                    self.record(*symbol, op, call.arguments);
+                }
+
+                let unchecked = match (op, call.arguments) {
+                    (Some(Op::Get | Op::Set | Op::Replace), [list, index, ..]) => {
+                        self.is_in_bounds(*list, *index)
+                    }
+                    _ => false,
+                };
+
+                let cont: &'a Stmt<'a> = arena.alloc(self.rewrite(cont));
+
+                let rewritten = match (op, &call.call_type) {
+                    _ if !unchecked => None,
+                    (Some(Op::Get), _) => {
+                        self.unchecked_get(*symbol, call.arguments, *layout, cont)
+                    }
+                    (Some(Op::Set), CallType::ByName { arg_layouts, .. }) => {
+                        self.unchecked_set(*symbol, call.arguments, arg_layouts, *layout, cont)
+                    }
+                    (Some(Op::Replace), _) => {
+                        let replace = self.lowlevel(LowLevel::ListReplaceUnsafe, call.arguments);
+
+                        Some(Let(*symbol, replace, *layout, cont))
+                    }
+                    _ => None,
+                };
+
+                match rewritten {
+                    Some(stmt) => {
+                        self.eliminated += 1;
+
+                        stmt
+                    }
+                    None => Let(*symbol, Expr::Call(call.clone()), *layout, cont),
+                }
+            }
+            Switch {
+                cond_symbol,
+                branches,
+                ..
+            } => {
+                let comparison = self.comparisons.get(cond_symbol).copied();
+                let pair_when = |taken: bool| {
+                    comparison
+                        .filter(|comparison| comparison.when_true == taken)
+                        .map(|comparison| (comparison.index, comparison.list))
+                };
+
+                // Only `if`s are boolean switches with a single branch for `true`
+                let is_if = matches!(branches, [(1, _, _)]);
+
+                rewrite_branches(self, stmt, |elimination, label, branch| {
+                    let pair = if is_if {
+                        pair_when(label.is_some())
+                    } else {
+                        None
+                    };
+
+                    elimination.eliminate_with(branch, pair)
+                })
+            }
+            _ => rewrite_children(self, stmt),
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use bumpalo::collections::vec::Vec;
+use bumpalo::collections::CollectIn;
+use bumpalo::Bump;
+use roc_collections::all::MutMap;
+use roc_module::symbol::{IdentIds, ModuleId, Symbol};
+use roc_target::TargetInfo;
+
+use crate::ir::{Proc, ProcLayout, Stmt};
+use crate::layout::{InLayout, STLayoutInterner};
+
+use super::CodeGenHelp;
+
+/// The result of [expand_refcounting]
+pub struct ExpandedRefcounting<'a> {
+    /// New bodies for the procs, where every `Refcounting` statement calls a helper proc instead
+    pub bodies: MutMap<(Symbol, ProcLayout<'a>), Stmt<'a>>,
+    /// The helper procs, which may also call each other
+    pub helpers: Vec<'a, Proc<'a>>,
+}
+
+/// Generate helper procs for all of the `Refcounting` statements in `procedures`,
+/// and bodies for the procs that call those helpers instead. The helpers are named in the
+/// order they are first needed, so sort `procedures` first to name them the same every time.
+///
+/// The Wasm and dev backends expand refcounting statements one at a time, as they generate code.
+/// The LLVM backend needs everything up front, and it keeps the original procs around, because
+/// alias analysis needs more precise types than the helpers have.
+pub fn expand_refcounting<'a>(
+    arena: &'a Bump,
+    target_info: TargetInfo,
+    home: ModuleId,
+    ident_ids: &mut IdentIds,
+    layout_interner: &mut STLayoutInterner<'a>,
+    procedures: &[((Symbol, ProcLayout<'a>), Proc<'a>)],
+) -> ExpandedRefcounting<'a> {
+    let mut expander = Expander {
+        arena,
+        help: CodeGenHelp::new(arena, target_info, home),
+        symbol_layouts: MutMap::default(),
+    };
+
+    let mut bodies = MutMap::default();
+
+    for (key, proc) in procedures.iter() {
+        expander.symbol_layouts.clear();
+        expander
+            .symbol_layouts
+            .extend(proc.args.iter().map(|(layout, symbol)| (*symbol, *layout)));
+
+        let body = expander.stmt(ident_ids, layout_interner, arena.alloc(proc.body.clone()));
+        bodies.insert(*key, body.clone());
+    }
+
+    ExpandedRefcounting {
+        bodies,
+        helpers: expander.help.take_procs(),
+    }
+}
+
+struct Expander<'a> {
+    arena: &'a Bump,
+    help: CodeGenHelp<'a>,
+    symbol_layouts: MutMap<Symbol, InLayout<'a>>,
+}
+
+impl<'a> Expander<'a> {
+    fn stmt(
+        &mut self,
+        ident_ids: &mut IdentIds,
+        layout_interner: &mut STLayoutInterner<'a>,
+        stmt: &'a Stmt<'a>,
+    ) -> &'a Stmt<'a> {
+        match stmt {
+            Stmt::Let(symbol, expr, layout, following) => {
+                self.symbol_layouts.insert(*symbol, *layout);
+                let following = self.stmt(ident_ids, layout_interner, following);
+
+                self.arena
+                    .alloc(Stmt::Let(*symbol, expr.clone(), *layout, following))
+            }
+            Stmt::Switch {
+                cond_symbol,
+                cond_layout,
+                branches,
+                default_branch,
+                ret_layout,
+            } => {
+                let arena = self.arena;
+                let branches = branches
+                    .iter()
+                    .map(|(id, info, branch)| {
+                        let branch = self.stmt(ident_ids, layout_interner, branch);
+                        (*id, info.clone(), branch.clone())
+                    })
+                    .collect_in::<Vec<_>>(arena)
+                    .into_bump_slice();
+                let default_branch = (
+                    default_branch.0.clone(),
+                    self.stmt(ident_ids, layout_interner, default_branch.1),
+                );
+
+                self.arena.alloc(Stmt::Switch {
+                    cond_symbol: *cond_symbol,
+                    cond_layout: *cond_layout,
+                    branches,
+                    default_branch,
+                    ret_layout: *ret_layout,
+                })
+            }
+            Stmt::Refcounting(modify, following) => {
+                let following = self.stmt(ident_ids, layout_interner, following);
+                let layout = self.symbol_layouts[&modify.get_symbol()];
+
+                // The helpers are all taken at the end, so there's no need to track them here
+                let (rc_stmt, _) = self.help.expand_refcount_stmt(
+                    ident_ids,
+                    layout_interner,
+                    layout,
+                    modify,
+                    following,
+                );
+
+                rc_stmt
+            }
+            Stmt::Expect {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => {
+                let remainder = self.stmt(ident_ids, layout_interner, remainder);
+
+                self.arena.alloc(Stmt::Expect {
+                    condition: *condition,
+                    region: *region,
+                    lookups: *lookups,
+                    variables: *variables,
+                    remainder,
+                })
+            }
+            Stmt::ExpectFx {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => {
+                let remainder = self.stmt(ident_ids, layout_interner, remainder);
+
+                self.arena.alloc(Stmt::ExpectFx {
+                    condition: *condition,
+                    region: *region,
+                    lookups: *lookups,
+                    variables: *variables,
+                    remainder,
+                })
+            }
+            Stmt::Dbg {
+                symbol,
+                variable,
+                remainder,
+            } => {
+                let remainder = self.stmt(ident_ids, layout_interner, remainder);
+
+                self.arena.alloc(Stmt::Dbg {
+                    symbol: *symbol,
+                    variable: *variable,
+                    remainder,
+                })
+            }
+            Stmt::Join {
+                id,
+                parameters,
+                body,
+                remainder,
+            } => {
+                self.symbol_layouts
+                    .extend(parameters.iter().map(|param| (param.symbol, param.layout)));
+
+                let body = self.stmt(ident_ids, layout_interner, body);
+                let remainder = self.stmt(ident_ids, layout_interner, remainder);
+
+                self.arena.alloc(Stmt::Join {
+                    id: *id,
+                    parameters: *parameters,
+                    body,
+                    remainder,
+                })
+            }
+            Stmt::Ret(_) | Stmt::Jump(..) | Stmt::Crash(..) => stmt,
+        }
+    }
+}
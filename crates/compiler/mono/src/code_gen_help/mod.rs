@@ -15,8 +15,11 @@ use crate::layout::{
 };
 
 mod equality;
+mod expand;
 mod refcount;
 
+pub use expand::{expand_refcounting, ExpandedRefcounting};
+
 const LAYOUT_BOOL: InLayout = Layout::BOOL;
 const LAYOUT_UNIT: InLayout = Layout::UNIT;
 
@@ -671,14 +674,42 @@ impl<'a> CallerProc<'a> {
             layout_interner.insert_no_semantic(LayoutRepr::Boxed(Layout::UNIT))
         };
 
-        let box_argument_layout = layout_interner
-            .insert_no_semantic(LayoutRepr::Boxed(passed_function.argument_layouts[0]));
+        // The arguments of the passed function, without its captured environment
+        let argument_layouts = match capture_layout {
+            Some(_) => passed_function.argument_layouts.split_last().unwrap().1,
+            None => passed_function.argument_layouts,
+        };
+
+        let box_argument_layouts = Vec::from_iter_in(
+            argument_layouts
+                .iter()
+                .map(|layout| layout_interner.insert_no_semantic(LayoutRepr::Boxed(*layout))),
+            arena,
+        );
 
         let box_return_layout =
             layout_interner.insert_no_semantic(LayoutRepr::Boxed(passed_function.return_layout));
 
+        // The caller takes the boxed captures, then each boxed argument, then the box to
+        // write the result to
+        let arg_symbols = &[
+            Symbol::ARG_1,
+            Symbol::ARG_2,
+            Symbol::ARG_3,
+            Symbol::ARG_4,
+            Symbol::ARG_5,
+            Symbol::ARG_6,
+        ][..argument_layouts.len() + 2];
+        let return_ptr = arg_symbols[arg_symbols.len() - 1];
+
+        let mut arg_layouts = Vec::with_capacity_in(arg_symbols.len(), arena);
+        arg_layouts.push(box_capture_layout);
+        arg_layouts.extend(box_argument_layouts.iter().copied());
+        arg_layouts.push(box_return_layout);
+        let arg_layouts = arg_layouts.into_bump_slice();
+
         let proc_layout = ProcLayout {
-            arguments: arena.alloc([box_capture_layout, box_argument_layout, box_return_layout]),
+            arguments: arg_layouts,
             result: Layout::UNIT,
             niche: Niche::NONE,
         };
@@ -688,20 +719,22 @@ impl<'a> CallerProc<'a> {
 
         ctx.new_linker_data.push((proc_symbol, proc_layout));
 
-        let unbox_capture = Expr::ExprUnbox {
-            symbol: Symbol::ARG_1,
-        };
-
-        let unbox_argument = Expr::ExprUnbox {
-            symbol: Symbol::ARG_2,
-        };
-
         let unboxed_capture = Self::create_symbol(home, ident_ids, "unboxed_capture");
-        let unboxed_argument = Self::create_symbol(home, ident_ids, "unboxed_argument");
+        let unboxed_arguments = Vec::from_iter_in(
+            argument_layouts
+                .iter()
+                .map(|_| Self::create_symbol(home, ident_ids, "unboxed_argument")),
+            arena,
+        );
         let call_result = Self::create_symbol(home, ident_ids, "call_result");
         let unit_symbol = Self::create_symbol(home, ident_ids, "unit_symbol");
         let ignored = Self::create_symbol(home, ident_ids, "ignored");
 
+        let mut call_arguments = unboxed_arguments.clone();
+        if capture_layout.is_some() {
+            call_arguments.push(unboxed_capture);
+        }
+
         let call = Expr::Call(Call {
             call_type: CallType::ByName {
                 name: passed_function.name,
@@ -709,11 +742,7 @@ impl<'a> CallerProc<'a> {
                 arg_layouts: passed_function.argument_layouts,
                 specialization_id: passed_function.specialization_id,
             },
-            arguments: if capture_layout.is_some() {
-                arena.alloc([unboxed_argument, unboxed_capture])
-            } else {
-                arena.alloc([unboxed_argument])
-            },
+            arguments: call_arguments.into_bump_slice(),
         });
 
         let ptr_write = Expr::Call(Call {
@@ -721,47 +750,56 @@ impl<'a> CallerProc<'a> {
                 op: LowLevel::PtrWrite,
                 update_mode: UpdateModeId::BACKEND_DUMMY,
             },
-            arguments: arena.alloc([Symbol::ARG_3, call_result]),
+            arguments: arena.alloc([return_ptr, call_result]),
         });
 
         let mut body = Stmt::Let(
-            unboxed_argument,
-            unbox_argument,
-            passed_function.argument_layouts[0],
+            call_result,
+            call,
+            passed_function.return_layout,
             arena.alloc(Stmt::Let(
-                call_result,
-                call,
-                passed_function.return_layout,
+                ignored,
+                ptr_write,
+                box_return_layout,
                 arena.alloc(Stmt::Let(
-                    ignored,
-                    ptr_write,
-                    box_return_layout,
-                    arena.alloc(Stmt::Let(
-                        unit_symbol,
-                        Expr::Struct(&[]),
-                        Layout::UNIT,
-                        arena.alloc(Stmt::Ret(unit_symbol)),
-                    )),
+                    unit_symbol,
+                    Expr::Struct(&[]),
+                    Layout::UNIT,
+                    arena.alloc(Stmt::Ret(unit_symbol)),
                 )),
             )),
         );
 
+        for ((unboxed_argument, layout), boxed) in unboxed_arguments
+            .iter()
+            .zip(argument_layouts)
+            .zip(&arg_symbols[1..])
+            .rev()
+        {
+            body = Stmt::Let(
+                *unboxed_argument,
+                Expr::ExprUnbox { symbol: *boxed },
+                *layout,
+                arena.alloc(body),
+            );
+        }
+
         if let Some(capture_layout) = capture_layout {
             body = Stmt::Let(
                 unboxed_capture,
-                unbox_capture,
+                Expr::ExprUnbox {
+                    symbol: Symbol::ARG_1,
+                },
                 capture_layout,
                 arena.alloc(body),
             );
         }
 
-        let args: &'a [(InLayout<'a>, Symbol)] = {
-            arena.alloc([
-                (box_capture_layout, ARG_1),
-                (box_argument_layout, ARG_2),
-                (box_return_layout, ARG_3),
-            ])
-        };
+        let args: &'a [(InLayout<'a>, Symbol)] = Vec::from_iter_in(
+            arg_layouts.iter().copied().zip(arg_symbols.iter().copied()),
+            arena,
+        )
+        .into_bump_slice();
 
         let proc = Proc {
             name: LambdaName::no_niche(proc_symbol),
@@ -795,6 +833,108 @@ impl<'a> CallerProc<'a> {
             proc,
         }
     }
+
+    /// A caller for the comparison function of `List.sortWith`, which Zig calls with pointers
+    /// to the captured environment and the two elements to compare, and which returns the
+    /// ordering by value.
+    pub fn new_compare(
+        arena: &'a Bump,
+        home: ModuleId,
+        ident_ids: &mut IdentIds,
+        layout_interner: &mut STLayoutInterner<'a>,
+        passed_function: &PassedFunction<'a>,
+        capture_layout: Option<InLayout<'a>>,
+    ) -> Self {
+        let box_capture_layout = if let Some(capture_layout) = capture_layout {
+            layout_interner.insert_no_semantic(LayoutRepr::Boxed(capture_layout))
+        } else {
+            layout_interner.insert_no_semantic(LayoutRepr::Boxed(Layout::UNIT))
+        };
+
+        let element_layout = passed_function.argument_layouts[0];
+        let box_element_layout =
+            layout_interner.insert_no_semantic(LayoutRepr::Boxed(element_layout));
+
+        let proc_layout = ProcLayout {
+            arguments: arena.alloc([box_capture_layout, box_element_layout, box_element_layout]),
+            result: passed_function.return_layout,
+            niche: Niche::NONE,
+        };
+
+        let proc_symbol = Self::create_caller_proc_symbol(
+            home,
+            ident_ids,
+            "compare",
+            passed_function.name.name(),
+        );
+
+        let unboxed_capture = Self::create_symbol(home, ident_ids, "unboxed_capture");
+        let unboxed_left = Self::create_symbol(home, ident_ids, "unboxed_left");
+        let unboxed_right = Self::create_symbol(home, ident_ids, "unboxed_right");
+        let call_result = Self::create_symbol(home, ident_ids, "call_result");
+
+        let call = Expr::Call(Call {
+            call_type: CallType::ByName {
+                name: passed_function.name,
+                ret_layout: passed_function.return_layout,
+                arg_layouts: passed_function.argument_layouts,
+                specialization_id: passed_function.specialization_id,
+            },
+            arguments: if capture_layout.is_some() {
+                arena.alloc([unboxed_left, unboxed_right, unboxed_capture])
+            } else {
+                arena.alloc([unboxed_left, unboxed_right])
+            },
+        });
+
+        let mut body = Stmt::Let(
+            unboxed_left,
+            Expr::ExprUnbox { symbol: ARG_2 },
+            element_layout,
+            arena.alloc(Stmt::Let(
+                unboxed_right,
+                Expr::ExprUnbox { symbol: ARG_3 },
+                element_layout,
+                arena.alloc(Stmt::Let(
+                    call_result,
+                    call,
+                    passed_function.return_layout,
+                    arena.alloc(Stmt::Ret(call_result)),
+                )),
+            )),
+        );
+
+        if let Some(capture_layout) = capture_layout {
+            body = Stmt::Let(
+                unboxed_capture,
+                Expr::ExprUnbox { symbol: ARG_1 },
+                capture_layout,
+                arena.alloc(body),
+            );
+        }
+
+        let args: &'a [(InLayout<'a>, Symbol)] = arena.alloc([
+            (box_capture_layout, ARG_1),
+            (box_element_layout, ARG_2),
+            (box_element_layout, ARG_3),
+        ]);
+
+        let proc = Proc {
+            name: LambdaName::no_niche(proc_symbol),
+            args,
+            body,
+            closure_data_layout: None,
+            ret_layout: passed_function.return_layout,
+            is_self_recursive: SelfRecursive::NotSelfRecursive,
+            host_exposed_layouts: HostExposedLayouts::NotHostExposed,
+        };
+
+        Self {
+            proc_symbol,
+            proc_layout,
+            proc,
+        }
+    }
 }
 
 fn let_lowlevel<'a>(
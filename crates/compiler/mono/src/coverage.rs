@@ -0,0 +1,177 @@
+//! Count how often each function body and branch runs, for `roc test --coverage`.
+//!
+//! When [Procs::coverage] is set, specialization puts a call to `roc_coverage_hit` at the start
+//! of every function body, and of every `if` and `when` branch. Its argument is a `U64` literal
+//! holding the offset of the region being counted, bound to a symbol of the module the region
+//! is in. Once everything is specialized, [number_coverage_points] gives each distinct region a
+//! counter index, and replaces the literals with those indices, so the counters can be stored in
+//! an array. The function has the C signature `void roc_coverage_hit(uint64_t counter)`.
+
+use bumpalo::Bump;
+use roc_collections::MutMap;
+use roc_module::ident::ForeignSymbol;
+use roc_module::symbol::{ModuleId, Symbol};
+use roc_region::all::Region;
+
+use crate::ir::{Call, CallType, Env, Expr, Literal, Proc, ProcLayout, Procs, Stmt};
+use crate::layout::Layout;
+use crate::rewrite::{rewrite_children, Rewrite};
+
+pub const COVERAGE_HIT: &str = "roc_coverage_hit";
+
+/// The array of counters the code generator defines, with one `u64` per [CoveragePoint]
+pub const COVERAGE_COUNTS: &str = "roc_coverage_counts";
+
+/// Set in the literal for branches, so a branch is never confused with a function body
+/// that starts at the same offset
+const BRANCH_BIT: u64 = 1 << 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CoverageKind {
+    Function,
+    Branch,
+}
+
+/// A region that has a counter, in the order of the counter array
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CoveragePoint {
+    pub module_id: ModuleId,
+    /// The offset in the module's source where the counted region starts
+    pub offset: u32,
+    pub kind: CoverageKind,
+}
+
+/// Count how often `stmt` runs, if coverage is enabled. Regions the user didn't write,
+/// like those of derived implementations, have no position, and aren't counted.
+pub(crate) fn count<'a>(
+    env: &mut Env<'a, '_>,
+    procs: &Procs<'a>,
+    kind: CoverageKind,
+    region: Region,
+    stmt: Stmt<'a>,
+) -> Stmt<'a> {
+    if !procs.coverage || region.is_empty() {
+        return stmt;
+    }
+
+    let key = match kind {
+        CoverageKind::Function => region.start().offset as u64,
+        CoverageKind::Branch => region.start().offset as u64 | BRANCH_BIT,
+    };
+
+    let key_symbol = env.unique_symbol();
+    let call = Expr::Call(Call {
+        call_type: CallType::Foreign {
+            foreign_symbol: ForeignSymbol::from(COVERAGE_HIT),
+            ret_layout: Layout::UNIT,
+        },
+        arguments: env.arena.alloc([key_symbol]),
+    });
+
+    let hit = Stmt::Let(
+        env.unique_symbol(),
+        call,
+        Layout::UNIT,
+        env.arena.alloc(stmt),
+    );
+
+    Stmt::Let(
+        key_symbol,
+        Expr::Literal(Literal::Int((key as i128).to_ne_bytes())),
+        Layout::U64,
+        env.arena.alloc(hit),
+    )
+}
+
+/// Give every counted region a counter, and make each `roc_coverage_hit` call pass its
+/// counter's index. The returned points are in index order.
+pub fn number_coverage_points<'a>(
+    arena: &'a Bump,
+    procedures: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) -> std::vec::Vec<CoveragePoint> {
+    let mut numbering = Numbering {
+        arena,
+        indices: MutMap::default(),
+        points: std::vec::Vec::new(),
+    };
+
+    // visit procs in a fixed order, so counters are numbered the same way every time
+    let mut keys: std::vec::Vec<_> = procedures.keys().copied().collect();
+    keys.sort_by_key(|(symbol, _)| *symbol);
+
+    for key in keys {
+        let proc = procedures.get_mut(&key).unwrap();
+
+        proc.body = numbering.rewrite(&proc.body);
+    }
+
+    numbering.points
+}
+
+struct Numbering<'a> {
+    arena: &'a Bump,
+    indices: MutMap<CoveragePoint, u64>,
+    points: std::vec::Vec<CoveragePoint>,
+}
+
+impl<'a> Numbering<'a> {
+    fn index_of(&mut self, point: CoveragePoint) -> u64 {
+        let points = &mut self.points;
+
+        *self.indices.entry(point).or_insert_with(|| {
+            points.push(point);
+
+            points.len() as u64 - 1
+        })
+    }
+}
+
+impl<'a> Rewrite<'a> for Numbering<'a> {
+    fn arena(&self) -> &'a Bump {
+        self.arena
+    }
+
+    fn rewrite(&mut self, stmt: &Stmt<'a>) -> Stmt<'a> {
+        match stmt {
+            Stmt::Let(symbol, Expr::Literal(Literal::Int(bytes)), layout, cont)
+                if is_coverage_hit(*symbol, cont) =>
+            {
+                let key = i128::from_ne_bytes(*bytes) as u64;
+                let kind = if key & BRANCH_BIT == 0 {
+                    CoverageKind::Function
+                } else {
+                    CoverageKind::Branch
+                };
+                let index = self.index_of(CoveragePoint {
+                    module_id: symbol.module_id(),
+                    offset: key as u32,
+                    kind,
+                });
+                let cont = self.rewrite(cont);
+
+                Stmt::Let(
+                    *symbol,
+                    Expr::Literal(Literal::Int((index as i128).to_ne_bytes())),
+                    *layout,
+                    self.arena.alloc(cont),
+                )
+            }
+            _ => rewrite_children(self, stmt),
+        }
+    }
+}
+
+pub(crate) fn is_coverage_hit(key_symbol: Symbol, cont: &Stmt) -> bool {
+    match cont {
+        Stmt::Let(
+            _,
+            Expr::Call(Call {
+                call_type: CallType::Foreign { foreign_symbol, .. },
+                arguments: [argument],
+            }),
+            _,
+            _,
+        ) => *argument == key_symbol && foreign_symbol.as_str() == COVERAGE_HIT,
+        _ => false,
+    }
+}
@@ -0,0 +1,247 @@
+//! Evaluate top-level constants at compile time.
+//!
+//! A top-level def like `table = List.map (List.range { start: At 0, end: Before 256 }) crc`
+//! becomes a thunk: a proc without arguments that recomputes the value on every use. When
+//! [the interpreter][crate::interp] can evaluate such a thunk (it is pure, terminates within
+//! [FUEL] statements, and only uses what the interpreter supports), we replace its body with
+//! the resulting value, built from literals. Backends emit lists of literals as static data,
+//! so large lookup tables are no longer rebuilt at runtime.
+//!
+//! This must run before refcounts are inserted, so that the new bodies get them too.
+
+use bumpalo::collections::Vec;
+use bumpalo::Bump;
+use roc_collections::{MutMap, MutSet};
+use roc_module::symbol::{IdentIds, ModuleId, Symbol};
+
+use crate::interp::{Interpreter, Value};
+use crate::ir::{Expr, ListLiteralElement, Literal, Proc, ProcLayout, Stmt};
+use crate::layout::{Builtin, InLayout, LayoutInterner, LayoutRepr, TagIdIntType, UnionLayout};
+use roc_builtins::bitcode::IntWidth;
+
+/// How many statements evaluating one constant may run before we leave it to runtime
+const FUEL: usize = 1_000_000;
+
+/// Constants with more values than this are left to runtime, so the binary doesn't balloon
+const MAX_VALUES: usize = 65_536;
+
+/// Only the thunks of `top_level_values` are folded. Entry points and expects are thunks too,
+/// but they are there to be run, and folding them would move their crashes to compile time.
+pub fn fold_constants<'a, I>(
+    arena: &'a Bump,
+    interner: &I,
+    home: ModuleId,
+    ident_ids: &mut IdentIds,
+    procedures: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+    top_level_values: &MutSet<Symbol>,
+) where
+    I: LayoutInterner<'a>,
+{
+    // Evaluate everything against the original procs first; a constant may use other constants.
+    let mut folded = std::vec::Vec::new();
+
+    for (key, proc) in procedures.iter() {
+        if !proc.args.is_empty() || !top_level_values.contains(&key.0) {
+            continue;
+        }
+
+        let mut interpreter = Interpreter::for_constants(procedures, interner, FUEL);
+
+        if let Ok(value) = interpreter.call_proc(key.0, key.1, std::vec::Vec::new()) {
+            if count_values(&value) <= MAX_VALUES {
+                folded.push((*key, value));
+            }
+        }
+    }
+
+    for (key, value) in folded {
+        let proc = procedures.get_mut(&key).unwrap();
+
+        let mut builder = Builder {
+            arena,
+            interner,
+            home,
+            ident_ids,
+            lets: std::vec::Vec::new(),
+        };
+
+        if let Some(body) = builder.build_body(&value, proc.ret_layout) {
+            proc.body = body;
+        }
+    }
+}
+
+fn count_values(value: &Value) -> usize {
+    match value {
+        Value::List(values)
+        | Value::Struct(values)
+        | Value::Tag {
+            arguments: values, ..
+        } => 1 + values.iter().map(count_values).sum::<usize>(),
+        Value::Boxed(value) => 1 + count_values(value),
+        Value::Bool(_)
+        | Value::Int(_)
+        | Value::Float(_)
+        | Value::Dec(_)
+        | Value::Str(_)
+        | Value::Null => 1,
+    }
+}
+
+struct Builder<'a, 'r, I> {
+    arena: &'a Bump,
+    interner: &'r I,
+    home: ModuleId,
+    ident_ids: &'r mut IdentIds,
+    /// The bindings that make up the new body, in order
+    lets: std::vec::Vec<(Symbol, Expr<'a>, InLayout<'a>)>,
+}
+
+impl<'a, 'r, I> Builder<'a, 'r, I>
+where
+    I: LayoutInterner<'a>,
+{
+    /// A body that builds `value` and returns it, or None if the value doesn't fit the layout
+    fn build_body(&mut self, value: &Value, ret_layout: InLayout<'a>) -> Option<Stmt<'a>> {
+        let result = self.build(value, ret_layout)?;
+
+        let mut body = Stmt::Ret(result);
+
+        for (symbol, expr, layout) in self.lets.drain(..).rev() {
+            body = Stmt::Let(symbol, expr, layout, self.arena.alloc(body));
+        }
+
+        Some(body)
+    }
+
+    fn bind(&mut self, expr: Expr<'a>, layout: InLayout<'a>) -> Symbol {
+        let symbol = Symbol::new(self.home, self.ident_ids.gen_unique());
+
+        self.lets.push((symbol, expr, layout));
+
+        symbol
+    }
+
+    fn build(&mut self, value: &Value, layout: InLayout<'a>) -> Option<Symbol> {
+        let repr = self.interner.get(layout).repr;
+
+        let expr = match (value, repr) {
+            (_, LayoutRepr::LambdaSet(lambda_set)) => {
+                return self.build(value, lambda_set.runtime_representation());
+            }
+            (_, LayoutRepr::RecursivePointer(union_layout)) => {
+                return self.build(value, union_layout);
+            }
+            (Value::List(elements), LayoutRepr::Builtin(Builtin::List(elem_layout))) => {
+                if elements.is_empty() {
+                    Expr::EmptyArray
+                } else {
+                    let mut elems = Vec::with_capacity_in(elements.len(), self.arena);
+
+                    for element in elements {
+                        let elem = match self.literal(element, elem_layout) {
+                            Some(literal) => ListLiteralElement::Literal(literal),
+                            None => ListLiteralElement::Symbol(self.build(element, elem_layout)?),
+                        };
+
+                        elems.push(elem);
+                    }
+
+                    Expr::Array {
+                        elem_layout,
+                        elems: elems.into_bump_slice(),
+                    }
+                }
+            }
+            (Value::Struct(fields), LayoutRepr::Struct { field_layouts })
+                if fields.len() == field_layouts.len() =>
+            {
+                Expr::Struct(self.build_all(fields, field_layouts)?)
+            }
+            (Value::Tag { tag_id, arguments }, LayoutRepr::Union(union_layout)) => {
+                let field_layouts = tag_field_layouts(&union_layout, *tag_id)?;
+
+                if arguments.len() != field_layouts.len() {
+                    return None;
+                }
+
+                Expr::Tag {
+                    tag_layout: union_layout,
+                    tag_id: *tag_id,
+                    arguments: self.build_all(arguments, field_layouts)?,
+                }
+            }
+            (Value::Null, LayoutRepr::Union(union_layout)) if union_layout.is_nullable() => {
+                Expr::NullPointer
+            }
+            (Value::Boxed(inner), LayoutRepr::Boxed(inner_layout)) => Expr::ExprBox {
+                symbol: self.build(inner, inner_layout)?,
+            },
+            _ => Expr::Literal(self.literal(value, layout)?),
+        };
+
+        Some(self.bind(expr, layout))
+    }
+
+    fn build_all(&mut self, values: &[Value], layouts: &[InLayout<'a>]) -> Option<&'a [Symbol]> {
+        let mut symbols = Vec::with_capacity_in(values.len(), self.arena);
+
+        for (value, layout) in values.iter().zip(layouts) {
+            symbols.push(self.build(value, *layout)?);
+        }
+
+        Some(symbols.into_bump_slice())
+    }
+
+    /// The value as a literal, if it is a number, Bool, or Str
+    fn literal(&self, value: &Value, layout: InLayout<'a>) -> Option<Literal<'a>> {
+        let builtin = match self.interner.get(layout).repr {
+            LayoutRepr::Builtin(builtin) => builtin,
+            _ => return None,
+        };
+
+        let literal = match (value, builtin) {
+            (Value::Int(int), Builtin::Int(IntWidth::U128)) => {
+                Literal::U128((*int as u128).to_ne_bytes())
+            }
+            (Value::Int(int), Builtin::Int(_)) => Literal::Int(int.to_ne_bytes()),
+            (Value::Float(float), Builtin::Float(_)) => Literal::Float(*float),
+            (Value::Dec(dec), Builtin::Decimal) => Literal::Decimal(dec.to_ne_bytes()),
+            (Value::Bool(bool), Builtin::Bool) => Literal::Bool(*bool),
+            (Value::Str(string), Builtin::Str) => Literal::Str(self.arena.alloc_str(string)),
+            _ => return None,
+        };
+
+        Some(literal)
+    }
+}
+
+fn tag_field_layouts<'a>(
+    union_layout: &UnionLayout<'a>,
+    tag_id: TagIdIntType,
+) -> Option<&'a [InLayout<'a>]> {
+    let tag_id = tag_id as usize;
+
+    match union_layout {
+        UnionLayout::NonRecursive(tags) | UnionLayout::Recursive(tags) => tags.get(tag_id).copied(),
+        UnionLayout::NonNullableUnwrapped(fields) => (tag_id == 0).then_some(*fields),
+        UnionLayout::NullableWrapped {
+            nullable_id,
+            other_tags,
+        } => {
+            let nullable_id = *nullable_id as usize;
+
+            if tag_id < nullable_id {
+                other_tags.get(tag_id).copied()
+            } else if tag_id > nullable_id {
+                other_tags.get(tag_id - 1).copied()
+            } else {
+                None
+            }
+        }
+        UnionLayout::NullableUnwrapped {
+            nullable_id,
+            other_fields,
+        } => (tag_id != *nullable_id as usize).then_some(*other_fields),
+    }
+}
@@ -0,0 +1,236 @@
+//! The functions a platform host calls to run the closures a Roc program gives it.
+//!
+//! The type of a host-exposed value like `mainForHost : Effect {}` can contain functions, so the
+//! host gets closures that it has to call back. For each such function type, specialization
+//! generates a proc that calls whichever function the closure data picks, and records it in the
+//! [HostExposedLayouts] of the exposed proc. Around that proc, a backend exports
+//!
+//! * `roc__{name}_caller(arg_1: *const A1, ..., closure: *const C, output: *mut R)`, which
+//!   calls the closure with the arguments and writes its result to `output`
+//! * `roc__{name}_size() -> i64`, the size of the closure data `C`
+//! * `roc__{name}_result_size() -> i64`, the size of the result `R`
+//!
+//! where `name` is the exposed value and the id of the function type, like `mainForHost_1`.
+//! For a function type without arguments only `result_size` is exported.
+//!
+//! [host_exposed_procs] builds these as ordinary procs, so that a backend only has to give them
+//! their names. The LLVM backend builds its own callers, which also catch crashes in tests, but
+//! names them the same way.
+
+use bumpalo::collections::Vec;
+use bumpalo::Bump;
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::{IdentIds, Interns, ModuleId, Symbol};
+
+use crate::ir::{
+    Call, CallSpecId, CallType, Expr, HostExposedLambdaSet, HostExposedLayouts, Literal, Proc,
+    ProcLayout, SelfRecursive, Stmt, UpdateModeId,
+};
+use crate::layout::{
+    InLayout, LambdaName, Layout, LayoutInterner, LayoutRepr, Niche, RawFunctionLayout,
+};
+
+/// The `name` in the exports for the function type `hels` in the type of `exposed`
+pub fn host_exposed_name(
+    interns: &Interns,
+    exposed: Symbol,
+    hels: &HostExposedLambdaSet,
+) -> String {
+    format!("{}_{}", exposed.as_str(interns), hels.id.0)
+}
+
+pub fn caller_name(name: &str) -> String {
+    format!("roc__{}_caller", name)
+}
+
+pub fn size_name(name: &str) -> String {
+    format!("roc__{}_size", name)
+}
+
+pub fn result_size_name(name: &str) -> String {
+    format!("roc__{}_result_size", name)
+}
+
+/// A proc to export to the host under `name`
+pub struct HostExposedProc<'a> {
+    pub name: String,
+    pub proc_layout: ProcLayout<'a>,
+    pub proc: Proc<'a>,
+}
+
+/// The procs to export for the function types in the type of the host-exposed `proc`
+pub fn host_exposed_procs<'a, I>(
+    arena: &'a Bump,
+    interner: &mut I,
+    interns: &mut Interns,
+    home: ModuleId,
+    proc: &Proc<'a>,
+) -> std::vec::Vec<HostExposedProc<'a>>
+where
+    I: LayoutInterner<'a>,
+{
+    let aliases = match &proc.host_exposed_layouts {
+        HostExposedLayouts::NotHostExposed => return std::vec::Vec::new(),
+        HostExposedLayouts::HostExposed { aliases, .. } => aliases,
+    };
+
+    let mut procs = std::vec::Vec::with_capacity(aliases.len() * 3);
+
+    for hels in aliases.values() {
+        let name = host_exposed_name(interns, proc.name.name(), hels);
+
+        let mut builder = Builder {
+            arena,
+            interner: &mut *interner,
+            ident_ids: interns.all_ident_ids.get_mut(&home).unwrap(),
+            home,
+        };
+
+        match hels.raw_function_layout {
+            RawFunctionLayout::Function(_, lambda_set, result) => {
+                procs.push(builder.caller(caller_name(&name), hels));
+                procs.push(builder.size(size_name(&name), lambda_set.runtime_representation()));
+                procs.push(builder.size(result_size_name(&name), result));
+            }
+            RawFunctionLayout::ZeroArgumentThunk(result) => {
+                procs.push(builder.size(result_size_name(&name), result));
+            }
+        }
+    }
+
+    procs
+}
+
+struct Builder<'a, 'r, I> {
+    arena: &'a Bump,
+    interner: &'r mut I,
+    ident_ids: &'r mut IdentIds,
+    home: ModuleId,
+}
+
+impl<'a, 'r, I> Builder<'a, 'r, I>
+where
+    I: LayoutInterner<'a>,
+{
+    fn unique_symbol(&mut self) -> Symbol {
+        Symbol::new(self.home, self.ident_ids.gen_unique())
+    }
+
+    fn boxed(&mut self, layout: InLayout<'a>) -> InLayout<'a> {
+        self.interner.insert_no_semantic(LayoutRepr::Boxed(layout))
+    }
+
+    /// Unbox the arguments and the closure data, call the proc for the function type with them,
+    /// and write its result to the output pointer
+    fn caller(&mut self, name: String, hels: &HostExposedLambdaSet<'a>) -> HostExposedProc<'a> {
+        let arena = self.arena;
+        let ProcLayout {
+            arguments, result, ..
+        } = hels.proc_layout;
+
+        let mut args = Vec::with_capacity_in(arguments.len() + 1, arena);
+        let mut unboxes = std::vec::Vec::with_capacity(arguments.len());
+
+        // the closure data is the last argument of the proc, so it comes right after the others
+        for layout in arguments {
+            let pointer = self.unique_symbol();
+            let value = self.unique_symbol();
+
+            args.push((self.boxed(*layout), pointer));
+            unboxes.push((value, pointer, *layout));
+        }
+
+        let output = self.unique_symbol();
+        let output_layout = self.boxed(result);
+
+        args.push((output_layout, output));
+
+        let value = self.unique_symbol();
+        let written = self.unique_symbol();
+        let unit = self.unique_symbol();
+
+        let call = Expr::Call(Call {
+            call_type: CallType::ByName {
+                name: LambdaName::no_niche(hels.symbol),
+                ret_layout: result,
+                arg_layouts: arguments,
+                specialization_id: CallSpecId::BACKEND_DUMMY,
+            },
+            arguments: Vec::from_iter_in(unboxes.iter().map(|(value, _, _)| *value), arena)
+                .into_bump_slice(),
+        });
+
+        let write = Expr::Call(Call {
+            call_type: CallType::LowLevel {
+                op: LowLevel::PtrWrite,
+                update_mode: UpdateModeId::BACKEND_DUMMY,
+            },
+            arguments: arena.alloc([output, value]),
+        });
+
+        let mut body = Stmt::Let(
+            unit,
+            Expr::Struct(&[]),
+            Layout::UNIT,
+            arena.alloc(Stmt::Ret(unit)),
+        );
+        body = Stmt::Let(written, write, output_layout, arena.alloc(body));
+        body = Stmt::Let(value, call, result, arena.alloc(body));
+
+        for (value, pointer, layout) in unboxes.into_iter().rev() {
+            let unbox = Expr::ExprUnbox { symbol: pointer };
+
+            body = Stmt::Let(value, unbox, layout, arena.alloc(body));
+        }
+
+        self.proc(name, args.into_bump_slice(), body, Layout::UNIT)
+    }
+
+    /// Return the size of `layout`
+    fn size(&mut self, name: String, layout: InLayout<'a>) -> HostExposedProc<'a> {
+        let size = self.interner.stack_size(layout) as i128;
+        let value = self.unique_symbol();
+
+        let body = Stmt::Let(
+            value,
+            Expr::Literal(Literal::Int(size.to_ne_bytes())),
+            Layout::I64,
+            self.arena.alloc(Stmt::Ret(value)),
+        );
+
+        self.proc(name, &[], body, Layout::I64)
+    }
+
+    fn proc(
+        &mut self,
+        name: String,
+        args: &'a [(InLayout<'a>, Symbol)],
+        body: Stmt<'a>,
+        ret_layout: InLayout<'a>,
+    ) -> HostExposedProc<'a> {
+        let symbol = self.unique_symbol();
+
+        let proc_layout = ProcLayout {
+            arguments: Vec::from_iter_in(args.iter().map(|(layout, _)| *layout), self.arena)
+                .into_bump_slice(),
+            result: ret_layout,
+            niche: Niche::NONE,
+        };
+
+        let proc = Proc {
+            name: LambdaName::no_niche(symbol),
+            args,
+            body,
+            closure_data_layout: None,
+            ret_layout,
+            is_self_recursive: SelfRecursive::NotSelfRecursive,
+            host_exposed_layouts: HostExposedLayouts::NotHostExposed,
+        };
+
+        HostExposedProc {
+            name,
+            proc_layout,
+            proc,
+        }
+    }
+}
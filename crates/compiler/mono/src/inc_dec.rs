@@ -5,19 +5,17 @@
 // Implementation based of Perceus: Garbage Free Reference Counting with Reuse
 // https://www.microsoft.com/en-us/research/uploads/prod/2021/06/perceus-pldi21.pdf
 
-use std::{collections::HashMap, hash::BuildHasherDefault};
-
 use bumpalo::collections::{CollectIn, Vec};
 use bumpalo::Bump;
-use roc_collections::{all::WyHash, MutMap, MutSet};
+use roc_collections::{MutMap, MutSet};
 use roc_module::low_level::LowLevel;
 use roc_module::{low_level::LowLevelWrapperType, symbol::Symbol};
 
 use crate::{
-    borrow::{lowlevel_borrow_signature, Ownership},
+    borrow::{infer_borrow, lowlevel_borrow_signature, Ownership},
     ir::{
-        BranchInfo, Call, CallType, Expr, HigherOrderLowLevel, JoinPointId, ListLiteralElement,
-        ModifyRc, Param, Proc, ProcLayout, Stmt,
+        BranchInfo, Call, CallType, Expr, HigherOrderLowLevel, HostExposedLayouts, JoinPointId,
+        ListLiteralElement, ModifyRc, Param, Proc, ProcLayout, Stmt,
     },
     layout::{InLayout, LayoutInterner, STLayoutInterner},
     low_level::HigherOrder,
@@ -29,7 +27,7 @@ Insert the reference count operations for procedures.
 pub fn insert_inc_dec_operations<'a>(
     arena: &'a Bump,
     layout_interner: &STLayoutInterner<'a>,
-    procedures: &mut HashMap<(Symbol, ProcLayout), Proc<'a>, BuildHasherDefault<WyHash>>,
+    procedures: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
 ) {
     // Create a SymbolRcTypesEnv for the procedures as they get referenced but should be marked as non reference counted.
     let mut symbol_rc_types_env = SymbolRcTypesEnv::from_layout_interner(layout_interner);
@@ -37,20 +35,124 @@ pub fn insert_inc_dec_operations<'a>(
         symbol_rc_types_env.insert_proc_symbol(proc_symbol);
     }
 
+    // Procedures borrow the arguments they only read, so neither they nor their callers
+    // have to change the reference counts of those.
+    let borrow_signatures = infer_borrow_signatures(arena, layout_interner, procedures);
+
     // All calls to lowlevels are wrapped in another function to help with type inference and return/parameter layouts.
     // But this lowlevel might get inlined into the caller of the wrapper and thus removing any reference counting operations.
     // Thus, these rc operations are performed on the caller of the wrapper instead, and we skip rc on the lowlevel.
     // It might be possible to inline the lowlevels at this point already,
     // but previous attempt conflicted as the parameters layouts and return layout do not match.
-    for ((symbol, _layout), proc) in procedures.iter_mut() {
+    for (key, proc) in procedures.iter_mut() {
         if matches!(
-            LowLevelWrapperType::from_symbol(*symbol),
+            LowLevelWrapperType::from_symbol(key.0),
             LowLevelWrapperType::NotALowLevelWrapper
         ) {
             // Clone the symbol_rc_types_env and insert the symbols in the current procedure.
             // As the symbols should be limited in scope for the current proc.
             let symbol_rc_types_env = symbol_rc_types_env.clone();
-            insert_inc_dec_operations_proc(arena, symbol_rc_types_env, proc);
+            insert_inc_dec_operations_proc(
+                arena,
+                symbol_rc_types_env,
+                &borrow_signatures,
+                borrow_signatures.get(key).copied(),
+                proc,
+            );
+        }
+    }
+}
+
+/**
+The ownership of the parameters of each procedure.
+*/
+type BorrowSignatures<'a> = MutMap<(Symbol, ProcLayout<'a>), &'a [Ownership]>;
+
+/**
+Infer which parameters each procedure can borrow, see [infer_borrow].
+Procedures called by the host, directly, through the caller of a host exposed closure or as a glue getter, or by a higher order lowlevel, are given their arguments as owned, so they must own all their parameters.
+*/
+fn infer_borrow_signatures<'a>(
+    arena: &'a Bump,
+    layout_interner: &STLayoutInterner<'a>,
+    procedures: &MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) -> BorrowSignatures<'a> {
+    let mut must_own = std::vec::Vec::new();
+
+    for ((symbol, _layout), proc) in procedures.iter() {
+        if let HostExposedLayouts::HostExposed {
+            aliases, getters, ..
+        } = &proc.host_exposed_layouts
+        {
+            must_own.push(*symbol);
+
+            // The host calls these closures through the callers generated for them.
+            must_own.extend(aliases.values().map(|lambda_set| lambda_set.symbol));
+
+            // And it hands the glue getters the boxed values they read a field of.
+            must_own.extend(getters.iter().copied());
+        }
+
+        passed_functions(&proc.body, &mut must_own);
+    }
+
+    let param_map = infer_borrow(arena, layout_interner, procedures, &must_own);
+
+    procedures
+        .keys()
+        .filter_map(|&(symbol, layout)| {
+            let params = param_map.get_symbol(layout_interner, symbol, layout)?;
+            let signature = params
+                .iter()
+                .map(|param| param.ownership)
+                .collect_in::<Vec<_>>(arena);
+
+            Some(((symbol, layout), signature.into_bump_slice()))
+        })
+        .collect()
+}
+
+/**
+Collect the functions passed to higher order lowlevels.
+*/
+fn passed_functions(stmt: &Stmt, functions: &mut std::vec::Vec<Symbol>) {
+    let mut stack = vec![stmt];
+
+    while let Some(stmt) = stack.pop() {
+        match stmt {
+            Stmt::Let(_, expr, _, cont) => {
+                if let Expr::Call(Call {
+                    call_type:
+                        CallType::HigherOrder(HigherOrderLowLevel {
+                            passed_function, ..
+                        }),
+                    ..
+                }) = expr
+                {
+                    functions.push(passed_function.name.name());
+                }
+
+                stack.push(cont);
+            }
+            Stmt::Switch {
+                branches,
+                default_branch,
+                ..
+            } => {
+                stack.extend(branches.iter().map(|(_, _, branch)| branch));
+                stack.push(default_branch.1);
+            }
+            Stmt::Join {
+                body, remainder, ..
+            } => {
+                stack.push(body);
+                stack.push(remainder);
+            }
+            Stmt::Refcounting(_, cont) => stack.push(cont),
+            Stmt::Expect { remainder, .. }
+            | Stmt::ExpectFx { remainder, .. }
+            | Stmt::Dbg { remainder, .. } => stack.push(remainder),
+            Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => {}
         }
     }
 }
@@ -250,15 +352,19 @@ The environment for the reference counting pass.
 Contains the symbols rc types and the ownership.
 */
 #[derive(Clone)]
-struct RefcountEnvironment<'v> {
+struct RefcountEnvironment<'v, 'a> {
     // Keep track which symbols are reference counted and which are not.
     symbols_rc_types: &'v SymbolRcTypes,
+    // Which arguments each procedure borrows.
+    borrow_signatures: &'v BorrowSignatures<'a>,
+    // The parameters of the current procedure that it borrows.
+    borrowed_parameters: &'v MutSet<Symbol>,
     // The Koka implementation assumes everything that is not owned to be borrowed.
     symbols_ownership: SymbolsOwnership,
     jointpoint_closures: MutMap<JoinPointId, JoinPointConsumption>,
 }
 
-impl<'v> RefcountEnvironment<'v> {
+impl<'v, 'a> RefcountEnvironment<'v, 'a> {
     /**
     Retrieve the rc type of a symbol.
     */
@@ -293,6 +399,13 @@ impl<'v> RefcountEnvironment<'v> {
             .expect("Expected symbol to be in environment")
     }
 
+    /**
+    Whether the symbol is a parameter that the current procedure borrows.
+    */
+    fn is_borrowed_parameter(&self, symbol: &Symbol) -> bool {
+        self.borrowed_parameters.contains(symbol)
+    }
+
     /**
        Retrieve the ownership of a symbol.
        If the symbol is not reference counted, it will None.
@@ -305,9 +418,17 @@ impl<'v> RefcountEnvironment<'v> {
     Add a symbol to the environment if it is reference counted.
     */
     fn add_symbol(&mut self, symbol: Symbol) {
+        self.add_symbol_with_ownership(symbol, Ownership::Owned)
+    }
+
+    /**
+    Add a symbol to the environment with the given ownership, if it is reference counted.
+    Used for the parameters of a procedure, which may be borrowed.
+    */
+    fn add_symbol_with_ownership(&mut self, symbol: Symbol, ownership: Ownership) {
         match self.get_symbol_rc_type(&symbol) {
             VarRcType::ReferenceCounted => {
-                self.symbols_ownership.insert(symbol, Ownership::Owned);
+                self.symbols_ownership.insert(symbol, ownership);
             }
             VarRcType::NotReferenceCounted => {
                 // If this symbol is not reference counted, we don't need to do anything.
@@ -403,22 +524,39 @@ impl<'v> RefcountEnvironment<'v> {
 fn insert_inc_dec_operations_proc<'a>(
     arena: &'a Bump,
     mut symbol_rc_types_env: SymbolRcTypesEnv<'a, '_>,
+    borrow_signatures: &BorrowSignatures<'a>,
+    borrow_signature: Option<&'a [Ownership]>,
     proc: &mut Proc<'a>,
 ) {
     // Clone the symbol_rc_types_env and insert the symbols in the current procedure.
     // As the symbols should be limited in scope for the current proc.
     symbol_rc_types_env.insert_symbols_rc_type_proc(proc);
 
+    let proc_symbols = proc.args.iter().map(|(_layout, symbol)| symbol);
+    let ownerships = proc_symbols.clone().enumerate().map(|(index, symbol)| {
+        let ownership = borrow_signature
+            .and_then(|signature| signature.get(index).copied())
+            .unwrap_or(Ownership::Owned);
+
+        (*symbol, ownership)
+    });
+
+    let borrowed_parameters = ownerships
+        .clone()
+        .filter_map(|(symbol, ownership)| ownership.is_borrowed().then_some(symbol))
+        .collect::<MutSet<_>>();
+
     let mut environment = RefcountEnvironment {
         symbols_rc_types: &symbol_rc_types_env.symbols_rc_type,
+        borrow_signatures,
+        borrowed_parameters: &borrowed_parameters,
         symbols_ownership: MutMap::default(),
         jointpoint_closures: MutMap::default(),
     };
 
-    // Add all arguments to the environment (if they are reference counted)
-    let proc_symbols = proc.args.iter().map(|(_layout, symbol)| symbol);
-    for symbol in proc_symbols.clone() {
-        environment.add_symbol(*symbol);
+    // Add all arguments to the environment (if they are reference counted), borrowed if the procedure only reads them.
+    for (symbol, ownership) in ownerships {
+        environment.add_symbol_with_ownership(symbol, ownership);
     }
 
     // Update the body with reference count statements.
@@ -450,7 +588,7 @@ Assuming that a symbol can only be defined once (no binding to the same symbol m
 */
 fn insert_refcount_operations_stmt<'v, 'a>(
     arena: &'a Bump,
-    environment: &mut RefcountEnvironment<'v>,
+    environment: &mut RefcountEnvironment<'v, 'a>,
     stmt: &Stmt<'a>,
 ) -> &'a Stmt<'a> {
     match &stmt {
@@ -564,7 +702,7 @@ fn insert_refcount_operations_stmt<'v, 'a>(
                         let consumed =
                             branch_envs
                                 .iter()
-                                .any(|branch_env: &&RefcountEnvironment<'v>| {
+                                .any(|branch_env: &&RefcountEnvironment<'v, 'a>| {
                                     matches!(
                                         branch_env.get_symbol_ownership(symbol).expect(error),
                                         Ownership::Borrowed
@@ -634,9 +772,15 @@ fn insert_refcount_operations_stmt<'v, 'a>(
             })
         }
         Stmt::Ret(s) => {
-            let ownership = environment.consume_symbol(s);
-            debug_assert!(matches!(ownership, None | Some(Ownership::Owned))); // the return value should be owned or not reference counted at the return.
-            return arena.alloc(Stmt::Ret(*s));
+            // The return value should be owned, so a borrowed parameter that is returned has to be incremented.
+            let new_ret = arena.alloc(Stmt::Ret(*s));
+
+            consume_and_insert_inc_stmts(
+                arena,
+                environment,
+                environment.owned_usages([*s]),
+                new_ret,
+            )
         }
         Stmt::Refcounting(_, _) => unreachable!("refcounting should not be in the AST yet"),
         Stmt::Expect {
@@ -708,10 +852,12 @@ fn insert_refcount_operations_stmt<'v, 'a>(
         } => {
             // Assuming that the values in the closure of the body of this jointpoint are already bound.
             // Assuming that all symbols are still owned. (So that we can determine what symbols got consumed in the join point.)
+            // Borrowed parameters of the procedure are the exception; they count as consumed, which only means they are never decremented.
             debug_assert!(environment
                 .symbols_ownership
                 .iter()
-                .all(|(_, ownership)| ownership.is_owned()));
+                .all(|(symbol, ownership)| ownership.is_owned()
+                    || environment.is_borrowed_parameter(symbol)));
 
             let mut body_env = environment.clone();
 
@@ -843,7 +989,7 @@ fn insert_refcount_operations_stmt<'v, 'a>(
 
 fn insert_refcount_operations_binding<'a>(
     arena: &'a Bump,
-    environment: &mut RefcountEnvironment,
+    environment: &mut RefcountEnvironment<'_, 'a>,
     binding: &Symbol,
     expr: &Expr<'a>,
     layout: &InLayout<'a>,
@@ -947,11 +1093,38 @@ fn insert_refcount_operations_binding<'a>(
         }) => {
             match call_type.clone().replace_lowlevel_wrapper() {
                 // A by name call refers to a normal function call.
-                // Normal functions take all their parameters as owned, so we can mark them all as such.
-                CallType::ByName { .. } => {
-                    let new_let = new_let!(stmt);
+                // Normal functions take their parameters as their borrow signature says, or else as owned.
+                CallType::ByName {
+                    name,
+                    ret_layout,
+                    arg_layouts,
+                    ..
+                } => {
+                    let layout = ProcLayout::new(arena, arg_layouts, name.niche(), ret_layout);
+
+                    match environment.borrow_signatures.get(&(name.name(), layout)) {
+                        Some(borrow_signature) => {
+                            let arguments_with_borrow_signature = arguments
+                                .iter()
+                                .copied()
+                                .zip(borrow_signature.iter().copied());
+                            let owned_arguments =
+                                arguments_with_borrow_signature.clone().filter_map(
+                                    |(symbol, ownership)| ownership.is_owned().then_some(symbol),
+                                );
+                            let borrowed_arguments = arguments_with_borrow_signature.filter_map(
+                                |(symbol, ownership)| ownership.is_borrowed().then_some(symbol),
+                            );
+                            let new_stmt = dec_borrowed!(borrowed_arguments, stmt);
+                            let new_let = new_let!(new_stmt);
+                            inc_owned!(owned_arguments, new_let)
+                        }
+                        None => {
+                            let new_let = new_let!(stmt);
 
-                    inc_owned!(arguments.iter().copied(), new_let)
+                            inc_owned!(arguments.iter().copied(), new_let)
+                        }
+                    }
                 }
                 CallType::Foreign { .. } => {
                     // Foreign functions should be responsible for their own memory management.
@@ -1095,6 +1268,20 @@ fn insert_refcount_operations_binding<'a>(
                                 panic!("ListSortWith should have 3 arguments");
                             }
                         }
+                        HigherOrder::StrWalkUtf8 { string, state } => {
+                            // The state is passed to the function, and the bytes of the string
+                            // are only read.
+                            if let [_string_symbol, _state_symbol, _function_symbol, closure_symbol] =
+                                &arguments
+                            {
+                                let new_stmt = dec_borrowed!([*string, *closure_symbol], stmt);
+                                let new_let = new_let!(new_stmt);
+
+                                inc_owned!([*state].into_iter(), new_let)
+                            } else {
+                                panic!("StrWalkUtf8 should have 4 arguments");
+                            }
+                        }
                     }
                 }
             }
@@ -0,0 +1,1197 @@
+//! A small interpreter for the [mono IR][crate::ir::Proc].
+//!
+//! It covers literals, structs, tags, lists, strings, integer and float arithmetic, calls
+//! between procs, and the `List.map` family and `List.sortWith`. That is enough to evaluate
+//! constants at compile time (see [crate::fold_constants]), and to run simple programs on
+//! targets where we can't JIT. Anything it does not cover is reported as
+//! [InterpError::Unsupported] rather than guessed at.
+//!
+//! Refcounting statements are skipped, since values here are plain Rust values.
+
+use roc_builtins::bitcode::{FloatWidth, IntWidth};
+use roc_collections::MutMap;
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::Symbol;
+use roc_region::all::Region;
+use std::cmp::Ordering;
+
+use crate::ir::{
+    Call, CallType, Expr, HigherOrderLowLevel, JoinPointId, ListLiteralElement, Literal, Param,
+    Proc, ProcLayout, Stmt,
+};
+use crate::layout::{Builtin, InLayout, LayoutInterner, LayoutRepr, TagIdIntType, UnionLayout};
+use crate::low_level::HigherOrder;
+
+/// How deep calls may nest before we give up. Tail calls are already loops (join points)
+/// in the IR, so only genuinely deep recursion gets here.
+const MAX_CALL_DEPTH: usize = 4096;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    /// Every integer width is stored as an i128; the layout says which width it really is.
+    /// U128 values above i128::MAX are not supported.
+    Int(i128),
+    Float(f64),
+    /// The raw representation of a RocDec, i.e. the value times 10^18
+    Dec(i128),
+    Str(String),
+    List(Vec<Value>),
+    Struct(Vec<Value>),
+    Tag {
+        tag_id: TagIdIntType,
+        arguments: Vec<Value>,
+    },
+    /// The tag of a nullable union that is represented by the null pointer
+    Null,
+    Boxed(Box<Value>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InterpError {
+    /// The program crashed, e.g. with `crash "message"` or an integer overflow
+    Crash(String),
+    /// An `expect` failed
+    ExpectFailed(Region),
+    /// The program uses something this interpreter can't evaluate
+    Unsupported(String),
+    /// A call to a proc that isn't in the given procs
+    MissingProc(Symbol),
+    /// Calls nested more than MAX_CALL_DEPTH deep
+    StackOverflow,
+    /// More statements ran than the interpreter's fuel allowed
+    OutOfFuel,
+}
+
+type InterpResult<T> = Result<T, InterpError>;
+
+pub struct Interpreter<'a, 'r, I>
+where
+    I: LayoutInterner<'a>,
+{
+    procs: &'r MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+    interner: &'r I,
+    depth: usize,
+    /// How many more statements may run, if that is limited
+    fuel: Option<usize>,
+    /// Whether `dbg` and `expect` are errors rather than being skipped. Evaluating a constant
+    /// at compile time must not drop their output.
+    reject_effects: bool,
+}
+
+/// The variables and join points in scope in one proc call
+struct Frame<'a, 'r> {
+    values: MutMap<Symbol, Value>,
+    join_points: MutMap<JoinPointId, (&'r [Param<'a>], &'r Stmt<'a>)>,
+}
+
+impl<'a, 'r> Frame<'a, 'r> {
+    fn lookup(&self, symbol: Symbol) -> InterpResult<Value> {
+        match self.values.get(&symbol) {
+            Some(value) => Ok(value.clone()),
+            None => Err(InterpError::Unsupported(format!(
+                "{:?} is not defined in this proc",
+                symbol
+            ))),
+        }
+    }
+
+    fn lookup_all(&self, symbols: &[Symbol]) -> InterpResult<Vec<Value>> {
+        symbols.iter().map(|symbol| self.lookup(*symbol)).collect()
+    }
+}
+
+impl<'a, 'r, I> Interpreter<'a, 'r, I>
+where
+    I: LayoutInterner<'a>,
+{
+    pub fn new(procs: &'r MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>, interner: &'r I) -> Self {
+        Self {
+            procs,
+            interner,
+            depth: 0,
+            fuel: None,
+            reject_effects: false,
+        }
+    }
+
+    /// An interpreter for evaluating constants at compile time: it gives up after running
+    /// `fuel` statements, and on any `dbg` or `expect`.
+    pub fn for_constants(
+        procs: &'r MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+        interner: &'r I,
+        fuel: usize,
+    ) -> Self {
+        Self {
+            fuel: Some(fuel),
+            reject_effects: true,
+            ..Self::new(procs, interner)
+        }
+    }
+
+    /// Call the proc with the given name and layout, passing it `arguments`.
+    pub fn call_proc(
+        &mut self,
+        name: Symbol,
+        proc_layout: ProcLayout<'a>,
+        arguments: Vec<Value>,
+    ) -> InterpResult<Value> {
+        let proc = match self.procs.get(&(name, proc_layout)) {
+            Some(proc) => proc,
+            None => return Err(InterpError::MissingProc(name)),
+        };
+
+        if proc.args.len() != arguments.len() {
+            return Err(InterpError::Unsupported(format!(
+                "{:?} takes {} arguments, but was given {}",
+                name,
+                proc.args.len(),
+                arguments.len()
+            )));
+        }
+
+        if self.depth >= MAX_CALL_DEPTH {
+            return Err(InterpError::StackOverflow);
+        }
+
+        let mut frame = Frame {
+            values: proc
+                .args
+                .iter()
+                .map(|(_, symbol)| *symbol)
+                .zip(arguments)
+                .collect(),
+            join_points: MutMap::default(),
+        };
+
+        self.depth += 1;
+        let result = self.eval_stmt(&mut frame, &proc.body);
+        self.depth -= 1;
+
+        result
+    }
+
+    fn eval_stmt(&mut self, frame: &mut Frame<'a, 'r>, stmt: &'r Stmt<'a>) -> InterpResult<Value> {
+        let mut stmt = stmt;
+
+        loop {
+            if let Some(fuel) = self.fuel.as_mut() {
+                match fuel.checked_sub(1) {
+                    Some(remaining) => *fuel = remaining,
+                    None => return Err(InterpError::OutOfFuel),
+                }
+            }
+
+            match stmt {
+                Stmt::Let(symbol, expr, layout, cont) => {
+                    let value = self.eval_expr(frame, expr, *layout)?;
+                    frame.values.insert(*symbol, value);
+                    stmt = *cont;
+                }
+                Stmt::Switch {
+                    cond_symbol,
+                    branches,
+                    default_branch,
+                    ..
+                } => {
+                    let cond = switch_key(&frame.lookup(*cond_symbol)?)?;
+
+                    stmt = match branches.iter().find(|(value, _, _)| *value == cond) {
+                        Some((_, _, branch)) => branch,
+                        None => default_branch.1,
+                    };
+                }
+                Stmt::Ret(symbol) => return frame.lookup(*symbol),
+                Stmt::Refcounting(_, cont) => stmt = *cont,
+                Stmt::Expect {
+                    condition,
+                    region,
+                    remainder,
+                    ..
+                }
+                | Stmt::ExpectFx {
+                    condition,
+                    region,
+                    remainder,
+                    ..
+                } => {
+                    if self.reject_effects {
+                        return Err(InterpError::Unsupported("expect".to_string()));
+                    }
+
+                    if frame.lookup(*condition)? == Value::Bool(false) {
+                        return Err(InterpError::ExpectFailed(*region));
+                    }
+
+                    stmt = *remainder;
+                }
+                Stmt::Dbg { .. } if self.reject_effects => {
+                    return Err(InterpError::Unsupported("dbg".to_string()));
+                }
+                Stmt::Dbg { remainder, .. } => stmt = *remainder,
+                Stmt::Join {
+                    id,
+                    parameters,
+                    body,
+                    remainder,
+                } => {
+                    frame.join_points.insert(*id, (*parameters, *body));
+                    stmt = *remainder;
+                }
+                Stmt::Jump(id, arguments) => {
+                    let (parameters, body) = match frame.join_points.get(id) {
+                        Some(join_point) => *join_point,
+                        None => {
+                            return Err(InterpError::Unsupported(format!(
+                                "jump to undefined join point {:?}",
+                                id
+                            )))
+                        }
+                    };
+
+                    let values = frame.lookup_all(arguments)?;
+
+                    for (param, value) in parameters.iter().zip(values) {
+                        frame.values.insert(param.symbol, value);
+                    }
+
+                    stmt = body;
+                }
+                Stmt::Crash(symbol, _) => {
+                    return match frame.lookup(*symbol)? {
+                        Value::Str(message) => Err(InterpError::Crash(message)),
+                        other => Err(InterpError::Crash(format!("{:?}", other))),
+                    };
+                }
+            }
+        }
+    }
+
+    fn eval_expr(
+        &mut self,
+        frame: &Frame<'a, 'r>,
+        expr: &'r Expr<'a>,
+        layout: InLayout<'a>,
+    ) -> InterpResult<Value> {
+        match expr {
+            Expr::Literal(Literal::Float(float)) => Ok(Value::Float(round_float(
+                *float,
+                self.interner.get(layout).repr,
+            ))),
+            Expr::Literal(literal) => eval_literal(literal),
+            Expr::Call(call) => self.eval_call(frame, call, layout),
+            Expr::Tag {
+                tag_id, arguments, ..
+            }
+            | Expr::Reuse {
+                tag_id, arguments, ..
+            } => Ok(Value::Tag {
+                tag_id: *tag_id,
+                arguments: frame.lookup_all(arguments)?,
+            }),
+            Expr::Struct(fields) => Ok(Value::Struct(frame.lookup_all(fields)?)),
+            Expr::NullPointer => Ok(Value::Null),
+            Expr::StructAtIndex {
+                index, structure, ..
+            } => match frame.lookup(*structure)? {
+                Value::Struct(mut fields) if (*index as usize) < fields.len() => {
+                    Ok(fields.swap_remove(*index as usize))
+                }
+                other => Err(InterpError::Unsupported(format!(
+                    "field {} of non-struct value {:?}",
+                    index, other
+                ))),
+            },
+            Expr::GetTagId {
+                structure,
+                union_layout,
+            } => match frame.lookup(*structure)? {
+                Value::Tag { tag_id, .. } => Ok(Value::Int(tag_id as i128)),
+                Value::Null => Ok(Value::Int(nullable_id(union_layout)? as i128)),
+                other => Err(InterpError::Unsupported(format!(
+                    "tag id of non-tag value {:?}",
+                    other
+                ))),
+            },
+            Expr::UnionAtIndex {
+                structure, index, ..
+            } => match frame.lookup(*structure)? {
+                Value::Tag { mut arguments, .. } if (*index as usize) < arguments.len() => {
+                    Ok(arguments.swap_remove(*index as usize))
+                }
+                other => Err(InterpError::Unsupported(format!(
+                    "payload {} of non-tag value {:?}",
+                    index, other
+                ))),
+            },
+            Expr::Array { elems, .. } => elems
+                .iter()
+                .map(|elem| match elem {
+                    ListLiteralElement::Literal(literal) => eval_literal(literal),
+                    ListLiteralElement::Symbol(symbol) => frame.lookup(*symbol),
+                })
+                .collect::<InterpResult<_>>()
+                .map(Value::List),
+            Expr::EmptyArray => Ok(Value::List(Vec::new())),
+            Expr::ExprBox { symbol } => Ok(Value::Boxed(Box::new(frame.lookup(*symbol)?))),
+            Expr::ExprUnbox { symbol } => match frame.lookup(*symbol)? {
+                Value::Boxed(inner) => Ok(*inner),
+                other => Err(InterpError::Unsupported(format!(
+                    "unbox of non-box value {:?}",
+                    other
+                ))),
+            },
+            // Nothing is ever reused here; the later Reuse just builds a fresh tag.
+            Expr::Reset { .. } | Expr::ResetRef { .. } => Ok(Value::Null),
+            Expr::RuntimeErrorFunction(message) => Err(InterpError::Crash(message.to_string())),
+        }
+    }
+
+    fn eval_call(
+        &mut self,
+        frame: &Frame<'a, 'r>,
+        call: &'r Call<'a>,
+        layout: InLayout<'a>,
+    ) -> InterpResult<Value> {
+        // The arguments of a higher order lowlevel include the name of the passed function,
+        // which is not a value; it looks up what it needs itself.
+        if let CallType::HigherOrder(higher_order) = &call.call_type {
+            return self.eval_higher_order(frame, higher_order);
+        }
+
+        let arguments = frame.lookup_all(call.arguments)?;
+
+        match call.call_type.clone().replace_lowlevel_wrapper() {
+            CallType::ByName {
+                name,
+                ret_layout,
+                arg_layouts,
+                ..
+            } => {
+                let proc_layout = ProcLayout {
+                    arguments: arg_layouts,
+                    result: ret_layout,
+                    niche: name.niche(),
+                };
+
+                self.call_proc(name.name(), proc_layout, arguments)
+            }
+            CallType::LowLevel { op, .. } => {
+                eval_lowlevel(op, &arguments, self.interner.get(layout).repr)
+            }
+            CallType::Foreign { foreign_symbol, .. } => Err(InterpError::Unsupported(format!(
+                "call to foreign function {:?}",
+                foreign_symbol
+            ))),
+            CallType::HigherOrder(_) => unreachable!("handled above"),
+        }
+    }
+
+    fn eval_higher_order(
+        &mut self,
+        frame: &Frame<'a, 'r>,
+        higher_order: &HigherOrderLowLevel<'a>,
+    ) -> InterpResult<Value> {
+        let passed_function = &higher_order.passed_function;
+        let proc_layout = ProcLayout {
+            arguments: passed_function.argument_layouts,
+            result: passed_function.return_layout,
+            niche: passed_function.name.niche(),
+        };
+
+        // The passed function takes its captured environment as its last argument, if it has one
+        let captured = match higher_order.closure_env_layout {
+            Some(_) => Some(frame.lookup(passed_function.captured_environment)?),
+            None => None,
+        };
+
+        let call = |interpreter: &mut Self, mut arguments: Vec<Value>| {
+            arguments.extend(captured.iter().cloned());
+            interpreter.call_proc(passed_function.name.name(), proc_layout, arguments)
+        };
+
+        let lists = |symbols: &[Symbol]| -> InterpResult<Vec<Vec<Value>>> {
+            symbols
+                .iter()
+                .map(|symbol| match frame.lookup(*symbol)? {
+                    Value::List(list) => Ok(list),
+                    other => Err(InterpError::Unsupported(format!(
+                        "{:?} on non-list value {:?}",
+                        higher_order.op, other
+                    ))),
+                })
+                .collect()
+        };
+
+        match higher_order.op {
+            HigherOrder::ListMap { xs } => map_lists(lists(&[xs])?, |args| call(self, args)),
+            HigherOrder::ListMap2 { xs, ys } => {
+                map_lists(lists(&[xs, ys])?, |args| call(self, args))
+            }
+            HigherOrder::ListMap3 { xs, ys, zs } => {
+                map_lists(lists(&[xs, ys, zs])?, |args| call(self, args))
+            }
+            HigherOrder::ListMap4 { xs, ys, zs, ws } => {
+                map_lists(lists(&[xs, ys, zs, ws])?, |args| call(self, args))
+            }
+            HigherOrder::ListSortWith { xs } => {
+                let mut list = lists(&[xs])?.remove(0);
+                let mut error = None;
+
+                // sort_by is stable, like the zig sort the backends use
+                list.sort_by(|a, b| {
+                    if error.is_some() {
+                        return Ordering::Equal;
+                    }
+
+                    // [EQ, GT, LT] are tag ids 0, 1 and 2
+                    match call(self, vec![a.clone(), b.clone()]).and_then(|v| switch_key(&v)) {
+                        Ok(0) => Ordering::Equal,
+                        Ok(1) => Ordering::Greater,
+                        Ok(2) => Ordering::Less,
+                        Ok(other) => {
+                            error = Some(InterpError::Unsupported(format!(
+                                "comparator returned tag id {}",
+                                other
+                            )));
+                            Ordering::Equal
+                        }
+                        Err(e) => {
+                            error = Some(e);
+                            Ordering::Equal
+                        }
+                    }
+                });
+
+                match error {
+                    Some(e) => Err(e),
+                    None => Ok(Value::List(list)),
+                }
+            }
+            HigherOrder::StrWalkUtf8 { string, state } => match frame.lookup(string)? {
+                Value::Str(string) => string
+                    .bytes()
+                    .try_fold(frame.lookup(state)?, |state, byte| {
+                        call(self, vec![state, Value::Int(byte as i128)])
+                    }),
+                other => Err(InterpError::Unsupported(format!(
+                    "{:?} on non-string value {:?}",
+                    higher_order.op, other
+                ))),
+            },
+        }
+    }
+}
+
+/// Calls `f` on the elements at each index of `lists`, up to the length of the shortest one.
+fn map_lists(
+    lists: Vec<Vec<Value>>,
+    mut f: impl FnMut(Vec<Value>) -> InterpResult<Value>,
+) -> InterpResult<Value> {
+    let len = lists.iter().map(Vec::len).min().unwrap_or(0);
+
+    (0..len)
+        .map(|i| f(lists.iter().map(|list| list[i].clone()).collect()))
+        .collect::<InterpResult<_>>()
+        .map(Value::List)
+}
+
+/// Floats are computed as f64s. Rounding every F32 result back to F32 precision gives exactly
+/// what F32 arithmetic would, since an f64 has more than twice the bits of an f32.
+fn round_float(float: f64, repr: LayoutRepr) -> f64 {
+    match repr {
+        LayoutRepr::Builtin(Builtin::Float(FloatWidth::F32)) => float as f32 as f64,
+        _ => float,
+    }
+}
+
+fn eval_literal(literal: &Literal) -> InterpResult<Value> {
+    match literal {
+        Literal::Int(bytes) => Ok(Value::Int(i128::from_ne_bytes(*bytes))),
+        Literal::U128(bytes) => match i128::try_from(u128::from_ne_bytes(*bytes)) {
+            Ok(int) => Ok(Value::Int(int)),
+            Err(_) => Err(InterpError::Unsupported(
+                "U128 literals above the maximum I128".to_string(),
+            )),
+        },
+        Literal::Float(float) => Ok(Value::Float(*float)),
+        Literal::Decimal(bytes) => Ok(Value::Dec(i128::from_ne_bytes(*bytes))),
+        Literal::Str(string) => Ok(Value::Str(string.to_string())),
+        Literal::Bool(bool) => Ok(Value::Bool(*bool)),
+        Literal::Byte(byte) => Ok(Value::Int(*byte as i128)),
+    }
+}
+
+fn switch_key(value: &Value) -> InterpResult<u64> {
+    match value {
+        Value::Bool(bool) => Ok(*bool as u64),
+        Value::Int(int) => Ok(*int as u64),
+        Value::Tag { tag_id, .. } => Ok(*tag_id as u64),
+        other => Err(InterpError::Unsupported(format!(
+            "switch on non-integer value {:?}",
+            other
+        ))),
+    }
+}
+
+fn nullable_id(union_layout: &UnionLayout) -> InterpResult<TagIdIntType> {
+    match union_layout {
+        UnionLayout::NullableWrapped { nullable_id, .. } => Ok(*nullable_id),
+        UnionLayout::NullableUnwrapped { nullable_id, .. } => Ok(*nullable_id as TagIdIntType),
+        other => Err(InterpError::Unsupported(format!(
+            "null pointer in non-nullable union {:?}",
+            other
+        ))),
+    }
+}
+
+enum OnOverflow {
+    Crash(&'static str),
+    Wrap,
+    Saturate,
+}
+
+fn int_width(repr: LayoutRepr) -> InterpResult<IntWidth> {
+    match repr {
+        LayoutRepr::Builtin(Builtin::Int(width)) => Ok(width),
+        other => Err(InterpError::Unsupported(format!(
+            "integer result with layout {:?}",
+            other
+        ))),
+    }
+}
+
+/// The values of `width` that we can represent. For U128 that is only up to i128::MAX.
+fn int_range(width: IntWidth) -> (i128, i128) {
+    use IntWidth::*;
+
+    match width {
+        U8 => (0, u8::MAX as i128),
+        U16 => (0, u16::MAX as i128),
+        U32 => (0, u32::MAX as i128),
+        U64 => (0, u64::MAX as i128),
+        U128 => (0, i128::MAX),
+        I8 => (i8::MIN as i128, i8::MAX as i128),
+        I16 => (i16::MIN as i128, i16::MAX as i128),
+        I32 => (i32::MIN as i128, i32::MAX as i128),
+        I64 => (i64::MIN as i128, i64::MAX as i128),
+        I128 => (i128::MIN, i128::MAX),
+    }
+}
+
+fn wrap_int(int: i128, width: IntWidth) -> i128 {
+    use IntWidth::*;
+
+    match width {
+        U8 => int as u8 as i128,
+        U16 => int as u16 as i128,
+        U32 => int as u32 as i128,
+        U64 => int as u64 as i128,
+        I8 => int as i8 as i128,
+        I16 => int as i16 as i128,
+        I32 => int as i32 as i128,
+        I64 => int as i64 as i128,
+        U128 | I128 => int,
+    }
+}
+
+fn int_result(
+    result: Option<i128>,
+    wrapped: i128,
+    width: IntWidth,
+    on_overflow: OnOverflow,
+) -> InterpResult<Value> {
+    let (min, max) = int_range(width);
+
+    match result {
+        Some(int) if min <= int && int <= max => Ok(Value::Int(int)),
+        // the real result may be a U128 above i128::MAX rather than an overflow
+        _ if width == IntWidth::U128 => Err(InterpError::Unsupported(
+            "U128 values above the maximum I128".to_string(),
+        )),
+        _ => match on_overflow {
+            OnOverflow::Crash(message) => Err(InterpError::Crash(message.to_string())),
+            OnOverflow::Wrap => Ok(Value::Int(wrap_int(wrapped, width))),
+            OnOverflow::Saturate => match result {
+                Some(int) => Ok(Value::Int(int.clamp(min, max))),
+                None => Err(InterpError::Unsupported(
+                    "saturating 128-bit arithmetic".to_string(),
+                )),
+            },
+        },
+    }
+}
+
+fn arithmetic(
+    args: &[Value],
+    ret: LayoutRepr,
+    checked: fn(i128, i128) -> Option<i128>,
+    wrapping: fn(i128, i128) -> i128,
+    float: fn(f64, f64) -> f64,
+    on_overflow: OnOverflow,
+) -> InterpResult<Value> {
+    match args {
+        [Value::Int(a), Value::Int(b)] => int_result(
+            checked(*a, *b),
+            wrapping(*a, *b),
+            int_width(ret)?,
+            on_overflow,
+        ),
+        [Value::Float(a), Value::Float(b)] => Ok(Value::Float(round_float(float(*a, *b), ret))),
+        _ => Err(InterpError::Unsupported(format!(
+            "arithmetic on {:?}",
+            args
+        ))),
+    }
+}
+
+fn compare(args: &[Value]) -> InterpResult<Ordering> {
+    let ordering = match args {
+        [Value::Int(a), Value::Int(b)] | [Value::Dec(a), Value::Dec(b)] => Some(a.cmp(b)),
+        [Value::Float(a), Value::Float(b)] => a.partial_cmp(b),
+        _ => None,
+    };
+
+    ordering.ok_or_else(|| InterpError::Unsupported(format!("comparison of {:?}", args)))
+}
+
+fn eval_lowlevel(op: LowLevel, args: &[Value], ret: LayoutRepr) -> InterpResult<Value> {
+    use LowLevel::*;
+
+    let unsupported = || Err(InterpError::Unsupported(format!("{:?} on {:?}", op, args)));
+
+    match op {
+        NumAdd => match args {
+            [Value::Dec(a), Value::Dec(b)] => match a.checked_add(*b) {
+                Some(dec) => Ok(Value::Dec(dec)),
+                None => Err(InterpError::Crash(
+                    "Decimal addition overflowed!".to_string(),
+                )),
+            },
+            _ => arithmetic(
+                args,
+                ret,
+                i128::checked_add,
+                i128::wrapping_add,
+                |a, b| a + b,
+                OnOverflow::Crash("Integer addition overflowed!"),
+            ),
+        },
+        NumSub => match args {
+            [Value::Dec(a), Value::Dec(b)] => match a.checked_sub(*b) {
+                Some(dec) => Ok(Value::Dec(dec)),
+                None => Err(InterpError::Crash(
+                    "Decimal subtraction overflowed!".to_string(),
+                )),
+            },
+            _ => arithmetic(
+                args,
+                ret,
+                i128::checked_sub,
+                i128::wrapping_sub,
+                |a, b| a - b,
+                OnOverflow::Crash("Integer subtraction overflowed!"),
+            ),
+        },
+        NumMul => arithmetic(
+            args,
+            ret,
+            i128::checked_mul,
+            i128::wrapping_mul,
+            |a, b| a * b,
+            OnOverflow::Crash("Integer multiplication overflowed!"),
+        ),
+        NumAddWrap => arithmetic(
+            args,
+            ret,
+            i128::checked_add,
+            i128::wrapping_add,
+            |a, b| a + b,
+            OnOverflow::Wrap,
+        ),
+        NumSubWrap => arithmetic(
+            args,
+            ret,
+            i128::checked_sub,
+            i128::wrapping_sub,
+            |a, b| a - b,
+            OnOverflow::Wrap,
+        ),
+        NumMulWrap => arithmetic(
+            args,
+            ret,
+            i128::checked_mul,
+            i128::wrapping_mul,
+            |a, b| a * b,
+            OnOverflow::Wrap,
+        ),
+        NumAddSaturated => arithmetic(
+            args,
+            ret,
+            i128::checked_add,
+            i128::wrapping_add,
+            |a, b| a + b,
+            OnOverflow::Saturate,
+        ),
+        NumSubSaturated => arithmetic(
+            args,
+            ret,
+            i128::checked_sub,
+            i128::wrapping_sub,
+            |a, b| a - b,
+            OnOverflow::Saturate,
+        ),
+        NumMulSaturated => arithmetic(
+            args,
+            ret,
+            i128::checked_mul,
+            i128::wrapping_mul,
+            |a, b| a * b,
+            OnOverflow::Saturate,
+        ),
+        NumDivFrac => match args {
+            [Value::Float(a), Value::Float(b)] => Ok(Value::Float(round_float(a / b, ret))),
+            _ => unsupported(),
+        },
+        NumDivTruncUnchecked | NumDivCeilUnchecked | NumRemUnchecked => match args {
+            [Value::Int(_), Value::Int(0)] => {
+                Err(InterpError::Crash("Integer division by 0!".to_string()))
+            }
+            [Value::Int(a), Value::Int(b)] => {
+                let result = match op {
+                    NumDivTruncUnchecked => a.checked_div(*b),
+                    NumRemUnchecked => a.checked_rem(*b),
+                    _ => a.checked_div(*b).map(|quotient| {
+                        let rounds_up = a % b != 0 && ((*a < 0) == (*b < 0));
+                        quotient + rounds_up as i128
+                    }),
+                };
+
+                int_result(
+                    result,
+                    0,
+                    int_width(ret)?,
+                    OnOverflow::Crash("Integer division overflowed!"),
+                )
+            }
+            _ => unsupported(),
+        },
+        NumNeg => match args {
+            [Value::Int(a)] => int_result(
+                a.checked_neg(),
+                0,
+                int_width(ret)?,
+                OnOverflow::Crash("Integer negation overflowed!"),
+            ),
+            [Value::Float(a)] => Ok(Value::Float(-a)),
+            [Value::Dec(a)] => Ok(Value::Dec(-a)),
+            _ => unsupported(),
+        },
+        NumAbs => match args {
+            [Value::Int(a)] => int_result(
+                a.checked_abs(),
+                0,
+                int_width(ret)?,
+                OnOverflow::Crash("Integer absolute value overflowed!"),
+            ),
+            [Value::Float(a)] => Ok(Value::Float(a.abs())),
+            [Value::Dec(a)] => Ok(Value::Dec(a.abs())),
+            _ => unsupported(),
+        },
+        NumGt | NumGte | NumLt | NumLte => {
+            let ordering = compare(args)?;
+
+            Ok(Value::Bool(match op {
+                NumGt => ordering == Ordering::Greater,
+                NumGte => ordering != Ordering::Less,
+                NumLt => ordering == Ordering::Less,
+                _ => ordering != Ordering::Greater,
+            }))
+        }
+        // [EQ, GT, LT] are tag ids 0, 1 and 2
+        NumCompare => Ok(Value::Int(match compare(args)? {
+            Ordering::Equal => 0,
+            Ordering::Greater => 1,
+            Ordering::Less => 2,
+        })),
+        NumBitwiseAnd | NumBitwiseOr | NumBitwiseXor => match args {
+            [Value::Int(a), Value::Int(b)] => Ok(Value::Int(match op {
+                NumBitwiseAnd => a & b,
+                NumBitwiseOr => a | b,
+                _ => a ^ b,
+            })),
+            _ => unsupported(),
+        },
+        NumIntCast => match (args, int_width(ret)?) {
+            ([Value::Int(a)], IntWidth::U128) if *a < 0 => Err(InterpError::Unsupported(
+                "U128 values above the maximum I128".to_string(),
+            )),
+            ([Value::Int(a)], width) => Ok(Value::Int(wrap_int(*a, width))),
+            _ => unsupported(),
+        },
+        NumToFrac => match (args, ret) {
+            ([Value::Int(a)], LayoutRepr::Builtin(Builtin::Float(FloatWidth::F32))) => {
+                Ok(Value::Float(*a as f32 as f64))
+            }
+            ([Value::Int(a)], LayoutRepr::Builtin(Builtin::Float(_))) => {
+                Ok(Value::Float(*a as f64))
+            }
+            ([Value::Float(a)], LayoutRepr::Builtin(Builtin::Float(_))) => {
+                Ok(Value::Float(round_float(*a, ret)))
+            }
+            _ => unsupported(),
+        },
+        NumToStr => match args {
+            [Value::Int(a)] => Ok(Value::Str(a.to_string())),
+            _ => unsupported(),
+        },
+        Eq => match args {
+            [a, b] => Ok(Value::Bool(a == b)),
+            _ => unsupported(),
+        },
+        NotEq => match args {
+            [a, b] => Ok(Value::Bool(a != b)),
+            _ => unsupported(),
+        },
+        And | Or => match args {
+            [Value::Bool(a), Value::Bool(b)] => {
+                Ok(Value::Bool(if op == And { *a && *b } else { *a || *b }))
+            }
+            _ => unsupported(),
+        },
+        Not => match args {
+            [Value::Bool(a)] => Ok(Value::Bool(!a)),
+            _ => unsupported(),
+        },
+        StrConcat => match args {
+            [Value::Str(a), Value::Str(b)] => Ok(Value::Str(format!("{}{}", a, b))),
+            _ => unsupported(),
+        },
+        StrIsEmpty => match args {
+            [Value::Str(a)] => Ok(Value::Bool(a.is_empty())),
+            _ => unsupported(),
+        },
+        StrCountUtf8Bytes => match args {
+            [Value::Str(a)] => Ok(Value::Int(a.len() as i128)),
+            _ => unsupported(),
+        },
+        StrStartsWith | StrEndsWith => match args {
+            [Value::Str(a), Value::Str(b)] => Ok(Value::Bool(if op == StrStartsWith {
+                a.starts_with(b.as_str())
+            } else {
+                a.ends_with(b.as_str())
+            })),
+            _ => unsupported(),
+        },
+        ListLen => match args {
+            [Value::List(list)] => Ok(Value::Int(list.len() as i128)),
+            _ => unsupported(),
+        },
+        ListWithCapacity => Ok(Value::List(Vec::new())),
+        ListReserve | ListReleaseExcessCapacity => match args {
+            [list @ Value::List(_), ..] => Ok(list.clone()),
+            _ => unsupported(),
+        },
+        ListGetUnsafe => match args {
+            [Value::List(list), Value::Int(index)] => match list.get(*index as usize) {
+                Some(elem) => Ok(elem.clone()),
+                None => Err(InterpError::Crash(format!(
+                    "List index {} is out of bounds for a list of length {}",
+                    index,
+                    list.len()
+                ))),
+            },
+            _ => unsupported(),
+        },
+        ListAppendUnsafe => match args {
+            [Value::List(list), elem] => {
+                let mut list = list.clone();
+                list.push(elem.clone());
+                Ok(Value::List(list))
+            }
+            _ => unsupported(),
+        },
+        ListPrepend => match args {
+            [Value::List(list), elem] => {
+                let mut list = list.clone();
+                list.insert(0, elem.clone());
+                Ok(Value::List(list))
+            }
+            _ => unsupported(),
+        },
+        ListConcat => match args {
+            [Value::List(a), Value::List(b)] => {
+                Ok(Value::List(a.iter().chain(b.iter()).cloned().collect()))
+            }
+            _ => unsupported(),
+        },
+        BoxExpr => match args {
+            [a] => Ok(Value::Boxed(Box::new(a.clone()))),
+            _ => unsupported(),
+        },
+        UnboxExpr => match args {
+            [Value::Boxed(a)] => Ok((**a).clone()),
+            _ => unsupported(),
+        },
+        Unreachable => Err(InterpError::Crash(
+            "Reached code that should be unreachable".to_string(),
+        )),
+        _ => unsupported(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::borrow::Ownership;
+    use crate::ir::{BranchInfo, CallSpecId, UpdateModeId};
+    use crate::layout::{LambdaName, Layout, Niche, STLayoutInterner};
+    use roc_target::TargetInfo;
+
+    fn int(value: i128) -> Expr<'static> {
+        Expr::Literal(Literal::Int(value.to_ne_bytes()))
+    }
+
+    fn lowlevel(op: LowLevel, arguments: &'static [Symbol]) -> Expr<'static> {
+        Expr::Call(Call {
+            call_type: CallType::LowLevel {
+                op,
+                update_mode: UpdateModeId::BACKEND_DUMMY,
+            },
+            arguments,
+        })
+    }
+
+    fn proc_layout(arguments: &'static [InLayout<'static>]) -> ProcLayout<'static> {
+        ProcLayout {
+            arguments,
+            result: Layout::I64,
+            niche: Niche::NONE,
+        }
+    }
+
+    fn proc(
+        name: Symbol,
+        args: &'static [(InLayout<'static>, Symbol)],
+        body: Stmt<'static>,
+    ) -> Proc<'static> {
+        Proc {
+            name: LambdaName::no_niche(name),
+            args,
+            body,
+            closure_data_layout: None,
+            ret_layout: Layout::I64,
+            is_self_recursive: crate::ir::SelfRecursive::NotSelfRecursive,
+            host_exposed_layouts: crate::ir::HostExposedLayouts::NotHostExposed,
+        }
+    }
+
+    fn leak<T>(value: T) -> &'static T {
+        Box::leak(Box::new(value))
+    }
+
+    #[test]
+    fn arithmetic_and_calls() {
+        let interner = STLayoutInterner::with_capacity(4, TargetInfo::default_x86_64());
+        let mut procs = MutMap::default();
+
+        // double = \x -> x + x
+        let double = Symbol::ARG_1;
+        let x = Symbol::ARG_2;
+        procs.insert(
+            (double, proc_layout(&[Layout::I64])),
+            proc(
+                double,
+                &[(Layout::I64, Symbol::ARG_2)],
+                Stmt::Let(
+                    Symbol::ARG_3,
+                    lowlevel(LowLevel::NumAdd, &[Symbol::ARG_2, Symbol::ARG_2]),
+                    Layout::I64,
+                    leak(Stmt::Ret(Symbol::ARG_3)),
+                ),
+            ),
+        );
+
+        // main = double 21
+        let main = Symbol::ARG_4;
+        procs.insert(
+            (main, proc_layout(&[])),
+            proc(
+                main,
+                &[],
+                Stmt::Let(
+                    x,
+                    int(21),
+                    Layout::I64,
+                    leak(Stmt::Let(
+                        Symbol::ARG_5,
+                        Expr::Call(Call {
+                            call_type: CallType::ByName {
+                                name: LambdaName::no_niche(double),
+                                ret_layout: Layout::I64,
+                                arg_layouts: &[Layout::I64],
+                                specialization_id: CallSpecId::BACKEND_DUMMY,
+                            },
+                            arguments: &[Symbol::ARG_2],
+                        }),
+                        Layout::I64,
+                        leak(Stmt::Ret(Symbol::ARG_5)),
+                    )),
+                ),
+            ),
+        );
+
+        let mut interpreter = Interpreter::new(&procs, &interner);
+
+        assert_eq!(
+            interpreter.call_proc(main, proc_layout(&[]), vec![]),
+            Ok(Value::Int(42))
+        );
+        assert_eq!(
+            interpreter.call_proc(
+                double,
+                proc_layout(&[Layout::I64]),
+                vec![Value::Int(i64::MAX as i128)]
+            ),
+            Err(InterpError::Crash(
+                "Integer addition overflowed!".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn u128_above_i128_max() {
+        let interner = STLayoutInterner::with_capacity(4, TargetInfo::default_x86_64());
+        let mut procs = MutMap::default();
+
+        // double = \x -> x + x, on U128
+        let double = Symbol::ARG_1;
+        let layout = ProcLayout {
+            arguments: &[Layout::U128],
+            result: Layout::U128,
+            niche: Niche::NONE,
+        };
+        procs.insert(
+            (double, layout),
+            Proc {
+                ret_layout: Layout::U128,
+                ..proc(
+                    double,
+                    &[(Layout::U128, Symbol::ARG_2)],
+                    Stmt::Let(
+                        Symbol::ARG_3,
+                        lowlevel(LowLevel::NumAdd, &[Symbol::ARG_2, Symbol::ARG_2]),
+                        Layout::U128,
+                        leak(Stmt::Ret(Symbol::ARG_3)),
+                    ),
+                )
+            },
+        );
+
+        let mut interpreter = Interpreter::new(&procs, &interner);
+
+        assert_eq!(
+            interpreter.call_proc(double, layout, vec![Value::Int(i64::MAX as i128)]),
+            Ok(Value::Int(2 * i64::MAX as i128))
+        );
+        // fits in a U128, but not in the i128 we store it in
+        assert!(matches!(
+            interpreter.call_proc(double, layout, vec![Value::Int(i128::MAX)]),
+            Err(InterpError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn join_points_and_switch() {
+        let interner = STLayoutInterner::with_capacity(4, TargetInfo::default_x86_64());
+        let mut procs = MutMap::default();
+
+        // sum = \n ->
+        //     joinpoint loop n acc =
+        //         if n == 0 then acc else jump loop (n - 1) (acc + n)
+        //     in jump loop n 0
+        let sum = Symbol::ARG_1;
+        let n = Symbol::ARG_2;
+        let acc = Symbol::ARG_3;
+        let zero = Symbol::ARG_4;
+        let one = Symbol::ARG_5;
+        let is_zero = Symbol::ARG_6;
+        let n1 = Symbol::ARG_7;
+        let acc1 = Symbol::ARG_8;
+        let id = JoinPointId(Symbol::ATTR_ATTR);
+
+        let param = |symbol| Param {
+            symbol,
+            ownership: Ownership::Owned,
+            layout: Layout::I64,
+        };
+
+        let body = Stmt::Let(
+            is_zero,
+            lowlevel(LowLevel::Eq, leak([n, zero])),
+            Layout::BOOL,
+            leak(Stmt::Switch {
+                cond_symbol: is_zero,
+                cond_layout: Layout::BOOL,
+                branches: leak([(1, BranchInfo::None, Stmt::Ret(acc))]),
+                default_branch: (
+                    BranchInfo::None,
+                    leak(Stmt::Let(
+                        n1,
+                        lowlevel(LowLevel::NumSub, leak([n, one])),
+                        Layout::I64,
+                        leak(Stmt::Let(
+                            acc1,
+                            lowlevel(LowLevel::NumAdd, leak([acc, n])),
+                            Layout::I64,
+                            leak(Stmt::Jump(id, leak([n1, acc1]))),
+                        )),
+                    )),
+                ),
+                ret_layout: Layout::I64,
+            }),
+        );
+
+        procs.insert(
+            (sum, proc_layout(&[Layout::I64])),
+            proc(
+                sum,
+                &[(Layout::I64, Symbol::ARG_2)],
+                Stmt::Let(
+                    zero,
+                    int(0),
+                    Layout::I64,
+                    leak(Stmt::Let(
+                        one,
+                        int(1),
+                        Layout::I64,
+                        leak(Stmt::Join {
+                            id,
+                            parameters: leak([param(n), param(acc)]),
+                            body: leak(body),
+                            remainder: leak(Stmt::Jump(id, leak([n, zero]))),
+                        }),
+                    )),
+                ),
+            ),
+        );
+
+        let mut interpreter = Interpreter::new(&procs, &interner);
+
+        assert_eq!(
+            interpreter.call_proc(sum, proc_layout(&[Layout::I64]), vec![Value::Int(100)]),
+            Ok(Value::Int(5050))
+        );
+
+        let mut interpreter = Interpreter::for_constants(&procs, &interner, 100);
+
+        assert_eq!(
+            interpreter.call_proc(sum, proc_layout(&[Layout::I64]), vec![Value::Int(5)]),
+            Ok(Value::Int(15))
+        );
+        assert_eq!(
+            interpreter.call_proc(sum, proc_layout(&[Layout::I64]), vec![Value::Int(100)]),
+            Err(InterpError::OutOfFuel)
+        );
+    }
+}
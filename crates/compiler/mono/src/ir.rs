@@ -1,6 +1,7 @@
 #![allow(clippy::manual_map)]
 
 use crate::borrow::Ownership;
+use crate::coverage::{self, CoverageKind};
 use crate::ir::literal::{make_num_literal, IntOrFloatValue};
 use crate::layout::{
     self, Builtin, ClosureCallOptions, ClosureRepresentation, EnumDispatch, InLayout, LambdaName,
@@ -17,8 +18,9 @@ use roc_collections::VecMap;
 use roc_debug_flags::dbg_do;
 #[cfg(debug_assertions)]
 use roc_debug_flags::{
-    ROC_PRINT_IR_AFTER_DROP_SPECIALIZATION, ROC_PRINT_IR_AFTER_REFCOUNT,
-    ROC_PRINT_IR_AFTER_RESET_REUSE, ROC_PRINT_IR_AFTER_SPECIALIZATION, ROC_PRINT_RUNTIME_ERROR_GEN,
+    ROC_PRINT_IR_AFTER_DROP_SPECIALIZATION, ROC_PRINT_IR_AFTER_FOLD_CONSTANTS,
+    ROC_PRINT_IR_AFTER_REFCOUNT, ROC_PRINT_IR_AFTER_RESET_REUSE, ROC_PRINT_IR_AFTER_SPECIALIZATION,
+    ROC_PRINT_RUNTIME_ERROR_GEN,
 };
 use roc_derive::SharedDerivedModule;
 use roc_error_macros::{internal_error, todo_abilities};
@@ -26,11 +28,11 @@ use roc_late_solve::storage::{ExternalModuleStorage, ExternalModuleStorageSnapsh
 use roc_late_solve::{resolve_ability_specialization, AbilitiesView, Resolved, UnificationFailed};
 use roc_module::ident::{ForeignSymbol, Lowercase, TagName};
 use roc_module::low_level::{LowLevel, LowLevelWrapperType};
-use roc_module::symbol::{IdentIds, ModuleId, Symbol};
+use roc_module::symbol::{IdentIds, Interns, ModuleId, Symbol};
 use roc_problem::can::{RuntimeError, ShadowKind};
 use roc_region::all::{Loc, Region};
 use roc_std::RocDec;
-use roc_target::TargetInfo;
+use roc_target::{Architecture, OperatingSystem, TargetInfo};
 use roc_types::subs::{
     instantiate_rigids, storage_copy_var_to, Content, ExhaustiveMark, FlatType, RedundantMark,
     StorageSubs, Subs, Variable, VariableSubsSlice,
@@ -51,6 +53,9 @@ pub fn pretty_print_ir_symbols() -> bool {
     dbg_do!(ROC_PRINT_IR_AFTER_SPECIALIZATION, {
         return true;
     });
+    dbg_do!(ROC_PRINT_IR_AFTER_FOLD_CONSTANTS, {
+        return true;
+    });
     dbg_do!(ROC_PRINT_IR_AFTER_RESET_REUSE, {
         return true;
     });
@@ -206,8 +211,13 @@ pub struct PartialProc<'a> {
     pub pattern_symbols: &'a [Symbol],
     pub captured_symbols: CapturedSymbols<'a>,
     pub body: roc_can::expr::Expr,
+    /// Where the body is in the source, for coverage
+    pub body_region: Region,
     pub body_var: Variable,
     pub is_self_recursive: bool,
+    /// The function calls itself in tail position, so each of its specializations should be
+    /// a loop; see [Procs::tail_recursive]
+    pub is_tail_recursive: bool,
 }
 
 impl<'a> PartialProc<'a> {
@@ -218,7 +228,7 @@ impl<'a> PartialProc<'a> {
         loc_args: std::vec::Vec<(Variable, AnnotatedMark, Loc<roc_can::pattern::Pattern>)>,
         loc_body: Loc<roc_can::expr::Expr>,
         captured_symbols: CapturedSymbols<'a>,
-        is_self_recursive: bool,
+        recursive: roc_can::expr::Recursive,
         ret_var: Variable,
     ) -> PartialProc<'a> {
         let number_of_arguments = loc_args.len();
@@ -235,8 +245,10 @@ impl<'a> PartialProc<'a> {
                     pattern_symbols,
                     captured_symbols,
                     body: body.value,
+                    body_region: body.region,
                     body_var: ret_var,
-                    is_self_recursive,
+                    is_self_recursive: recursive.is_recursive(),
+                    is_tail_recursive: recursive == roc_can::expr::Recursive::TailRecursive,
                 }
             }
 
@@ -252,8 +264,10 @@ impl<'a> PartialProc<'a> {
                     pattern_symbols: pattern_symbols.into_bump_slice(),
                     captured_symbols: CapturedSymbols::None,
                     body: roc_can::expr::Expr::RuntimeError(error.value),
+                    body_region: Region::zero(),
                     body_var: ret_var,
                     is_self_recursive: false,
+                    is_tail_recursive: false,
                 }
             }
         }
@@ -323,6 +337,8 @@ pub enum HostExposedLayouts<'a> {
     HostExposed {
         rigids: BumpMap<Lowercase, InLayout<'a>>,
         aliases: BumpMap<Symbol, HostExposedLambdaSet<'a>>,
+        /// The glue getters generated for the layouts in this procedure's type
+        getters: &'a [Symbol],
     },
 }
 
@@ -927,6 +943,18 @@ impl<'a> SpecializationStack<'a> {
     }
 }
 
+/// Functions specialized more times than this are reported, since that usually means their
+/// specializations are multiplying, and building the app will take much longer than it should.
+pub const SPECIALIZATION_WARNING_THRESHOLD: usize = 256;
+
+/// One specialization of a function, and the function whose specialization asked for it.
+#[derive(Clone, Copy, Debug)]
+pub struct SpecializationOrigin<'a> {
+    pub layout: ProcLayout<'a>,
+    /// `None` if the specialization was asked for by another module or the host
+    pub requested_by: Option<Symbol>,
+}
+
 #[derive(Clone, Debug)]
 pub struct Procs<'a> {
     pub partial_procs: PartialProcs<'a>,
@@ -937,6 +965,23 @@ pub struct Procs<'a> {
     pub externals_we_need: BumpMap<ModuleId, ExternalSpecializations<'a>>,
     symbol_specializations: SymbolSpecializations<'a>,
     specialization_stack: SpecializationStack<'a>,
+    /// The distinct specializations made of each function
+    pub specialization_origins: MutMap<Symbol, std::vec::Vec<SpecializationOrigin<'a>>>,
+    /// If set, a function isn't specialized more times than this; the rest of its
+    /// specializations become runtime errors instead.
+    pub max_specializations: Option<usize>,
+    /// Count how often function bodies and branches run, for `roc test --coverage`.
+    /// See [crate::coverage].
+    pub coverage: bool,
+    /// Leave out `expect`s inside function bodies, so their conditions are never computed.
+    /// Optimized builds do this.
+    pub strip_expects: bool,
+    /// The specialized functions that call themselves in tail position, and where their bodies
+    /// are. Once refcounts are inserted, each of their specializations should be a loop; see
+    /// [crate::tail_recursion::is_loop].
+    pub tail_recursive: MutMap<Symbol, Region>,
+    /// Where the body of each specialized function is, for debug info
+    pub body_regions: MutMap<Symbol, Region>,
 
     pub imported_module_thunks: &'a [Symbol],
     pub module_thunks: &'a [Symbol],
@@ -954,6 +999,12 @@ impl<'a> Procs<'a> {
             externals_we_need: BumpMap::new_in(arena),
             symbol_specializations: Default::default(),
             specialization_stack: SpecializationStack(Vec::with_capacity_in(16, arena)),
+            specialization_origins: MutMap::default(),
+            max_specializations: None,
+            coverage: false,
+            strip_expects: false,
+            tail_recursive: MutMap::default(),
+            body_regions: MutMap::default(),
 
             imported_module_thunks: &[],
             module_thunks: &[],
@@ -961,6 +1012,34 @@ impl<'a> Procs<'a> {
         }
     }
 
+    /// Record that `proc_name` is being specialized to `raw`. Returns false if that's more
+    /// specializations of `proc_name` than [Procs::max_specializations] allows.
+    fn record_specialization(
+        &mut self,
+        arena: &'a Bump,
+        proc_name: LambdaName<'a>,
+        raw: RawFunctionLayout<'a>,
+    ) -> bool {
+        let layout = ProcLayout::from_raw_named(arena, proc_name, raw);
+        let requested_by = self.specialization_stack.0.last().copied();
+        let origins = self
+            .specialization_origins
+            .entry(proc_name.name())
+            .or_default();
+
+        if !origins.iter().any(|origin| origin.layout == layout) {
+            origins.push(SpecializationOrigin {
+                layout,
+                requested_by,
+            });
+        }
+
+        match self.max_specializations {
+            Some(max) => origins.len() <= max,
+            None => true,
+        }
+    }
+
     fn push_active_specialization(&mut self, specialization: Symbol) {
         self.specialization_stack.0.push(specialization);
     }
@@ -1122,8 +1201,10 @@ impl<'a> Procs<'a> {
                                         pattern_symbols,
                                         captured_symbols,
                                         body: body.value,
+                                        body_region: body.region,
                                         body_var: ret_var,
                                         is_self_recursive,
+                                        is_tail_recursive: false,
                                     };
 
                                     self.partial_procs.insert(name.name(), partial_proc);
@@ -1150,8 +1231,10 @@ impl<'a> Procs<'a> {
                                     pattern_symbols,
                                     captured_symbols,
                                     body: body.value,
+                                    body_region: body.region,
                                     body_var: ret_var,
                                     is_self_recursive,
+                                    is_tail_recursive: false,
                                 };
 
                                 self.partial_procs.insert(name.name(), partial_proc)
@@ -1579,6 +1662,8 @@ pub enum CrashTag {
     Roc = 0,
     /// The crash is user-defined.
     User = 1,
+    /// The host could not provide memory: `roc_alloc` or `roc_realloc` returned null.
+    OutOfMemory = 2,
 }
 
 impl TryFrom<u32> for CrashTag {
@@ -1588,6 +1673,7 @@ impl TryFrom<u32> for CrashTag {
         match value {
             0 => Ok(Self::Roc),
             1 => Ok(Self::User),
+            2 => Ok(Self::OutOfMemory),
             _ => Err(()),
         }
     }
@@ -2368,7 +2454,8 @@ fn from_can_let<'a>(
                 lower_rest!(variable, cont.value)
             }
             Var(original, _) | AbilityMember(original, _, _)
-                if procs.get_partial_proc(original).is_none() =>
+                if procs.get_partial_proc(original).is_none()
+                    && target_tag(env, original).is_none() =>
             {
                 // a variable is aliased, e.g.
                 //
@@ -3044,6 +3131,8 @@ fn specialize_external_help<'a>(
                 host_exposed_layouts.sort();
                 host_exposed_layouts.dedup();
 
+                let mut getter_names = Vec::new_in(env.arena);
+
                 // Computer the getter procs for every host-exposed layout.
                 for in_layout in host_exposed_layouts {
                     let layout = layout_cache.interner.get(in_layout);
@@ -3063,6 +3152,7 @@ fn specialize_external_help<'a>(
 
                     for (_layout, glue_procs) in getters {
                         for glue_proc in glue_procs {
+                            getter_names.push(glue_proc.name);
                             procs.specialized.insert_specialized(
                                 glue_proc.proc.name.name(),
                                 glue_proc.proc_layout,
@@ -3114,12 +3204,22 @@ fn specialize_external_help<'a>(
                         aliases.insert(key, hels);
                     }
 
+                    let getters = getter_names.into_bump_slice();
+
                     match &mut proc.host_exposed_layouts {
-                        HostExposedLayouts::HostExposed { aliases: old, .. } => old.extend(aliases),
+                        HostExposedLayouts::HostExposed {
+                            aliases: old,
+                            getters: old_getters,
+                            ..
+                        } => {
+                            old.extend(aliases);
+                            *old_getters = getters;
+                        }
                         hep @ HostExposedLayouts::NotHostExposed => {
                             *hep = HostExposedLayouts::HostExposed {
                                 aliases,
                                 rigids: Default::default(),
+                                getters,
                             };
                         }
                     }
@@ -3408,12 +3508,26 @@ fn specialize_proc_help<'a>(
     };
 
     let body = partial_proc.body.clone();
+    let body_region = partial_proc.body_region;
     let body_var = partial_proc.body_var;
 
+    if partial_proc.is_tail_recursive {
+        procs.tail_recursive.insert(lambda_name.name(), body_region);
+    }
+
+    procs.body_regions.insert(lambda_name.name(), body_region);
+
     // host-exposed functions are tagged on later
     let host_exposed_layouts = HostExposedLayouts::NotHostExposed;
 
-    let mut specialized_body = from_can(env, body_var, body, procs, layout_cache);
+    let specialized_body = from_can(env, body_var, body, procs, layout_cache);
+    let mut specialized_body = coverage::count(
+        env,
+        procs,
+        CoverageKind::Function,
+        body_region,
+        specialized_body,
+    );
 
     let specialized_proc = match specialized {
         SpecializedLayout::FunctionPointerBody {
@@ -3879,6 +3993,14 @@ fn specialize_variable<'a>(
         raw
     };
 
+    if !procs.record_specialization(env.arena, proc_name, raw) {
+        rollback_typestate(env.subs, procs, layout_cache, snapshot);
+
+        return Err(SpecializeFailure {
+            attempted_layout: raw,
+        });
+    }
+
     // make sure rigid variables in the annotation are converted to flex variables
     let annotation_var = procs.partial_procs.get_id(partial_proc_id).annotation;
     instantiate_rigids(env.subs, annotation_var);
@@ -4000,6 +4122,39 @@ impl<'a, 'r, I: LayoutInterner<'a>> std::fmt::Debug for DbgProcLayout<'a, 'r, I>
     }
 }
 
+/// A key to sort procs by that doesn't depend on how modules, idents and layouts happen to be
+/// numbered. That numbering changes from run to run when modules load on several threads, so
+/// the backends generate procs in this order to keep `--reproducible` builds byte-for-byte equal.
+pub fn proc_sort_key<'a>(
+    interns: &Interns,
+    interner: &impl LayoutInterner<'a>,
+    symbol: Symbol,
+    layout: &ProcLayout<'a>,
+) -> String {
+    use std::fmt::Write;
+
+    let module_name = interns.module_ids.get_name(symbol.module_id()).unwrap();
+    let mut key = format!("{}.{}", module_name, symbol.as_str(interns));
+
+    for argument in layout.arguments {
+        write!(key, " {}", interner.dbg(*argument)).unwrap();
+    }
+
+    let alloc: ven_pretty::Arena<()> = ven_pretty::Arena::new();
+    let niche = layout
+        .niche
+        .to_doc(&alloc, interner, &mut Default::default());
+    write!(
+        key,
+        " {} -> {}",
+        niche.1.pretty(80),
+        interner.dbg(layout.result)
+    )
+    .unwrap();
+
+    key
+}
+
 fn specialize_naked_symbol<'a>(
     env: &mut Env<'a, '_>,
     variable: Variable,
@@ -4222,6 +4377,22 @@ pub fn with_hole<'a>(
             )
         }
         Var(mut symbol, _) => {
+            if let Some(tag_name) = target_tag(env, symbol) {
+                let arena = env.arena;
+
+                return convert_tag_union(
+                    env,
+                    variable,
+                    assigned,
+                    hole,
+                    tag_name,
+                    procs,
+                    layout_cache,
+                    vec![],
+                    arena,
+                );
+            }
+
             // If this symbol is a raw value, find the real name we gave to its specialized usage.
             if let ReuseSymbol::Value(_symbol) = can_reuse_symbol(
                 env,
@@ -4428,6 +4599,12 @@ pub fn with_hole<'a>(
             branches,
             final_else,
         } => {
+            if let Some(chain) = IntLiteralChain::find(env, layout_cache, &branches) {
+                let when = chain.into_when(branch_var, branches, *final_else);
+
+                return with_hole(env, when, variable, procs, layout_cache, assigned, hole);
+            }
+
             match (
                 layout_cache.from_var(env.arena, branch_var, env.subs),
                 layout_cache.from_var(env.arena, cond_var, env.subs),
@@ -4440,6 +4617,7 @@ pub fn with_hole<'a>(
                     if is_terminated {
                         let terminator = hole;
 
+                        let else_region = final_else.region;
                         let mut stmt = with_hole(
                             env,
                             final_else.value,
@@ -4449,6 +4627,7 @@ pub fn with_hole<'a>(
                             assigned,
                             terminator,
                         );
+                        stmt = coverage::count(env, procs, CoverageKind::Branch, else_region, stmt);
 
                         for (loc_cond, loc_then) in branches.into_iter().rev() {
                             let branching_symbol = env.unique_symbol();
@@ -4462,6 +4641,13 @@ pub fn with_hole<'a>(
                                 assigned,
                                 terminator,
                             );
+                            let then = coverage::count(
+                                env,
+                                procs,
+                                CoverageKind::Branch,
+                                loc_then.region,
+                                then,
+                            );
 
                             stmt = cond(env, branching_symbol, cond_layout, then, stmt, ret_layout);
 
@@ -4485,6 +4671,7 @@ pub fn with_hole<'a>(
                             .arena
                             .alloc(Stmt::Jump(id, env.arena.alloc([assigned_in_jump])));
 
+                        let else_region = final_else.region;
                         let mut stmt = with_hole(
                             env,
                             final_else.value,
@@ -4494,6 +4681,7 @@ pub fn with_hole<'a>(
                             assigned_in_jump,
                             terminator,
                         );
+                        stmt = coverage::count(env, procs, CoverageKind::Branch, else_region, stmt);
 
                         for (loc_cond, loc_then) in branches.into_iter().rev() {
                             let branching_symbol = possible_reuse_symbol_or_specialize(
@@ -4513,6 +4701,13 @@ pub fn with_hole<'a>(
                                 assigned_in_jump,
                                 terminator,
                             );
+                            let then = coverage::count(
+                                env,
+                                procs,
+                                CoverageKind::Branch,
+                                loc_then.region,
+                                then,
+                            );
 
                             stmt = cond(env, branching_symbol, cond_layout, then, stmt, ret_layout);
 
@@ -5525,6 +5720,14 @@ pub fn with_hole<'a>(
                     let xs = arg_symbols[0];
                     match_on_closure_argument!(ListSortWith, [xs])
                 }
+                StrWalkUtf8 => {
+                    debug_assert_eq!(arg_symbols.len(), 3);
+
+                    let string = arg_symbols[0];
+                    let state = arg_symbols[1];
+
+                    match_on_closure_argument!(StrWalkUtf8, [string, state])
+                }
                 ListMap2 => {
                     debug_assert_eq!(arg_symbols.len(), 3);
 
@@ -6448,8 +6651,6 @@ fn register_noncapturing_closure<'a>(
 
     let loc_body = *boxed_body;
 
-    let is_self_recursive = !matches!(recursive, roc_can::expr::Recursive::NotRecursive);
-
     // this should be a top-level declaration, and hence have no captured symbols
     // if we ever do hit this (and it's not a bug), we should make sure to put the
     // captured symbols into a CapturedSymbols and give it to PartialProc::from_named_function
@@ -6461,7 +6662,7 @@ fn register_noncapturing_closure<'a>(
         arguments,
         loc_body,
         CapturedSymbols::None,
-        is_self_recursive,
+        recursive,
         return_type,
     );
 
@@ -6494,8 +6695,6 @@ fn register_capturing_closure<'a>(
         } = closure_data;
         let loc_body = *boxed_body;
 
-        let is_self_recursive = !matches!(recursive, roc_can::expr::Recursive::NotRecursive);
-
         let captured_symbols = match *env.subs.get_content_without_compacting(function_type) {
             Content::Structure(FlatType::Func(args, closure_var, ret)) => {
                 let lambda_set_layout = {
@@ -6552,7 +6751,7 @@ fn register_capturing_closure<'a>(
             arguments,
             loc_body,
             captured_symbols,
-            is_self_recursive,
+            recursive,
             return_type,
         );
 
@@ -6616,6 +6815,12 @@ pub fn from_can<'a>(
             branches,
             final_else,
         } => {
+            if let Some(chain) = IntLiteralChain::find(env, layout_cache, &branches) {
+                let when = chain.into_when(branch_var, branches, *final_else);
+
+                return from_can(env, variable, when, procs, layout_cache);
+            }
+
             let ret_layout = return_on_layout_error!(
                 env,
                 layout_cache.from_var(env.arena, branch_var, env.subs),
@@ -6627,7 +6832,9 @@ pub fn from_can<'a>(
                 "invalid condition type in if expression"
             );
 
+            let else_region = final_else.region;
             let mut stmt = from_can(env, branch_var, final_else.value, procs, layout_cache);
+            stmt = coverage::count(env, procs, CoverageKind::Branch, else_region, stmt);
 
             for (loc_cond, loc_then) in branches.into_iter().rev() {
                 let branching_symbol = possible_reuse_symbol_or_specialize(
@@ -6638,6 +6845,7 @@ pub fn from_can<'a>(
                     cond_var,
                 );
                 let then = from_can(env, branch_var, loc_then.value, procs, layout_cache);
+                let then = coverage::count(env, procs, CoverageKind::Branch, loc_then.region, then);
 
                 stmt = cond(env, branching_symbol, cond_layout, then, stmt, ret_layout);
 
@@ -6661,6 +6869,11 @@ pub fn from_can<'a>(
             lookups_in_cond,
         } => {
             let rest = from_can(env, variable, loc_continuation.value, procs, layout_cache);
+
+            if procs.strip_expects {
+                return rest;
+            }
+
             let cond_symbol = env.unique_symbol();
 
             let mut lookups = Vec::with_capacity_in(lookups_in_cond.len(), env.arena);
@@ -6730,6 +6943,11 @@ pub fn from_can<'a>(
             lookups_in_cond,
         } => {
             let rest = from_can(env, variable, loc_continuation.value, procs, layout_cache);
+
+            if procs.strip_expects {
+                return rest;
+            }
+
             let cond_symbol = env.unique_symbol();
 
             let mut lookups = Vec::with_capacity_in(lookups_in_cond.len(), env.arena);
@@ -6895,7 +7113,7 @@ fn to_opt_branches<'a>(
 ) -> std::vec::Vec<(
     Pattern<'a>,
     Option<Loc<roc_can::expr::Expr>>,
-    roc_can::expr::Expr,
+    Loc<roc_can::expr::Expr>,
 )> {
     debug_assert!(!branches.is_empty());
 
@@ -6930,7 +7148,8 @@ fn to_opt_branches<'a>(
                             loc_expr = Loc::at(region, new_expr);
                         }
 
-                        loc_expr
+                        // the region of the branch's body, rather than of its pattern, for coverage
+                        Loc::at(when_branch.value.region, loc_expr.value)
                     } else {
                         // This pattern is degenerate; when it's reached we must emit a runtime
                         // error.
@@ -6940,14 +7159,14 @@ fn to_opt_branches<'a>(
                     };
 
                     // TODO remove clone?
-                    opt_branches.push((mono_pattern, when_branch.guard.clone(), loc_expr.value));
+                    opt_branches.push((mono_pattern, when_branch.guard.clone(), loc_expr));
                 }
                 Err(runtime_error) => {
                     // TODO remove clone?
                     opt_branches.push((
                         Pattern::Underscore,
                         when_branch.guard.clone(),
-                        roc_can::expr::Expr::RuntimeError(runtime_error),
+                        Loc::at_zero(roc_can::expr::Expr::RuntimeError(runtime_error)),
                     ));
                 }
             }
@@ -6960,13 +7179,166 @@ fn to_opt_branches<'a>(
         opt_branches.push((
             Pattern::Underscore,
             None,
-            roc_can::expr::Expr::RuntimeError(roc_problem::can::RuntimeError::NonExhaustivePattern),
+            Loc::at_zero(roc_can::expr::Expr::RuntimeError(
+                roc_problem::can::RuntimeError::NonExhaustivePattern,
+            )),
         ));
     }
 
     opt_branches
 }
 
+/// An `if` chain whose conditions all compare the same integer to a literal, like
+/// `if n == 1 then a else if n == 2 then b else c`. It's compiled as a `when` on that integer,
+/// so the chain becomes a single switch rather than a switch on every condition in turn.
+struct IntLiteralChain {
+    cond_var: Variable,
+    loc_cond: Loc<roc_can::expr::Expr>,
+    /// The literal each branch compares to, or `None` if an earlier branch already did
+    patterns: std::vec::Vec<Option<Loc<roc_can::pattern::Pattern>>>,
+}
+
+impl IntLiteralChain {
+    fn find<'a>(
+        env: &mut Env<'a, '_>,
+        layout_cache: &mut LayoutCache<'a>,
+        branches: &[(Loc<roc_can::expr::Expr>, Loc<roc_can::expr::Expr>)],
+    ) -> Option<Self> {
+        // a single condition is already a single switch
+        if branches.len() < 2 {
+            return None;
+        }
+
+        let (cond_var, loc_cond, _, _) = compared_to_int_literal(&branches[0].0.value)?;
+        let layout = layout_cache.from_var(env.arena, cond_var, env.subs).ok()?;
+
+        if !matches!(
+            layout_cache.interner.get(layout).repr,
+            LayoutRepr::Builtin(Builtin::Int(_))
+        ) {
+            return None;
+        }
+
+        let mut values = std::vec::Vec::with_capacity(branches.len());
+        let mut patterns = std::vec::Vec::with_capacity(branches.len());
+
+        for (loc_branch_cond, _) in branches {
+            let (_, loc_var, value, pattern) = compared_to_int_literal(&loc_branch_cond.value)?;
+
+            match (&loc_cond.value, &loc_var.value) {
+                (roc_can::expr::Expr::Var(first, _), roc_can::expr::Expr::Var(this, _))
+                    if first == this => {}
+                _ => return None,
+            }
+
+            if values.contains(&value) {
+                patterns.push(None);
+            } else {
+                values.push(value);
+                patterns.push(Some(Loc::at(loc_branch_cond.region, pattern)));
+            }
+        }
+
+        Some(Self {
+            cond_var,
+            loc_cond: loc_cond.clone(),
+            patterns,
+        })
+    }
+
+    fn into_when(
+        self,
+        branch_var: Variable,
+        branches: std::vec::Vec<(Loc<roc_can::expr::Expr>, Loc<roc_can::expr::Expr>)>,
+        final_else: Loc<roc_can::expr::Expr>,
+    ) -> roc_can::expr::Expr {
+        use roc_can::expr::{WhenBranch, WhenBranchPattern};
+
+        let region = Region::span_across(&self.loc_cond.region, &final_else.region);
+        let when_branch = |pattern, value| WhenBranch {
+            patterns: vec![WhenBranchPattern {
+                pattern,
+                degenerate: false,
+            }],
+            value,
+            guard: None,
+            redundant: RedundantMark::known_non_redundant(),
+        };
+
+        let mut when_branches: std::vec::Vec<_> = branches
+            .into_iter()
+            .zip(self.patterns)
+            .filter_map(|((_, loc_then), pattern)| Some(when_branch(pattern?, loc_then)))
+            .collect();
+
+        when_branches.push(when_branch(
+            Loc::at(final_else.region, roc_can::pattern::Pattern::Underscore),
+            final_else,
+        ));
+
+        roc_can::expr::Expr::When {
+            loc_cond: Box::new(self.loc_cond),
+            cond_var: self.cond_var,
+            expr_var: branch_var,
+            region,
+            branches: when_branches,
+            branches_cond_var: self.cond_var,
+            exhaustive: ExhaustiveMark::known_exhaustive(),
+        }
+    }
+}
+
+/// For a condition like `n == 1` or `1 == n`: the type of `n`, `n` itself, and the literal's
+/// value and pattern.
+fn compared_to_int_literal(
+    cond: &roc_can::expr::Expr,
+) -> Option<(
+    Variable,
+    &Loc<roc_can::expr::Expr>,
+    roc_can::expr::IntValue,
+    roc_can::pattern::Pattern,
+)> {
+    use roc_can::expr::Expr::*;
+
+    let (fn_expr, args) = match cond {
+        Call(boxed, args, _) => (&boxed.1.value, args),
+        _ => return None,
+    };
+
+    if !matches!(
+        fn_expr,
+        AbilityMember(Symbol::BOOL_IS_EQ, _, _) | Var(Symbol::BOOL_IS_EQ, _)
+    ) {
+        return None;
+    }
+
+    let (var, loc_var, literal) = match args.as_slice() {
+        [(var, loc_var @ Loc { value: Var(..), .. }), (_, literal)]
+        | [(_, literal), (var, loc_var @ Loc { value: Var(..), .. })] => (*var, loc_var, literal),
+        _ => return None,
+    };
+
+    let (value, pattern) = match &literal.value {
+        Num(var, num_str, value, bound) => (
+            *value,
+            roc_can::pattern::Pattern::NumLiteral(*var, num_str.clone(), *value, *bound),
+        ),
+        Int(var, precision_var, int_str, value, bound) => (
+            *value,
+            roc_can::pattern::Pattern::IntLiteral(
+                *var,
+                *precision_var,
+                int_str.clone(),
+                *value,
+                *bound,
+            ),
+        ),
+        _ => return None,
+    };
+
+    Some((var, loc_var, value, pattern))
+}
+
 #[allow(clippy::too_many_arguments)]
 fn from_can_when<'a>(
     env: &mut Env<'a, '_>,
@@ -7001,7 +7373,7 @@ fn from_can_when<'a>(
     let arena = env.arena;
     let it = opt_branches
         .into_iter()
-        .filter_map(|(pattern, opt_guard, can_expr)| {
+        .filter_map(|(pattern, opt_guard, loc_can_expr)| {
             // If the pattern has a void layout we can drop it; however, we must still perform the
             // work of building the body, because that may contain specializations we must
             // discover for use elsewhere. See
@@ -7018,6 +7390,7 @@ fn from_can_when<'a>(
                 None
             };
 
+            let can_expr = loc_can_expr.value;
             let branch_stmt = match join_point {
                 None => from_can(env, expr_var, can_expr, procs, layout_cache),
                 Some(id) => {
@@ -7028,6 +7401,13 @@ fn from_can_when<'a>(
                     with_hole(env, can_expr, expr_var, procs, layout_cache, symbol, jump)
                 }
             };
+            let branch_stmt = coverage::count(
+                env,
+                procs,
+                CoverageKind::Branch,
+                loc_can_expr.region,
+                branch_stmt,
+            );
 
             use decision_tree::Guard;
             let result = if let Some(loc_expr) = opt_guard {
@@ -7626,7 +8006,7 @@ fn can_reuse_symbol<'a>(
         AbilityMember(member, specialization_id, _) => {
             late_resolve_ability_specialization(env, *member, *specialization_id, expr_var)
         }
-        Var(symbol, _) => *symbol,
+        Var(symbol, _) if target_tag(env, *symbol).is_none() => *symbol,
         _ => return NotASymbol,
     };
 
@@ -7653,6 +8033,29 @@ fn can_reuse_symbol<'a>(
     }
 }
 
+/// The tag that `Target.targetOs` or `Target.targetArchitecture` stands for on the target we are
+/// compiling for, or None for any other symbol. Uses of them become that tag, so that
+/// branches on them can be pruned; see [crate::prune_branches].
+fn target_tag(env: &Env, symbol: Symbol) -> Option<TagName> {
+    let name = match symbol {
+        Symbol::TARGET_TARGET_OS => match env.target_info.operating_system {
+            OperatingSystem::Windows => "Windows",
+            OperatingSystem::Unix => "Unix",
+            OperatingSystem::Wasi => "Wasi",
+        },
+        Symbol::TARGET_TARGET_ARCHITECTURE => match env.target_info.architecture {
+            Architecture::Aarch32 => "Aarch32",
+            Architecture::Aarch64 => "Aarch64",
+            Architecture::Wasm32 => "Wasm32",
+            Architecture::X86_32 => "X86x32",
+            Architecture::X86_64 => "X86x64",
+        },
+        _ => return None,
+    };
+
+    Some(TagName(name.into()))
+}
+
 fn possible_reuse_symbol_or_specialize<'a>(
     env: &mut Env<'a, '_>,
     procs: &mut Procs<'a>,
@@ -908,7 +908,7 @@ impl<'a> UnionLayout<'a> {
     }
 
     fn stores_tag_id_in_pointer_bits(tags: &[&[InLayout<'a>]], target_info: TargetInfo) -> bool {
-        tags.len() < target_info.ptr_width() as usize
+        target_info.pointer_tagging && tags.len() < target_info.ptr_width() as usize
     }
 
     pub const POINTER_MASK_32BIT: usize = 0b0000_0111;
@@ -4667,4 +4667,179 @@ mod test {
         let interner = STLayoutInterner::with_capacity(4, TargetInfo::default_x86_64());
         assert_eq!(interner.alignment_bytes(Layout::U128), 16);
     }
+
+    #[test]
+    fn tag_id_in_pointer_only_with_pointer_tagging() {
+        let tags = [&[Layout::U64] as &[_], &[Layout::U64, Layout::U64] as &[_]];
+        let union_layout = UnionLayout::Recursive(&tags);
+
+        let target_info = TargetInfo::default_x86_64();
+        assert!(union_layout.stores_tag_id_in_pointer(target_info));
+        assert!(!union_layout.stores_tag_id_as_data(target_info));
+
+        let target_info = target_info.without_pointer_tagging();
+        assert!(!union_layout.stores_tag_id_in_pointer(target_info));
+        assert!(union_layout.stores_tag_id_as_data(target_info));
+    }
+
+    mod properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        /// A description of a layout, which proptest can generate and shrink.
+        #[derive(Debug, Clone)]
+        enum Shape {
+            Builtin(LayoutRepr<'static>),
+            List(Box<Shape>),
+            Struct(std::vec::Vec<Shape>),
+            Union(std::vec::Vec<std::vec::Vec<Shape>>),
+        }
+
+        fn shape() -> impl Strategy<Value = Shape> {
+            let leaf = prop::sample::select(vec![
+                LayoutRepr::BOOL,
+                LayoutRepr::U8,
+                LayoutRepr::U16,
+                LayoutRepr::U32,
+                LayoutRepr::I64,
+                LayoutRepr::U128,
+                LayoutRepr::F32,
+                LayoutRepr::F64,
+                LayoutRepr::DEC,
+                LayoutRepr::STR,
+            ])
+            .prop_map(Shape::Builtin);
+
+            leaf.prop_recursive(4, 48, 4, |inner| {
+                prop_oneof![
+                    inner.clone().prop_map(|elem| Shape::List(Box::new(elem))),
+                    prop::collection::vec(inner.clone(), 0..4).prop_map(Shape::Struct),
+                    prop::collection::vec(prop::collection::vec(inner, 0..3), 2..5)
+                        .prop_map(Shape::Union),
+                ]
+            })
+        }
+
+        /// Build the layout for a shape, checking the invariants of every layout along the way.
+        fn build<'a>(
+            arena: &'a Bump,
+            interner: &mut STLayoutInterner<'a>,
+            target_info: TargetInfo,
+            shape: &Shape,
+        ) -> InLayout<'a> {
+            let repr = match shape {
+                Shape::Builtin(repr) => *repr,
+                Shape::List(elem) => {
+                    let elem = build(arena, interner, target_info, elem);
+
+                    LayoutRepr::Builtin(Builtin::List(elem))
+                }
+                Shape::Struct(fields) => {
+                    let mut fields: std::vec::Vec<_> = fields
+                        .iter()
+                        .map(|field| build(arena, interner, target_info, field))
+                        .collect();
+
+                    // Like the compiler does, order fields by decreasing alignment.
+                    fields.sort_by_key(|field| {
+                        let interner = &*interner;
+
+                        std::cmp::Reverse(
+                            interner.get(*field).alignment_bytes(interner, target_info),
+                        )
+                    });
+
+                    LayoutRepr::struct_(arena.alloc_slice_copy(&fields))
+                }
+                Shape::Union(tags) => {
+                    let tags = &*arena.alloc_slice_fill_iter(tags.iter().map(|fields| {
+                        let fields: std::vec::Vec<_> = fields
+                            .iter()
+                            .map(|field| build(arena, interner, target_info, field))
+                            .collect();
+
+                        &*arena.alloc_slice_copy(&fields)
+                    }));
+
+                    LayoutRepr::Union(UnionLayout::NonRecursive(tags))
+                }
+            };
+
+            check_invariants(interner, target_info, repr);
+
+            interner.insert_no_semantic(repr)
+        }
+
+        fn check_invariants<'a>(
+            interner: &STLayoutInterner<'a>,
+            target_info: TargetInfo,
+            repr: LayoutRepr<'a>,
+        ) {
+            let (size, align) = repr.stack_size_and_alignment(interner, target_info);
+
+            if align > 0 {
+                assert!(align.is_power_of_two(), "{repr:?} has alignment {align}");
+                assert_eq!(
+                    size % align,
+                    0,
+                    "{repr:?} has size {size}, alignment {align}"
+                );
+            }
+
+            let size_of =
+                |field: &InLayout<'a>| interner.get(*field).stack_size(interner, target_info);
+            let align_of =
+                |field: &InLayout<'a>| interner.get(*field).alignment_bytes(interner, target_info);
+
+            match repr {
+                LayoutRepr::Struct { field_layouts } => {
+                    let mut offset = 0;
+
+                    for field in field_layouts {
+                        let field_align = align_of(field);
+
+                        if field_align > 0 {
+                            assert_eq!(offset % field_align, 0, "misaligned field in {repr:?}");
+                        }
+
+                        offset += size_of(field);
+                    }
+
+                    assert!(offset <= size, "fields of {repr:?} extend past its size");
+                }
+                LayoutRepr::Union(union_layout @ UnionLayout::NonRecursive(tags)) => {
+                    let max_payload = tags
+                        .iter()
+                        .map(|fields| fields.iter().map(size_of).sum::<u32>())
+                        .max()
+                        .unwrap_or(0);
+
+                    let tag_id_offset = union_layout.tag_id_offset(interner, target_info).unwrap();
+                    let tag_id_size = union_layout.discriminant().stack_size();
+
+                    assert!(
+                        max_payload <= tag_id_offset,
+                        "payload of {repr:?} overlaps its tag id"
+                    );
+                    assert!(
+                        tag_id_offset + tag_id_size <= size,
+                        "tag id of {repr:?} extends past its size"
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        proptest! {
+            #[test]
+            fn layout_size_and_alignment_invariants(shape in shape()) {
+                for target_info in [TargetInfo::default_x86_64(), TargetInfo::default_wasm32()] {
+                    let arena = Bump::new();
+                    let mut interner = STLayoutInterner::with_capacity(4, target_info);
+
+                    build(&arena, &mut interner, target_info, &shape);
+                }
+            }
+        }
+    }
 }
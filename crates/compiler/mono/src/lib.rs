@@ -9,15 +9,28 @@
 // Not a useful lint for us
 #![allow(clippy::too_many_arguments)]
 
+pub mod arena_allocation;
 pub mod borrow;
+pub mod bounds_checks;
 pub mod code_gen_help;
+pub mod coverage;
 pub mod drop_specialization;
+pub mod fold_constants;
+pub mod host_exposed;
 pub mod inc_dec;
+pub mod interp;
 pub mod ir;
 pub mod layout;
 pub mod layout_soa;
+pub mod loop_invariants;
 pub mod low_level;
+pub mod overflow;
+pub mod profile;
+pub mod prune_branches;
 pub mod reset_reuse;
+pub mod rewrite;
+pub mod stack_trace;
 pub mod tail_recursion;
+pub mod trace_calls;
 
 pub mod debug;
@@ -0,0 +1,265 @@
+//! Hoist loop-invariant computations out of loops.
+//!
+//! Tail-recursive procs become join points that jump to themselves (see
+//! [crate::tail_recursion]). A parameter that every such jump passes back unchanged has the same
+//! value in every iteration, and so does a pure, cheap expression that only uses such parameters
+//! and symbols defined before the loop: a literal, a field of a struct, a tag id. We bind those
+//! once, before the first jump into the loop. LLVM finds these itself, but the dev and wasm
+//! backends do not.
+//!
+//! Only expressions that can't fail are hoisted, because a hoisted expression runs even when the
+//! branch it came from would not have. That rules out calls, and reading the payload of a tag
+//! union with more than one tag.
+//!
+//! This must run before refcounts are inserted.
+
+use bumpalo::Bump;
+use roc_collections::{MutMap, MutSet};
+use roc_module::symbol::Symbol;
+
+use crate::ir::{Expr, JoinPointId, Param, Proc, ProcLayout, Stmt};
+use crate::layout::{InLayout, UnionLayout};
+use crate::rewrite::{for_each_child, rewrite_children, Rewrite};
+
+pub fn hoist_loop_invariants<'a>(
+    arena: &'a Bump,
+    procedures: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) {
+    let mut hoist = Hoist { arena };
+
+    for proc in procedures.values_mut() {
+        proc.body = hoist.rewrite(&proc.body);
+    }
+}
+
+struct Hoist<'a> {
+    arena: &'a Bump,
+}
+
+impl<'a> Rewrite<'a> for Hoist<'a> {
+    fn arena(&self) -> &'a Bump {
+        self.arena
+    }
+
+    /// Hoist out of every loop in `stmt`, innermost loops first, so that what is invariant in an
+    /// outer loop too moves all the way out.
+    fn rewrite(&mut self, stmt: &Stmt<'a>) -> Stmt<'a> {
+        match stmt {
+            // the shape `make_tail_recursive` produces
+            Stmt::Join {
+                id,
+                parameters,
+                body,
+                remainder: Stmt::Jump(entry_id, entry_arguments),
+            } if entry_id == id => {
+                let body = self.rewrite(body);
+
+                hoist_loop(self.arena, *id, *parameters, body, *entry_arguments)
+            }
+            _ => rewrite_children(self, stmt),
+        }
+    }
+}
+
+fn hoist_loop<'a>(
+    arena: &'a Bump,
+    id: JoinPointId,
+    parameters: &'a [Param<'a>],
+    body: Stmt<'a>,
+    entry_arguments: &'a [Symbol],
+) -> Stmt<'a> {
+    let mut scan = Scan {
+        id,
+        jumps: std::vec::Vec::new(),
+        definitions: MutMap::default(),
+    };
+    scan.scan(&body);
+
+    let mut varying = MutSet::default();
+    let mut substitutions = MutMap::default();
+
+    for (position, (param, entry)) in parameters.iter().zip(entry_arguments).enumerate() {
+        let unchanged = scan
+            .jumps
+            .iter()
+            .all(|arguments| arguments[position] == param.symbol);
+
+        if unchanged {
+            substitutions.insert(param.symbol, *entry);
+        } else {
+            varying.insert(param.symbol);
+        }
+    }
+
+    // Symbols defined more than once (in different branches) could not all be hoisted
+    // to the same place, so we leave them alone.
+    let duplicated = scan
+        .definitions
+        .iter()
+        .filter(|(_, count)| **count > 1)
+        .map(|(symbol, _)| *symbol)
+        .collect();
+
+    varying.extend(scan.definitions.into_keys());
+
+    let mut hoisting = Hoisting {
+        arena,
+        varying,
+        duplicated,
+        substitutions,
+        hoisted: std::vec::Vec::new(),
+    };
+
+    let body = if scan.jumps.is_empty() {
+        // not a loop after all
+        body
+    } else {
+        hoisting.rewrite(&body)
+    };
+
+    let mut stmt = Stmt::Join {
+        id,
+        parameters,
+        body: arena.alloc(body),
+        remainder: arena.alloc(Stmt::Jump(id, entry_arguments)),
+    };
+
+    for (symbol, expr, layout) in hoisting.hoisted.into_iter().rev() {
+        stmt = Stmt::Let(symbol, expr, layout, arena.alloc(stmt));
+    }
+
+    stmt
+}
+
+/// Collects the jumps back to the loop, and how often each symbol is defined in its body
+struct Scan<'a> {
+    id: JoinPointId,
+    jumps: std::vec::Vec<&'a [Symbol]>,
+    definitions: MutMap<Symbol, usize>,
+}
+
+impl<'a> Scan<'a> {
+    fn define(&mut self, symbol: Symbol) {
+        *self.definitions.entry(symbol).or_insert(0) += 1;
+    }
+
+    fn scan(&mut self, stmt: &Stmt<'a>) {
+        match stmt {
+            Stmt::Let(symbol, ..) => self.define(*symbol),
+            Stmt::Join { parameters, .. } => {
+                for param in parameters.iter() {
+                    self.define(param.symbol);
+                }
+            }
+            Stmt::Jump(id, arguments) if *id == self.id => self.jumps.push(*arguments),
+            _ => {}
+        }
+
+        for_each_child(stmt, |child| self.scan(child));
+    }
+}
+
+struct Hoisting<'a> {
+    arena: &'a Bump,
+    /// Symbols whose value may change from one iteration to the next
+    varying: MutSet<Symbol>,
+    duplicated: MutSet<Symbol>,
+    /// Parameters that keep their value, to the symbols passed in on entry
+    substitutions: MutMap<Symbol, Symbol>,
+    /// The bindings to put before the loop, in order
+    hoisted: std::vec::Vec<(Symbol, Expr<'a>, InLayout<'a>)>,
+}
+
+impl<'a> Hoisting<'a> {
+    fn substitute(&self, symbol: Symbol) -> Option<Symbol> {
+        if self.varying.contains(&symbol) {
+            None
+        } else {
+            Some(*self.substitutions.get(&symbol).unwrap_or(&symbol))
+        }
+    }
+
+    /// `expr` as it would be written before the loop, if it is invariant and can't fail
+    fn invariant(&self, expr: &Expr<'a>) -> Option<Expr<'a>> {
+        use Expr::*;
+
+        match expr {
+            Literal(_) | EmptyArray | NullPointer => Some(expr.clone()),
+            Struct(fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|field| self.substitute(*field))
+                    .collect::<Option<std::vec::Vec<_>>>()?;
+
+                Some(Struct(self.arena.alloc_slice_copy(&fields)))
+            }
+            StructAtIndex {
+                index,
+                field_layouts,
+                structure,
+            } => Some(StructAtIndex {
+                index: *index,
+                field_layouts,
+                structure: self.substitute(*structure)?,
+            }),
+            GetTagId {
+                structure,
+                union_layout,
+            } => Some(GetTagId {
+                structure: self.substitute(*structure)?,
+                union_layout: *union_layout,
+            }),
+            UnionAtIndex {
+                structure,
+                tag_id,
+                union_layout,
+                index,
+            } if has_one_tag(union_layout) => Some(UnionAtIndex {
+                structure: self.substitute(*structure)?,
+                tag_id: *tag_id,
+                union_layout: *union_layout,
+                index: *index,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Rewrite<'a> for Hoisting<'a> {
+    fn arena(&self) -> &'a Bump {
+        self.arena
+    }
+
+    /// `stmt` without the bindings that moved before the loop
+    fn rewrite(&mut self, stmt: &Stmt<'a>) -> Stmt<'a> {
+        match stmt {
+            Stmt::Let(symbol, expr, layout, cont) => {
+                let invariant = if self.duplicated.contains(symbol) {
+                    None
+                } else {
+                    self.invariant(expr)
+                };
+
+                match invariant {
+                    Some(expr) => {
+                        self.varying.remove(symbol);
+                        self.hoisted.push((*symbol, expr, *layout));
+
+                        self.rewrite(cont)
+                    }
+                    None => rewrite_children(self, stmt),
+                }
+            }
+            _ => rewrite_children(self, stmt),
+        }
+    }
+}
+
+/// Whether reading the payload of a value of this layout always finds the tag it expects
+fn has_one_tag(union_layout: &UnionLayout) -> bool {
+    match union_layout {
+        UnionLayout::NonRecursive(tags) | UnionLayout::Recursive(tags) => tags.len() == 1,
+        UnionLayout::NonNullableUnwrapped(_) => true,
+        UnionLayout::NullableWrapped { .. } | UnionLayout::NullableUnwrapped { .. } => false,
+    }
+}
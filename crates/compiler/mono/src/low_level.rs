@@ -1,3 +1,5 @@
+use roc_builtins::bitcode;
+use roc_module::low_level::LowLevel;
 use roc_module::symbol::Symbol;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -23,6 +25,10 @@ pub enum HigherOrder {
     ListSortWith {
         xs: Symbol,
     },
+    StrWalkUtf8 {
+        string: Symbol,
+        state: Symbol,
+    },
 }
 
 impl HigherOrder {
@@ -33,6 +39,7 @@ impl HigherOrder {
             HigherOrder::ListMap3 { .. } => 3,
             HigherOrder::ListMap4 { .. } => 4,
             HigherOrder::ListSortWith { .. } => 2,
+            HigherOrder::StrWalkUtf8 { .. } => 2,
         }
     }
 
@@ -43,7 +50,7 @@ impl HigherOrder {
 
         match self {
             ListMap { .. } | ListSortWith { .. } => 2,
-            ListMap2 { .. } => 3,
+            ListMap2 { .. } | StrWalkUtf8 { .. } => 3,
             ListMap3 { .. } => 4,
             ListMap4 { .. } => 5,
         }
@@ -55,6 +62,158 @@ impl HigherOrder {
     }
 }
 
+/// How the backends implement a [LowLevel].
+/// This is the one place that has to change when a lowlevel is added,
+/// so that every backend agrees on which ones are plain calls to Zig builtins.
+/// The dev and wasm backends use it; the LLVM backend still calls each builtin itself,
+/// because it also needs to know how the builtin returns its result.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LowLevelImpl {
+    /// Generated by each backend, usually as a few instructions.
+    /// The ones that call a Zig builtin can't be [LowLevelImpl::ZigBuiltin], because which
+    /// builtin they call, or what extra arguments it takes, depends on the layouts involved.
+    /// [LowLevelImpl::of] says why for each group of them.
+    Inline,
+    /// A call to this Zig builtin, with the arguments passed as they are.
+    ZigBuiltin(&'static str),
+    /// Inline for primitive layouts, otherwise a call to a helper proc generated by `CodeGenHelp`.
+    HelperProc,
+    /// Takes a function argument. See [HigherOrder].
+    HigherOrder,
+}
+
+impl LowLevelImpl {
+    pub fn of(lowlevel: LowLevel) -> Self {
+        use LowLevel::*;
+        use LowLevelImpl::*;
+
+        match lowlevel {
+            StrConcat => ZigBuiltin(bitcode::STR_CONCAT),
+            StrJoinWith => ZigBuiltin(bitcode::STR_JOIN_WITH),
+            StrStartsWith => ZigBuiltin(bitcode::STR_STARTS_WITH),
+            StrStartsWithScalar => ZigBuiltin(bitcode::STR_STARTS_WITH_SCALAR),
+            StrEndsWith => ZigBuiltin(bitcode::STR_ENDS_WITH),
+            StrSplit => ZigBuiltin(bitcode::STR_SPLIT),
+            StrCountGraphemes => ZigBuiltin(bitcode::STR_COUNT_GRAPEHEME_CLUSTERS),
+            StrCountUtf8Bytes => ZigBuiltin(bitcode::STR_COUNT_UTF8_BYTES),
+            StrToUtf8 => ZigBuiltin(bitcode::STR_TO_UTF8),
+            StrRepeat => ZigBuiltin(bitcode::STR_REPEAT),
+            StrTrim => ZigBuiltin(bitcode::STR_TRIM),
+            StrTrimLeft => ZigBuiltin(bitcode::STR_TRIM_LEFT),
+            StrTrimRight => ZigBuiltin(bitcode::STR_TRIM_RIGHT),
+            StrToScalars => ZigBuiltin(bitcode::STR_TO_SCALARS),
+            StrGetUnsafe => ZigBuiltin(bitcode::STR_GET_UNSAFE),
+            StrSubstringUnsafe => ZigBuiltin(bitcode::STR_SUBSTRING_UNSAFE),
+            StrReserve => ZigBuiltin(bitcode::STR_RESERVE),
+            StrAppendScalar => ZigBuiltin(bitcode::STR_APPEND_SCALAR),
+            StrGetScalarUnsafe => ZigBuiltin(bitcode::STR_GET_SCALAR_UNSAFE),
+            StrGetCapacity => ZigBuiltin(bitcode::STR_CAPACITY),
+            StrWithCapacity => ZigBuiltin(bitcode::STR_WITH_CAPACITY),
+            StrGraphemes => ZigBuiltin(bitcode::STR_GRAPHEMES),
+            StrReleaseExcessCapacity => ZigBuiltin(bitcode::STR_RELEASE_EXCESS_CAPACITY),
+            StrReplaceEachUnsafe => ZigBuiltin(bitcode::STR_REPLACE_EACH_UNSAFE),
+            StrSplitOn => ZigBuiltin(bitcode::STR_SPLIT_ON),
+            ListGetCapacity => ZigBuiltin(bitcode::LIST_CAPACITY),
+            ListIsUnique => ZigBuiltin(bitcode::LIST_IS_UNIQUE),
+            NumBytesToU16 => ZigBuiltin(bitcode::NUM_BYTES_TO_U16),
+            NumBytesToU32 => ZigBuiltin(bitcode::NUM_BYTES_TO_U32),
+            NumBytesToU64 => ZigBuiltin(bitcode::NUM_BYTES_TO_U64),
+            NumBytesToU128 => ZigBuiltin(bitcode::NUM_BYTES_TO_U128),
+            RefCountIncRcPtr => ZigBuiltin(bitcode::UTILS_INCREF_RC_PTR),
+            RefCountDecRcPtr => ZigBuiltin(bitcode::UTILS_DECREF_RC_PTR),
+            RefCountIncDataPtr => ZigBuiltin(bitcode::UTILS_INCREF_DATA_PTR),
+            RefCountDecDataPtr => ZigBuiltin(bitcode::UTILS_DECREF_DATA_PTR),
+            RefCountIsUnique => ZigBuiltin(bitcode::UTILS_IS_UNIQUE),
+
+            Eq | NotEq => HelperProc,
+
+            ListMap | ListMap2 | ListMap3 | ListMap4 | ListSortWith | StrWalkUtf8 => {
+                Self::HigherOrder
+            }
+
+            // Small enough that backends read the fields directly instead of calling a builtin.
+            StrIsEmpty | ListLen => Inline,
+
+            // The Zig builtin to call depends on the width of the number.
+            StrFromInt | StrFromFloat | StrToNum | NumToStr => Inline,
+
+            // The Zig builtin writes its result to a struct that each backend sets up itself.
+            StrFromUtf8Range => Inline,
+
+            // These depend on the element layout. Backends either use its size and alignment
+            // directly, or pass them to the Zig builtin along with refcounting functions.
+            ListWithCapacity
+            | ListReserve
+            | ListReleaseExcessCapacity
+            | ListAppendUnsafe
+            | ListGetUnsafe
+            | ListReplaceUnsafe
+            | ListConcat
+            | ListPrepend
+            | ListSublist
+            | ListDropAt
+            | ListSwap => Inline,
+
+            // A few instructions, or for wide numbers and floats, a Zig builtin picked by width.
+            NumAdd
+            | NumAddWrap
+            | NumAddChecked
+            | NumAddSaturated
+            | NumSub
+            | NumSubWrap
+            | NumSubChecked
+            | NumSubSaturated
+            | NumMul
+            | NumMulWrap
+            | NumMulSaturated
+            | NumMulChecked
+            | NumGt
+            | NumGte
+            | NumLt
+            | NumLte
+            | NumCompare
+            | NumDivFrac
+            | NumDivTruncUnchecked
+            | NumDivCeilUnchecked
+            | NumRemUnchecked
+            | NumIsMultipleOf
+            | NumAbs
+            | NumNeg
+            | NumSin
+            | NumCos
+            | NumSqrtUnchecked
+            | NumLogUnchecked
+            | NumRound
+            | NumToFrac
+            | NumPow
+            | NumCeiling
+            | NumPowInt
+            | NumFloor
+            | NumIsNan
+            | NumIsInfinite
+            | NumIsFinite
+            | NumAtan
+            | NumAcos
+            | NumAsin
+            | NumBitwiseAnd
+            | NumBitwiseXor
+            | NumBitwiseOr
+            | NumShiftLeftBy
+            | NumShiftRightBy
+            | NumShiftRightZfBy
+            | NumIntCast
+            | NumToFloatCast
+            | NumToIntChecked
+            | NumCountLeadingZeroBits
+            | NumCountTrailingZeroBits
+            | NumCountOneBits => Inline,
+
+            // Instructions on booleans and pointers, and the crash for unreachable code.
+            And | Or | Not | PtrCast | PtrWrite | BoxExpr | UnboxExpr | Unreachable => Inline,
+        }
+    }
+}
+
 #[allow(dead_code)]
 enum FirstOrder {
     StrConcat,
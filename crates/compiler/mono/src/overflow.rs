@@ -0,0 +1,113 @@
+//! Decide what integer `+`, `-` and `*` do when the result doesn't fit.
+//!
+//! Every backend implements `NumAdd`, `NumSub` and `NumMul` on integers by crashing on overflow,
+//! which catches bugs but costs a check on every operation. Builds that ask for it can instead
+//! turn them into `NumAddWrap`, `NumSubWrap` and `NumMulWrap`, which wrap around like the
+//! hardware does. Crashing stays the default at every optimization level, so turning on
+//! optimizations never changes what a program computes. Code that relies on a particular
+//! behavior should say so with `Num.addWrap`, `Num.addSaturated` or `Num.addChecked`, which are
+//! never changed here.
+
+use bumpalo::Bump;
+use roc_collections::MutMap;
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::Symbol;
+
+use crate::ir::{Call, CallType, Expr, Proc, ProcLayout, Stmt};
+use crate::layout::{Builtin, LayoutInterner, LayoutRepr, STLayoutInterner};
+use crate::rewrite::{rewrite_children, Rewrite};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowMode {
+    Panic,
+    Wrap,
+}
+
+impl std::str::FromStr for OverflowMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "panic" => Ok(OverflowMode::Panic),
+            "wrap" => Ok(OverflowMode::Wrap),
+            other => Err(format!(
+                "`{}` is not an overflow mode; expected `panic` or `wrap`",
+                other
+            )),
+        }
+    }
+}
+
+/// Make integer arithmetic in every proc behave according to `mode`.
+pub fn apply_overflow_mode<'a>(
+    arena: &'a Bump,
+    interner: &STLayoutInterner<'a>,
+    procedures: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+    mode: OverflowMode,
+) {
+    match mode {
+        // This is what the backends already do
+        OverflowMode::Panic => {}
+        OverflowMode::Wrap => {
+            let mut wrapping = Wrapping { arena, interner };
+
+            for proc in procedures.values_mut() {
+                proc.body = wrapping.rewrite(&proc.body);
+            }
+        }
+    }
+}
+
+fn wrapping_op(op: LowLevel) -> Option<LowLevel> {
+    match op {
+        LowLevel::NumAdd => Some(LowLevel::NumAddWrap),
+        LowLevel::NumSub => Some(LowLevel::NumSubWrap),
+        LowLevel::NumMul => Some(LowLevel::NumMulWrap),
+        _ => None,
+    }
+}
+
+struct Wrapping<'a, 'r> {
+    arena: &'a Bump,
+    interner: &'r STLayoutInterner<'a>,
+}
+
+impl<'a, 'r> Rewrite<'a> for Wrapping<'a, 'r> {
+    fn arena(&self) -> &'a Bump {
+        self.arena
+    }
+
+    fn rewrite(&mut self, stmt: &Stmt<'a>) -> Stmt<'a> {
+        match stmt {
+            Stmt::Let(symbol, expr, layout, cont) => {
+                let cont = self.rewrite(cont);
+
+                let expr = match expr {
+                    Expr::Call(Call {
+                        call_type: CallType::LowLevel { op, update_mode },
+                        arguments,
+                    }) if matches!(
+                        self.interner.get(*layout).repr,
+                        LayoutRepr::Builtin(Builtin::Int(_))
+                    ) =>
+                    {
+                        match wrapping_op(*op) {
+                            Some(op) => Expr::Call(Call {
+                                call_type: CallType::LowLevel {
+                                    op,
+                                    update_mode: *update_mode,
+                                },
+                                arguments: *arguments,
+                            }),
+                            None => expr.clone(),
+                        }
+                    }
+                    _ => expr.clone(),
+                };
+
+                Stmt::Let(*symbol, expr, *layout, self.arena.alloc(cont))
+            }
+            _ => rewrite_children(self, stmt),
+        }
+    }
+}
@@ -0,0 +1,289 @@
+//! Profile-guided optimization, for `roc build --profile-generate` and `--profile-use`.
+//!
+//! A `--profile-generate` build counts how often each function body and branch runs, with the
+//! counters of [crate::coverage], and writes the counts to `<app>.rocprof` when the program
+//! exits. The file starts with a header that names the counted regions, because the next build
+//! may number its counters differently:
+//!
+//! ```text
+//! roc-profile 1
+//! <module> <offset> function|branch
+//! ...
+//!
+//! ```
+//!
+//! The blank line is followed by one native-endian `u64` count per region, in header order.
+//!
+//! A `--profile-use` build is instrumented the same way, which tells us which counter belongs to
+//! which body and branch. [apply_profile] then takes the counters out again, puts the branches of
+//! every `when` in order of how often they were taken, and picks the procs the backend should
+//! inline into their callers, and those it should treat as cold.
+
+use bumpalo::Bump;
+use roc_collections::{MutMap, MutSet};
+use roc_module::symbol::{Interns, Symbol};
+
+use crate::coverage::{is_coverage_hit, CoverageKind, CoveragePoint};
+use crate::ir::{Expr, Literal, Proc, ProcLayout, Stmt};
+use crate::rewrite::{for_each_child, rewrite_children, Rewrite};
+
+/// The profile is written next to the executable, with this extension
+pub const PROFILE_EXTENSION: &str = "rocprof";
+
+const PROFILE_MAGIC: &str = "roc-profile 1\n";
+
+/// Procs with more statements than this are never inlined because they are hot
+const INLINE_MAX_STATEMENTS: usize = 24;
+
+/// A proc is hot if it ran at least this fraction (1 / HOT_DIVISOR) as often as the most
+/// frequently run proc
+const HOT_DIVISOR: u64 = 100;
+
+/// The header the program writes before its counts; see the module docs
+pub fn profile_header(points: &[CoveragePoint], interns: &Interns) -> String {
+    let mut header = String::from(PROFILE_MAGIC);
+
+    for point in points {
+        let kind = match point.kind {
+            CoverageKind::Function => "function",
+            CoverageKind::Branch => "branch",
+        };
+
+        header.push_str(&format!(
+            "{} {} {}\n",
+            interns.module_name(point.module_id).as_str(),
+            point.offset,
+            kind
+        ));
+    }
+
+    header.push('\n');
+
+    header
+}
+
+type ProfileKey = (String, u32, CoverageKind);
+
+/// The counts of a `--profile-generate` run
+#[derive(Debug, Default)]
+pub struct Profile {
+    counts: MutMap<ProfileKey, u64>,
+}
+
+impl Profile {
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        let rest = bytes
+            .strip_prefix(PROFILE_MAGIC.as_bytes())
+            .ok_or("this is not a profile written by a --profile-generate build")?;
+
+        // the header ends with an empty line
+        let header_len = if rest.starts_with(b"\n") {
+            0
+        } else {
+            rest.windows(2)
+                .position(|window| window == b"\n\n")
+                .ok_or("the profile header is malformed")?
+                + 1
+        };
+
+        let header = &rest[..header_len];
+        let counts = &rest[header_len + 1..];
+
+        let header = std::str::from_utf8(header).map_err(|_| "the profile header is not UTF-8")?;
+        let keys = header
+            .lines()
+            .map(parse_key)
+            .collect::<Option<std::vec::Vec<_>>>()
+            .ok_or("the profile header is malformed")?;
+
+        if counts.len() != keys.len() * 8 {
+            return Err(format!(
+                "the profile names {} regions but has {} bytes of counts",
+                keys.len(),
+                counts.len()
+            ));
+        }
+
+        let mut profile = Profile::default();
+
+        for (key, count) in keys.into_iter().zip(counts.chunks_exact(8)) {
+            let count = u64::from_ne_bytes(count.try_into().unwrap());
+
+            // the same region may be counted in more than one specialization
+            *profile.counts.entry(key).or_insert(0) += count;
+        }
+
+        Ok(profile)
+    }
+
+    fn count(&self, point: &CoveragePoint, interns: &Interns) -> Option<u64> {
+        let key = (
+            interns.module_name(point.module_id).as_str().to_string(),
+            point.offset,
+            point.kind,
+        );
+
+        self.counts.get(&key).copied()
+    }
+}
+
+fn parse_key(line: &str) -> Option<ProfileKey> {
+    // module names may contain spaces in theory, so split from the end
+    let mut parts = line.rsplitn(3, ' ');
+
+    let kind = match parts.next()? {
+        "function" => CoverageKind::Function,
+        "branch" => CoverageKind::Branch,
+        _ => return None,
+    };
+    let offset = parts.next()?.parse().ok()?;
+    let module = parts.next()?.to_string();
+
+    Some((module, offset, kind))
+}
+
+/// What the backend should do differently for some procs
+#[derive(Debug, Default, Clone)]
+pub struct ProfileHints {
+    /// Small procs that ran often; the backend should inline them into their callers
+    pub hot: MutSet<Symbol>,
+    /// Procs that never ran; the backend should keep them out of the way of the others
+    pub cold: MutSet<Symbol>,
+}
+
+/// Remove the counters of an instrumented build, using the counts of `profile` to order
+/// branches and to pick hot and cold procs.
+pub fn apply_profile<'a>(
+    arena: &'a Bump,
+    procedures: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+    points: &[CoveragePoint],
+    interns: &Interns,
+    profile: &Profile,
+) -> ProfileHints {
+    let mut applying = Applying {
+        arena,
+        counts: points
+            .iter()
+            .map(|point| profile.count(point, interns))
+            .collect(),
+    };
+
+    let mut proc_counts = std::vec::Vec::new();
+
+    for ((symbol, _), proc) in procedures.iter_mut() {
+        if let Some(count) = applying.leading_count(&proc.body) {
+            proc_counts.push((*symbol, count, count_statements(&proc.body)));
+        }
+
+        proc.body = applying.rewrite(&proc.body);
+    }
+
+    let max_count = proc_counts
+        .iter()
+        .map(|(_, count, _)| *count)
+        .max()
+        .unwrap_or(0);
+
+    let mut hints = ProfileHints::default();
+    let mut ran = MutSet::default();
+
+    for (symbol, count, statements) in proc_counts {
+        if count == 0 {
+            hints.cold.insert(symbol);
+        } else {
+            ran.insert(symbol);
+
+            if count >= max_count / HOT_DIVISOR && statements <= INLINE_MAX_STATEMENTS {
+                hints.hot.insert(symbol);
+            }
+        }
+    }
+
+    // a proc is only cold if none of its specializations ran
+    hints.cold.retain(|symbol| !ran.contains(symbol));
+
+    hints
+}
+
+struct Applying<'a> {
+    arena: &'a Bump,
+    /// The count of every counter, by index; None if the profile doesn't have its region
+    counts: std::vec::Vec<Option<u64>>,
+}
+
+impl<'a> Applying<'a> {
+    /// The count of the counter `stmt` starts with, if any
+    fn leading_count(&self, stmt: &Stmt<'a>) -> Option<u64> {
+        match stmt {
+            // refcounting may have been inserted before the counter
+            Stmt::Refcounting(_, cont) => self.leading_count(cont),
+            Stmt::Let(symbol, Expr::Literal(Literal::Int(bytes)), _, cont)
+                if is_coverage_hit(*symbol, cont) =>
+            {
+                let index = i128::from_ne_bytes(*bytes) as usize;
+
+                self.counts.get(index).copied().flatten()
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Rewrite<'a> for Applying<'a> {
+    fn arena(&self) -> &'a Bump {
+        self.arena
+    }
+
+    fn rewrite(&mut self, stmt: &Stmt<'a>) -> Stmt<'a> {
+        match stmt {
+            Stmt::Let(symbol, Expr::Literal(Literal::Int(_)), _, cont)
+                if is_coverage_hit(*symbol, cont) =>
+            {
+                match cont {
+                    Stmt::Let(_, _, _, after_hit) => self.rewrite(after_hit),
+                    _ => unreachable!(),
+                }
+            }
+            Stmt::Switch {
+                cond_symbol,
+                cond_layout,
+                branches,
+                default_branch,
+                ret_layout,
+            } => {
+                let mut counted = std::vec::Vec::from_iter(
+                    branches
+                        .iter()
+                        .map(|branch| (self.leading_count(&branch.2), branch.clone())),
+                );
+
+                // The order of the branches doesn't change what they mean, but the backends
+                // test for them in this order.
+                if counted.iter().all(|(count, _)| count.is_some()) {
+                    counted.sort_by_key(|(count, _)| std::cmp::Reverse(*count));
+                }
+
+                let sorted = Stmt::Switch {
+                    cond_symbol: *cond_symbol,
+                    cond_layout: *cond_layout,
+                    branches: self
+                        .arena
+                        .alloc_slice_fill_iter(counted.into_iter().map(|(_, branch)| branch)),
+                    default_branch: default_branch.clone(),
+                    ret_layout: *ret_layout,
+                };
+
+                rewrite_children(self, &sorted)
+            }
+            _ => rewrite_children(self, stmt),
+        }
+    }
+}
+
+fn count_statements(stmt: &Stmt) -> usize {
+    let mut count = 1;
+
+    for_each_child(stmt, |child| count += count_statements(child));
+
+    count
+}
@@ -0,0 +1,203 @@
+//! Remove the branches of switches whose condition is known at compile time.
+//!
+//! `when Target.targetOs is ...` and `if Target.targetArchitecture == Wasm32 then ...` branch on
+//! a tag that [mono][crate::ir] already replaced with the one for the target. We follow such
+//! tags, and booleans, through `==`, `!=` and `Bool.not`, and replace a switch on one of them
+//! with the branch it would take, so the other branches cost nothing at runtime.
+//!
+//! Procs that were called from a removed branch, and are no longer called from anywhere else,
+//! are removed too, along with the procs that only they called.
+
+use bumpalo::Bump;
+use roc_collections::{MutMap, MutSet};
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::Symbol;
+
+use crate::ir::{Call, CallType, Expr, HostExposedLayouts, Literal, Proc, ProcLayout, Stmt};
+use crate::rewrite::{for_each_child, rewrite_children, Rewrite};
+
+/// `roots` are the procs that are used from outside the IR, such as the ones exposed to the
+/// host and the expects; they are kept even if no proc calls them.
+pub fn prune_branches<'a>(
+    arena: &'a Bump,
+    procedures: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+    roots: &MutSet<Symbol>,
+) {
+    let mut pruning = Pruning {
+        arena,
+        known: MutMap::default(),
+        removed_calls: MutSet::default(),
+    };
+
+    for proc in procedures.values_mut() {
+        proc.body = pruning.rewrite(&proc.body);
+
+        pruning.known.clear();
+    }
+
+    remove_dead_procs(procedures, roots, pruning.removed_calls);
+}
+
+/// Remove the procs in `candidates` that no other proc calls, and then the ones that only the
+/// removed procs called. Procs that call each other are kept, even if nothing else calls them.
+fn remove_dead_procs<'a>(
+    procedures: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+    roots: &MutSet<Symbol>,
+    mut candidates: MutSet<Symbol>,
+) {
+    loop {
+        let mut called = MutSet::default();
+
+        for proc in procedures.values() {
+            let mut calls = MutSet::default();
+            proc_calls(proc, &mut calls);
+
+            // specializations of a recursive proc call each other
+            calls.remove(&proc.name.name());
+            called.extend(calls);
+        }
+
+        let dead: std::vec::Vec<_> = procedures
+            .iter()
+            .filter(|((symbol, _), proc)| {
+                candidates.contains(symbol)
+                    && !called.contains(symbol)
+                    && !roots.contains(symbol)
+                    && matches!(
+                        proc.host_exposed_layouts,
+                        HostExposedLayouts::NotHostExposed
+                    )
+            })
+            .map(|(key, _)| *key)
+            .collect();
+
+        if dead.is_empty() {
+            break;
+        }
+
+        for key in dead {
+            if let Some(proc) = procedures.remove(&key) {
+                proc_calls(&proc, &mut candidates);
+            }
+        }
+    }
+}
+
+/// Add the procs `proc` calls or passes to a higher-order lowlevel to `calls`
+fn proc_calls(proc: &Proc, calls: &mut MutSet<Symbol>) {
+    if let HostExposedLayouts::HostExposed {
+        aliases, getters, ..
+    } = &proc.host_exposed_layouts
+    {
+        calls.extend(aliases.keys().copied());
+        calls.extend(getters.iter().copied());
+    }
+
+    stmt_calls(&proc.body, calls);
+}
+
+fn stmt_calls(stmt: &Stmt, calls: &mut MutSet<Symbol>) {
+    if let Stmt::Let(_, Expr::Call(call), _, _) = stmt {
+        match &call.call_type {
+            CallType::ByName { name, .. } => {
+                calls.insert(name.name());
+            }
+            CallType::HigherOrder(higher_order) => {
+                calls.insert(higher_order.passed_function.name.name());
+            }
+            CallType::Foreign { .. } | CallType::LowLevel { .. } => {}
+        }
+    }
+
+    for_each_child(stmt, |child| stmt_calls(child, calls));
+}
+
+struct Pruning<'a> {
+    arena: &'a Bump,
+    /// Symbols bound to a boolean or a tag of an enumeration, as the label a switch on them
+    /// would compare with
+    known: MutMap<Symbol, u64>,
+    /// The procs called from the branches that were removed
+    removed_calls: MutSet<Symbol>,
+}
+
+impl<'a> Pruning<'a> {
+    fn value(&self, expr: &Expr<'a>) -> Option<u64> {
+        match expr {
+            Expr::Literal(Literal::Bool(b)) => Some(*b as u64),
+            Expr::Literal(Literal::Byte(byte)) => Some(*byte as u64),
+            Expr::Call(Call {
+                call_type: CallType::LowLevel { op, .. },
+                arguments,
+            }) => {
+                let values: Option<std::vec::Vec<_>> = arguments
+                    .iter()
+                    .map(|argument| self.known.get(argument).copied())
+                    .collect();
+
+                match (op, values?.as_slice()) {
+                    (LowLevel::Eq, [a, b]) => Some((a == b) as u64),
+                    (LowLevel::NotEq, [a, b]) => Some((a != b) as u64),
+                    (LowLevel::Not, [b]) => Some((*b == 0) as u64),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Rewrite<'a> for Pruning<'a> {
+    fn arena(&self) -> &'a Bump {
+        self.arena
+    }
+
+    fn rewrite(&mut self, stmt: &Stmt<'a>) -> Stmt<'a> {
+        match stmt {
+            Stmt::Let(symbol, expr, layout, cont) => {
+                let expr = match self.value(expr) {
+                    Some(value) => {
+                        self.known.insert(*symbol, value);
+
+                        match expr {
+                            Expr::Call(_) => Expr::Literal(Literal::Bool(value != 0)),
+                            _ => expr.clone(),
+                        }
+                    }
+                    None => expr.clone(),
+                };
+
+                let cont = self.rewrite(cont);
+
+                Stmt::Let(*symbol, expr, *layout, self.arena.alloc(cont))
+            }
+            Stmt::Switch {
+                cond_symbol,
+                branches,
+                default_branch,
+                ..
+            } => match self.known.get(cond_symbol) {
+                Some(value) => {
+                    let taken = branches
+                        .iter()
+                        .find(|(label, _, _)| label == value)
+                        .map_or(default_branch.1, |(_, _, branch)| branch);
+
+                    let removed = branches
+                        .iter()
+                        .map(|(_, _, branch)| branch)
+                        .chain(std::iter::once(default_branch.1))
+                        .filter(|branch| !std::ptr::eq(*branch, taken));
+
+                    for branch in removed {
+                        stmt_calls(branch, &mut self.removed_calls);
+                    }
+
+                    self.rewrite(taken)
+                }
+                None => rewrite_children(self, stmt),
+            },
+            _ => rewrite_children(self, stmt),
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! The recursion shared by passes that rebuild proc bodies with a few statements changed.
+//!
+//! A pass implements [Rewrite::rewrite] for the statements it changes, and hands every other
+//! statement to [rewrite_children], which rebuilds it around its rewritten sub-statements.
+//! Passes that only look at a body use [for_each_child] to reach the statements nested in one.
+
+use bumpalo::collections::Vec;
+use bumpalo::Bump;
+
+use crate::ir::Stmt;
+
+pub trait Rewrite<'a> {
+    fn arena(&self) -> &'a Bump;
+
+    /// Rewrite `stmt` and everything nested in it. By default only the nested statements
+    /// are rewritten.
+    fn rewrite(&mut self, stmt: &Stmt<'a>) -> Stmt<'a> {
+        rewrite_children(self, stmt)
+    }
+}
+
+/// `stmt` with each of its sub-statements rewritten by `rewriter`
+pub fn rewrite_children<'a, R>(rewriter: &mut R, stmt: &Stmt<'a>) -> Stmt<'a>
+where
+    R: Rewrite<'a> + ?Sized,
+{
+    use Stmt::*;
+
+    let arena = rewriter.arena();
+
+    match stmt {
+        Let(symbol, expr, layout, cont) => {
+            let cont = rewriter.rewrite(cont);
+
+            Let(*symbol, expr.clone(), *layout, arena.alloc(cont))
+        }
+        Switch { .. } => rewrite_branches(rewriter, stmt, |rewriter, _, branch| {
+            rewriter.rewrite(branch)
+        }),
+        Refcounting(modify_rc, cont) => {
+            let cont = rewriter.rewrite(cont);
+
+            Refcounting(*modify_rc, arena.alloc(cont))
+        }
+        Expect {
+            condition,
+            region,
+            lookups,
+            variables,
+            remainder,
+        } => {
+            let remainder = rewriter.rewrite(remainder);
+
+            Expect {
+                condition: *condition,
+                region: *region,
+                lookups: *lookups,
+                variables: *variables,
+                remainder: arena.alloc(remainder),
+            }
+        }
+        ExpectFx {
+            condition,
+            region,
+            lookups,
+            variables,
+            remainder,
+        } => {
+            let remainder = rewriter.rewrite(remainder);
+
+            ExpectFx {
+                condition: *condition,
+                region: *region,
+                lookups: *lookups,
+                variables: *variables,
+                remainder: arena.alloc(remainder),
+            }
+        }
+        Dbg {
+            symbol,
+            variable,
+            remainder,
+        } => {
+            let remainder = rewriter.rewrite(remainder);
+
+            Dbg {
+                symbol: *symbol,
+                variable: *variable,
+                remainder: arena.alloc(remainder),
+            }
+        }
+        Join {
+            id,
+            parameters,
+            body,
+            remainder,
+        } => {
+            let body = rewriter.rewrite(body);
+            let remainder = rewriter.rewrite(remainder);
+
+            Join {
+                id: *id,
+                parameters: *parameters,
+                body: arena.alloc(body),
+                remainder: arena.alloc(remainder),
+            }
+        }
+        Ret(..) | Jump(..) | Crash(..) => stmt.clone(),
+    }
+}
+
+/// The switch `stmt` with each branch rewritten by `f`, which is given the label of the branch,
+/// or None for the default branch. Any other statement is returned unchanged.
+pub fn rewrite_branches<'a, R, F>(rewriter: &mut R, stmt: &Stmt<'a>, mut f: F) -> Stmt<'a>
+where
+    R: Rewrite<'a> + ?Sized,
+    F: FnMut(&mut R, Option<u64>, &Stmt<'a>) -> Stmt<'a>,
+{
+    let arena = rewriter.arena();
+
+    match stmt {
+        Stmt::Switch {
+            cond_symbol,
+            cond_layout,
+            branches,
+            default_branch,
+            ret_layout,
+        } => {
+            let branches = Vec::from_iter_in(
+                branches.iter().map(|(label, info, branch)| {
+                    (*label, info.clone(), f(rewriter, Some(*label), branch))
+                }),
+                arena,
+            );
+            let default = f(rewriter, None, default_branch.1);
+
+            Stmt::Switch {
+                cond_symbol: *cond_symbol,
+                cond_layout: *cond_layout,
+                branches: branches.into_bump_slice(),
+                default_branch: (default_branch.0.clone(), arena.alloc(default)),
+                ret_layout: *ret_layout,
+            }
+        }
+        _ => stmt.clone(),
+    }
+}
+
+/// Call `f` on each statement directly nested in `stmt`, in order
+pub fn for_each_child<'a, 's>(stmt: &'s Stmt<'a>, mut f: impl FnMut(&'s Stmt<'a>)) {
+    use Stmt::*;
+
+    match stmt {
+        Let(_, _, _, cont) | Refcounting(_, cont) => f(cont),
+        Expect { remainder, .. } | ExpectFx { remainder, .. } | Dbg { remainder, .. } => {
+            f(remainder)
+        }
+        Switch {
+            branches,
+            default_branch,
+            ..
+        } => {
+            for (_, _, branch) in branches.iter() {
+                f(branch);
+            }
+
+            f(default_branch.1);
+        }
+        Join {
+            body, remainder, ..
+        } => {
+            f(body);
+            f(remainder);
+        }
+        Ret(..) | Jump(..) | Crash(..) => {}
+    }
+}
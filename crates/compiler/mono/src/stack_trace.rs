@@ -0,0 +1,70 @@
+//! Keep track of which Roc procs are running, for `--stack-traces` builds, so that a crash can
+//! say where it happened.
+//!
+//! Each proc pushes its name and the location of its body onto a stack kept by the builtins,
+//! and pops it right before every return. When the program crashes, the builtins add that
+//! stack to the crash message, most recent call first, before calling the host's `roc_panic`.
+//! This needs no debug info or frame pointers, so it works in optimized builds too.
+
+use bumpalo::Bump;
+use roc_builtins::bitcode;
+use roc_collections::MutMap;
+use roc_module::symbol::{Interns, ModuleId, Symbol};
+use roc_region::all::{LineInfo, Region};
+use std::path::PathBuf;
+
+use crate::ir::{Proc, ProcLayout};
+use crate::trace_calls::instrument_procs;
+
+/// Like [crate::trace_calls::insert_trace_calls], this must run after refcounts are inserted.
+pub fn insert_stack_trace_calls<'a>(
+    arena: &'a Bump,
+    interns: &mut Interns,
+    home: ModuleId,
+    body_regions: &MutMap<Symbol, Region>,
+    sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+    procedures: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) {
+    let mut line_infos: MutMap<ModuleId, LineInfo> = MutMap::default();
+
+    let names: MutMap<Symbol, &'a str> = procedures
+        .keys()
+        .map(|(symbol, _)| {
+            let mut name = format!(
+                "{}.{}",
+                symbol.module_string(interns),
+                symbol.as_str(interns)
+            );
+
+            // e.g. `Main.parse (main.roc:12:5)`, with the line and column counted from 1
+            let module_id = symbol.module_id();
+            if let (Some(region), Some((path, src))) =
+                (body_regions.get(symbol), sources.get(&module_id))
+            {
+                let line_info = line_infos
+                    .entry(module_id)
+                    .or_insert_with(|| LineInfo::new(src));
+                let start = line_info.convert_pos(region.start());
+
+                name.push_str(&format!(
+                    " ({}:{}:{})",
+                    path.display(),
+                    start.line + 1,
+                    start.column + 1
+                ));
+            }
+
+            (*symbol, &*arena.alloc_str(&name))
+        })
+        .collect();
+
+    instrument_procs(
+        arena,
+        interns,
+        home,
+        procedures,
+        &names,
+        bitcode::UTILS_STACK_TRACE_PUSH,
+        bitcode::UTILS_STACK_TRACE_POP,
+    );
+}
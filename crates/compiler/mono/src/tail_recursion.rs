@@ -1,10 +1,11 @@
 #![allow(clippy::manual_map)]
 
 use crate::borrow::Ownership;
-use crate::ir::{CallType, Expr, JoinPointId, Param, Stmt};
+use crate::ir::{CallType, Expr, JoinPointId, Param, Proc, ProcLayout, SelfRecursive, Stmt};
 use crate::layout::{InLayout, LambdaName};
 use bumpalo::collections::Vec;
 use bumpalo::Bump;
+use roc_collections::all::MutMap;
 use roc_module::symbol::Symbol;
 
 /// Make tail calls into loops (using join points)
@@ -68,6 +69,61 @@ pub fn make_tail_recursive<'a>(
     Some(join)
 }
 
+/// The functions for which `should_loop` holds that have a specialization that isn't a loop,
+/// in a stable order.
+///
+/// This is meant to run right after refcounting, which could, in principle, put work after a
+/// call that made it no longer a tail call.
+pub fn not_loops(
+    procedures: &MutMap<(Symbol, ProcLayout), Proc>,
+    should_loop: impl Fn(Symbol) -> bool,
+) -> std::vec::Vec<Symbol> {
+    let mut not_loops: std::vec::Vec<Symbol> = procedures
+        .iter()
+        .filter(|((symbol, _), proc)| should_loop(*symbol) && !is_loop(proc))
+        .map(|((symbol, _), _)| *symbol)
+        .collect();
+
+    not_loops.sort();
+    not_loops.dedup();
+
+    not_loops
+}
+
+/// Whether the proc's calls to itself became jumps, so it runs as a loop.
+pub fn is_loop(proc: &Proc) -> bool {
+    match proc.is_self_recursive {
+        SelfRecursive::SelfRecursive(id) => jumps_to(&proc.body, id),
+        SelfRecursive::NotSelfRecursive => false,
+    }
+}
+
+fn jumps_to(stmt: &Stmt, goal_id: JoinPointId) -> bool {
+    use Stmt::*;
+
+    match stmt {
+        Jump(id, _) => *id == goal_id,
+        Join {
+            body, remainder, ..
+        } => jumps_to(body, goal_id) || jumps_to(remainder, goal_id),
+        Switch {
+            branches,
+            default_branch,
+            ..
+        } => {
+            branches
+                .iter()
+                .any(|(_, _, branch)| jumps_to(branch, goal_id))
+                || jumps_to(default_branch.1, goal_id)
+        }
+        Let(_, _, _, cont) | Refcounting(_, cont) => jumps_to(cont, goal_id),
+        Dbg { remainder, .. } | Expect { remainder, .. } | ExpectFx { remainder, .. } => {
+            jumps_to(remainder, goal_id)
+        }
+        Ret(_) | Crash(..) => false,
+    }
+}
+
 fn insert_jumps<'a>(
     arena: &'a Bump,
     stmt: &'a Stmt<'a>,
@@ -0,0 +1,142 @@
+//! Instrument every proc with calls to host-provided trace hooks, for `--trace-calls` builds.
+//!
+//! Each proc calls `roc_trace_enter` with its name on entry, and `roc_trace_exit` with its name
+//! right before every return. The host decides what to record (e.g. a timestamp), which is
+//! enough to draw a flame graph of a Roc program without a native profiler.
+//! Both hooks have the C signature `void roc_trace_xxx(const struct RocStr* proc_name)`.
+
+use bumpalo::Bump;
+use roc_collections::MutMap;
+use roc_module::ident::ForeignSymbol;
+use roc_module::symbol::{IdentIds, Interns, ModuleId, Symbol};
+
+use crate::ir::{Call, CallType, Expr, Literal, Proc, ProcLayout, Stmt};
+use crate::layout::Layout;
+use crate::rewrite::{rewrite_children, Rewrite};
+
+pub const TRACE_ENTER: &str = "roc_trace_enter";
+pub const TRACE_EXIT: &str = "roc_trace_exit";
+
+/// This must run after refcounts are inserted: the proc name is a string literal,
+/// which is never refcounted, so no refcounting operations are needed for it.
+pub fn insert_trace_calls<'a>(
+    arena: &'a Bump,
+    interns: &mut Interns,
+    home: ModuleId,
+    procedures: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) {
+    let names: MutMap<Symbol, &'a str> = procedures
+        .keys()
+        .map(|(symbol, _)| {
+            let name = format!(
+                "{}.{}",
+                symbol.module_string(interns),
+                symbol.as_str(interns)
+            );
+
+            (*symbol, &*arena.alloc_str(&name))
+        })
+        .collect();
+
+    instrument_procs(
+        arena,
+        interns,
+        home,
+        procedures,
+        &names,
+        TRACE_ENTER,
+        TRACE_EXIT,
+    );
+}
+
+/// Make each proc call `enter` on entry and `exit` right before every return, both with the
+/// proc's name from `names` as their only argument.
+pub(crate) fn instrument_procs<'a>(
+    arena: &'a Bump,
+    interns: &mut Interns,
+    home: ModuleId,
+    procedures: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+    names: &MutMap<Symbol, &'a str>,
+    enter: &str,
+    exit: &'static str,
+) {
+    let ident_ids = interns.all_ident_ids.get_mut(&home).unwrap();
+
+    for proc in procedures.values_mut() {
+        let name = names[&proc.name.name()];
+
+        // Refcounting and equality helpers are called constantly, and are
+        // part of the compiler rather than of the program being traced.
+        if name.contains(".#help") {
+            continue;
+        }
+
+        let name_symbol = Symbol::new(home, ident_ids.gen_unique());
+        let mut env = Env {
+            arena,
+            home,
+            ident_ids: &mut *ident_ids,
+            name_symbol,
+            exit,
+        };
+
+        let body = env.rewrite(&proc.body);
+        let call_enter = Stmt::Let(
+            env.fresh_symbol(),
+            env.hook_call(enter),
+            Layout::UNIT,
+            arena.alloc(body),
+        );
+
+        proc.body = Stmt::Let(
+            env.name_symbol,
+            Expr::Literal(Literal::Str(name)),
+            Layout::STR,
+            arena.alloc(call_enter),
+        );
+    }
+}
+
+struct Env<'a, 'i> {
+    arena: &'a Bump,
+    home: ModuleId,
+    ident_ids: &'i mut IdentIds,
+    name_symbol: Symbol,
+    exit: &'static str,
+}
+
+impl<'a, 'i> Env<'a, 'i> {
+    fn fresh_symbol(&mut self) -> Symbol {
+        Symbol::new(self.home, self.ident_ids.gen_unique())
+    }
+
+    fn hook_call(&self, hook: &str) -> Expr<'a> {
+        Expr::Call(Call {
+            call_type: CallType::Foreign {
+                foreign_symbol: ForeignSymbol::from(hook),
+                ret_layout: Layout::UNIT,
+            },
+            arguments: self.arena.alloc([self.name_symbol]),
+        })
+    }
+}
+
+impl<'a, 'i> Rewrite<'a> for Env<'a, 'i> {
+    fn arena(&self) -> &'a Bump {
+        self.arena
+    }
+
+    /// Call the exit hook right before every `ret`. A jump continues in this proc, and a crash
+    /// never returns.
+    fn rewrite(&mut self, stmt: &Stmt<'a>) -> Stmt<'a> {
+        match stmt {
+            Stmt::Ret(symbol) => Stmt::Let(
+                self.fresh_symbol(),
+                self.hook_call(self.exit),
+                Layout::UNIT,
+                self.arena.alloc(Stmt::Ret(*symbol)),
+            ),
+            _ => rewrite_children(self, stmt),
+        }
+    }
+}
@@ -314,6 +314,9 @@ pub enum Expr<'a> {
         /// a guard (".. if ..").
         &'a [&'a WhenBranch<'a>],
     ),
+    /// The conditions of a `when a, b is`, which can only be the condition of a `When`.
+    /// Desugared to a tuple, which each branch's patterns match on.
+    WhenConditions(&'a [&'a Loc<Expr<'a>>]),
 
     // Blank Space (e.g. comments, spaces, newlines) before or after an expression.
     // We preserve this for the formatter; canonicalization ignores it.
@@ -321,6 +324,10 @@ pub enum Expr<'a> {
     SpaceAfter(&'a Expr<'a>, &'a [CommentOrNewline<'a>]),
     ParensAround(&'a Expr<'a>),
 
+    /// An identifier followed by the `!` await suffix, e.g. `File.read!`.
+    /// This is desugared into backpassing on `Task.await` or `Result.try`.
+    TaskAwaitBang(&'a Expr<'a>),
+
     // Problems
     MalformedIdent(&'a str, crate::ident::BadIdent),
     MalformedClosure,
@@ -441,6 +448,12 @@ pub enum ValueDef<'a> {
         condition: &'a Loc<Expr<'a>>,
         preceding_comment: Region,
     },
+
+    /// A top-level `bench`, which `roc bench` times. Its `condition` can have any type.
+    Bench {
+        condition: &'a Loc<Expr<'a>>,
+        preceding_comment: Region,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -1528,10 +1541,12 @@ impl<'a> Malformed for Expr<'a> {
             UnaryOp(expr, _) => expr.is_malformed(),
             If(chain, els) => chain.iter().any(|(cond, body)| cond.is_malformed() || body.is_malformed()) || els.is_malformed(),
             When(cond, branches) => cond.is_malformed() || branches.iter().any(|branch| branch.is_malformed()),
+            WhenConditions(conditions) => conditions.iter().any(|cond| cond.is_malformed()),
 
             SpaceBefore(expr, _) |
             SpaceAfter(expr, _) |
-            ParensAround(expr) => expr.is_malformed(),
+            ParensAround(expr) |
+            TaskAwaitBang(expr) => expr.is_malformed(),
 
             MalformedIdent(_, _) |
             MalformedClosure |
@@ -1764,6 +1779,10 @@ impl<'a> Malformed for ValueDef<'a> {
             | ValueDef::ExpectFx {
                 condition,
                 preceding_comment: _,
+            }
+            | ValueDef::Bench {
+                condition,
+                preceding_comment: _,
             } => condition.is_malformed(),
         }
     }
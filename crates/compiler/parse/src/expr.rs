@@ -170,10 +170,7 @@ fn loc_term_or_underscore_or_conditional<'a>(
         loc!(underscore_expression()),
         loc!(record_literal_help()),
         loc!(specialize(EExpr::List, list_literal_help())),
-        loc!(map_with_arena!(
-            assign_or_destructure_identifier(),
-            ident_to_expr
-        )),
+        loc!(identifier_expr()),
     )
 }
 
@@ -190,10 +187,7 @@ fn loc_term_or_underscore<'a>(
         loc!(underscore_expression()),
         loc!(record_literal_help()),
         loc!(specialize(EExpr::List, list_literal_help())),
-        loc!(map_with_arena!(
-            assign_or_destructure_identifier(),
-            ident_to_expr
-        )),
+        loc!(identifier_expr()),
     )
 }
 
@@ -205,10 +199,7 @@ fn loc_term<'a>(options: ExprParseOptions) -> impl Parser<'a, Loc<Expr<'a>>, EEx
         loc!(specialize(EExpr::Closure, closure_help(options))),
         loc!(record_literal_help()),
         loc!(specialize(EExpr::List, list_literal_help())),
-        loc!(map_with_arena!(
-            assign_or_destructure_identifier(),
-            ident_to_expr
-        )),
+        loc!(identifier_expr()),
     )
 }
 
@@ -562,7 +553,9 @@ pub fn parse_single_def<'a>(
 
     let parse_expect_vanilla = crate::parser::keyword_e(crate::keyword::EXPECT, EExpect::Expect);
     let parse_expect_fx = crate::parser::keyword_e(crate::keyword::EXPECT_FX, EExpect::Expect);
+    let parse_bench = crate::parser::keyword_e(crate::keyword::BENCH, EExpect::Expect);
     let parse_expect = either!(parse_expect_fx, parse_expect_vanilla);
+    let parse_statement_keyword = either!(parse_bench, parse_expect);
 
     match space0_after_e(crate::pattern::loc_pattern_help(), EPattern::IndentEnd).parse(
         arena,
@@ -570,7 +563,7 @@ pub fn parse_single_def<'a>(
         min_indent,
     ) {
         Err((NoProgress, _)) => {
-            match parse_expect.parse(arena, state.clone(), min_indent) {
+            match parse_statement_keyword.parse(arena, state.clone(), min_indent) {
                 Err((_, _)) => {
                     // a hacky way to get expression-based error messages. TODO fix this
                     Ok((NoProgress, None, initial))
@@ -584,11 +577,15 @@ pub fn parse_single_def<'a>(
                     spaces_before_current_start,
                     spaces_before_current,
                     |preceding_comment, loc_def_expr| match expect_flavor {
-                        Either::Second(_) => ValueDef::Expect {
+                        Either::Second(Either::Second(_)) => ValueDef::Expect {
                             condition: arena.alloc(loc_def_expr),
                             preceding_comment,
                         },
-                        Either::First(_) => ValueDef::ExpectFx {
+                        Either::Second(Either::First(_)) => ValueDef::ExpectFx {
+                            condition: arena.alloc(loc_def_expr),
+                            preceding_comment,
+                        },
+                        Either::First(_) => ValueDef::Bench {
                             condition: arena.alloc(loc_def_expr),
                             preceding_comment,
                         },
@@ -819,7 +816,7 @@ pub fn parse_single_def<'a>(
     }
 }
 
-/// e.g. Things that can be on their own line in a def, e.g. `expect`, `expect-fx`, or `dbg`
+/// e.g. Things that can be on their own line in a def, e.g. `expect`, `expect-fx`, `bench`, or `dbg`
 #[allow(clippy::too_many_arguments)]
 fn parse_statement_inside_def<'a>(
     arena: &'a Bump,
@@ -1921,12 +1918,14 @@ fn expr_to_pattern_help<'a>(arena: &'a Bump, expr: &Expr<'a>) -> Result<Pattern<
         | Expr::Defs(_, _)
         | Expr::If(_, _)
         | Expr::When(_, _)
+        | Expr::WhenConditions(_)
         | Expr::Expect(_, _)
         | Expr::Dbg(_, _)
         | Expr::MalformedClosure
         | Expr::PrecedenceConflict { .. }
         | Expr::MultipleRecordBuilders { .. }
         | Expr::UnappliedRecordBuilder { .. }
+        | Expr::TaskAwaitBang(_)
         | Expr::RecordUpdate { .. }
         | Expr::UnaryOp(_, _)
         | Expr::Crash => return Err(()),
@@ -2084,10 +2083,7 @@ mod when {
             and!(
                 indented_seq!(
                     parser::keyword_e(keyword::WHEN, EWhen::When),
-                    space0_around_e_no_after_indent_check(
-                        specialize_ref(EWhen::Condition, expr_start(options)),
-                        EWhen::IndentCondition,
-                    )
+                    conditions(options)
                 ),
                 // Note that we allow the `is` to be at any indent level, since this doesn't introduce any
                 // ambiguity. The formatter will fix it up.
@@ -2104,6 +2100,45 @@ mod when {
         )
     }
 
+    /// The value(s) to match on. `when a, b is` matches on the tuple `(a, b)`, and its branches
+    /// have one pattern per value, e.g. `Ok x, Ok y ->`, which is parsed as a tuple pattern.
+    fn conditions<'a>(options: ExprParseOptions) -> impl Parser<'a, Loc<Expr<'a>>, EWhen<'a>> {
+        // the comma separating the conditions conflicts with the one separating backpassing
+        // arguments, like in list literals
+        let options = ExprParseOptions {
+            accept_multi_backpassing: false,
+            ..options
+        };
+
+        map_with_arena!(
+            sep_by1(
+                word1(b',', EWhen::Comma),
+                space0_around_e_no_after_indent_check(
+                    specialize_ref(EWhen::Condition, expr_start(options)),
+                    EWhen::IndentCondition,
+                )
+            ),
+            |arena: &'a Bump, conditions: Vec<'a, Loc<Expr<'a>>>| {
+                if conditions.len() == 1 {
+                    return conditions[0];
+                }
+
+                let region = Region::span_across(
+                    &conditions[0].region,
+                    &conditions[conditions.len() - 1].region,
+                );
+                let conditions = Vec::from_iter_in(
+                    conditions
+                        .into_iter()
+                        .map(|condition| &*arena.alloc(condition)),
+                    arena,
+                );
+
+                Loc::at(region, Expr::WhenConditions(conditions.into_bump_slice()))
+            }
+        )
+    }
+
     fn branches<'a>(
         options: ExprParseOptions,
     ) -> impl Parser<'a, Vec<'a, &'a WhenBranch<'a>>, EWhen<'a>> {
@@ -2214,12 +2249,31 @@ mod when {
             let (_, spaces, state) =
                 backtrackable(space0_e(EWhen::IndentPattern)).parse(arena, state, min_indent)?;
 
-            let (_, loc_pattern, state) = space0_after_e(
-                specialize(EWhen::Pattern, crate::pattern::loc_pattern_help()),
-                EWhen::IndentPattern,
+            let (_, loc_patterns, state) = sep_by1(
+                word1(b',', EWhen::Comma),
+                space0_around_ee(
+                    specialize(EWhen::Pattern, crate::pattern::loc_pattern_help()),
+                    EWhen::IndentPattern,
+                    EWhen::IndentPattern,
+                ),
             )
             .parse(arena, state, min_indent)?;
 
+            // one pattern per condition of a `when a, b is`, which matches on a tuple
+            let loc_pattern = if loc_patterns.len() == 1 {
+                loc_patterns[0]
+            } else {
+                let region = Region::span_across(
+                    &loc_patterns[0].region,
+                    &loc_patterns[loc_patterns.len() - 1].region,
+                );
+
+                Loc::at(
+                    region,
+                    Pattern::Tuple(Collection::with_items(loc_patterns.into_bump_slice())),
+                )
+            };
+
             Ok((
                 MadeProgress,
                 if spaces.is_empty() {
@@ -2455,6 +2509,26 @@ fn assign_or_destructure_identifier<'a>() -> impl Parser<'a, Ident<'a>, EExpr<'a
     crate::ident::parse_ident
 }
 
+/// An identifier, optionally followed by the `!` await suffix (as in `File.read!`).
+/// A `!` directly followed by `=` is the `!=` operator, not a suffix.
+fn identifier_expr<'a>() -> impl Parser<'a, Expr<'a>, EExpr<'a>> {
+    move |arena: &'a Bump, state: State<'a>, min_indent: u32| {
+        let (progress, ident, state) =
+            assign_or_destructure_identifier().parse(arena, state, min_indent)?;
+
+        let is_access = matches!(ident, Ident::Access { .. });
+        let expr = ident_to_expr(arena, ident);
+
+        if is_access && state.bytes().starts_with(b"!") && !state.bytes().starts_with(b"!=") {
+            let state = state.advance(1);
+
+            Ok((progress, Expr::TaskAwaitBang(arena.alloc(expr)), state))
+        } else {
+            Ok((progress, expr, state))
+        }
+    }
+}
+
 #[allow(dead_code)]
 fn with_indent<'a, E, T, P>(parser: P) -> impl Parser<'a, u32, E>
 where
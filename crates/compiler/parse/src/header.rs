@@ -130,21 +130,37 @@ impl<'a> ModuleName<'a> {
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
-pub struct ExposedName<'a>(&'a str);
+pub struct ExposedName<'a> {
+    name: &'a str,
+    opaque: bool,
+}
 
 impl<'a> From<ExposedName<'a>> for &'a str {
     fn from(name: ExposedName<'a>) -> Self {
-        name.0
+        name.name
     }
 }
 
 impl<'a> ExposedName<'a> {
     pub const fn new(name: &'a str) -> Self {
-        ExposedName(name)
+        ExposedName {
+            name,
+            opaque: false,
+        }
+    }
+
+    /// A type alias exposed as `opaque Name`: other modules can refer to it by name,
+    /// but they see it as an opaque type, so its structure stays private to this module.
+    pub const fn new_opaque(name: &'a str) -> Self {
+        ExposedName { name, opaque: true }
     }
 
     pub fn as_str(&'a self) -> &'a str {
-        self.0
+        self.name
+    }
+
+    pub const fn is_opaque(&self) -> bool {
+        self.opaque
     }
 }
 
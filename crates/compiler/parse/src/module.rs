@@ -9,9 +9,9 @@ use crate::header::{
 use crate::ident::{self, lowercase_ident, unqualified_ident, uppercase, UppercaseIdent};
 use crate::parser::Progress::{self, *};
 use crate::parser::{
-    backtrackable, increment_min_indent, optional, reset_min_indent, specialize, word1, word2,
-    EExposes, EGenerates, EGeneratesWith, EHeader, EImports, EPackages, EProvides, ERequires,
-    ETypedIdent, Parser, SourceError, SpaceProblem, SyntaxError,
+    backtrackable, increment_min_indent, keyword_e, optional, reset_min_indent, specialize, word1,
+    word2, EExposes, EGenerates, EGeneratesWith, EHeader, EImports, EPackages, EProvides,
+    ERequires, ETypedIdent, Parser, SourceError, SpaceProblem, SyntaxError,
 };
 use crate::state::State;
 use crate::string_literal::{self, parse_str_literal};
@@ -48,8 +48,6 @@ pub fn parse_header<'a>(
 }
 
 pub fn header<'a>() -> impl Parser<'a, Module<'a>, EHeader<'a>> {
-    use crate::parser::keyword_e;
-
     record!(Module {
         comments: space0_e(EHeader::IndentStart),
         header: one_of![
@@ -328,6 +326,40 @@ where
     ))
 }
 
+/// Like [exposes_entry], but a type alias can also be exposed as `opaque Name`.
+fn exposes_value_entry<'a>() -> impl Parser<'a, Loc<Spaced<'a, ExposedName<'a>>>, EExposes> {
+    loc!(map!(
+        one_of!(
+            opaque_exposed_name(),
+            map!(
+                specialize(|_, pos| EExposes::Identifier(pos), unqualified_ident()),
+                ExposedName::new
+            )
+        ),
+        Spaced::Item
+    ))
+}
+
+/// `opaque Name`. Anything else that starts with `opaque` is left for the other entries to parse.
+fn opaque_exposed_name<'a>() -> impl Parser<'a, ExposedName<'a>, EExposes> {
+    let parser = skip_first!(
+        and!(
+            keyword_e("opaque", EExposes::Identifier),
+            word1(b' ', EExposes::Identifier)
+        ),
+        specialize(|_, pos| EExposes::Identifier(pos), uppercase())
+    );
+
+    move |arena, state: State<'a>, min_indent| match parser.parse(arena, state, min_indent) {
+        Ok((_, name, state)) => {
+            let name: UppercaseIdent<'a> = name;
+
+            Ok((MadeProgress, ExposedName::new_opaque(name.into()), state))
+        }
+        Err((_, fail)) => Err((NoProgress, fail)),
+    }
+}
+
 #[inline(always)]
 fn requires<'a>(
 ) -> impl Parser<'a, KeywordItem<'a, RequiresKeyword, PlatformRequires<'a>>, ERequires<'a>> {
@@ -395,7 +427,7 @@ fn exposes_values<'a>() -> impl Parser<
         ),
         item: collection_trailing_sep_e!(
             word1(b'[', EExposes::ListStart),
-            exposes_entry(EExposes::Identifier),
+            exposes_value_entry(),
             word1(b',', EExposes::ListEnd),
             word1(b']', EExposes::ListEnd),
             Spaced::SpaceBefore
@@ -416,7 +448,7 @@ where
         and!(
             skip_second!(
                 backtrackable(space0_e(indent_problem1)),
-                crate::parser::keyword_e(K::KEYWORD, expectation)
+                keyword_e(K::KEYWORD, expectation)
             ),
             space0_e(indent_problem2)
         ),
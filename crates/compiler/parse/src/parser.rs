@@ -468,6 +468,7 @@ pub enum EWhen<'a> {
     Pattern(EPattern<'a>, Position),
     Arrow(Position),
     Bar(Position),
+    Comma(Position),
 
     IfToken(Position),
     IfGuard(&'a EExpr<'a>, Position),
@@ -10,8 +10,17 @@ use roc_parse::pattern::PatternType;
 use roc_region::all::{Loc, Region};
 use roc_types::types::AliasKind;
 
+use crate::lint::Lint;
 use crate::Severity;
 
+/// A module exposing a value with the name of a failed lookup, e.g. `Str` for `trim`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExposingModule {
+    pub module_name: ModuleName,
+    /// Whether the module with the failed lookup already imports this module
+    pub imported: bool,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct CycleEntry {
     pub symbol: Symbol,
@@ -141,6 +150,9 @@ pub enum Problem {
     AbilityNotOnToplevel {
         region: Region,
     },
+    BenchNotOnToplevel {
+        region: Region,
+    },
     AbilityUsedAsType(Lowercase, Symbol, Region),
     NestedSpecialization(Symbol, Region),
     IllegalDerivedAbility(Region),
@@ -265,6 +277,7 @@ impl Problem {
             Problem::DoesNotImplementAbility { .. } => RuntimeError,
             Problem::NotBoundInAllPatterns { .. } => RuntimeError,
             Problem::NoIdentifiersIntroduced(_) => Warning,
+            Problem::BenchNotOnToplevel { .. } => Warning,
             Problem::OverloadedSpecialization { .. } => Warning, // Ideally, will compile
             Problem::UnnecessaryOutputWildcard { .. } => Warning,
             // TODO: sometimes this can just be a warning, e.g. if you have [1, .., .., 2] but we
@@ -280,6 +293,70 @@ impl Problem {
         }
     }
 
+    /// The lint this problem belongs to, if users can configure its severity.
+    pub fn lint(&self) -> Option<Lint> {
+        match self {
+            Problem::UnusedDef(_, _)
+            | Problem::UnusedBranchDef(_, _)
+            | Problem::DefsOnlyUsedInRecursion(_, _) => Some(Lint::UnusedDef),
+            Problem::UnusedImport(_, _) | Problem::UnusedModuleImport(_, _) => {
+                Some(Lint::UnusedImport)
+            }
+            Problem::UnusedArgument(_, _, _, _) => Some(Lint::UnusedArgument),
+            Problem::PhantomTypeArgument { .. } => Some(Lint::UnusedTypeVariable),
+            Problem::DuplicateRecordFieldValue { .. }
+            | Problem::DuplicateHasAbility { .. }
+            | Problem::DuplicateImpl { .. }
+            | Problem::OverloadedSpecialization { .. } => Some(Lint::Duplicate),
+            Problem::NotAnAbility(_)
+            | Problem::ImplementsNonRequired { .. }
+            | Problem::NoIdentifiersIntroduced(_)
+            | Problem::BenchNotOnToplevel { .. }
+            | Problem::UnnecessaryOutputWildcard { .. } => Some(Lint::Unnecessary),
+            // These make the program crash or fail to build, so they're always errors.
+            Problem::ExposedButNotDefined(_)
+            | Problem::UnknownGeneratesWith(_)
+            | Problem::PrecedenceProblem(_)
+            | Problem::UnsupportedPattern(_, _)
+            | Problem::Shadowing { .. }
+            | Problem::CyclicAlias(..)
+            | Problem::BadRecursion(_)
+            | Problem::UnboundTypeVariable { .. }
+            | Problem::DuplicateRecordFieldType { .. }
+            | Problem::InvalidOptionalValue { .. }
+            | Problem::DuplicateTag { .. }
+            | Problem::RuntimeError(_)
+            | Problem::SignatureDefMismatch { .. }
+            | Problem::InvalidAliasRigid { .. }
+            | Problem::InvalidInterpolation(_)
+            | Problem::InvalidHexadecimal(_)
+            | Problem::InvalidUnicodeCodePt(_)
+            | Problem::NestedDatatype { .. }
+            | Problem::InvalidExtensionType { .. }
+            | Problem::AbilityHasTypeVariables { .. }
+            | Problem::HasClauseIsNotAbility { .. }
+            | Problem::IllegalHasClause { .. }
+            | Problem::AbilityMemberMissingHasClause { .. }
+            | Problem::AbilityMemberMultipleBoundVars { .. }
+            | Problem::AbilityNotOnToplevel { .. }
+            | Problem::AbilityUsedAsType(_, _, _)
+            | Problem::NestedSpecialization(_, _)
+            | Problem::IllegalDerivedAbility(_)
+            | Problem::ImplementationNotFound { .. }
+            | Problem::NotAnAbilityMember { .. }
+            | Problem::OptionalAbilityImpl { .. }
+            | Problem::QualifiedAbilityImpl { .. }
+            | Problem::AbilityImplNotIdent { .. }
+            | Problem::DoesNotImplementAbility { .. }
+            | Problem::NotBoundInAllPatterns { .. }
+            | Problem::MultipleListRestPattern { .. }
+            | Problem::BadTypeArguments { .. }
+            | Problem::UnappliedCrash { .. }
+            | Problem::OverAppliedCrash { .. }
+            | Problem::FileProblem { .. } => None,
+        }
+    }
+
     /// Returns a Region value from the Problem, if possible.
     /// Some problems have more than one region; in those cases,
     /// this tries to pick the one that's closest to the original
@@ -335,7 +412,10 @@ impl Problem {
             })
             | Problem::RuntimeError(RuntimeError::UnsupportedPattern(region))
             | Problem::RuntimeError(RuntimeError::MalformedPattern(_, region))
-            | Problem::RuntimeError(RuntimeError::LookupNotInScope(Loc { region, .. }, _))
+            | Problem::RuntimeError(RuntimeError::LookupNotInScope {
+                loc_name: Loc { region, .. },
+                ..
+            })
             | Problem::RuntimeError(RuntimeError::OpaqueNotDefined {
                 usage: Loc { region, .. },
                 ..
@@ -363,6 +443,7 @@ impl Problem {
             | Problem::RuntimeError(RuntimeError::DegenerateBranch(region))
             | Problem::RuntimeError(RuntimeError::MultipleRecordBuilders(region))
             | Problem::RuntimeError(RuntimeError::UnappliedRecordBuilder(region))
+            | Problem::RuntimeError(RuntimeError::MisplacedTaskAwaitBang(region))
             | Problem::InvalidAliasRigid { region, .. }
             | Problem::InvalidInterpolation(region)
             | Problem::InvalidHexadecimal(region)
@@ -384,6 +465,7 @@ impl Problem {
                 ..
             }
             | Problem::AbilityNotOnToplevel { region }
+            | Problem::BenchNotOnToplevel { region }
             | Problem::AbilityUsedAsType(_, _, region)
             | Problem::NestedSpecialization(_, region)
             | Problem::IllegalDerivedAbility(region)
@@ -505,7 +587,14 @@ pub enum RuntimeError {
     UnresolvedTypeVar,
     ErroneousType,
 
-    LookupNotInScope(Loc<Ident>, MutSet<Box<str>>),
+    LookupNotInScope {
+        loc_name: Loc<Ident>,
+        /// All of the names in scope (for the error message)
+        suggestion_options: MutSet<Box<str>>,
+        /// Other modules which expose a value with exactly this name. Canonicalization only
+        /// knows about the modules this one imports, so the loader fills this in afterwards.
+        exposed_by: Vec<ExposingModule>,
+    },
     OpaqueNotDefined {
         usage: Loc<Ident>,
         opaques_in_scope: MutSet<Box<str>>,
@@ -593,6 +682,8 @@ pub enum RuntimeError {
 
     MultipleRecordBuilders(Region),
     UnappliedRecordBuilder(Region),
+    /// A `!` await suffix that is not on the right-hand side of a def, e.g. `f (g! x)`
+    MisplacedTaskAwaitBang(Region),
 }
 
 impl RuntimeError {
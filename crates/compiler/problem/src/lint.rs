@@ -0,0 +1,146 @@
+//! Users can choose how loudly some problems get reported.
+//!
+//! Problems that are warnings by default belong to a [Lint], and a [SeverityConfig]
+//! says whether each lint should be reported as an error, a warning, or not at all.
+//! Problems that would make the program misbehave are always errors.
+use std::fmt;
+use std::str::FromStr;
+
+use crate::Severity;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Lint {
+    /// Definitions that are never used, or only used by themselves.
+    UnusedDef,
+    /// Modules or values that are imported but never used.
+    UnusedImport,
+    /// Function arguments that are never used.
+    UnusedArgument,
+    /// Type alias parameters that don't appear in the alias.
+    UnusedTypeVariable,
+    /// Something declared twice where the first one wins, like a record field.
+    Duplicate,
+    /// Code that has no effect, like a wildcard in an output type.
+    Unnecessary,
+    /// `when` branches that can never match.
+    RedundantPattern,
+    /// Number literals that the float type they are used as can't represent exactly.
+    PrecisionLoss,
+    /// `Result`s thrown away by the `_` of a backpassing arrow.
+    DiscardedResult,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 9] = [
+        Lint::UnusedDef,
+        Lint::UnusedImport,
+        Lint::UnusedArgument,
+        Lint::UnusedTypeVariable,
+        Lint::Duplicate,
+        Lint::Unnecessary,
+        Lint::RedundantPattern,
+        Lint::PrecisionLoss,
+        Lint::DiscardedResult,
+    ];
+
+    /// The name used for this lint on the command line and in project config.
+    pub fn name(self) -> &'static str {
+        match self {
+            Lint::UnusedDef => "unused-def",
+            Lint::UnusedImport => "unused-import",
+            Lint::UnusedArgument => "unused-argument",
+            Lint::UnusedTypeVariable => "unused-type-variable",
+            Lint::Duplicate => "duplicate",
+            Lint::Unnecessary => "unnecessary",
+            Lint::RedundantPattern => "redundant-pattern",
+            Lint::PrecisionLoss => "precision-loss",
+            Lint::DiscardedResult => "discarded-result",
+        }
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Lint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Lint::ALL
+            .into_iter()
+            .find(|lint| lint.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<_> = Lint::ALL.iter().map(|lint| lint.name()).collect();
+
+                format!(
+                    "`{}` is not a kind of warning. Expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Level {
+    Error,
+    Warning,
+    Off,
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(Level::Error),
+            "warning" => Ok(Level::Warning),
+            "off" => Ok(Level::Off),
+            _ => Err(format!(
+                "`{}` is not a severity. Expected one of: error, warning, off",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SeverityConfig {
+    levels: Vec<(Lint, Level)>,
+    /// Report every warning as an error, e.g. to keep CI builds warning-free.
+    pub fail_on_warnings: bool,
+}
+
+impl SeverityConfig {
+    pub fn set(&mut self, lint: Lint, level: Level) {
+        match self.levels.iter_mut().find(|(other, _)| *other == lint) {
+            Some((_, existing)) => *existing = level,
+            None => self.levels.push((lint, level)),
+        }
+    }
+
+    pub fn level(&self, lint: Lint) -> Option<Level> {
+        self.levels
+            .iter()
+            .find(|(other, _)| *other == lint)
+            .map(|(_, level)| *level)
+    }
+
+    /// The severity to report a problem with, or `None` if its lint is turned off.
+    pub fn severity(&self, default: Severity, lint: Option<Lint>) -> Option<Severity> {
+        let severity = match (default, lint.and_then(|lint| self.level(lint))) {
+            (Severity::Warning, Some(Level::Off)) => return None,
+            (Severity::Warning, Some(Level::Error)) => Severity::RuntimeError,
+            (severity, _) => severity,
+        };
+
+        if self.fail_on_warnings && severity == Severity::Warning {
+            Some(Severity::RuntimeError)
+        } else {
+            Some(severity)
+        }
+    }
+}
@@ -53,6 +53,9 @@ impl From<target_lexicon::OperatingSystem> for OperatingSystem {
 pub struct TargetInfo {
     pub architecture: Architecture,
     pub operating_system: OperatingSystem,
+    /// Whether recursive tag unions may store their tag id in the unused low bits of a pointer.
+    /// Memory checkers see such pointers as pointing into the middle of an allocation.
+    pub pointer_tagging: bool,
 }
 
 impl TargetInfo {
@@ -75,6 +78,7 @@ impl TargetInfo {
         TargetInfo {
             architecture: Architecture::Aarch64,
             operating_system: OperatingSystem::Unix,
+            pointer_tagging: true,
         }
     }
 
@@ -82,6 +86,7 @@ impl TargetInfo {
         TargetInfo {
             architecture: Architecture::X86_64,
             operating_system: OperatingSystem::Unix,
+            pointer_tagging: true,
         }
     }
 
@@ -89,6 +94,16 @@ impl TargetInfo {
         TargetInfo {
             architecture: Architecture::Wasm32,
             operating_system: OperatingSystem::Wasi,
+            pointer_tagging: true,
+        }
+    }
+
+    /// Store the tag ids of recursive tag unions next to their data instead of in pointers,
+    /// so tools like Valgrind and AddressSanitizer see ordinary pointers
+    pub const fn without_pointer_tagging(self) -> Self {
+        Self {
+            pointer_tagging: false,
+            ..self
         }
     }
 }
@@ -101,6 +116,7 @@ impl From<&target_lexicon::Triple> for TargetInfo {
         Self {
             architecture,
             operating_system,
+            pointer_tagging: true,
         }
     }
 }
@@ -10,17 +10,27 @@ pub fn serialize_slice<T: Copy>(
     writer: &mut impl Write,
     written: usize,
 ) -> io::Result<usize> {
-    let alignment = std::mem::align_of::<T>();
+    let written = serialize_padding(writer, written, std::mem::align_of::<T>())?;
+
+    let bytes_slice = unsafe { slice_as_bytes(slice) };
+    writer.write_all(bytes_slice)?;
+
+    Ok(written + bytes_slice.len())
+}
+
+/// Writes zeroes until `written` is a multiple of `alignment`.
+pub fn serialize_padding(
+    writer: &mut impl Write,
+    written: usize,
+    alignment: usize,
+) -> io::Result<usize> {
     let padding_bytes = next_multiple_of(written, alignment) - written;
 
     for _ in 0..padding_bytes {
         writer.write_all(&[0])?;
     }
 
-    let bytes_slice = unsafe { slice_as_bytes(slice) };
-    writer.write_all(bytes_slice)?;
-
-    Ok(written + padding_bytes + bytes_slice.len())
+    Ok(written + padding_bytes)
 }
 
 pub fn deserialize_slice<T: Copy>(bytes: &[u8], length: usize, mut offset: usize) -> (&[T], usize) {
@@ -213,8 +223,8 @@ mod test {
 
     use super::{
         deserialize_map, deserialize_slice, deserialize_slice_of_slices, deserialize_vec,
-        deserialize_vec_map, serialize_map, serialize_slice, serialize_slice_of_slices,
-        serialize_vec_map,
+        deserialize_vec_map, serialize_map, serialize_padding, serialize_slice,
+        serialize_slice_of_slices, serialize_vec_map,
     };
 
     #[test]
@@ -241,6 +251,18 @@ mod test {
         assert_eq!(size, 4 * 8);
     }
 
+    #[test]
+    fn padding() {
+        let mut buf = vec![1u8, 2, 3];
+        let written = serialize_padding(&mut buf, 3, 8).unwrap();
+        assert_eq!(written, 8);
+        assert_eq!(buf, [1, 2, 3, 0, 0, 0, 0, 0]);
+
+        let written = serialize_padding(&mut buf, 8, 8).unwrap();
+        assert_eq!(written, 8);
+        assert_eq!(buf.len(), 8);
+    }
+
     #[test]
     fn serde_vec() {
         let input: &[u64] = &[15u64, 23, 37, 89];
@@ -0,0 +1,62 @@
+//! Warn about `Result`s that a backpassing `_` throws away.
+//!
+//! Roc has no statements, so the only place a value is dropped without naming it is the `_` of
+//! `_ <- task`, which is how a line of effectful code is written. When that value is a `Result`,
+//! like the one `Task.attempt` gives, its error is silently ignored. A named underscore like
+//! `_result <- task` still drops a `Result` without a warning, for when that is intended.
+
+use roc_can::expr::{ClosureData, Declarations, Expr};
+use roc_can::pattern::Pattern;
+use roc_can::traverse::{walk_expr, Visitor};
+use roc_module::symbol::Symbol;
+use roc_region::all::Region;
+use roc_solve_problem::TypeError;
+use roc_types::subs::{Content, Subs, Variable};
+
+pub fn check_discarded_results(subs: &Subs, decls: &Declarations, problems: &mut Vec<TypeError>) {
+    let mut checker = Checker { subs, problems };
+
+    checker.visit_decls(decls);
+}
+
+struct Checker<'a> {
+    subs: &'a Subs,
+    problems: &'a mut Vec<TypeError>,
+}
+
+impl Visitor for Checker<'_> {
+    fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+        if let Expr::Call(_, args, _) = expr {
+            // Desugaring `pattern <- body` appends the continuation to the call in `body`,
+            // and gives both the region of the whole backpassing expression.
+            let continuation = args.last().and_then(|(_, loc_arg)| match &loc_arg.value {
+                Expr::Closure(closure) if loc_arg.region == region => Some(closure),
+                _ => None,
+            });
+
+            if let Some(ClosureData { arguments, .. }) = continuation {
+                if let [(arg_var, _, loc_pattern)] = arguments.as_slice() {
+                    // A named underscore like `_result` canonicalizes to the same pattern as `_`,
+                    // but it is the explicit way to drop the value, so only a bare `_` counts.
+                    let is_bare_underscore = matches!(loc_pattern.value, Pattern::Underscore)
+                        && loc_pattern.region.end().offset - loc_pattern.region.start().offset == 1;
+
+                    if is_bare_underscore && is_result(self.subs, *arg_var) {
+                        self.problems.push(TypeError::DiscardedResult {
+                            region: loc_pattern.region,
+                        });
+                    }
+                }
+            }
+        }
+
+        walk_expr(self, expr, var)
+    }
+}
+
+fn is_result(subs: &Subs, var: Variable) -> bool {
+    matches!(
+        subs.get_content_without_compacting(var),
+        Content::Alias(Symbol::RESULT_RESULT, _, _, _)
+    )
+}
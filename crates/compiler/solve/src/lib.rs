@@ -5,6 +5,8 @@
 #![allow(clippy::large_enum_variant)]
 
 pub mod ability;
+pub mod discarded;
 pub mod module;
+pub mod precision;
 pub mod solve;
 pub mod specialize;
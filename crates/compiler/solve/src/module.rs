@@ -4,13 +4,13 @@ use roc_can::constraint::{Constraint as ConstraintSoa, Constraints};
 use roc_can::expr::PendingDerives;
 use roc_can::module::{ExposedByModule, ResolvedImplementations, RigidVariables};
 use roc_collections::all::MutMap;
-use roc_collections::VecMap;
+use roc_collections::{VecMap, VecSet};
 use roc_derive::SharedDerivedModule;
 use roc_error_macros::internal_error;
 use roc_module::symbol::{ModuleId, Symbol};
 use roc_solve_problem::TypeError;
 use roc_types::subs::{Content, ExposedTypesStorageSubs, FlatType, StorageSubs, Subs, Variable};
-use roc_types::types::{Alias, MemberImpl, Types};
+use roc_types::types::{Alias, AliasKind, MemberImpl, Types};
 
 /// A marker that a given Subs has been solved.
 /// The only way to obtain a Solved<Subs> is by running the solver on it.
@@ -108,6 +108,7 @@ pub fn exposed_types_storage_subs(
     exposed_vars_by_symbol: &[(Symbol, Variable)],
     solved_implementations: &ResolvedImplementations,
     abilities_store: &AbilitiesStore,
+    opaque_exposes: &VecSet<Symbol>,
 ) -> ExposedTypesStorageSubs {
     let subs = solved_subs.inner_mut();
     let mut storage_subs = StorageSubs::new(Subs::new());
@@ -170,6 +171,8 @@ pub fn exposed_types_storage_subs(
         })
         .collect();
 
+    hide_opaquely_exposed_aliases(storage_subs.as_inner_mut(), opaque_exposes);
+
     ExposedTypesStorageSubs {
         storage_subs,
         stored_vars_by_symbol,
@@ -178,6 +181,29 @@ pub fn exposed_types_storage_subs(
     }
 }
 
+/// Aliases exposed as `opaque Name` are opaque types to the modules that import them, so that
+/// they can't see through to (or depend on) the alias's structure.
+fn hide_opaquely_exposed_aliases(storage_subs: &mut Subs, opaque_exposes: &VecSet<Symbol>) {
+    if opaque_exposes.is_empty() {
+        return;
+    }
+
+    for index in 0..storage_subs.len() {
+        let var = unsafe { Variable::from_index(index as u32) };
+
+        if let Content::Alias(symbol, args, real_var, AliasKind::Structural) =
+            *storage_subs.get_content_without_compacting(var)
+        {
+            if opaque_exposes.contains(&symbol) {
+                storage_subs.set_content(
+                    var,
+                    Content::Alias(symbol, args, real_var, AliasKind::Opaque),
+                );
+            }
+        }
+    }
+}
+
 /// Extracts the ability member implementations owned by a solved module.
 pub fn extract_module_owned_implementations(
     module_id: ModuleId,
@@ -0,0 +1,118 @@
+//! Warn about number literals that lose precision because of the type they were solved to.
+//!
+//! A literal takes the type of whatever it is used with, so `x < 16777217` makes `16777217` an
+//! `F32` when `x` is one, and `F32` can't represent that number: it becomes `16777216`. Nothing
+//! in the source says so, so we report it once solving has decided the type of every literal.
+//! Fractional literals are only checked against `F32`; the canonicalizer already stored them as
+//! `F64`.
+
+use roc_can::expr::{Declarations, Expr, IntValue};
+use roc_can::traverse::{walk_expr, Visitor};
+use roc_module::symbol::Symbol;
+use roc_region::all::Region;
+use roc_solve_problem::TypeError;
+use roc_types::subs::{Content, Subs, Variable};
+
+pub fn check_literal_precision(subs: &Subs, decls: &Declarations, problems: &mut Vec<TypeError>) {
+    let mut checker = Checker { subs, problems };
+
+    checker.visit_decls(decls);
+}
+
+struct Checker<'a> {
+    subs: &'a Subs,
+    problems: &'a mut Vec<TypeError>,
+}
+
+impl Visitor for Checker<'_> {
+    fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+        match expr {
+            Expr::Num(num_var, _, value, _) | Expr::Int(num_var, _, _, value, _) => {
+                if let Some(float_type) = float_type(self.subs, *num_var) {
+                    self.check(region, float_type, rounded_int(float_type, value));
+                }
+            }
+            Expr::Float(num_var, _, _, value, _) => {
+                if let Some(float_type) = float_type(self.subs, *num_var) {
+                    self.check(region, float_type, rounded_float(float_type, *value));
+                }
+            }
+            _ => walk_expr(self, expr, var),
+        }
+    }
+}
+
+impl Checker<'_> {
+    fn check(&mut self, region: Region, float_type: Symbol, rounded: Option<String>) {
+        if let Some(rounded) = rounded {
+            self.problems.push(TypeError::ImpreciseNumber {
+                region,
+                float_type,
+                rounded,
+            });
+        }
+    }
+}
+
+/// `F32` or `F64`, if that is what `var` was solved to
+fn float_type(subs: &Subs, var: Variable) -> Option<Symbol> {
+    match subs.get_content_without_compacting(var) {
+        Content::Alias(Symbol::NUM_F32 | Symbol::NUM_BINARY32, _, _, _) => Some(Symbol::NUM_F32),
+        Content::Alias(Symbol::NUM_F64 | Symbol::NUM_BINARY64, _, _, _) => Some(Symbol::NUM_F64),
+        Content::Alias(
+            Symbol::NUM_NUM | Symbol::NUM_FRAC | Symbol::NUM_FLOATINGPOINT,
+            args,
+            _,
+            _,
+        ) if args.len() == 1 => {
+            let arg = args.into_iter().next()?;
+
+            float_type(subs, subs[arg])
+        }
+        _ => None,
+    }
+}
+
+/// What an integer literal becomes as `float_type`, if that is not the number that was written
+fn rounded_int(float_type: Symbol, value: &IntValue) -> Option<String> {
+    let (rounded, exact) = match *value {
+        IntValue::I128(bytes) => {
+            let value = i128::from_ne_bytes(bytes);
+
+            if float_type == Symbol::NUM_F32 {
+                let rounded = value as f32;
+                (rounded.to_string(), rounded as i128 == value)
+            } else {
+                let rounded = value as f64;
+                (rounded.to_string(), rounded as i128 == value)
+            }
+        }
+        IntValue::U128(bytes) => {
+            let value = u128::from_ne_bytes(bytes);
+
+            if float_type == Symbol::NUM_F32 {
+                let rounded = value as f32;
+                (rounded.to_string(), rounded as u128 == value)
+            } else {
+                let rounded = value as f64;
+                (rounded.to_string(), rounded as u128 == value)
+            }
+        }
+    };
+
+    (!exact).then_some(rounded)
+}
+
+/// What a fractional literal becomes as `float_type`, if that has fewer significant digits than
+/// the literal
+fn rounded_float(float_type: Symbol, value: f64) -> Option<String> {
+    if float_type != Symbol::NUM_F32 {
+        return None;
+    }
+
+    // Both print as the shortest decimal that converts back to the same float, so `0.1` is
+    // fine, but `3.14159265358979` becomes `3.1415927`.
+    let rounded = (value as f32).to_string();
+
+    (rounded != value.to_string()).then_some(rounded)
+}
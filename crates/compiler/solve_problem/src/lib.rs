@@ -3,7 +3,7 @@ use std::{path::PathBuf, str::Utf8Error};
 
 use roc_can::expected::{Expected, PExpected};
 use roc_module::{ident::Lowercase, symbol::Symbol};
-use roc_problem::{can::CycleEntry, Severity};
+use roc_problem::{can::CycleEntry, lint::Lint, Severity};
 use roc_region::all::Region;
 
 use roc_types::types::{Category, ErrorType, PatternCategory};
@@ -33,6 +33,19 @@ pub enum TypeError {
     },
     IngestedFileBadUtf8(Box<PathBuf>, Utf8Error),
     IngestedFileUnsupportedType(Box<PathBuf>, ErrorType),
+    /// A number literal that the float type it is used as can't represent exactly, like
+    /// `16777217` used as an `F32`.
+    ImpreciseNumber {
+        region: Region,
+        /// `F32` or `F64`
+        float_type: Symbol,
+        /// The value the literal will have
+        rounded: String,
+    },
+    /// A `Result` thrown away by the `_` of `_ <- task`, so its error is never looked at.
+    DiscardedResult {
+        region: Region,
+    },
 }
 
 impl TypeError {
@@ -54,6 +67,54 @@ impl TypeError {
             TypeError::WrongSpecialization { .. } => RuntimeError,
             TypeError::IngestedFileBadUtf8(..) => Fatal,
             TypeError::IngestedFileUnsupportedType(..) => Fatal,
+            TypeError::ImpreciseNumber { .. } => Warning,
+            TypeError::DiscardedResult { .. } => Warning,
+        }
+    }
+
+    /// The lint this problem belongs to, if users can configure its severity.
+    pub fn lint(&self) -> Option<Lint> {
+        match self {
+            TypeError::Exhaustive(exhtv) => exhtv.lint(),
+            TypeError::ImpreciseNumber { .. } => Some(Lint::PrecisionLoss),
+            TypeError::DiscardedResult { .. } => Some(Lint::DiscardedResult),
+            TypeError::BadExpr(..)
+            | TypeError::BadPattern(..)
+            | TypeError::CircularType(..)
+            | TypeError::CircularDef(_)
+            | TypeError::UnexposedLookup(_)
+            | TypeError::UnfulfilledAbility(_)
+            | TypeError::BadExprMissingAbility(_, _, _, _)
+            | TypeError::BadPatternMissingAbility(_, _, _, _)
+            | TypeError::StructuralSpecialization { .. }
+            | TypeError::WrongSpecialization { .. }
+            | TypeError::IngestedFileBadUtf8(..)
+            | TypeError::IngestedFileUnsupportedType(..) => None,
+        }
+    }
+
+    /// The region of the source this problem is reported at, if it has one.
+    pub fn region(&self) -> Option<Region> {
+        match self {
+            TypeError::BadExpr(region, ..)
+            | TypeError::BadPattern(region, ..)
+            | TypeError::CircularType(region, ..)
+            | TypeError::BadExprMissingAbility(region, ..)
+            | TypeError::BadPatternMissingAbility(region, ..)
+            | TypeError::StructuralSpecialization { region, .. }
+            | TypeError::WrongSpecialization { region, .. }
+            | TypeError::ImpreciseNumber { region, .. }
+            | TypeError::DiscardedResult { region } => Some(*region),
+            TypeError::CircularDef(entries) => entries.first().map(|entry| entry.symbol_region),
+            TypeError::Exhaustive(roc_exhaustive::Error::Incomplete(region, ..)) => Some(*region),
+            TypeError::Exhaustive(
+                roc_exhaustive::Error::Redundant { branch_region, .. }
+                | roc_exhaustive::Error::Unmatchable { branch_region, .. },
+            ) => Some(*branch_region),
+            TypeError::UnexposedLookup(_)
+            | TypeError::UnfulfilledAbility(_)
+            | TypeError::IngestedFileBadUtf8(..)
+            | TypeError::IngestedFileUnsupportedType(..) => None,
         }
     }
 }
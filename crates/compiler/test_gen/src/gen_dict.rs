@@ -550,3 +550,76 @@ fn walk_sum_keys() {
         i64
     );
 }
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn record_key_with_padding() {
+    // The U8 field is followed by padding, which must not affect hashing or equality
+    assert_evals_to!(
+        indoc!(
+            r#"
+            makeKey : U8, U64 -> { kind : U8, id : U64 }
+            makeKey = \kind, id -> { kind, id }
+
+            dict : Dict.Dict { kind : U8, id : U64 } Str
+            dict =
+                Dict.empty {}
+                    |> Dict.insert (makeKey 1 100) "a"
+                    |> Dict.insert (makeKey 2 200) "b"
+                    |> Dict.insert (makeKey 1 100) "c"
+
+            when Dict.get dict { kind: 1, id: 100 } is
+                Ok value -> Str.concat value (Num.toStr (Dict.len dict))
+                Err KeyNotFound -> "not found"
+            "#
+        ),
+        RocStr::from("c2"),
+        RocStr
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn record_key_walk_keys_values() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            dict : Dict.Dict { kind : U8, id : U64 } U64
+            dict =
+                Dict.empty {}
+                    |> Dict.insert { kind: 1, id: 10 } 1
+                    |> Dict.insert { kind: 2, id: 20 } 2
+                    |> Dict.insert { kind: 3, id: 30 } 3
+                    |> Dict.remove { kind: 2, id: 20 }
+
+            keySum = List.walk (Dict.keys dict) 0 \sum, { id } -> sum + id
+            valueSum = List.sum (Dict.values dict)
+            walked = Dict.walk dict 0 \sum, { kind }, value -> sum + Num.toU64 kind * value
+
+            [keySum, valueSum, walked]
+            "#
+        ),
+        RocList::from_slice(&[40, 4, 10]),
+        RocList<u64>
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn tuple_key() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            dict : Dict.Dict (U8, U64) U64
+            dict =
+                Dict.empty {}
+                    |> Dict.insert (1, 10) 100
+                    |> Dict.insert (2, 10) 200
+
+            Dict.get dict (2, 10) |> Result.withDefault 0
+            "#
+        ),
+        200,
+        u64
+    );
+}
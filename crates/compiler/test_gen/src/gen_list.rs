@@ -2910,7 +2910,7 @@ fn cleanup_because_exception() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn list_sort_with() {
     assert_evals_to!(
         "List.sortWith [] Num.compare",
@@ -2930,7 +2930,24 @@ fn list_sort_with() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn list_sort_with_closure() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            target = 10
+
+            List.sortWith [4, 13, 9, 20, 12] \a, b ->
+                Num.compare (Num.absDiff a target) (Num.absDiff b target)
+            "#
+        ),
+        RocList::from_slice(&[9, 12, 13, 4, 20]),
+        RocList<i64>
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn list_sort_asc() {
     assert_evals_to!(
         "List.sortAsc []",
@@ -2945,7 +2962,7 @@ fn list_sort_asc() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn list_sort_desc() {
     assert_evals_to!(
         "List.sortDesc []",
@@ -3809,6 +3826,126 @@ fn list_range_length_overflow() {
     );
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn top_level_constant_table() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            squares : List U64
+            squares = List.map (List.range { start: At 0, end: Before 16 }) \x -> x * x
+
+            sorted : List Str
+            sorted = List.sortWith ["c", "a", "b"] \a, b -> Num.compare (Str.countUtf8Bytes a) (Str.countUtf8Bytes b)
+
+            main =
+                List.sum squares + Num.toU64 (List.len sorted)
+            "#
+        ),
+        1240 + 3,
+        u64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn list_get_set_in_bounds_loop() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            exclaim : List Str, Nat -> List Str
+            exclaim = \list, index ->
+                if index < List.len list then
+                    when List.get list index is
+                        Ok str -> exclaim (List.set list index (Str.concat str "!")) (index + 1)
+                        Err OutOfBounds -> list
+                else
+                    list
+
+            main = exclaim ["a", "b", "c"] 0
+            "#
+        ),
+        RocList::from_slice(&[RocStr::from("a!"), RocStr::from("b!"), RocStr::from("c!"),]),
+        RocList<RocStr>
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn list_get_checked_against_other_list() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            short = [1, 2]
+            long = [1, 2, 3, 4]
+            index = 3
+
+            if index < List.len long then
+                when List.get short index is
+                    Ok _ -> 1
+                    Err OutOfBounds -> 2
+            else
+                3
+            "#
+        ),
+        2,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn list_get_checked_with_lte() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            list = [1, 2, 3]
+            index = List.len list
+
+            if index <= List.len list then
+                when List.get list index is
+                    Ok _ -> 1
+                    Err OutOfBounds -> 2
+            else
+                3
+            "#
+        ),
+        2,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn list_get_after_list_rebound() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            firstAfterDrop : List I64, Nat -> Result I64 [OutOfBounds]
+            firstAfterDrop = \list, index ->
+                if index < List.len list then
+                    shorter = List.dropFirst list
+                    List.get shorter index
+                else
+                    Err OutOfBounds
+
+            main =
+                when firstAfterDrop [1, 2, 3] 2 is
+                    Ok _ -> 1
+                    Err OutOfBounds -> 2
+            "#
+        ),
+        2,
+        i64
+    );
+}
+
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
 mod pattern_match {
     #[cfg(feature = "gen-llvm")]
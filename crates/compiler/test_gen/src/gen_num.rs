@@ -9,8 +9,12 @@ use crate::helpers::wasm::assert_evals_to;
 
 // use crate::assert_wasm_evals_to as assert_evals_to;
 #[allow(unused_imports)]
+use crate::helpers::with_overflow_mode;
+#[allow(unused_imports)]
 use indoc::indoc;
 #[allow(unused_imports)]
+use roc_mono::overflow::OverflowMode;
+#[allow(unused_imports)]
 use roc_std::{RocDec, RocOrder, RocResult};
 
 #[test]
@@ -1539,6 +1543,25 @@ fn tail_call_elimination() {
     );
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn tail_call_loop_invariant_fields() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+                    sum = \config, n, accum ->
+                        when n is
+                            0 -> accum
+                            _ -> sum config (n - 1) (accum + config.step * 2)
+
+                    sum { step: 3, unused: 1 } 1_000 0
+                "#
+        ),
+        6000,
+        i64
+    );
+}
+
 #[test]
 #[cfg(any(feature = "gen-dev"))]
 fn int_negate_dev() {
@@ -1966,6 +1989,68 @@ fn int_negative_mul_overflow() {
     );
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[should_panic(expected = r#"Roc failed with message: "integer addition overflowed!"#)]
+fn int_add_overflow_panic_mode() {
+    with_overflow_mode(OverflowMode::Panic, || {
+        assert_evals_to!("Num.maxI64 + 1", 0, i64);
+    });
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[should_panic(expected = r#"Roc failed with message: "integer subtraction overflowed!"#)]
+fn int_sub_overflow_panic_mode() {
+    with_overflow_mode(OverflowMode::Panic, || {
+        assert_evals_to!("Num.minI64 - 1", 0, i64);
+    });
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[should_panic(expected = r#"Roc failed with message: "integer multiplication overflowed!"#)]
+fn int_mul_overflow_panic_mode() {
+    with_overflow_mode(OverflowMode::Panic, || {
+        assert_evals_to!("Num.maxI64 * 2", 0, i64);
+    });
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn int_add_overflow_wrap_mode() {
+    with_overflow_mode(OverflowMode::Wrap, || {
+        assert_evals_to!("Num.maxI64 + 1", i64::MIN, i64);
+        assert_evals_to!("Num.maxU8 + 2", 1, u8);
+    });
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn int_sub_overflow_wrap_mode() {
+    with_overflow_mode(OverflowMode::Wrap, || {
+        assert_evals_to!("Num.minI64 - 1", i64::MAX, i64);
+        assert_evals_to!("Num.minU8 - 1", u8::MAX, u8);
+    });
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn int_mul_overflow_wrap_mode() {
+    with_overflow_mode(OverflowMode::Wrap, || {
+        assert_evals_to!("Num.maxI64 * 2", -2, i64);
+        assert_evals_to!("Num.maxU8 * 2", 254, u8);
+    });
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn float_add_ignores_wrap_mode() {
+    with_overflow_mode(OverflowMode::Wrap, || {
+        assert_evals_to!("1.5f64 + 2.25", 3.75, f64);
+    });
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
 fn float_positive_mul_overflow() {
@@ -2075,18 +2160,11 @@ fn shift_left_by() {
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn shift_right_by() {
     // Sign Extended Right Shift
-
-    let is_llvm_release_mode = cfg!(feature = "gen-llvm") && !cfg!(debug_assertions);
-
     assert_evals_to!("Num.shiftRightBy 0b0100_0000i8 2", 0b0001_0000i8, i8);
     assert_evals_to!("Num.shiftRightBy 0b1110_0000u8 1", 0b1111_0000u8, u8);
     assert_evals_to!("Num.shiftRightBy 0b1100_0000u8 2", 0b1111_0000u8, u8);
     assert_evals_to!("Num.shiftRightBy 0b0100_0000u8 12", 0b0000_0000u8, u8);
-
-    // LLVM in release mode returns 0 instead of -1 for some reason
-    if !is_llvm_release_mode {
-        assert_evals_to!("Num.shiftRightBy 0b1000_0000u8 12", 0b1111_1111u8, u8);
-    }
+    assert_evals_to!("Num.shiftRightBy 0b1000_0000u8 12", 0b1111_1111u8, u8);
     assert_evals_to!("Num.shiftRightBy 12 0", 12, i64);
     assert_evals_to!("Num.shiftRightBy 12 1", 6, i64);
     assert_evals_to!("Num.shiftRightBy -12 1", -6, i64);
@@ -2105,10 +2183,7 @@ fn shift_right_by() {
     assert_evals_to!("Num.shiftRightBy 12i8 1", 6, i8);
     assert_evals_to!("Num.shiftRightBy -12i8 1", -6, i8);
     assert_evals_to!("Num.shiftRightBy 12i8 8", 0, i8);
-
-    if !is_llvm_release_mode {
-        assert_evals_to!("Num.shiftRightBy -12i8 8", -1, i8);
-    }
+    assert_evals_to!("Num.shiftRightBy -12i8 8", -1, i8);
 }
 
 #[test]
@@ -2155,6 +2230,61 @@ fn shift_right_cast_i8() {
     );
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn shift_by_bit_width_or_more() {
+    assert_evals_to!("Num.shiftLeftBy 1u8 8", 0, u8);
+    assert_evals_to!("Num.shiftLeftBy 1u16 200", 0, u16);
+    assert_evals_to!("Num.shiftLeftBy 1u32 32", 0, u32);
+    assert_evals_to!("Num.shiftLeftBy 1u64 64", 0, u64);
+    assert_evals_to!("Num.shiftLeftBy 1u128 128", 0, u128);
+
+    assert_evals_to!("Num.shiftRightZfBy -1i32 32", 0, i32);
+    assert_evals_to!("Num.shiftRightZfBy -1i64 255", 0, i64);
+    assert_evals_to!("Num.shiftRightZfBy -1i128 128", 0, i128);
+
+    assert_evals_to!("Num.shiftRightBy -8i32 32", -1, i32);
+    assert_evals_to!("Num.shiftRightBy 8i32 32", 0, i32);
+    assert_evals_to!("Num.shiftRightBy -8i64 100", -1, i64);
+    assert_evals_to!(
+        "Num.shiftRightBy 0x8000_0000_0000_0000u64 64",
+        u64::MAX,
+        u64
+    );
+    assert_evals_to!("Num.shiftRightBy -8i128 128", -1, i128);
+    assert_evals_to!("Num.shiftRightBy 8i128 255", 0, i128);
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn shift_128() {
+    assert_evals_to!("Num.shiftLeftBy 1u128 100", 1u128 << 100, u128);
+    assert_evals_to!("Num.shiftLeftBy 3i128 126", 3i128 << 126, i128);
+    assert_evals_to!("Num.shiftRightBy -1024i128 3", -128, i128);
+    assert_evals_to!(
+        "Num.shiftRightBy (Num.shiftLeftBy 1u128 127) 64",
+        !0u128 << 63,
+        u128
+    );
+    assert_evals_to!("Num.shiftRightZfBy -1i128 127", 1, i128);
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn bitwise_128() {
+    assert_evals_to!(
+        "Num.bitwiseAnd 0xffff_0000_0000_0000_0000_0000_0000_ffffu128 0x0ff0_0000_0000_0000_0000_0000_0000_0ff0u128",
+        0x0ff0_0000_0000_0000_0000_0000_0000_0ff0u128,
+        u128
+    );
+    assert_evals_to!(
+        "Num.bitwiseOr 0xf000_0000_0000_0000_0000_0000_0000_0000u128 1u128",
+        0xf000_0000_0000_0000_0000_0000_0000_0001u128,
+        u128
+    );
+    assert_evals_to!("Num.bitwiseXor -1i128 5i128", -6, i128);
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn min_i128() {
@@ -2548,6 +2678,30 @@ to_int_checked_tests! {
     )
 }
 
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn to_f32_checked() {
+    assert_evals_to!("Result.withDefault (Num.toF32Checked 1.5f64) 23", 1.5, f32);
+    assert_evals_to!("Result.withDefault (Num.toF32Checked 15u128) 23", 15.0, f32);
+    assert_evals_to!(
+        "Result.withDefault (Num.toF32Checked 1e300f64) 23",
+        23.0,
+        f32
+    );
+    assert_evals_to!(
+        "Result.withDefault (Num.toF32Checked -1e300f64) 23",
+        23.0,
+        f32
+    );
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn to_f64_checked() {
+    assert_evals_to!("Result.withDefault (Num.toF64Checked 1.5f32) 23", 1.5, f64);
+    assert_evals_to!("Result.withDefault (Num.toF64Checked -15i8) 23", -15.0, f64);
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn is_multiple_of_signed() {
@@ -3152,15 +3306,17 @@ fn num_to_str_f32() {
     assert_evals_to!(r#"Num.toStr 1.75f32"#, RocStr::from("1.75"), RocStr);
     assert_evals_to!(r#"Num.toStr 10.75f32"#, RocStr::from("10.75"), RocStr);
 
+    assert_evals_to!(r#"Num.toStr 0.1f32"#, RocStr::from("0.1"), RocStr);
+
     assert_evals_to!(
         r#"Num.toStr Num.maxF32"#,
-        RocStr::from("340282346638528860000000000000000000000"),
+        RocStr::from("340282350000000000000000000000000000000"),
         RocStr
     );
 
     assert_evals_to!(
         r#"Num.toStr Num.minF32"#,
-        RocStr::from("-340282346638528860000000000000000000000"),
+        RocStr::from("-340282350000000000000000000000000000000"),
         RocStr
     );
 }
@@ -3189,6 +3345,39 @@ fn num_to_str_f64() {
     );
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn num_to_str_float_shortest_round_trip() {
+    use roc_std::RocStr;
+
+    assert_evals_to!(
+        r#"Num.toStr (0.1f64 + 0.2)"#,
+        RocStr::from("0.30000000000000004"),
+        RocStr
+    );
+    assert_evals_to!(
+        r#"Num.toStr 0.0000001f64"#,
+        RocStr::from("0.0000001"),
+        RocStr
+    );
+    assert_evals_to!(
+        r#"Num.toStr 123456789.125f64"#,
+        RocStr::from("123456789.125"),
+        RocStr
+    );
+    assert_evals_to!(r#"Num.toStr 0.3f32"#, RocStr::from("0.3"), RocStr);
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn num_to_str_float_non_finite() {
+    use roc_std::RocStr;
+
+    assert_evals_to!(r#"Num.toStr (1f64 / 0)"#, RocStr::from("∞"), RocStr);
+    assert_evals_to!(r#"Num.toStr (-1f64 / 0)"#, RocStr::from("-∞"), RocStr);
+    assert_evals_to!(r#"Num.toStr (0f32 / 0)"#, RocStr::from("NaN"), RocStr);
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
 fn num_to_str_dec() {
@@ -1130,3 +1130,31 @@ fn pass_record_of_u8s() {
         bool
     )
 }
+
+#[test]
+#[cfg(feature = "gen-wasm")]
+fn exposed_function_returns_large_record() {
+    use roc_wasm_module::Value;
+
+    // The record is too big to be a Wasm return value, so the host passes a pointer for it.
+    // Building it in a helper puts it on the shadow stack before it gets there.
+    let actual: (i64, i64, i64, i64) = crate::helpers::wasm::call_exposed_generic_help(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            makeRecord = \x -> { a: x, b: x + 1, c: x * 2, d: 0 }
+
+            main : I64 -> { a : I64, b : I64, c : I64, d : I64 }
+            main = \x ->
+                r = makeRecord x
+
+                { r & d: r.a * r.c }
+            "#
+        ),
+        &[Value::I64(7)],
+    )
+    .unwrap();
+
+    assert_eq!(actual, (7, 8, 14, 98));
+}
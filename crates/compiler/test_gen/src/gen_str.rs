@@ -1107,6 +1107,89 @@ fn str_repeat_zero_times() {
     assert_evals_to!(indoc!(r#"Str.repeat "Roc" 0"#), RocStr::from(""), RocStr);
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+fn str_replace_each_same_length() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            Str.replaceEach "more/than/23/characters/now" "/" "_"
+            |> Result.withDefault ""
+            "#
+        ),
+        RocStr::from("more_than_23_characters_now"),
+        RocStr
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+fn str_replace_each_shorter_and_longer() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            Str.replaceEach "more, than, 23, characters, now" ", " " "
+            |> Result.try \str -> Str.replaceEach str " " " -- "
+            |> Result.withDefault ""
+            "#
+        ),
+        RocStr::from("more -- than -- 23 -- characters -- now"),
+        RocStr
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+fn str_replace_each_not_found() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            Str.replaceEach "no slashes here" "/" "_"
+            |> Result.withDefault "not found"
+            "#
+        ),
+        RocStr::from("not found"),
+        RocStr
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+fn str_split_on_big_str() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            Str.splitOn "01234567789abcdefghi 3ch 01234567789abcdefghi" "3ch"
+            "#
+        ),
+        RocList::from_slice(&[
+            RocStr::from("01234567789abcdefghi "),
+            RocStr::from(" 01234567789abcdefghi")
+        ]),
+        RocList<RocStr>
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+fn str_split_on_string_used_again() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            str = "01234567789abcdefghi,01234567789abcdefghi"
+
+            List.append (Str.splitOn str ",") str
+            "#
+        ),
+        RocList::from_slice(&[
+            RocStr::from("01234567789abcdefghi"),
+            RocStr::from("01234567789abcdefghi"),
+            RocStr::from("01234567789abcdefghi,01234567789abcdefghi")
+        ]),
+        RocList<RocStr>
+    );
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
 fn str_trim_empty_string() {
@@ -1590,6 +1673,78 @@ fn str_to_f32() {
     );
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm"))]
+fn str_to_f64_correctly_rounded() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            when Str.toF64 "0.1000000000000000055511151231257827021181583404541015625" is
+                Ok n -> n
+                Err _ -> 0
+
+            "#
+        ),
+        0.1,
+        f64
+    );
+
+    assert_evals_to!(
+        indoc!(
+            r#"
+            when Str.toF64 "2.2250738585072011e-308" is
+                Ok n -> n
+                Err _ -> 0
+
+            "#
+        ),
+        2.2250738585072011e-308,
+        f64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm"))]
+fn str_to_float_round_trip() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            when Str.toF64 (Num.toStr (0.1f64 + 0.2)) is
+                Ok n -> n
+                Err _ -> 0
+
+            "#
+        ),
+        0.1 + 0.2,
+        f64
+    );
+
+    assert_evals_to!(
+        indoc!(
+            r#"
+            when Str.toF32 (Num.toStr (1f32 / 3)) is
+                Ok n -> n
+                Err _ -> 0
+
+            "#
+        ),
+        1.0 / 3.0,
+        f32
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm"))]
+fn str_to_float_invalid() {
+    assert_evals_to!(r#"Result.isErr (Str.toF64 "")"#, true, bool);
+    assert_evals_to!(r#"Result.isErr (Str.toF64 "1e")"#, true, bool);
+    assert_evals_to!(r#"Result.isErr (Str.toF64 "1.2.3")"#, true, bool);
+    assert_evals_to!(r#"Result.isErr (Str.toF64 " 1")"#, true, bool);
+    assert_evals_to!(r#"Result.isErr (Str.toF64 "1e400")"#, true, bool);
+    assert_evals_to!(r#"Result.isErr (Str.toF32 "3.5e38")"#, true, bool);
+    assert_evals_to!(r#"Result.isErr (Str.toF64 "-.5e-3")"#, false, bool);
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm"))]
 fn str_to_dec() {
@@ -1883,6 +2038,26 @@ fn str_walk_utf8() {
     );
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+fn str_walk_utf8_captures() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            offset = 1u64
+
+            Str.walkUtf8 "a string that is too long to be small" 0 \sum, byte ->
+                sum + Num.toU64 byte + offset
+            "#
+        ),
+        b"a string that is too long to be small"
+            .iter()
+            .map(|byte| *byte as u64 + 1)
+            .sum::<u64>(),
+        u64
+    );
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
 fn str_walk_utf8_with_index() {
@@ -1002,7 +1002,7 @@ fn result_never() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn nested_recursive_literal() {
     assert_evals_to!(
         indoc!(
@@ -1022,7 +1022,7 @@ fn nested_recursive_literal() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn newtype_wrapper() {
     assert_evals_to!(
         indoc!(
@@ -1141,7 +1141,7 @@ fn tag_must_be_its_own_type() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn recursive_tag_union_into_flat_tag_union() {
     // Comprehensive test for correctness in cli/tests/repl_eval
     assert_evals_to!(
@@ -1593,7 +1593,7 @@ fn issue_2900_unreachable_pattern() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn issue_3261_non_nullable_unwrapped_recursive_union_at_index() {
     assert_evals_to!(
         indoc!(
@@ -2094,7 +2094,7 @@ fn nullable_wrapped_with_non_nullable_singleton_tags() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn nullable_wrapped_with_nullable_not_last_index() {
     assert_evals_to!(
         indoc!(
@@ -2189,3 +2189,35 @@ fn issue_5162_recast_nested_nullable_unwrapped_layout() {
         bool
     );
 }
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn build_target_os() {
+    let expected = if cfg!(any(feature = "gen-wasm", feature = "gen-llvm-wasm")) {
+        "Wasi"
+    } else if cfg!(windows) {
+        "Windows"
+    } else {
+        "Unix"
+    };
+
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test"
+                imports [Target]
+                provides [main] to "./platform"
+
+            main =
+                isWasm = Target.targetOs == Wasi
+
+                when Target.targetOs is
+                    Windows -> "Windows"
+                    Unix if !isWasm -> "Unix"
+                    _ -> "Wasi"
+            "#
+        ),
+        RocStr::from(expected),
+        RocStr
+    );
+}
@@ -185,6 +185,27 @@ fn when_tuple_with_guard_pattern() {
     );
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn when_on_multiple_conditions() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+                f : Result I64 I64, Result I64 I64 -> I64
+                f = \a, b ->
+                    when a, b is
+                        Ok x, Ok y if x > y -> x - y
+                        Ok x, Ok y -> y - x
+                        Err e, _ | _, Err e -> e
+
+                f (Ok 5) (Ok 2) + 10 * f (Ok 2) (Ok 6) + 100 * f (Ok 1) (Err 7) + 1000 * f (Err 3) (Err 4)
+                "#
+        ),
+        3743,
+        i64
+    );
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
 fn let_with_tuple_pattern() {
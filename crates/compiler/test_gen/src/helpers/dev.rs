@@ -58,6 +58,12 @@ pub fn helper(
         palette: roc_reporting::report::DEFAULT_PALETTE,
         threading: Threading::Single,
         exec_mode: ExecutionMode::Executable,
+        max_specializations: None,
+        coverage: false,
+        fold_constants: false,
+        hoist_loop_invariants: true,
+        strip_expects: false,
+        cancelled: None,
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -73,13 +79,20 @@ pub fn helper(
     use roc_load::MonomorphizedModule;
     let MonomorphizedModule {
         module_id,
-        procedures,
+        mut procedures,
         mut interns,
         exposed_to_host,
         mut layout_interner,
         ..
     } = loaded;
 
+    roc_mono::overflow::apply_overflow_mode(
+        arena,
+        &layout_interner,
+        &mut procedures,
+        crate::helpers::overflow_mode(),
+    );
+
     // You can comment and uncomment this block out to get more useful information
     // while you're working on the dev backend!
     {
@@ -74,6 +74,12 @@ fn create_llvm_module<'a>(
         palette: DEFAULT_PALETTE,
         threading: Threading::Single,
         exec_mode: ExecutionMode::Executable,
+        max_specializations: None,
+        coverage: false,
+        fold_constants: config.opt_level == OptLevel::Optimize,
+        hoist_loop_invariants: config.opt_level == OptLevel::Optimize,
+        strip_expects: false,
+        cancelled: None,
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -97,12 +103,20 @@ fn create_llvm_module<'a>(
 
     use roc_load::MonomorphizedModule;
     let MonomorphizedModule {
-        procedures,
-        interns,
+        module_id,
+        mut procedures,
+        mut interns,
         mut layout_interner,
         ..
     } = loaded;
 
+    roc_mono::overflow::apply_overflow_mode(
+        arena,
+        &layout_interner,
+        &mut procedures,
+        crate::helpers::overflow_mode(),
+    );
+
     let mut lines = Vec::new();
     // errors whose reporting we delay (so we can see that code gen generates runtime errors)
     let mut delayed_errors = Vec::new();
@@ -213,6 +227,15 @@ fn create_llvm_module<'a>(
         }
     }
 
+    let procedures = roc_gen_llvm::llvm::build::expand_refcounting_procs(
+        arena,
+        target_info,
+        module_id,
+        &mut interns,
+        &mut layout_interner,
+        procedures,
+    );
+
     // Compile and add all the Procs before adding main
     let env = roc_gen_llvm::llvm::build::Env {
         arena,
@@ -226,6 +249,11 @@ fn create_llvm_module<'a>(
         mode: config.mode,
         // important! we don't want any procedures to get the C calling convention
         exposed_to_host: MutSet::default(),
+        heap_profile: false,
+        stack_limit_checks: false,
+        arena_allocation: false,
+        profile_hints: None,
+        func_spec_numbers: Default::default(),
     };
 
     // strip Zig debug stuff
@@ -613,6 +641,7 @@ where
         Err((msg, tag)) => match tag {
             CrashTag::Roc => panic!(r#"Roc failed with message: "{}""#, msg),
             CrashTag::User => panic!(r#"User crash with message: "{}""#, msg),
+            CrashTag::OutOfMemory => panic!(r#"Out of memory with message: "{}""#, msg),
         },
     }
 }
@@ -1,5 +1,8 @@
 extern crate bumpalo;
 
+use roc_mono::overflow::OverflowMode;
+use std::cell::Cell;
+
 pub mod platform_functions;
 
 #[cfg(feature = "gen-dev")]
@@ -37,6 +40,27 @@ pub(crate) fn save_wasm_file(app_module_bytes: &[u8], build_dir_hash: u64) {
     );
 }
 
+thread_local! {
+    static OVERFLOW_MODE: Cell<OverflowMode> = Cell::new(OverflowMode::Panic);
+}
+
+/// Compile the apps in `run_test` so that integer `+`, `-` and `*` do what `mode` says
+/// when they overflow.
+#[allow(dead_code)]
+pub fn with_overflow_mode<F>(mode: OverflowMode, run_test: F)
+where
+    F: FnOnce(),
+{
+    let previous = OVERFLOW_MODE.with(|cell| cell.replace(mode));
+    run_test();
+    OVERFLOW_MODE.with(|cell| cell.set(previous));
+}
+
+#[allow(dead_code)]
+pub(crate) fn overflow_mode() -> OverflowMode {
+    OVERFLOW_MODE.with(|cell| cell.get())
+}
+
 /// Used in the with_larger_debug_stack() function, for tests that otherwise
 /// run out of stack space in debug builds (but don't in --release builds)
 #[allow(dead_code)]
@@ -3,6 +3,7 @@ use crate::helpers::from_wasm32_memory::FromWasm32Memory;
 use bumpalo::Bump;
 use roc_collections::all::MutSet;
 use roc_gen_wasm::wasm32_result::Wasm32Result;
+use roc_gen_wasm::wasm32_sized::Wasm32Sized;
 use roc_gen_wasm::DEBUG_SETTINGS;
 use roc_load::{ExecutionMode, LoadConfig, Threading};
 use roc_packaging::cache::RocCacheDir;
@@ -15,6 +16,11 @@ use std::path::PathBuf;
 
 const TEST_WRAPPER_NAME: &str = "test_wrapper";
 const INIT_REFCOUNT_NAME: &str = "init_refcount_test";
+const ALLOC_RESULT_NAME: &str = "alloc_test_result";
+const MAIN_EXPOSED_GENERIC_NAME: &str = "roc__main_1_exposed_generic";
+
+/// Adds a wrapper that calls `main`, see [Wasm32Result]
+type InsertWrapper<'a> = fn(&'a Bump, &mut WasmModule<'a>, &'static str, u32);
 
 macro_rules! host_bytes_path {
     () => {
@@ -50,13 +56,17 @@ fn write_final_wasm() -> bool {
 pub fn compile_to_wasm_bytes<'a, T: Wasm32Result>(
     arena: &'a bumpalo::Bump,
     src: &str,
-    test_wrapper_type_info: PhantomData<T>,
+    _test_wrapper_type_info: PhantomData<T>,
 ) -> Vec<u8> {
     let platform_bytes = include_bytes!(host_bytes_path!());
     println!("Loading test host {}", host_bytes_path!());
 
-    let compiled_bytes =
-        compile_roc_to_wasm_bytes(arena, platform_bytes, src, test_wrapper_type_info);
+    let compiled_bytes = compile_roc_to_wasm_bytes(
+        arena,
+        platform_bytes,
+        src,
+        Some(T::insert_wrapper as InsertWrapper<'a>),
+    );
 
     if write_final_wasm() {
         let build_dir_hash = crate::helpers::src_hash(src);
@@ -66,11 +76,11 @@ pub fn compile_to_wasm_bytes<'a, T: Wasm32Result>(
     compiled_bytes
 }
 
-fn compile_roc_to_wasm_bytes<'a, T: Wasm32Result>(
+fn compile_roc_to_wasm_bytes<'a>(
     arena: &'a bumpalo::Bump,
     host_bytes: &[u8],
     src: &str,
-    _test_wrapper_type_info: PhantomData<T>,
+    insert_test_wrapper: Option<InsertWrapper<'a>>,
 ) -> Vec<u8> {
     let filename = PathBuf::from("Test.roc");
     let src_dir = PathBuf::from("fake/test/path");
@@ -92,6 +102,12 @@ fn compile_roc_to_wasm_bytes<'a, T: Wasm32Result>(
         palette: DEFAULT_PALETTE_HTML,
         threading: Threading::Single,
         exec_mode: ExecutionMode::Executable,
+        max_specializations: None,
+        coverage: false,
+        fold_constants: false,
+        hoist_loop_invariants: true,
+        strip_expects: false,
+        cancelled: None,
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -107,13 +123,20 @@ fn compile_roc_to_wasm_bytes<'a, T: Wasm32Result>(
     use roc_load::MonomorphizedModule;
     let MonomorphizedModule {
         module_id,
-        procedures,
+        mut procedures,
         mut interns,
         exposed_to_host,
         mut layout_interner,
         ..
     } = loaded;
 
+    roc_mono::overflow::apply_overflow_mode(
+        arena,
+        &layout_interner,
+        &mut procedures,
+        crate::helpers::overflow_mode(),
+    );
+
     debug_assert_eq!(exposed_to_host.top_level_values.len(), 1);
 
     let exposed_to_host = exposed_to_host
@@ -127,6 +150,8 @@ fn compile_roc_to_wasm_bytes<'a, T: Wasm32Result>(
         module_id,
         exposed_to_host,
         stack_bytes: roc_gen_wasm::Env::DEFAULT_STACK_BYTES,
+        features: roc_gen_wasm::WasmFeatures::default(),
+        wasi_hooks: false,
     };
 
     let host_module = roc_gen_wasm::parse_host(env.arena, host_bytes).unwrap_or_else(|e| {
@@ -146,23 +171,27 @@ fn compile_roc_to_wasm_bytes<'a, T: Wasm32Result>(
         procedures,
     );
 
-    T::insert_wrapper(arena, &mut module, TEST_WRAPPER_NAME, main_fn_index);
-    called_fns.push(true);
-
-    // Export the initialiser function for refcount tests
-    let init_refcount_idx = module
-        .names
-        .function_names
-        .iter()
-        .filter(|(_, name)| *name == INIT_REFCOUNT_NAME)
-        .map(|(i, _)| *i)
-        .next()
-        .unwrap();
-    module.export.append(Export {
-        name: INIT_REFCOUNT_NAME,
-        ty: ExportType::Func,
-        index: init_refcount_idx,
-    });
+    if let Some(insert_wrapper) = insert_test_wrapper {
+        insert_wrapper(arena, &mut module, TEST_WRAPPER_NAME, main_fn_index);
+        called_fns.push(true);
+    }
+
+    // Export the test host's functions that the tests call directly
+    for host_fn_name in [INIT_REFCOUNT_NAME, ALLOC_RESULT_NAME] {
+        let host_fn_idx = module
+            .names
+            .function_names
+            .iter()
+            .filter(|(_, name)| *name == host_fn_name)
+            .map(|(i, _)| *i)
+            .next()
+            .unwrap();
+        module.export.append(Export {
+            name: host_fn_name,
+            ty: ExportType::Func,
+            index: host_fn_idx,
+        });
+    }
 
     module.eliminate_dead_code(env.arena, called_fns);
 
@@ -184,6 +213,51 @@ where
     run_wasm_test_bytes::<T>(TEST_WRAPPER_NAME, wasm_bytes)
 }
 
+/// Compile `src`, an app that provides a `main` function, and call it the way a host would:
+/// through its `_exposed_generic` wrapper, with `arguments` and a pointer to write the result to.
+#[allow(dead_code)]
+pub fn call_exposed_generic_help<T>(src: &str, arguments: &[Value]) -> Result<T, String>
+where
+    T: FromWasm32Memory,
+{
+    let arena = Bump::new();
+    let platform_bytes = include_bytes!(host_bytes_path!());
+    let wasm_bytes = compile_roc_to_wasm_bytes(&arena, platform_bytes, src, None);
+
+    let require_relocatable = false;
+    let module = WasmModule::preload(&arena, &wasm_bytes, require_relocatable)
+        .map_err(|e| format!("{:?}", e))?;
+
+    let dispatcher = TestDispatcher {
+        wasi: wasi::WasiDispatcher::default(),
+    };
+    let is_debug_mode = roc_debug_flags::dbg_set!(roc_debug_flags::ROC_LOG_WASM_INTERP);
+    let mut inst = Instance::for_module(&arena, &module, dispatcher, is_debug_mode)?;
+
+    let result_size = <T as Wasm32Sized>::SIZE_OF_WASM as i32;
+    let result_addr = inst
+        .call_export(ALLOC_RESULT_NAME, [Value::I32(result_size)])?
+        .ok_or_else(|| format!("No return address from {}", ALLOC_RESULT_NAME))?
+        .expect_i32()
+        .map_err(|type_err| format!("{:?}", type_err))?;
+
+    let mut wasm_arguments = arguments.to_vec();
+    wasm_arguments.push(Value::I32(result_addr));
+
+    let return_value = inst.call_export(MAIN_EXPOSED_GENERIC_NAME, wasm_arguments)?;
+    if return_value.is_some() {
+        return Err(format!(
+            "{} should write its result to memory, but it returned {:?}",
+            MAIN_EXPOSED_GENERIC_NAME, return_value
+        ));
+    }
+
+    Ok(<T as FromWasm32Memory>::decode(
+        &inst.memory,
+        result_addr as u32,
+    ))
+}
+
 struct TestDispatcher<'a> {
     wasi: WasiDispatcher<'a>,
 }
@@ -205,6 +279,7 @@ impl<'a> ImportDispatcher for TestDispatcher<'a> {
             let msg = match tag {
                 0 => format!(r#"Roc failed with message: "{}""#, roc_msg),
                 1 => format!(r#"User crash with message: "{}""#, roc_msg),
+                2 => format!(r#"Out of memory with message: "{}""#, roc_msg),
                 tag => format!(r#"Got an invald panic tag: "{}""#, tag),
             };
             panic!("{}", msg)
@@ -31,6 +31,12 @@ Vector *init_refcount_test(size_t capacity)
     return rc_pointers;
 }
 
+// Tests that call the app the way a host would pass this as the pointer to write the result to
+void *alloc_test_result(size_t size)
+{
+    return malloc(size);
+}
+
 #if ENABLE_PRINTF
 #define ASSERT(condition, format, ...)                       \
     if (!(condition))                                        \
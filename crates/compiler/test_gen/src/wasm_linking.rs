@@ -1,7 +1,7 @@
 #![cfg(feature = "gen-wasm")]
 
 use bumpalo::Bump;
-use roc_gen_wasm::Env;
+use roc_gen_wasm::{Env, WasmFeatures};
 use roc_target::TargetInfo;
 use std::fs;
 use std::process::Command;
@@ -160,6 +160,7 @@ impl<'a> BackendInputs<'a> {
             module_id,
             exposed_to_host,
             stack_bytes: Env::DEFAULT_STACK_BYTES,
+            features: WasmFeatures::default(),
         };
 
         // Identifier stuff for the backend
@@ -1327,3 +1327,80 @@ fn str_walk_scalars() {
         RocList<char>
     );
 }
+
+#[test]
+fn str_walk_utf8() {
+    assert_evals_to!(
+        // Reverse the bytes
+        indoc!(
+            r#"
+            Str.walkUtf8 "abcd" [] (\list, byte -> List.prepend list byte)
+            "#
+        ),
+        RocList::from_slice(&[b'd', b'c', b'b', b'a']),
+        RocList<u8>
+    );
+}
+
+#[test]
+fn str_walk_utf8_captures() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            offset = 1u64
+
+            Str.walkUtf8 "a string that is too long to be small" 0 \sum, byte ->
+                sum + Num.toU64 byte + offset
+            "#
+        ),
+        b"a string that is too long to be small"
+            .iter()
+            .map(|byte| *byte as u64 + 1)
+            .sum::<u64>(),
+        u64
+    );
+}
+
+#[test]
+fn str_split_on() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            Str.splitOn "01234567789abcdefghi?01234567789abcdefghi" "?"
+            "#
+        ),
+        RocList::from_slice(&[
+            RocStr::from("01234567789abcdefghi"),
+            RocStr::from("01234567789abcdefghi")
+        ]),
+        RocList<RocStr>
+    );
+}
+
+#[test]
+fn str_split_on_small_str() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            Str.splitOn "J!J!J" "!"
+            "#
+        ),
+        RocList::from_slice(&[RocStr::from("J"), RocStr::from("J"), RocStr::from("J")]),
+        RocList<RocStr>
+    );
+}
+
+#[test]
+fn str_replace_each() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            Str.replaceEach "more, than, 23, characters, now" ", " " "
+            |> Result.try \str -> Str.replaceEach str " " " -- "
+            |> Result.withDefault ""
+            "#
+        ),
+        RocStr::from("more -- than -- 23 -- characters -- now"),
+        RocStr
+    );
+}
@@ -19,7 +19,9 @@ use roc_load::ExecutionMode;
 use roc_load::LoadConfig;
 use roc_load::LoadMonomorphizedError;
 use roc_load::Threading;
+use roc_module::symbol::IdentIds;
 use roc_module::symbol::Interns;
+use roc_module::symbol::ModuleId;
 use roc_module::symbol::Symbol;
 use roc_mono::ir::Proc;
 use roc_mono::ir::ProcLayout;
@@ -76,7 +78,16 @@ fn promote_expr_to_module(src: &str) -> String {
     buffer
 }
 
-fn compiles_to_ir(test_name: &str, src: &str, mode: &str, allow_type_errors: bool, no_check: bool) {
+fn compiles_to_ir(
+    test_name: &str,
+    src: &str,
+    mode: &str,
+    allow_type_errors: bool,
+    no_check: bool,
+    strip_expects: bool,
+    arena_allocation: bool,
+    fold_constants: bool,
+) {
     use roc_packaging::cache::RocCacheDir;
     use std::path::PathBuf;
 
@@ -108,6 +119,12 @@ fn compiles_to_ir(test_name: &str, src: &str, mode: &str, allow_type_errors: boo
         render: roc_reporting::report::RenderTarget::Generic,
         palette: roc_reporting::report::DEFAULT_PALETTE,
         exec_mode,
+        max_specializations: None,
+        coverage: false,
+        fold_constants,
+        hoist_loop_invariants: false,
+        strip_expects,
+        cancelled: None,
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -132,10 +149,10 @@ fn compiles_to_ir(test_name: &str, src: &str, mode: &str, allow_type_errors: boo
     use roc_load::MonomorphizedModule;
     let MonomorphizedModule {
         module_id: home,
-        procedures,
+        mut procedures,
         exposed_to_host,
         mut layout_interner,
-        interns,
+        mut interns,
         ..
     } = loaded;
 
@@ -150,11 +167,111 @@ fn compiles_to_ir(test_name: &str, src: &str, mode: &str, allow_type_errors: boo
 
     let main_fn_symbol = exposed_to_host.top_level_values.keys().copied().next();
 
+    if arena_allocation {
+        roc_mono::arena_allocation::skip_recursive_decrements(arena, &mut procedures);
+    }
+
     if !no_check {
         check_procedures(arena, &interns, &mut layout_interner, &procedures);
     }
 
-    verify_procedures(test_name, layout_interner, procedures, main_fn_symbol);
+    let ident_ids = interns.all_ident_ids.get_mut(&home).unwrap();
+    let helper_procs = helper_procs(arena, home, ident_ids, &mut layout_interner, &procedures);
+
+    verify_procedures(
+        test_name,
+        layout_interner,
+        procedures,
+        helper_procs,
+        main_fn_symbol,
+    );
+}
+
+/// The refcount and equality helper procs that the dev backends generate for these procedures.
+fn helper_procs<'a>(
+    arena: &'a Bump,
+    home: ModuleId,
+    ident_ids: &mut IdentIds,
+    interner: &mut STLayoutInterner<'a>,
+    procedures: &MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) -> Vec<Proc<'a>> {
+    use roc_module::low_level::LowLevel;
+    use roc_mono::code_gen_help::CodeGenHelp;
+    use roc_mono::ir::{Call, CallType, Expr, Stmt};
+    use roc_mono::layout::{Builtin, LayoutInterner, LayoutRepr};
+
+    let mut helper_gen = CodeGenHelp::new(arena, TARGET_INFO, home);
+
+    for proc in procedures.values() {
+        let mut layouts: MutMap<Symbol, _> = proc.args.iter().map(|(l, s)| (*s, *l)).collect();
+        let mut stack = vec![&proc.body];
+
+        while let Some(stmt) = stack.pop() {
+            match stmt {
+                Stmt::Let(symbol, expr, layout, cont) => {
+                    layouts.insert(*symbol, *layout);
+
+                    if let Expr::Call(Call {
+                        call_type:
+                            CallType::LowLevel {
+                                op: LowLevel::Eq | LowLevel::NotEq,
+                                ..
+                            },
+                        arguments,
+                    }) = expr
+                    {
+                        let arg_layout = layouts[&arguments[0]];
+
+                        // the backends compare these inline
+                        let inline = match interner.get(arg_layout).repr {
+                            LayoutRepr::Builtin(builtin) => !matches!(builtin, Builtin::List(_)),
+                            LayoutRepr::Struct { field_layouts, .. } => field_layouts.is_empty(),
+                            _ => false,
+                        };
+
+                        if !inline {
+                            helper_gen.call_specialized_equals(
+                                ident_ids, interner, arg_layout, arguments,
+                            );
+                        }
+                    }
+
+                    stack.push(cont);
+                }
+                Stmt::Refcounting(modify, cont) => {
+                    let layout = layouts[&modify.get_symbol()];
+                    helper_gen.expand_refcount_stmt(ident_ids, interner, layout, modify, cont);
+
+                    stack.push(cont);
+                }
+                Stmt::Switch {
+                    branches,
+                    default_branch,
+                    ..
+                } => {
+                    stack.extend(branches.iter().map(|(_, _, branch)| branch));
+                    stack.push(default_branch.1);
+                }
+                Stmt::Join {
+                    parameters,
+                    body,
+                    remainder,
+                    ..
+                } => {
+                    layouts.extend(parameters.iter().map(|p| (p.symbol, p.layout)));
+
+                    stack.push(body);
+                    stack.push(remainder);
+                }
+                Stmt::Expect { remainder, .. }
+                | Stmt::ExpectFx { remainder, .. }
+                | Stmt::Dbg { remainder, .. } => stack.push(remainder),
+                Stmt::Ret(_) | Stmt::Jump(..) | Stmt::Crash(..) => {}
+            }
+        }
+    }
+
+    helper_gen.take_procs().into_iter().collect()
 }
 
 fn check_procedures<'a>(
@@ -176,10 +293,12 @@ fn verify_procedures<'a>(
     test_name: &str,
     interner: STLayoutInterner<'a>,
     procedures: MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+    helper_procs: Vec<Proc<'a>>,
     opt_main_fn_symbol: Option<Symbol>,
 ) {
     let mut procs_string = procedures
         .values()
+        .chain(&helper_procs)
         .map(|proc| proc.to_pretty(&interner, 200, false))
         .collect::<Vec<_>>();
 
@@ -458,6 +577,51 @@ fn if_multi_branch() {
     "#
 }
 
+#[mono_test]
+fn if_multi_branch_on_int_literals() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        describe : U8 -> Str
+        describe = \n ->
+            word =
+                if n == 1 then
+                    "one"
+                else if 2 == n then
+                    "two"
+                else if n == 1 then
+                    "one again"
+                else
+                    "many"
+
+            Str.concat word "!"
+
+        main = describe 2
+        "#
+    )
+}
+
+#[mono_test]
+fn if_multi_branch_on_different_ints() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        describe : U8, U8 -> Str
+        describe = \n, m ->
+            if n == 1 then
+                "one"
+            else if m == 2 then
+                "two"
+            else
+                "many"
+
+        main = describe 2 3
+        "#
+    )
+}
+
 #[mono_test]
 fn when_on_result() {
     r#"
@@ -756,6 +920,36 @@ fn is_nil() {
     "#
 }
 
+#[mono_test(arena_allocation = true)]
+fn is_nil_arena_allocation() {
+    r#"
+    ConsList a : [Cons a (ConsList a), Nil]
+
+    isNil : ConsList a -> Bool
+    isNil = \list ->
+        when list is
+            Nil -> Bool.true
+            Cons _ _ -> Bool.false
+
+    isNil (Cons 0x2 Nil)
+    "#
+}
+
+#[mono_test(arena_allocation = true)]
+fn cons_list_of_strings_arena_allocation() {
+    r#"
+    ConsList a : [Cons a (ConsList a), Nil]
+
+    length : ConsList a -> U64
+    length = \list ->
+        when list is
+            Nil -> 0
+            Cons _ rest -> 1 + length rest
+
+    length (Cons "first" (Cons "second" Nil))
+    "#
+}
+
 #[mono_test]
 #[ignore]
 fn has_none() {
@@ -3010,3 +3204,121 @@ fn rb_tree_fbip() {
         "#
     )
 }
+
+#[mono_test(strip_expects = "true")]
+fn inline_expect_stripped() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        double = \x ->
+            expect x > 0
+            x * 2
+
+        main = double 21i64
+        "#
+    )
+}
+
+#[mono_test(fold_constants = true)]
+fn fold_top_level_constant_but_not_main() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        table = List.map [1, 2, 3] \x -> x * 2
+
+        main = List.len table
+        "#
+    )
+}
+
+/// Whether every specialization of the top-level function `name` in `src` runs as a loop once
+/// refcounts are inserted. Also checks that no function was reported as not being a loop.
+fn compiles_to_loop(src: &str, name: &str) -> bool {
+    use roc_packaging::cache::RocCacheDir;
+    use std::path::PathBuf;
+
+    let arena = &Bump::new();
+    let load_config = LoadConfig {
+        target_info: TARGET_INFO,
+        threading: Threading::Single,
+        render: roc_reporting::report::RenderTarget::Generic,
+        palette: roc_reporting::report::DEFAULT_PALETTE,
+        exec_mode: ExecutionMode::Executable,
+        max_specializations: None,
+        coverage: false,
+        fold_constants: false,
+        hoist_loop_invariants: false,
+        strip_expects: false,
+        cancelled: None,
+    };
+    let loaded = roc_load::load_and_monomorphize_from_str(
+        arena,
+        PathBuf::from("Test.roc"),
+        src,
+        PathBuf::from("fake/test/path"),
+        RocCacheDir::Disallowed,
+        load_config,
+    )
+    .unwrap_or_else(|problem| panic!("{:?}", problem));
+
+    assert_eq!(loaded.specialization_warnings, Vec::<String>::new());
+
+    let home = loaded.module_id;
+    let ident_id = loaded
+        .interns
+        .all_ident_ids
+        .get(&home)
+        .and_then(|ident_ids| ident_ids.get_id(name))
+        .unwrap();
+    let symbol = Symbol::new(home, ident_id);
+
+    let mut procs = loaded
+        .procedures
+        .iter()
+        .filter(|((proc_symbol, _), _)| *proc_symbol == symbol)
+        .peekable();
+
+    assert!(procs.peek().is_some(), "{} was not specialized", name);
+
+    procs.all(|(_, proc)| roc_mono::tail_recursion::is_loop(proc))
+}
+
+#[test]
+fn tail_recursive_function_is_a_loop() {
+    assert!(compiles_to_loop(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            sum = \n, total ->
+                when n is
+                    0 -> total
+                    _ -> sum (n - 1) (total + n)
+
+            main = sum 10 0
+            "#
+        ),
+        "sum"
+    ));
+}
+
+#[test]
+fn non_tail_recursive_function_is_not_a_loop() {
+    assert!(!compiles_to_loop(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            sum = \n ->
+                when n is
+                    0 -> 0
+                    _ -> n + sum (n - 1)
+
+            main = sum 10
+            "#
+        ),
+        "sum"
+    ));
+}
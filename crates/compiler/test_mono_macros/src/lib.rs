@@ -8,23 +8,43 @@ use quote::quote;
 pub fn mono_test(args: TokenStream, item: TokenStream) -> TokenStream {
     let mut no_check = false;
     let mut allow_type_errors = false;
+    let mut strip_expects = false;
+    let mut arena_allocation = false;
+    let mut fold_constants = false;
     let mut mode = "exec".to_owned();
     for arg in syn::parse_macro_input!(args as syn::AttributeArgs) {
         use syn::{Lit, Meta, MetaNameValue, NestedMeta};
         if let NestedMeta::Meta(Meta::NameValue(MetaNameValue {
             path,
             eq_token: _,
-            lit: Lit::Str(s),
+            lit,
         })) = arg
         {
+            // flags are set by any string, like `no_check = "true"`, or by a bool
+            let flag = match &lit {
+                Lit::Bool(b) => b.value,
+                _ => true,
+            };
+
             if path.is_ident("mode") {
-                mode = s.value();
+                if let Lit::Str(s) = &lit {
+                    mode = s.value();
+                }
             }
             if path.is_ident("no_check") {
-                no_check = true;
+                no_check = flag;
             }
             if path.is_ident("allow_type_errors") {
-                allow_type_errors = true;
+                allow_type_errors = flag;
+            }
+            if path.is_ident("strip_expects") {
+                strip_expects = flag;
+            }
+            if path.is_ident("arena_allocation") {
+                arena_allocation = flag;
+            }
+            if path.is_ident("fold_constants") {
+                fold_constants = flag;
             }
         }
     }
@@ -44,7 +64,7 @@ pub fn mono_test(args: TokenStream, item: TokenStream) -> TokenStream {
         #[test]
         #(#attributes)*
         #visibility fn #name(#args) {
-            compiles_to_ir(#name_str, #body, &#mode, #allow_type_errors, #no_check);
+            compiles_to_ir(#name_str, #body, &#mode, #allow_type_errors, #no_check, #strip_expects, #arena_allocation, #fold_constants);
 
         }
     };
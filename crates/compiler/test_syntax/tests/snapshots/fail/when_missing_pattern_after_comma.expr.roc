@@ -0,0 +1,2 @@
+when a, b is
+    Ok x, -> x
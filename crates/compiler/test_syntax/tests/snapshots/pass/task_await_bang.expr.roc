@@ -0,0 +1,4 @@
+x = Stdout.line! "hi"
+y = read!
+
+x != y
@@ -0,0 +1,3 @@
+when a, b is
+    Ok x, Ok y -> x
+    _, _ -> 0
@@ -3654,6 +3654,26 @@ mod test_fmt {
         );
     }
 
+    #[test]
+    fn when_with_multiple_conditions() {
+        expr_formats_to(
+            indoc!(
+                r#"
+            when a,b is
+                Ok x,Ok y -> x + y
+                Err e, _ | _, Err e -> e
+            "#
+            ),
+            indoc!(
+                r#"
+            when a, b is
+                Ok x, Ok y -> x + y
+                Err e, _ | _, Err e -> e
+                "#
+            ),
+        );
+    }
+
     #[test]
     fn when_with_alternatives_4() {
         expr_formats_to(
@@ -4745,6 +4765,16 @@ mod test_fmt {
         ));
     }
 
+    #[test]
+    fn interface_exposing_opaque() {
+        module_formats_same(indoc!(
+            r#"
+                interface Foo
+                    exposes [opaque Bar, Baz, opaque, b]
+                    imports []"#
+        ));
+    }
+
     #[test]
     fn interface_importing() {
         module_formats_same(indoc!(
@@ -5267,6 +5297,35 @@ mod test_fmt {
         );
     }
 
+    #[test]
+    fn task_await_bang() {
+        expr_formats_same(indoc!(
+            r#"
+                main = \{} ->
+                    name = Stdin.line!
+                    _ = Stdout.line! "Hello, \(name)!"
+                    Task.ok {}
+
+                main
+            "#
+        ));
+
+        expr_formats_to(
+            indoc!(
+                r#"
+                    x = read!
+                    x!=y
+                "#
+            ),
+            indoc!(
+                r#"
+                    x = read!
+                    x != y
+                "#
+            ),
+        );
+    }
+
     #[test]
     fn backpassing_body_on_newline() {
         expr_formats_same(indoc!(
@@ -5674,6 +5733,24 @@ mod test_fmt {
         ));
     }
 
+    #[test]
+    fn bench_single_and_multiline() {
+        module_formats_same(indoc!(
+            r#"
+                interface Foo exposes [] imports []
+
+                # sort a big list
+                bench List.sortAsc bigList
+
+                bench
+                    bigList
+                    |> List.map Num.toStr
+
+                foo = bar
+            "#
+        ));
+    }
+
     #[test]
     fn single_line_string_literal_in_pattern() {
         expr_formats_same(indoc!(
@@ -243,6 +243,7 @@ mod test_snapshots {
         fail/unicode_not_hex.expr,
         fail/weird_escape.expr,
         fail/when_missing_arrow.expr,
+        fail/when_missing_pattern_after_comma.expr,
         fail/when_outdented_branch.expr,
         fail/when_over_indented_int.expr,
         fail/when_over_indented_underscore.expr,
@@ -439,6 +440,7 @@ mod test_snapshots {
         pass/sub_var_with_spaces.expr,
         pass/sub_with_spaces.expr,
         pass/tag_pattern.expr,
+        pass/task_await_bang.expr,
         pass/ten_times_eleven.expr,
         pass/three_arg_closure.expr,
         pass/tuple_access_after_ident.expr,
@@ -476,6 +478,7 @@ mod test_snapshots {
         pass/when_in_parens_indented.expr,
         pass/when_with_alternative_patterns.expr,
         pass/when_with_function_application.expr,
+        pass/when_with_multiple_conditions.expr,
         pass/when_with_negative_numbers.expr,
         pass/when_with_numbers.expr,
         pass/when_with_records.expr,
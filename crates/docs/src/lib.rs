@@ -4,7 +4,7 @@ extern crate pulldown_cmark;
 extern crate roc_load;
 use bumpalo::Bump;
 use roc_can::scope::Scope;
-use roc_collections::VecSet;
+use roc_collections::{VecMap, VecSet};
 use roc_load::docs::{DocEntry, TypeAnnotation};
 use roc_load::docs::{ModuleDocumentation, RecordField};
 use roc_load::{ExecutionMode, LoadConfig, LoadedModule, LoadingProblem, Threading};
@@ -13,6 +13,7 @@ use roc_packaging::cache::{self, RocCacheDir};
 use roc_parse::ident::{parse_ident, Accessor, Ident};
 use roc_parse::state::State;
 use roc_region::all::Region;
+use roc_types::pretty_print::{name_and_print_var, DebugPrint};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -22,7 +23,7 @@ const LINK_SVG: &str = include_str!("./static/link.svg");
 
 pub fn generate_docs_html(root_file: PathBuf) {
     let build_dir = Path::new(BUILD_DIR);
-    let loaded_module = load_module_for_docs(root_file);
+    let mut loaded_module = load_module_for_docs(root_file);
 
     // TODO get these from the platform's source file rather than hardcoding them!
     let package_name = "Documentation".to_string();
@@ -133,6 +134,8 @@ pub fn generate_docs_html(root_file: PathBuf) {
         set
     };
 
+    let inferred_types = inferred_types(&mut loaded_module, &all_exposed_symbols);
+
     // Write index.html for package (/index.html)
     {
         let rendered_package = template_html
@@ -176,8 +179,13 @@ pub fn generate_docs_html(root_file: PathBuf) {
             )
             .replace(
                 "<!-- Module Docs -->",
-                render_module_documentation(module_docs, &loaded_module, &all_exposed_symbols)
-                    .as_str(),
+                render_module_documentation(
+                    module_docs,
+                    &loaded_module,
+                    &all_exposed_symbols,
+                    &inferred_types,
+                )
+                .as_str(),
             );
 
         fs::write(module_dir.join("index.html"), rendered_module)
@@ -228,10 +236,48 @@ fn render_package_index(root_module: &LoadedModule) -> String {
     index_buf
 }
 
+/// The inferred types of exposed values that don't have a type annotation.
+///
+/// Only the root module's solved `Subs` survive loading, so values exposed from
+/// other modules need an annotation to get a type in the docs.
+fn inferred_types(
+    loaded_module: &mut LoadedModule,
+    all_exposed_symbols: &VecSet<Symbol>,
+) -> VecMap<Symbol, String> {
+    let mut types = VecMap::default();
+    let home = loaded_module.module_id;
+
+    let declarations = match loaded_module.declarations_by_id.get(&home) {
+        Some(declarations) => declarations,
+        None => return types,
+    };
+
+    let subs = loaded_module.solved.inner_mut();
+
+    for (index, loc_symbol) in declarations.symbols.iter().enumerate() {
+        let symbol = loc_symbol.value;
+
+        if declarations.annotations[index].is_none() && all_exposed_symbols.contains(&symbol) {
+            let type_str = name_and_print_var(
+                declarations.variables[index],
+                subs,
+                home,
+                &loaded_module.interns,
+                DebugPrint::NOTHING,
+            );
+
+            types.insert(symbol, type_str);
+        }
+    }
+
+    types
+}
+
 fn render_module_documentation(
     module: &ModuleDocumentation,
     root_module: &LoadedModule,
     all_exposed_symbols: &VecSet<Symbol>,
+    inferred_types: &VecMap<Symbol, String>,
 ) -> String {
     let mut buf = String::new();
 
@@ -272,6 +318,9 @@ fn render_module_documentation(
                     if !matches!(type_ann, TypeAnnotation::NoTypeAnn) {
                         content.push_str(" : ");
                         type_annotation_to_html(0, &mut content, type_ann, false);
+                    } else if let Some(type_str) = inferred_types.get(&doc_def.symbol) {
+                        content.push_str(" : ");
+                        content.push_str(type_str);
                     }
 
                     push_html(
@@ -457,9 +506,15 @@ pub fn load_module_for_docs(filename: PathBuf) -> LoadedModule {
     let load_config = LoadConfig {
         target_info: roc_target::TargetInfo::default_x86_64(), // This is just type-checking for docs, so "target" doesn't matter
         render: roc_reporting::report::RenderTarget::ColorTerminal,
-        palette: roc_reporting::report::DEFAULT_PALETTE,
+        palette: roc_reporting::report::Palette::from_env(),
         threading: Threading::AllAvailable,
         exec_mode: ExecutionMode::Check,
+        max_specializations: None,
+        coverage: false,
+        fold_constants: false,
+        hoist_loop_invariants: false,
+        strip_expects: false,
+        cancelled: None,
     };
     match roc_load::load_and_typecheck(
         &arena,
@@ -0,0 +1,158 @@
+//! Provides syntax highlighting, formatting and syntax checking for the Roc website using
+//! WebAssembly, so the playground and the online docs use the real parser and formatter.
+//!
+//! Only the parser and the formatter are compiled in, which keeps the module small and lets it
+//! build for `wasm32-unknown-unknown` without LLVM or anything else that needs the host.
+//!
+//! The code doesn't need a module header: docs and the playground mostly show a few defs.
+
+// wasm_bindgen procedural macro breaks this clippy rule
+// https://github.com/rustwasm/wasm-bindgen/issues/2774
+#![allow(clippy::unused_unit)]
+
+use bumpalo::Bump;
+use roc_fmt::def::fmt_defs;
+use roc_fmt::module::fmt_module;
+use roc_fmt::Buf;
+use roc_parse::ast::{Defs, Module};
+use roc_parse::module::{self, module_defs};
+use roc_parse::parser::{EHeader, Parser, SourceError, SyntaxError};
+use roc_parse::state::State;
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::wasm_bindgen;
+
+/// The tokens of `src` as a JSON array of `{ "start", "end", "token" }`, where `token` is the name
+/// of a [roc_parse::highlight::Token]. Offsets count UTF-16 code units, like JavaScript strings.
+#[wasm_bindgen]
+pub fn tokenize(src: &str) -> String {
+    let mut cursor = Utf16Cursor {
+        src,
+        byte: 0,
+        utf16: 0,
+    };
+
+    let tokens = roc_parse::highlight::highlight(src)
+        .into_iter()
+        .map(|loc_token| {
+            json!({
+                "start": cursor.advance_to(loc_token.region.start().offset),
+                "end": cursor.advance_to(loc_token.region.end().offset),
+                "token": format!("{:?}", loc_token.value),
+            })
+        })
+        .collect();
+
+    Value::Array(tokens).to_string()
+}
+
+/// `src` formatted like `roc format` would, or the syntax error that keeps it from being formatted.
+#[wasm_bindgen]
+pub fn format(src: &str) -> Result<String, String> {
+    let arena = Bump::new();
+    let (header, defs) = parse(&arena, src)
+        .map_err(|problem| format!("It doesn't parse. Parse error was:\n\n{:?}", problem))?;
+    let mut buf = Buf::new_in(&arena);
+
+    if let Some(header) = &header {
+        fmt_module(&mut buf, header);
+    }
+
+    fmt_defs(&mut buf, &defs, 0);
+    buf.fmt_end_of_file();
+
+    Ok(buf.as_str().to_string())
+}
+
+/// The syntax error in `src`, if there is one.
+#[wasm_bindgen]
+pub fn check_syntax(src: &str) -> Option<String> {
+    let arena = Bump::new();
+
+    parse(&arena, src)
+        .err()
+        .map(|problem| format!("{:?}", problem))
+}
+
+/// Parse a whole module, or just defs if `src` doesn't start with a header.
+fn parse<'a>(
+    arena: &'a Bump,
+    src: &'a str,
+) -> Result<(Option<Module<'a>>, Defs<'a>), SyntaxError<'a>> {
+    let state = State::new(src.as_bytes());
+
+    let (header, state) = match module::parse_header(arena, state.clone()) {
+        Ok((header, state)) => (Some(header), state),
+        Err(SourceError {
+            problem: EHeader::Start(_),
+            ..
+        }) => (None, state),
+        Err(error) => return Err(SyntaxError::Header(error.problem)),
+    };
+
+    let (_, defs, _) = module_defs()
+        .parse(arena, state, 0)
+        .map_err(|(_, problem)| problem)?;
+
+    Ok((header, defs))
+}
+
+/// Converts byte offsets to UTF-16 offsets, going forward from the last offset it converted.
+struct Utf16Cursor<'a> {
+    src: &'a str,
+    byte: usize,
+    utf16: usize,
+}
+
+impl Utf16Cursor<'_> {
+    fn advance_to(&mut self, byte: u32) -> usize {
+        let byte = byte as usize;
+
+        if byte < self.byte {
+            self.byte = 0;
+            self.utf16 = 0;
+        }
+
+        self.utf16 += self.src[self.byte..byte].encode_utf16().count();
+        self.byte = byte;
+
+        self.utf16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utf16_offsets() {
+        let tokens: Value = serde_json::from_str(&tokenize("s = \"é\"")).unwrap();
+
+        assert_eq!(
+            tokens,
+            json!([
+                { "start": 0, "end": 1, "token": "LowerIdent" },
+                { "start": 2, "end": 3, "token": "Equals" },
+                { "start": 4, "end": 7, "token": "String" },
+            ])
+        );
+    }
+
+    #[test]
+    fn format_defs_without_header() {
+        assert_eq!(format("x=1\ny  =  x+1").unwrap(), "x = 1\ny = x + 1\n");
+    }
+
+    #[test]
+    fn format_module() {
+        let src = "interface Foo exposes [x] imports []\n\nx = 1\n";
+
+        assert_eq!(format(src).unwrap(), src);
+    }
+
+    #[test]
+    fn syntax_errors() {
+        assert_eq!(check_syntax("x = 1"), None);
+        assert!(check_syntax("x = [1,").is_some());
+        assert!(check_syntax("interface Foo exposes [x imports []").is_some());
+    }
+}
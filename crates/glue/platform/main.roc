@@ -1,6 +1,6 @@
 platform "roc-lang/glue"
     requires {} { makeGlue : List Types -> Result (List File) Str }
-    exposes [Shape, File, Types, TypeId, Target]
+    exposes [Shape, File, Types, TypeId]
     packages {}
     imports [Types.{ Types }, File.{ File }]
     provides [makeGlueForHost]
@@ -0,0 +1,1065 @@
+//! Generates an ES module that loads a Roc app compiled to Wasm and converts values between
+//! Roc's memory layout and JavaScript, along with TypeScript declarations for it.
+//!
+//! Every type gets a `read<id>` and a `write<id>` function, named after its [TypeId]. The
+//! runtime they build on is in `templates/roc_app.js`.
+use crate::types::{
+    File, RocNum, RocSingleTagPayload, RocStructFields, RocTagUnion, RocType, TypeId, Types,
+};
+use roc_mono::layout::{round_up_to_alignment, UnionLayout};
+use roc_target::{Architecture, TargetInfo};
+use std::fmt::Write;
+
+pub static PRELUDE: &str = include_str!("../templates/roc_app.js");
+pub static DECLARATIONS_PRELUDE: &str = include_str!("../templates/roc_app.d.ts");
+const INDENT: &str = "    ";
+
+// Wasm is 32-bit, so Str and List are three 4-byte fields.
+const PTR_WIDTH: u32 = 4;
+const STR_OR_LIST_SIZE: u32 = 3 * PTR_WIDTH;
+
+pub fn emit(types: &[Types]) -> Vec<File> {
+    let types = types
+        .iter()
+        .find(|types| types.target().architecture == Architecture::Wasm32)
+        .expect("glue types are generated for every architecture, including wasm32");
+
+    vec![
+        File {
+            name: "roc_app.js".to_string(),
+            content: js_module(types),
+        },
+        File {
+            name: "roc_app.d.ts".to_string(),
+            content: declarations(types),
+        },
+    ]
+}
+
+fn js_module(types: &Types) -> String {
+    let mut buf = String::from(PRELUDE);
+    let symbols: Vec<String> = types
+        .entry_points()
+        .iter()
+        .map(|(name, _)| js_string(&exposed_symbol(name)))
+        .collect();
+
+    writeln!(buf, "\nconst EXPOSED_SYMBOLS = [{}];", symbols.join(", ")).unwrap();
+
+    for id in types.ids() {
+        let mut read = String::new();
+        let mut write = String::new();
+        let index = id.index();
+
+        add_converters(types, id, &mut read, &mut write);
+
+        write!(
+            buf,
+            "\nfunction read{index}(roc, ptr, consume) {{\n{read}}}\n\nfunction write{index}(roc, ptr, value) {{\n{write}}}\n"
+        )
+        .unwrap();
+    }
+
+    buf.push_str("\nfunction makeApp(roc, callExport) {\n");
+
+    for (name, _) in types.entry_points() {
+        let symbol = js_string(&exposed_symbol(name));
+
+        line(
+            &mut buf,
+            1,
+            &format!("const {name}Export = callExport({symbol});"),
+        );
+    }
+
+    buf.push('\n');
+    line(&mut buf, 1, "return {");
+
+    for (name, id) in types.entry_points() {
+        add_entry_point(types, name, *id, &mut buf);
+    }
+
+    line(&mut buf, 1, "};");
+    buf.push_str("}\n");
+
+    buf
+}
+
+fn declarations(types: &Types) -> String {
+    let mut buf = String::from(DECLARATIONS_PRELUDE);
+
+    for id in types.sorted_ids() {
+        match types.get_type(id) {
+            RocType::Struct { name, fields } => {
+                writeln!(buf, "\nexport interface {name} {{").unwrap();
+
+                for (label, field) in struct_fields(fields) {
+                    line(&mut buf, 1, &format!("{label}: {};", ts_type(types, field)));
+                }
+
+                buf.push_str("}\n");
+            }
+            RocType::TagUnionPayload { name, fields } => {
+                let elements: Vec<String> = tuple_fields(fields)
+                    .into_iter()
+                    .map(|field| ts_type(types, field))
+                    .collect();
+
+                writeln!(buf, "\nexport type {name} = [{}];", elements.join(", ")).unwrap();
+            }
+            RocType::TagUnion(RocTagUnion::Enumeration { name, tags, .. }) => {
+                let tags: Vec<String> = tags.iter().map(|tag| js_string(tag)).collect();
+
+                writeln!(buf, "\nexport type {name} = {};", tags.join(" | ")).unwrap();
+            }
+            RocType::TagUnion(union) => {
+                writeln!(buf, "\nexport type {} =", tag_union_name(union)).unwrap();
+
+                let variants = ts_variants(types, union);
+
+                for (index, (tag, payload)) in variants.iter().enumerate() {
+                    let end = if index + 1 == variants.len() { ";" } else { "" };
+                    let variant = match payload {
+                        Some(payload) => {
+                            format!("{{ tag: {}; payload: {payload} }}", js_string(tag))
+                        }
+                        None => format!("{{ tag: {} }}", js_string(tag)),
+                    };
+
+                    line(&mut buf, 1, &format!("| {variant}{end}"));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    buf.push_str("\nexport interface RocApp {\n");
+
+    for (name, id) in types.entry_points() {
+        let (args, ret) = entry_point_signature(types, *id);
+        let params: Vec<String> = args
+            .iter()
+            .enumerate()
+            .map(|(index, arg)| format!("arg{index}: {}", ts_type(types, *arg)))
+            .collect();
+
+        line(
+            &mut buf,
+            1,
+            &format!(
+                "{name}({}): Promise<{}>;",
+                params.join(", "),
+                ts_type(types, ret)
+            ),
+        );
+    }
+
+    buf.push_str("}\n");
+
+    buf
+}
+
+fn add_entry_point(types: &Types, name: &str, id: TypeId, buf: &mut String) {
+    let (args, ret) = entry_point_signature(types, id);
+    let params: Vec<String> = (0..args.len()).map(|index| format!("arg{index}")).collect();
+    let mut setup = String::new();
+    let mut call_args = vec!["result".to_string()];
+
+    line(buf, 2, &format!("async {name}({}) {{", params.join(", ")));
+
+    for (param, arg) in params.iter().zip(args.iter()) {
+        match add_call_arg(types, *arg, param, &mut setup) {
+            Some(call_arg) => call_args.push(call_arg),
+            None => {
+                let description = format!(
+                    "passing {} arguments to Roc entry points",
+                    ts_type(types, *arg)
+                );
+
+                line(
+                    buf,
+                    3,
+                    &format!("unsupported({});", js_string(&description)),
+                );
+                line(buf, 2, "},");
+
+                return;
+            }
+        }
+    }
+
+    line(buf, 3, "const scratch = roc.scratch();");
+    buf.push('\n');
+    line(buf, 3, "try {");
+    line(
+        buf,
+        4,
+        &format!(
+            "const result = scratch.alloc({}, {});",
+            size(types, ret),
+            types.align(ret)
+        ),
+    );
+    buf.push_str(&setup);
+    buf.push('\n');
+    line(
+        buf,
+        4,
+        &format!("await {name}Export({});", call_args.join(", ")),
+    );
+    buf.push('\n');
+    line(
+        buf,
+        4,
+        &format!("return read{}(roc, result, true);", ret.index()),
+    );
+    line(buf, 3, "} finally {");
+    line(buf, 4, "scratch.free();");
+    line(buf, 3, "}");
+    line(buf, 2, "},");
+}
+
+/// Returns how to pass `arg` to a `_exposed_generic` function, or None if we don't know how.
+///
+/// Numbers are passed as Wasm numbers, with 128-bit ones split into two i64s. Str and List are
+/// passed by reference, and heap-allocated values as the pointer to their allocation.
+fn add_call_arg(types: &Types, id: TypeId, arg: &str, setup: &mut String) -> Option<String> {
+    let mut by_reference = |size: u32| {
+        setup.push('\n');
+        line(
+            setup,
+            4,
+            &format!("const {arg}Ptr = scratch.alloc({size}, {PTR_WIDTH});"),
+        );
+        line(
+            setup,
+            4,
+            &format!("write{}(roc, {arg}Ptr, {arg});", id.index()),
+        );
+    };
+
+    match types.get_type(id) {
+        RocType::Num(RocNum::I64 | RocNum::U64) => Some(format!("BigInt.asIntN(64, {arg})")),
+        RocType::Num(RocNum::I128 | RocNum::U128 | RocNum::Dec) => Some(format!(
+            "BigInt.asIntN(64, {arg}), BigInt.asIntN(64, {arg} >> 64n)"
+        )),
+        RocType::Num(_) => Some(arg.to_string()),
+        RocType::Bool => Some(format!("{arg} ? 1 : 0")),
+        RocType::RocStr | RocType::RocList(_) => {
+            by_reference(STR_OR_LIST_SIZE);
+
+            Some(format!("{arg}Ptr"))
+        }
+        RocType::RocBox(_)
+        | RocType::RecursivePointer(_)
+        | RocType::TagUnion(
+            RocTagUnion::Recursive { .. }
+            | RocTagUnion::NullableWrapped { .. }
+            | RocTagUnion::NonNullableUnwrapped { .. }
+            | RocTagUnion::NullableUnwrapped { .. },
+        ) => {
+            by_reference(PTR_WIDTH);
+
+            Some(format!("roc.view.getUint32({arg}Ptr, true)"))
+        }
+        _ => None,
+    }
+}
+
+fn add_converters(types: &Types, id: TypeId, read: &mut String, write: &mut String) {
+    match types.get_type(id) {
+        RocType::RocStr => {
+            line(read, 1, "return roc.readStr(ptr, consume);");
+            line(write, 1, "roc.writeStr(ptr, value);");
+        }
+        RocType::Bool => {
+            line(read, 1, "return roc.view.getUint8(ptr) !== 0;");
+            line(write, 1, "roc.view.setUint8(ptr, value ? 1 : 0);");
+        }
+        RocType::Num(num) => match data_view_type(*num) {
+            Some(data_view_type) => {
+                line(
+                    read,
+                    1,
+                    &format!("return roc.view.get{data_view_type}(ptr, true);"),
+                );
+                line(
+                    write,
+                    1,
+                    &format!("roc.view.set{data_view_type}(ptr, value, true);"),
+                );
+            }
+            None => {
+                let signed = !matches!(num, RocNum::U128);
+
+                line(read, 1, &format!("return roc.readI128(ptr, {signed});"));
+                line(write, 1, "roc.writeI128(ptr, value);");
+            }
+        },
+        RocType::RocResult(ok, err) => {
+            // Err sorts before Ok, so it has tag id 0
+            let tags = [
+                ("Err".to_string(), Some(*err)),
+                ("Ok".to_string(), Some(*ok)),
+            ];
+            let align = types.align(*ok).max(types.align(*err));
+            let discriminant_offset =
+                round_up_to_alignment(size(types, *ok).max(size(types, *err)), align);
+
+            add_non_recursive_converters("Result", &tags, discriminant_offset, 1, read, write);
+        }
+        RocType::RocList(elem) => {
+            let (index, size, align) = (elem.index(), size(types, *elem), types.align(*elem));
+
+            line(
+                read,
+                1,
+                &format!("return roc.readList(ptr, {size}, {align}, consume, (ptr, consume) => read{index}(roc, ptr, consume));"),
+            );
+            line(
+                write,
+                1,
+                &format!("roc.writeList(ptr, value, {size}, {align}, (ptr, value) => write{index}(roc, ptr, value));"),
+            );
+        }
+        RocType::RocBox(inner) => {
+            let (index, size, align) = (inner.index(), size(types, *inner), types.align(*inner));
+
+            line(
+                read,
+                1,
+                &format!("return roc.readBox(ptr, {align}, consume, (ptr, consume) => read{index}(roc, ptr, consume));"),
+            );
+            line(
+                write,
+                1,
+                &format!("roc.writeBox(ptr, value, {size}, {align}, (ptr, value) => write{index}(roc, ptr, value));"),
+            );
+        }
+        RocType::RocDict(_, _) => add_unsupported("Dict values", read, write),
+        RocType::RocSet(_) => add_unsupported("Set values", read, write),
+        RocType::TagUnion(union) => add_tag_union_converters(types, union, read, write),
+        RocType::EmptyTagUnion => add_unsupported("values of empty tag unions", read, write),
+        RocType::Struct {
+            fields: RocStructFields::HasNoClosure { fields },
+            ..
+        } => {
+            let offsets = field_offsets(types, fields.iter().map(|(_, field)| *field));
+
+            line(read, 1, "return {");
+
+            for ((label, field), offset) in fields.iter().zip(offsets) {
+                let ptr = at("ptr", offset);
+
+                line(
+                    read,
+                    2,
+                    &format!("{label}: read{}(roc, {ptr}, consume),", field.index()),
+                );
+                line(
+                    write,
+                    1,
+                    &format!("write{}(roc, {ptr}, value.{label});", field.index()),
+                );
+            }
+
+            line(read, 1, "};");
+        }
+        RocType::TagUnionPayload {
+            fields: RocStructFields::HasNoClosure { fields },
+            ..
+        } => {
+            let offsets = field_offsets(types, fields.iter().map(|(_, field)| *field));
+            let mut elements: Vec<(usize, TypeId, u32)> = fields
+                .iter()
+                .zip(offsets)
+                .map(|((label, field), offset)| (tuple_index(label), *field, offset))
+                .collect();
+
+            // Fields are in memory order, but the tuple is in the order of the tag's arguments.
+            elements.sort_by_key(|(index, _, _)| *index);
+
+            add_tuple_converters(&elements, read, write);
+        }
+        RocType::Struct { .. } | RocType::TagUnionPayload { .. } => {
+            add_unsupported("values that contain functions", read, write)
+        }
+        RocType::RecursivePointer(union) => {
+            let index = union.index();
+
+            line(read, 1, &format!("return read{index}(roc, ptr, consume);"));
+            line(write, 1, &format!("write{index}(roc, ptr, value);"));
+        }
+        RocType::Function(_) => {
+            add_unsupported("passing functions between Roc and JavaScript", read, write)
+        }
+        RocType::Unit => {
+            line(read, 1, "return {};");
+            line(write, 1, "// zero-sized, so there is nothing to write");
+        }
+        RocType::Unsized => add_unsupported("values without a fixed size", read, write),
+    }
+}
+
+fn add_tag_union_converters(
+    types: &Types,
+    union: &RocTagUnion,
+    read: &mut String,
+    write: &mut String,
+) {
+    match union {
+        RocTagUnion::Enumeration { name, tags, size } => {
+            let data_view_type = discriminant_data_view_type(*size);
+            let tags: Vec<String> = tags.iter().map(|tag| js_string(tag)).collect();
+            let tags = tags.join(", ");
+            let name = js_string(name);
+
+            line(read, 1, &format!("const tags = [{tags}];"));
+            line(
+                read,
+                1,
+                &format!("const tagId = roc.view.get{data_view_type}(ptr, true);"),
+            );
+            read.push('\n');
+            line(read, 1, "if (tagId >= tags.length) {");
+            line(read, 2, &format!("throw invalidTagId({name}, tagId);"));
+            line(read, 1, "}");
+            read.push('\n');
+            line(read, 1, "return tags[tagId];");
+
+            line(
+                write,
+                1,
+                &format!(
+                    "roc.view.set{data_view_type}(ptr, tagIndex({name}, [{tags}], value), true);"
+                ),
+            );
+        }
+        RocTagUnion::NonRecursive {
+            name,
+            tags,
+            discriminant_offset,
+            discriminant_size,
+        } => add_non_recursive_converters(
+            name,
+            tags,
+            *discriminant_offset,
+            *discriminant_size,
+            read,
+            write,
+        ),
+        RocTagUnion::Recursive {
+            name,
+            tags,
+            discriminant_offset,
+            discriminant_size,
+        } => {
+            let location = tag_id_location(
+                types.target(),
+                tags.len(),
+                *discriminant_offset,
+                *discriminant_size,
+            );
+
+            add_heap_union_converters(types, name, tags, None, location, read, write);
+        }
+        RocTagUnion::NullableWrapped {
+            name,
+            index_of_null_tag,
+            tags,
+            discriminant_size,
+            discriminant_offset,
+        } => {
+            let location = tag_id_location(
+                types.target(),
+                tags.len() - 1,
+                *discriminant_offset,
+                *discriminant_size,
+            );
+            let null_tag = Some(*index_of_null_tag as usize);
+
+            add_heap_union_converters(types, name, tags, null_tag, location, read, write);
+        }
+        RocTagUnion::NonNullableUnwrapped {
+            name,
+            tag_name,
+            payload,
+        } => {
+            let tags = [(tag_name.clone(), Some(*payload))];
+
+            add_heap_union_converters(types, name, &tags, None, TagId::Implicit, read, write);
+        }
+        RocTagUnion::NullableUnwrapped {
+            name,
+            null_tag,
+            non_null_tag,
+            non_null_payload,
+            null_represents_first_tag,
+        } => {
+            let null = (null_tag.clone(), None);
+            let non_null = (non_null_tag.clone(), Some(*non_null_payload));
+            let (tags, null_tag) = if *null_represents_first_tag {
+                ([null, non_null], 0)
+            } else {
+                ([non_null, null], 1)
+            };
+
+            add_heap_union_converters(
+                types,
+                name,
+                &tags,
+                Some(null_tag),
+                TagId::Implicit,
+                read,
+                write,
+            );
+        }
+        RocTagUnion::SingleTagStruct {
+            name: _,
+            tag_name,
+            payload: RocSingleTagPayload::HasNoClosure { payload_fields },
+        } => {
+            let tag = js_string(tag_name);
+
+            if payload_fields.is_empty() {
+                line(read, 1, &format!("return {{ tag: {tag} }};"));
+                line(write, 1, "// zero-sized, so there is nothing to write");
+
+                return;
+            }
+
+            let offsets = field_offsets(types, payload_fields.iter().copied());
+            let elements: Vec<(usize, TypeId, u32)> = payload_fields
+                .iter()
+                .zip(offsets)
+                .enumerate()
+                .map(|(index, (field, offset))| (index, *field, offset))
+                .collect();
+
+            line(read, 1, &format!("return {{ tag: {tag}, payload: ["));
+
+            for (_, field, offset) in elements.iter() {
+                line(
+                    read,
+                    2,
+                    &format!(
+                        "read{}(roc, {}, consume),",
+                        field.index(),
+                        at("ptr", *offset)
+                    ),
+                );
+            }
+
+            line(read, 1, "] };");
+
+            for (index, field, offset) in elements {
+                line(
+                    write,
+                    1,
+                    &format!(
+                        "write{}(roc, {}, value.payload[{index}]);",
+                        field.index(),
+                        at("ptr", offset)
+                    ),
+                );
+            }
+        }
+        RocTagUnion::SingleTagStruct { .. } => {
+            add_unsupported("values that contain functions", read, write)
+        }
+    }
+}
+
+fn add_non_recursive_converters(
+    name: &str,
+    tags: &[(String, Option<TypeId>)],
+    discriminant_offset: u32,
+    discriminant_size: u32,
+    read: &mut String,
+    write: &mut String,
+) {
+    let data_view_type = discriminant_data_view_type(discriminant_size);
+    let discriminant = at("ptr", discriminant_offset);
+    let name = js_string(name);
+
+    line(
+        read,
+        1,
+        &format!("const tagId = roc.view.get{data_view_type}({discriminant}, true);"),
+    );
+    read.push('\n');
+    line(read, 1, "switch (tagId) {");
+
+    for (tag_id, (tag, payload)) in tags.iter().enumerate() {
+        line(read, 2, &format!("case {tag_id}:"));
+        line(
+            read,
+            3,
+            &format!("return {};", variant(tag, *payload, "ptr")),
+        );
+    }
+
+    line(read, 2, "default:");
+    line(read, 3, &format!("throw invalidTagId({name}, tagId);"));
+    line(read, 1, "}");
+
+    line(write, 1, "switch (value.tag) {");
+
+    for (tag_id, (tag, payload)) in tags.iter().enumerate() {
+        line(write, 2, &format!("case {}:", js_string(tag)));
+
+        if let Some(payload) = payload {
+            line(
+                write,
+                3,
+                &format!("write{}(roc, ptr, value.payload);", payload.index()),
+            );
+        }
+
+        line(
+            write,
+            3,
+            &format!("roc.view.set{data_view_type}({discriminant}, {tag_id}, true);"),
+        );
+        line(write, 3, "break;");
+    }
+
+    line(write, 2, "default:");
+    line(write, 3, &format!("throw invalidTag({name}, value.tag);"));
+    line(write, 1, "}");
+}
+
+/// Where a heap-allocated tag union keeps its tag id
+#[derive(Clone, Copy)]
+enum TagId {
+    /// In the low bits of the pointer to its allocation
+    Pointer { mask: usize },
+    /// In the allocation, after the payload
+    Data { offset: u32, size: u32 },
+    /// Nowhere, because there's only one tag that isn't null
+    Implicit,
+}
+
+/// This matches when `UnionLayout` stores tag ids in pointer bits.
+fn tag_id_location(target: TargetInfo, non_null_tags: usize, offset: u32, size: u32) -> TagId {
+    if target.pointer_tagging && non_null_tags < target.ptr_width() as usize {
+        let (_, mask) = UnionLayout::tag_id_pointer_bits_and_mask(target);
+
+        TagId::Pointer { mask }
+    } else {
+        TagId::Data { offset, size }
+    }
+}
+
+/// Recursive tag unions are pointers to a refcounted allocation holding the payload, with null
+/// standing for the payload-less `null_tag` (if there is one).
+fn add_heap_union_converters(
+    types: &Types,
+    name: &str,
+    tags: &[(String, Option<TypeId>)],
+    null_tag: Option<usize>,
+    location: TagId,
+    read: &mut String,
+    write: &mut String,
+) {
+    let payloads = tags.iter().filter_map(|(_, payload)| *payload);
+    let align = payloads
+        .clone()
+        .map(|payload| types.align(payload))
+        .max()
+        .unwrap_or(1);
+    let data_size = match location {
+        TagId::Data { offset, size } => offset + size,
+        TagId::Pointer { .. } | TagId::Implicit => payloads
+            .map(|payload| size(types, payload))
+            .max()
+            .unwrap_or(0),
+    };
+    let name = js_string(name);
+
+    line(read, 1, "const tagged = roc.view.getUint32(ptr, true);");
+    read.push('\n');
+
+    if let Some(null_tag) = null_tag {
+        line(read, 1, "if (tagged === 0) {");
+        line(
+            read,
+            2,
+            &format!("return {};", variant(&tags[null_tag].0, None, "data")),
+        );
+        line(read, 1, "}");
+        read.push('\n');
+    }
+
+    match location {
+        TagId::Pointer { mask } => {
+            line(read, 1, &format!("const data = (tagged & ~{mask}) >>> 0;"));
+            line(read, 1, &format!("const tagId = tagged & {mask};"));
+        }
+        TagId::Data { offset, size } => {
+            let data_view_type = discriminant_data_view_type(size);
+
+            line(read, 1, "const data = tagged;");
+            line(
+                read,
+                1,
+                &format!(
+                    "const tagId = roc.view.get{data_view_type}({}, true);",
+                    at("data", offset)
+                ),
+            );
+        }
+        TagId::Implicit => line(read, 1, "const data = tagged;"),
+    }
+
+    read.push('\n');
+    line(
+        read,
+        1,
+        &format!("return roc.readRefcounted(data, {align}, consume, (consume) => {{"),
+    );
+
+    match location {
+        TagId::Implicit => {
+            let (tag, payload) = tags
+                .iter()
+                .enumerate()
+                .find_map(|(index, tag)| (Some(index) != null_tag).then_some(tag))
+                .expect("a heap-allocated tag union always has a tag that isn't null");
+
+            line(
+                read,
+                2,
+                &format!("return {};", variant(tag, *payload, "data")),
+            );
+        }
+        TagId::Pointer { .. } | TagId::Data { .. } => {
+            line(read, 2, "switch (tagId) {");
+
+            for (tag_id, (tag, payload)) in tags.iter().enumerate() {
+                if Some(tag_id) != null_tag {
+                    line(read, 3, &format!("case {tag_id}:"));
+                    line(
+                        read,
+                        4,
+                        &format!("return {};", variant(tag, *payload, "data")),
+                    );
+                }
+            }
+
+            line(read, 3, "default:");
+            line(read, 4, &format!("throw invalidTagId({name}, tagId);"));
+            line(read, 2, "}");
+        }
+    }
+
+    line(read, 1, "});");
+
+    line(write, 1, "switch (value.tag) {");
+
+    for (tag_id, (tag, payload)) in tags.iter().enumerate() {
+        if Some(tag_id) == null_tag {
+            line(write, 2, &format!("case {}:", js_string(tag)));
+            line(write, 3, "roc.view.setUint32(ptr, 0, true);");
+            line(write, 3, "break;");
+
+            continue;
+        }
+
+        line(write, 2, &format!("case {}: {{", js_string(tag)));
+        line(
+            write,
+            3,
+            &format!("const data = roc.allocRefcounted({data_size}, {align});"),
+        );
+        write.push('\n');
+
+        if let Some(payload) = payload {
+            line(
+                write,
+                3,
+                &format!("write{}(roc, data, value.payload);", payload.index()),
+            );
+        }
+
+        let tagged = match location {
+            TagId::Pointer { .. } if tag_id > 0 => format!("data | {tag_id}"),
+            TagId::Data { offset, size } => {
+                let data_view_type = discriminant_data_view_type(size);
+
+                line(
+                    write,
+                    3,
+                    &format!(
+                        "roc.view.set{data_view_type}({}, {tag_id}, true);",
+                        at("data", offset)
+                    ),
+                );
+
+                "data".to_string()
+            }
+            TagId::Pointer { .. } | TagId::Implicit => "data".to_string(),
+        };
+
+        line(
+            write,
+            3,
+            &format!("roc.view.setUint32(ptr, {tagged}, true);"),
+        );
+        line(write, 3, "break;");
+        line(write, 2, "}");
+    }
+
+    line(write, 2, "default:");
+    line(write, 3, &format!("throw invalidTag({name}, value.tag);"));
+    line(write, 1, "}");
+}
+
+fn add_tuple_converters(elements: &[(usize, TypeId, u32)], read: &mut String, write: &mut String) {
+    line(read, 1, "return [");
+
+    for (index, field, offset) in elements {
+        line(
+            read,
+            2,
+            &format!(
+                "read{}(roc, {}, consume),",
+                field.index(),
+                at("ptr", *offset)
+            ),
+        );
+        line(
+            write,
+            1,
+            &format!(
+                "write{}(roc, {}, value[{index}]);",
+                field.index(),
+                at("ptr", *offset)
+            ),
+        );
+    }
+
+    line(read, 1, "];");
+}
+
+fn add_unsupported(description: &str, read: &mut String, write: &mut String) {
+    let call = format!("unsupported({});", js_string(description));
+
+    line(read, 1, &call);
+    line(write, 1, &call);
+}
+
+/// The JS object for a tag, e.g. `{ tag: "Foo", payload: read3(roc, ptr, consume) }`
+fn variant(tag: &str, payload: Option<TypeId>, ptr: &str) -> String {
+    match payload {
+        Some(payload) => format!(
+            "{{ tag: {}, payload: read{}(roc, {ptr}, consume) }}",
+            js_string(tag),
+            payload.index()
+        ),
+        None => format!("{{ tag: {} }}", js_string(tag)),
+    }
+}
+
+fn ts_type(types: &Types, id: TypeId) -> String {
+    match types.get_type(id) {
+        RocType::RocStr => "string".to_string(),
+        RocType::Bool => "boolean".to_string(),
+        RocType::RocResult(ok, err) => {
+            format!(
+                "RocResult<{}, {}>",
+                ts_type(types, *ok),
+                ts_type(types, *err)
+            )
+        }
+        RocType::Num(RocNum::Dec) => "RocDec".to_string(),
+        RocType::Num(num) => match data_view_type(*num) {
+            Some("BigInt64" | "BigUint64") | None => "bigint".to_string(),
+            Some(_) => "number".to_string(),
+        },
+        RocType::RocList(elem) => format!("Array<{}>", ts_type(types, *elem)),
+        RocType::RocDict(key, value) => {
+            format!("Map<{}, {}>", ts_type(types, *key), ts_type(types, *value))
+        }
+        RocType::RocSet(elem) => format!("Set<{}>", ts_type(types, *elem)),
+        RocType::RocBox(inner) | RocType::RecursivePointer(inner) => ts_type(types, *inner),
+        RocType::TagUnion(union) => tag_union_name(union).to_string(),
+        RocType::Struct { name, .. } | RocType::TagUnionPayload { name, .. } => name.clone(),
+        RocType::EmptyTagUnion | RocType::Function(_) => "never".to_string(),
+        RocType::Unit => "{}".to_string(),
+        RocType::Unsized => "unknown".to_string(),
+    }
+}
+
+fn ts_variants(types: &Types, union: &RocTagUnion) -> Vec<(String, Option<String>)> {
+    let ts_payload = |payload: &Option<TypeId>| payload.map(|payload| ts_type(types, payload));
+
+    match union {
+        RocTagUnion::Enumeration { tags, .. } => {
+            tags.iter().map(|tag| (tag.clone(), None)).collect()
+        }
+        RocTagUnion::NonRecursive { tags, .. }
+        | RocTagUnion::Recursive { tags, .. }
+        | RocTagUnion::NullableWrapped { tags, .. } => tags
+            .iter()
+            .map(|(tag, payload)| (tag.clone(), ts_payload(payload)))
+            .collect(),
+        RocTagUnion::NonNullableUnwrapped {
+            tag_name, payload, ..
+        } => vec![(tag_name.clone(), Some(ts_type(types, *payload)))],
+        RocTagUnion::SingleTagStruct {
+            tag_name, payload, ..
+        } => {
+            let fields: Vec<TypeId> = match payload {
+                RocSingleTagPayload::HasNoClosure { payload_fields } => payload_fields.clone(),
+                RocSingleTagPayload::HasClosure { payload_getters } => {
+                    payload_getters.iter().map(|(field, _)| *field).collect()
+                }
+            };
+            let elements: Vec<String> = fields.iter().map(|field| ts_type(types, *field)).collect();
+            let payload = (!elements.is_empty()).then(|| format!("[{}]", elements.join(", ")));
+
+            vec![(tag_name.clone(), payload)]
+        }
+        RocTagUnion::NullableUnwrapped {
+            null_tag,
+            non_null_tag,
+            non_null_payload,
+            null_represents_first_tag,
+            ..
+        } => {
+            let null = (null_tag.clone(), None);
+            let non_null = (
+                non_null_tag.clone(),
+                Some(ts_type(types, *non_null_payload)),
+            );
+
+            if *null_represents_first_tag {
+                vec![null, non_null]
+            } else {
+                vec![non_null, null]
+            }
+        }
+    }
+}
+
+fn tag_union_name(union: &RocTagUnion) -> &str {
+    match union {
+        RocTagUnion::Enumeration { name, .. }
+        | RocTagUnion::NonRecursive { name, .. }
+        | RocTagUnion::Recursive { name, .. }
+        | RocTagUnion::NonNullableUnwrapped { name, .. }
+        | RocTagUnion::SingleTagStruct { name, .. }
+        | RocTagUnion::NullableWrapped { name, .. }
+        | RocTagUnion::NullableUnwrapped { name, .. } => name,
+    }
+}
+
+fn struct_fields(fields: &RocStructFields) -> Vec<(&str, TypeId)> {
+    match fields {
+        RocStructFields::HasNoClosure { fields } => fields
+            .iter()
+            .map(|(label, field)| (label.as_str(), *field))
+            .collect(),
+        RocStructFields::HasClosure { fields } => fields
+            .iter()
+            .map(|(label, field, _)| (label.as_str(), *field))
+            .collect(),
+    }
+}
+
+/// Tag payload fields are labeled with their position in the tag, but sorted in memory order.
+fn tuple_fields(fields: &RocStructFields) -> Vec<TypeId> {
+    let mut fields = struct_fields(fields);
+
+    fields.sort_by_key(|(label, _)| tuple_index(label));
+
+    fields.into_iter().map(|(_, field)| field).collect()
+}
+
+fn tuple_index(label: &str) -> usize {
+    label
+        .parse()
+        .expect("tag payload fields are labeled with their position")
+}
+
+/// Offsets of fields laid out one after another, each aligned to its own alignment
+fn field_offsets(types: &Types, fields: impl Iterator<Item = TypeId>) -> Vec<u32> {
+    let mut offset = 0;
+
+    fields
+        .map(|field| {
+            let field_offset = round_up_to_alignment(offset, types.align(field));
+
+            offset = field_offset + size(types, field);
+
+            field_offset
+        })
+        .collect()
+}
+
+/// [Types] gives the unit type a size of 1, but it takes up no space in Roc's memory.
+fn size(types: &Types, id: TypeId) -> u32 {
+    match types.get_type(id) {
+        RocType::Unit => 0,
+        _ => types.size_rounded_to_alignment(id),
+    }
+}
+
+fn entry_point_signature(types: &Types, id: TypeId) -> (&[TypeId], TypeId) {
+    match types.get_type(id) {
+        RocType::Function(roc_fn) => (roc_fn.args.as_slice(), roc_fn.ret),
+        _ => (&[], id),
+    }
+}
+
+fn exposed_symbol(entry_point: &str) -> String {
+    format!("roc__{entry_point}_1_exposed_generic")
+}
+
+/// The `DataView` getter and setter suffix for numbers that fit in a JS number or bigint
+fn data_view_type(num: RocNum) -> Option<&'static str> {
+    match num {
+        RocNum::I8 => Some("Int8"),
+        RocNum::U8 => Some("Uint8"),
+        RocNum::I16 => Some("Int16"),
+        RocNum::U16 => Some("Uint16"),
+        RocNum::I32 => Some("Int32"),
+        RocNum::U32 => Some("Uint32"),
+        RocNum::I64 => Some("BigInt64"),
+        RocNum::U64 => Some("BigUint64"),
+        RocNum::F32 => Some("Float32"),
+        RocNum::F64 => Some("Float64"),
+        RocNum::I128 | RocNum::U128 | RocNum::Dec => None,
+    }
+}
+
+fn discriminant_data_view_type(size: u32) -> &'static str {
+    match size {
+        0 | 1 => "Uint8",
+        2 => "Uint16",
+        _ => "Uint32",
+    }
+}
+
+fn at(ptr: &str, offset: u32) -> String {
+    if offset == 0 {
+        ptr.to_string()
+    } else {
+        format!("{ptr} + {offset}")
+    }
+}
+
+/// Tag and field names are identifiers, so Rust's escaping is valid JS too.
+fn js_string(text: &str) -> String {
+    format!("{:?}", text)
+}
+
+fn line(buf: &mut String, depth: usize, text: &str) {
+    for _ in 0..depth {
+        buf.push_str(INDENT);
+    }
+
+    buf.push_str(text);
+    buf.push('\n');
+}
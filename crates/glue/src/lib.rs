@@ -1,8 +1,10 @@
 //! Generates code needed for platform hosts to communicate with Roc apps.
 //! This tool is not necessary for writing a platform in another language,
-//! however, it's a great convenience! Currently supports Rust platforms, and
-//! the plan is to support any language via a plugin model.
+//! however, it's a great convenience! Currently supports Rust platforms and
+//! JavaScript hosts of Wasm apps, and the plan is to support any language via
+//! a plugin model.
 pub mod enums;
+pub mod js_glue;
 pub mod load;
 pub mod roc_type;
 pub mod rust_glue;
@@ -12,7 +14,7 @@ pub mod types;
 #[rustfmt::skip]
 pub mod glue;
 
-pub use load::generate;
+pub use load::{generate, generate_js};
 
 // required because we use roc_std here
 mod roc_externs {
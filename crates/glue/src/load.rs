@@ -1,5 +1,6 @@
+use crate::js_glue;
 use crate::roc_type;
-use crate::types::Types;
+use crate::types::{self, Types};
 use bumpalo::Bump;
 use libloading::Library;
 use roc_build::{
@@ -10,10 +11,13 @@ use roc_build::{
     },
 };
 use roc_collections::MutMap;
+use roc_gen_wasm::WasmFeatures;
 use roc_load::{ExecutionMode, LoadConfig, LoadedModule, LoadingProblem, Threading};
 use roc_mono::ir::{generate_glue_procs, GlueProc, OptLevel};
 use roc_mono::layout::{GlobalLayoutInterner, LayoutCache, LayoutInterner};
+use roc_mono::overflow::OverflowMode;
 use roc_packaging::cache::{self, RocCacheDir};
+use roc_problem::lint::SeverityConfig;
 use roc_reporting::report::{RenderTarget, DEFAULT_PALETTE};
 use roc_target::{Architecture, TargetInfo};
 use roc_types::subs::{Subs, Variable};
@@ -54,6 +58,20 @@ pub fn generate(
                 backend,
                 opt_level: OptLevel::Development,
                 emit_debug_info: false,
+                trace_calls: false,
+                arena_allocation: false,
+                overflow: OverflowMode::Panic,
+                heap_profile: false,
+                stack_traces: false,
+                stack_limit_checks: false,
+                sanitizer_friendly: false,
+                emit_host_layouts: false,
+                emit_call_graph: false,
+                emit_c_header: false,
+                emit_llvm_ir: false,
+                profile_generate: false,
+                profile_use: false,
+                wasi_hooks: false,
             };
 
             let load_config = standard_load_config(
@@ -76,12 +94,15 @@ pub fn generate(
                 spec_path.to_path_buf(),
                 code_gen_options,
                 false,
+                None,
                 link_type,
                 linking_strategy,
                 true,
                 None,
+                WasmFeatures::default(),
                 RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
                 load_config,
+                &SeverityConfig::default(),
             );
 
             match res_binary_path {
@@ -90,6 +111,7 @@ pub fn generate(
                     problems,
                     total_time,
                     expect_metadata: _,
+                    inputs: _,
                 }) => {
                     // TODO: Should binary_path be update to deal with extensions?
                     use target_lexicon::OperatingSystem;
@@ -142,47 +164,12 @@ pub fn generate(
 
                         process::exit(1);
                     });
-                    for roc_type::File { name, content } in &files {
-                        let valid_name = PathBuf::from(name.as_str())
-                            .components()
-                            .all(|comp| matches!(comp, Component::CurDir | Component::Normal(_)));
-                        if !valid_name {
-                            eprintln!("File name was invalid: {}", &name);
-
-                            process::exit(1);
-                        }
-                        let full_path = output_path.join(name.as_str());
-                        if let Some(dir_path) = full_path.parent() {
-                            std::fs::create_dir_all(dir_path).unwrap_or_else(|err| {
-                                eprintln!(
-                                    "Unable to create output directory {} - {:?}",
-                                    dir_path.display(),
-                                    err
-                                );
-
-                                process::exit(1);
-                            });
-                        }
-                        let mut file = File::create(&full_path).unwrap_or_else(|err| {
-                            eprintln!(
-                                "Unable to create output file {} - {:?}",
-                                full_path.display(),
-                                err
-                            );
-
-                            process::exit(1);
-                        });
-
-                        file.write_all(content.as_bytes()).unwrap_or_else(|err| {
-                            eprintln!(
-                                "Unable to write bindings to output file {} - {:?}",
-                                full_path.display(),
-                                err
-                            );
-
-                            process::exit(1);
-                        });
-                    }
+                    write_files(
+                        output_path,
+                        files.iter().map(|roc_type::File { name, content }| {
+                            (name.as_str(), content.as_str())
+                        }),
+                    );
 
                     println!(
                         "🎉 Generated type declarations in:\n\n\t{}",
@@ -191,26 +178,106 @@ pub fn generate(
 
                     Ok(0)
                 }
-                Err(BuildFileError::ErrorModule { module, total_time }) => {
-                    handle_error_module(module, total_time, spec_path.as_os_str(), true)
-                }
+                Err(BuildFileError::ErrorModule { module, total_time }) => handle_error_module(
+                    module,
+                    total_time,
+                    spec_path.as_os_str(),
+                    true,
+                    &SeverityConfig::default(),
+                ),
                 Err(BuildFileError::LoadingProblem(problem)) => handle_loading_problem(problem),
             }
         }
-        Err(err) => match err.kind() {
-            ErrorKind::NotFound => {
-                eprintln!("Platform module file not found: {}", input_path.display());
-                process::exit(1);
-            }
-            error => {
+        Err(err) => exit_with_load_error(input_path, err),
+    }
+}
+
+/// Generates glue for a Wasm app with the built-in JS generator, instead of a glue spec.
+pub fn generate_js(input_path: &Path, output_path: &Path) -> io::Result<i32> {
+    match load_types(
+        input_path.to_path_buf(),
+        Threading::AllAvailable,
+        IgnoreErrors::NONE,
+    ) {
+        Ok(types) => {
+            let files = js_glue::emit(&types);
+
+            write_files(
+                output_path,
+                files
+                    .iter()
+                    .map(|types::File { name, content }| (name.as_str(), content.as_str())),
+            );
+
+            println!(
+                "🎉 Generated type declarations in:\n\n\t{}",
+                output_path.display()
+            );
+
+            Ok(0)
+        }
+        Err(err) => exit_with_load_error(input_path, err),
+    }
+}
+
+fn exit_with_load_error(input_path: &Path, err: io::Error) -> ! {
+    match err.kind() {
+        ErrorKind::NotFound => {
+            eprintln!("Platform module file not found: {}", input_path.display());
+            process::exit(1);
+        }
+        error => {
+            eprintln!(
+                "Error loading platform module file {} - {:?}",
+                input_path.display(),
+                error
+            );
+            process::exit(1);
+        }
+    }
+}
+
+fn write_files<'a>(output_path: &Path, files: impl IntoIterator<Item = (&'a str, &'a str)>) {
+    for (name, content) in files {
+        let valid_name = PathBuf::from(name)
+            .components()
+            .all(|comp| matches!(comp, Component::CurDir | Component::Normal(_)));
+        if !valid_name {
+            eprintln!("File name was invalid: {}", name);
+
+            process::exit(1);
+        }
+        let full_path = output_path.join(name);
+        if let Some(dir_path) = full_path.parent() {
+            std::fs::create_dir_all(dir_path).unwrap_or_else(|err| {
                 eprintln!(
-                    "Error loading platform module file {} - {:?}",
-                    input_path.display(),
-                    error
+                    "Unable to create output directory {} - {:?}",
+                    dir_path.display(),
+                    err
                 );
+
                 process::exit(1);
-            }
-        },
+            });
+        }
+        let mut file = File::create(&full_path).unwrap_or_else(|err| {
+            eprintln!(
+                "Unable to create output file {} - {:?}",
+                full_path.display(),
+                err
+            );
+
+            process::exit(1);
+        });
+
+        file.write_all(content.as_bytes()).unwrap_or_else(|err| {
+            eprintln!(
+                "Unable to write bindings to output file {} - {:?}",
+                full_path.display(),
+                err
+            );
+
+            process::exit(1);
+        });
     }
 }
 
@@ -354,6 +421,12 @@ pub fn load_types(
             palette: DEFAULT_PALETTE,
             threading,
             exec_mode: ExecutionMode::Check,
+            max_specializations: None,
+            coverage: false,
+            fold_constants: false,
+            hoist_loop_invariants: false,
+            strip_expects: false,
+            cancelled: None,
         },
     )
     .unwrap_or_else(|problem| match problem {
@@ -398,6 +471,7 @@ pub fn load_types(
         let target_info = TargetInfo {
             architecture,
             operating_system,
+            pointer_tagging: true,
         };
         let mut layout_cache = LayoutCache::new(layout_interner.fork(), target_info);
         let mut glue_procs_by_layout = MutMap::default();
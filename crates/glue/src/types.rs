@@ -44,6 +44,11 @@ impl TypeId {
 
     /// When adding, we check for overflow based on whether we've exceeded this.
     const MAX: Self = Self(Self::PENDING.0 - 1);
+
+    /// For generators that name things after the type they're for, e.g. `read3`
+    pub(crate) fn index(self) -> usize {
+        self.0
+    }
 }
 
 // TODO: remove this and instead generate directly into roc_type::Types
@@ -0,0 +1,36 @@
+// ⚠️ GENERATED CODE ⚠️ - this entire file was generated by the `roc glue --lang=js` CLI command
+
+/** Thrown when the Roc app crashes, with the message it crashed with. */
+export class RocPanic extends Error {}
+
+/** Reads and writes Roc values in the memory of a running Roc app, for effect handlers. */
+export class RocMemory {
+    readonly exports: WebAssembly.Exports;
+    readonly view: DataView;
+    readonly bytes: Uint8Array;
+    alloc(size: number, align: number): number;
+    dealloc(ptr: number, align: number): void;
+    allocRefcounted(size: number, align: number): number;
+    readStr(ptr: number, consume: boolean): string;
+    writeStr(ptr: number, text: string): void;
+    readI128(ptr: number, signed: boolean): bigint;
+    writeI128(ptr: number, value: bigint): void;
+}
+
+/** A handler for one of the app's `roc_fx_<name>` imports, called with its raw Wasm arguments. */
+export type EffectHandler = (roc: RocMemory, ...args: any[]) => unknown;
+
+export interface LoadOptions {
+    effects?: Record<string, EffectHandler>;
+    imports?: WebAssembly.Imports;
+}
+
+export type RocResult<T, E> = { tag: "Ok"; payload: T } | { tag: "Err"; payload: E };
+
+/** `Dec` values are the underlying fixed-point integer, scaled by 10^18. */
+export type RocDec = bigint;
+
+export function load(
+    source: WebAssembly.Module | BufferSource | Response | PromiseLike<Response>,
+    options?: LoadOptions
+): Promise<RocApp>;
@@ -0,0 +1,378 @@
+// ⚠️ GENERATED CODE ⚠️ - this entire file was generated by the `roc glue --lang=js` CLI command
+
+// Roc's refcounts count up from the most negative i32; 0 means the data is a constant that
+// must never be freed.
+const REFCOUNT_ONE = -0x80000000;
+const REFCOUNT_READONLY = 0;
+const SEAMLESS_SLICE_BIT = 0x80000000;
+const SMALL_STR_BIT = 0x80;
+const PTR_WIDTH = 4;
+const STR_SIZE = 12;
+
+const jspi =
+    typeof WebAssembly.Suspending === "function" && typeof WebAssembly.promising === "function";
+
+const encoder = new TextEncoder();
+const decoder = new TextDecoder();
+
+/** Thrown when the Roc app crashes, with the message it crashed with. */
+export class RocPanic extends Error {
+    constructor(message) {
+        super(message);
+        this.name = "RocPanic";
+    }
+}
+
+/**
+ * Reads and writes Roc values in the memory of a running Roc app.
+ *
+ * Reading with `consume` set gives up one reference to the value, freeing it once nothing else
+ * refers to it; that's how values Roc hands over (like the results of entry points) get freed.
+ * Writing always creates new values with a single reference, for Roc to take ownership of.
+ */
+export class RocMemory {
+    constructor() {
+        this.exports = null;
+    }
+
+    // The memory's buffer is replaced whenever it grows, so never hold on to these.
+    get view() {
+        return new DataView(this.exports.memory.buffer);
+    }
+
+    get bytes() {
+        return new Uint8Array(this.exports.memory.buffer);
+    }
+
+    alloc(size, align) {
+        const ptr = this.exports.roc_alloc(Math.max(size, 1), align);
+
+        if (ptr === 0) {
+            throw new RocPanic(`Roc ran out of memory allocating ${size} bytes`);
+        }
+
+        return ptr;
+    }
+
+    dealloc(ptr, align) {
+        this.exports.roc_dealloc(ptr, align);
+    }
+
+    /** Allocates `size` bytes with a refcount of one in front of them. */
+    allocRefcounted(size, align) {
+        const header = Math.max(align, PTR_WIDTH);
+        const data = this.alloc(header + size, Math.max(align, PTR_WIDTH)) + header;
+
+        this.view.setInt32(data - PTR_WIDTH, REFCOUNT_ONE, true);
+
+        return data;
+    }
+
+    /**
+     * Calls `readContents` on the refcounted data at `data`. When consuming the last reference,
+     * the contents are consumed too and the allocation is freed afterwards.
+     */
+    readRefcounted(data, align, consume, readContents) {
+        if (!consume) {
+            return readContents(false);
+        }
+
+        const refcount = this.view.getInt32(data - PTR_WIDTH, true);
+
+        if (refcount === REFCOUNT_ONE) {
+            const value = readContents(true);
+            const header = Math.max(align, PTR_WIDTH);
+
+            this.dealloc(data - header, Math.max(align, PTR_WIDTH));
+
+            return value;
+        }
+
+        if (refcount !== REFCOUNT_READONLY) {
+            this.view.setInt32(data - PTR_WIDTH, refcount - 1, true);
+        }
+
+        return readContents(false);
+    }
+
+    /** Scratch space for the arguments and result of one call into Roc. */
+    scratch() {
+        const allocations = [];
+
+        return {
+            alloc: (size, align) => {
+                const ptr = this.alloc(size, align);
+
+                allocations.push([ptr, align]);
+
+                return ptr;
+            },
+            free: () => {
+                for (const [ptr, align] of allocations) {
+                    this.dealloc(ptr, align);
+                }
+            },
+        };
+    }
+
+    readStr(ptr, consume) {
+        const view = this.view;
+        const lastByte = view.getUint8(ptr + STR_SIZE - 1);
+
+        if (lastByte & SMALL_STR_BIT) {
+            const len = lastByte & ~SMALL_STR_BIT;
+
+            return decoder.decode(this.bytes.slice(ptr, ptr + len));
+        }
+
+        const elements = view.getUint32(ptr, true);
+        const lenField = view.getUint32(ptr + PTR_WIDTH, true);
+        const capacityField = view.getUint32(ptr + 2 * PTR_WIDTH, true);
+        const len = lenField & ~SEAMLESS_SLICE_BIT;
+        const text = decoder.decode(this.bytes.slice(elements, elements + len));
+
+        // a seamless slice keeps the start of its allocation, shifted right by one, in its capacity
+        const allocation =
+            lenField & SEAMLESS_SLICE_BIT ? (capacityField << 1) >>> 0 : capacityField && elements;
+
+        if (allocation !== 0) {
+            this.readRefcounted(allocation, PTR_WIDTH, consume, () => {});
+        }
+
+        return text;
+    }
+
+    writeStr(ptr, text) {
+        const encoded = encoder.encode(text);
+        const len = encoded.length;
+
+        if (len < STR_SIZE) {
+            const bytes = this.bytes;
+
+            bytes.fill(0, ptr, ptr + STR_SIZE);
+            bytes.set(encoded, ptr);
+            bytes[ptr + STR_SIZE - 1] = len | SMALL_STR_BIT;
+
+            return;
+        }
+
+        const elements = this.allocRefcounted(len, PTR_WIDTH);
+
+        this.bytes.set(encoded, elements);
+        this.writeListFields(ptr, elements, len);
+    }
+
+    readList(ptr, elementSize, elementAlign, consume, readElement) {
+        const view = this.view;
+        const elements = view.getUint32(ptr, true);
+        const len = view.getUint32(ptr + PTR_WIDTH, true);
+        const capacityField = view.getUint32(ptr + 2 * PTR_WIDTH, true);
+
+        // a seamless slice keeps the start of its allocation, shifted right by one, in its capacity
+        const allocation =
+            capacityField & SEAMLESS_SLICE_BIT ? (capacityField << 1) >>> 0 : capacityField && elements;
+
+        const readElements = (consume) => {
+            const items = new Array(len);
+
+            for (let index = 0; index < len; index++) {
+                items[index] = readElement(elements + index * elementSize, consume);
+            }
+
+            return items;
+        };
+
+        if (allocation === 0) {
+            return readElements(false);
+        }
+
+        return this.readRefcounted(allocation, elementAlign, consume, readElements);
+    }
+
+    writeList(ptr, items, elementSize, elementAlign, writeElement) {
+        if (items.length === 0) {
+            this.writeListFields(ptr, 0, 0);
+
+            return;
+        }
+
+        const elements = this.allocRefcounted(items.length * elementSize, elementAlign);
+
+        items.forEach((item, index) => writeElement(elements + index * elementSize, item));
+        this.writeListFields(ptr, elements, items.length);
+    }
+
+    writeListFields(ptr, elements, len) {
+        const view = this.view;
+
+        view.setUint32(ptr, elements, true);
+        view.setUint32(ptr + PTR_WIDTH, len, true);
+        view.setUint32(ptr + 2 * PTR_WIDTH, len, true);
+    }
+
+    readBox(ptr, align, consume, readInner) {
+        const data = this.view.getUint32(ptr, true);
+
+        return this.readRefcounted(data, align, consume, (consume) => readInner(data, consume));
+    }
+
+    writeBox(ptr, value, size, align, writeInner) {
+        const data = this.allocRefcounted(size, align);
+
+        writeInner(data, value);
+        this.view.setUint32(ptr, data, true);
+    }
+
+    readI128(ptr, signed) {
+        const view = this.view;
+        const low = view.getBigUint64(ptr, true);
+        const high = signed ? view.getBigInt64(ptr + 8, true) : view.getBigUint64(ptr + 8, true);
+
+        return (high << 64n) | low;
+    }
+
+    writeI128(ptr, value) {
+        const view = this.view;
+
+        view.setBigUint64(ptr, BigInt.asUintN(64, value), true);
+        view.setBigUint64(ptr + 8, BigInt.asUintN(64, value >> 64n), true);
+    }
+}
+
+function tagIndex(typeName, tags, tag) {
+    const index = tags.indexOf(tag);
+
+    if (index === -1) {
+        throw invalidTag(typeName, tag);
+    }
+
+    return index;
+}
+
+function invalidTag(typeName, tag) {
+    return new TypeError(`${JSON.stringify(tag)} is not a tag of ${typeName}`);
+}
+
+function invalidTagId(typeName, tagId) {
+    return new Error(`Roc returned a ${typeName} with the invalid tag id ${tagId}`);
+}
+
+function unsupported(description) {
+    throw new TypeError(`JS glue does not support ${description} yet`);
+}
+
+async function compile(source) {
+    source = await source;
+
+    if (source instanceof WebAssembly.Module) {
+        return source;
+    }
+
+    if (typeof Response === "function" && source instanceof Response) {
+        return WebAssembly.compileStreaming(source);
+    }
+
+    return WebAssembly.compile(source);
+}
+
+// Catch glue that was generated for a different version of the app before calling into it.
+function checkMetadata(module) {
+    const sections = WebAssembly.Module.customSections(module, "roc_metadata");
+
+    if (sections.length === 0) {
+        return;
+    }
+
+    const metadata = JSON.parse(decoder.decode(sections[0]));
+    const exposed = new Set(metadata.procs.map((proc) => `${proc.exposed_symbol}_generic`));
+
+    for (const symbol of EXPOSED_SYMBOLS) {
+        if (!exposed.has(symbol)) {
+            throw new Error(
+                `This glue expects the Roc app to expose ${symbol}, but it doesn't. Was the glue generated for a different version of the app?`
+            );
+        }
+    }
+}
+
+// Without JSPI, effects run synchronously, so there is nothing to wait for if they return a promise.
+function effectImport(roc, name, handler) {
+    if (jspi) {
+        return new WebAssembly.Suspending((...args) => handler(roc, ...args));
+    }
+
+    return (...args) => {
+        const result = handler(roc, ...args);
+
+        if (result instanceof Promise) {
+            throw new Error(
+                `The ${name} effect returned a Promise, but this JavaScript engine does not support JSPI, so Roc cannot wait for it.`
+            );
+        }
+
+        return result;
+    };
+}
+
+/**
+ * Instantiates a Roc app compiled to Wasm.
+ *
+ * `source` can be a `WebAssembly.Module`, the bytes of one, or a `fetch` response.
+ * `effects` has a handler for each `roc_fx_<name>` function the app imports, keyed by `<name>`.
+ * Handlers are called with a `RocMemory` followed by the raw Wasm arguments, and may be async
+ * where the engine supports JSPI. `imports` are passed on to the app as extra Wasm imports.
+ */
+export async function load(source, { effects = {}, imports = {} } = {}) {
+    const module = await compile(source);
+
+    checkMetadata(module);
+
+    const roc = new RocMemory();
+    const env = { ...imports.env };
+
+    for (const { module: importModule, name } of WebAssembly.Module.imports(module)) {
+        if (importModule !== "env" || name in env) {
+            continue;
+        }
+
+        if (name.startsWith("roc_fx_")) {
+            const effect = name.slice("roc_fx_".length);
+
+            if (typeof effects[effect] !== "function") {
+                throw new Error(`The Roc app needs a handler for the ${effect} effect`);
+            }
+
+            env[name] = effectImport(roc, effect, effects[effect]);
+        } else if (name === "roc_panic") {
+            env[name] = (message) => {
+                throw new RocPanic(roc.readStr(message, false));
+            };
+        }
+    }
+
+    const instance = await WebAssembly.instantiate(module, { ...imports, env });
+
+    roc.exports = instance.exports;
+
+    for (const name of ["memory", "roc_alloc", "roc_dealloc"]) {
+        if (!(name in roc.exports)) {
+            throw new Error(`JS glue needs the Roc app's platform to export ${name}`);
+        }
+    }
+
+    if (typeof roc.exports._initialize === "function") {
+        roc.exports._initialize();
+    }
+
+    const callExport = (symbol) => {
+        const exported = roc.exports[symbol];
+
+        if (typeof exported !== "function") {
+            throw new Error(`The Roc app does not export ${symbol}`);
+        }
+
+        return jspi ? WebAssembly.promising(exported) : exported;
+    };
+
+    return makeApp(roc, callExport);
+}
@@ -0,0 +1,115 @@
+#[macro_use]
+extern crate indoc;
+
+mod helpers;
+
+#[cfg(test)]
+mod test_gen_js {
+    use crate::helpers::generate_js_bindings;
+    use roc_glue::js_glue::{DECLARATIONS_PRELUDE, PRELUDE};
+    use roc_glue::types::File;
+
+    fn bindings(module: &str) -> (String, String) {
+        match generate_js_bindings(module).as_slice() {
+            [File {
+                name: js_name,
+                content: js,
+            }, File {
+                name: ts_name,
+                content: ts,
+            }] => {
+                assert_eq!(js_name, "roc_app.js");
+                assert_eq!(ts_name, "roc_app.d.ts");
+                assert!(js.starts_with(PRELUDE));
+                assert!(ts.starts_with(DECLARATIONS_PRELUDE));
+
+                (js.clone(), ts[DECLARATIONS_PRELUDE.len()..].to_string())
+            }
+            files => panic!("expected roc_app.js and roc_app.d.ts, got {:?}", files),
+        }
+    }
+
+    #[test]
+    fn basic_record_aliased() {
+        let module = indoc!(
+            r#"
+            MyRcd : { a : U64, b : I128 }
+
+            main : MyRcd
+            main = { a: 1u64, b: 2i128 }
+            "#
+        );
+
+        let (js, ts) = bindings(module);
+
+        assert!(js.contains(r#"const EXPOSED_SYMBOLS = ["roc__main_1_exposed_generic"];"#));
+        assert!(js.contains(r#"const mainExport = callExport("roc__main_1_exposed_generic");"#));
+        assert!(js.contains("return roc.readI128(ptr, true);"));
+        assert!(js.contains("roc.view.setBigUint64(ptr, value, true);"));
+        assert_eq!(
+            ts,
+            indoc!(
+                r#"
+
+                export interface MyRcd {
+                    b: bigint;
+                    a: bigint;
+                }
+
+                export interface RocApp {
+                    main(): Promise<MyRcd>;
+                }
+                "#
+            )
+        );
+    }
+
+    #[test]
+    fn tag_union_enumeration() {
+        let module = indoc!(
+            r#"
+            Enumeration : [Blah, Foo, Bar,]
+
+            main : Enumeration
+            main = Foo
+            "#
+        );
+
+        let (js, ts) = bindings(module);
+
+        assert!(js.contains(r#"const tags = ["Bar", "Blah", "Foo"];"#));
+        assert!(js.contains(
+            r#"roc.view.setUint8(ptr, tagIndex("Enumeration", ["Bar", "Blah", "Foo"], value), true);"#
+        ));
+        assert_eq!(
+            ts,
+            indoc!(
+                r#"
+
+                export type Enumeration = "Bar" | "Blah" | "Foo";
+
+                export interface RocApp {
+                    main(): Promise<Enumeration>;
+                }
+                "#
+            )
+        );
+    }
+
+    #[test]
+    fn function_with_str_argument() {
+        let module = indoc!(
+            r#"
+            main : Str, U8 -> List Str
+            main = \name, count -> List.repeat name (Num.toNat count)
+            "#
+        );
+
+        let (js, ts) = bindings(module);
+
+        assert!(js.contains("async main(arg0, arg1) {"));
+        assert!(js.contains("const arg0Ptr = scratch.alloc(12, 4);"));
+        assert!(js.contains("await mainExport(result, arg0Ptr, arg1);"));
+        assert!(ts.contains("main(arg0: string, arg1: number): Promise<Array<string>>;"));
+    }
+}
@@ -1,5 +1,6 @@
 use roc_glue::load::{load_types, IgnoreErrors};
-use roc_glue::rust_glue;
+use roc_glue::types::Types;
+use roc_glue::{js_glue, rust_glue};
 use roc_load::Threading;
 use std::env;
 use std::fs::File;
@@ -8,6 +9,16 @@ use std::path::PathBuf;
 
 #[allow(dead_code)]
 pub fn generate_bindings(decl_src: &str) -> Vec<roc_glue::types::File> {
+    rust_glue::emit(&load_platform_types(decl_src))
+}
+
+#[allow(dead_code)]
+pub fn generate_js_bindings(decl_src: &str) -> Vec<roc_glue::types::File> {
+    js_glue::emit(&load_platform_types(decl_src))
+}
+
+#[allow(dead_code)]
+fn load_platform_types(decl_src: &str) -> Vec<Types> {
     use tempfile::tempdir;
 
     let mut src = indoc!(
@@ -25,7 +36,7 @@ pub fn generate_bindings(decl_src: &str) -> Vec<roc_glue::types::File> {
 
     src.push_str(decl_src);
 
-    let types = {
+    {
         let dir = tempdir().expect("Unable to create tempdir");
         let filename = PathBuf::from("platform.roc");
         let file_path = dir.path().join(filename);
@@ -43,9 +54,7 @@ pub fn generate_bindings(decl_src: &str) -> Vec<roc_glue::types::File> {
         dir.close().expect("Unable to close tempdir");
 
         result.expect("had problems loading")
-    };
-
-    rust_glue::emit(&types)
+    }
 }
 
 #[allow(dead_code)]
@@ -0,0 +1,538 @@
+//! Answers editor queries by running the same load and type-check pipeline as `roc check`.
+use std::path::{Path, PathBuf};
+
+use bumpalo::Bump;
+use roc_can::expr::{Declarations, Expr};
+use roc_can::pattern::Pattern;
+use roc_can::references::ReferenceKind;
+use roc_can::traverse::{find_declaration, Visitor};
+use roc_fmt::def::fmt_defs;
+use roc_fmt::module::fmt_module;
+use roc_fmt::Buf;
+use roc_load::{LoadedModule, LoadingProblem};
+use roc_module::symbol::{ModuleId, Symbol};
+use roc_packaging::cache::{self, RocCacheDir};
+use roc_parse::module::{self, module_defs};
+use roc_parse::parser::Parser;
+use roc_parse::state::State;
+use roc_problem::lint::SeverityConfig;
+use roc_problem::Severity;
+use roc_region::all::{LineInfo, Region};
+use roc_reporting::report::{
+    can_problem, type_problem, RenderTarget, RocDocAllocator, DEFAULT_PALETTE,
+};
+use roc_target::TargetInfo;
+use roc_types::pretty_print::{name_and_print_var, DebugPrint};
+use roc_types::subs::Variable;
+
+use crate::annotate::{missing_annotations, MissingAnnotation};
+use crate::code_actions::{self, Fix};
+use crate::rename::{self, RenameError};
+
+/// A zero-based line and UTF-16 column, as the Language Server Protocol counts them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub line: u32,
+    pub character: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub range: Range,
+    pub severity: Severity,
+    pub message: String,
+    pub fixes: Vec<Fix>,
+}
+
+/// A quick fix or refactoring, with its edit converted to the positions of the file it edits.
+#[derive(Debug, Clone)]
+pub struct CodeAction {
+    pub title: String,
+    /// The LSP code action kind, e.g. `quickfix`
+    pub kind: &'static str,
+    pub path: PathBuf,
+    pub range: Range,
+    pub new_text: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Location {
+    pub path: PathBuf,
+    pub range: Range,
+}
+
+/// The result of checking one version of an open document.
+pub struct Analysis {
+    pub diagnostics: Vec<Diagnostic>,
+    source: String,
+    loaded: Option<LoadedModule>,
+    missing_annotations: Vec<MissingAnnotation>,
+}
+
+impl Analysis {
+    /// Load and type-check a document, along with everything it imports.
+    /// Modules other than the document itself are read from disk.
+    pub fn new(path: &Path, source: String) -> Analysis {
+        let arena = Bump::new();
+        let src_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+        let result = roc_load::load_and_typecheck_str(
+            &arena,
+            path.to_path_buf(),
+            arena.alloc_str(&source),
+            src_dir,
+            TargetInfo::default_x86_64(),
+            RenderTarget::Generic,
+            RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
+            DEFAULT_PALETTE,
+        );
+
+        match result {
+            Ok(mut loaded) => {
+                let home = loaded.module_id;
+
+                Analysis {
+                    diagnostics: module_diagnostics(&mut loaded, home, &SeverityConfig::default()),
+                    missing_annotations: missing_annotations(&mut loaded),
+                    source,
+                    loaded: Some(loaded),
+                }
+            }
+            Err(problem) => Analysis {
+                diagnostics: vec![loading_diagnostic(problem)],
+                source,
+                loaded: None,
+                missing_annotations: Vec::new(),
+            },
+        }
+    }
+
+    /// The inferred type of the innermost expression or pattern at the given position.
+    pub fn hover(&mut self, position: Position) -> Option<(Range, String)> {
+        let offset = to_offset(&self.source, position)?;
+        let loaded = self.loaded.as_mut()?;
+        let home = loaded.module_id;
+        let decls = loaded.declarations_by_id.get(&home)?;
+        let found = innermost_at(decls, offset)?;
+        let var = found.var?;
+
+        let type_str = name_and_print_var(
+            var,
+            loaded.solved.inner_mut(),
+            home,
+            &loaded.interns,
+            DebugPrint::NOTHING,
+        );
+
+        Some((to_range(&self.source, found.region), type_str))
+    }
+
+    /// Where the symbol referenced at the given position is defined.
+    pub fn definition(&self, position: Position) -> Option<Location> {
+        let offset = to_offset(&self.source, position)?;
+        let loaded = self.loaded.as_ref()?;
+        let home = loaded.module_id;
+        let symbol = innermost_at(loaded.declarations_by_id.get(&home)?, offset)?.symbol?;
+        let module_id = symbol.module_id();
+        let decls = loaded.declarations_by_id.get(&module_id)?;
+        let region = find_declaration(symbol, decls)?.region();
+
+        let (path, source) = if module_id == home {
+            (loaded.sources.get(&home)?.0.clone(), self.source.as_str())
+        } else {
+            let (path, source) = loaded.sources.get(&module_id)?;
+
+            (path.clone(), &**source)
+        };
+
+        Some(Location {
+            path,
+            range: to_range(source, region),
+        })
+    }
+
+    /// Every place the symbol at the given position is used, in any loaded module, and also
+    /// where it is defined if `include_declaration` is set.
+    pub fn references(&self, position: Position, include_declaration: bool) -> Vec<Location> {
+        let (loaded, symbol) = match self.symbol_at(position) {
+            Some(found) => found,
+            None => return Vec::new(),
+        };
+
+        loaded
+            .references(symbol)
+            .into_iter()
+            .filter(|(_, reference)| include_declaration || reference.kind == ReferenceKind::Usage)
+            .filter_map(|(module_id, reference)| {
+                let (path, source) = loaded.sources.get(&module_id)?;
+
+                Some(Location {
+                    path: path.clone(),
+                    range: to_range(source, reference.region),
+                })
+            })
+            .collect()
+    }
+
+    fn symbol_at(&self, position: Position) -> Option<(&LoadedModule, Symbol)> {
+        let offset = to_offset(&self.source, position)?;
+        let loaded = self.loaded.as_ref()?;
+        let index = loaded.references_by_id.get(&loaded.module_id)?;
+        let symbol = index.symbol_at(roc_region::all::Position::new(offset))?;
+
+        Some((loaded, symbol))
+    }
+
+    /// The edits, by file, that rename the value referenced or defined at the given position.
+    pub fn rename(
+        &self,
+        position: Position,
+        new_name: &str,
+    ) -> Option<Result<Vec<(PathBuf, Vec<(Range, String)>)>, RenameError>> {
+        let offset = to_offset(&self.source, position)?;
+        let loaded = self.loaded.as_ref()?;
+        let decls = loaded.declarations_by_id.get(&loaded.module_id)?;
+        let symbol = innermost_at(decls, offset)?.symbol?;
+
+        let result = rename::rename(loaded, symbol, new_name).map(|files| {
+            files
+                .into_iter()
+                .filter_map(|file| {
+                    let (_, source) = loaded.sources.get(&file.module_id)?;
+                    let edits = file
+                        .edits
+                        .into_iter()
+                        .map(|edit| (to_range(source, edit.region), edit.new_text))
+                        .collect();
+
+                    Some((file.path, edits))
+                })
+                .collect()
+        });
+
+        Some(result)
+    }
+
+    /// The quick fixes for the diagnostics that overlap the given range, and annotations
+    /// with the inferred types of the unannotated top-level defs that overlap it.
+    pub fn code_actions(&self, range: Range) -> Vec<CodeAction> {
+        let loaded = match &self.loaded {
+            Some(loaded) => loaded,
+            None => return Vec::new(),
+        };
+        let overlaps = |other: Range| other.start <= range.end && range.start <= other.end;
+
+        let fixes = self
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| overlaps(diagnostic.range))
+            .flat_map(|diagnostic| diagnostic.fixes.iter())
+            .filter_map(|fix| {
+                let (_, source) = loaded.sources.get(&fix.module_id)?;
+
+                Some(CodeAction {
+                    title: fix.title.clone(),
+                    kind: "quickfix",
+                    path: fix.path.clone(),
+                    range: to_range(source, fix.edit.region),
+                    new_text: fix.edit.new_text.clone(),
+                })
+            });
+
+        let home_path = loaded.sources.get(&loaded.module_id).map(|(path, _)| path);
+        let annotations = self
+            .missing_annotations
+            .iter()
+            .filter(|missing| overlaps(to_range(&self.source, missing.def_region)))
+            .filter_map(|missing| {
+                Some(CodeAction {
+                    title: format!(
+                        "Add type annotation for `{}`",
+                        missing.symbol.as_str(&loaded.interns)
+                    ),
+                    kind: "refactor.rewrite",
+                    path: home_path?.clone(),
+                    range: to_range(&self.source, missing.edit.region),
+                    new_text: missing.edit.new_text.clone(),
+                })
+            });
+
+        fixes.chain(annotations).collect()
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether the document imports the module at `path`, directly or not.
+    /// If the document didn't load, there's no telling, so it might.
+    pub fn depends_on(&self, path: &Path) -> bool {
+        match &self.loaded {
+            Some(loaded) => loaded.sources.iter().any(|(module_id, (module_path, _))| {
+                *module_id != loaded.module_id && module_path == path
+            }),
+            None => true,
+        }
+    }
+
+    /// The whole document, formatted the way `roc format` would.
+    /// Returns `None` if the document doesn't parse.
+    pub fn format(&self) -> Option<String> {
+        format_source(&self.source)
+    }
+
+    /// The range covering the entire document, to be replaced by [Analysis::format].
+    pub fn full_range(&self) -> Range {
+        let end = self.source.len() as u32;
+
+        Range {
+            start: Position {
+                line: 0,
+                character: 0,
+            },
+            end: to_position(&self.source, end),
+        }
+    }
+}
+
+/// The diagnostics for the problems in `module_id`, at the severity `severity_config` gives
+/// them. Problems whose lint is turned off are left out.
+pub(crate) fn module_diagnostics(
+    loaded: &mut LoadedModule,
+    module_id: ModuleId,
+    severity_config: &SeverityConfig,
+) -> Vec<Diagnostic> {
+    let (path, source) = match loaded.sources.get(&module_id) {
+        Some((path, source)) => (path.clone(), source.to_string()),
+        None => return Vec::new(),
+    };
+    let can_problems = loaded.can_problems.remove(&module_id).unwrap_or_default();
+    let type_problems = loaded.type_problems.remove(&module_id).unwrap_or_default();
+    let loaded = &*loaded;
+    let src_lines: Vec<&str> = source.split('\n').collect();
+    let lines = LineInfo::new(&source);
+    let alloc = RocDocAllocator::new(&src_lines, module_id, &loaded.interns);
+    let mut diagnostics = Vec::new();
+
+    for problem in can_problems {
+        let severity = match severity_config.severity(problem.severity(), problem.lint()) {
+            Some(severity) => severity,
+            None => continue,
+        };
+        let region = problem.region().unwrap_or_else(Region::zero);
+        let fixes = code_actions::fixes(loaded, module_id, &problem);
+        let report = can_problem(&alloc, &lines, path.clone(), problem);
+        let mut message = String::new();
+
+        report.render_ci(&mut message, &alloc);
+
+        diagnostics.push(Diagnostic {
+            range: to_range(&source, region),
+            severity,
+            message,
+            fixes,
+        });
+    }
+
+    for problem in type_problems {
+        let severity = match severity_config.severity(problem.severity(), problem.lint()) {
+            Some(severity) => severity,
+            None => continue,
+        };
+        let region = problem.region().unwrap_or_else(Region::zero);
+
+        if let Some(report) = type_problem(&alloc, &lines, path.clone(), problem) {
+            let mut message = String::new();
+
+            report.render_ci(&mut message, &alloc);
+
+            diagnostics.push(Diagnostic {
+                range: to_range(&source, region),
+                severity,
+                message,
+                fixes: Vec::new(),
+            });
+        }
+    }
+
+    diagnostics
+}
+
+/// Problems that stop the module from loading at all, e.g. a syntax error,
+/// are only available as an already-rendered report, so they go at the top of the document.
+pub(crate) fn loading_diagnostic(problem: LoadingProblem) -> Diagnostic {
+    let message = match problem {
+        LoadingProblem::FormattedReport(report) => report,
+        other => format!("{:?}", other),
+    };
+
+    Diagnostic {
+        range: Range {
+            start: Position {
+                line: 0,
+                character: 0,
+            },
+            end: Position {
+                line: 0,
+                character: 0,
+            },
+        },
+        severity: Severity::Fatal,
+        message,
+        fixes: Vec::new(),
+    }
+}
+
+fn format_source(source: &str) -> Option<String> {
+    let arena = Bump::new();
+    let (header, state) = module::parse_header(&arena, State::new(source.as_bytes())).ok()?;
+    let (_, defs, _) = module_defs().parse(&arena, state, 0).ok()?;
+    let mut buf = Buf::new_in(&arena);
+
+    fmt_module(&mut buf, &header);
+    fmt_defs(&mut buf, &defs, 0);
+    buf.fmt_end_of_file();
+
+    Some(buf.as_str().to_string())
+}
+
+struct Found {
+    region: Region,
+    var: Option<Variable>,
+    symbol: Option<Symbol>,
+}
+
+/// Find the smallest expression or pattern whose region contains the given byte offset.
+fn innermost_at(decls: &Declarations, offset: u32) -> Option<Found> {
+    let mut visitor = Finder {
+        offset,
+        found: None,
+    };
+    visitor.visit_decls(decls);
+    return visitor.found;
+
+    struct Finder {
+        offset: u32,
+        found: Option<Found>,
+    }
+
+    impl Finder {
+        fn contains(&self, region: Region) -> bool {
+            region.start().offset <= self.offset && self.offset < region.end().offset
+        }
+
+        fn record(&mut self, region: Region, var: Option<Variable>, symbol: Option<Symbol>) {
+            let is_smaller = match &self.found {
+                Some(found) => found.region.contains(&region),
+                None => true,
+            };
+
+            if self.contains(region) && is_smaller {
+                self.found = Some(Found {
+                    region,
+                    var,
+                    symbol,
+                });
+            }
+        }
+    }
+
+    impl Visitor for Finder {
+        fn should_visit(&mut self, region: Region) -> bool {
+            self.contains(region)
+        }
+
+        fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+            if self.should_visit(region) {
+                let symbol = match expr {
+                    Expr::Var(symbol, _) | Expr::AbilityMember(symbol, _, _) => Some(*symbol),
+                    _ => None,
+                };
+
+                self.record(region, Some(var), symbol);
+                roc_can::traverse::walk_expr(self, expr, var);
+            }
+        }
+
+        fn visit_pattern(&mut self, pattern: &Pattern, region: Region, opt_var: Option<Variable>) {
+            if self.should_visit(region) {
+                let symbol = match pattern {
+                    Pattern::Identifier(symbol) => Some(*symbol),
+                    _ => None,
+                };
+
+                self.record(region, opt_var, symbol);
+                roc_can::traverse::walk_pattern(self, pattern);
+            }
+        }
+    }
+}
+
+/// Convert an LSP position to a byte offset into the source.
+fn to_offset(source: &str, position: Position) -> Option<u32> {
+    let line_start = if position.line == 0 {
+        0
+    } else {
+        source
+            .match_indices('\n')
+            .nth(position.line as usize - 1)
+            .map(|(index, _)| index + 1)?
+    };
+    let line = source[line_start..].split('\n').next().unwrap_or("");
+    let mut utf16_column = 0;
+
+    for (index, c) in line.char_indices() {
+        if utf16_column >= position.character {
+            return Some((line_start + index) as u32);
+        }
+
+        utf16_column += c.len_utf16() as u32;
+    }
+
+    Some((line_start + line.len()) as u32)
+}
+
+pub(crate) fn to_position(source: &str, offset: u32) -> Position {
+    let offset = (offset as usize).min(source.len());
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map(|index| index + 1).unwrap_or(0);
+
+    Position {
+        line: before.matches('\n').count() as u32,
+        character: before[line_start..].encode_utf16().count() as u32,
+    }
+}
+
+pub(crate) fn to_range(source: &str, region: Region) -> Range {
+    Range {
+        start: to_position(source, region.start().offset),
+        end: to_position(source, region.end().offset),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_round_trip() {
+        let source = "app \"x\"\n    x = \"é😀\" |> f\n";
+        let offset = source.find("|>").unwrap() as u32;
+        let position = to_position(source, offset);
+
+        assert_eq!(
+            position,
+            Position {
+                line: 1,
+                character: 14
+            }
+        );
+        assert_eq!(to_offset(source, position), Some(offset));
+    }
+}
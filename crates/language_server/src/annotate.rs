@@ -0,0 +1,132 @@
+//! Insert the inferred types of top-level defs as annotations, for the
+//! "Add type annotation" code action and `roc annotate`.
+use bumpalo::Bump;
+use roc_can::expr::DeclarationTag;
+use roc_can::traverse::find_declaration;
+use roc_fmt::def::fmt_defs;
+use roc_fmt::Buf;
+use roc_load::LoadedModule;
+use roc_module::symbol::Symbol;
+use roc_parse::module::module_defs;
+use roc_parse::parser::Parser;
+use roc_parse::state::State;
+use roc_region::all::{Position, Region};
+use roc_types::pretty_print::{name_and_print_var, DebugPrint};
+
+use crate::rename::Edit;
+
+#[derive(Debug, Clone)]
+pub struct MissingAnnotation {
+    pub symbol: Symbol,
+    /// The whole def, so an editor can offer the annotation anywhere in it
+    pub def_region: Region,
+    /// Inserts the annotation on the line before the def
+    pub edit: Edit,
+}
+
+/// Every top-level value and function in the loaded module that doesn't have an annotation,
+/// in source order, with an edit that adds its inferred type as one.
+pub fn missing_annotations(loaded: &mut LoadedModule) -> Vec<MissingAnnotation> {
+    let home = loaded.module_id;
+    let (source, decls) = match (
+        loaded.sources.get(&home),
+        loaded.declarations_by_id.get(&home),
+    ) {
+        (Some((_, source)), Some(decls)) => (source, decls),
+        _ => return Vec::new(),
+    };
+
+    let subs = loaded.solved.inner_mut();
+    let mut missing = Vec::new();
+
+    for (index, tag) in decls.declarations.iter().enumerate() {
+        let is_annotatable = matches!(
+            tag,
+            DeclarationTag::Value
+                | DeclarationTag::Function(_)
+                | DeclarationTag::Recursive(_)
+                | DeclarationTag::TailRecursive(_)
+        );
+
+        if !is_annotatable || decls.annotations[index].is_some() {
+            continue;
+        }
+
+        let loc_symbol = decls.symbols[index];
+        let name = loc_symbol.value.as_str(&loaded.interns);
+
+        // e.g. the defs the compiler generates for the `main` of an app
+        if name.starts_with('#') {
+            continue;
+        }
+
+        let def_region = match find_declaration(loc_symbol.value, decls) {
+            Some(found) => found.region(),
+            None => loc_symbol.region,
+        };
+
+        let type_str = name_and_print_var(
+            decls.variables[index],
+            subs,
+            home,
+            &loaded.interns,
+            DebugPrint::NOTHING,
+        );
+
+        // An annotation can't say anything useful about a def that doesn't type-check.
+        if type_str.contains("<type mismatch>") {
+            continue;
+        }
+
+        let line_start = source[..loc_symbol.region.start().offset as usize]
+            .rfind('\n')
+            .map_or(0, |newline| newline + 1) as u32;
+
+        missing.push(MissingAnnotation {
+            symbol: loc_symbol.value,
+            def_region,
+            edit: Edit {
+                region: Region::new(Position::new(line_start), Position::new(line_start)),
+                new_text: format_annotation(name, &type_str),
+            },
+        });
+    }
+
+    // Declarations are in dependency order, but edits are applied in source order.
+    missing.sort_by_key(|missing| missing.edit.region.start().offset);
+
+    missing
+}
+
+/// Format the annotation the way `roc format` would, if it parses.
+fn format_annotation(name: &str, type_str: &str) -> String {
+    let annotation = format!("{} : {}\n", name, type_str);
+    let arena = Bump::new();
+
+    let formatted = match module_defs().parse(&arena, State::new(annotation.as_bytes()), 0) {
+        Ok((_, defs, _)) => {
+            let mut buf = Buf::new_in(&arena);
+
+            fmt_defs(&mut buf, &defs, 0);
+
+            Some(format!("{}\n", buf.as_str().trim_end()))
+        }
+        Err(_) => None,
+    };
+
+    formatted.unwrap_or(annotation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_printed_types() {
+        assert_eq!(format_annotation("pick", "a,  a ->a"), "pick : a, a -> a\n");
+        assert_eq!(
+            format_annotation("main", "Task {} []"),
+            "main : Task {} []\n"
+        );
+    }
+}
@@ -0,0 +1,169 @@
+//! The diagnostics for every loaded module as JSON, for `roc check --format=json`, so editors
+//! and scripts that don't speak LSP can show problems and apply their quick fixes.
+use std::path::Path;
+
+use roc_load::{LoadedModule, LoadingProblem};
+use roc_problem::lint::SeverityConfig;
+use roc_problem::Severity;
+use serde_json::{json, Value};
+
+use crate::analysis::{loading_diagnostic, module_diagnostics, to_range, Diagnostic, Range};
+
+/// Every problem in every module of `loaded`, sorted by path, as a JSON array of
+/// `{ "path", "range", "severity", "message", "fixes" }`. Each fix is
+/// `{ "title", "path", "range", "newText" }`, where `path` is the file to edit, which isn't
+/// always the file with the problem. Lines and characters count from zero, like in LSP.
+pub fn diagnostics_json(loaded: &mut LoadedModule, severity_config: &SeverityConfig) -> Value {
+    let mut module_ids: Vec<_> = loaded.sources.keys().copied().collect();
+
+    module_ids.sort_by(|a, b| loaded.sources[a].0.cmp(&loaded.sources[b].0));
+
+    let mut diagnostics = Vec::new();
+
+    for module_id in module_ids {
+        let path = loaded.sources[&module_id].0.clone();
+
+        for diagnostic in module_diagnostics(loaded, module_id, severity_config) {
+            let fixes: Vec<Value> = diagnostic
+                .fixes
+                .iter()
+                .filter_map(|fix| {
+                    let (_, source) = loaded.sources.get(&fix.module_id)?;
+
+                    Some(json!({
+                        "title": fix.title,
+                        "path": fix.path,
+                        "range": range_json(to_range(source, fix.edit.region)),
+                        "newText": fix.edit.new_text,
+                    }))
+                })
+                .collect();
+
+            diagnostics.push(diagnostic_json(&path, &diagnostic, fixes));
+        }
+    }
+
+    Value::Array(diagnostics)
+}
+
+/// A problem that kept `path` from loading at all, e.g. a syntax error, as a one-element array
+/// in the same shape as [diagnostics_json].
+pub fn loading_problem_json(path: &Path, problem: LoadingProblem) -> Value {
+    let diagnostic = loading_diagnostic(problem);
+
+    json!([diagnostic_json(path, &diagnostic, Vec::new())])
+}
+
+fn diagnostic_json(path: &Path, diagnostic: &Diagnostic, fixes: Vec<Value>) -> Value {
+    json!({
+        "path": path,
+        "range": range_json(diagnostic.range),
+        "severity": match diagnostic.severity {
+            Severity::Fatal => "fatal",
+            Severity::RuntimeError => "error",
+            Severity::Warning => "warning",
+        },
+        "message": diagnostic.message,
+        "fixes": fixes,
+    })
+}
+
+fn range_json(range: Range) -> Value {
+    json!({
+        "start": { "line": range.start.line, "character": range.start.character },
+        "end": { "line": range.end.line, "character": range.end.character },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bumpalo::Bump;
+    use indoc::indoc;
+    use roc_load::{ExecutionMode, LoadConfig, Threading};
+    use roc_packaging::cache::RocCacheDir;
+    use roc_reporting::report::{RenderTarget, DEFAULT_PALETTE};
+    use roc_target::TargetInfo;
+    use std::fs;
+
+    #[test]
+    fn fixes_edit_the_module_they_belong_to() {
+        let dir = tempfile::tempdir().unwrap();
+        let dep_path = dir.path().join("Dep.roc");
+        let main_path = dir.path().join("Main.roc");
+
+        fs::write(
+            &dep_path,
+            indoc!(
+                r#"
+                interface Dep
+                    exposes [x]
+                    imports []
+
+                x = 1
+
+                hidden = 2
+                "#
+            ),
+        )
+        .unwrap();
+        fs::write(
+            &main_path,
+            indoc!(
+                r#"
+                interface Main exposes [y] imports [Dep]
+
+                y = Dep.x + Dep.hidden
+                "#
+            ),
+        )
+        .unwrap();
+
+        let arena = Bump::new();
+        let load_config = LoadConfig {
+            target_info: TargetInfo::default_x86_64(),
+            render: RenderTarget::Generic,
+            palette: DEFAULT_PALETTE,
+            threading: Threading::Single,
+            exec_mode: ExecutionMode::Check,
+            max_specializations: None,
+            coverage: false,
+            fold_constants: false,
+            hoist_loop_invariants: false,
+            strip_expects: false,
+            cancelled: None,
+        };
+        let mut loaded = roc_load::load_and_typecheck(
+            &arena,
+            main_path.clone(),
+            RocCacheDir::Disallowed,
+            load_config,
+        )
+        .unwrap();
+
+        let json = diagnostics_json(&mut loaded, &SeverityConfig::default());
+
+        // `Dep` also reports `hidden` as unused; the fix belongs to the error in `Main`.
+        let diagnostic = json
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|diagnostic| diagnostic["path"] == json!(main_path))
+            .unwrap();
+
+        assert_eq!(diagnostic["severity"], "error");
+        assert_eq!(diagnostic["range"]["start"]["line"], 2);
+        assert_eq!(
+            diagnostic["fixes"],
+            json!([{
+                "title": "Expose `hidden` from `Dep`",
+                "path": dep_path,
+                "range": {
+                    "start": { "line": 0, "character": 0 },
+                    "end": { "line": 2, "character": 14 },
+                },
+                "newText": "interface Dep\n    exposes [x, hidden]\n    imports []",
+            }])
+        );
+    }
+}
@@ -0,0 +1,348 @@
+//! Quick fixes for problems that are solved by editing a module header: importing a module,
+//! importing a value from a module that exposes it, or exposing a value from its module.
+//!
+//! The header is changed in its AST and then formatted, so the fix is laid out the way
+//! `roc format` would lay it out, including in multiline lists.
+use std::path::{Path, PathBuf};
+
+use bumpalo::Bump;
+use roc_fmt::module::fmt_module;
+use roc_fmt::Buf;
+use roc_load::LoadedModule;
+use roc_module::symbol::ModuleId;
+use roc_parse::ast::{Collection, CommentOrNewline, ExtractSpaces, Header, Spaced, Spaces};
+use roc_parse::header::{ExposedName, ImportsEntry, ImportsKeyword, KeywordItem, ModuleName};
+use roc_parse::state::State;
+use roc_problem::can::{Problem, RuntimeError};
+use roc_region::all::{Loc, Position, Region};
+
+use crate::rename::Edit;
+
+/// A fix for a problem, to offer as a code action.
+#[derive(Debug, Clone)]
+pub struct Fix {
+    pub title: String,
+    pub module_id: ModuleId,
+    pub path: PathBuf,
+    pub edit: Edit,
+}
+
+/// The fixes for a canonicalization problem in the module `home`.
+pub fn fixes(loaded: &LoadedModule, home: ModuleId, problem: &Problem) -> Vec<Fix> {
+    let (home_path, home_source) = match loaded.sources.get(&home) {
+        Some((path, source)) => (path, &**source),
+        None => return Vec::new(),
+    };
+
+    let fix_home = |title: String, edit: Option<Edit>| {
+        edit.map(|edit| Fix {
+            title,
+            module_id: home,
+            path: home_path.clone(),
+            edit,
+        })
+    };
+
+    match problem {
+        Problem::RuntimeError(RuntimeError::ModuleNotImported {
+            module_name,
+            module_exists: true,
+            ..
+        }) => {
+            let module_name = module_name.as_str();
+            let edit = add_import(home_path, home_source, module_name, None);
+
+            fix_home(format!("Import `{}`", module_name), edit)
+                .into_iter()
+                .collect()
+        }
+        Problem::RuntimeError(RuntimeError::LookupNotInScope {
+            loc_name,
+            exposed_by,
+            ..
+        }) => exposed_by
+            .iter()
+            .filter_map(|exposing| {
+                let module_name = exposing.module_name.as_str();
+                let ident = loc_name.value.as_str();
+                let edit = add_import(home_path, home_source, module_name, Some(ident));
+
+                fix_home(format!("Import `{}` from `{}`", ident, module_name), edit)
+            })
+            .collect(),
+        Problem::RuntimeError(RuntimeError::ValueNotExposed {
+            module_name, ident, ..
+        }) => {
+            let module_name = module_name.as_str();
+            let found = loaded.sources.iter().find(|(module_id, _)| {
+                let interned = loaded.interns.module_name(**module_id).as_str();
+
+                !module_id.is_builtin()
+                    && (interned == module_name || interned.ends_with(&format!(".{}", module_name)))
+            });
+
+            match found {
+                Some((module_id, (path, source))) => add_exposed(source, ident.as_str())
+                    .map(|edit| Fix {
+                        title: format!("Expose `{}` from `{}`", ident, module_name),
+                        module_id: *module_id,
+                        path: path.clone(),
+                        edit,
+                    })
+                    .into_iter()
+                    .collect(),
+                None => Vec::new(),
+            }
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Import `module_name`, and `exposed` from it if given. A module next to this one is imported
+/// by name; otherwise it's assumed to come from the only package this module imports from.
+fn add_import(path: &Path, source: &str, module_name: &str, exposed: Option<&str>) -> Option<Edit> {
+    let arena = Bump::new();
+    let is_local = path
+        .parent()
+        .map(|dir| {
+            dir.join(module_name.replace('.', "/"))
+                .with_extension("roc")
+                .exists()
+        })
+        .unwrap_or(false);
+
+    edit_header(&arena, source, |arena, header| {
+        let imports = match header {
+            Header::Interface(header) => &mut header.imports,
+            Header::Hosted(header) => &mut header.imports,
+            Header::Platform(header) => &mut header.imports,
+            Header::App(header) => header.imports.get_or_insert(KeywordItem {
+                keyword: Spaces {
+                    before: &[CommentOrNewline::Newline],
+                    item: ImportsKeyword,
+                    after: &[],
+                },
+                item: Collection::empty(),
+            }),
+            Header::Package(_) => return false,
+        };
+
+        let shorthand = if is_local {
+            None
+        } else {
+            match package_shorthand(&imports.item) {
+                Some(shorthand) => Some(shorthand),
+                None => return false,
+            }
+        };
+
+        match with_import(arena, &imports.item, shorthand, module_name, exposed) {
+            Some(updated) => {
+                imports.item = updated;
+
+                true
+            }
+            None => false,
+        }
+    })
+}
+
+/// Add `ident` to the `exposes` list of the module with this source.
+fn add_exposed(source: &str, ident: &str) -> Option<Edit> {
+    let arena = Bump::new();
+
+    edit_header(&arena, source, |arena, header| {
+        let exposes = match header {
+            Header::Interface(header) => &mut header.exposes,
+            Header::Hosted(header) => &mut header.exposes,
+            _ => return false,
+        };
+
+        match with_name(arena, &exposes.item, ident) {
+            Some(updated) => {
+                exposes.item = updated;
+
+                true
+            }
+            None => false,
+        }
+    })
+}
+
+/// The package every package import comes from, e.g. `pf` in `imports [pf.Stdout, pf.Task]`.
+/// Returns `None` if there's more than one, or none at all.
+fn package_shorthand<'a>(
+    imports: &Collection<'a, Loc<Spaced<'a, ImportsEntry<'a>>>>,
+) -> Option<&'a str> {
+    let mut shorthands =
+        imports
+            .iter()
+            .filter_map(|entry| match entry.value.extract_spaces().item {
+                ImportsEntry::Package(shorthand, _, _) => Some(shorthand),
+                _ => None,
+            });
+
+    let first = shorthands.next()?;
+
+    if shorthands.all(|shorthand| shorthand == first) {
+        Some(first)
+    } else {
+        None
+    }
+}
+
+/// The imports with `module_name` (and `exposed` from it) added, or `None` if it's already there.
+fn with_import<'a>(
+    arena: &'a Bump,
+    imports: &Collection<'a, Loc<Spaced<'a, ImportsEntry<'a>>>>,
+    shorthand: Option<&'a str>,
+    module_name: &str,
+    exposed: Option<&str>,
+) -> Option<Collection<'a, Loc<Spaced<'a, ImportsEntry<'a>>>>> {
+    let module_name = ModuleName::new(arena.alloc_str(module_name));
+    let mut items: Vec<_> = imports.iter().copied().collect();
+
+    let existing = items.iter().position(|entry| {
+        matches!(
+            entry.value.extract_spaces().item,
+            ImportsEntry::Module(name, _) | ImportsEntry::Package(_, name, _)
+                if name == module_name
+        )
+    });
+
+    match existing {
+        Some(index) => {
+            let spaces = items[index].value.extract_spaces();
+            let updated = match spaces.item {
+                ImportsEntry::Module(name, names) => {
+                    ImportsEntry::Module(name, with_name(arena, &names, exposed?)?)
+                }
+                ImportsEntry::Package(shorthand, name, names) => {
+                    ImportsEntry::Package(shorthand, name, with_name(arena, &names, exposed?)?)
+                }
+                ImportsEntry::IngestedFile(..) => return None,
+            };
+
+            items[index].value = respace(arena, spaces.before, updated, spaces.after);
+        }
+        None => {
+            let names = match exposed {
+                Some(ident) => with_name(arena, &Collection::empty(), ident)?,
+                None => Collection::empty(),
+            };
+            let entry = match shorthand {
+                Some(shorthand) => ImportsEntry::Package(shorthand, module_name, names),
+                None => ImportsEntry::Module(module_name, names),
+            };
+
+            items.push(Loc::at(Region::zero(), Spaced::Item(entry)));
+        }
+    }
+
+    Some(imports.replace_items(arena.alloc_slice_copy(&items)))
+}
+
+/// The names with `ident` added, or `None` if it's already there.
+fn with_name<'a>(
+    arena: &'a Bump,
+    names: &Collection<'a, Loc<Spaced<'a, ExposedName<'a>>>>,
+    ident: &str,
+) -> Option<Collection<'a, Loc<Spaced<'a, ExposedName<'a>>>>> {
+    if names
+        .iter()
+        .any(|name| <&str>::from(name.value.extract_spaces().item) == ident)
+    {
+        return None;
+    }
+
+    let new_name = ExposedName::new(arena.alloc_str(ident));
+    let mut items: Vec<_> = names.iter().copied().collect();
+
+    items.push(Loc::at(Region::zero(), Spaced::Item(new_name)));
+
+    Some(names.replace_items(arena.alloc_slice_copy(&items)))
+}
+
+fn respace<'a, T: Copy>(
+    arena: &'a Bump,
+    before: &'a [CommentOrNewline<'a>],
+    item: T,
+    after: &'a [CommentOrNewline<'a>],
+) -> Spaced<'a, T> {
+    let mut spaced = Spaced::Item(item);
+
+    if !after.is_empty() {
+        spaced = Spaced::SpaceAfter(arena.alloc(spaced), after);
+    }
+
+    if !before.is_empty() {
+        spaced = Spaced::SpaceBefore(arena.alloc(spaced), before);
+    }
+
+    spaced
+}
+
+/// Parse the header, change it with `update`, and return an edit that replaces the old header
+/// with the formatted new one. `update` returns false if there's nothing to change.
+fn edit_header<'a>(
+    arena: &'a Bump,
+    source: &str,
+    update: impl FnOnce(&'a Bump, &mut Header<'a>) -> bool,
+) -> Option<Edit> {
+    let source: &'a str = arena.alloc_str(source);
+    let (mut module, state) =
+        roc_parse::module::parse_header(arena, State::new(source.as_bytes())).ok()?;
+
+    if !update(arena, &mut module.header) {
+        return None;
+    }
+
+    let header_end = state.pos().offset as usize;
+    let old_len = source[..header_end].trim_end().len();
+    let mut buf = Buf::new_in(arena);
+
+    fmt_module(&mut buf, arena.alloc(module));
+
+    Some(Edit {
+        region: Region::new(Position::new(0), Position::new(old_len as u32)),
+        new_text: buf.as_str().trim_end().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rename::apply_edits;
+
+    #[test]
+    fn expose_a_value() {
+        let source = "interface Foo\n    exposes [a]\n    imports []\n\na = 1\nb = 2\n";
+        let edit = add_exposed(source, "b").unwrap();
+
+        assert_eq!(
+            apply_edits(source, &[edit]),
+            "interface Foo\n    exposes [a, b]\n    imports []\n\na = 1\nb = 2\n"
+        );
+        assert!(add_exposed(source, "a").is_none());
+    }
+
+    #[test]
+    fn import_from_a_package() {
+        let source = "app \"x\"\n    packages { pf: \"main.roc\" }\n    imports [pf.Stdout, Json.{ decode }]\n    provides [main] to pf\n";
+        let path = Path::new("/nonexistent/main.roc");
+
+        let edit = add_import(path, source, "Task", Some("await")).unwrap();
+
+        assert_eq!(
+            apply_edits(source, &[edit]),
+            "app \"x\"\n    packages { pf: \"main.roc\" }\n    imports [pf.Stdout, Json.{ decode }, pf.Task.{ await }]\n    provides [main] to pf\n"
+        );
+
+        let edit = add_import(path, source, "Json", Some("encode")).unwrap();
+
+        assert_eq!(
+            apply_edits(source, &[edit]),
+            "app \"x\"\n    packages { pf: \"main.roc\" }\n    imports [pf.Stdout, Json.{ decode, encode }]\n    provides [main] to pf\n"
+        );
+    }
+}
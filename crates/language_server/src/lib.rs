@@ -0,0 +1,10 @@
+//! Editor tooling built on the same load and type-check pipeline as `roc check`.
+//! Used by the `roc_ls` language server, and by `roc rename`, `roc annotate` and
+//! `roc check --format=json`.
+pub mod analysis;
+pub mod annotate;
+pub mod check;
+pub mod code_actions;
+pub mod rename;
+pub mod semantic_tokens;
+pub mod workspace;
@@ -0,0 +1,531 @@
+//! A language server for Roc, so any editor with LSP support gets diagnostics,
+//! hover types, go-to-definition, find references, rename, formatting and semantic highlighting
+//! without a Roc-specific plugin.
+//!
+//! It speaks JSON-RPC over stdin and stdout. Documents are checked with the same pipeline as
+//! `roc check` when they're opened or changed, and when a module they import is saved.
+//! Messages are read on their own thread, so when edits come in faster than they can be checked,
+//! only the latest one is.
+use roc_language_server::analysis::{Analysis, Diagnostic, Location, Position, Range};
+use roc_language_server::semantic_tokens::{encode_for_lsp, semantic_tokens, SemanticToken};
+use roc_language_server::workspace::Workspace;
+use roc_problem::Severity;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Read, Write};
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+
+fn main() -> io::Result<()> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let stdin = io::stdin();
+        let mut input = stdin.lock();
+
+        loop {
+            let message = read_message(&mut input).transpose();
+            let is_last = !matches!(message, Some(Ok(_)));
+
+            if let Some(message) = message {
+                if sender.send(message).is_err() {
+                    break;
+                }
+            }
+
+            if is_last {
+                break;
+            }
+        }
+    });
+
+    let mut server = Server::default();
+    let mut pending = VecDeque::new();
+
+    loop {
+        pending.extend(receiver.try_iter());
+
+        let message = match pending.pop_front() {
+            Some(message) => message?,
+            None => match receiver.recv() {
+                Ok(message) => message?,
+                // The input ended
+                Err(_) => break,
+            },
+        };
+
+        if is_superseded(&message, &pending) {
+            continue;
+        }
+
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+
+        match message.get("id") {
+            Some(id) => {
+                let response = match server.request(method, params) {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    Err((code, message)) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": code, "message": message },
+                    }),
+                };
+
+                write_message(&response)?;
+            }
+            None => {
+                if method == "exit" {
+                    std::process::exit(if server.shutdown_requested { 0 } else { 1 });
+                }
+
+                server.notification(method, params)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether a change to a document is followed right away by another one. Every change has the
+/// whole document, so there's no need to check the one in between.
+fn is_superseded(message: &Value, pending: &VecDeque<io::Result<Value>>) -> bool {
+    const DID_CHANGE: &str = "textDocument/didChange";
+
+    match pending.front() {
+        Some(Ok(next)) => {
+            message["method"] == DID_CHANGE
+                && next["method"] == DID_CHANGE
+                && message["params"]["textDocument"]["uri"] == next["params"]["textDocument"]["uri"]
+        }
+        _ => false,
+    }
+}
+
+const METHOD_NOT_FOUND: i64 = -32601;
+const REQUEST_FAILED: i64 = -32803;
+
+#[derive(Default)]
+struct Server {
+    workspace: Workspace,
+    shutdown_requested: bool,
+}
+
+impl Server {
+    fn request(&mut self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    // Full sync: every change notification has the whole document.
+                    "textDocumentSync": { "openClose": true, "change": 1, "save": true },
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "referencesProvider": true,
+                    "documentFormattingProvider": true,
+                    "renameProvider": true,
+                    "codeActionProvider": true,
+                    "semanticTokensProvider": {
+                        "legend": {
+                            "tokenTypes": SemanticToken::ALL.map(SemanticToken::lsp_name),
+                            "tokenModifiers": [],
+                        },
+                        "full": true,
+                    },
+                },
+                "serverInfo": { "name": "roc_ls" },
+            })),
+            "shutdown" => {
+                self.shutdown_requested = true;
+
+                Ok(Value::Null)
+            }
+            "textDocument/hover" => {
+                let hover = self
+                    .document(params)
+                    .and_then(|doc| doc.hover(position(&params["position"])));
+
+                Ok(match hover {
+                    Some((range, type_str)) => json!({
+                        "contents": { "kind": "markdown", "value": format!("```roc\n{}\n```", type_str) },
+                        "range": range_json(range),
+                    }),
+                    None => Value::Null,
+                })
+            }
+            "textDocument/definition" => {
+                let location = self
+                    .document(params)
+                    .and_then(|doc| doc.definition(position(&params["position"])));
+
+                Ok(match location {
+                    Some(location) => location_json(location),
+                    None => Value::Null,
+                })
+            }
+            "textDocument/references" => {
+                let include_declaration = params["context"]["includeDeclaration"]
+                    .as_bool()
+                    .unwrap_or(false);
+                let locations = match self.document(params) {
+                    Some(doc) => doc.references(position(&params["position"]), include_declaration),
+                    None => Vec::new(),
+                };
+
+                Ok(locations.into_iter().map(location_json).collect())
+            }
+            "textDocument/formatting" => {
+                let edit = self
+                    .document(params)
+                    .and_then(|doc| Some((doc.full_range(), doc.format()?)));
+
+                Ok(match edit {
+                    Some((range, new_text)) => {
+                        json!([{ "range": range_json(range), "newText": new_text }])
+                    }
+                    // The document doesn't parse, so leave it alone.
+                    None => Value::Null,
+                })
+            }
+            "textDocument/semanticTokens/full" => Ok(match self.document(params) {
+                Some(doc) => {
+                    let tokens = semantic_tokens(doc.source());
+
+                    json!({ "data": encode_for_lsp(doc.source(), &tokens) })
+                }
+                None => Value::Null,
+            }),
+            "textDocument/codeAction" => {
+                let range = Range {
+                    start: position(&params["range"]["start"]),
+                    end: position(&params["range"]["end"]),
+                };
+                let actions = match self.document(params) {
+                    Some(doc) => doc.code_actions(range),
+                    None => Vec::new(),
+                };
+
+                Ok(actions
+                    .into_iter()
+                    .map(|action| {
+                        let mut changes = serde_json::Map::new();
+
+                        changes.insert(
+                            path_to_uri(&action.path),
+                            json!([{ "range": range_json(action.range), "newText": action.new_text }]),
+                        );
+
+                        json!({
+                            "title": action.title,
+                            "kind": action.kind,
+                            "edit": { "changes": changes },
+                        })
+                    })
+                    .collect())
+            }
+            "textDocument/rename" => {
+                let new_name = params["newName"].as_str().unwrap_or_default();
+                let rename = self
+                    .document(params)
+                    .and_then(|doc| doc.rename(position(&params["position"]), new_name));
+
+                match rename {
+                    Some(Ok(files)) => {
+                        let changes: serde_json::Map<String, Value> = files
+                            .into_iter()
+                            .map(|(path, edits)| {
+                                let edits = edits
+                                    .into_iter()
+                                    .map(|(range, new_text)| {
+                                        json!({ "range": range_json(range), "newText": new_text })
+                                    })
+                                    .collect();
+
+                                (path_to_uri(&path), Value::Array(edits))
+                            })
+                            .collect();
+
+                        Ok(json!({ "changes": changes }))
+                    }
+                    Some(Err(problem)) => Err((REQUEST_FAILED, problem.to_string())),
+                    None => Ok(Value::Null),
+                }
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("Unsupported method: {}", method))),
+        }
+    }
+
+    fn notification(&mut self, method: &str, params: &Value) -> io::Result<()> {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+
+        let source = match method {
+            "textDocument/didOpen" => params["textDocument"]["text"].as_str(),
+            "textDocument/didChange" => params["contentChanges"]
+                .as_array()
+                .and_then(|changes| changes.last())
+                .and_then(|change| change["text"].as_str()),
+            "textDocument/didSave" => {
+                // The documents that import this one read it from disk, so they're checked too.
+                let source = params["text"].as_str().map(str::to_string);
+
+                for rechecked in self.workspace.save(uri, source) {
+                    if let Some(doc) = self.workspace.get(&rechecked) {
+                        publish_diagnostics(&rechecked, &doc.diagnostics)?;
+                    }
+                }
+
+                return Ok(());
+            }
+            "textDocument/didClose" => {
+                self.workspace.close(uri);
+
+                return publish_diagnostics(uri, &[]);
+            }
+            _ => None,
+        };
+
+        match (source, uri_to_path(uri)) {
+            (Some(source), Some(path)) => {
+                if self.workspace.update(uri, path, source.to_string()) {
+                    if let Some(doc) = self.workspace.get(uri) {
+                        publish_diagnostics(uri, &doc.diagnostics)?;
+                    }
+                }
+
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn document(&mut self, params: &Value) -> Option<&mut Analysis> {
+        let uri = params["textDocument"]["uri"].as_str()?;
+
+        self.workspace.get(uri)
+    }
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: &[Diagnostic]) -> io::Result<()> {
+    let diagnostics: Vec<Value> = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            json!({
+                "range": range_json(diagnostic.range),
+                "severity": match diagnostic.severity {
+                    Severity::Fatal | Severity::RuntimeError => 1,
+                    Severity::Warning => 2,
+                },
+                "source": "roc",
+                "message": diagnostic.message,
+            })
+        })
+        .collect();
+
+    write_message(&json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    }))
+}
+
+fn position(value: &Value) -> Position {
+    Position {
+        line: value["line"].as_u64().unwrap_or_default() as u32,
+        character: value["character"].as_u64().unwrap_or_default() as u32,
+    }
+}
+
+fn range_json(range: Range) -> Value {
+    json!({
+        "start": { "line": range.start.line, "character": range.start.character },
+        "end": { "line": range.end.line, "character": range.end.character },
+    })
+}
+
+fn location_json(location: Location) -> Value {
+    json!({
+        "uri": path_to_uri(&location.path),
+        "range": range_json(location.range),
+    })
+}
+
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match (bytes[i], escaped) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    let path = String::from_utf8(decoded).ok()?;
+
+    // On Windows, file URIs look like file:///C:/path
+    if cfg!(windows) {
+        Some(PathBuf::from(path.trim_start_matches('/')))
+    } else {
+        Some(PathBuf::from(path))
+    }
+}
+
+fn path_to_uri(path: &std::path::Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from(if path.starts_with('/') {
+        "file://"
+    } else {
+        "file:///"
+    });
+
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' | b':' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    uri
+}
+
+/// Read one message, framed by a `Content-Length` header. Returns `None` at end of input.
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut content_length = None;
+
+    loop {
+        let mut line = String::new();
+
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let line = line.trim_end();
+
+        if line.is_empty() {
+            break;
+        }
+
+        if let Some(length) = line.strip_prefix("Content-Length:") {
+            content_length = length.trim().parse::<usize>().ok();
+        }
+    }
+
+    let content_length = content_length.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length header")
+    })?;
+
+    let mut content = vec![0; content_length];
+    input.read_exact(&mut content)?;
+
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+fn write_message(message: &Value) -> io::Result<()> {
+    let content = message.to_string();
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+
+    write!(
+        output,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn did_change(uri: &str) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didChange",
+            "params": { "textDocument": { "uri": uri }, "contentChanges": [{ "text": "" }] },
+        })
+    }
+
+    #[test]
+    fn only_the_latest_of_consecutive_changes_is_checked() {
+        let hover = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "textDocument/hover",
+            "params": { "textDocument": { "uri": "file:///A.roc" } },
+        });
+        let pending = |messages: Vec<Value>| messages.into_iter().map(Ok).collect();
+
+        assert!(is_superseded(
+            &did_change("file:///A.roc"),
+            &pending(vec![did_change("file:///A.roc")])
+        ));
+        assert!(!is_superseded(
+            &did_change("file:///A.roc"),
+            &pending(vec![did_change("file:///B.roc")])
+        ));
+        // The hover is about the document as of this change, so it has to be checked first.
+        assert!(!is_superseded(
+            &did_change("file:///A.roc"),
+            &pending(vec![hover, did_change("file:///A.roc")])
+        ));
+        assert!(!is_superseded(
+            &did_change("file:///A.roc"),
+            &pending(vec![])
+        ));
+    }
+}
+
+// These functions don't end up in the final Roc binary but Windows linker needs a definition inside the crate.
+// On Windows, there seems to be less dead-code-elimination than on Linux or MacOS, or maybe it's done later.
+#[cfg(windows)]
+#[allow(unused_imports)]
+use windows_roc_platform_functions::*;
+
+#[cfg(windows)]
+mod windows_roc_platform_functions {
+    use core::ffi::c_void;
+
+    /// # Safety
+    /// The Roc application needs this.
+    #[no_mangle]
+    pub unsafe fn roc_alloc(size: usize, _alignment: u32) -> *mut c_void {
+        libc::malloc(size)
+    }
+
+    /// # Safety
+    /// The Roc application needs this.
+    #[no_mangle]
+    pub unsafe fn roc_realloc(
+        c_ptr: *mut c_void,
+        new_size: usize,
+        _old_size: usize,
+        _alignment: u32,
+    ) -> *mut c_void {
+        libc::realloc(c_ptr, new_size)
+    }
+
+    /// # Safety
+    /// The Roc application needs this.
+    #[no_mangle]
+    pub unsafe fn roc_dealloc(c_ptr: *mut c_void, _alignment: u32) {
+        libc::free(c_ptr)
+    }
+}
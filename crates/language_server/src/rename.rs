@@ -0,0 +1,619 @@
+//! Rename a value across every loaded module, for both `textDocument/rename` and `roc rename`.
+//!
+//! References are found in the canonical IR of each module, so a local that happens to have the
+//! same name as the renamed value is left alone. The header `exposes`, `imports` and `provides`
+//! lists aren't part of the canonical IR, so those are found by parsing each header again.
+use std::path::PathBuf;
+
+use bumpalo::Bump;
+use roc_can::def::Def;
+use roc_can::expr::{Declarations, Expr};
+use roc_can::pattern::{DestructType, Pattern, RecordDestruct};
+use roc_can::traverse::{
+    walk_decls, walk_def, walk_expr, walk_pattern, walk_record_destruct, Visitor,
+};
+use roc_load::LoadedModule;
+use roc_module::symbol::{ModuleId, Symbol};
+use roc_parse::ast::{Collection, ExtractSpaces, Header, Spaced};
+use roc_parse::header::{ExposedName, ImportsEntry};
+use roc_parse::keyword::KEYWORDS;
+use roc_parse::state::State;
+use roc_region::all::{Loc, Position, Region};
+use roc_types::subs::Variable;
+
+/// Replace the source text in `region` with `new_text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub region: Region,
+    pub new_text: String,
+}
+
+/// All the edits to make in one module, in source order.
+#[derive(Debug, Clone)]
+pub struct FileEdits {
+    pub module_id: ModuleId,
+    pub path: PathBuf,
+    pub edits: Vec<Edit>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenameError {
+    /// The new name isn't a lowercase identifier, or it's a keyword.
+    InvalidName(String),
+    /// A module that would refer to the value by its new name, without qualifying it,
+    /// already has something with that name: either the module that defines the value,
+    /// or one that imports it by name.
+    NameTaken { name: String, path: PathBuf },
+    /// The value is defined in a builtin, or in a module whose source isn't available.
+    NotInWorkspace(Symbol),
+}
+
+impl std::fmt::Display for RenameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RenameError::InvalidName(name) => write!(
+                f,
+                "`{}` is not a valid name for a value: it must start with a lowercase letter, contain only letters and digits, and not be a keyword.",
+                name
+            ),
+            RenameError::NameTaken { name, path } => {
+                write!(f, "`{}` is already used in {}.", name, path.display())
+            }
+            RenameError::NotInWorkspace(_) => {
+                write!(f, "This value is defined in a module that can't be edited.")
+            }
+        }
+    }
+}
+
+/// Find a top-level value by name in the given module.
+pub fn find_top_level(loaded: &LoadedModule, module_id: ModuleId, name: &str) -> Option<Symbol> {
+    let decls = loaded.declarations_by_id.get(&module_id)?;
+
+    decls
+        .symbols
+        .iter()
+        .map(|loc_symbol| loc_symbol.value)
+        .find(|symbol| symbol.as_str(&loaded.interns) == name)
+}
+
+/// The edits that rename `symbol` to `new_name` in every loaded module that mentions it.
+pub fn rename(
+    loaded: &LoadedModule,
+    symbol: Symbol,
+    new_name: &str,
+) -> Result<Vec<FileEdits>, RenameError> {
+    let home = symbol.module_id();
+
+    if home.is_builtin() || !loaded.sources.contains_key(&home) {
+        return Err(RenameError::NotInWorkspace(symbol));
+    }
+
+    if !is_valid_name(new_name) {
+        return Err(RenameError::InvalidName(new_name.to_string()));
+    }
+
+    let old_name = symbol.as_str(&loaded.interns);
+
+    if old_name == new_name {
+        return Ok(Vec::new());
+    }
+
+    let home_name = loaded.interns.module_name(home).as_str();
+    // A module from a package is interned with its package shorthand, e.g. `pf.Task`
+    let home_name = home_name.rsplit('.').next().unwrap_or(home_name);
+    let mut all_edits = Vec::new();
+
+    for (module_id, (path, source)) in loaded.sources.iter() {
+        if module_id.is_builtin() {
+            continue;
+        }
+
+        let mut edits = header_edits(source, old_name, new_name, *module_id == home, home_name);
+
+        // The reference index tells us which modules mention the value at all, so the others
+        // don't have to be walked.
+        let mentioned = loaded
+            .references_by_id
+            .get(module_id)
+            .map_or(true, |index| !index.references(symbol).is_empty());
+
+        if let (true, Some(decls)) = (mentioned, loaded.declarations_by_id.get(module_id)) {
+            let mut finder = ReferenceFinder {
+                symbol,
+                source,
+                old_name,
+                new_name,
+                edits: &mut edits,
+            };
+
+            finder.visit_decls(decls);
+        }
+
+        if !edits.is_empty() {
+            edits.sort_by_key(|edit| edit.region);
+            edits.dedup();
+
+            if name_taken(
+                loaded,
+                *module_id,
+                source,
+                &edits,
+                new_name,
+                *module_id == home,
+            ) {
+                return Err(RenameError::NameTaken {
+                    name: new_name.to_string(),
+                    path: path.clone(),
+                });
+            }
+
+            all_edits.push(FileEdits {
+                module_id: *module_id,
+                path: path.clone(),
+                edits,
+            });
+        }
+    }
+
+    all_edits.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(all_edits)
+}
+
+/// Apply edits that are sorted and don't overlap, as returned by [rename].
+pub fn apply_edits(source: &str, edits: &[Edit]) -> String {
+    let mut result = String::with_capacity(source.len());
+    let mut copied_up_to = 0;
+
+    for edit in edits {
+        let start = edit.region.start().offset as usize;
+        let end = edit.region.end().offset as usize;
+
+        result.push_str(&source[copied_up_to..start]);
+        result.push_str(&edit.new_text);
+        copied_up_to = end;
+    }
+
+    result.push_str(&source[copied_up_to..]);
+
+    result
+}
+
+/// Whether `new_name` already means something else in a module that gets `edits`. Only the
+/// defining module and modules that import the value by name refer to it unqualified; a module
+/// that only writes `Dep.foo` can have its own `bar` without it clashing with `Dep.bar`.
+fn name_taken(
+    loaded: &LoadedModule,
+    module_id: ModuleId,
+    source: &str,
+    edits: &[Edit],
+    new_name: &str,
+    is_home: bool,
+) -> bool {
+    let unqualified = is_home
+        || edits
+            .iter()
+            .any(|edit| !source[..edit.region.start().offset as usize].ends_with('.'));
+
+    if !unqualified {
+        return false;
+    }
+
+    let defined = loaded
+        .interns
+        .all_ident_ids
+        .get(&module_id)
+        .and_then(|ident_ids| ident_ids.get_id(new_name))
+        .is_some();
+
+    defined || imports_by_name(source, new_name)
+}
+
+/// Whether the module's header imports a value called `name`, like `imports [Other.{ name }]`
+/// or `imports ["file.txt" as name : Str]`.
+fn imports_by_name(source: &str, name: &str) -> bool {
+    let arena = Bump::new();
+
+    let module = match roc_parse::module::parse_header(&arena, State::new(source.as_bytes())) {
+        Ok((module, _)) => module,
+        Err(_) => return false,
+    };
+
+    let imports = match &module.header {
+        Header::Interface(header) => Some(&header.imports.item),
+        Header::Hosted(header) => Some(&header.imports.item),
+        Header::App(header) => header.imports.as_ref().map(|imports| &imports.item),
+        Header::Platform(header) => Some(&header.imports.item),
+        Header::Package(_) => None,
+    };
+
+    let imported = imports
+        .into_iter()
+        .flat_map(|imports| imports.iter())
+        .any(|loc_entry| match loc_entry.value.extract_spaces().item {
+            ImportsEntry::Module(_, exposed) | ImportsEntry::Package(_, _, exposed) => exposed
+                .iter()
+                .any(|loc_name| <&str>::from(loc_name.value.extract_spaces().item) == name),
+            ImportsEntry::IngestedFile(_, typed_ident) => {
+                typed_ident.extract_spaces().item.ident.value == name
+            }
+        });
+
+    imported
+}
+
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    matches!(chars.next(), Some(c) if c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_alphanumeric())
+        && !KEYWORDS.contains(&name)
+}
+
+struct ReferenceFinder<'r> {
+    symbol: Symbol,
+    source: &'r str,
+    old_name: &'r str,
+    new_name: &'r str,
+    edits: &'r mut Vec<Edit>,
+}
+
+impl ReferenceFinder<'_> {
+    /// Rename the occurrence at `region`. For a qualified reference like `Json.foo`,
+    /// only the part after the last dot is replaced.
+    fn rename_at(&mut self, region: Region) {
+        let text = &self.source[region.start().offset as usize..region.end().offset as usize];
+
+        if text == self.old_name || text.ends_with(&format!(".{}", self.old_name)) {
+            let start = region.end().offset - self.old_name.len() as u32;
+
+            self.edits.push(Edit {
+                region: Region::new(Position::new(start), region.end()),
+                new_text: self.new_name.to_string(),
+            });
+        }
+    }
+
+    /// A shorthand record field like `{ foo }` has to become `{ foo: bar }`,
+    /// or the field would be renamed along with the value.
+    fn expand_shorthand(&mut self, region: Region, label: &str) {
+        let text = &self.source[region.start().offset as usize..region.end().offset as usize];
+
+        if text == label {
+            self.edits.push(Edit {
+                region,
+                new_text: format!("{}: {}", label, self.new_name),
+            });
+        } else {
+            self.rename_at(region);
+        }
+    }
+
+    /// An annotation like `foo : Str` only keeps the region of `Str`, so look for the name
+    /// right before the colon.
+    fn rename_annotation(&mut self, signature_region: Region) {
+        let before = self.source[..signature_region.start().offset as usize].trim_end();
+
+        if let Some(before_colon) = before.strip_suffix(':') {
+            let before_colon = before_colon.trim_end();
+
+            if before_colon.ends_with(self.old_name) {
+                let end = before_colon.len() as u32;
+                let start = end - self.old_name.len() as u32;
+
+                self.rename_at(Region::new(Position::new(start), Position::new(end)));
+            }
+        }
+    }
+}
+
+impl Visitor for ReferenceFinder<'_> {
+    fn visit_decls(&mut self, decls: &Declarations) {
+        for (loc_symbol, annotation) in decls.symbols.iter().zip(decls.annotations.iter()) {
+            if let (true, Some(annotation)) = (loc_symbol.value == self.symbol, annotation) {
+                self.rename_annotation(annotation.region);
+            }
+        }
+
+        walk_decls(self, decls);
+    }
+
+    fn visit_def(&mut self, def: &Def) {
+        if let (Pattern::Identifier(symbol), Some(annotation)) =
+            (&def.loc_pattern.value, &def.annotation)
+        {
+            if *symbol == self.symbol {
+                self.rename_annotation(annotation.region);
+            }
+        }
+
+        walk_def(self, def);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+        match expr {
+            Expr::Var(symbol, _) | Expr::AbilityMember(symbol, _, _) if *symbol == self.symbol => {
+                self.rename_at(region);
+            }
+            Expr::Record { fields, .. } => {
+                for (label, field) in fields.iter() {
+                    let is_shorthand = field.region == field.loc_expr.region;
+
+                    match &field.loc_expr.value {
+                        Expr::Var(symbol, _) if *symbol == self.symbol && is_shorthand => {
+                            self.expand_shorthand(field.region, label.as_str());
+                        }
+                        other => self.visit_expr(other, field.loc_expr.region, field.var),
+                    }
+                }
+
+                return;
+            }
+            _ => {}
+        }
+
+        walk_expr(self, expr, var);
+    }
+
+    fn visit_pattern(&mut self, pattern: &Pattern, region: Region, _opt_var: Option<Variable>) {
+        if let Pattern::Identifier(symbol) = pattern {
+            if *symbol == self.symbol {
+                self.rename_at(region);
+            }
+        }
+
+        walk_pattern(self, pattern);
+    }
+
+    fn visit_record_destruct(&mut self, destruct: &RecordDestruct, region: Region) {
+        match destruct.typ {
+            DestructType::Required if destruct.symbol == self.symbol => {
+                self.expand_shorthand(region, destruct.label.as_str());
+            }
+            _ => walk_record_destruct(self, destruct),
+        }
+    }
+}
+
+/// Edits for the `exposes` and `provides` lists of the module defining the value,
+/// and for the `imports` lists of modules that import it by name.
+fn header_edits(
+    source: &str,
+    old_name: &str,
+    new_name: &str,
+    is_home: bool,
+    home_name: &str,
+) -> Vec<Edit> {
+    let arena = Bump::new();
+    let mut edits = Vec::new();
+
+    let module = match roc_parse::module::parse_header(&arena, State::new(source.as_bytes())) {
+        Ok((module, _)) => module,
+        Err(_) => return edits,
+    };
+
+    let mut rename_in = |names: &Collection<Loc<Spaced<ExposedName>>>| {
+        for loc_name in names.iter() {
+            if <&str>::from(loc_name.value.extract_spaces().item) == old_name {
+                if let Some(region) = name_within(source, loc_name.region, old_name) {
+                    edits.push(Edit {
+                        region,
+                        new_text: new_name.to_string(),
+                    });
+                }
+            }
+        }
+    };
+
+    let imports = match &module.header {
+        Header::Interface(header) => {
+            if is_home {
+                rename_in(&header.exposes.item);
+            }
+
+            Some(&header.imports.item)
+        }
+        Header::Hosted(header) => {
+            if is_home {
+                rename_in(&header.exposes.item);
+            }
+
+            Some(&header.imports.item)
+        }
+        Header::App(header) => {
+            if is_home {
+                rename_in(&header.provides.entries);
+            }
+
+            header.imports.as_ref().map(|imports| &imports.item)
+        }
+        Header::Platform(header) => {
+            if is_home {
+                rename_in(&header.provides.item);
+            }
+
+            Some(&header.imports.item)
+        }
+        Header::Package(_) => None,
+    };
+
+    for loc_entry in imports.into_iter().flat_map(|imports| imports.iter()) {
+        match loc_entry.value.extract_spaces().item {
+            ImportsEntry::Module(module_name, exposed)
+            | ImportsEntry::Package(_, module_name, exposed)
+                if <&str>::from(module_name) == home_name =>
+            {
+                rename_in(&exposed);
+            }
+            _ => {}
+        }
+    }
+
+    edits
+}
+
+/// The region of `name` as a whole word inside `region`.
+fn name_within(source: &str, region: Region, name: &str) -> Option<Region> {
+    let start = region.start().offset as usize;
+    let text = &source[start..region.end().offset as usize];
+    let is_ident_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+
+    text.match_indices(name)
+        .map(|(index, _)| index)
+        .find(|&index| {
+            let before = text[..index].chars().next_back();
+            let after = text[index + name.len()..].chars().next();
+
+            !before.map_or(false, is_ident_char) && !after.map_or(false, is_ident_char)
+        })
+        .map(|index| {
+            let start = (start + index) as u32;
+
+            Region::new(
+                Position::new(start),
+                Position::new(start + name.len() as u32),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use roc_load::{ExecutionMode, LoadConfig, Threading};
+    use roc_packaging::cache::RocCacheDir;
+    use roc_reporting::report::{RenderTarget, DEFAULT_PALETTE};
+    use roc_target::TargetInfo;
+    use std::fs;
+    use std::path::Path;
+
+    /// Rename `foo` from `Dep` to `bar`, in a `Main` module that imports `Dep`.
+    fn rename_in_dep(dir: &Path, main_source: &str) -> Result<Vec<FileEdits>, RenameError> {
+        let dep_source = indoc!(
+            r#"
+            interface Dep exposes [foo] imports []
+
+            foo = 1
+            "#
+        );
+        let main_path = dir.join("Main.roc");
+
+        fs::write(dir.join("Dep.roc"), dep_source).unwrap();
+        fs::write(&main_path, main_source).unwrap();
+
+        let arena = Bump::new();
+        let load_config = LoadConfig {
+            target_info: TargetInfo::default_x86_64(),
+            render: RenderTarget::Generic,
+            palette: DEFAULT_PALETTE,
+            threading: Threading::Single,
+            exec_mode: ExecutionMode::Check,
+            max_specializations: None,
+            coverage: false,
+            fold_constants: false,
+            hoist_loop_invariants: false,
+            strip_expects: false,
+            cancelled: None,
+        };
+        let loaded =
+            roc_load::load_and_typecheck(&arena, main_path, RocCacheDir::Disallowed, load_config)
+                .unwrap();
+        let dep_id = *loaded
+            .sources
+            .iter()
+            .find(|(_, (path, _))| path.ends_with("Dep.roc"))
+            .unwrap()
+            .0;
+        let symbol = find_top_level(&loaded, dep_id, "foo").unwrap();
+
+        rename(&loaded, symbol, "bar")
+    }
+
+    #[test]
+    fn name_taken_in_a_module_that_imports_it_by_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = rename_in_dep(
+            dir.path(),
+            indoc!(
+                r#"
+                interface Main exposes [y] imports [Dep.{ foo }]
+
+                bar = 2
+
+                y = foo + bar
+                "#
+            ),
+        );
+
+        assert_eq!(
+            result.unwrap_err(),
+            RenameError::NameTaken {
+                name: "bar".to_string(),
+                path: dir.path().join("Main.roc"),
+            }
+        );
+    }
+
+    #[test]
+    fn same_name_in_a_module_that_qualifies_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = rename_in_dep(
+            dir.path(),
+            indoc!(
+                r#"
+                interface Main exposes [y] imports [Dep]
+
+                bar = 2
+
+                y = Dep.foo + bar
+                "#
+            ),
+        )
+        .unwrap();
+
+        let main = files
+            .iter()
+            .find(|file| file.path.ends_with("Main.roc"))
+            .unwrap();
+
+        assert_eq!(main.edits.len(), 1);
+        assert_eq!(main.edits[0].new_text, "bar");
+    }
+
+    #[test]
+    fn apply_sorted_edits() {
+        let source = "x = foo 1\ny = Dep.foo 2\n";
+        let edit = |start: u32, end: u32| Edit {
+            region: Region::new(Position::new(start), Position::new(end)),
+            new_text: "bar".to_string(),
+        };
+
+        assert_eq!(
+            apply_edits(source, &[edit(4, 7), edit(18, 21)]),
+            "x = bar 1\ny = Dep.bar 2\n"
+        );
+    }
+
+    #[test]
+    fn header_lists() {
+        let source = "interface Dep\n    exposes [foo, fooBar]\n    imports [Other.{ foo }]\n";
+
+        let edits = header_edits(source, "foo", "bar", true, "Other");
+        let renamed = apply_edits(source, &edits);
+
+        assert_eq!(
+            renamed,
+            "interface Dep\n    exposes [bar, fooBar]\n    imports [Other.{ bar }]\n"
+        );
+    }
+
+    #[test]
+    fn valid_names() {
+        assert!(is_valid_name("fooBar2"));
+        assert!(!is_valid_name("FooBar"));
+        assert!(!is_valid_name("foo_bar"));
+        assert!(!is_valid_name("when"));
+        assert!(!is_valid_name(""));
+    }
+}
@@ -0,0 +1,403 @@
+//! Classify the spans of a Roc source file for syntax highlighting, so editors can use
+//! `textDocument/semanticTokens` instead of a regex grammar.
+//!
+//! This refines the tokens from [roc_parse::highlight], which can't tell a module from a type
+//! or a tag, or a field from a variable. Those are told apart by the tokens around them, so
+//! it works on code that doesn't parse, which is most code while it's being edited.
+use bumpalo::Bump;
+use roc_parse::highlight::{highlight, Token};
+use roc_parse::state::State;
+use roc_region::all::{Loc, Region};
+
+use crate::analysis::to_position;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SemanticToken {
+    Keyword,
+    Type,
+    Tag,
+    Field,
+    Module,
+    Number,
+    String,
+    Comment,
+}
+
+impl SemanticToken {
+    /// In the order of the legend sent to LSP clients.
+    pub const ALL: [SemanticToken; 8] = [
+        SemanticToken::Keyword,
+        SemanticToken::Type,
+        SemanticToken::Tag,
+        SemanticToken::Field,
+        SemanticToken::Module,
+        SemanticToken::Number,
+        SemanticToken::String,
+        SemanticToken::Comment,
+    ];
+
+    /// The standard LSP token type, which editor themes already have colors for.
+    pub fn lsp_name(self) -> &'static str {
+        match self {
+            SemanticToken::Keyword => "keyword",
+            SemanticToken::Type => "type",
+            SemanticToken::Tag => "enumMember",
+            SemanticToken::Field => "property",
+            SemanticToken::Module => "namespace",
+            SemanticToken::Number => "number",
+            SemanticToken::String => "string",
+            SemanticToken::Comment => "comment",
+        }
+    }
+}
+
+/// The classified spans of the source, in order. Spans that aren't any of the
+/// [SemanticToken] kinds, like variables and operators, are left out.
+pub fn semantic_tokens(source: &str) -> Vec<Loc<SemanticToken>> {
+    let tokens = highlight(source);
+    let header_end = header_end(source);
+    let mut classifier = Classifier {
+        source,
+        tokens: &tokens,
+        open_brackets: Vec::new(),
+        type_indent: None,
+    };
+
+    let mut result = Vec::with_capacity(tokens.len());
+
+    for index in 0..tokens.len() {
+        if let Some(token) = classifier.classify(index, header_end) {
+            result.push(Loc::at(tokens[index].region, token));
+        }
+    }
+
+    result
+}
+
+/// Encode tokens the way `textDocument/semanticTokens/full` expects: five numbers per token,
+/// with each position relative to the previous token. Tokens spanning several lines, like
+/// multiline strings, are split into one token per line.
+pub fn encode_for_lsp(source: &str, tokens: &[Loc<SemanticToken>]) -> Vec<u32> {
+    let mut data = Vec::with_capacity(tokens.len() * 5);
+    let mut previous_line = 0;
+    let mut previous_start = 0;
+
+    for token in tokens {
+        let token_type = SemanticToken::ALL
+            .iter()
+            .position(|kind| *kind == token.value)
+            .unwrap_or_default() as u32;
+
+        let start = token.region.start().offset as usize;
+        let text = &source[start..token.region.end().offset as usize];
+        let mut line_start = start;
+
+        for line in text.split('\n') {
+            let position = to_position(source, line_start as u32);
+            let length = line.trim_end_matches('\r').encode_utf16().count() as u32;
+
+            if length > 0 {
+                let delta_line = position.line - previous_line;
+                let delta_start = if delta_line == 0 {
+                    position.character - previous_start
+                } else {
+                    position.character
+                };
+
+                data.extend([delta_line, delta_start, length, token_type, 0]);
+
+                previous_line = position.line;
+                previous_start = position.character;
+            }
+
+            line_start += line.len() + 1;
+        }
+    }
+
+    data
+}
+
+fn header_end(source: &str) -> u32 {
+    let arena = Bump::new();
+
+    match roc_parse::module::parse_header(&arena, State::new(source.as_bytes())) {
+        Ok((_, state)) => state.pos().offset,
+        Err(_) => 0,
+    }
+}
+
+struct Classifier<'a> {
+    source: &'a str,
+    tokens: &'a [Loc<Token>],
+    /// The brackets, braces and parens that are open at the current token
+    open_brackets: Vec<u8>,
+    /// In a type annotation, the indentation of the line it starts on.
+    /// The annotation continues until a line that's indented no further than that.
+    type_indent: Option<usize>,
+}
+
+impl<'a> Classifier<'a> {
+    fn classify(&mut self, index: usize, header_end: u32) -> Option<SemanticToken> {
+        let token = self.tokens[index].value;
+        let region = self.tokens[index].region;
+        let in_header = region.start().offset < header_end;
+
+        if let Some(indent) = self.type_indent {
+            if self.starts_line(region) && self.indent(region) <= indent {
+                self.type_indent = None;
+            }
+        }
+
+        match token {
+            Token::Keyword => Some(SemanticToken::Keyword),
+            Token::Number => Some(SemanticToken::Number),
+            Token::String | Token::SingleQuote => Some(SemanticToken::String),
+            Token::LineComment | Token::DocComment => Some(SemanticToken::Comment),
+            Token::Brace | Token::Bracket | Token::Paren => {
+                match self.text(region).as_bytes()[0] {
+                    open @ (b'{' | b'[' | b'(') => self.open_brackets.push(open),
+                    _ => {
+                        self.open_brackets.pop();
+                    }
+                }
+
+                None
+            }
+            Token::Colon | Token::ColonEquals if !in_header && self.type_indent.is_none() => {
+                // A colon in a record, like `{ x: 1 }`, doesn't start an annotation.
+                if !self.open_brackets.contains(&b'{') {
+                    let line_start = self.source[..region.start().offset as usize]
+                        .rfind('\n')
+                        .map_or(0, |newline| newline + 1);
+                    let line = &self.source[line_start..];
+
+                    self.type_indent = Some(line.len() - line.trim_start().len());
+                }
+
+                None
+            }
+            Token::UpperIdent => Some(self.classify_upper(index, in_header)),
+            Token::LowerIdent => self.classify_lower(index),
+            _ => None,
+        }
+    }
+
+    fn classify_upper(&self, index: usize, in_header: bool) -> SemanticToken {
+        if self.is_qualifier(index) {
+            SemanticToken::Module
+        } else if in_header {
+            // e.g. `imports [Json.{ Decoder }]`
+            if self.open_brackets.last() == Some(&b'{') {
+                SemanticToken::Type
+            } else {
+                SemanticToken::Module
+            }
+        } else if self.is_after(index, Token::AtSign) {
+            // an opaque type, e.g. `@Age 23`
+            SemanticToken::Type
+        } else if self.type_indent.is_some() {
+            // In a type, uppercase names are types, except right at the start
+            // of a tag union's elements, e.g. `[Ok Str, Err Str]`.
+            let starts_element = matches!(
+                self.previous(index).map(|token| token.value),
+                Some(Token::Bracket | Token::Comma)
+            );
+
+            if starts_element && self.open_brackets.last() == Some(&b'[') {
+                SemanticToken::Tag
+            } else {
+                SemanticToken::Type
+            }
+        } else if self.defines_type(index) {
+            SemanticToken::Type
+        } else {
+            SemanticToken::Tag
+        }
+    }
+
+    fn classify_lower(&self, index: usize) -> Option<SemanticToken> {
+        let region = self.tokens[index].region;
+
+        if let Some(dot) = self.previous(index) {
+            let is_access = dot.value == Token::Decimal && dot.region.end() == region.start();
+
+            if is_access {
+                // `Str.concat` is a qualified value, but `user.name` and `.name` are fields.
+                let qualified = matches!(
+                    self.previous(index - 1),
+                    Some(Loc { value: Token::UpperIdent, region }) if region.end() == dot.region.start()
+                );
+
+                return if qualified {
+                    None
+                } else {
+                    Some(SemanticToken::Field)
+                };
+            }
+        }
+
+        let before_colon = matches!(
+            self.next(index).map(|token| token.value),
+            Some(Token::Colon | Token::QuestionMark)
+        );
+
+        if before_colon && self.open_brackets.last() == Some(&b'{') {
+            Some(SemanticToken::Field)
+        } else {
+            None
+        }
+    }
+
+    /// Whether this is the `Json` in `Json.decode` or `Json.{ decode }`
+    fn is_qualifier(&self, index: usize) -> bool {
+        let region = self.tokens[index].region;
+
+        match self.next(index) {
+            Some(dot) => dot.value == Token::Decimal && dot.region.start() == region.end(),
+            None => false,
+        }
+    }
+
+    /// Whether this starts a type alias, opaque type or ability definition,
+    /// e.g. `Pair a : (a, a)`
+    fn defines_type(&self, index: usize) -> bool {
+        if !self.starts_line(self.tokens[index].region) {
+            return false;
+        }
+
+        for token in &self.tokens[index + 1..] {
+            match token.value {
+                Token::LowerIdent => continue,
+                Token::Colon | Token::ColonEquals => return true,
+                Token::Keyword => return self.text(token.region) == "implements",
+                _ => return false,
+            }
+        }
+
+        false
+    }
+
+    fn is_after(&self, index: usize, token: Token) -> bool {
+        matches!(self.previous(index), Some(previous) if previous.value == token)
+    }
+
+    fn previous(&self, index: usize) -> Option<&Loc<Token>> {
+        self.tokens[..index]
+            .iter()
+            .rev()
+            .find(|token| !is_comment(token))
+    }
+
+    fn next(&self, index: usize) -> Option<&Loc<Token>> {
+        self.tokens[index + 1..]
+            .iter()
+            .find(|token| !is_comment(token))
+    }
+
+    fn text(&self, region: Region) -> &'a str {
+        &self.source[region.start().offset as usize..region.end().offset as usize]
+    }
+
+    fn starts_line(&self, region: Region) -> bool {
+        let before = &self.source[..region.start().offset as usize];
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+
+        before[line_start..].trim().is_empty()
+    }
+
+    fn indent(&self, region: Region) -> usize {
+        let before = &self.source[..region.start().offset as usize];
+
+        before.len() - before.rfind('\n').map_or(0, |newline| newline + 1)
+    }
+}
+
+fn is_comment(token: &Loc<Token>) -> bool {
+    matches!(token.value, Token::LineComment | Token::DocComment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classified(source: &str) -> Vec<(&str, SemanticToken)> {
+        semantic_tokens(source)
+            .into_iter()
+            .map(|token| {
+                let start = token.region.start().offset as usize;
+                let end = token.region.end().offset as usize;
+
+                (&source[start..end], token.value)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn classify_body() {
+        use SemanticToken::*;
+
+        let source = indoc::indoc!(
+            r#"
+            interface Foo
+                exposes [parse]
+                imports [Json.{ Decoder }]
+
+            Shape : [Circle F64, Rect { w : F64 }]
+
+            # Parse a shape
+            parse : Str -> Result Shape [Invalid]
+            parse = \str ->
+                when Str.toF64 str is
+                    Ok r -> Ok (Circle r)
+                    Err _ -> Err { reason: "bad", at: 1 }.reason
+            "#
+        );
+
+        assert_eq!(
+            classified(source),
+            vec![
+                ("interface", Keyword),
+                ("Foo", Module),
+                ("exposes", Keyword),
+                ("imports", Keyword),
+                ("Json", Module),
+                ("Decoder", Type),
+                ("Shape", Type),
+                ("Circle", Tag),
+                ("F64", Type),
+                ("Rect", Tag),
+                ("w", Field),
+                ("F64", Type),
+                ("# Parse a shape", Comment),
+                ("Str", Type),
+                ("Result", Type),
+                ("Shape", Type),
+                ("Invalid", Tag),
+                ("when", Keyword),
+                ("Str", Module),
+                ("is", Keyword),
+                ("Ok", Tag),
+                ("Ok", Tag),
+                ("Circle", Tag),
+                ("Err", Tag),
+                ("Err", Tag),
+                ("reason", Field),
+                ("\"bad\"", String),
+                ("at", Field),
+                ("1", Number),
+                ("reason", Field),
+            ]
+        );
+    }
+
+    #[test]
+    fn encode_relative_positions() {
+        let source = "x = 1\n  y = \"a\"\n";
+        let tokens = semantic_tokens(source);
+
+        assert_eq!(
+            encode_for_lsp(source, &tokens),
+            vec![0, 4, 1, 5, 0, 1, 6, 3, 6, 0]
+        );
+    }
+}
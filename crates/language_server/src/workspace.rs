@@ -0,0 +1,187 @@
+//! The open documents, and which of them need to be checked again when one of them changes.
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::analysis::Analysis;
+
+/// Every open document's latest analysis, by URI.
+///
+/// A document is only checked again when its own text changes, or when a module it imports is
+/// saved: modules that aren't open are read from disk, so unsaved edits to one open document
+/// can't change the diagnostics of another.
+#[derive(Default)]
+pub struct Workspace {
+    documents: HashMap<String, Document>,
+}
+
+struct Document {
+    path: PathBuf,
+    analysis: Analysis,
+}
+
+impl Workspace {
+    /// Check a document that was just opened or changed. Returns `false` without checking it
+    /// again if its text is the same as what was already checked.
+    pub fn update(&mut self, uri: &str, path: PathBuf, source: String) -> bool {
+        if let Some(document) = self.documents.get(uri) {
+            if document.path == path && document.analysis.source() == source {
+                return false;
+            }
+        }
+
+        let analysis = Analysis::new(&path, source);
+
+        self.documents
+            .insert(uri.to_string(), Document { path, analysis });
+
+        true
+    }
+
+    /// Check a saved document again, along with every other open document that imports it,
+    /// directly or not. Returns the URIs of the documents that were checked.
+    pub fn save(&mut self, uri: &str, source: Option<String>) -> Vec<String> {
+        let path = match self.documents.get(uri) {
+            Some(document) => document.path.clone(),
+            None => return Vec::new(),
+        };
+
+        let mut rechecked: Vec<String> = self
+            .documents
+            .iter()
+            .filter(|(other_uri, document)| {
+                *other_uri == uri || document.analysis.depends_on(&path)
+            })
+            .map(|(other_uri, _)| other_uri.clone())
+            .collect();
+
+        rechecked.sort();
+
+        for recheck_uri in rechecked.iter() {
+            let document = self.documents.get_mut(recheck_uri).unwrap();
+            let source = match &source {
+                Some(source) if recheck_uri == uri => source.clone(),
+                _ => document.analysis.source().to_string(),
+            };
+
+            document.analysis = Analysis::new(&document.path, source);
+        }
+
+        rechecked
+    }
+
+    pub fn close(&mut self, uri: &str) {
+        self.documents.remove(uri);
+    }
+
+    pub fn get(&mut self, uri: &str) -> Option<&mut Analysis> {
+        self.documents
+            .get_mut(uri)
+            .map(|document| &mut document.analysis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use std::fs;
+    use std::path::Path;
+
+    fn write(dir: &Path, name: &str, source: &str) -> (String, PathBuf, String) {
+        let path = dir.join(name);
+
+        fs::write(&path, source).unwrap();
+
+        (
+            format!("file://{}", path.display()),
+            path,
+            source.to_string(),
+        )
+    }
+
+    #[test]
+    fn unchanged_text_is_not_checked_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let (uri, path, source) = write(
+            dir.path(),
+            "Main.roc",
+            "interface Main exposes [x] imports []\n\nx = 1\n",
+        );
+        let mut workspace = Workspace::default();
+
+        assert!(workspace.update(&uri, path.clone(), source.clone()));
+        assert!(!workspace.update(&uri, path.clone(), source));
+        assert!(workspace.update(
+            &uri,
+            path,
+            "interface Main exposes [x] imports []\n\nx = 2\n".into()
+        ));
+    }
+
+    #[test]
+    fn saving_rechecks_the_documents_that_import_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let (dep_uri, dep_path, dep_source) = write(
+            dir.path(),
+            "Dep.roc",
+            indoc!(
+                r#"
+                interface Dep exposes [x] imports []
+
+                x = 1
+                "#
+            ),
+        );
+        let (main_uri, main_path, main_source) = write(
+            dir.path(),
+            "Main.roc",
+            indoc!(
+                r#"
+                interface Main exposes [y] imports [Dep]
+
+                y = Dep.x + 1
+                "#
+            ),
+        );
+        let (other_uri, other_path, other_source) = write(
+            dir.path(),
+            "Other.roc",
+            indoc!(
+                r#"
+                interface Other exposes [z] imports []
+
+                z = 2
+                "#
+            ),
+        );
+        let mut workspace = Workspace::default();
+
+        workspace.update(&dep_uri, dep_path.clone(), dep_source);
+        workspace.update(&main_uri, main_path, main_source);
+        workspace.update(&other_uri, other_path, other_source);
+
+        assert!(workspace.get(&main_uri).unwrap().diagnostics.is_empty());
+
+        let (_, _, dep_source) = write(
+            dir.path(),
+            "Dep.roc",
+            indoc!(
+                r#"
+                interface Dep exposes [x] imports []
+
+                x = "one"
+                "#
+            ),
+        );
+
+        // Editing Dep doesn't affect Main until it's saved, since Main reads Dep from disk.
+        workspace.update(&dep_uri, dep_path, dep_source);
+        assert!(workspace.get(&main_uri).unwrap().diagnostics.is_empty());
+
+        let mut expected = vec![dep_uri.clone(), main_uri.clone()];
+        expected.sort();
+
+        assert_eq!(workspace.save(&dep_uri, None), expected);
+        assert!(!workspace.get(&main_uri).unwrap().diagnostics.is_empty());
+    }
+}
@@ -25,7 +25,7 @@ use crate::{
 
 const MIN_SECTION_ALIGNMENT: usize = 0x40;
 
-// TODO: Analyze if this offset is always correct.
+// Every PLT entry is 16 bytes, both in `.plt` and in `.plt.sec`.
 const PLT_ADDRESS_OFFSET: u64 = 0x10;
 
 struct ElfDynamicDeps {
@@ -333,6 +333,85 @@ impl<'a> Surgeries<'a> {
     }
 }
 
+/// Finds the PLT entry that calls to each app function go through.
+///
+/// Rather than assuming a layout, this matches the GOT slot every PLT entry jumps through
+/// against the `JUMP_SLOT` relocations of the app functions. That way it works for the classic
+/// `.plt`, and for `.plt.sec`, which is what hosts built with `-fcf-protection` call instead.
+/// Hosts built with `-fno-plt` have no PLT at all, and only go through the GOT.
+fn collect_plt_addresses(
+    exec_obj: &object::File,
+    app_syms: &[Symbol],
+    verbose: bool,
+) -> MutMap<String, (u64, u64)> {
+    let got_slots: MutMap<u64, &str> = exec_obj
+        .dynamic_relocations()
+        .into_iter()
+        .flatten()
+        .filter_map(|(got_address, reloc)| {
+            if let RelocationKind::Elf(elf::R_X86_64_JUMP_SLOT) = reloc.kind() {
+                app_syms
+                    .iter()
+                    .find(|sym| reloc.target() == RelocationTarget::Symbol(sym.index()))
+                    .map(|sym| (got_address, sym.name().unwrap()))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    let mut plt_addresses = MutMap::default();
+
+    for plt_section_name in [".plt", ".plt.sec"] {
+        let section = match exec_obj.section_by_name(plt_section_name) {
+            Some(section) => section,
+            None => continue,
+        };
+        let plt_offset = match section.compressed_file_range() {
+            Ok(
+                range @ CompressedFileRange {
+                    format: CompressionFormat::None,
+                    ..
+                },
+            ) => range.offset,
+            _ => {
+                internal_error!(
+                    "Surgical linking does not work with compressed {} section",
+                    plt_section_name
+                );
+            }
+        };
+        let plt_address = section.address();
+        if verbose {
+            println!("{} Address: {:+x}", plt_section_name, plt_address);
+            println!("{} File Offset: {:+x}", plt_section_name, plt_offset);
+        }
+
+        let data = section.data().unwrap_or_else(|err| {
+            internal_error!("Failed to load {} section: {}", plt_section_name, err)
+        });
+        let mut decoder = Decoder::with_ip(64, data, plt_address, DecoderOptions::NONE);
+        let mut inst = Instruction::default();
+
+        while decoder.can_decode() {
+            decoder.decode_out(&mut inst);
+
+            if !(inst.is_jmp_near_indirect() && inst.is_ip_rel_memory_operand()) {
+                continue;
+            }
+
+            if let Some(name) = got_slots.get(&inst.ip_rel_memory_address()) {
+                // The entry may start with an endbr64, so go back to its start.
+                let func_address = inst.ip() - (inst.ip() - plt_address) % PLT_ADDRESS_OFFSET;
+                let func_offset = func_address - plt_address + plt_offset;
+                plt_addresses.insert(name.to_string(), (func_offset, func_address));
+            }
+        }
+    }
+
+    plt_addresses
+}
+
 /// Constructs a `Metadata` from a host executable binary, and writes it to disk
 pub(crate) fn preprocess_elf(
     endianness: target_lexicon::Endianness,
@@ -390,65 +469,22 @@ pub(crate) fn preprocess_elf(
     // procedures/functions whose address isn't known in the time of linking, and is left
     // to be resolved by the dynamic linker at run time.
     let symbol_and_plt_processing_start = Instant::now();
-    let plt_section_name = ".plt";
-    let (plt_address, plt_offset) = match exec_obj.section_by_name(plt_section_name) {
-        Some(section) => {
-            let file_offset = match section.compressed_file_range() {
-                Ok(
-                    range @ CompressedFileRange {
-                        format: CompressionFormat::None,
-                        ..
-                    },
-                ) => range.offset,
-                _ => {
-                    internal_error!("Surgical linking does not work with compressed plt section");
-                }
-            };
-            (section.address(), file_offset)
-        }
-        None => {
-            internal_error!("Failed to find PLT section. Probably an malformed executable.");
-        }
-    };
-    if verbose {
-        println!("PLT Address: {:+x}", plt_address);
-        println!("PLT File Offset: {:+x}", plt_offset);
-    }
-
     let app_syms: Vec<_> = exec_obj
         .dynamic_symbols()
         .filter(is_roc_undefined)
         .collect();
 
-    let mut app_func_addresses: MutMap<u64, &str> = MutMap::default();
+    md.plt_addresses = collect_plt_addresses(&exec_obj, &app_syms, verbose);
 
-    let plt_relocs = (match exec_obj.dynamic_relocations() {
-                Some(relocs) => relocs,
-                None => {
-                    internal_error!("Executable does not have any dynamic relocations. No work to do. Probably an invalid input.");
-                }
-            })
-            .filter_map(|(_, reloc)| {
-                if let RelocationKind::Elf(7) = reloc.kind() {
-                    Some(reloc)
-                } else {
-                    None
-                }
-            });
-    for (i, reloc) in plt_relocs.enumerate() {
-        for symbol in app_syms.iter() {
-            if reloc.target() == RelocationTarget::Symbol(symbol.index()) {
-                let func_address = (i as u64 + 1) * PLT_ADDRESS_OFFSET + plt_address;
-                let func_offset = (i as u64 + 1) * PLT_ADDRESS_OFFSET + plt_offset;
-                app_func_addresses.insert(func_address, symbol.name().unwrap());
-                md.plt_addresses.insert(
-                    symbol.name().unwrap().to_string(),
-                    (func_offset, func_address),
-                );
-                break;
-            }
-        }
-    }
+    let app_func_addresses: MutMap<u64, &str> = app_syms
+        .iter()
+        .filter_map(|sym| {
+            let name = sym.name().unwrap();
+            md.plt_addresses
+                .get(name)
+                .map(|(_, func_address)| (*func_address, name))
+        })
+        .collect();
 
     for sym in app_syms.iter() {
         let name = sym.name().unwrap().to_string();
@@ -1749,6 +1785,14 @@ mod tests {
         )
     }
 
+    #[test]
+    fn dynhost_defines_host_hooks() {
+        let mut triple = Triple::host();
+        triple.binary_format = target_lexicon::BinaryFormat::Elf;
+
+        assert!(crate::missing_host_hooks(&triple, ELF64_DYNHOST).is_empty());
+    }
+
     #[test]
     fn collect_undefined_symbols_elf() {
         let object = object::File::parse(ELF64_DYNHOST).unwrap();
@@ -1774,6 +1818,30 @@ mod tests {
         )
     }
 
+    #[test]
+    fn collect_plt_addresses_elf() {
+        let object = object::File::parse(ELF64_DYNHOST).unwrap();
+
+        let app_syms: Vec<_> = object.dynamic_symbols().filter(is_roc_undefined).collect();
+        let plt_addresses = collect_plt_addresses(&object, &app_syms, false);
+
+        let mut entries: Vec<_> = plt_addresses
+            .iter()
+            .map(|(name, (offset, address))| (name.as_str(), *offset, *address))
+            .collect();
+        entries.sort_unstable();
+
+        assert_eq!(
+            [
+                ("roc__mainForHost_1__Fx_caller", 0x521e0, 0x531e0),
+                ("roc__mainForHost_1__Fx_result_size", 0x521d0, 0x531d0),
+                ("roc__mainForHost_1_exposed_generic", 0x521b0, 0x531b0),
+                ("roc__mainForHost_size", 0x521a0, 0x531a0),
+            ],
+            entries.as_slice()
+        )
+    }
+
     #[allow(dead_code)]
     fn zig_host_app_help(dir: &Path, target: &Triple) {
         let host_zig = indoc!(
@@ -4,8 +4,8 @@
 //! system and final linking of the executable, it is significantly less
 //! practical to use a regular linker.
 use memmap2::{Mmap, MmapMut};
-use object::Object;
-use roc_error_macros::internal_error;
+use object::{Object, ObjectSymbol};
+use roc_error_macros::{internal_error, user_error};
 use roc_load::{EntryPoint, ExecutionMode, ExposedToHost, LoadConfig, Threading};
 use roc_module::symbol::Interns;
 use roc_packaging::cache::RocCacheDir;
@@ -21,13 +21,17 @@ mod macho;
 mod pe;
 
 mod generate_dylib;
+mod size_report;
+
+pub use size_report::{size_report, CodeOrigin, SizeReportEntry};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum LinkType {
-    // These numbers correspond to the --lib and --no-link flags
+    // These numbers correspond to the --lib, --no-link and --static-lib flags
     Executable = 0,
     Dylib = 1,
     None = 2,
+    StaticLib = 3,
 }
 
 pub fn supported(link_type: LinkType, target: &Triple) -> bool {
@@ -63,11 +67,95 @@ pub fn supported(link_type: LinkType, target: &Triple) -> bool {
 
 pub const PRECOMPILED_HOST_EXT: &str = "rh"; // Short for "roc host"
 
+/// A function that every host must define, because compiled Roc code calls it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HostHook {
+    pub name: &'static str,
+    /// The C signature the host's definition must have
+    pub signature: &'static str,
+}
+
+/// The hooks the builtins and generated code call into. These are the same for every app,
+/// so e.g. `roc glue` can use this table to generate stubs for them.
+pub const REQUIRED_HOST_HOOKS: &[HostHook] = &[
+    HostHook {
+        name: "roc_alloc",
+        signature: "void* roc_alloc(size_t size, uint32_t alignment)",
+    },
+    HostHook {
+        name: "roc_realloc",
+        signature:
+            "void* roc_realloc(void* ptr, size_t new_size, size_t old_size, uint32_t alignment)",
+    },
+    HostHook {
+        name: "roc_dealloc",
+        signature: "void roc_dealloc(void* ptr, uint32_t alignment)",
+    },
+    HostHook {
+        name: "roc_panic",
+        signature: "void roc_panic(const struct RocStr* msg, uint32_t tag_id)",
+    },
+];
+
+/// The required hooks that the given host binary does not define.
+/// If the binary has no symbol table (e.g. it was stripped), we can't tell, so nothing is reported.
+pub fn missing_host_hooks(target: &Triple, host_bytes: &[u8]) -> Vec<HostHook> {
+    let object = match object::File::parse(host_bytes) {
+        Ok(object) => object,
+        Err(_) => return Vec::new(),
+    };
+
+    if object.symbols().next().is_none() && object.dynamic_symbols().next().is_none() {
+        return Vec::new();
+    }
+
+    // Mach-O prefixes C symbol names with an underscore. PE executables usually carry
+    // no symbol table, and only export what the host chose to, so we can't check those.
+    let prefix = match target.binary_format {
+        target_lexicon::BinaryFormat::Elf => "",
+        target_lexicon::BinaryFormat::Macho => "_",
+        _ => return Vec::new(),
+    };
+
+    let defined: Vec<&str> = object
+        .symbols()
+        .chain(object.dynamic_symbols())
+        .filter(|sym| sym.is_definition())
+        .filter_map(|sym| sym.name().ok())
+        .filter_map(|name| name.strip_prefix(prefix))
+        .collect();
+
+    REQUIRED_HOST_HOOKS
+        .iter()
+        .filter(|hook| !defined.contains(&hook.name))
+        .copied()
+        .collect()
+}
+
+fn validate_host_hooks(target: &Triple, host_exe_path: &Path) {
+    let host_bytes = open_mmap(host_exe_path);
+    let missing = missing_host_hooks(target, &host_bytes);
+
+    if !missing.is_empty() {
+        let signatures: Vec<String> = missing
+            .iter()
+            .map(|hook| format!("    {}", hook.signature))
+            .collect();
+
+        user_error!(
+            "The host {} does not define these functions, which every Roc host must provide:\n\n{}\n",
+            host_exe_path.display(),
+            signatures.join("\n")
+        );
+    }
+}
+
 pub fn preprocessed_host_filename(target: &Triple) -> Option<String> {
     roc_target::get_target_triple_str(target).map(|x| format!("{}.{}", x, PRECOMPILED_HOST_EXT))
 }
 
-fn metadata_file_name(target: &Triple) -> String {
+/// The file the surgical linker writes next to the preprocessed host, which describes it
+pub fn metadata_file_name(target: &Triple) -> String {
     let target_triple_str = get_target_triple_str(target);
 
     format!("metadata_{}.rm", target_triple_str.unwrap_or("unknown"))
@@ -105,6 +193,12 @@ pub fn generate_stub_lib(
             palette: DEFAULT_PALETTE,
             threading: Threading::AllAvailable,
             exec_mode: ExecutionMode::Executable,
+            max_specializations: None,
+            coverage: false,
+            fold_constants: false,
+            hoist_loop_invariants: false,
+            strip_expects: false,
+            cancelled: None,
         },
     )
     .unwrap_or_else(|problem| todo!("{:?}", problem));
@@ -396,6 +490,8 @@ pub fn preprocess_host(
         platform_main_roc.with_file_name("dynhost")
     };
 
+    validate_host_hooks(target, &host_exe_path);
+
     preprocess(
         target,
         &host_exe_path,
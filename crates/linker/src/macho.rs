@@ -1,7 +1,7 @@
 use bincode::{deserialize_from, serialize_into};
 use iced_x86::{Decoder, DecoderOptions, Instruction, OpCodeOperandKind, OpKind};
 use memmap2::MmapMut;
-use object::macho;
+use object::{endian, macho};
 use object::{
     CompressedFileRange, CompressionFormat, LittleEndian as LE, Object, ObjectSection,
     ObjectSymbol, RelocationKind, RelocationTarget, Section, SectionIndex, SectionKind, Symbol,
@@ -726,15 +726,12 @@ fn gen_macho_le(
 
     let out_header = load_struct_inplace_mut::<macho::MachHeader64<LE>>(&mut out_mmap, 0);
 
-    // TODO: this needs to change to adding the 2 new commands when we are ready.
-    // -1 because we're deleting 1 load command and then NOT adding 2 new ones.
-    {
-        let added_bytes = -(total_cmd_size as isize); // TODO: Change when add the new sections.
-        out_header.ncmds.set(LE, num_load_cmds - 1);
-        out_header
-            .sizeofcmds
-            .set(LE, (size_of_cmds as isize + added_bytes) as u32);
-    }
+    // -1 because we're deleting 1 load command.
+    // The 2 new segment commands for the app are added during surgery, once their sizes are known.
+    out_header.ncmds.set(LE, num_load_cmds - 1);
+    out_header
+        .sizeofcmds
+        .set(LE, (size_of_cmds - total_cmd_size) as u32);
 
     // Go through every command and shift it by added_bytes if it's absolute, unless it's inside the command header
     let mut offset = mem::size_of_val(exec_header);
@@ -778,6 +775,9 @@ fn gen_macho_le(
                     cmd.vmsize.set(LE, cmd.vmsize.get(LE) + md.added_byte_count);
                 }
 
+                // The app's segments go after every segment of the host.
+                md.last_vaddr = md.last_vaddr.max(cmd.vmaddr.get(LE) + cmd.vmsize.get(LE));
+
                 // let num_sections = cmd.nsects.get(LE);
                 // let sections = load_structs_inplace_mut::<macho::Section64<LE >>(
                 //     &mut out_mmap,
@@ -1113,7 +1113,7 @@ fn gen_macho_le(
             }
         }
 
-        offset += dbg!(cmd_size);
+        offset += cmd_size;
     }
 
     // cmd_loc should be where the last offset ended
@@ -1228,7 +1228,7 @@ fn surgery_macho_help(
     app_obj: object::File,
 ) {
     let mut offset = align_by_constraint(md.exec_len as usize, MIN_SECTION_ALIGNMENT);
-    // let new_rodata_section_offset = offset;
+    let new_rodata_section_offset = offset;
 
     // Align physical and virtual address of new segment.
     let mut virt_offset = align_to_offset_by_constraint(
@@ -1326,13 +1326,13 @@ fn surgery_macho_help(
         println!("Found App Function Symbols: {:+x?}", app_func_vaddr_map);
     }
 
-    // let (new_text_section_offset, new_text_section_vaddr) = text_sections
-    //     .iter()
-    //     .map(|sec| section_offset_map.get(&sec.index()).unwrap())
-    //     .min()
-    //     .unwrap();
-    // let (new_text_section_offset, new_text_section_vaddr) =
-    //     (*new_text_section_offset, *new_text_section_vaddr);
+    let (new_text_section_offset, new_text_section_vaddr) = text_sections
+        .iter()
+        .map(|sec| section_offset_map.get(&sec.index()).unwrap())
+        .min()
+        .unwrap();
+    let (new_text_section_offset, new_text_section_vaddr) =
+        (*new_text_section_offset, *new_text_section_vaddr);
 
     // Move data and deal with relocations.
     for sec in rodata_sections
@@ -1453,95 +1453,86 @@ fn surgery_macho_help(
 
     // TODO: look into merging symbol tables, debug info, and eh frames to enable better debugger experience.
 
-    // let mut cmd_offset = md.macho_cmd_loc as usize;
-
-    // // Load this section (we made room for it earlier) and then mutate all its data to make it the desired command
-    // {
-    //     let cmd =
-    //         load_struct_inplace_mut::<macho::SegmentCommand64<LE >>(exec_mmap, cmd_offset);
-    //     let size_of_section = mem::size_of::<macho::Section64<LE >>() as u32;
-    //     let size_of_cmd = mem::size_of_val(cmd);
-
-    //     cmd_offset += size_of_cmd;
-
-    //     cmd.cmd.set(LE , macho::LC_SEGMENT_64);
-    //     cmd.cmdsize
-    //         .set(LE , size_of_section + size_of_cmd as u32);
-    //     cmd.segname = *b"__DATA_CONST\0\0\0\0";
-    //     cmd.vmaddr
-    //         .set(LE , new_rodata_section_vaddr as u64);
-    //     cmd.vmsize.set(
-    //         LE ,
-    //         (new_text_section_vaddr - new_rodata_section_vaddr) as u64,
-    //     );
-    //     cmd.fileoff
-    //         .set(LE , new_rodata_section_offset as u64);
-    //     cmd.filesize.set(
-    //         LE ,
-    //         (new_text_section_offset - new_rodata_section_offset) as u64,
-    //     );
-    //     cmd.nsects.set(LE , 1);
-    //     cmd.maxprot.set(LE , 0x00000003);
-    //     cmd.initprot.set(LE , 0x00000003);
-
-    //     // TODO set protection
-    // }
-
-    // {
-    //     let cmd = load_struct_inplace_mut::<macho::Section64<LE >>(exec_mmap, cmd_offset);
-    //     let size_of_cmd = mem::size_of_val(cmd);
-
-    //     cmd_offset += size_of_cmd;
-
-    //     cmd.sectname = *b"__const\0\0\0\0\0\0\0\0\0";
-    //     cmd.segname = *b"__DATA_CONST\0\0\0\0";
-    //     cmd.addr.set(LE , new_rodata_section_vaddr as u64);
-    //     cmd.size.set(
-    //         LE ,
-    //         (new_text_section_offset - new_rodata_section_offset) as u64,
-    //     );
-    //     cmd.offset.set(LE , 0); // TODO is this offset since the start of the file, or segment offset?
-    //     cmd.align.set(LE , 12); // TODO should this be 4096?
-    //     cmd.reloff.set(LE , 264); // TODO this should NOT be hardcoded! Should get it from somewhere.
-    // }
-
-    // {
-    //     let cmd =
-    //         load_struct_inplace_mut::<macho::SegmentCommand64<LE >>(exec_mmap, cmd_offset);
-    //     let size_of_section = mem::size_of::<macho::Section64<LE >>() as u32;
-    //     let size_of_cmd = mem::size_of_val(cmd);
-
-    //     cmd_offset += size_of_cmd;
-
-    //     cmd.cmd.set(LE , macho::LC_SEGMENT_64);
-    //     cmd.cmdsize
-    //         .set(LE , size_of_section + size_of_cmd as u32);
-    //     cmd.segname = *b"__TEXT\0\0\0\0\0\0\0\0\0\0";
-    //     cmd.vmaddr.set(LE , new_text_section_vaddr as u64);
-    //     cmd.vmsize
-    //         .set(LE , (offset - new_text_section_offset) as u64);
-    //     cmd.fileoff
-    //         .set(LE , new_text_section_offset as u64);
-    //     cmd.filesize
-    //         .set(LE , (offset - new_text_section_offset) as u64);
-    //     cmd.nsects.set(LE , 1);
-    //     cmd.maxprot.set(LE , 0x00000005); // this is what a zig-generated host had
-    //     cmd.initprot.set(LE , 0x00000005); // this is what a zig-generated host had
-    // }
-
-    // {
-    //     let cmd = load_struct_inplace_mut::<macho::Section64<LE >>(exec_mmap, cmd_offset);
-
-    //     cmd.segname = *b"__TEXT\0\0\0\0\0\0\0\0\0\0";
-    //     cmd.sectname = *b"__text\0\0\0\0\0\0\0\0\0\0";
-    //     cmd.addr.set(LE , new_text_section_vaddr as u64);
-    //     cmd.size
-    //         .set(LE , (offset - new_text_section_offset) as u64);
-    //     cmd.offset.set(LE , 0); // TODO is this offset since the start of the file, or segment offset?
-    //     cmd.align.set(LE , 12); // TODO this is 4096 (2^12) - which load_align_constraint does, above - but should it?
-    //     cmd.flags.set(LE , 0x80000400); // TODO this is what a zig-generated host had
-    //     cmd.reloff.set(LE , 264); // TODO this should NOT be hardcoded! Should get it from somewhere.
-    // }
+    // Preprocessing left room for these right after the host's load commands.
+    let new_segments = [
+        (
+            *b"__ROC_CONST\0\0\0\0\0",
+            *b"__const\0\0\0\0\0\0\0\0\0",
+            new_rodata_section_vaddr,
+            new_text_section_vaddr - new_rodata_section_vaddr,
+            new_rodata_section_offset,
+            new_text_section_offset - new_rodata_section_offset,
+            macho::VM_PROT_READ,
+            macho::S_REGULAR,
+        ),
+        (
+            *b"__ROC_TEXT\0\0\0\0\0\0",
+            *b"__text\0\0\0\0\0\0\0\0\0\0",
+            new_text_section_vaddr,
+            offset - new_text_section_offset,
+            new_text_section_offset,
+            offset - new_text_section_offset,
+            macho::VM_PROT_READ | macho::VM_PROT_EXECUTE,
+            macho::S_ATTR_PURE_INSTRUCTIONS | macho::S_ATTR_SOME_INSTRUCTIONS,
+        ),
+    ];
+
+    let segment_cmd_size = mem::size_of::<macho::SegmentCommand64<LE>>();
+    let section_size = mem::size_of::<macho::Section64<LE>>();
+    let mut cmd_offset = md.macho_cmd_loc as usize;
+
+    for (segname, sectname, vmaddr, vmsize, fileoff, filesize, prot, flags) in new_segments {
+        if verbose {
+            println!(
+                "Adding segment {}: {:+x} (virt: {:+x})",
+                String::from_utf8_lossy(&segname).trim_end_matches('\0'),
+                fileoff,
+                vmaddr
+            );
+        }
+
+        let cmd = load_struct_inplace_mut::<macho::SegmentCommand64<LE>>(exec_mmap, cmd_offset);
+        *cmd = macho::SegmentCommand64 {
+            cmd: endian::U32::new(LE, macho::LC_SEGMENT_64),
+            cmdsize: endian::U32::new(LE, (segment_cmd_size + section_size) as u32),
+            segname,
+            vmaddr: endian::U64::new(LE, vmaddr as u64),
+            vmsize: endian::U64::new(LE, vmsize as u64),
+            fileoff: endian::U64::new(LE, fileoff as u64),
+            filesize: endian::U64::new(LE, filesize as u64),
+            maxprot: endian::U32::new(LE, prot),
+            initprot: endian::U32::new(LE, prot),
+            nsects: endian::U32::new(LE, 1),
+            flags: endian::U32::new(LE, 0),
+        };
+        cmd_offset += segment_cmd_size;
+
+        let section = load_struct_inplace_mut::<macho::Section64<LE>>(exec_mmap, cmd_offset);
+        *section = macho::Section64 {
+            sectname,
+            segname,
+            addr: endian::U64::new(LE, vmaddr as u64),
+            size: endian::U64::new(LE, filesize as u64),
+            offset: endian::U32::new(LE, fileoff as u32),
+            align: endian::U32::new(LE, MIN_SECTION_ALIGNMENT.trailing_zeros()),
+            reloff: endian::U32::new(LE, 0),
+            nreloc: endian::U32::new(LE, 0),
+            flags: endian::U32::new(LE, flags),
+            reserved1: endian::U32::new(LE, 0),
+            reserved2: endian::U32::new(LE, 0),
+            reserved3: endian::U32::new(LE, 0),
+        };
+        cmd_offset += section_size;
+    }
+
+    let header = load_struct_inplace_mut::<macho::MachHeader64<LE>>(exec_mmap, 0);
+    header
+        .ncmds
+        .set(LE, header.ncmds.get(LE) + new_segments.len() as u32);
+    header.sizeofcmds.set(
+        LE,
+        header.sizeofcmds.get(LE) + (cmd_offset - md.macho_cmd_loc as usize) as u32,
+    );
 
     // Update calls from platform and dynamic symbols.
     // let dynsym_offset = md.dynamic_symbol_table_section_offset + md.added_byte_count;
@@ -0,0 +1,140 @@
+//! Attributes the code size of a linked executable to the functions it contains,
+//! so it's easy to see what dominates the size of a Roc program.
+use object::{Object, ObjectSection, ObjectSymbol, SectionKind, SymbolKind};
+
+/// Where a function in the final executable came from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CodeOrigin {
+    /// A specialization of a proc the user (or a package) wrote
+    RocProc,
+    /// A refcounting, equality or other helper proc generated by the compiler
+    GeneratedHelper,
+    /// A function from the Zig builtins
+    Builtin,
+    /// Everything else, i.e. the platform's host code and the libraries it links
+    Host,
+}
+
+impl CodeOrigin {
+    pub fn description(self) -> &'static str {
+        match self {
+            CodeOrigin::RocProc => "Roc procs",
+            CodeOrigin::GeneratedHelper => "generated helper procs",
+            CodeOrigin::Builtin => "builtins",
+            CodeOrigin::Host => "host code",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeReportEntry {
+    pub name: String,
+    pub size: u64,
+    pub origin: CodeOrigin,
+}
+
+/// Every function in the executable's code sections, largest first.
+/// Returns `None` if the bytes aren't an object file we can parse.
+pub fn size_report(exe_bytes: &[u8]) -> Option<Vec<SizeReportEntry>> {
+    let object = object::File::parse(exe_bytes).ok()?;
+
+    // Mach-O prefixes C symbol names with an underscore.
+    let prefix = match object.format() {
+        object::BinaryFormat::MachO => "_",
+        _ => "",
+    };
+
+    let mut entries = Vec::new();
+
+    for section in object.sections() {
+        if section.kind() != SectionKind::Text {
+            continue;
+        }
+
+        let mut symbols: Vec<_> = object
+            .symbols()
+            .filter(|sym| sym.section_index() == Some(section.index()))
+            .filter(|sym| sym.is_definition() && sym.kind() == SymbolKind::Text)
+            .filter_map(|sym| Some((sym.address(), sym.size(), sym.name().ok()?)))
+            .collect();
+
+        symbols.sort_by_key(|(address, _, _)| *address);
+
+        let section_end = section.address() + section.size();
+
+        for (i, (address, size, name)) in symbols.iter().enumerate() {
+            // Mach-O symbols have no size, so estimate it by where the next symbol starts.
+            let size = if *size > 0 {
+                *size
+            } else {
+                let next = symbols[i + 1..]
+                    .iter()
+                    .map(|(next, _, _)| *next)
+                    .find(|next| next > address)
+                    .unwrap_or(section_end);
+
+                next.saturating_sub(*address)
+            };
+
+            let name = name.strip_prefix(prefix).unwrap_or(name);
+
+            entries.push(SizeReportEntry {
+                name: name.to_string(),
+                size,
+                origin: code_origin(name),
+            });
+        }
+    }
+
+    entries.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+
+    Some(entries)
+}
+
+/// Classify a function by its symbol name. Roc procs are named `Module_ident_layoutId`
+/// (see `LayoutId::to_symbol_string`), generated helpers have an ident starting with `#help`,
+/// and the exposed wrappers are named `roc__ident_layoutId_exposed`.
+fn code_origin(name: &str) -> CodeOrigin {
+    if name.starts_with("roc_builtins.") {
+        CodeOrigin::Builtin
+    } else if name.contains("_#help") {
+        CodeOrigin::GeneratedHelper
+    } else if name.starts_with("roc__") || is_roc_proc_name(name) {
+        CodeOrigin::RocProc
+    } else {
+        CodeOrigin::Host
+    }
+}
+
+fn is_roc_proc_name(name: &str) -> bool {
+    let starts_with_module_name =
+        name.starts_with(|c: char| c.is_ascii_uppercase()) || name.starts_with("#UserApp_");
+
+    let ends_with_layout_id = match name.rsplit_once('_') {
+        Some((_, id)) => !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit()),
+        None => false,
+    };
+
+    starts_with_module_name && ends_with_layout_id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_symbol_names() {
+        assert_eq!(code_origin("roc_builtins.list.concat"), CodeOrigin::Builtin);
+        assert_eq!(
+            code_origin("List_#help3_Dec_List(Str)_1"),
+            CodeOrigin::GeneratedHelper
+        );
+        assert_eq!(
+            code_origin("roc__mainForHost_1_exposed"),
+            CodeOrigin::RocProc
+        );
+        assert_eq!(code_origin("Json_encodeList_12"), CodeOrigin::RocProc);
+        assert_eq!(code_origin("main"), CodeOrigin::Host);
+        assert_eq!(code_origin("Os_write_all"), CodeOrigin::Host);
+    }
+}
@@ -156,6 +156,29 @@ pub fn download_and_hash(
     decompress_into(dest_dir, encoding, resp.take(max_download_bytes))
 }
 
+/// Downloads a small, uncompressed text file (such as a package index) into memory.
+pub fn download_text(url: &str, max_download_bytes: u64) -> Result<String, Problem> {
+    let resp = reqwest::blocking::Client::new()
+        .get(url)
+        .send()
+        .and_then(|resp| resp.error_for_status())
+        .map_err(Problem::HttpErr)?;
+
+    if let Some(content_len) = resp.content_length() {
+        if content_len > max_download_bytes {
+            return Err(Problem::DownloadTooBig(content_len));
+        }
+    }
+
+    let mut text = String::new();
+
+    resp.take(max_download_bytes)
+        .read_to_string(&mut text)
+        .map_err(Problem::IoErr)?;
+
+    Ok(text)
+}
+
 /// The content encodings we support
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum Encoding {
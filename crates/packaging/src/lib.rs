@@ -1,4 +1,7 @@
 pub mod cache;
 #[cfg(not(target_family = "wasm"))]
 pub mod https;
+pub mod manifest;
+pub mod resolve;
+pub mod semver;
 pub mod tarball;
@@ -0,0 +1,487 @@
+//! A project's package dependencies, declared in the `[dependencies]` section of its `roc.toml`,
+//! and the `roc.lock` file recording which version of each one was chosen.
+//!
+//! ```toml
+//! [registry]
+//! index = "https://example.com/roc-index.txt" # or a path relative to roc.toml
+//!
+//! [dependencies]
+//! basic-cli = "^0.5.0"
+//! json = ">=0.2.0, <0.4.0"
+//! ```
+//!
+//! Once the lockfile exists, a module can name a dependency in its header instead of a URL,
+//! e.g. `packages { pf: "basic-cli" }`.
+use std::{
+    collections::BTreeMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    resolve::{self, Index, ResolveProblem},
+    semver::{Version, VersionReq},
+};
+
+pub const MANIFEST_FILENAME: &str = "roc.toml";
+pub const LOCKFILE_FILENAME: &str = "roc.lock";
+
+#[cfg(not(target_family = "wasm"))]
+const MAX_INDEX_BYTES: u64 = 64 * 1_000_000; // MB
+
+const LOCKFILE_HEADER: &str =
+    "# This file is generated from the [dependencies] in roc.toml. Please don't edit it by hand!\n";
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Manifest {
+    /// Where to find the package index: a https:// URL, or a path relative to the manifest
+    pub index: Option<String>,
+    pub dependencies: Vec<(String, VersionReq)>,
+}
+
+impl Manifest {
+    /// Only the `[registry]` and `[dependencies]` tables are read; other sections belong to someone else.
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut manifest = Manifest::default();
+
+        for setting in settings(contents, "registry")? {
+            match setting.key {
+                "index" => manifest.index = Some(setting.unquoted_value().to_string()),
+                key => return Err(setting.problem(format!("unknown registry setting `{}`", key))),
+            }
+        }
+
+        for setting in settings(contents, "dependencies")? {
+            if !is_package_name(setting.key) {
+                return Err(
+                    setting.problem(format!("`{}` is not a valid package name", setting.key))
+                );
+            }
+
+            let req = setting
+                .unquoted_value()
+                .parse()
+                .map_err(|problem| setting.problem(problem))?;
+
+            manifest.dependencies.push((setting.key.to_string(), req));
+        }
+
+        Ok(manifest)
+    }
+}
+
+/// A `key = value` line from one table of a `roc.toml`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Setting<'a> {
+    pub key: &'a str,
+    /// The value as written, so strings still have their quotes
+    pub value: &'a str,
+    /// 1-based, for error messages
+    pub line: usize,
+}
+
+impl<'a> Setting<'a> {
+    pub fn unquoted_value(&self) -> &'a str {
+        self.value.trim_matches('"')
+    }
+
+    /// `problem`, prefixed with the line this setting is on.
+    pub fn problem(&self, problem: String) -> String {
+        format!("line {}: {}", self.line, problem)
+    }
+}
+
+/// The settings in the `[table]` section of a `roc.toml`.
+///
+/// This understands just enough TOML for roc.toml: table headers, `key = value` lines and
+/// `#` comments. Lines in other tables are skipped without being checked, since they
+/// belong to whoever reads those tables.
+pub fn settings<'a>(contents: &'a str, table: &str) -> Result<Vec<Setting<'a>>, String> {
+    let mut settings = Vec::new();
+    let mut in_table = false;
+
+    for (index, line) in contents.lines().enumerate() {
+        let line = strip_comment(line).trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('[') {
+            in_table = header.strip_suffix(']').map(str::trim) == Some(table);
+            continue;
+        }
+
+        if !in_table {
+            continue;
+        }
+
+        match line.split_once('=') {
+            Some((key, value)) => settings.push(Setting {
+                key: key.trim(),
+                value: value.trim(),
+                line: index + 1,
+            }),
+            None => {
+                return Err(format!(
+                    "line {}: expected `key = value`, found `{}`",
+                    index + 1,
+                    line
+                ))
+            }
+        }
+    }
+
+    Ok(settings)
+}
+
+/// `line` without its `#` comment, if it has one. A `#` inside a string doesn't start a comment.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut chars = line.char_indices();
+
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' if in_string => {
+                // skip the escaped character, which might be a quote
+                chars.next();
+            }
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..index],
+            _ => {}
+        }
+    }
+
+    line
+}
+
+/// Package names are made of ASCII letters, digits, `-` and `_`, which keeps them
+/// from being mistaken for the relative paths a module header can also contain.
+pub fn is_package_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockedPackage {
+    pub version: Version,
+    pub url: String,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Lockfile {
+    pub packages: BTreeMap<String, LockedPackage>,
+}
+
+impl Lockfile {
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut packages = BTreeMap::new();
+
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let line_problem = |problem: String| format!("line {}: {}", index + 1, problem);
+
+            match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+                [name, version, url] => {
+                    let version = version.parse().map_err(line_problem)?;
+                    let url = url.to_string();
+
+                    packages.insert(name.to_string(), LockedPackage { version, url });
+                }
+                _ => {
+                    return Err(line_problem(format!(
+                        "expected `name version url`, found `{}`",
+                        line
+                    )))
+                }
+            }
+        }
+
+        Ok(Lockfile { packages })
+    }
+
+    /// Reads the lockfile in `dir`, if there is one.
+    pub fn read(dir: &Path) -> Result<Option<Self>, ManifestProblem> {
+        let path = dir.join(LOCKFILE_FILENAME);
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => match Lockfile::parse(&contents) {
+                Ok(lockfile) => Ok(Some(lockfile)),
+                Err(problem) => Err(ManifestProblem::Syntax { path, problem }),
+            },
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(ManifestProblem::Io { path, error }),
+        }
+    }
+
+    fn satisfies(&self, dependencies: &[(String, VersionReq)]) -> bool {
+        dependencies.iter().all(|(name, req)| {
+            self.packages
+                .get(name)
+                .map_or(false, |locked| req.matches(&locked.version))
+        })
+    }
+}
+
+impl fmt::Display for Lockfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(LOCKFILE_HEADER)?;
+
+        for (name, LockedPackage { version, url }) in self.packages.iter() {
+            writeln!(f, "{} {} {}", name, version, url)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// If `src` (from a module header's `packages` section) names a package in the lockfile
+/// in `dir`, returns the URL of the version that was locked for it.
+pub fn locked_url(dir: &Path, src: &str) -> Result<Option<String>, ManifestProblem> {
+    if !is_package_name(src) {
+        return Ok(None);
+    }
+
+    Ok(Lockfile::read(dir)?
+        .and_then(|mut lockfile| lockfile.packages.remove(src))
+        .map(|locked| locked.url))
+}
+
+#[derive(Debug)]
+pub enum ManifestProblem {
+    Io {
+        path: PathBuf,
+        error: io::Error,
+    },
+    Syntax {
+        path: PathBuf,
+        problem: String,
+    },
+    MissingIndex,
+    #[cfg(not(target_family = "wasm"))]
+    Download(crate::https::Problem),
+    Resolve(ResolveProblem),
+}
+
+impl fmt::Display for ManifestProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManifestProblem::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            ManifestProblem::Syntax { path, problem } => {
+                write!(f, "{}: {}", path.display(), problem)
+            }
+            ManifestProblem::MissingIndex => write!(
+                f,
+                "{} has [dependencies] but no package index to find them in; add `index = \"...\"` under [registry]",
+                MANIFEST_FILENAME
+            ),
+            #[cfg(not(target_family = "wasm"))]
+            ManifestProblem::Download(problem) => {
+                write!(f, "could not download the package index: {:?}", problem)
+            }
+            ManifestProblem::Resolve(problem) => write!(f, "{}", problem),
+        }
+    }
+}
+
+/// Makes sure `roc.lock` in `project_dir` has a version of every dependency in `roc.toml`.
+///
+/// The package index is only consulted when the lockfile is missing or no longer satisfies the
+/// manifest, and even then the versions that were locked before are kept wherever they still fit.
+/// Returns None if the project doesn't declare any dependencies.
+pub fn update_lockfile(project_dir: &Path) -> Result<Option<Lockfile>, ManifestProblem> {
+    let manifest_path = project_dir.join(MANIFEST_FILENAME);
+    let manifest = match fs::read_to_string(&manifest_path) {
+        Ok(contents) => Manifest::parse(&contents).map_err(|problem| ManifestProblem::Syntax {
+            path: manifest_path.clone(),
+            problem,
+        })?,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => {
+            return Err(ManifestProblem::Io {
+                path: manifest_path,
+                error,
+            })
+        }
+    };
+
+    if manifest.dependencies.is_empty() {
+        return Ok(None);
+    }
+
+    let existing = Lockfile::read(project_dir)?.unwrap_or_default();
+
+    if existing.satisfies(&manifest.dependencies) {
+        return Ok(Some(existing));
+    }
+
+    let index_src = manifest
+        .index
+        .as_ref()
+        .ok_or(ManifestProblem::MissingIndex)?;
+    let index = Index::parse(&read_index(project_dir, index_src)?).map_err(|problem| {
+        ManifestProblem::Syntax {
+            path: PathBuf::from(index_src),
+            problem,
+        }
+    })?;
+
+    let locked_versions = existing
+        .packages
+        .iter()
+        .map(|(name, locked)| (name.clone(), locked.version))
+        .collect();
+
+    let resolved = resolve::resolve(&index, &manifest.dependencies, &locked_versions)
+        .map_err(ManifestProblem::Resolve)?;
+
+    let lockfile = Lockfile {
+        packages: resolved
+            .into_iter()
+            .map(|(name, entry)| {
+                let locked = LockedPackage {
+                    version: entry.version,
+                    url: entry.url.clone(),
+                };
+
+                (name, locked)
+            })
+            .collect(),
+    };
+
+    let lockfile_path = project_dir.join(LOCKFILE_FILENAME);
+
+    fs::write(&lockfile_path, lockfile.to_string()).map_err(|error| ManifestProblem::Io {
+        path: lockfile_path,
+        error,
+    })?;
+
+    Ok(Some(lockfile))
+}
+
+fn read_index(project_dir: &Path, index_src: &str) -> Result<String, ManifestProblem> {
+    if index_src.starts_with("https://") {
+        #[cfg(not(target_family = "wasm"))]
+        {
+            crate::https::download_text(index_src, MAX_INDEX_BYTES)
+                .map_err(ManifestProblem::Download)
+        }
+
+        #[cfg(target_family = "wasm")]
+        {
+            panic!("Downloading a package index is currently unsupported in wasm.");
+        }
+    } else {
+        let path = project_dir.join(index_src);
+
+        fs::read_to_string(&path).map_err(|error| ManifestProblem::Io { path, error })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest_sections() {
+        let manifest = Manifest::parse(
+            "[warnings]\nunused-def = \"off\"\n\n[registry]\nindex = \"index.txt\"\n\n[dependencies]\nbasic-cli = \"^0.5.0\" # the platform\njson = \">=0.2.0, <0.4.0\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(manifest.index.as_deref(), Some("index.txt"));
+        assert_eq!(
+            manifest.dependencies,
+            [
+                ("basic-cli".to_string(), "^0.5.0".parse().unwrap()),
+                ("json".to_string(), ">=0.2.0, <0.4.0".parse().unwrap()),
+            ]
+        );
+        assert!(Manifest::parse("[dependencies]\n../json = \"1.0.0\"\n").is_err());
+    }
+
+    #[test]
+    fn hash_inside_a_string_is_not_a_comment() {
+        let manifest = Manifest::parse(
+            "[registry] # where packages come from\nindex = \"https://example.com/index.txt#main\" # pinned\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest.index.as_deref(),
+            Some("https://example.com/index.txt#main")
+        );
+    }
+
+    #[test]
+    fn settings_of_one_table() {
+        let contents = "[package]\nnot a setting\n\n[warnings]\nunused-def = \"off\" # noisy\nname = \"a \\\" # b\"\n";
+
+        assert_eq!(
+            settings(contents, "warnings"),
+            Ok(vec![
+                Setting {
+                    key: "unused-def",
+                    value: "\"off\"",
+                    line: 5,
+                },
+                Setting {
+                    key: "name",
+                    value: "\"a \\\" # b\"",
+                    line: 6,
+                },
+            ])
+        );
+        assert!(settings(contents, "package").is_err());
+        assert_eq!(settings(contents, "dependencies"), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn lockfile_roundtrip() {
+        let lockfile = Lockfile::parse(
+            "# comment\nbasic-cli 0.5.0 https://example.com/cli/b.tar.br\njson 0.2.4 https://example.com/json/e.tar.br\n",
+        )
+        .unwrap();
+
+        assert_eq!(Lockfile::parse(&lockfile.to_string()), Ok(lockfile));
+    }
+
+    #[test]
+    fn lockfile_written_from_index_and_kept() {
+        let dir = tempfile::tempdir().unwrap();
+
+        fs::write(
+            dir.path().join(MANIFEST_FILENAME),
+            "[registry]\nindex = \"index.txt\"\n\n[dependencies]\nbasic-cli = \"^0.4.0\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("index.txt"),
+            "basic-cli 0.4.0 https://example.com/cli/a.tar.br json@^0.1.0\njson 0.1.3 https://example.com/json/c.tar.br\n",
+        )
+        .unwrap();
+
+        let lockfile = update_lockfile(dir.path()).unwrap().unwrap();
+        assert_eq!(lockfile.packages.len(), 2);
+        assert_eq!(
+            locked_url(dir.path(), "json").unwrap().as_deref(),
+            Some("https://example.com/json/c.tar.br")
+        );
+        assert_eq!(locked_url(dir.path(), "../json/main.roc").unwrap(), None);
+
+        // A newer release doesn't change anything while the lockfile still satisfies roc.toml
+        fs::write(
+            dir.path().join("index.txt"),
+            "basic-cli 0.4.1 https://example.com/cli/f.tar.br\n",
+        )
+        .unwrap();
+        assert_eq!(update_lockfile(dir.path()).unwrap(), Some(lockfile));
+    }
+}
@@ -0,0 +1,308 @@
+//! Picks one version of every package a project needs, given the versions a package index offers.
+//!
+//! An index is a plain text file with one released version per line:
+//!
+//! ```text
+//! # name      version  url                                           dependencies
+//! basic-cli   0.5.0    https://example.com/basic-cli/<hash>.tar.br   json@^0.2.0
+//! json        0.2.1    https://example.com/json/<hash>.tar.br
+//! ```
+//!
+//! Each dependency is written `name@requirement`, and a requirement may not contain spaces.
+use std::{collections::BTreeMap, fmt};
+
+use crate::semver::{Version, VersionReq};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexEntry {
+    pub name: String,
+    pub version: Version,
+    pub url: String,
+    pub dependencies: Vec<(String, VersionReq)>,
+}
+
+#[derive(Debug, Default)]
+pub struct Index {
+    /// For each package, its versions from newest to oldest
+    packages: BTreeMap<String, Vec<IndexEntry>>,
+}
+
+impl Index {
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut packages: BTreeMap<String, Vec<IndexEntry>> = BTreeMap::new();
+
+        for (index, line) in contents.lines().enumerate() {
+            let line_problem = |problem: String| format!("line {}: {}", index + 1, problem);
+            let line = match line.split_once('#') {
+                Some((before, _)) => before.trim(),
+                None => line.trim(),
+            };
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut words = line.split_whitespace();
+            let (name, version, url) = match (words.next(), words.next(), words.next()) {
+                (Some(name), Some(version), Some(url)) => (name, version, url),
+                _ => {
+                    return Err(line_problem(format!(
+                        "expected `name version url`, found `{}`",
+                        line
+                    )))
+                }
+            };
+
+            let mut dependencies = Vec::new();
+
+            for word in words {
+                let (dep_name, req) = word.split_once('@').ok_or_else(|| {
+                    line_problem(format!("expected `name@requirement`, found `{}`", word))
+                })?;
+
+                dependencies.push((dep_name.to_string(), req.parse().map_err(line_problem)?));
+            }
+
+            packages
+                .entry(name.to_string())
+                .or_default()
+                .push(IndexEntry {
+                    name: name.to_string(),
+                    version: version.parse().map_err(line_problem)?,
+                    url: url.to_string(),
+                    dependencies,
+                });
+        }
+
+        for versions in packages.values_mut() {
+            versions.sort_by(|a, b| b.version.cmp(&a.version));
+            versions.dedup_by(|a, b| a.version == b.version);
+        }
+
+        Ok(Index { packages })
+    }
+
+    fn versions(&self, name: &str) -> Option<&[IndexEntry]> {
+        self.packages.get(name).map(|versions| versions.as_slice())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ResolveProblem {
+    UnknownPackage {
+        name: String,
+        required_by: Option<String>,
+    },
+    /// Nothing in the index satisfies every one of these requirements at once
+    NoMatchingVersion {
+        name: String,
+        requirements: Vec<(Option<String>, VersionReq)>,
+    },
+}
+
+impl fmt::Display for ResolveProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let describe = |required_by: &Option<String>| match required_by {
+            Some(package) => package.clone(),
+            None => "this project".to_string(),
+        };
+
+        match self {
+            ResolveProblem::UnknownPackage { name, required_by } => write!(
+                f,
+                "{} depends on `{}`, but the package index has no such package",
+                describe(required_by),
+                name
+            ),
+            ResolveProblem::NoMatchingVersion { name, requirements } => {
+                write!(f, "no version of `{}` satisfies all of:", name)?;
+
+                for (required_by, req) in requirements {
+                    write!(f, "\n    {} (required by {})", req, describe(required_by))?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Chooses a version of each package reachable from `root`, preferring the versions in `locked`
+/// and otherwise the newest ones that fit. Backtracks when a choice turns out to conflict with a
+/// requirement discovered later.
+pub fn resolve<'a>(
+    index: &'a Index,
+    root: &'a [(String, VersionReq)],
+    locked: &BTreeMap<String, Version>,
+) -> Result<BTreeMap<String, &'a IndexEntry>, ResolveProblem> {
+    let mut selected = BTreeMap::new();
+
+    resolve_help(index, root, locked, &mut selected)?;
+
+    Ok(selected
+        .into_iter()
+        .map(|(name, entry)| (name.to_string(), entry))
+        .collect())
+}
+
+fn resolve_help<'a>(
+    index: &'a Index,
+    root: &'a [(String, VersionReq)],
+    locked: &BTreeMap<String, Version>,
+    selected: &mut BTreeMap<&'a str, &'a IndexEntry>,
+) -> Result<(), ResolveProblem> {
+    // Every requirement currently in play, along with who made it
+    let requirements: Vec<(Option<&'a str>, &'a str, &'a VersionReq)> = root
+        .iter()
+        .map(|(name, req)| (None, name.as_str(), req))
+        .chain(selected.values().copied().flat_map(|entry| {
+            entry
+                .dependencies
+                .iter()
+                .map(|(name, req)| (Some(entry.name.as_str()), name.as_str(), req))
+        }))
+        .collect();
+
+    let (required_by, name) = match requirements
+        .iter()
+        .find(|(_, name, _)| !selected.contains_key(name))
+    {
+        Some((required_by, name, _)) => (*required_by, *name),
+        None => return Ok(()),
+    };
+
+    let versions = index
+        .versions(name)
+        .ok_or_else(|| ResolveProblem::UnknownPackage {
+            name: name.to_string(),
+            required_by: required_by.map(str::to_string),
+        })?;
+
+    let reqs_on_name: Vec<_> = requirements
+        .iter()
+        .filter(|(_, required, _)| *required == name)
+        .collect();
+
+    let mut candidates: Vec<&IndexEntry> = versions
+        .iter()
+        .filter(|entry| {
+            reqs_on_name
+                .iter()
+                .all(|(_, _, req)| req.matches(&entry.version))
+        })
+        .collect();
+
+    // Stick with the locked version if it still fits, so that builds are repeatable.
+    if let Some(locked_version) = locked.get(name) {
+        if let Some(position) = candidates.iter().position(|e| e.version == *locked_version) {
+            let entry = candidates.remove(position);
+            candidates.insert(0, entry);
+        }
+    }
+
+    // If every candidate leads to a dead end, the problem found deepest down is the most specific.
+    let mut deepest_problem = None;
+
+    for candidate in candidates {
+        // The candidate's own dependencies must also agree with what's already been picked.
+        let compatible = candidate.dependencies.iter().all(|(dep, req)| {
+            selected
+                .get(dep.as_str())
+                .map_or(true, |entry| req.matches(&entry.version))
+        });
+
+        if !compatible {
+            continue;
+        }
+
+        selected.insert(name, candidate);
+
+        match resolve_help(index, root, locked, selected) {
+            Ok(()) => return Ok(()),
+            Err(problem) => deepest_problem = Some(problem),
+        }
+
+        selected.remove(name);
+    }
+
+    Err(
+        deepest_problem.unwrap_or_else(|| ResolveProblem::NoMatchingVersion {
+            name: name.to_string(),
+            requirements: reqs_on_name
+                .into_iter()
+                .map(|(required_by, _, req)| (required_by.map(str::to_string), (*req).clone()))
+                .collect(),
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INDEX: &str = "\
+# name      version  url
+basic-cli   0.4.0    https://example.com/cli/a.tar.br   json@^0.1.0
+basic-cli   0.5.0    https://example.com/cli/b.tar.br   json@^0.2.0
+json        0.1.3    https://example.com/json/c.tar.br
+json        0.2.0    https://example.com/json/d.tar.br
+json        0.2.4    https://example.com/json/e.tar.br
+";
+
+    fn req(name: &str, req: &str) -> (String, VersionReq) {
+        (name.to_string(), req.parse().unwrap())
+    }
+
+    fn versions(resolved: &BTreeMap<String, &IndexEntry>) -> Vec<String> {
+        resolved
+            .iter()
+            .map(|(name, entry)| format!("{} {}", name, entry.version))
+            .collect()
+    }
+
+    #[test]
+    fn picks_newest_compatible_versions() {
+        let index = Index::parse(INDEX).unwrap();
+        let root = [req("basic-cli", "*")];
+        let resolved = resolve(&index, &root, &BTreeMap::new()).unwrap();
+
+        assert_eq!(versions(&resolved), ["basic-cli 0.5.0", "json 0.2.4"]);
+    }
+
+    #[test]
+    fn backtracks_on_conflict() {
+        let index = Index::parse(INDEX).unwrap();
+        let root = [req("basic-cli", "*"), req("json", "~0.1.0")];
+        let resolved = resolve(&index, &root, &BTreeMap::new()).unwrap();
+
+        assert_eq!(versions(&resolved), ["basic-cli 0.4.0", "json 0.1.3"]);
+    }
+
+    #[test]
+    fn prefers_locked_versions() {
+        let index = Index::parse(INDEX).unwrap();
+        let locked = BTreeMap::from([("json".to_string(), Version::new(0, 2, 0))]);
+        let root = [req("basic-cli", "^0.5.0")];
+        let resolved = resolve(&index, &root, &locked).unwrap();
+
+        assert_eq!(versions(&resolved), ["basic-cli 0.5.0", "json 0.2.0"]);
+    }
+
+    #[test]
+    fn reports_unsatisfiable_requirements() {
+        let index = Index::parse(INDEX).unwrap();
+        let root = [req("basic-cli", "^0.5.0"), req("json", "~0.1.0")];
+
+        assert!(matches!(
+            resolve(&index, &root, &BTreeMap::new()),
+            Err(ResolveProblem::NoMatchingVersion { name, .. }) if name == "json"
+        ));
+        assert_eq!(
+            resolve(&index, &[req("http", "*")], &BTreeMap::new()),
+            Err(ResolveProblem::UnknownPackage {
+                name: "http".to_string(),
+                required_by: None,
+            })
+        );
+    }
+}
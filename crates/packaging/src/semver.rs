@@ -0,0 +1,239 @@
+//! Semantic versions and the version requirements written in a project's `[dependencies]`.
+//!
+//! Requirements follow Cargo's conventions: `1.2.3` and `^1.2.3` both mean "compatible with
+//! 1.2.3", `~1.2.3` allows only patch updates, `*` allows anything, and comparisons like
+//! `>=1.2.0, <1.5.0` can be combined with commas. Pre-release and build metadata suffixes are
+//! not supported.
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl Version {
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for Version {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().split('.');
+        let mut next_part = || -> Result<u64, String> {
+            let part = parts
+                .next()
+                .ok_or_else(|| format!("`{}` should look like MAJOR.MINOR.PATCH", s))?;
+
+            // `u64::from_str` would also accept a leading `+`
+            if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(format!("`{}` is not a valid version number", s));
+            }
+
+            part.parse()
+                .map_err(|_| format!("`{}` is not a valid version number", s))
+        };
+
+        let version = Version::new(next_part()?, next_part()?, next_part()?);
+
+        match parts.next() {
+            None => Ok(version),
+            Some(_) => Err(format!("`{}` should look like MAJOR.MINOR.PATCH", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    Tilde,
+    Caret,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Comparator {
+    op: Op,
+    version: Version,
+}
+
+impl Comparator {
+    fn matches(&self, version: &Version) -> bool {
+        let Comparator { op, version: v } = self;
+        let ordering = version.cmp(v);
+
+        match op {
+            Op::Exact => ordering == Ordering::Equal,
+            Op::Greater => ordering == Ordering::Greater,
+            Op::GreaterEq => ordering != Ordering::Less,
+            Op::Less => ordering == Ordering::Less,
+            Op::LessEq => ordering != Ordering::Greater,
+            Op::Tilde => {
+                ordering != Ordering::Less && version.major == v.major && version.minor == v.minor
+            }
+            // Before 1.0.0, the leftmost nonzero component is the one that signals breaking changes.
+            Op::Caret => {
+                ordering != Ordering::Less
+                    && match (v.major, v.minor) {
+                        (0, 0) => {
+                            version.major == 0 && version.minor == 0 && version.patch == v.patch
+                        }
+                        (0, minor) => version.major == 0 && version.minor == minor,
+                        (major, _) => version.major == major,
+                    }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Comparator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self.op {
+            Op::Exact => "=",
+            Op::Greater => ">",
+            Op::GreaterEq => ">=",
+            Op::Less => "<",
+            Op::LessEq => "<=",
+            Op::Tilde => "~",
+            Op::Caret => "^",
+        };
+
+        write!(f, "{}{}", op, self.version)
+    }
+}
+
+/// A set of comparators which a version must all satisfy. No comparators means any version will do.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct VersionReq {
+    comparators: Vec<Comparator>,
+}
+
+impl VersionReq {
+    pub fn matches(&self, version: &Version) -> bool {
+        self.comparators.iter().all(|c| c.matches(version))
+    }
+}
+
+impl fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.comparators.is_empty() {
+            return write!(f, "*");
+        }
+
+        for (index, comparator) in self.comparators.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+
+            write!(f, "{}", comparator)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for VersionReq {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim() == "*" {
+            return Ok(VersionReq::default());
+        }
+
+        let mut comparators = Vec::new();
+
+        for part in s.split(',') {
+            let part = part.trim();
+
+            // Longer operators have to be tried before their prefixes
+            let (op, version) = [
+                (">=", Op::GreaterEq),
+                ("<=", Op::LessEq),
+                (">", Op::Greater),
+                ("<", Op::Less),
+                ("=", Op::Exact),
+                ("~", Op::Tilde),
+                ("^", Op::Caret),
+            ]
+            .iter()
+            .find_map(|(prefix, op)| part.strip_prefix(prefix).map(|rest| (*op, rest)))
+            .unwrap_or((Op::Caret, part));
+
+            comparators.push(Comparator {
+                op,
+                version: version.parse()?,
+            });
+        }
+
+        Ok(VersionReq { comparators })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(req: &str, version: &str) -> bool {
+        req.parse::<VersionReq>()
+            .unwrap()
+            .matches(&version.parse().unwrap())
+    }
+
+    #[test]
+    fn parse_version() {
+        assert_eq!("1.20.3".parse(), Ok(Version::new(1, 20, 3)));
+        assert!("1.2".parse::<Version>().is_err());
+        assert!("1.2.3.4".parse::<Version>().is_err());
+        assert!("1.+2.3".parse::<Version>().is_err());
+        assert!("1.2.3-alpha".parse::<Version>().is_err());
+    }
+
+    #[test]
+    fn caret_requirements() {
+        assert!(matches("1.2.3", "1.9.0"));
+        assert!(matches("^1.2.3", "1.2.3"));
+        assert!(!matches("^1.2.3", "1.2.2"));
+        assert!(!matches("^1.2.3", "2.0.0"));
+        assert!(matches("^0.2.3", "0.2.9"));
+        assert!(!matches("^0.2.3", "0.3.0"));
+        assert!(matches("^0.0.3", "0.0.3"));
+        assert!(!matches("^0.0.3", "0.0.4"));
+    }
+
+    #[test]
+    fn other_requirements() {
+        assert!(matches("~1.2.3", "1.2.7"));
+        assert!(!matches("~1.2.3", "1.3.0"));
+        assert!(matches("=1.2.3", "1.2.3"));
+        assert!(!matches("=1.2.3", "1.2.4"));
+        assert!(matches(">=0.4.0, <0.6.0", "0.5.1"));
+        assert!(!matches(">=0.4.0, <0.6.0", "0.6.0"));
+        assert!(matches("*", "12.0.0"));
+    }
+
+    #[test]
+    fn display_roundtrip() {
+        for req in ["*", "^1.2.3", ">=0.4.0, <0.6.0", "~0.1.0"] {
+            assert_eq!(req.parse::<VersionReq>().unwrap().to_string(), req);
+        }
+
+        assert_eq!("1.2.3".parse::<VersionReq>().unwrap().to_string(), "^1.2.3");
+    }
+}
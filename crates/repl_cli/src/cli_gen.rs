@@ -13,7 +13,7 @@ use roc_parse::ast::Expr;
 use roc_repl_eval::eval::jit_to_ast;
 use roc_repl_eval::gen::{compile_to_mono, format_answer, Problems, ReplOutput};
 use roc_repl_eval::{ReplApp, ReplAppMemory};
-use roc_reporting::report::DEFAULT_PALETTE;
+use roc_reporting::report::Palette;
 use roc_std::RocStr;
 use roc_target::TargetInfo;
 use roc_types::pretty_print::{name_and_print_var, DebugPrint};
@@ -25,6 +25,7 @@ pub fn gen_and_eval_llvm<'a, I: Iterator<Item = &'a str>>(
     src: &str,
     target: Triple,
     opt_level: OptLevel,
+    palette: Palette,
 ) -> (Option<ReplOutput>, Problems) {
     let arena = Bump::new();
     let target_info = TargetInfo::from(&target);
@@ -32,7 +33,7 @@ pub fn gen_and_eval_llvm<'a, I: Iterator<Item = &'a str>>(
     let mut loaded;
     let problems;
 
-    match compile_to_mono(&arena, defs, src, target_info, DEFAULT_PALETTE) {
+    match compile_to_mono(&arena, defs, src, target_info, palette) {
         (Some(mono), probs) => {
             loaded = mono;
             problems = probs;
@@ -100,6 +101,41 @@ pub fn gen_and_eval_llvm<'a, I: Iterator<Item = &'a str>>(
     )
 }
 
+/// Type-check `src` in the context of `defs` and pretty-print its type, without generating
+/// any code for it. This is what `:type` in the repl uses.
+pub fn gen_expr_type<'a, I: Iterator<Item = &'a str>>(
+    defs: I,
+    src: &str,
+    target: Triple,
+    palette: Palette,
+) -> (Option<String>, Problems) {
+    let arena = Bump::new();
+    let target_info = TargetInfo::from(&target);
+
+    match compile_to_mono(&arena, defs, src, target_info, palette) {
+        (Some(mut loaded), problems) => {
+            let (_, main_fn_var) = loaded
+                .exposed_to_host
+                .top_level_values
+                .iter()
+                .next()
+                .unwrap();
+            let main_fn_var = *main_fn_var;
+
+            let expr_type_str = name_and_print_var(
+                main_fn_var,
+                &mut loaded.subs,
+                loaded.module_id,
+                &loaded.interns,
+                DebugPrint::NOTHING,
+            );
+
+            (Some(expr_type_str), problems)
+        }
+        (None, problems) => (None, problems),
+    }
+}
+
 struct CliApp {
     lib: Library,
 }
@@ -189,14 +225,24 @@ fn mono_module_to_dylib<'a>(
     let target_info = TargetInfo::from(&target);
 
     let MonomorphizedModule {
+        module_id,
         procedures,
         entry_point,
-        interns,
+        mut interns,
         subs,
         mut layout_interner,
         ..
     } = loaded;
 
+    let procedures = roc_gen_llvm::llvm::build::expand_refcounting_procs(
+        arena,
+        target_info,
+        module_id,
+        &mut interns,
+        &mut layout_interner,
+        procedures,
+    );
+
     let context = Context::create();
     let builder = context.create_builder();
     let module = arena.alloc(roc_gen_llvm::llvm::build::module_from_builtins(
@@ -222,6 +268,11 @@ fn mono_module_to_dylib<'a>(
         mode: LlvmBackendMode::GenTest, // so roc_panic is generated
         // important! we don't want any procedures to get the C calling convention
         exposed_to_host: MutSet::default(),
+        heap_profile: false,
+        stack_limit_checks: false,
+        arena_allocation: false,
+        profile_hints: None,
+        func_spec_numbers: Default::default(),
     };
 
     // Add roc_alloc, roc_realloc, and roc_dealloc, since the repl has no
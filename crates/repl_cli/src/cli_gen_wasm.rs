@@ -0,0 +1,237 @@
+//! Evaluates REPL input by compiling it with the Wasm backend and running it in the
+//! embedded Wasm interpreter. Unlike the LLVM JIT, this works the same on every host.
+use bumpalo::{collections::vec::Vec, Bump};
+use roc_collections::all::MutSet;
+use roc_gen_wasm::wasm32_result;
+use roc_load::MonomorphizedModule;
+use roc_parse::ast::Expr;
+use roc_repl_eval::eval::jit_to_ast;
+use roc_repl_eval::gen::{compile_to_mono, format_answer, Problems, ReplOutput};
+use roc_repl_eval::wasm_memory::WasmMemory;
+use roc_repl_eval::{ReplApp, ReplAppMemory};
+use roc_reporting::report::Palette;
+use roc_target::TargetInfo;
+use roc_types::pretty_print::{name_and_print_var, DebugPrint};
+use roc_wasm_interp::{DefaultImportDispatcher, Instance};
+
+const WRAPPER_NAME: &str = "wrapper";
+
+#[cfg(not(windows))]
+const PRE_LINKED_BINARY: &[u8] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/pre_linked_binary.o")) as &[_];
+
+#[cfg(windows)]
+const PRE_LINKED_BINARY: &[u8] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/pre_linked_binary.obj")) as &[_];
+
+pub fn gen_and_eval_wasm<'a, I: Iterator<Item = &'a str>>(
+    defs: I,
+    src: &str,
+    palette: Palette,
+) -> (Option<ReplOutput>, Problems) {
+    let arena = Bump::new();
+    let target_info = TargetInfo::default_wasm32();
+
+    let mono = match compile_to_mono(&arena, defs, src, target_info, palette) {
+        (Some(mono), problems) => (mono, problems),
+        (None, problems) => return (None, problems),
+    };
+
+    let (
+        MonomorphizedModule {
+            module_id,
+            procedures,
+            mut interns,
+            mut subs,
+            exposed_to_host,
+            mut layout_interner,
+            ..
+        },
+        problems,
+    ) = mono;
+
+    debug_assert_eq!(exposed_to_host.top_level_values.len(), 1);
+    let (main_fn_symbol, main_fn_var) = exposed_to_host.top_level_values.iter().next().unwrap();
+    let main_fn_symbol = *main_fn_symbol;
+    let main_fn_var = *main_fn_var;
+
+    // pretty-print the expr type string for later.
+    let expr_type_str = name_and_print_var(
+        main_fn_var,
+        &mut subs,
+        module_id,
+        &interns,
+        DebugPrint::NOTHING,
+    );
+
+    let (_, main_fn_layout) = match procedures.keys().find(|(s, _)| *s == main_fn_symbol) {
+        Some(layout) => *layout,
+        None => {
+            let empty_vec: std::vec::Vec<String> = std::vec::Vec::new(); // rustc can't infer the type of this Vec.
+            debug_assert_ne!(problems.errors, empty_vec, "Got no errors but also no valid layout for the generated main function in the repl!");
+
+            return (None, problems);
+        }
+    };
+
+    let app_module_bytes = {
+        let env = roc_gen_wasm::Env {
+            arena: &arena,
+            module_id,
+            stack_bytes: roc_gen_wasm::Env::DEFAULT_STACK_BYTES,
+            features: roc_gen_wasm::WasmFeatures::default(),
+            wasi_hooks: false,
+            exposed_to_host: exposed_to_host
+                .top_level_values
+                .keys()
+                .copied()
+                .collect::<MutSet<_>>(),
+        };
+
+        let (mut module, mut called_fns, main_fn_index) = {
+            let host_module = roc_gen_wasm::parse_host(env.arena, PRE_LINKED_BINARY).unwrap();
+            roc_gen_wasm::build_app_module(
+                &env,
+                &mut layout_interner,
+                &mut interns, // NOTE: must drop this mutable ref before jit_to_ast
+                host_module,
+                procedures,
+            )
+        };
+
+        wasm32_result::insert_wrapper_for_layout(
+            &arena,
+            &layout_interner,
+            &mut module,
+            WRAPPER_NAME,
+            main_fn_index,
+            main_fn_layout.result,
+        );
+        called_fns.push(true);
+
+        module.eliminate_dead_code(env.arena, called_fns);
+
+        let mut buffer = Vec::with_capacity_in(module.size(), &arena);
+        module.serialize(&mut buffer);
+
+        buffer
+    };
+
+    let is_debug_mode = false; // logs every instruction!
+    let instance = Instance::from_bytes(
+        &arena,
+        &app_module_bytes,
+        DefaultImportDispatcher::default(),
+        is_debug_mode,
+    )
+    .expect("we produce a valid Wasm module");
+
+    let mut app = InterpApp {
+        arena: &arena,
+        instance,
+    };
+
+    let expr = jit_to_ast(
+        &arena,
+        &mut app,
+        "", // main_fn_name is ignored; the wrapper is always called WRAPPER_NAME
+        main_fn_layout,
+        main_fn_var,
+        &subs,
+        &interns,
+        layout_interner.into_global().fork(),
+        target_info,
+    );
+    let expr_str = format_answer(&arena, expr).to_string();
+
+    (
+        Some(ReplOutput {
+            expr: expr_str,
+            expr_type: expr_type_str,
+        }),
+        problems,
+    )
+}
+
+struct InterpApp<'a> {
+    arena: &'a Bump,
+    instance: Instance<'a, DefaultImportDispatcher<'a>>,
+}
+
+impl<'a> InterpApp<'a> {
+    /// Run the app's wrapper function, then copy out the app's memory.
+    /// Returns the copy, along with the address of the result within it.
+    fn run(&mut self) -> (&'a WasmMemory<'a>, usize) {
+        let result_addr = self
+            .instance
+            .call_export(WRAPPER_NAME, [])
+            .unwrap_or_else(|e| panic!("The Wasm app crashed: {}", e))
+            .expect("No return address from wrapper")
+            .expect_i32()
+            .unwrap();
+
+        let copied_bytes = self.arena.alloc_slice_copy(&self.instance.memory);
+        let mem = self.arena.alloc(WasmMemory::new(copied_bytes));
+
+        (mem, result_addr as usize)
+    }
+}
+
+impl<'a> ReplApp<'a> for InterpApp<'a> {
+    type Memory = WasmMemory<'a>;
+
+    /// Run user code that returns a type with a `Builtin` layout
+    /// Size of the return value is statically determined from its Rust type
+    fn call_function<Return, F>(&mut self, _main_fn_name: &str, mut transform: F) -> Expr<'a>
+    where
+        F: FnMut(&'a Self::Memory, Return) -> Expr<'a>,
+        Self::Memory: 'a,
+    {
+        let (mem, result_addr) = self.run();
+
+        let result_bytes = &mem.copied_bytes()[result_addr..][..std::mem::size_of::<Return>()];
+        let result: Return = unsafe {
+            let ptr = result_bytes.as_ptr() as *const Return;
+            // The copied memory has no particular alignment
+            ptr.read_unaligned()
+        };
+
+        transform(mem, result)
+    }
+
+    /// A RocList in wasm32 is three 32-bit words, not three of the host's usize.
+    fn call_function_returns_roc_list<F>(
+        &mut self,
+        _main_fn_name: &str,
+        mut transform: F,
+    ) -> Expr<'a>
+    where
+        F: FnMut(&'a Self::Memory, (usize, usize, usize)) -> Expr<'a>,
+        Self::Memory: 'a,
+    {
+        let (mem, result_addr) = self.run();
+
+        let elements = mem.deref_usize(result_addr);
+        let length = mem.deref_usize(result_addr + 4);
+        let capacity = mem.deref_usize(result_addr + 8);
+
+        transform(mem, (elements, length, capacity))
+    }
+
+    /// Run user code that returns a struct or union, whose size is provided as an argument
+    /// _ret_bytes is compiled-in to the wrapper function of the app itself
+    fn call_function_dynamic_size<T, F>(
+        &mut self,
+        _main_fn_name: &str,
+        _ret_bytes: usize,
+        mut transform: F,
+    ) -> T
+    where
+        F: FnMut(&'a Self::Memory, usize) -> T,
+        Self::Memory: 'a,
+    {
+        let (mem, result_addr) = self.run();
+
+        transform(mem, result_addr)
+    }
+}
@@ -1,4 +1,11 @@
-pub const BLUE: &str = "\u{001b}[36m";
-pub const PINK: &str = "\u{001b}[35m";
-pub const GREEN: &str = "\u{001b}[32m";
-pub const END_COL: &str = "\u{001b}[0m";
+use roc_reporting::report::{Palette, StyleCodes, ANSI_STYLE_CODES, NO_COLOR_STYLE_CODES};
+
+/// The REPL's own styling (prompts, tips, the type separator) goes plain
+/// whenever the palette used for reports does.
+pub fn style_codes(palette: &Palette) -> &'static StyleCodes {
+    if palette.is_colored() {
+        &ANSI_STYLE_CODES
+    } else {
+        &NO_COLOR_STYLE_CODES
+    }
+}
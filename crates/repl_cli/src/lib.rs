@@ -1,48 +1,69 @@
 //! Command Line Interface (CLI) functionality for the Read-Evaluate-Print-Loop (REPL).
 mod cli_gen;
+mod cli_gen_wasm;
 mod colors;
 pub mod repl_state;
 
-use colors::{BLUE, END_COL, PINK};
-use const_format::concatcp;
-use repl_state::ReplState;
+use colors::style_codes;
+use repl_state::{ReplBackend, ReplState};
+use roc_reporting::report::Palette;
+use std::path::PathBuf;
 
-use crate::repl_state::PROMPT;
+use crate::repl_state::prompt;
 
-pub const WELCOME_MESSAGE: &str = concatcp!(
-    "\n  The rockin’ ",
-    BLUE,
-    "roc repl",
-    END_COL,
-    "\n",
-    PINK,
-    "────────────────────────",
-    END_COL,
-    "\n\n"
-);
+pub fn welcome_message(palette: &Palette) -> String {
+    let codes = style_codes(palette);
+
+    format!(
+        "\n  The rockin’ {}roc repl{}\n{}────────────────────────{}\n\n",
+        codes.cyan, codes.reset, codes.magenta, codes.reset
+    )
+}
 
 // For when nothing is entered in the repl
 // TODO add link to repl tutorial(does not yet exist).
 pub const SHORT_INSTRUCTIONS: &str = "Enter an expression, or :help, or :q to quit.\n\n";
 
-pub fn main() -> i32 {
+/// Where we keep the inputs of past repl sessions, so the up arrow can bring them back.
+fn history_path() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(".roc_repl_history"))
+}
+
+pub fn main(backend: ReplBackend) -> i32 {
     use rustyline::error::ReadlineError;
     use rustyline::Editor;
 
     // To debug rustyline:
     // <UNCOMMENT> env_logger::init();
     // <RUN WITH:> RUST_LOG=rustyline=debug cargo run repl 2> debug.log
-    print!("{}{}", WELCOME_MESSAGE, SHORT_INSTRUCTIONS);
+    let palette = Palette::from_env();
+    let prompt = prompt(&palette);
+
+    print!("{}{}", welcome_message(&palette), SHORT_INSTRUCTIONS);
 
     let mut editor = Editor::<ReplState>::new();
-    let repl_helper = ReplState::new();
+    let repl_helper = ReplState::with_palette(palette).with_backend(backend);
     editor.set_helper(Some(repl_helper));
 
+    let history_path = history_path();
+
+    if let Some(path) = &history_path {
+        // There's no history file yet the first time the repl is run, so ignore errors.
+        let _ = editor.load_history(path);
+    }
+
     loop {
-        match editor.readline(PROMPT) {
+        match editor.readline(&prompt) {
             Ok(line) => {
                 editor.add_history_entry(line.trim());
 
+                if let Some(path) = &history_path {
+                    // Not being able to persist history shouldn't stop the repl from working.
+                    let _ = editor.save_history(path);
+                }
+
                 let dimensions = editor.dimensions();
                 let repl_helper = editor.helper_mut().expect("Editor helper was not set");
 
@@ -1,11 +1,12 @@
-use crate::cli_gen::gen_and_eval_llvm;
-use crate::colors::{BLUE, END_COL, GREEN, PINK};
+use crate::cli_gen::{gen_and_eval_llvm, gen_expr_type};
+use crate::cli_gen_wasm::gen_and_eval_wasm;
+use crate::colors::style_codes;
 use bumpalo::Bump;
-use const_format::concatcp;
 use roc_collections::MutSet;
 use roc_mono::ir::OptLevel;
 use roc_parse::ast::{Expr, Pattern, TypeDef, TypeHeader, ValueDef};
 use roc_parse::expr::{parse_single_def, ExprParseOptions, SingleDef};
+use roc_parse::module::{module_defs, parse_header};
 use roc_parse::parser::Parser;
 use roc_parse::parser::{EClosure, EExpr, EPattern};
 use roc_parse::parser::{EWhen, Either};
@@ -13,54 +14,54 @@ use roc_parse::state::State;
 use roc_parse::{join_alias_to_body, join_ann_to_body};
 use roc_region::all::Loc;
 use roc_repl_eval::gen::{Problems, ReplOutput};
+use roc_reporting::report::{Palette, StyleCodes, DEFAULT_PALETTE};
 use rustyline::highlight::{Highlighter, PromptInfo};
 use rustyline::validate::{self, ValidationContext, ValidationResult, Validator};
 use rustyline_derive::{Completer, Helper, Hinter};
 use std::borrow::Cow;
 use target_lexicon::Triple;
 
-pub const PROMPT: &str = concatcp!(BLUE, "»", END_COL, " ");
-pub const CONT_PROMPT: &str = concatcp!(BLUE, "…", END_COL, " ");
-
 /// The prefix we use for the automatic variable names we assign to each expr,
 /// e.g. if the prefix is "val" then the first expr you enter will be named "val1"
 pub const AUTO_VAR_PREFIX: &str = "val";
 
+pub fn prompt(palette: &Palette) -> String {
+    let codes = style_codes(palette);
+
+    format!("{}»{} ", codes.cyan, codes.reset)
+}
+
+fn cont_prompt(palette: &Palette) -> String {
+    let codes = style_codes(palette);
+
+    format!("{}…{} ", codes.cyan, codes.reset)
+}
+
 // TODO add link to repl tutorial(does not yet exist).
-pub const TIPS: &str = concatcp!(
-    "\nEnter an expression to evaluate, or a definition (like ",
-    BLUE,
-    "x = 1",
-    END_COL,
-    ") to use in future expressions.\n\nUnless there was a compile-time error, expressions get automatically named so you can refer to them later.\nFor example, if you see ",
-    GREEN,
-    "# val1",
-    END_COL,
-    " after an output, you can now refer to that expression as ",
-    BLUE,
-    "val1",
-    END_COL,
-    " in future expressions.\n\nTips:\n\n",
-    BLUE,
-    "  - ",
-    END_COL,
-    PINK,
-    "ctrl-v",
-    END_COL,
-    " + ",
-    PINK,
-    "ctrl-j",
-    END_COL,
-    " makes a newline\n\n",
-    BLUE,
-    "  - ",
-    END_COL,
-    ":q to quit\n\n",
-    BLUE,
-    "  - ",
-    END_COL,
-    ":help"
-);
+pub fn tips(palette: &Palette) -> String {
+    let StyleCodes {
+        cyan: blue,
+        magenta: pink,
+        green,
+        reset: end_col,
+        ..
+    } = style_codes(palette);
+
+    format!(
+        "\nEnter an expression to evaluate, or a definition (like {blue}x = 1{end_col}) to use in future expressions.\n\nUnless there was a compile-time error, expressions get automatically named so you can refer to them later.\nFor example, if you see {green}# val1{end_col} after an output, you can now refer to that expression as {blue}val1{end_col} in future expressions.\n\nTips:\n\n{blue}  - {end_col}{pink}ctrl-v{end_col} + {pink}ctrl-j{end_col} makes a newline\n\n{blue}  - {end_col}:type {blue}expr{end_col} shows the type of an expression without running it\n\n{blue}  - {end_col}:load {blue}File.roc{end_col} adds the definitions in a file to this session\n\n{blue}  - {end_col}:reset forgets all definitions entered so far\n\n{blue}  - {end_col}:q to quit\n\n{blue}  - {end_col}:help"
+    )
+}
+
+/// How the repl compiles and runs the expressions entered into it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReplBackend {
+    /// Compile with LLVM and run the result natively, via a dynamic library.
+    #[default]
+    Llvm,
+    /// Compile with the Wasm backend and run the result in the embedded Wasm interpreter.
+    /// This doesn't depend on LLVM being able to target the host.
+    Wasm,
+}
 
 #[derive(Debug, Clone, PartialEq)]
 struct PastDef {
@@ -74,6 +75,8 @@ pub struct ReplState {
     past_defs: Vec<PastDef>,
     past_def_idents: MutSet<String>,
     last_auto_ident: u64,
+    palette: Palette,
+    backend: ReplBackend,
 }
 
 impl Default for ReplState {
@@ -84,19 +87,33 @@ impl Default for ReplState {
 
 impl ReplState {
     pub fn new() -> Self {
+        Self::with_palette(DEFAULT_PALETTE)
+    }
+
+    pub fn with_palette(palette: Palette) -> Self {
         Self {
             validator: InputValidator::new(),
             past_defs: Default::default(),
             past_def_idents: Default::default(),
             last_auto_ident: 0,
+            palette,
+            backend: ReplBackend::default(),
         }
     }
 
+    pub fn with_backend(self, backend: ReplBackend) -> Self {
+        Self { backend, ..self }
+    }
+
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
     pub fn step(&mut self, line: &str, dimensions: Option<(usize, usize)>) -> Result<String, i32> {
         let arena = Bump::new();
 
         match parse_src(&arena, line) {
-            ParseOutcome::Empty => Ok(TIPS.to_string()),
+            ParseOutcome::Empty => Ok(tips(&self.palette)),
             ParseOutcome::Expr(_)
             | ParseOutcome::ValueDef(_)
             | ParseOutcome::TypeDef(_)
@@ -104,12 +121,84 @@ impl ReplState {
             | ParseOutcome::Incomplete => Ok(self.eval_and_format(line, dimensions)),
             ParseOutcome::Help => {
                 // TODO add link to repl tutorial(does not yet exist).
-                Ok(TIPS.to_string())
+                Ok(tips(&self.palette))
+            }
+            ParseOutcome::Reset => {
+                self.reset();
+
+                Ok(String::new())
             }
+            ParseOutcome::Type(expr_src) => Ok(self.type_and_format(expr_src, dimensions)),
+            ParseOutcome::Load(path) => Ok(self.load_file(path)),
             ParseOutcome::Exit => Err(0),
         }
     }
 
+    /// Forget every def entered so far, including the automatically named ones.
+    pub fn reset(&mut self) {
+        self.past_defs.clear();
+        self.past_def_idents.clear();
+        self.last_auto_ident = 0;
+    }
+
+    /// Infer the type of an expression (for `:type`) without generating code for it or running it.
+    pub fn type_and_format(&self, src: &str, dimensions: Option<(usize, usize)>) -> String {
+        let (opt_type, problems) = gen_expr_type(
+            self.past_defs.iter().map(|def| def.src.as_str()),
+            src,
+            Triple::host(),
+            self.palette,
+        );
+
+        let output = opt_type.map(|expr_type| ReplOutput {
+            expr: src.trim().to_string(),
+            expr_type,
+        });
+
+        format_output(
+            output,
+            problems,
+            None,
+            dimensions,
+            style_codes(&self.palette),
+        )
+    }
+
+    /// Add the top-level defs of a .roc file (for `:load`) to the defs of this session.
+    /// The defs are not evaluated until something refers to them.
+    pub fn load_file(&mut self, path: &str) -> String {
+        let src = match std::fs::read_to_string(path) {
+            Ok(src) => src,
+            Err(err) => return format!("\nI could not read {path}: {err}\n"),
+        };
+
+        let arena = Bump::new();
+
+        let state = match parse_header(&arena, State::new(src.as_bytes())) {
+            Ok((_, state)) => state,
+            Err(_) => return format!("\nI could not parse the module header of {path}.\n"),
+        };
+
+        let defs = match module_defs().parse(&arena, state, 0) {
+            Ok((_, defs, _)) => defs,
+            Err(_) => return format!("\nI could not parse the definitions in {path}.\n"),
+        };
+
+        let mut loaded = 0;
+
+        for (def, region) in defs.defs().zip(defs.regions.iter()) {
+            if let Some(ident) = def_ident(def) {
+                let def_src = &src[region.start().offset as usize..region.end().offset as usize];
+
+                self.add_past_def(ident.trim_end().to_string(), def_src.to_string());
+
+                loaded += 1;
+            }
+        }
+
+        format!("\nLoaded {loaded} definitions from {path}\n")
+    }
+
     pub fn eval_and_format(&mut self, src: &str, dimensions: Option<(usize, usize)>) -> String {
         let arena = Bump::new();
         let pending_past_def;
@@ -187,6 +276,10 @@ impl ReplState {
                     ValueDef::ExpectFx { .. } => {
                         todo!("handle receiving an `expect-fx` - what should the repl do for that?")
                     }
+                    ValueDef::Bench { .. } => {
+                        // Timing something needs an optimized build, which the repl doesn't do
+                        return "\nThe repl doesn't run benchmarks. Put the `bench` at the top level of a module and use `roc bench` instead.\n".to_string();
+                    }
                 }
             }
             ParseOutcome::TypeDef(TypeDef::Alias {
@@ -220,7 +313,12 @@ impl ReplState {
                 // can be evaluated as expressions.
                 return String::new();
             }
-            ParseOutcome::Empty | ParseOutcome::Help | ParseOutcome::Exit => unreachable!(),
+            ParseOutcome::Empty
+            | ParseOutcome::Help
+            | ParseOutcome::Reset
+            | ParseOutcome::Type(_)
+            | ParseOutcome::Load(_)
+            | ParseOutcome::Exit => unreachable!(),
         };
 
         // Record e.g. "val1" as a past def, unless our input was exactly the name of
@@ -231,20 +329,10 @@ impl ReplState {
                 Some(existing_ident) => {
                     opt_var_name = Some(existing_ident);
 
-                    gen_and_eval_llvm(
-                        self.past_defs.iter().map(|def| def.src.as_str()),
-                        src,
-                        Triple::host(),
-                        OptLevel::Normal,
-                    )
+                    self.gen_and_eval(src)
                 }
                 None => {
-                    let (output, problems) = gen_and_eval_llvm(
-                        self.past_defs.iter().map(|def| def.src.as_str()),
-                        src,
-                        Triple::host(),
-                        OptLevel::Normal,
-                    );
+                    let (output, problems) = self.gen_and_eval(src);
 
                     // Don't persist defs that have compile errors
                     if problems.errors.is_empty() {
@@ -266,7 +354,24 @@ impl ReplState {
             self.add_past_def(ident, src);
         }
 
-        format_output(output, problems, opt_var_name, dimensions)
+        format_output(
+            output,
+            problems,
+            opt_var_name,
+            dimensions,
+            style_codes(&self.palette),
+        )
+    }
+
+    fn gen_and_eval(&self, src: &str) -> (Option<ReplOutput>, Problems) {
+        let defs = self.past_defs.iter().map(|def| def.src.as_str());
+
+        match self.backend {
+            ReplBackend::Llvm => {
+                gen_and_eval_llvm(defs, src, Triple::host(), OptLevel::Normal, self.palette)
+            }
+            ReplBackend::Wasm => gen_and_eval_wasm(defs, src, self.palette),
+        }
     }
 
     fn next_auto_ident(&mut self) -> u64 {
@@ -292,13 +397,81 @@ enum ParseOutcome<'a> {
     SyntaxErr,
     Empty,
     Help,
+    Reset,
+    Type(&'a str),
+    Load(&'a str),
     Exit,
 }
 
+/// The name a top-level def in a loaded file will be known by in the repl,
+/// or None if it's not something the repl can keep as a past def (e.g. an `expect`).
+fn def_ident<'a>(def: Result<&TypeDef<'a>, &ValueDef<'a>>) -> Option<&'a str> {
+    match def {
+        Ok(TypeDef::Alias {
+            header:
+                TypeHeader {
+                    name: Loc { value: ident, .. },
+                    ..
+                },
+            ..
+        })
+        | Ok(TypeDef::Opaque {
+            header:
+                TypeHeader {
+                    name: Loc { value: ident, .. },
+                    ..
+                },
+            ..
+        })
+        | Ok(TypeDef::Ability {
+            header:
+                TypeHeader {
+                    name: Loc { value: ident, .. },
+                    ..
+                },
+            ..
+        })
+        | Err(ValueDef::Annotation(
+            Loc {
+                value: Pattern::Identifier(ident),
+                ..
+            },
+            _,
+        ))
+        | Err(ValueDef::Body(
+            Loc {
+                value: Pattern::Identifier(ident),
+                ..
+            },
+            _,
+        ))
+        | Err(ValueDef::AnnotatedBody {
+            body_pattern:
+                Loc {
+                    value: Pattern::Identifier(ident),
+                    ..
+                },
+            ..
+        }) => Some(ident),
+        _ => None,
+    }
+}
+
 fn parse_src<'a>(arena: &'a Bump, line: &'a str) -> ParseOutcome<'a> {
-    match line.trim().to_lowercase().as_str() {
+    let trimmed = line.trim();
+
+    if let Some(expr_src) = trimmed.strip_prefix(":type ") {
+        return ParseOutcome::Type(expr_src);
+    }
+
+    if let Some(path) = trimmed.strip_prefix(":load ") {
+        return ParseOutcome::Load(path.trim());
+    }
+
+    match trimmed.to_lowercase().as_str() {
         "" => ParseOutcome::Empty,
         ":help" => ParseOutcome::Help,
+        ":reset" => ParseOutcome::Reset,
         ":exit" | ":quit" | ":q" => ParseOutcome::Exit,
         _ => {
             let src_bytes = line.as_bytes();
@@ -493,6 +666,9 @@ pub fn is_incomplete(input: &str) -> bool {
         }
         ParseOutcome::Empty
         | ParseOutcome::Help
+        | ParseOutcome::Reset
+        | ParseOutcome::Type(_)
+        | ParseOutcome::Load(_)
         | ParseOutcome::Exit
         | ParseOutcome::ValueDef(_)
         | ParseOutcome::TypeDef(_)
@@ -512,7 +688,7 @@ impl Highlighter for ReplState {
         info: PromptInfo<'_>,
     ) -> Cow<'b, str> {
         if info.line_no() > 0 {
-            CONT_PROMPT.into()
+            cont_prompt(&self.palette).into()
         } else {
             prompt.into()
         }
@@ -537,6 +713,7 @@ fn format_output(
     problems: Problems,
     opt_var_name: Option<String>,
     dimensions: Option<(usize, usize)>,
+    codes: &StyleCodes,
 ) -> String {
     let mut buf = String::new();
 
@@ -563,9 +740,9 @@ fn format_output(
             {
                 buf.push('\n');
                 buf.push_str(&expr);
-                buf.push_str(PINK); // Color for the type separator
+                buf.push_str(codes.magenta); // Color for the type separator
                 buf.push_str(EXPR_TYPE_SEPARATOR);
-                buf.push_str(END_COL);
+                buf.push_str(codes.reset);
                 buf.push_str(&expr_type);
             }
 
@@ -603,10 +780,10 @@ fn format_output(
                     buf.push(' ');
                 }
 
-                buf.push_str(GREEN);
+                buf.push_str(codes.green);
                 buf.push_str(VAR_NAME_PREFIX);
                 buf.push_str(&var_name);
-                buf.push_str(END_COL);
+                buf.push_str(codes.reset);
                 buf.push('\n');
             }
         }
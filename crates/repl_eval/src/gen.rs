@@ -66,6 +66,12 @@ pub fn compile_to_mono<'a, 'i, I: Iterator<Item = &'i str>>(
             palette,
             threading: Threading::Single,
             exec_mode: ExecutionMode::Executable,
+            max_specializations: None,
+            coverage: false,
+            fold_constants: false,
+            hoist_loop_invariants: false,
+            strip_expects: false,
+            cancelled: None,
         },
     );
 
@@ -0,0 +1,644 @@
+//! The reverse of `eval`: turns Roc literal syntax into a runtime value in the app's memory.
+//!
+//! Like the pretty-printer, this walks the type alongside the layout, since the layout alone
+//! doesn't know about record field names or tag names.
+use std::fmt;
+
+use bumpalo::collections::{CollectIn, Vec};
+use bumpalo::Bump;
+use roc_builtins::bitcode::{FloatWidth, IntWidth};
+use roc_module::called_via::UnaryOp;
+use roc_module::ident::TagName;
+use roc_module::symbol::{ModuleId, Symbol};
+use roc_mono::layout::{
+    self, cmp_fields, union_sorted_tags_pub, Builtin, InLayout, LayoutCache, LayoutInterner,
+    LayoutRepr, TLLayoutInterner, UnionVariant,
+};
+use roc_parse::ast::{AssignedField, Base, Expr, StrLiteral, StrSegment};
+use roc_std::RocDec;
+use roc_target::TargetInfo;
+use roc_types::subs::{Content, FlatType, GetSubsSlice, Subs, TagExt, Variable};
+
+use crate::ReplAppMemoryMut;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputProblem {
+    /// The text isn't valid Roc syntax
+    Syntax(String),
+    /// The text is valid Roc, but not a value of the expected type
+    Mismatch { expected: String, found: String },
+    /// A value of this type can't be written as a literal (yet)
+    Unsupported(String),
+}
+
+impl fmt::Display for InputProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputProblem::Syntax(problem) => write!(f, "This isn't valid Roc: {}", problem),
+            InputProblem::Mismatch { expected, found } => {
+                write!(f, "I was expecting {}, but found `{}`", expected, found)
+            }
+            InputProblem::Unsupported(what) => {
+                write!(f, "I can't read {} from a literal yet", what)
+            }
+        }
+    }
+}
+
+struct Env<'a, 'env> {
+    arena: &'a Bump,
+    subs: &'env Subs,
+    target_info: TargetInfo,
+    layout_cache: LayoutCache<'a>,
+}
+
+/// Parse `text` as a Roc literal of type `var`, and write it to `addr` in the app's memory.
+/// The caller must have reserved enough space at `addr` for a value of `layout`; anything
+/// the value points to (like the elements of a list) is allocated through `mem`.
+///
+/// Numbers, strings, lists, records, and tag unions whose tags have no payloads are supported.
+#[allow(clippy::too_many_arguments)]
+pub fn value_from_str<'a, M: ReplAppMemoryMut>(
+    arena: &'a Bump,
+    mem: &mut M,
+    addr: usize,
+    text: &'a str,
+    layout: InLayout<'a>,
+    var: Variable,
+    subs: &Subs,
+    layout_interner: TLLayoutInterner<'a>,
+    target_info: TargetInfo,
+) -> Result<(), InputProblem> {
+    let expr = roc_parse::test_helpers::parse_expr_with(arena, text)
+        .map_err(|problem| InputProblem::Syntax(format!("{:?}", problem)))?;
+
+    let mut env = Env {
+        arena,
+        subs,
+        target_info,
+        layout_cache: LayoutCache::new(layout_interner, target_info),
+    };
+
+    write_value(&mut env, mem, addr, &expr, layout, var)
+}
+
+fn write_value<'a, M: ReplAppMemoryMut>(
+    env: &mut Env<'a, '_>,
+    mem: &mut M,
+    addr: usize,
+    expr: &Expr<'a>,
+    layout: InLayout<'a>,
+    var: Variable,
+) -> Result<(), InputProblem> {
+    let expr = without_spaces(expr);
+    let var = without_aliases(env.subs, var);
+    let content = env.subs.get_content_without_compacting(var);
+
+    // Newtypes have the same layout as the single thing they wrap.
+    match content {
+        Content::Structure(FlatType::Record(fields, _)) if fields.len() == 1 => {
+            let (label, field) = fields
+                .sorted_iterator(env.subs, Variable::EMPTY_RECORD)
+                .next()
+                .unwrap();
+
+            return match expr {
+                Expr::Record(assigned) if assigned.len() == 1 => {
+                    match field_value(&assigned.items[0].value) {
+                        Some((name, value)) if name == label.as_str() => {
+                            write_value(env, mem, addr, value, layout, field.into_inner())
+                        }
+                        _ => Err(mismatch(
+                            format!("the record {{ {} }}", label.as_str()),
+                            expr,
+                        )),
+                    }
+                }
+                _ => Err(mismatch(
+                    format!("the record {{ {} }}", label.as_str()),
+                    expr,
+                )),
+            };
+        }
+        Content::Structure(FlatType::TagUnion(tags, _)) if tags.len() == 1 => {
+            let (tag_name, payload_vars) = tags
+                .unsorted_iterator(env.subs, TagExt::Any(Variable::EMPTY_TAG_UNION))
+                .next()
+                .unwrap();
+
+            if let [payload_var] = payload_vars {
+                let expected = || format!("the tag {}", tag_name.as_ident_str());
+
+                return match expr {
+                    Expr::Apply(tag, [payload], _) => match tag.value {
+                        Expr::Tag(name) if name == tag_name.as_ident_str().as_str() => {
+                            write_value(env, mem, addr, &payload.value, layout, *payload_var)
+                        }
+                        _ => Err(mismatch(expected(), expr)),
+                    },
+                    _ => Err(mismatch(expected(), expr)),
+                };
+            }
+        }
+        _ => {}
+    }
+
+    match (content, env.layout_cache.get_in(layout).repr) {
+        (Content::Structure(FlatType::Func(_, _, _)), _) | (_, LayoutRepr::LambdaSet(_)) => {
+            Err(InputProblem::Unsupported("functions".to_string()))
+        }
+        (_, LayoutRepr::Builtin(Builtin::Bool)) => {
+            let value = bool_value(env, expr, content)?;
+
+            mem.write_bytes(addr, &[value as u8]);
+
+            Ok(())
+        }
+        (_, LayoutRepr::Builtin(Builtin::Int(IntWidth::U8))) if !matches!(content, Content::Alias(name, ..) if name.module_id() == ModuleId::NUM) =>
+        {
+            let value = byte_value(env, expr, content)?;
+
+            mem.write_bytes(addr, &[value]);
+
+            Ok(())
+        }
+        (_, LayoutRepr::Builtin(Builtin::Int(int_width))) => {
+            let expected = || format!("an integer that fits in {:?}", int_width);
+            let (is_negative, magnitude) =
+                int_value(expr).ok_or_else(|| mismatch(expected(), expr))?;
+            let bytes = int_bytes(int_width, is_negative, magnitude)
+                .ok_or_else(|| mismatch(expected(), expr))?;
+
+            mem.write_bytes(addr, &bytes);
+
+            Ok(())
+        }
+        (_, LayoutRepr::Builtin(Builtin::Float(float_width))) => {
+            let value = float_value(expr).ok_or_else(|| mismatch("a number", expr))?;
+
+            match float_width {
+                FloatWidth::F32 => mem.write_bytes(addr, &(value as f32).to_le_bytes()),
+                FloatWidth::F64 => mem.write_bytes(addr, &value.to_le_bytes()),
+            }
+
+            Ok(())
+        }
+        (_, LayoutRepr::Builtin(Builtin::Decimal)) => {
+            let text = match expr {
+                Expr::Num(string) | Expr::Float(string) => number_without_suffix(string),
+                _ => return Err(mismatch("a decimal number", expr)),
+            };
+
+            if RocDec::from_str(text).is_none() {
+                return Err(mismatch("a decimal number", expr));
+            }
+
+            let bits = RocDec::from_str_to_i128_unsafe(text);
+
+            mem.write_bytes(addr, &bits.to_le_bytes());
+
+            Ok(())
+        }
+        (_, LayoutRepr::Builtin(Builtin::Str)) => {
+            let string = str_value(env, expr)?;
+
+            write_str(env, mem, addr, string.as_bytes());
+
+            Ok(())
+        }
+        (_, LayoutRepr::Builtin(Builtin::List(elem_layout))) => {
+            let elem_var = match content {
+                Content::Structure(FlatType::Apply(Symbol::LIST_LIST, vars)) => {
+                    env.subs.get_subs_slice(*vars)[0]
+                }
+                other => unreachable!("Something had a List layout, but a {:?} type", other),
+            };
+
+            let items = match expr {
+                Expr::List(items) => items,
+                _ => return Err(mismatch("a list", expr)),
+            };
+
+            let interner = &env.layout_cache.interner;
+            let elem_size = interner.stack_size(elem_layout) as usize;
+            let elem_alignment = interner.alignment_bytes(elem_layout);
+
+            let elems_addr = if items.is_empty() {
+                0
+            } else {
+                mem.alloc_refcounted(elem_size * items.len(), elem_alignment)
+            };
+
+            for (index, item) in items.iter().enumerate() {
+                let elem_addr = elems_addr + index * elem_size;
+
+                write_value(env, mem, elem_addr, &item.value, elem_layout, elem_var)?;
+            }
+
+            let len = items.len();
+            write_usizes(env, mem, addr, &[elems_addr, len, len]);
+
+            Ok(())
+        }
+        (Content::Structure(FlatType::EmptyRecord), LayoutRepr::Struct { .. }) => match expr {
+            Expr::Record(fields) if fields.is_empty() => Ok(()),
+            _ => Err(mismatch("the empty record {}", expr)),
+        },
+        (Content::Structure(FlatType::Record(fields, _)), LayoutRepr::Struct { .. }) => {
+            let assigned = match expr {
+                Expr::Record(assigned) => assigned,
+                _ => return Err(mismatch("a record", expr)),
+            };
+
+            let arena = env.arena;
+            let subs = env.subs;
+
+            // the type checker stores record fields in alphabetical order
+            let alphabetical_fields: Vec<_> = fields
+                .sorted_iterator(subs, Variable::EMPTY_RECORD)
+                .map(|(label, field)| {
+                    let layout = env
+                        .layout_cache
+                        .from_var(arena, field.into_inner(), subs)
+                        .unwrap();
+
+                    (label, field.into_inner(), layout)
+                })
+                .collect_in(arena);
+
+            let expected = || {
+                let labels: std::vec::Vec<_> = alphabetical_fields
+                    .iter()
+                    .map(|(label, _, _)| label.as_str())
+                    .collect();
+
+                format!("a record with the fields {}", labels.join(", "))
+            };
+
+            let mut values = std::vec::Vec::with_capacity(alphabetical_fields.len());
+
+            for (label, _, _) in alphabetical_fields.iter() {
+                let value = assigned
+                    .iter()
+                    .filter_map(|loc| field_value(&loc.value))
+                    .find(|(name, _)| *name == label.as_str())
+                    .map(|(_, value)| value)
+                    .ok_or_else(|| mismatch(expected(), expr))?;
+
+                values.push((label.clone(), value));
+            }
+
+            if assigned.len() != alphabetical_fields.len() {
+                return Err(mismatch(expected(), expr));
+            }
+
+            // but the memory representation sorts first by size (and uses field name as a tie breaker)
+            let mut in_memory_fields = alphabetical_fields;
+            in_memory_fields.sort_by(|(label1, _, layout1), (label2, _, layout2)| {
+                cmp_fields(
+                    &env.layout_cache.interner,
+                    label1,
+                    *layout1,
+                    label2,
+                    *layout2,
+                    env.target_info,
+                )
+            });
+
+            let mut field_addr = addr;
+
+            for (label, field_var, field_layout) in in_memory_fields {
+                let (_, value) = values.iter().find(|(other, _)| *other == label).unwrap();
+
+                write_value(env, mem, field_addr, value, field_layout, field_var)?;
+
+                field_addr += env.layout_cache.interner.stack_size(field_layout) as usize;
+            }
+
+            Ok(())
+        }
+        (Content::Structure(FlatType::Tuple(..)), _) => {
+            Err(InputProblem::Unsupported("tuples".to_string()))
+        }
+        _ => Err(InputProblem::Unsupported(
+            "tags with payloads, or recursive types".to_string(),
+        )),
+    }
+}
+
+fn without_spaces<'a, 'b>(mut expr: &'b Expr<'a>) -> &'b Expr<'a> {
+    loop {
+        match expr {
+            Expr::SpaceBefore(inner, _)
+            | Expr::SpaceAfter(inner, _)
+            | Expr::ParensAround(inner) => expr = inner,
+            _ => return expr,
+        }
+    }
+}
+
+/// Bool and the number aliases decide how their values are written, so stop at those.
+fn without_aliases(subs: &Subs, mut var: Variable) -> Variable {
+    while let Content::Alias(name, _, real_var, _) = subs.get_content_without_compacting(var) {
+        if *name == Symbol::BOOL_BOOL || name.module_id() == ModuleId::NUM {
+            break;
+        }
+
+        var = *real_var;
+    }
+
+    var
+}
+
+fn mismatch(expected: impl Into<String>, found: &Expr) -> InputProblem {
+    InputProblem::Mismatch {
+        expected: expected.into(),
+        found: format!("{:?}", found),
+    }
+}
+
+fn field_value<'a, 'b>(field: &'b AssignedField<'a, Expr<'a>>) -> Option<(&'a str, &'b Expr<'a>)> {
+    match field {
+        AssignedField::RequiredValue(name, _, value) => Some((name.value, &value.value)),
+        AssignedField::SpaceBefore(inner, _) | AssignedField::SpaceAfter(inner, _) => {
+            field_value(inner)
+        }
+        _ => None,
+    }
+}
+
+fn tag_name<'a>(expr: &Expr<'a>) -> Option<&'a str> {
+    match expr {
+        Expr::Tag(name) => Some(*name),
+        Expr::Apply(tag, [], _) => match tag.value {
+            Expr::Tag(name) => Some(name),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The tag names of a union, if none of them have payloads
+fn payloadless_tags(subs: &Subs, content: &Content) -> Option<std::vec::Vec<TagName>> {
+    match content {
+        Content::Structure(FlatType::TagUnion(tags, _)) => tags
+            .unsorted_iterator(subs, TagExt::Any(Variable::EMPTY_TAG_UNION))
+            .map(|(name, payloads)| payloads.is_empty().then(|| name.clone()))
+            .collect(),
+        Content::Structure(FlatType::FunctionOrTagUnion(tags, _, _)) => {
+            Some(subs.get_subs_slice(*tags).to_vec())
+        }
+        _ => None,
+    }
+}
+
+fn bool_value(env: &Env, expr: &Expr, content: &Content) -> Result<bool, InputProblem> {
+    if let Content::Alias(Symbol::BOOL_BOOL, ..) = content {
+        return match expr {
+            Expr::Var {
+                module_name: "Bool",
+                ident: "true",
+            } => Ok(true),
+            Expr::Var {
+                module_name: "Bool",
+                ident: "false",
+            } => Ok(false),
+            _ => Err(mismatch("Bool.true or Bool.false", expr)),
+        };
+    }
+
+    let tags = payloadless_tags(env.subs, content)
+        .filter(|tags| tags.len() == 2)
+        .unwrap_or_else(|| unreachable!("Something had a Bool layout, but a {:?} type", content));
+
+    let mut names: std::vec::Vec<_> = tags.iter().map(|tag| tag.as_ident_str()).collect();
+    names.sort();
+
+    // The tag that sorts last is represented as true
+    match tag_name(expr).and_then(|name| names.iter().position(|other| other.as_str() == name)) {
+        Some(index) => Ok(index == 1),
+        None => Err(mismatch(format!("{} or {}", names[0], names[1]), expr)),
+    }
+}
+
+fn byte_value(env: &mut Env, expr: &Expr, content: &Content) -> Result<u8, InputProblem> {
+    let tags = payloadless_tags(env.subs, content)
+        .unwrap_or_else(|| unreachable!("Something had a U8 layout, but a {:?} type", content));
+
+    let union_variant = {
+        let mut layout_env = layout::Env::from_components(
+            &mut env.layout_cache,
+            env.subs,
+            env.arena,
+            env.target_info,
+        );
+        let tags = tags.into_iter().map(|name| (name, vec![])).collect();
+
+        union_sorted_tags_pub(&mut layout_env, tags, None)
+    };
+
+    let tag_names = match union_variant {
+        UnionVariant::ByteUnion(tag_names) => tag_names,
+        _ => unreachable!("invalid union variant for a Byte!"),
+    };
+
+    let expected = || {
+        let names: std::vec::Vec<_> = tag_names
+            .iter()
+            .map(|tag| tag.expect_tag_ref().as_ident_str().to_string())
+            .collect();
+
+        format!("one of {}", names.join(", "))
+    };
+
+    let name = tag_name(expr).ok_or_else(|| mismatch(expected(), expr))?;
+
+    tag_names
+        .iter()
+        .position(|tag| tag.expect_tag_ref().as_ident_str().as_str() == name)
+        .map(|index| index as u8)
+        .ok_or_else(|| mismatch(expected(), expr))
+}
+
+/// Numeric literals may end in a type suffix like `u8` or `dec`.
+fn number_without_suffix(string: &str) -> &str {
+    strip_suffix(string, &['u', 'i', 'f', 'd'])
+}
+
+fn strip_suffix<'b>(string: &'b str, suffix_starts: &[char]) -> &'b str {
+    match string.find(suffix_starts) {
+        Some(index) => &string[..index],
+        None => string,
+    }
+}
+
+/// The sign and magnitude of an integer literal
+fn int_value(expr: &Expr) -> Option<(bool, u128)> {
+    match expr {
+        Expr::Num(string) => {
+            let string = number_without_suffix(string);
+            let (is_negative, digits) = match string.strip_prefix('-') {
+                Some(digits) => (true, digits),
+                None => (false, string),
+            };
+
+            let digits: String = digits.chars().filter(|c| *c != '_').collect();
+
+            Some((is_negative, digits.parse().ok()?))
+        }
+        Expr::NonBase10Int {
+            string,
+            base,
+            is_negative,
+        } => {
+            let radix = match base {
+                Base::Hex => 16,
+                Base::Octal => 8,
+                Base::Binary => 2,
+                Base::Decimal => 10,
+            };
+
+            // Hex digits include `d` and `f`, so only integer suffixes can follow them.
+            let digits: String = strip_suffix(string, &['u', 'i'])
+                .chars()
+                .filter(|c| *c != '_')
+                .collect();
+
+            Some((*is_negative, u128::from_str_radix(&digits, radix).ok()?))
+        }
+        Expr::UnaryOp(inner, op) if op.value == UnaryOp::Negate => {
+            let (is_negative, magnitude) = int_value(without_spaces(&inner.value))?;
+
+            Some((!is_negative, magnitude))
+        }
+        _ => None,
+    }
+}
+
+/// Little-endian bytes for an integer of the given width, or None if it doesn't fit
+fn int_bytes(int_width: IntWidth, is_negative: bool, magnitude: u128) -> Option<std::vec::Vec<u8>> {
+    use IntWidth::*;
+
+    macro_rules! unsigned {
+        ($ty: ty) => {{
+            if is_negative && magnitude != 0 {
+                return None;
+            }
+
+            <$ty>::try_from(magnitude).ok()?.to_le_bytes().to_vec()
+        }};
+    }
+
+    macro_rules! signed {
+        ($ty: ty) => {{
+            let value = if is_negative {
+                0i128.checked_sub_unsigned(magnitude)?
+            } else {
+                i128::try_from(magnitude).ok()?
+            };
+
+            <$ty>::try_from(value).ok()?.to_le_bytes().to_vec()
+        }};
+    }
+
+    let bytes = match int_width {
+        U8 => unsigned!(u8),
+        U16 => unsigned!(u16),
+        U32 => unsigned!(u32),
+        U64 => unsigned!(u64),
+        U128 => unsigned!(u128),
+        I8 => signed!(i8),
+        I16 => signed!(i16),
+        I32 => signed!(i32),
+        I64 => signed!(i64),
+        I128 => signed!(i128),
+    };
+
+    Some(bytes)
+}
+
+fn float_value(expr: &Expr) -> Option<f64> {
+    match expr {
+        Expr::Num(string) | Expr::Float(string) => {
+            let digits: String = number_without_suffix(string)
+                .chars()
+                .filter(|c| *c != '_')
+                .collect();
+
+            digits.parse().ok()
+        }
+        Expr::UnaryOp(inner, op) if op.value == UnaryOp::Negate => {
+            float_value(without_spaces(&inner.value)).map(|value| -value)
+        }
+        _ => None,
+    }
+}
+
+fn str_value<'a>(env: &Env<'a, '_>, expr: &Expr<'a>) -> Result<&'a str, InputProblem> {
+    let segments: &[StrSegment] = match expr {
+        Expr::Str(StrLiteral::PlainLine(string)) => return Ok(string),
+        Expr::Str(StrLiteral::Line(segments)) => segments,
+        Expr::Str(StrLiteral::Block(lines)) => {
+            let segments: Vec<_> = lines
+                .iter()
+                .flat_map(|line| line.iter())
+                .copied()
+                .collect_in(env.arena);
+
+            segments.into_bump_slice()
+        }
+        _ => return Err(mismatch("a string", expr)),
+    };
+
+    let mut string = bumpalo::collections::String::new_in(env.arena);
+
+    for segment in segments {
+        match segment {
+            StrSegment::Plaintext(text) => string.push_str(text),
+            StrSegment::EscapedChar(escaped) => string.push(escaped.unescape()),
+            StrSegment::Unicode(code) => {
+                match u32::from_str_radix(code.value, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                {
+                    Some(c) => string.push(c),
+                    None => return Err(mismatch("a valid unicode escape", expr)),
+                }
+            }
+            StrSegment::Interpolated(_) => {
+                return Err(InputProblem::Unsupported(
+                    "string interpolation".to_string(),
+                ))
+            }
+        }
+    }
+
+    Ok(string.into_bump_str())
+}
+
+fn write_usizes<M: ReplAppMemoryMut>(env: &Env, mem: &mut M, addr: usize, values: &[usize]) {
+    let ptr_width = env.target_info.ptr_width() as usize;
+
+    for (index, value) in values.iter().enumerate() {
+        mem.write_bytes(addr + index * ptr_width, &value.to_le_bytes()[..ptr_width]);
+    }
+}
+
+/// Strings short enough to fit in the struct itself are stored there, with their length
+/// in the last byte and its highest bit set. Longer ones go on the heap like a list.
+fn write_str<M: ReplAppMemoryMut>(env: &Env, mem: &mut M, addr: usize, bytes: &[u8]) {
+    let struct_size = 3 * env.target_info.ptr_width() as usize;
+
+    if bytes.len() < struct_size {
+        let mut small = vec![0; struct_size];
+        small[..bytes.len()].copy_from_slice(bytes);
+        small[struct_size - 1] = bytes.len() as u8 | 0x80;
+
+        mem.write_bytes(addr, &small);
+    } else {
+        let heap_addr = mem.alloc_refcounted(bytes.len(), 1);
+        mem.write_bytes(heap_addr, bytes);
+
+        write_usizes(env, mem, addr, &[heap_addr, bytes.len(), bytes.len()]);
+    }
+}
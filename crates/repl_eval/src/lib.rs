@@ -5,6 +5,8 @@ use roc_target::TargetInfo;
 
 pub mod eval;
 pub mod gen;
+pub mod input;
+pub mod wasm_memory;
 
 pub trait ReplApp<'a> {
     type Memory: 'a + ReplAppMemory;
@@ -85,3 +87,12 @@ pub trait ReplAppMemory {
 
     fn deref_pointer_with_tag_id(&self, addr: usize) -> (u16, u64);
 }
+
+/// The reverse of [ReplAppMemory], for building values in the app's memory.
+pub trait ReplAppMemoryMut {
+    /// Allocate `size` bytes on the app's heap for a refcounted value, like the elements of a
+    /// list, with its refcount set to 1. Returns the address of the first byte after the refcount.
+    fn alloc_refcounted(&mut self, size: usize, alignment: u32) -> usize;
+
+    fn write_bytes(&mut self, addr: usize, bytes: &[u8]);
+}
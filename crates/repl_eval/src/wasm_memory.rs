@@ -0,0 +1,94 @@
+//! Reading the result of a Roc app that was compiled to Wasm, from a copy of the app's memory.
+//! This is shared by the web REPL and by the CLI REPL's Wasm backend.
+use std::mem::size_of;
+
+use crate::ReplAppMemory;
+
+/// A copy of the app's memory, made after running the main function
+/// The Wasm app ran in a separate address space from the compiler and the eval code.
+/// This means we can't simply dereference its pointers as if they were local, because
+/// an unrelated value may exist at the same-numbered address in our own address space!
+/// Instead we have dereferencing methods that index into the copied bytes.
+pub struct WasmMemory<'a> {
+    copied_bytes: &'a [u8],
+}
+
+impl<'a> WasmMemory<'a> {
+    pub fn new(copied_bytes: &'a [u8]) -> Self {
+        WasmMemory { copied_bytes }
+    }
+
+    pub fn copied_bytes(&self) -> &'a [u8] {
+        self.copied_bytes
+    }
+}
+
+macro_rules! deref_number {
+    ($name: ident, $t: ty) => {
+        fn $name(&self, address: usize) -> $t {
+            const N: usize = size_of::<$t>();
+            let mut array = [0; N];
+            array.copy_from_slice(&self.copied_bytes[address..][..N]);
+            <$t>::from_le_bytes(array)
+        }
+    };
+}
+
+impl<'a> ReplAppMemory for WasmMemory<'a> {
+    fn deref_bool(&self, address: usize) -> bool {
+        self.copied_bytes[address] != 0
+    }
+
+    deref_number!(deref_u8, u8);
+    deref_number!(deref_u16, u16);
+    deref_number!(deref_u32, u32);
+    deref_number!(deref_u64, u64);
+    deref_number!(deref_u128, u128);
+
+    // The app is always wasm32, but this code may be running on a 64-bit host (in the CLI REPL),
+    // so pointer-sized values must be read as 32 bits rather than the host's usize.
+    fn deref_usize(&self, address: usize) -> usize {
+        self.deref_u32(address) as usize
+    }
+
+    deref_number!(deref_i8, i8);
+    deref_number!(deref_i16, i16);
+    deref_number!(deref_i32, i32);
+    deref_number!(deref_i64, i64);
+    deref_number!(deref_i128, i128);
+
+    fn deref_isize(&self, address: usize) -> isize {
+        self.deref_i32(address) as isize
+    }
+
+    deref_number!(deref_f32, f32);
+    deref_number!(deref_f64, f64);
+
+    fn deref_str(&self, addr: usize) -> &str {
+        // We can't use RocStr, we need our own small/big string logic.
+        // The first field is *not* a pointer. We can calculate a pointer for it, but only for big strings.
+        // If changing this code, remember it also runs in wasm32, not just the app.
+        let last_byte = self.copied_bytes[addr + 4 + 4 + 3] as i8;
+        let is_small = last_byte < 0;
+
+        let str_bytes = if is_small {
+            let len = (last_byte & 0x7f) as usize;
+            &self.copied_bytes[addr..][..len]
+        } else {
+            let chars_index = self.deref_usize(addr);
+            let len = self.deref_usize(addr + 4);
+            &self.copied_bytes[chars_index..][..len]
+        };
+
+        unsafe { std::str::from_utf8_unchecked(str_bytes) }
+    }
+
+    fn deref_pointer_with_tag_id(&self, addr: usize) -> (u16, u64) {
+        let addr_with_id = self.deref_usize(addr);
+        let tag_id_mask = 0b11;
+
+        let tag_id = addr_with_id & tag_id_mask;
+        let data_addr = addr_with_id & !tag_id_mask;
+        (tag_id as _, data_addr as _)
+    }
+}
@@ -0,0 +1,273 @@
+//! Times top-level `bench`es, for `roc bench`.
+//!
+//! Each bench is compiled to a thunk that evaluates its body and throws the result away. After
+//! a warm-up run, the thunk is run in batches that take at least [MIN_SAMPLE_TIME] each, so the
+//! timer's resolution doesn't matter, and the time per run of each batch is one sample.
+use std::mem::MaybeUninit;
+use std::time::{Duration, Instant};
+
+use bumpalo::collections::Vec as BumpVec;
+use roc_collections::VecMap;
+use roc_error_macros::internal_error;
+use roc_gen_llvm::run_roc::RocCallResult;
+use roc_load::Expectations;
+use roc_module::symbol::ModuleId;
+use roc_mono::ir::CrashTag;
+use roc_parse::keyword;
+use roc_region::all::Region;
+
+use crate::run::ToplevelExpect;
+
+const MIN_SAMPLE_TIME: Duration = Duration::from_millis(1);
+/// Keep taking samples until this much time has passed...
+const TARGET_TIME: Duration = Duration::from_secs(1);
+/// ...and at least this many samples were taken
+const MIN_SAMPLES: usize = 10;
+
+type BenchFn = unsafe extern "C" fn(*mut RocCallResult<()>);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchResult {
+    pub label: String,
+    /// The timing, or the message the bench crashed with
+    pub timing: Result<Timing, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timing {
+    /// The median time of a single run, in nanoseconds
+    pub median_ns: f64,
+    /// The median absolute deviation of the samples from the median, in nanoseconds
+    pub spread_ns: f64,
+    pub samples: usize,
+}
+
+pub fn run_toplevel_benches(
+    lib: &libloading::Library,
+    expectations: &VecMap<ModuleId, Expectations>,
+    benches: BumpVec<'_, ToplevelExpect<'_>>,
+) -> Vec<BenchResult> {
+    benches
+        .into_iter()
+        .map(|bench| {
+            let module_id = bench.symbol.module_id();
+            let source = match expectations.get(&module_id) {
+                Some(data) => std::fs::read_to_string(&data.path).unwrap_or_default(),
+                None => String::new(),
+            };
+
+            BenchResult {
+                label: bench_label(&source, bench.region),
+                timing: time_bench(lib, bench.name),
+            }
+        })
+        .collect()
+}
+
+fn time_bench(lib: &libloading::Library, name: &str) -> Result<Timing, String> {
+    let bench: libloading::Symbol<BenchFn> = unsafe { lib.get(name.as_bytes()) }
+        .unwrap_or_else(|_| internal_error!("bench function `{}` was not compiled", name));
+
+    // warm up, and find out whether the bench crashes before timing anything
+    run_once(&bench)?;
+
+    // double the batch size until a batch takes long enough to time reliably
+    let mut batch_size: u64 = 1;
+
+    loop {
+        if run_batch(&bench, batch_size)? >= MIN_SAMPLE_TIME {
+            break;
+        }
+
+        batch_size *= 2;
+    }
+
+    let mut samples = Vec::with_capacity(MIN_SAMPLES);
+    let start = Instant::now();
+
+    while samples.len() < MIN_SAMPLES || start.elapsed() < TARGET_TIME {
+        let elapsed = run_batch(&bench, batch_size)?;
+
+        samples.push(elapsed.as_nanos() as f64 / batch_size as f64);
+    }
+
+    Ok(summarize(&mut samples))
+}
+
+fn run_batch(bench: &BenchFn, batch_size: u64) -> Result<Duration, String> {
+    let start = Instant::now();
+
+    for _ in 0..batch_size {
+        run_once(bench)?;
+    }
+
+    Ok(start.elapsed())
+}
+
+fn run_once(bench: &BenchFn) -> Result<(), String> {
+    let mut result = MaybeUninit::uninit();
+
+    let result: Result<(), (String, CrashTag)> = unsafe {
+        bench(result.as_mut_ptr());
+
+        result.assume_init().into()
+    };
+
+    result.map_err(|(message, _)| message)
+}
+
+fn summarize(samples: &mut [f64]) -> Timing {
+    let median_ns = median(samples);
+
+    let mut deviations: Vec<f64> = samples.iter().map(|s| (s - median_ns).abs()).collect();
+    let spread_ns = median(&mut deviations);
+
+    Timing {
+        median_ns,
+        spread_ns,
+        samples: samples.len(),
+    }
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    debug_assert!(!values.is_empty());
+
+    values.sort_by(|a, b| a.total_cmp(b));
+
+    let middle = values.len() / 2;
+
+    if values.len() % 2 == 0 {
+        (values[middle - 1] + values[middle]) / 2.0
+    } else {
+        values[middle]
+    }
+}
+
+/// The comment directly above the bench, or if there isn't one, the first line of its body.
+/// The region starts at the comments before the `bench` keyword.
+pub fn bench_label(source: &str, region: Region) -> String {
+    let start = region.start().offset as usize;
+    let end = region.end().offset as usize;
+    let text = source.get(start..end).unwrap_or_default();
+
+    let mut comment = None;
+
+    for line in text.lines().map(str::trim) {
+        if let Some(rest) = line.strip_prefix(keyword::BENCH) {
+            return match comment {
+                Some(comment) => comment,
+                None => rest.trim().to_string(),
+            };
+        }
+
+        if line.starts_with('#') {
+            comment = Some(line.trim_start_matches('#').trim().to_string());
+        }
+    }
+
+    comment.unwrap_or_default()
+}
+
+/// Parse a baseline saved by `roc bench --save-baseline`. Each line is the median in
+/// nanoseconds, then a space, then the label.
+pub fn parse_baseline(contents: &str) -> VecMap<String, f64> {
+    let mut baseline = VecMap::default();
+
+    for line in contents.lines() {
+        if let Some((median_ns, label)) = line.split_once(' ') {
+            if let Ok(median_ns) = median_ns.parse() {
+                baseline.insert(label.to_string(), median_ns);
+            }
+        }
+    }
+
+    baseline
+}
+
+pub fn format_baseline(results: &[BenchResult]) -> String {
+    let mut buf = String::new();
+
+    for result in results {
+        if let Ok(timing) = &result.timing {
+            buf.push_str(&format!("{} {}\n", timing.median_ns, result.label));
+        }
+    }
+
+    buf
+}
+
+/// How much slower (positive) or faster (negative) `median_ns` is than `baseline_ns`, in percent
+pub fn change_from_baseline(median_ns: f64, baseline_ns: f64) -> f64 {
+    (median_ns - baseline_ns) / baseline_ns * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roc_region::all::Position;
+
+    fn region_of(source: &str) -> Region {
+        Region::new(Position::new(0), Position::new(source.len() as u32))
+    }
+
+    #[test]
+    fn median_of_odd_and_even() {
+        assert_eq!(median(&mut [3.0, 1.0, 2.0]), 2.0);
+        assert_eq!(median(&mut [4.0, 1.0, 3.0, 2.0]), 2.5);
+    }
+
+    #[test]
+    fn spread_is_median_absolute_deviation() {
+        let timing = summarize(&mut [10.0, 11.0, 9.0, 30.0, 10.0]);
+
+        assert_eq!(timing.median_ns, 10.0);
+        assert_eq!(timing.spread_ns, 1.0);
+        assert_eq!(timing.samples, 5);
+    }
+
+    #[test]
+    fn label_from_comment() {
+        let source = "\n# sort a big list\nbench List.sortAsc bigList";
+
+        assert_eq!(bench_label(source, region_of(source)), "sort a big list");
+    }
+
+    #[test]
+    fn label_from_body() {
+        let source = "\nbench List.sortAsc bigList";
+
+        assert_eq!(
+            bench_label(source, region_of(source)),
+            "List.sortAsc bigList"
+        );
+    }
+
+    #[test]
+    fn baseline_roundtrip() {
+        let results = [
+            BenchResult {
+                label: "sort a big list".to_string(),
+                timing: Ok(Timing {
+                    median_ns: 1500.5,
+                    spread_ns: 3.0,
+                    samples: 10,
+                }),
+            },
+            BenchResult {
+                label: "crashes".to_string(),
+                timing: Err("oops".to_string()),
+            },
+        ];
+
+        let baseline = parse_baseline(&format_baseline(&results));
+
+        assert_eq!(baseline.len(), 1);
+        assert_eq!(baseline.get(&"sort a big list".to_string()), Some(&1500.5));
+    }
+
+    #[test]
+    fn change_in_percent() {
+        assert_eq!(change_from_baseline(110.0, 100.0), 10.0);
+        assert_eq!(change_from_baseline(50.0, 100.0), -50.0);
+    }
+}
@@ -0,0 +1,203 @@
+//! Reads the counters of `roc test --coverage`, and reports them in the lcov format.
+//!
+//! Every counted function body becomes an `FN`/`FNDA` pair and a `DA` line, and every counted
+//! `if` or `when` branch becomes a `BRDA` line and a `DA` line. Functions that were never used
+//! are not specialized, so they have no counter, and aren't in the report.
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use roc_collections::MutMap;
+use roc_module::symbol::ModuleId;
+use roc_mono::coverage::{CoverageKind, CoveragePoint, COVERAGE_COUNTS};
+use roc_region::all::LineInfo;
+
+/// The counts in the dylib's counter array, in the same order as `points`
+pub fn read_counts(lib: &libloading::Library, points: &[CoveragePoint]) -> Vec<u64> {
+    if points.is_empty() {
+        return Vec::new();
+    }
+
+    unsafe {
+        match lib.get::<*const u64>(COVERAGE_COUNTS.as_bytes()) {
+            Ok(counts) => std::slice::from_raw_parts(*counts, points.len()).to_vec(),
+            Err(_) => vec![0; points.len()],
+        }
+    }
+}
+
+pub fn lcov(
+    points: &[CoveragePoint],
+    counts: &[u64],
+    sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+) -> String {
+    debug_assert_eq!(points.len(), counts.len());
+
+    // group the points by module, and within a module by where they are
+    let mut by_module: MutMap<ModuleId, Vec<(CoveragePoint, u64)>> = MutMap::default();
+
+    for (point, count) in points.iter().zip(counts) {
+        by_module
+            .entry(point.module_id)
+            .or_default()
+            .push((*point, *count));
+    }
+
+    let mut modules: Vec<_> = by_module
+        .into_iter()
+        .filter_map(|(module_id, points)| {
+            sources
+                .get(&module_id)
+                .map(|(path, source)| (path, source, points))
+        })
+        .collect();
+
+    modules.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut buf = String::new();
+
+    for (path, source, mut points) in modules {
+        points.sort_by_key(|(point, _)| (point.offset, point.kind == CoverageKind::Branch));
+
+        let line_info = LineInfo::new(source);
+        let line_of = |point: &CoveragePoint| {
+            line_info
+                .convert_offset(point.offset)
+                .line
+                .saturating_add(1)
+        };
+
+        writeln!(buf, "SF:{}", path.display()).unwrap();
+
+        let functions: Vec<_> = points
+            .iter()
+            .filter(|(point, _)| point.kind == CoverageKind::Function)
+            .map(|(point, count)| (line_of(point), *count))
+            .collect();
+
+        for (line, _) in functions.iter() {
+            writeln!(buf, "FN:{line},line{line}").unwrap();
+        }
+
+        for (line, count) in functions.iter() {
+            writeln!(buf, "FNDA:{count},line{line}").unwrap();
+        }
+
+        writeln!(buf, "FNF:{}", functions.len()).unwrap();
+        writeln!(
+            buf,
+            "FNH:{}",
+            functions.iter().filter(|(_, count)| *count > 0).count()
+        )
+        .unwrap();
+
+        let branches: Vec<_> = points
+            .iter()
+            .filter(|(point, _)| point.kind == CoverageKind::Branch)
+            .map(|(point, count)| (line_of(point), *count))
+            .collect();
+
+        for (index, (line, count)) in branches.iter().enumerate() {
+            writeln!(buf, "BRDA:{line},0,{index},{count}").unwrap();
+        }
+
+        writeln!(buf, "BRF:{}", branches.len()).unwrap();
+        writeln!(
+            buf,
+            "BRH:{}",
+            branches.iter().filter(|(_, count)| *count > 0).count()
+        )
+        .unwrap();
+
+        // a line can have several counted regions; it ran as often as the busiest one
+        let mut lines: Vec<(u32, u64)> = Vec::new();
+
+        for (point, count) in points.iter() {
+            let line = line_of(point);
+
+            match lines.last_mut() {
+                Some((last_line, last_count)) if *last_line == line => {
+                    *last_count = (*last_count).max(*count);
+                }
+                _ => lines.push((line, *count)),
+            }
+        }
+
+        for (line, count) in lines.iter() {
+            writeln!(buf, "DA:{line},{count}").unwrap();
+        }
+
+        writeln!(buf, "LF:{}", lines.len()).unwrap();
+        writeln!(
+            buf,
+            "LH:{}",
+            lines.iter().filter(|(_, count)| *count > 0).count()
+        )
+        .unwrap();
+
+        buf.push_str("end_of_record\n");
+    }
+
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(offset: u32, kind: CoverageKind) -> CoveragePoint {
+        CoveragePoint {
+            module_id: ModuleId::ATTR,
+            offset,
+            kind,
+        }
+    }
+
+    fn sources(source: &str) -> MutMap<ModuleId, (PathBuf, Box<str>)> {
+        let mut sources = MutMap::default();
+        sources.insert(ModuleId::ATTR, (PathBuf::from("main.roc"), source.into()));
+
+        sources
+    }
+
+    #[test]
+    fn functions_and_branches() {
+        let source = "f = \\x ->\n    if x then\n        1\n    else\n        2\n";
+        let points = [
+            point(source.find("if").unwrap() as u32, CoverageKind::Function),
+            point(source.find('1').unwrap() as u32, CoverageKind::Branch),
+            point(source.find('2').unwrap() as u32, CoverageKind::Branch),
+        ];
+
+        let report = lcov(&points, &[3, 3, 0], &sources(source));
+
+        assert_eq!(
+            report,
+            "SF:main.roc\n\
+             FN:2,line2\n\
+             FNDA:3,line2\n\
+             FNF:1\n\
+             FNH:1\n\
+             BRDA:3,0,0,3\n\
+             BRDA:5,0,1,0\n\
+             BRF:2\n\
+             BRH:1\n\
+             DA:2,3\n\
+             DA:3,3\n\
+             DA:5,0\n\
+             LF:3\n\
+             LH:2\n\
+             end_of_record\n"
+        );
+    }
+
+    #[test]
+    fn modules_without_source_are_skipped() {
+        let points = [CoveragePoint {
+            module_id: ModuleId::NUM,
+            offset: 0,
+            kind: CoverageKind::Function,
+        }];
+
+        assert_eq!(lcov(&points, &[1], &sources("")), "");
+    }
+}
@@ -15,6 +15,10 @@ use {
 #[cfg(not(windows))]
 mod app;
 #[cfg(not(windows))]
+pub mod bench;
+#[cfg(not(windows))]
+pub mod coverage;
+#[cfg(not(windows))]
 pub mod run;
 
 #[cfg(not(windows))]
@@ -104,6 +108,10 @@ mod test {
     use super::*;
 
     fn run_expect_test(source: &str, expected: &str) {
+        run_expect_test_help(source, expected, false)
+    }
+
+    fn run_expect_test_help(source: &str, expected: &str, arena_allocation: bool) {
         let arena = bumpalo::Bump::new();
         let arena = &arena;
 
@@ -125,6 +133,12 @@ mod test {
             palette: DEFAULT_PALETTE,
             threading: Threading::Single,
             exec_mode: ExecutionMode::Test,
+            max_specializations: None,
+            coverage: false,
+            fold_constants: false,
+            hoist_loop_invariants: false,
+            strip_expects: false,
+            cancelled: None,
         };
         let loaded = match roc_load::load_and_monomorphize_from_str(
             arena,
@@ -143,6 +157,11 @@ mod test {
 
         let mut loaded = loaded;
         let mut expectations = std::mem::take(&mut loaded.expectations);
+
+        if arena_allocation {
+            roc_mono::arena_allocation::skip_recursive_decrements(arena, &mut loaded.procedures);
+        }
+
         let loaded = loaded;
 
         let interns = loaded.interns.clone();
@@ -153,6 +172,7 @@ mod test {
             loaded,
             opt_level,
             LlvmBackendMode::CliTest,
+            arena_allocation,
         )
         .unwrap();
 
@@ -734,6 +754,50 @@ mod test {
         );
     }
 
+    #[test]
+    fn linked_list_with_arena_allocation() {
+        run_expect_test_help(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main = 0
+
+                ConsList a : [ Nil, Cons a (ConsList a) ]
+
+                cons = \list, x -> Cons x list
+
+                sum : ConsList I64 -> I64
+                sum = \list ->
+                    when list is
+                        Nil -> 0
+                        Cons x rest -> x + sum rest
+
+                expect
+                    total = List.range { start: At 0, end: Before 1000 } |> List.walk Nil cons |> sum
+
+                    total == 0
+                "#
+            ),
+            indoc!(
+                r#"
+                This expectation failed:
+
+                15│>  expect
+                16│>      total = List.range { start: At 0, end: Before 1000 } |> List.walk Nil cons |> sum
+                17│>
+                18│>      total == 0
+
+                When it failed, these variables had these values:
+
+                total : I64
+                total = 499500
+                "#
+            ),
+            true,
+        );
+    }
+
     #[test]
     fn nullable_tree() {
         run_expect_test(
@@ -14,7 +14,10 @@ use roc_can::expr::ExpectLookup;
 use roc_collections::{MutSet, VecMap};
 use roc_error_macros::internal_error;
 use roc_gen_llvm::{
-    llvm::{build::LlvmBackendMode, externs::add_default_roc_externs},
+    llvm::{
+        build::LlvmBackendMode,
+        externs::{add_coverage_counters, add_default_roc_externs},
+    },
     run_roc::RocCallResult,
     run_roc_dylib,
 };
@@ -696,6 +699,7 @@ pub struct ToplevelExpect<'a> {
 pub struct ExpectFunctions<'a> {
     pub pure: BumpVec<'a, ToplevelExpect<'a>>,
     pub fx: BumpVec<'a, ToplevelExpect<'a>>,
+    pub benches: BumpVec<'a, ToplevelExpect<'a>>,
 }
 
 pub fn expect_mono_module_to_dylib<'a>(
@@ -704,6 +708,7 @@ pub fn expect_mono_module_to_dylib<'a>(
     loaded: MonomorphizedModule<'a>,
     opt_level: OptLevel,
     mode: LlvmBackendMode,
+    arena_allocation: bool,
 ) -> Result<
     (
         libloading::Library,
@@ -715,13 +720,24 @@ pub fn expect_mono_module_to_dylib<'a>(
     let target_info = TargetInfo::from(&target);
 
     let MonomorphizedModule {
+        module_id,
         toplevel_expects,
         procedures,
-        interns,
+        mut interns,
         mut layout_interner,
+        coverage_points,
         ..
     } = loaded;
 
+    let procedures = roc_gen_llvm::llvm::build::expand_refcounting_procs(
+        arena,
+        target_info,
+        module_id,
+        &mut interns,
+        &mut layout_interner,
+        procedures,
+    );
+
     let context = Context::create();
     let builder = context.create_builder();
     let module = arena.alloc(roc_gen_llvm::llvm::build::module_from_builtins(
@@ -747,17 +763,24 @@ pub fn expect_mono_module_to_dylib<'a>(
         mode,
         // important! we don't want any procedures to get the C calling convention
         exposed_to_host: MutSet::default(),
+        heap_profile: false,
+        stack_limit_checks: false,
+        arena_allocation,
+        profile_hints: None,
+        func_spec_numbers: Default::default(),
     };
 
     // Add roc_alloc, roc_realloc, and roc_dealloc, since the repl has no
     // platform to provide them.
     add_default_roc_externs(&env);
 
-    let capacity = toplevel_expects.pure.len() + toplevel_expects.fx.len();
+    let capacity =
+        toplevel_expects.pure.len() + toplevel_expects.fx.len() + toplevel_expects.benches.len();
     let mut expect_symbols = BumpVec::with_capacity_in(capacity, env.arena);
 
     expect_symbols.extend(toplevel_expects.pure.keys().copied());
     expect_symbols.extend(toplevel_expects.fx.keys().copied());
+    expect_symbols.extend(toplevel_expects.benches.keys().copied());
 
     let expect_names = roc_gen_llvm::llvm::build::build_procedures_expose_expects(
         &env,
@@ -767,6 +790,27 @@ pub fn expect_mono_module_to_dylib<'a>(
         procedures,
     );
 
+    if !coverage_points.is_empty() {
+        add_coverage_counters(&env, coverage_points.len());
+    }
+
+    let benches = bumpalo::collections::Vec::from_iter_in(
+        toplevel_expects
+            .benches
+            .into_iter()
+            .zip(
+                expect_names
+                    .iter()
+                    .skip(toplevel_expects.pure.len() + toplevel_expects.fx.len()),
+            )
+            .map(|((symbol, region), name)| ToplevelExpect {
+                symbol,
+                region,
+                name,
+            }),
+        env.arena,
+    );
+
     let expects_fx = bumpalo::collections::Vec::from_iter_in(
         toplevel_expects
             .fx
@@ -796,6 +840,7 @@ pub fn expect_mono_module_to_dylib<'a>(
     let expects = ExpectFunctions {
         pure: expects_pure,
         fx: expects_fx,
+        benches,
     };
 
     env.dibuilder.finalize();
@@ -3,7 +3,8 @@ use std::io::Write;
 use std::path::PathBuf;
 use std::process::{Command, ExitStatus, Stdio};
 
-use roc_repl_cli::{SHORT_INSTRUCTIONS, WELCOME_MESSAGE};
+use roc_repl_cli::{welcome_message, SHORT_INSTRUCTIONS};
+use roc_reporting::report::Palette;
 use roc_test_utils::assert_multiline_str_eq;
 
 const ERROR_MESSAGE_START: char = '─';
@@ -75,7 +76,12 @@ pub fn repl_eval(input: &str) -> Out {
 
     // Remove the initial instructions from the output.
 
-    let expected_instructions = format!("{}{}", WELCOME_MESSAGE, SHORT_INSTRUCTIONS);
+    // The repl picks its colors from the same environment we're running in.
+    let expected_instructions = format!(
+        "{}{}",
+        welcome_message(&Palette::from_env()),
+        SHORT_INSTRUCTIONS
+    );
     let stdout = String::from_utf8(output.stdout).unwrap();
 
     assert!(
@@ -1,5 +1,5 @@
 use indoc::indoc;
-use roc_repl_cli::repl_state::{is_incomplete, ReplState, TIPS};
+use roc_repl_cli::repl_state::{is_incomplete, ReplBackend, ReplState};
 
 // These are tests of the REPL state machine. They work without actually
 // running the CLI, and without using rustyline, and instead verify
@@ -10,6 +10,23 @@ fn one_plus_one() {
     complete("1 + 1", &mut ReplState::new(), Ok(("2 : Num *", "val1")));
 }
 
+#[test]
+fn wasm_backend() {
+    let mut state = ReplState::new().with_backend(ReplBackend::Wasm);
+
+    complete("x = 5", &mut state, Ok(("5 : Num *", "x")));
+    complete(
+        "Str.concat \"Hello, \" \"World!\"",
+        &mut state,
+        Ok(("\"Hello, World!\" : Str", "val1")),
+    );
+    complete(
+        "List.map [1, 2, 3] (\\n -> n + x)",
+        &mut state,
+        Ok(("[6, 7, 8] : List (Num *)", "val2")),
+    );
+}
+
 #[test]
 fn generated_expr_names() {
     let mut state = ReplState::new();
@@ -30,6 +47,30 @@ fn persisted_defs() {
     complete("val1 + x + y", &mut state, Ok(("15 : Num *", "val2")));
 }
 
+#[test]
+fn reset_forgets_defs() {
+    let mut state = ReplState::new();
+
+    complete("x = 5", &mut state, Ok(("5 : Num *", "x")));
+    complete("7 - 3", &mut state, Ok(("4 : Num *", "val1")));
+
+    assert_eq!(state.step(":reset", None), Ok(String::new()));
+
+    // Auto-generated names start over after a reset
+    complete("2 * 3", &mut state, Ok(("6 : Num *", "val1")));
+}
+
+#[test]
+fn type_command() {
+    let mut state = ReplState::new();
+
+    complete("x = 5", &mut state, Ok(("5 : Num *", "x")));
+    complete(":type x + 1", &mut state, Ok(("x + 1 : Num *", "")));
+
+    // :type doesn't name its expression, so this is still val1
+    complete("1 + 1", &mut state, Ok(("2 : Num *", "val1")));
+}
+
 #[test]
 fn annotated_body() {
     let mut input = "t : [A, B, C]".to_string();
@@ -66,7 +107,7 @@ fn exhaustiveness_problem() {
 
         const EXPECTED_ERROR: &str = indoc!(
             r#"
-            ── UNSAFE PATTERN ──────────────────────────────────────────────────────────────
+            ── UNSAFE PATTERN [E0401] ──────────────────────────────────────────────────────
 
             This when does not cover all the possibilities:
 
@@ -88,7 +129,10 @@ fn exhaustiveness_problem() {
 #[test]
 fn tips() {
     assert!(!is_incomplete(""));
-    assert_eq!(ReplState::new().step("", None), Ok(TIPS.to_string()));
+    let mut state = ReplState::new();
+    let expected = roc_repl_cli::repl_state::tips(state.palette());
+
+    assert_eq!(state.step("", None), Ok(expected));
 }
 
 #[test]
@@ -101,6 +145,22 @@ fn standalone_annotation() {
     assert_eq!(state.step(&input, None), Ok(String::new()));
 }
 
+#[test]
+fn bench_is_ignored() {
+    let mut state = ReplState::new();
+
+    complete("x = 5", &mut state, Ok(("5 : Num *", "x")));
+
+    error(
+        "bench x + 1",
+        &mut state,
+        "The repl doesn't run benchmarks. Put the `bench` at the top level of a module and use `roc bench` instead.".to_string(),
+    );
+
+    // The bench didn't get a name or become a def
+    complete("x + 1", &mut state, Ok(("6 : Num *", "val1")));
+}
+
 /// validate and step the given input, then check the Result vs the output
 /// with ANSI escape codes stripped.
 fn complete(input: &str, state: &mut ReplState, expected_step_result: Result<(&str, &str), i32>) {
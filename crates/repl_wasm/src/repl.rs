@@ -8,7 +8,8 @@ use roc_parse::ast::Expr;
 use roc_repl_eval::{
     eval::jit_to_ast,
     gen::{compile_to_mono, format_answer},
-    ReplApp, ReplAppMemory,
+    wasm_memory::WasmMemory,
+    ReplApp,
 };
 use roc_reporting::report::DEFAULT_PALETTE_HTML;
 use roc_target::TargetInfo;
@@ -22,77 +23,6 @@ pub struct WasmReplApp<'a> {
     arena: &'a Bump,
 }
 
-/// A copy of the app's memory, made after running the main function
-/// The Wasm app ran in a separate address space from the compiler and the eval code.
-/// This means we can't simply dereference its pointers as if they were local, because
-/// an unrelated value may exist at the same-numbered address in our own address space!
-/// Instead we have dereferencing methods that index into the copied bytes.
-pub struct WasmMemory<'a> {
-    copied_bytes: &'a [u8],
-}
-
-macro_rules! deref_number {
-    ($name: ident, $t: ty) => {
-        fn $name(&self, address: usize) -> $t {
-            const N: usize = size_of::<$t>();
-            let mut array = [0; N];
-            array.copy_from_slice(&self.copied_bytes[address..][..N]);
-            <$t>::from_le_bytes(array)
-        }
-    };
-}
-
-impl<'a> ReplAppMemory for WasmMemory<'a> {
-    fn deref_bool(&self, address: usize) -> bool {
-        self.copied_bytes[address] != 0
-    }
-
-    deref_number!(deref_u8, u8);
-    deref_number!(deref_u16, u16);
-    deref_number!(deref_u32, u32);
-    deref_number!(deref_u64, u64);
-    deref_number!(deref_u128, u128);
-    deref_number!(deref_usize, usize);
-
-    deref_number!(deref_i8, i8);
-    deref_number!(deref_i16, i16);
-    deref_number!(deref_i32, i32);
-    deref_number!(deref_i64, i64);
-    deref_number!(deref_i128, i128);
-    deref_number!(deref_isize, isize);
-
-    deref_number!(deref_f32, f32);
-    deref_number!(deref_f64, f64);
-
-    fn deref_str(&self, addr: usize) -> &str {
-        // We can't use RocStr, we need our own small/big string logic.
-        // The first field is *not* a pointer. We can calculate a pointer for it, but only for big strings.
-        // If changing this code, remember it also runs in wasm32, not just the app.
-        let last_byte = self.copied_bytes[addr + 4 + 4 + 3] as i8;
-        let is_small = last_byte < 0;
-
-        let str_bytes = if is_small {
-            let len = (last_byte & 0x7f) as usize;
-            &self.copied_bytes[addr..][..len]
-        } else {
-            let chars_index = self.deref_usize(addr);
-            let len = self.deref_usize(addr + 4);
-            &self.copied_bytes[chars_index..][..len]
-        };
-
-        unsafe { std::str::from_utf8_unchecked(str_bytes) }
-    }
-
-    fn deref_pointer_with_tag_id(&self, addr: usize) -> (u16, u64) {
-        let addr_with_id = self.deref_usize(addr);
-        let tag_id_mask = 0b11;
-
-        let tag_id = addr_with_id & tag_id_mask;
-        let data_addr = addr_with_id & !tag_id_mask;
-        (tag_id as _, data_addr as _)
-    }
-}
-
 impl<'a> WasmReplApp<'a> {
     /// Allocate a buffer to copy the app memory into
     /// Buffer is aligned to 64 bits to preserve the original alignment of all Wasm numbers
@@ -134,7 +64,7 @@ impl<'a> ReplApp<'a> for WasmReplApp<'a> {
             ptr.read()
         };
 
-        let mem = self.arena.alloc(WasmMemory { copied_bytes });
+        let mem = self.arena.alloc(WasmMemory::new(copied_bytes));
 
         transform(mem, result)
     }
@@ -158,7 +88,7 @@ impl<'a> ReplApp<'a> for WasmReplApp<'a> {
         let copied_bytes: &mut [u8] = self.allocate_buffer(app_final_memory_size);
 
         let app_result_addr = js_get_result_and_memory(copied_bytes.as_mut_ptr());
-        let mem = self.arena.alloc(WasmMemory { copied_bytes });
+        let mem = self.arena.alloc(WasmMemory::new(copied_bytes));
 
         transform(mem, app_result_addr)
     }
@@ -241,6 +171,8 @@ pub async fn entrypoint_from_js(src: String) -> Result<String, String> {
             arena,
             module_id,
             stack_bytes: roc_gen_wasm::Env::DEFAULT_STACK_BYTES,
+            features: roc_gen_wasm::WasmFeatures::default(),
+            wasi_hooks: false,
             exposed_to_host: exposed_to_host
                 .top_level_values
                 .keys()
@@ -1,15 +1,28 @@
 use std::path::PathBuf;
 
+use crate::report::{Palette, RenderTarget, Report, ANSI_STYLE_CODES, NO_COLOR_STYLE_CODES};
 use roc_collections::MutMap;
 use roc_module::symbol::{Interns, ModuleId};
+use roc_problem::lint::SeverityConfig;
 use roc_region::all::LineInfo;
 use roc_solve_problem::TypeError;
 
-#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Problems {
     pub fatally_errored: bool,
     pub errors: usize,
     pub warnings: usize,
+    /// Error and warning counts for each module that had any, sorted by path.
+    pub by_module: Vec<ModuleProblems>,
+    /// The rendered reports to show: the errors, or the warnings if there are no errors.
+    pub reports: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ModuleProblems {
+    pub path: PathBuf,
+    pub errors: usize,
+    pub warnings: usize,
 }
 
 impl Problems {
@@ -23,25 +36,30 @@ impl Problems {
     }
 
     pub fn print_to_stdout(&self, total_time: std::time::Duration) {
-        const GREEN: usize = 32;
-        const YELLOW: usize = 33;
+        let codes = if Palette::from_env().is_colored() {
+            ANSI_STYLE_CODES
+        } else {
+            NO_COLOR_STYLE_CODES
+        };
 
         print!(
-            "\x1B[{}m{}\x1B[39m {} and \x1B[{}m{}\x1B[39m {} found in {} ms",
+            "{}{}{} {} and {}{}{} {} found in {} ms",
             match self.errors {
-                0 => GREEN,
-                _ => YELLOW,
+                0 => codes.green,
+                _ => codes.yellow,
             },
             self.errors,
+            codes.color_reset,
             match self.errors {
                 1 => "error",
                 _ => "errors",
             },
             match self.warnings {
-                0 => GREEN,
-                _ => YELLOW,
+                0 => codes.green,
+                _ => codes.yellow,
             },
             self.warnings,
+            codes.color_reset,
             match self.warnings {
                 1 => "warning",
                 _ => "warnings",
@@ -49,26 +67,93 @@ impl Problems {
             total_time.as_millis(),
         );
     }
+
+    /// Print the reports, with a horizontal rule after them if there are any.
+    pub fn print_reports_to_stdout(&self, palette: &Palette) {
+        for report in self.reports.iter() {
+            println!("\n{}\n", report);
+        }
+
+        // The horizontal rule is nice when running the program right after
+        // compiling it, as it lets you clearly see where the compiler
+        // errors/warnings end and the program output begins.
+        // It also clears any ANSI escape codes (e.g. colors) we've used.
+        if !self.reports.is_empty() {
+            println!("{}{}\n", Report::horizontal_rule(palette), palette.reset);
+        }
+    }
+
+    /// Print one line per module that had problems, e.g. `    Foo.roc: 1 error, 2 warnings`
+    pub fn print_by_module_to_stdout(&self) {
+        for module in self.by_module.iter() {
+            println!(
+                "    {}: {} {}, {} {}",
+                module.path.display(),
+                module.errors,
+                match module.errors {
+                    1 => "error",
+                    _ => "errors",
+                },
+                module.warnings,
+                match module.warnings {
+                    1 => "warning",
+                    _ => "warnings",
+                },
+            );
+        }
+    }
 }
 
+/// Render the problems, and print them to stdout
 pub fn report_problems(
     total_problems: usize,
     sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
     interns: &Interns,
     can_problems: &mut MutMap<ModuleId, Vec<roc_problem::can::Problem>>,
     type_problems: &mut MutMap<ModuleId, Vec<TypeError>>,
+    severity_config: &SeverityConfig,
+    palette: &Palette,
+) -> Problems {
+    let problems = render_problems(
+        total_problems,
+        sources,
+        interns,
+        can_problems,
+        type_problems,
+        severity_config,
+        RenderTarget::ColorTerminal,
+        palette,
+    );
+
+    problems.print_reports_to_stdout(palette);
+
+    problems
+}
+
+/// Render the problems into [Problems::reports], without printing them
+pub fn render_problems(
+    total_problems: usize,
+    sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+    interns: &Interns,
+    can_problems: &mut MutMap<ModuleId, Vec<roc_problem::can::Problem>>,
+    type_problems: &mut MutMap<ModuleId, Vec<TypeError>>,
+    severity_config: &SeverityConfig,
+    render: RenderTarget,
+    palette: &Palette,
 ) -> Problems {
-    use crate::report::{can_problem, type_problem, Report, RocDocAllocator, DEFAULT_PALETTE};
+    use crate::report::{can_problem, type_problem, RocDocAllocator};
     use roc_problem::Severity::*;
-    let palette = DEFAULT_PALETTE;
 
     // This will often over-allocate total memory, but it means we definitely
     // never need to re-allocate either the warnings or the errors vec!
     let mut warnings = Vec::with_capacity(total_problems);
     let mut errors = Vec::with_capacity(total_problems);
     let mut fatally_errored = false;
+    let mut by_module = Vec::new();
 
     for (home, (module_path, src)) in sources.iter() {
+        let errors_before = errors.len();
+        let warnings_before = warnings.len();
         let mut src_lines: Vec<&str> = Vec::new();
 
         src_lines.extend(src.split('\n'));
@@ -81,11 +166,15 @@ pub fn report_problems(
         let problems = can_problems.remove(home).unwrap_or_default();
 
         for problem in problems.into_iter() {
+            let severity = match severity_config.severity(problem.severity(), problem.lint()) {
+                Some(severity) => severity,
+                None => continue,
+            };
+
             let report = can_problem(&alloc, &lines, module_path.clone(), problem);
-            let severity = report.severity;
             let mut buf = String::new();
 
-            report.render_color_terminal(&mut buf, &alloc, &palette);
+            report.render(render, &mut buf, &alloc, palette);
 
             match severity {
                 Warning => {
@@ -104,11 +193,15 @@ pub fn report_problems(
         let problems = type_problems.remove(home).unwrap_or_default();
 
         for problem in problems {
+            let severity = match severity_config.severity(problem.severity(), problem.lint()) {
+                Some(severity) => severity,
+                None => continue,
+            };
+
             if let Some(report) = type_problem(&alloc, &lines, module_path.clone(), problem) {
-                let severity = report.severity;
                 let mut buf = String::new();
 
-                report.render_color_terminal(&mut buf, &alloc, &palette);
+                report.render(render, &mut buf, &alloc, palette);
 
                 match severity {
                     Warning => {
@@ -124,38 +217,32 @@ pub fn report_problems(
                 }
             }
         }
-    }
-
-    let problems_reported;
 
-    // Only print warnings if there are no errors
-    if errors.is_empty() {
-        problems_reported = warnings.len();
-
-        for warning in warnings.iter() {
-            println!("\n{}\n", warning);
-        }
-    } else {
-        problems_reported = errors.len();
+        let module_errors = errors.len() - errors_before;
+        let module_warnings = warnings.len() - warnings_before;
 
-        for error in errors.iter() {
-            println!("\n{}\n", error);
+        if module_errors + module_warnings > 0 {
+            by_module.push(ModuleProblems {
+                path: module_path.clone(),
+                errors: module_errors,
+                warnings: module_warnings,
+            });
         }
     }
 
-    // If we printed any problems, print a horizontal rule at the end,
-    // and then clear any ANSI escape codes (e.g. colors) we've used.
-    //
-    // The horizontal rule is nice when running the program right after
-    // compiling it, as it lets you clearly see where the compiler
-    // errors/warnings end and the program output begins.
-    if problems_reported > 0 {
-        println!("{}\u{001B}[0m\n", Report::horizontal_rule(&palette));
-    }
+    by_module.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let error_count = errors.len();
+    let warning_count = warnings.len();
+
+    // Only show warnings if there are no errors
+    let reports = if errors.is_empty() { warnings } else { errors };
 
     Problems {
         fatally_errored,
-        errors: errors.len(),
-        warnings: warnings.len(),
+        errors: error_count,
+        warnings: warning_count,
+        by_module,
+        reports,
     }
 }
@@ -3,8 +3,8 @@ use roc_module::ident::{Ident, Lowercase, ModuleName};
 use roc_module::symbol::DERIVABLE_ABILITIES;
 use roc_problem::can::PrecedenceProblem::BothNonAssociative;
 use roc_problem::can::{
-    BadPattern, CycleEntry, ExtensionTypeKind, FloatErrorKind, IntErrorKind, Problem, RuntimeError,
-    ShadowKind,
+    BadPattern, CycleEntry, ExposingModule, ExtensionTypeKind, FloatErrorKind, IntErrorKind,
+    Problem, RuntimeError, ShadowKind,
 };
 use roc_problem::Severity;
 use roc_region::all::{LineColumn, LineColumnRegion, LineInfo, Loc, Region};
@@ -48,6 +48,7 @@ const ABILITY_MEMBER_MISSING_HAS_CLAUSE: &str = "ABILITY MEMBER MISSING HAS CLAU
 const ABILITY_MEMBER_BINDS_MULTIPLE_VARIABLES: &str = "ABILITY MEMBER BINDS MULTIPLE VARIABLES";
 const ABILITY_NOT_ON_TOPLEVEL: &str = "ABILITY NOT ON TOP-LEVEL";
 const SPECIALIZATION_NOT_ON_TOPLEVEL: &str = "SPECIALIZATION NOT ON TOP-LEVEL";
+const BENCH_NOT_ON_TOPLEVEL: &str = "BENCH NOT ON TOP-LEVEL";
 const ABILITY_USED_AS_TYPE: &str = "ABILITY USED AS TYPE";
 const ILLEGAL_DERIVE: &str = "ILLEGAL DERIVE";
 const IMPLEMENTATION_NOT_FOUND: &str = "IMPLEMENTATION NOT FOUND";
@@ -765,6 +766,23 @@ pub fn can_problem<'b>(
             title = ABILITY_NOT_ON_TOPLEVEL.to_string();
         }
 
+        Problem::BenchNotOnToplevel { region } => {
+            doc = alloc.stack([
+                alloc.concat([
+                    alloc.reflow("This "),
+                    alloc.keyword("bench"),
+                    alloc.reflow(" is not on the top-level of a module:"),
+                ]),
+                alloc.region(lines.convert_region(region)),
+                alloc.concat([
+                    alloc.reflow("Only top-level benches are run by "),
+                    alloc.keyword("roc bench"),
+                    alloc.reflow(", so this one will never run."),
+                ]),
+            ]);
+            title = BENCH_NOT_ON_TOPLEVEL.to_string();
+        }
+
         Problem::AbilityUsedAsType(suggested_var_name, ability, region) => {
             doc = alloc.stack([
                 alloc.concat([
@@ -1580,8 +1598,19 @@ fn pretty_runtime_error<'b>(
             (title, doc) = report_shadowing(alloc, lines, original_region, shadow, kind);
         }
 
-        RuntimeError::LookupNotInScope(loc_name, options) => {
-            doc = not_found(alloc, lines, loc_name.region, &loc_name.value, options);
+        RuntimeError::LookupNotInScope {
+            loc_name,
+            suggestion_options,
+            exposed_by,
+        } => {
+            doc = not_found(
+                alloc,
+                lines,
+                loc_name.region,
+                &loc_name.value,
+                suggestion_options,
+                exposed_by,
+            );
             title = UNRECOGNIZED_NAME;
         }
         RuntimeError::CircularDef(entries) => {
@@ -2159,6 +2188,28 @@ fn pretty_runtime_error<'b>(
 
             title = "UNAPPLIED RECORD BUILDER";
         }
+        RuntimeError::MisplacedTaskAwaitBang(region) => {
+            doc = alloc.stack([
+                alloc.concat([
+                    alloc.reflow("This "),
+                    alloc.keyword("!"),
+                    alloc.reflow(" suffix is not on the right side of a definition:"),
+                ]),
+                alloc.region(lines.convert_region(region)),
+                alloc.concat([
+                    alloc.reflow("I can only await a call like "),
+                    alloc.parser_suggestion("x = f! arg"),
+                    alloc.reflow(", where the rest of the block runs after it."),
+                ]),
+                alloc.tip().append(alloc.concat([
+                    alloc.reflow("Give the result a name first, or use "),
+                    alloc.parser_suggestion("<-"),
+                    alloc.reflow(" to await it explicitly."),
+                ])),
+            ]);
+
+            title = "MISPLACED AWAIT SUFFIX";
+        }
     }
 
     (doc, title)
@@ -2219,10 +2270,24 @@ fn not_found<'b>(
     region: roc_region::all::Region,
     name: &Ident,
     options: MutSet<Box<str>>,
+    exposed_by: Vec<ExposingModule>,
 ) -> RocDocBuilder<'b> {
-    let mut suggestions = suggest::sort(
-        name.as_inline_str().as_str(),
-        options.iter().map(|v| v.as_ref()).collect(),
+    let (imported, not_imported): (Vec<_>, Vec<_>) =
+        exposed_by.into_iter().partition(|module| module.imported);
+
+    // A module we already import exposing this exact name is the best suggestion,
+    // since all that's missing is the qualifier.
+    let mut suggestions: Vec<String> = imported
+        .into_iter()
+        .map(|module| format!("{}.{}", module.module_name, name))
+        .collect();
+    suggestions.extend(
+        suggest::sort(
+            name.as_inline_str().as_str(),
+            options.iter().map(|v| v.as_ref()).collect(),
+        )
+        .into_iter()
+        .map(|v| v.to_string()),
     );
     suggestions.truncate(4);
 
@@ -2236,28 +2301,61 @@ fn not_found<'b>(
 
     let default_yes = alloc.reflow("Did you mean one of these?");
 
-    let to_details = |no_suggestion_details, yes_suggestion_details| {
-        if suggestions.is_empty() {
-            no_suggestion_details
-        } else {
-            alloc.stack([
-                yes_suggestion_details,
-                alloc
-                    .vcat(suggestions.into_iter().map(|v| alloc.string(v.to_string())))
-                    .indent(4),
-            ])
-        }
-    };
-
-    alloc.stack([
+    let mut docs = vec![
         alloc.concat([
             alloc.reflow("Nothing is named `"),
             alloc.string(name.to_string()),
             alloc.reflow("` in this scope."),
         ]),
         alloc.region(lines.convert_region(region)),
-        to_details(default_no, default_yes),
-    ])
+    ];
+
+    if !suggestions.is_empty() {
+        docs.push(
+            alloc.stack([
+                default_yes,
+                alloc
+                    .vcat(suggestions.into_iter().map(|v| alloc.string(v)))
+                    .indent(4),
+            ]),
+        );
+    } else if not_imported.is_empty() {
+        // If we know which module to import, that already answers this question
+        docs.push(default_no);
+    }
+
+    match not_imported.as_slice() {
+        [] => {}
+        [module] => docs.push(alloc.concat([
+            alloc.reflow("The "),
+            alloc.module_name(module.module_name.clone()),
+            alloc.reflow(" module exposes a value with this name, but it isn't imported. "),
+            alloc.reflow("Maybe add it to the "),
+            alloc.keyword("imports"),
+            alloc.reflow(" list up-top?"),
+        ])),
+        modules => docs.push(
+            alloc.stack([
+                alloc.concat([
+                    alloc.reflow(
+                        "These modules expose a value with this name, but they aren't imported. ",
+                    ),
+                    alloc.reflow("Maybe add one of them to the "),
+                    alloc.keyword("imports"),
+                    alloc.reflow(" list up-top?"),
+                ]),
+                alloc
+                    .vcat(
+                        modules
+                            .iter()
+                            .map(|module| alloc.module_name(module.module_name.clone())),
+                    )
+                    .indent(4),
+            ]),
+        ),
+    }
+
+    alloc.stack(docs)
 }
 
 /// Generate a message informing the user that a module was referenced, but not found
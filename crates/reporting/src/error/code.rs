@@ -0,0 +1,570 @@
+//! Stable error codes for reports.
+//!
+//! Every report title maps to a code such as `E0301`, which is shown in the
+//! report header and can be looked up with `roc explain E0301`. Codes are
+//! grouped by the phase that produces them:
+//!
+//! - `E01xx` parsing
+//! - `E02xx` naming and canonicalization
+//! - `E03xx` type checking
+//! - `E04xx` pattern matching
+//! - `E05xx` abilities
+//! - `E06xx` modules, headers and files
+//! - `E07xx` warnings about unused or unnecessary code
+//! - `E08xx` expectations
+//! - `E09xx` specialization and code generation
+//!
+//! Once a code has been released it must never be reused for a different
+//! problem, so new entries always get a fresh number.
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ErrorCode(u16);
+
+struct Entry {
+    code: u16,
+    title: &'static str,
+    explanation: Option<&'static str>,
+}
+
+const fn entry(code: u16, title: &'static str) -> Entry {
+    Entry {
+        code,
+        title,
+        explanation: None,
+    }
+}
+
+const fn explained(code: u16, title: &'static str, explanation: &'static str) -> Entry {
+    Entry {
+        code,
+        title,
+        explanation: Some(explanation),
+    }
+}
+
+impl ErrorCode {
+    /// The code for reports with the given title, if the title is registered.
+    pub fn from_title(title: &str) -> Option<Self> {
+        REGISTRY
+            .iter()
+            .find(|entry| entry.title == title)
+            .map(|entry| ErrorCode(entry.code))
+    }
+
+    /// Parses a code as written by users, e.g. `E0301` or `e0301`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let digits = text.strip_prefix(&['E', 'e'][..])?;
+
+        if digits.len() != 4 {
+            return None;
+        }
+
+        let code = digits.parse().ok()?;
+
+        REGISTRY
+            .iter()
+            .any(|entry| entry.code == code)
+            .then_some(ErrorCode(code))
+    }
+
+    pub fn title(self) -> &'static str {
+        self.entry().title
+    }
+
+    /// The extended explanation printed by `roc explain`.
+    pub fn explanation(self) -> String {
+        let entry = self.entry();
+
+        match entry.explanation {
+            Some(explanation) => format!("{} [{}]\n\n{}", entry.title, self, explanation),
+            None => format!(
+                "{} [{}]\n\nThere is no extended explanation for this problem yet. The report itself describes what went wrong and how to fix it.\n",
+                entry.title, self
+            ),
+        }
+    }
+
+    pub fn all() -> impl Iterator<Item = ErrorCode> {
+        REGISTRY.iter().map(|entry| ErrorCode(entry.code))
+    }
+
+    fn entry(self) -> &'static Entry {
+        REGISTRY
+            .iter()
+            .find(|entry| entry.code == self.0)
+            .expect("ErrorCode values are only created from registry entries")
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "E{:04}", self.0)
+    }
+}
+
+static REGISTRY: &[Entry] = &[
+    // Parsing
+    explained(101, "SYNTAX PROBLEM", SYNTAX_PROBLEM),
+    entry(102, "PARSE PROBLEM"),
+    entry(103, "RECORD PARSE PROBLEM"),
+    entry(104, "ARGUMENTS BEFORE EQUALS"),
+    entry(105, "BAD BACKPASSING ARROW"),
+    entry(106, "BAD OPTIONAL VALUE"),
+    entry(107, "BAD RECORD BUILDER"),
+    entry(108, "BAD RECORD UPDATE"),
+    entry(109, "BAD REQUIRES RIGIDS"),
+    entry(110, "BAD REQUIRES"),
+    entry(111, "BAD TYPE VARIABLE"),
+    entry(112, "DOUBLE COMMA"),
+    entry(113, "DOUBLE DOT"),
+    entry(114, "EMPTY PARENTHESES"),
+    entry(115, "END OF FILE"),
+    entry(116, "ENDLESS FORMAT"),
+    entry(117, "ENDLESS SCALAR"),
+    explained(118, "ENDLESS STRING", ENDLESS_STRING),
+    entry(119, "EXPECTED STRING"),
+    entry(120, "IF GUARD NO CONDITION"),
+    entry(121, "INCOMPLETE HEADER"),
+    entry(122, "INCORRECT REST PATTERN"),
+    entry(123, "INDENT ENDS AFTER EXPRESSION"),
+    entry(124, "INSUFFICIENT INDENT IN MULTI-LINE STRING"),
+    entry(125, "INVALID NUMBER LITERAL"),
+    entry(126, "INVALID PACKAGE NAME"),
+    entry(127, "INVALID PLATFORM NAME"),
+    entry(128, "INVALID SCALAR"),
+    entry(129, "INVALID UTF-8"),
+    entry(130, "MISSING ARROW"),
+    entry(131, "MISSING EXPRESSION"),
+    explained(132, "MISSING FINAL EXPRESSION", MISSING_FINAL_EXPRESSION),
+    entry(133, "MISSING HEADER"),
+    entry(134, "MISSING PACKAGES"),
+    entry(135, "MISSING REQUIRES"),
+    explained(136, "NEED MORE INDENTATION", NEED_MORE_INDENTATION),
+    entry(137, "NOT AN INLINE ALIAS"),
+    entry(138, "NOT END OF FILE"),
+    entry(139, "PROBLEM IN RECORD PATTERN"),
+    entry(140, "PROBLEM IN RECORD TYPE"),
+    entry(141, "QUALIFIED ALIAS NAME"),
+    explained(142, "TAB CHARACTER", TAB_CHARACTER),
+    entry(143, "TRAILING DOT"),
+    entry(144, "TYPE ARGUMENT NOT LOWERCASE"),
+    entry(145, "UNEXPECTED ARROW"),
+    entry(146, "UNFINISHED ABILITY"),
+    entry(147, "UNFINISHED ARGUMENT LIST"),
+    entry(148, "UNFINISHED FUNCTION"),
+    entry(149, "UNFINISHED IF"),
+    entry(150, "UNFINISHED INLINE ALIAS"),
+    entry(151, "UNFINISHED LIST PATTERN"),
+    entry(152, "UNFINISHED LIST"),
+    entry(153, "UNFINISHED PARENTHESES"),
+    entry(154, "UNFINISHED PATTERN"),
+    entry(155, "UNFINISHED RECORD PATTERN"),
+    entry(156, "UNFINISHED RECORD TYPE"),
+    entry(157, "UNFINISHED TAG UNION TYPE"),
+    entry(158, "UNFINISHED TYPE"),
+    entry(159, "UNFINISHED WHEN"),
+    explained(160, "UNKNOWN OPERATOR", UNKNOWN_OPERATOR),
+    entry(161, "WEIRD APP NAME"),
+    entry(162, "WEIRD ARROW"),
+    entry(163, "WEIRD CODE POINT"),
+    entry(164, "WEIRD ESCAPE"),
+    entry(165, "WEIRD EXPOSES"),
+    entry(166, "WEIRD GENERATED TYPE NAME"),
+    entry(167, "WEIRD GENERATES"),
+    entry(168, "WEIRD IDENTIFIER"),
+    entry(169, "WEIRD IMPORTS"),
+    entry(170, "WEIRD MODULE NAME"),
+    entry(171, "WEIRD PROVIDES"),
+    entry(172, "WEIRD QUALIFIED NAME"),
+    entry(173, "WEIRD TAG NAME"),
+    // Naming and canonicalization
+    entry(201, "NAMING PROBLEM"),
+    explained(202, "UNRECOGNIZED NAME", UNRECOGNIZED_NAME),
+    explained(203, "DUPLICATE NAME", DUPLICATE_NAME),
+    entry(204, "DUPLICATE FIELD NAME"),
+    entry(205, "DUPLICATE TAG NAME"),
+    explained(206, "CIRCULAR DEFINITION", CIRCULAR_DEFINITION),
+    entry(207, "CYCLIC ALIAS"),
+    entry(208, "NESTED DATATYPE"),
+    entry(209, "UNBOUND TYPE VARIABLE"),
+    entry(210, "INVALID UNICODE"),
+    entry(211, "INVALID_EXTENSION_TYPE"),
+    entry(212, "CONFLICTING NUMBER SUFFIX"),
+    entry(213, "NUMBER OVERFLOWS SUFFIX"),
+    entry(214, "NUMBER UNDERFLOWS SUFFIX"),
+    explained(215, "OPAQUE TYPE NOT DEFINED", OPAQUE_TYPE_NOT_DEFINED),
+    entry(216, "OPAQUE TYPE DECLARED OUTSIDE SCOPE"),
+    entry(217, "OPAQUE TYPE NOT APPLIED"),
+    entry(218, "OPAQUE TYPE APPLIED TO TOO MANY ARGS"),
+    entry(219, "TOO MANY TYPE ARGUMENTS"),
+    entry(220, "TOO FEW TYPE ARGUMENTS"),
+    entry(221, "NAME NOT BOUND IN ALL PATTERNS"),
+    entry(222, "MULTIPLE LIST REST PATTERNS"),
+    entry(223, "UNAPPLIED CRASH"),
+    entry(224, "OVERAPPLIED CRASH"),
+    entry(225, "MULTIPLE RECORD BUILDERS"),
+    entry(226, "UNAPPLIED RECORD BUILDER"),
+    entry(227, "DEGENERATE BRANCH"),
+    entry(228, "UNKNOWN GENERATES FUNCTION"),
+    entry(229, "BENCH NOT ON TOP-LEVEL"),
+    entry(230, "MISPLACED AWAIT SUFFIX"),
+    // Type checking
+    explained(301, "TYPE MISMATCH", TYPE_MISMATCH),
+    explained(302, "TOO MANY ARGS", TOO_MANY_ARGS),
+    entry(303, "TOO FEW ARGS"),
+    explained(304, "CIRCULAR TYPE", CIRCULAR_TYPE),
+    entry(305, "INVALID TYPE FOR INGESTED FILE"),
+    entry(306, "IMPRECISE NUMBER"),
+    entry(307, "DISCARDED RESULT"),
+    // Pattern matching
+    explained(401, "UNSAFE PATTERN", UNSAFE_PATTERN),
+    explained(402, "REDUNDANT PATTERN", REDUNDANT_PATTERN),
+    entry(403, "UNMATCHABLE PATTERN"),
+    // Abilities
+    entry(501, "ABILITY HAS TYPE VARIABLES"),
+    entry(502, "HAS CLAUSE IS NOT AN ABILITY"),
+    entry(503, "ILLEGAL HAS CLAUSE"),
+    entry(504, "ABILITY MEMBER MISSING HAS CLAUSE"),
+    entry(505, "ABILITY MEMBER BINDS MULTIPLE VARIABLES"),
+    entry(506, "ABILITY NOT ON TOP-LEVEL"),
+    entry(507, "SPECIALIZATION NOT ON TOP-LEVEL"),
+    entry(508, "ABILITY USED AS TYPE"),
+    explained(509, "ILLEGAL DERIVE", ILLEGAL_DERIVE),
+    entry(510, "IMPLEMENTATION NOT FOUND"),
+    entry(511, "NOT AN ABILITY MEMBER"),
+    entry(512, "NOT AN ABILITY"),
+    entry(513, "OPTIONAL ABILITY IMPLEMENTATION"),
+    entry(514, "QUALIFIED ABILITY IMPLEMENTATION"),
+    entry(515, "ABILITY IMPLEMENTATION NOT IDENTIFIER"),
+    entry(516, "DUPLICATE IMPLEMENTATION"),
+    explained(
+        517,
+        "INCOMPLETE ABILITY IMPLEMENTATION",
+        INCOMPLETE_ABILITY_IMPLEMENTATION,
+    ),
+    entry(518, "DUPLICATE BOUND ABILITY"),
+    entry(519, "ILLEGAL SPECIALIZATION"),
+    entry(520, "WRONG SPECIALIZATION TYPE"),
+    entry(521, "OVERLOADED SPECIALIZATION"),
+    // Modules, headers and files
+    explained(601, "IMPORT CYCLE", IMPORT_CYCLE),
+    entry(602, "INCORRECT MODULE NAME"),
+    entry(603, "NO PLATFORM"),
+    entry(604, "FILE NOT FOUND"),
+    entry(605, "FILE PERMISSION DENIED"),
+    entry(606, "FILE PROBLEM"),
+    entry(607, "MISSING DEFINITION"),
+    explained(608, "NOT EXPOSED", NOT_EXPOSED),
+    explained(609, "MODULE NOT IMPORTED", MODULE_NOT_IMPORTED),
+    entry(610, "LOCKFILE PROBLEM"),
+    // Unused and unnecessary code
+    explained(701, "UNUSED DEFINITION", UNUSED_DEFINITION),
+    explained(702, "UNUSED IMPORT", UNUSED_IMPORT),
+    entry(703, "UNUSED ARGUMENT"),
+    entry(704, "UNUSED TYPE ALIAS PARAMETER"),
+    entry(705, "DEFINITION ONLY USED IN RECURSION"),
+    entry(706, "DEFINITIONs ONLY USED IN RECURSION"),
+    entry(707, "UNNECESSARY DEFINITION"),
+    entry(708, "UNNECESSARY WILDCARD"),
+    entry(709, "UNNECESSARY IMPLEMENTATIONS"),
+    // Expectations
+    entry(801, "EXPECT FAILED"),
+    entry(802, "EXPECT PANICKED"),
+    // Specialization and code generation
+    entry(901, "TOO MANY SPECIALIZATIONS"),
+    entry(902, "NOT A LOOP"),
+];
+
+const SYNTAX_PROBLEM: &str = r#"The parser could not make sense of this part of the program. This usually
+means a keyword, operator or bracket is missing or in the wrong place.
+
+For example, an `if` needs both a `then` and an `else` branch:
+
+    x = if isReady then 1
+
+Adding the missing branch fixes it:
+
+    x = if isReady then 1 else 0
+"#;
+
+const ENDLESS_STRING: &str = r#"A string literal was opened with a double quote, but the closing quote was
+never found before the end of the line.
+
+    greeting = "Hello, World!
+
+Add the closing quote:
+
+    greeting = "Hello, World!"
+
+For text that spans several lines, use a multi-line string:
+
+    poem =
+        """
+        Roses are red
+        """
+"#;
+
+const MISSING_FINAL_EXPRESSION: &str = r#"A block of definitions must end with an expression that gives the block its
+value. Definitions on their own do not produce anything.
+
+    main =
+        x = 1
+        y = 2
+
+Add an expression after the last definition:
+
+    main =
+        x = 1
+        y = 2
+
+        x + y
+"#;
+
+const NEED_MORE_INDENTATION: &str = r#"Roc uses indentation to tell where one expression ends and the next begins.
+Here a part of an expression is not indented far enough to belong to the
+expression above it.
+
+    total =
+    1 + 2
+
+Indent the continuation relative to the start of the definition:
+
+    total =
+        1 + 2
+"#;
+
+const TAB_CHARACTER: &str = r#"Roc source files may only use spaces for indentation. A tab character was
+found in the file.
+
+Configure your editor to insert spaces instead of tabs, or run `roc format`
+to rewrite the file.
+"#;
+
+const UNKNOWN_OPERATOR: &str = r#"This operator is not one Roc knows about. Some operators from other
+languages have a different spelling in Roc:
+
+    a != b     # use this for "not equal", not `a <> b` or `a =/= b`
+    a == b     # use this for equality, `=` only introduces definitions
+    a && b     # boolean and
+    a |> f     # pass `a` as the first argument to `f`
+"#;
+
+const UNRECOGNIZED_NAME: &str = r#"A name was used that is not defined anywhere in scope. Common causes are a
+typo, a definition that comes from another module that has not been
+imported, or a variable defined inside a different branch or function.
+
+    main = greting
+
+Fix the spelling, or define the name before using it:
+
+    greeting = "Hi!"
+
+    main = greeting
+
+If the value lives in another module, add that module to the `imports` list
+in the header and refer to it with its module name, like `Str.concat`.
+"#;
+
+const DUPLICATE_NAME: &str = r#"The same name was defined twice in the same scope. Roc does not allow
+shadowing, so every name can only be defined once.
+
+    x = 1
+    x = 2
+
+Give the second definition a different name:
+
+    x = 1
+    y = 2
+"#;
+
+const CIRCULAR_DEFINITION: &str = r#"A value is defined in terms of itself without any function in between, so it
+would never finish evaluating.
+
+    x = x + 1
+
+Only functions may refer to themselves. If you meant to compute something
+from a previous value, give it a new name:
+
+    x = 1
+    y = x + 1
+"#;
+
+const OPAQUE_TYPE_NOT_DEFINED: &str = r#"An opaque type can only be wrapped or unwrapped with the `@Name` syntax in the
+module that defines it, and the name must match a `:=` definition.
+
+    Age := U32
+
+    fromNum = \n -> @Agee n
+
+Use the exact name of the opaque type:
+
+    fromNum = \n -> @Age n
+"#;
+
+const TYPE_MISMATCH: &str = r#"Two parts of the program disagree about the type of a value. The report shows
+the type that was found and the type that was expected.
+
+    x : Str
+    x = 42
+
+The annotation says `x` is a `Str`, but the definition gives it a number.
+Either change the value:
+
+    x : Str
+    x = "42"
+
+or change the annotation:
+
+    x : U64
+    x = 42
+
+Type mismatches also happen when calling a function with an argument of the
+wrong type, when the branches of an `if` or `when` produce different types,
+and when the elements of a list have different types:
+
+    [1, "two", 3]
+"#;
+
+const TOO_MANY_ARGS: &str = r#"A function was called with more arguments than it accepts.
+
+    add = \a, b -> a + b
+
+    main = add 1 2 3
+
+Remove the extra argument, or change the function to accept it:
+
+    main = add 1 2
+
+This can also happen when a value that is not a function at all is followed by
+an argument, often because of a missing operator or comma.
+"#;
+
+const CIRCULAR_TYPE: &str = r#"Type inference found a type that would have to contain itself, which would
+make it infinitely large.
+
+    f = \x -> f [x]
+
+Here `f` takes an `a` but calls itself with a `List a`, which would require
+`a` and `List a` to be the same type. This usually points at a recursive call
+whose arguments are wrapped one time too many, or a missing annotation on a
+recursive function.
+"#;
+
+const UNSAFE_PATTERN: &str = r#"A `when` expression does not cover every possible value, so the program could
+crash at runtime. The report lists the values that are not handled.
+
+    color : [Red, Green, Blue]
+
+    when color is
+        Red -> "red"
+        Green -> "green"
+
+Add a branch for each missing value, or a catch-all branch:
+
+    when color is
+        Red -> "red"
+        Green -> "green"
+        Blue -> "blue"
+"#;
+
+const REDUNDANT_PATTERN: &str = r#"A branch of a `when` can never be reached, because every value it would match
+is already matched by an earlier branch.
+
+    when n is
+        _ -> "anything"
+        0 -> "zero"
+
+Move more specific patterns above the general ones, or remove the branch:
+
+    when n is
+        0 -> "zero"
+        _ -> "anything"
+"#;
+
+const ILLEGAL_DERIVE: &str = r#"This ability cannot be derived automatically. Only builtin abilities such as
+`Eq`, `Hash`, `Encoding` and `Decoding` can be listed after `has` without
+an implementation.
+
+    Id := U64 has [MyAbility]
+
+Provide the implementation yourself:
+
+    Id := U64 has [MyAbility { myMember }]
+"#;
+
+const INCOMPLETE_ABILITY_IMPLEMENTATION: &str = r#"A type claims to implement an ability, but does not provide an implementation
+for every member of that ability.
+
+    MHash has
+        hash : a -> U64 | a has MHash
+        isEmpty : a -> Bool | a has MHash
+
+    Id := U64 has [MHash { hash }]
+
+List an implementation for each member:
+
+    Id := U64 has [MHash { hash, isEmpty }]
+"#;
+
+const IMPORT_CYCLE: &str = r#"Modules import each other in a cycle. Roc needs to check a module's
+dependencies before the module itself, so imports must form a tree.
+
+    # A.roc
+    interface A exposes [a] imports [B]
+
+    # B.roc
+    interface B exposes [b] imports [A]
+
+Move the definitions that both modules need into a third module that both can
+import, and remove one of the imports.
+"#;
+
+const NOT_EXPOSED: &str = r#"A module was imported, but the value that was used is not in its `exposes`
+list. Only exposed values can be used from other modules.
+
+    # Parser.roc
+    interface Parser exposes [parse] imports []
+
+    # Main.roc
+    main = Parser.tokenize "input"
+
+Either use a value the module exposes, or add the value to the `exposes`
+list of that module:
+
+    interface Parser exposes [parse, tokenize] imports []
+"#;
+
+const MODULE_NOT_IMPORTED: &str = r#"A qualified name like `Json.decode` was used, but the module `Json` is not
+in the `imports` list of the current module.
+
+    app "example"
+        packages { pf: "platform/main.roc" }
+        imports []
+        provides [main] to pf
+
+    main = Json.decode input
+
+Add the module to the header:
+
+        imports [Json]
+"#;
+
+const UNUSED_DEFINITION: &str = r#"A value was defined but never used. This is a warning: the program still
+builds, but the definition is usually a leftover or a sign that the wrong
+name was used somewhere else.
+
+    main =
+        unused = 1
+        2
+
+Remove the definition, or use it where it was meant to be used.
+"#;
+
+const UNUSED_IMPORT: &str = r#"A module was imported but nothing from it is used. This is a warning: the
+program still builds.
+
+    imports [Dict]
+
+Remove the module from the `imports` list, or use something from it.
+"#;
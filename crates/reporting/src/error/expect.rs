@@ -10,7 +10,7 @@ use roc_types::{
     types::{ErrorType, Polarity},
 };
 
-use crate::report::{RenderTarget, RocDocAllocator, RocDocBuilder};
+use crate::report::{Palette, RenderTarget, RocDocAllocator, RocDocBuilder};
 
 pub struct Renderer<'a> {
     arena: &'a Bump,
@@ -18,6 +18,7 @@ pub struct Renderer<'a> {
     filename: PathBuf,
     line_info: LineInfo,
     render_target: RenderTarget,
+    palette: Palette,
 }
 
 impl<'a> Renderer<'a> {
@@ -40,9 +41,15 @@ impl<'a> Renderer<'a> {
             line_info,
             filename,
             render_target,
+            palette: Palette::from_env(),
         }
     }
 
+    /// Use the given palette instead of the one picked from the environment.
+    pub fn with_palette(self, palette: Palette) -> Self {
+        Self { palette, ..self }
+    }
+
     fn render_expr(&'a self, error_type: ErrorType) -> RocDocBuilder<'a> {
         use crate::error::r#type::error_type_to_doc;
 
@@ -160,12 +167,7 @@ impl<'a> Renderer<'a> {
 
         let mut buf = String::new();
 
-        report.render(
-            self.render_target,
-            &mut buf,
-            &self.alloc,
-            &crate::report::DEFAULT_PALETTE,
-        );
+        report.render(self.render_target, &mut buf, &self.alloc, &self.palette);
 
         write!(writer, "{}", buf)
     }
@@ -184,10 +186,12 @@ impl<'a> Renderer<'a> {
         let line_col_region = self.to_line_col_region(expect_region, dbg_expr_region);
         write!(
             writer,
-            "\u{001b}[36m[{} {}:{}] \u{001b}[0m",
+            "{}[{} {}:{}] {}",
+            self.palette.header,
             self.filename.display(),
             line_col_region.start.line + 1,
-            line_col_region.start.column + 1
+            line_col_region.start.column + 1,
+            self.palette.reset,
         )?;
 
         let expr = expressions[0];
@@ -231,12 +235,7 @@ impl<'a> Renderer<'a> {
 
         let mut buf = String::new();
 
-        report.render(
-            self.render_target,
-            &mut buf,
-            &self.alloc,
-            &crate::report::DEFAULT_PALETTE,
-        );
+        report.render(self.render_target, &mut buf, &self.alloc, &self.palette);
 
         write!(writer, "{}", buf)
     }
@@ -1,4 +1,5 @@
 pub mod canonicalize;
+pub mod code;
 pub mod expect;
 pub mod parse;
 pub mod r#type;
@@ -1,7 +1,7 @@
 #![allow(clippy::too_many_arguments)]
 
 use crate::error::canonicalize::{to_circular_def_doc, CIRCULAR_DEF};
-use crate::report::{Annotation, Report, RocDocAllocator, RocDocBuilder};
+use crate::report::{Annotation, LabeledRegion, Report, RocDocAllocator, RocDocBuilder};
 use itertools::EitherOrBoth;
 use itertools::Itertools;
 use roc_can::expected::{Expected, PExpected};
@@ -248,6 +248,52 @@ pub fn type_problem<'b>(
                 severity,
             })
         }
+        ImpreciseNumber {
+            region,
+            float_type,
+            rounded,
+        } => {
+            let stack = [
+                alloc.concat([
+                    alloc.reflow("This number can't be represented exactly as an "),
+                    alloc.symbol_unqualified(float_type),
+                    alloc.reflow(":"),
+                ]),
+                alloc.region(lines.convert_region(region)),
+                alloc.concat([
+                    alloc.reflow("It will be rounded to "),
+                    alloc.text(rounded),
+                    alloc.reflow("."),
+                ]),
+                alloc.tip().append(alloc.reflow(
+                    "Write the rounded number to make the rounding explicit, or use a type with more precision, like F64 or Dec. Functions like Num.toF64 and Num.toFrac convert other numbers to those types.",
+                )),
+            ];
+
+            Some(Report {
+                title: "IMPRECISE NUMBER".to_string(),
+                filename,
+                doc: alloc.stack(stack),
+                severity,
+            })
+        }
+        DiscardedResult { region } => {
+            let stack = [
+                alloc.reflow("This `_` throws away a Result:"),
+                alloc.region(lines.convert_region(region)),
+                alloc.reflow("If it is an error, nothing will ever find out."),
+                alloc.tip().append(alloc.reflow(
+                    "Name the Result and handle its error, or name it `_result` if ignoring it is intended.",
+                )),
+            ];
+
+            Some(Report {
+                title: "DISCARDED RESULT".to_string(),
+                filename,
+                doc: alloc.stack(stack),
+                severity,
+            })
+        }
     }
 }
 
@@ -804,13 +850,26 @@ fn to_expr_report<'b>(
                 doc: alloc.stack([
                     alloc.text("Something is off with the ").append(thing),
                     {
-                        // for typed bodies, include the line(s) with the signature
-                        let joined =
-                            roc_region::all::Region::span_across(&ann_region, &expr_region);
-                        alloc.region_with_subregion(
-                            lines.convert_region(joined),
-                            lines.convert_region(expr_region),
-                        )
+                        // for typed bodies, point at the signature as well as the body
+                        if ann_region.contains(&expr_region) || expr_region.contains(&ann_region) {
+                            let joined =
+                                roc_region::all::Region::span_across(&ann_region, &expr_region);
+                            alloc.region_with_subregion(
+                                lines.convert_region(joined),
+                                lines.convert_region(expr_region),
+                            )
+                        } else {
+                            alloc.region_with_labels(&[
+                                LabeledRegion::secondary(
+                                    lines.convert_region(ann_region),
+                                    "expected because of this annotation",
+                                ),
+                                LabeledRegion::primary(
+                                    lines.convert_region(expr_region),
+                                    "but this doesn't match it",
+                                ),
+                            ])
+                        }
                     },
                     comparison,
                 ]),
@@ -2,12 +2,13 @@ use roc_module::ident::Ident;
 use roc_module::ident::{Lowercase, ModuleName, TagName, Uppercase};
 use roc_module::symbol::{Interns, ModuleId, ModuleIds, PQModuleName, PackageQualified, Symbol};
 use roc_problem::Severity;
-use roc_region::all::LineColumnRegion;
+use roc_region::all::{LineColumnRegion, LineInfo, Region};
 use std::path::{Path, PathBuf};
 use std::{fmt, io};
 use ven_pretty::{text, BoxAllocator, DocAllocator, DocBuilder, Render, RenderAnnotated};
 
 pub use crate::error::canonicalize::can_problem;
+pub use crate::error::code::ErrorCode;
 pub use crate::error::parse::parse_problem;
 pub use crate::error::r#type::type_problem;
 
@@ -23,7 +24,12 @@ const CYCLE_MID: &str = CYCLE_ELEMENTS[2];
 const CYCLE_END: &str = CYCLE_ELEMENTS[3];
 
 const GUTTER_BAR: &str = "│";
+const SKIPPED_LINES: &str = "┆";
 const ERROR_UNDERLINE: &str = "^";
+const SECONDARY_UNDERLINE: &str = "-";
+
+/// If a snippet would take more than 1 full screen, we leave out some of its lines.
+pub(crate) const MAX_SNIPPET_LINES: u32 = 60;
 
 /// The number of monospace spaces the gutter bar takes up.
 /// (This is not necessarily the same as GUTTER_BAR.len()!)
@@ -62,13 +68,14 @@ pub fn cycle<'b>(
 
 const HEADER_WIDTH: usize = 80;
 
-pub fn pretty_header(title: &str) -> String {
+pub fn pretty_header(title: &str, code: Option<ErrorCode>) -> String {
+    let title = title_with_code(title, code);
     let title_width = title.len() + 4;
     let header = format!("── {} {}", title, "─".repeat(HEADER_WIDTH - title_width));
     header
 }
 
-pub fn pretty_header_with_path(title: &str, path: &Path) -> String {
+pub fn pretty_header_with_path(title: &str, code: Option<ErrorCode>, path: &Path) -> String {
     let cwd = std::env::current_dir().unwrap();
     let relative_path = match path.strip_prefix(cwd) {
         Ok(p) => p,
@@ -77,9 +84,13 @@ pub fn pretty_header_with_path(title: &str, path: &Path) -> String {
     .to_str()
     .unwrap();
 
+    // The error code doesn't count against the path: a header with a long path can go
+    // past 80 characters by the width of the code, rather than cutting more off the path.
+    let path_title_width = title.len() + 4;
+    let title = title_with_code(title, code);
     let title_width = title.len() + 4;
     let relative_path_width = relative_path.len() + 3;
-    let available_path_width = HEADER_WIDTH - title_width - 1;
+    let available_path_width = HEADER_WIDTH - path_title_width - 1;
 
     // If path is too long to fit in 80 characters with everything else then truncate it
     let path_width = relative_path_width.min(available_path_width);
@@ -93,13 +104,20 @@ pub fn pretty_header_with_path(title: &str, path: &Path) -> String {
     let header = format!(
         "── {} {} {} ─",
         title,
-        "─".repeat(HEADER_WIDTH - (title_width + path_width)),
+        "─".repeat(HEADER_WIDTH.saturating_sub(title_width + path_width).max(1)),
         path
     );
 
     header
 }
 
+fn title_with_code(title: &str, code: Option<ErrorCode>) -> String {
+    match code {
+        Some(code) => format!("{} [{}]", title, code),
+        None => title.to_string(),
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum RenderTarget {
     ColorTerminal,
@@ -118,7 +136,7 @@ impl<'b> Report<'b> {
     pub fn render(
         self,
         target: RenderTarget,
-        buf: &'b mut String,
+        buf: &mut String,
         alloc: &'b RocDocAllocator<'b>,
         palette: &'b Palette,
     ) {
@@ -129,7 +147,7 @@ impl<'b> Report<'b> {
     }
 
     /// Render to CI console output, where no colors are available.
-    pub fn render_ci(self, buf: &'b mut String, alloc: &'b RocDocAllocator<'b>) {
+    pub fn render_ci(self, buf: &mut String, alloc: &'b RocDocAllocator<'b>) {
         let err_msg = "<buffer is not a utf-8 encoded string>";
 
         self.pretty(alloc)
@@ -154,14 +172,20 @@ impl<'b> Report<'b> {
             .expect(err_msg);
     }
 
+    /// The stable code for this kind of report, e.g. `E0301` for a type mismatch.
+    pub fn code(&self) -> Option<ErrorCode> {
+        ErrorCode::from_title(&self.title)
+    }
+
     pub fn pretty(self, alloc: &'b RocDocAllocator<'b>) -> RocDocBuilder<'b> {
         if self.title.is_empty() {
             self.doc
         } else {
+            let code = self.code();
             let header = if self.filename == PathBuf::from("") {
-                crate::report::pretty_header(&self.title)
+                crate::report::pretty_header(&self.title, code)
             } else {
-                crate::report::pretty_header_with_path(&self.title, &self.filename)
+                crate::report::pretty_header_with_path(&self.title, code, &self.filename)
             };
 
             alloc.stack([alloc.text(header).annotate(Annotation::Header), self.doc])
@@ -198,6 +222,7 @@ pub struct Palette {
     pub typo: &'static str,
     pub typo_suggestion: &'static str,
     pub parser_suggestion: &'static str,
+    pub secondary_highlight: &'static str,
     pub bold: &'static str,
     pub underline: &'static str,
     pub reset: &'static str,
@@ -225,6 +250,7 @@ const fn default_palette_from_style_codes(codes: StyleCodes) -> Palette {
         typo: codes.yellow,
         typo_suggestion: codes.yellow,
         parser_suggestion: codes.yellow,
+        secondary_highlight: codes.cyan,
         bold: codes.bold,
         underline: codes.underline,
         reset: codes.reset,
@@ -235,6 +261,75 @@ pub const DEFAULT_PALETTE: Palette = default_palette_from_style_codes(ANSI_STYLE
 
 pub const DEFAULT_PALETTE_HTML: Palette = default_palette_from_style_codes(HTML_STYLE_CODES);
 
+/// Sticks to red, yellow, green and white, leaving out the blues and cyans
+/// that are hard to read on many terminal backgrounds.
+const fn high_contrast_palette_from_style_codes(codes: StyleCodes) -> Palette {
+    Palette {
+        primary: codes.white,
+        code_block: codes.white,
+        keyword: codes.yellow,
+        ellipsis: codes.yellow,
+        variable: codes.white,
+        type_variable: codes.yellow,
+        structure: codes.yellow,
+        alias: codes.yellow,
+        opaque: codes.yellow,
+        error: codes.red,
+        line_number: codes.white,
+        header: codes.yellow,
+        gutter_bar: codes.white,
+        module_name: codes.yellow,
+        binop: codes.yellow,
+        typo: codes.red,
+        typo_suggestion: codes.green,
+        parser_suggestion: codes.green,
+        secondary_highlight: codes.yellow,
+        bold: codes.bold,
+        underline: codes.underline,
+        reset: codes.reset,
+    }
+}
+
+pub const HIGH_CONTRAST_PALETTE: Palette = high_contrast_palette_from_style_codes(ANSI_STYLE_CODES);
+
+pub const HIGH_CONTRAST_PALETTE_HTML: Palette =
+    high_contrast_palette_from_style_codes(HTML_STYLE_CODES);
+
+/// Renders without any styling, for terminals that can't show colors
+/// or users who asked not to see them.
+pub const NO_COLOR_PALETTE: Palette = default_palette_from_style_codes(NO_COLOR_STYLE_CODES);
+
+impl Palette {
+    /// The palette to use for reports written to the user's terminal.
+    ///
+    /// Colors are turned off when `NO_COLOR` is set (see https://no-color.org)
+    /// or when `TERM` says the terminal can't show them. Otherwise
+    /// `ROC_COLOR_THEME` picks between the `default` and `high-contrast` themes.
+    pub fn from_env() -> Palette {
+        Palette::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// Like [Palette::from_env], but reads variables through the given lookup.
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Palette {
+        let no_color = lookup("NO_COLOR").map_or(false, |value| !value.is_empty());
+        let dumb_terminal = lookup("TERM").map_or(false, |term| term == "dumb");
+
+        if no_color || dumb_terminal {
+            return NO_COLOR_PALETTE;
+        }
+
+        match lookup("ROC_COLOR_THEME").as_deref() {
+            Some("high-contrast") => HIGH_CONTRAST_PALETTE,
+            Some("none") => NO_COLOR_PALETTE,
+            _ => DEFAULT_PALETTE,
+        }
+    }
+
+    pub fn is_colored(&self) -> bool {
+        !self.reset.is_empty()
+    }
+}
+
 /// A machine-readable format for text styles (colors and other styles)
 pub struct StyleCodes {
     pub red: &'static str,
@@ -264,6 +359,20 @@ pub const ANSI_STYLE_CODES: StyleCodes = StyleCodes {
     color_reset: "\u{1b}[39m",
 };
 
+pub const NO_COLOR_STYLE_CODES: StyleCodes = StyleCodes {
+    red: "",
+    green: "",
+    yellow: "",
+    blue: "",
+    magenta: "",
+    cyan: "",
+    white: "",
+    bold: "",
+    underline: "",
+    reset: "",
+    color_reset: "",
+};
+
 macro_rules! html_color {
     ($name: expr) => {
         concat!("<span class='color-", $name, "'>")
@@ -646,7 +755,7 @@ impl<'a> RocDocAllocator<'a> {
         // debug_assert!(region.contains(&sub_region));
 
         // If the outer region takes more than 1 full screen (~60 lines), only show the inner region
-        if region.end().line.saturating_sub(region.start().line) > 60 {
+        if region.end().line.saturating_sub(region.start().line) > MAX_SNIPPET_LINES {
             // If the inner region contains the outer region (or if they are the same),
             // attempting this will recurse forever, so don't do that! Instead, give up and
             // accept that this report will take up more than 1 full screen.
@@ -737,6 +846,137 @@ impl<'a> RocDocAllocator<'a> {
         result
     }
 
+    /// Show a snippet of source code in which every one of the given regions is underlined,
+    /// each with its own label. The primary region is underlined with `^`, secondary
+    /// regions with `-`. Regions that span multiple lines are marked with a `>` in the
+    /// gutter instead, and their label is printed below the last line of the region.
+    /// Regions more than a screen apart are shown without the lines between them.
+    pub fn region_with_labels(
+        &'a self,
+        labels: &[LabeledRegion<'a>],
+    ) -> DocBuilder<'a, Self, Annotation> {
+        let mut labels = labels.to_vec();
+        labels.sort_by_key(|label| (label.region.start(), label.region.end()));
+
+        let first_line = match labels.first() {
+            Some(first) => first.region.start().line,
+            None => return self.nil(),
+        };
+        let last_line = labels
+            .iter()
+            .map(|label| label.region.end().line)
+            .max()
+            .unwrap_or(first_line);
+
+        // If the regions are too far apart to fit on one screen, skip the lines between them.
+        let collapse_gaps = last_line - first_line > MAX_SNIPPET_LINES;
+        let is_shown = |i: u32| {
+            !collapse_gaps
+                || labels
+                    .iter()
+                    .any(|label| i >= label.region.start().line && i <= label.region.end().line)
+        };
+
+        let has_multiline = labels.iter().any(|label| label.is_multiline());
+
+        let max_line_number_length = (last_line + 1).to_string().len();
+        let indent = 2;
+        // Underlines are aligned with the source code, which is shifted one column further
+        // right when the gutter has room for `>` markers.
+        let underline_offset =
+            max_line_number_length + GUTTER_BAR_WIDTH + indent + has_multiline as usize;
+
+        let label_line = |label: &LabeledRegion<'a>, start_column: u32, end_column: u32| {
+            let (underline, annotation) = label.style();
+            let width = end_column.saturating_sub(start_column).max(1) as usize;
+
+            self.line().append(
+                self.text(" ".repeat(underline_offset + start_column as usize))
+                    .append(
+                        self.text(underline.repeat(width))
+                            .append(self.text(" "))
+                            .append(self.text(label.label))
+                            .annotate(annotation),
+                    ),
+            )
+        };
+
+        let mut result = self.nil();
+        for i in first_line..=last_line {
+            if !is_shown(i) {
+                if is_shown(i - 1) {
+                    result = result
+                        .append(self.text(" ".repeat(max_line_number_length)))
+                        .append(self.text(SKIPPED_LINES).annotate(Annotation::GutterBar))
+                        .append(self.line());
+                }
+                continue;
+            }
+
+            let line_number = (i + 1).to_string();
+
+            let line: &str = self.src_lines.get(i as usize).unwrap_or(&"");
+            let is_line_empty = line.trim().is_empty();
+
+            let marker = labels
+                .iter()
+                .find(|label| {
+                    label.is_multiline()
+                        && i >= label.region.start().line
+                        && i <= label.region.end().line
+                })
+                .map(|label| self.text(">").annotate(label.style().1));
+
+            let up_to_gutter = self
+                .text(" ".repeat(max_line_number_length - line_number.len()))
+                .append(self.text(line_number).annotate(Annotation::LineNumber))
+                .append(self.text(GUTTER_BAR).annotate(Annotation::GutterBar));
+
+            let source_line = match marker {
+                Some(marker) => up_to_gutter.append(marker),
+                None if has_multiline && !is_line_empty => up_to_gutter.append(self.text(" ")),
+                None => up_to_gutter,
+            };
+
+            result = result.append(if is_line_empty {
+                // Don't put an trailing space after the gutter
+                source_line
+            } else {
+                source_line.append(
+                    self.text(line)
+                        .annotate(Annotation::CodeBlock)
+                        .indent(indent),
+                )
+            });
+
+            for label in labels.iter() {
+                if label.is_multiline() {
+                    if label.region.end().line == i {
+                        // Point at the start of the line; the `>` markers show the extent
+                        let start_column = line.len() - line.trim_start().len();
+                        result = result.append(label_line(
+                            label,
+                            start_column as u32,
+                            label.region.end().column,
+                        ));
+                    }
+                } else if label.region.start().line == i {
+                    result = result.append(label_line(
+                        label,
+                        label.region.start().column,
+                        label.region.end().column,
+                    ));
+                }
+            }
+
+            if i != last_line {
+                result = result.append(self.line())
+            }
+        }
+
+        result
+    }
+
     pub fn region(&'a self, region: LineColumnRegion) -> DocBuilder<'a, Self, Annotation> {
         self.region_with_subregion(region, region)
     }
@@ -814,6 +1054,52 @@ impl<'a> RocDocAllocator<'a> {
     }
 }
 
+/// A region of source code to underline in a report, along with a short note
+/// explaining why it's relevant (e.g. "expected because of this annotation").
+#[derive(Clone, Copy, Debug)]
+pub struct LabeledRegion<'a> {
+    pub region: LineColumnRegion,
+    pub label: &'a str,
+    pub kind: LabelKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LabelKind {
+    /// Where the problem is.
+    Primary,
+    /// Context that explains the problem.
+    Secondary,
+}
+
+impl<'a> LabeledRegion<'a> {
+    pub fn primary(region: LineColumnRegion, label: &'a str) -> Self {
+        LabeledRegion {
+            region,
+            label,
+            kind: LabelKind::Primary,
+        }
+    }
+
+    pub fn secondary(region: LineColumnRegion, label: &'a str) -> Self {
+        LabeledRegion {
+            region,
+            label,
+            kind: LabelKind::Secondary,
+        }
+    }
+
+    fn is_multiline(&self) -> bool {
+        self.region.start().line != self.region.end().line
+    }
+
+    fn style(&self) -> (&'static str, Annotation) {
+        match self.kind {
+            LabelKind::Primary => (ERROR_UNDERLINE, Annotation::Error),
+            LabelKind::Secondary => (SECONDARY_UNDERLINE, Annotation::SecondaryHighlight),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub enum Annotation {
     Emphasized,
@@ -842,6 +1128,7 @@ pub enum Annotation {
     Tip,
     Header,
     ParserSuggestion,
+    SecondaryHighlight,
 }
 
 /// Render with minimal formatting
@@ -1049,6 +1336,9 @@ where
             ParserSuggestion => {
                 self.write_str(self.palette.parser_suggestion)?;
             }
+            SecondaryHighlight => {
+                self.write_str(self.palette.secondary_highlight)?;
+            }
             TypeBlock | InlineTypeBlock | Tag | RecordField | TupleElem => { /* nothing yet */ }
         }
         self.style_stack.push(*annotation);
@@ -1063,7 +1353,7 @@ where
             Some(annotation) => match annotation {
                 Emphasized | Url | TypeVariable | Alias | Symbol | BinOp | Error | GutterBar
                 | Ellipsis | Typo | TypoSuggestion | ParserSuggestion | Structure | CodeBlock
-                | PlainText | LineNumber | Tip | Module | Header | Keyword => {
+                | PlainText | LineNumber | Tip | Module | Header | Keyword | SecondaryHighlight => {
                     self.write_str(self.palette.reset)?;
                 }
 
@@ -1075,7 +1365,37 @@ where
     }
 }
 
-pub fn to_file_problem_report_string(filename: &Path, error: io::ErrorKind) -> String {
+/// A function calls itself in tail position, but the call did not become a jump, so each call
+/// grows the stack.
+pub fn to_not_a_loop_report<'b>(
+    alloc: &'b RocDocAllocator<'b>,
+    lines: &LineInfo,
+    filename: PathBuf,
+    symbol: Symbol,
+    region: Region,
+) -> Report<'b> {
+    let doc = alloc.stack([
+        alloc.concat([
+            alloc.symbol_unqualified(symbol),
+            alloc.reflow(" calls itself in tail position, but I could not compile that call into a loop:"),
+        ]),
+        alloc.region(lines.convert_region(region)),
+        alloc.reflow("So every call will take up more stack space, and a deep enough recursion will overflow the stack. This happens when the call goes through a closure, or when it is made with different types than the function itself was called with."),
+    ]);
+
+    Report {
+        filename,
+        doc,
+        title: "NOT A LOOP".to_string(),
+        severity: Severity::Warning,
+    }
+}
+
+pub fn to_file_problem_report_string(
+    filename: &Path,
+    error: io::ErrorKind,
+    palette: Palette,
+) -> String {
     let src_lines: Vec<&str> = Vec::new();
 
     let mut module_ids = ModuleIds::default();
@@ -1088,13 +1408,66 @@ pub fn to_file_problem_report_string(filename: &Path, error: io::ErrorKind) -> S
     let alloc = RocDocAllocator::new(&src_lines, module_id, &interns);
 
     let mut buf = String::new();
-    let palette = DEFAULT_PALETTE;
     let report = to_file_problem_report(&alloc, filename, error);
     report.render_color_terminal(&mut buf, &alloc, &palette);
 
     buf
 }
 
+pub fn to_lockfile_problem_report_string(
+    filename: &Path,
+    problem: &str,
+    palette: Palette,
+) -> String {
+    let src_lines: Vec<&str> = Vec::new();
+
+    let mut module_ids = ModuleIds::default();
+
+    let module_id = module_ids.get_or_insert(&"find module name somehow?".into());
+
+    let interns = Interns::default();
+
+    let alloc = RocDocAllocator::new(&src_lines, module_id, &interns);
+
+    let mut buf = String::new();
+    let report = to_lockfile_problem_report(&alloc, filename, problem);
+    report.render_color_terminal(&mut buf, &alloc, &palette);
+
+    buf
+}
+
+/// The lockfile a module header's package name was looked up in could not be read.
+pub fn to_lockfile_problem_report<'b>(
+    alloc: &'b RocDocAllocator<'b>,
+    filename: &Path,
+    problem: &str,
+) -> Report<'b> {
+    let filename: String = filename.to_str().unwrap().to_string();
+    let doc = alloc.stack([
+        alloc.reflow(r"I tried to look up package versions in this lockfile:"),
+        alloc.string(filename).annotate(Annotation::Error).indent(4),
+        alloc.reflow(r"But ran into:"),
+        alloc
+            .string(problem.to_string())
+            .annotate(Annotation::Error)
+            .indent(4),
+        alloc.concat([
+            alloc.reflow(r"The lockfile is generated from the "),
+            alloc.keyword("[dependencies]"),
+            alloc.reflow(r" in roc.toml, so deleting it and running "),
+            alloc.keyword("roc build"),
+            alloc.reflow(r" will write a new one."),
+        ]),
+    ]);
+
+    Report {
+        filename: "UNKNOWN.roc".into(),
+        doc,
+        title: "LOCKFILE PROBLEM".to_string(),
+        severity: Severity::Fatal,
+    }
+}
+
 pub fn to_file_problem_report<'b>(
     alloc: &'b RocDocAllocator<'b>,
     filename: &Path,
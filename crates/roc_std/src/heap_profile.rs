@@ -0,0 +1,100 @@
+//! Counts live allocations per layout, for apps built with `--heap-profile`.
+//!
+//! Such an app calls `roc_heap_profile_alloc` and `roc_heap_profile_dealloc` whenever it
+//! allocates or frees a box or a recursive tag union. With the `heap-profile` feature, this
+//! module defines both to call [record_alloc] and [record_dealloc]. A host can then call
+//! [write_live_by_layout], e.g. right before exiting, to see which layouts were never freed.
+use core::ffi::{c_char, CStr};
+use core::fmt;
+use core::ptr;
+use core::sync::atomic::{AtomicIsize, AtomicPtr, Ordering};
+
+/// Layouts with a larger id are not counted
+pub const MAX_LAYOUTS: usize = 1 << 14;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_ALLOCATIONS: AtomicIsize = AtomicIsize::new(0);
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_NAME: AtomicPtr<c_char> = AtomicPtr::new(ptr::null_mut());
+
+static LIVE: [AtomicIsize; MAX_LAYOUTS] = [NO_ALLOCATIONS; MAX_LAYOUTS];
+static NAMES: [AtomicPtr<c_char>; MAX_LAYOUTS] = [NO_NAME; MAX_LAYOUTS];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LiveLayout {
+    pub layout_id: u32,
+    pub layout_name: &'static str,
+    /// Allocations minus deallocations
+    pub live: isize,
+}
+
+/// # Safety
+///
+/// `layout_name` must be null, or a NUL-terminated string that is never freed.
+pub unsafe fn record_alloc(layout_id: u32, layout_name: *const c_char) {
+    if let Some(live) = LIVE.get(layout_id as usize) {
+        NAMES[layout_id as usize].store(layout_name as *mut c_char, Ordering::Relaxed);
+        live.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+pub fn record_dealloc(layout_id: u32) {
+    if let Some(live) = LIVE.get(layout_id as usize) {
+        live.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Every layout with allocations that weren't freed, by id
+pub fn live_by_layout() -> impl Iterator<Item = LiveLayout> {
+    LIVE.iter()
+        .zip(NAMES.iter())
+        .enumerate()
+        .filter_map(|(layout_id, (live, name))| {
+            let live = live.load(Ordering::Relaxed);
+
+            if live == 0 {
+                return None;
+            }
+
+            let name = name.load(Ordering::Relaxed);
+            let layout_name = if name.is_null() {
+                ""
+            } else {
+                unsafe { CStr::from_ptr(name) }.to_str().unwrap_or("")
+            };
+
+            Some(LiveLayout {
+                layout_id: layout_id as u32,
+                layout_name,
+                live,
+            })
+        })
+}
+
+/// One line per layout with live allocations: the count, the id, and the layout
+pub fn write_live_by_layout(out: &mut impl fmt::Write) -> fmt::Result {
+    for layout in live_by_layout() {
+        writeln!(
+            out,
+            "{:>10} live  #{:<6} {}",
+            layout.live, layout.layout_id, layout.layout_name
+        )?;
+    }
+
+    Ok(())
+}
+
+/// # Safety
+///
+/// Only the app calls this, with a `layout_name` that is never freed.
+#[cfg(feature = "heap-profile")]
+#[no_mangle]
+pub unsafe extern "C" fn roc_heap_profile_alloc(layout_id: u32, layout_name: *const c_char) {
+    record_alloc(layout_id, layout_name)
+}
+
+#[cfg(feature = "heap-profile")]
+#[no_mangle]
+pub extern "C" fn roc_heap_profile_dealloc(layout_id: u32, _layout_name: *const c_char) {
+    record_dealloc(layout_id)
+}
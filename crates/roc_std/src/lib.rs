@@ -11,11 +11,13 @@ use core::mem::{ManuallyDrop, MaybeUninit};
 use core::ops::Drop;
 use core::str;
 
+pub mod heap_profile;
 mod roc_box;
 mod roc_dict;
 mod roc_list;
 mod roc_set;
 mod roc_str;
+pub mod stack_limit;
 mod storage;
 
 pub use roc_box::RocBox;
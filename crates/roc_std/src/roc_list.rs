@@ -9,7 +9,7 @@ use core::{
     intrinsics::copy_nonoverlapping,
     iter::FromIterator,
     mem::{self, ManuallyDrop},
-    ops::{Deref, DerefMut},
+    ops::{Deref, DerefMut, Range},
     ptr::{self, NonNull},
 };
 
@@ -24,6 +24,9 @@ use serde::{
     Deserialize, Serialize,
 };
 
+/// Marks a seamless slice, in `capacity_or_ref_ptr`
+const SEAMLESS_SLICE_BIT: usize = isize::MIN as usize;
+
 #[repr(C)]
 pub struct RocList<T> {
     elements: Option<NonNull<ManuallyDrop<T>>>,
@@ -232,6 +235,56 @@ where
         list
     }
 
+    /// The elements in `range`, like `List.sublist` in Roc, without copying them.
+    ///
+    /// A unique list is shortened in place if `range` starts at 0. Otherwise the result is a
+    /// seamless slice: it points into this list's allocation, and keeps the reference to it.
+    /// The elements of a shared list are cloned instead, if they need to be dropped, since the
+    /// slice couldn't drop the ones outside `range` when it ends up with the last reference.
+    ///
+    /// Panics if `range` is out of bounds, like slice indexing.
+    pub fn sublist(self, range: Range<usize>) -> Self {
+        let _ = &self.as_slice()[range.clone()];
+
+        let elements = match self.elements_and_storage() {
+            Some((elements, _)) if !range.is_empty() => elements,
+            _ => return Self::empty(),
+        };
+
+        if !self.is_unique() {
+            if mem::needs_drop::<T>() && !self.is_readonly() {
+                return Self::from_slice(&self[range]);
+            }
+        } else {
+            // Nothing else can see the elements outside `range` anymore.
+            for index in (0..range.start).chain(range.end..self.len()) {
+                unsafe { ManuallyDrop::drop(&mut *elements.as_ptr().add(index)) };
+            }
+
+            if range.start == 0 && !self.is_seamless_slice() {
+                let mut list = ManuallyDrop::new(self);
+                list.length = range.len();
+
+                return ManuallyDrop::into_inner(list);
+            }
+        }
+
+        let capacity_or_ref_ptr = if self.is_seamless_slice() {
+            self.capacity_or_ref_ptr
+        } else {
+            (elements.as_ptr() as usize >> 1) | SEAMLESS_SLICE_BIT
+        };
+
+        // The slice takes over this list's reference to the allocation.
+        mem::forget(self);
+
+        Self {
+            elements: NonNull::new(unsafe { elements.as_ptr().add(range.start) }),
+            length: range.len(),
+            capacity_or_ref_ptr,
+        }
+    }
+
     pub fn extend_from_slice(&mut self, slice: &[T]) {
         // TODO: Can we do better for ZSTs? Alignment might be a problem.
         if slice.is_empty() {
@@ -244,9 +297,10 @@ where
             let mut copy = storage.get();
             let is_unique = copy.decrease();
 
-            if is_unique {
+            // A seamless slice can't grow in place: its allocation belongs to the list it was sliced from.
+            if is_unique && !self.is_seamless_slice() {
                 // If we have enough capacity, we can add to the existing elements in-place.
-                if self.capacity() >= slice.len() {
+                if self.capacity() >= new_len {
                     elements
                 } else {
                     // There wasn't enough capacity, so we need a new allocation.
@@ -267,19 +321,26 @@ where
                     }))
                 }
             } else {
-                if !copy.is_readonly() {
-                    // Write the decremented reference count back.
-                    storage.set(copy);
-                }
-
                 // Allocate new memory.
-                self.capacity_or_ref_ptr = slice.len();
-                let new_elements = Self::elems_with_capacity(slice.len());
+                let new_elements = Self::elems_with_capacity(new_len);
 
                 // Copy the old elements to the new allocation.
                 unsafe {
                     copy_nonoverlapping(elements.as_ptr(), new_elements.as_ptr(), self.len());
                 }
+
+                if is_unique {
+                    // This was the last reference to the allocation of the list this was sliced
+                    // from. Its elements were moved out above, so they don't get dropped.
+                    unsafe {
+                        roc_dealloc(self.ptr_to_allocation(), Self::alloc_alignment());
+                    }
+                } else if !copy.is_readonly() {
+                    // Write the decremented reference count back.
+                    storage.set(copy);
+                }
+
+                self.capacity_or_ref_ptr = new_len;
                 // Clear the seamless slice bit since we now have clear ownership.
                 self.length = self.len();
 
@@ -324,7 +385,8 @@ impl<T> RocList<T> {
 
         match self.elements_and_storage() {
             Some((elements, storage)) => {
-                if storage.get().is_unique() {
+                // A seamless slice can't be reallocated: its allocation belongs to the list it was sliced from.
+                if storage.get().is_unique() && !self.is_seamless_slice() {
                     unsafe {
                         let old_alloc = self.ptr_to_allocation();
 
@@ -0,0 +1,35 @@
+//! The stack limit for apps built with `--stack-limit-checks`.
+//!
+//! Such an app calls `roc_stack_limit` at the start of every function, and crashes with a
+//! "stack overflow" message when its stack frame is below the address that returns. With the
+//! `stack-limit-checks` feature, this module defines `roc_stack_limit` to return the limit the
+//! host last set, or 0, which is never reached, if it hasn't set one. The limit is shared by all
+//! threads; a host that runs Roc code on several stacks should define `roc_stack_limit` itself.
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+static LIMIT: AtomicUsize = AtomicUsize::new(0);
+
+/// Crash once Roc code uses a stack address below `limit`
+pub fn set_stack_limit(limit: usize) {
+    LIMIT.store(limit, Ordering::Relaxed);
+}
+
+/// Crash once Roc code uses more than `bytes` of stack below the caller's frame. The stack the
+/// caller runs on must have that much room left, with some to spare for the crash itself.
+#[inline(never)]
+pub fn set_stack_limit_below_here(bytes: usize) {
+    let here = 0u8;
+    let address = core::hint::black_box(&here) as *const u8 as usize;
+
+    set_stack_limit(address.saturating_sub(bytes));
+}
+
+pub fn stack_limit() -> usize {
+    LIMIT.load(Ordering::Relaxed)
+}
+
+#[cfg(feature = "stack-limit-checks")]
+#[no_mangle]
+pub extern "C" fn roc_stack_limit() -> usize {
+    stack_limit()
+}
@@ -239,6 +239,122 @@ mod test_roc_std {
         assert_eq!(from_array.capacity(), from_slice.capacity());
     }
 
+    #[test]
+    fn list_extend_past_capacity() {
+        let mut list = RocList::with_capacity(4);
+        list.extend_from_slice(&[1, 2, 3]);
+        list.extend_from_slice(&[4, 5]);
+
+        assert_eq!(list.as_slice(), &[1, 2, 3, 4, 5]);
+        assert!(list.capacity() >= 5);
+    }
+
+    #[test]
+    fn list_extend_shared() {
+        let list = RocList::from_slice(&[1, 2, 3]);
+        let mut extended = list.clone();
+        extended.extend_from_slice(&[4, 5]);
+
+        assert_eq!(list.as_slice(), &[1, 2, 3]);
+        assert_eq!(extended.as_slice(), &[1, 2, 3, 4, 5]);
+        assert!(list.is_unique());
+        assert!(extended.is_unique());
+    }
+
+    #[test]
+    fn sublist_shares_the_allocation() {
+        let list = RocList::from_slice(&[1, 2, 3, 4, 5]);
+        let first = list.as_ptr();
+        let other = list.clone();
+
+        let slice = list.sublist(1..4);
+
+        assert_eq!(slice.as_slice(), &[2, 3, 4]);
+        assert_eq!(slice.as_ptr(), first.wrapping_add(1));
+        assert!(!slice.is_unique());
+
+        drop(other);
+
+        assert!(slice.is_unique());
+    }
+
+    #[test]
+    fn sublist_from_start_of_unique_list_keeps_capacity() {
+        let list = RocList::from_slice(&[1, 2, 3, 4, 5]);
+        let first = list.as_ptr();
+
+        let prefix = list.sublist(0..2);
+
+        assert_eq!(prefix.as_slice(), &[1, 2]);
+        assert_eq!(prefix.as_ptr(), first);
+        assert_eq!(prefix.capacity(), 5);
+    }
+
+    #[test]
+    fn sublist_of_sublist() {
+        let list = RocList::from_slice(&[1, 2, 3, 4, 5]);
+        let first = list.as_ptr();
+
+        let slice = list.sublist(1..5).sublist(1..3);
+
+        assert_eq!(slice.as_slice(), &[3, 4]);
+        assert_eq!(slice.as_ptr(), first.wrapping_add(2));
+    }
+
+    #[test]
+    fn sublist_of_shared_list_with_refcounted_elements_copies() {
+        let strings = [
+            RocStr::from("a string that is too long to be a small string"),
+            RocStr::from("another string that is too long to be small"),
+        ];
+        let list = RocList::from_slice(&strings);
+        let other = list.clone();
+
+        let slice = list.sublist(1..2);
+
+        assert_eq!(slice.as_slice(), &strings[1..]);
+        assert_ne!(slice.as_ptr(), other.as_ptr().wrapping_add(1));
+        assert!(other.is_unique());
+    }
+
+    #[test]
+    fn sublist_empty_range() {
+        let list = RocList::from_slice(&[1, 2, 3]);
+
+        assert_eq!(list.sublist(1..1), RocList::empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn sublist_out_of_bounds() {
+        let list = RocList::from_slice(&[1, 2, 3]);
+
+        list.sublist(2..4);
+    }
+
+    #[test]
+    fn extend_slice_copies_out() {
+        let list = RocList::from_slice(&[1, 2, 3, 4, 5]);
+        let mut slice = list.sublist(2..4);
+        slice.extend_from_slice(&[9]);
+
+        assert_eq!(slice.as_slice(), &[3, 4, 9]);
+        assert!(slice.is_unique());
+        assert!(slice.capacity() >= 3);
+    }
+
+    #[test]
+    fn reserve_slice_copies_out() {
+        let list = RocList::from_slice(&[1, 2, 3, 4, 5]);
+        let other = list.clone();
+        let mut slice = list.sublist(3..5);
+        slice.reserve(10);
+
+        assert_eq!(slice.as_slice(), &[4, 5]);
+        assert!(slice.capacity() >= 12);
+        assert!(other.is_unique());
+    }
+
     #[test]
     fn roc_result_to_rust_result() {
         let greeting = "Hello, World!";
@@ -478,3 +594,64 @@ mod with_terminator {
         // verify_temp_c(&string_for_len(65), 64);
     }
 }
+
+#[cfg(test)]
+mod heap_profile {
+    use roc_std::heap_profile::{live_by_layout, record_alloc, record_dealloc, LiveLayout};
+
+    #[test]
+    fn counts_live_allocations() {
+        let name = b"Boxed(Str)\0".as_ptr() as *const _;
+
+        unsafe {
+            record_alloc(7, name);
+            record_alloc(7, name);
+            record_alloc(8, name);
+        }
+
+        record_dealloc(7);
+        record_dealloc(8);
+
+        let live: Vec<_> = live_by_layout()
+            .filter(|layout| matches!(layout.layout_id, 7 | 8))
+            .collect();
+
+        assert_eq!(
+            live,
+            [LiveLayout {
+                layout_id: 7,
+                layout_name: "Boxed(Str)",
+                live: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn ignores_ids_out_of_range() {
+        let id = roc_std::heap_profile::MAX_LAYOUTS as u32;
+
+        unsafe { record_alloc(id, core::ptr::null()) };
+
+        assert!(live_by_layout().all(|layout| layout.layout_id != id));
+    }
+}
+
+#[cfg(test)]
+mod stack_limit {
+    use roc_std::stack_limit::{set_stack_limit, set_stack_limit_below_here, stack_limit};
+
+    #[test]
+    fn limit_is_below_the_caller() {
+        let here = 0u8;
+        let address = &here as *const u8 as usize;
+
+        set_stack_limit_below_here(1 << 20);
+
+        let limit = stack_limit();
+        assert!(limit < address);
+        assert!(limit > address - (2 << 20));
+
+        set_stack_limit(0);
+        assert_eq!(stack_limit(), 0);
+    }
+}
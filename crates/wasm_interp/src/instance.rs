@@ -2,7 +2,7 @@ use bumpalo::{collections::Vec, Bump};
 use std::fmt::{self, Write};
 use std::iter::{self, once, Iterator};
 
-use roc_wasm_module::opcodes::OpCode;
+use roc_wasm_module::opcodes::{MiscOpCode, OpCode};
 use roc_wasm_module::parse::{Parse, SkipBytes};
 use roc_wasm_module::sections::{ImportDesc, MemorySection, SignatureParamsIter};
 use roc_wasm_module::{ExportType, WasmModule};
@@ -424,6 +424,18 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
         }
     }
 
+    fn check_memory_range(&self, addr: usize, size: usize) -> Result<(), Error> {
+        let memory_size = self.memory.len();
+        if addr + size > memory_size {
+            Err(Error::MemoryAccessOutOfBounds(
+                (addr + size) as u32,
+                memory_size as u32,
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
     fn write_debug<T: fmt::Debug>(&mut self, value: T) {
         if let Some(debug_string) = self.debug_string.as_mut() {
             std::write!(debug_string, "{:?} ", value).unwrap();
@@ -1703,6 +1715,34 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
                 self.value_store
                     .push(Value::F64(f64::from_ne_bytes(x.to_ne_bytes())));
             }
+            MISCPREFIX => {
+                let sub_opcode = self.fetch_immediate_u32(module);
+                if sub_opcode == MiscOpCode::MEMORYCOPY as u32 {
+                    let dst_memory = self.fetch_immediate_u32(module);
+                    let src_memory = self.fetch_immediate_u32(module);
+                    assert_eq!((dst_memory, src_memory), (0, 0));
+                    let size = self.value_store.pop_u32()? as usize;
+                    let src = self.value_store.pop_u32()? as usize;
+                    let dst = self.value_store.pop_u32()? as usize;
+                    self.check_memory_range(src, size)?;
+                    self.check_memory_range(dst, size)?;
+                    self.memory.copy_within(src..src + size, dst);
+                } else if sub_opcode == MiscOpCode::MEMORYFILL as u32 {
+                    let memory_index = self.fetch_immediate_u32(module);
+                    assert_eq!(memory_index, 0);
+                    let size = self.value_store.pop_u32()? as usize;
+                    let byte = self.value_store.pop_i32()? as u8;
+                    let dst = self.value_store.pop_u32()? as usize;
+                    self.check_memory_range(dst, size)?;
+                    self.memory[dst..][..size].fill(byte);
+                } else {
+                    return Err(Error::UnsupportedOp(format!("0xfc {}", sub_opcode)));
+                }
+            }
+            SIMDPREFIX => {
+                let sub_opcode = self.fetch_immediate_u32(module);
+                return Err(Error::UnsupportedOp(format!("0xfd {} (SIMD)", sub_opcode)));
+            }
         }
 
         if let Some(debug_string) = &self.debug_string {
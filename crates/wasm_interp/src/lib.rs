@@ -69,6 +69,7 @@ pub(crate) enum Error {
     StackEmpty,
     MemoryAccessOutOfBounds(u32, u32),
     UnreachableOp,
+    UnsupportedOp(String),
 }
 
 impl Error {
@@ -98,6 +99,12 @@ impl Error {
                     file_offset
                 )
             }
+            Error::UnsupportedOp(instruction) => {
+                format!(
+                    "ERROR: The interpreter doesn't support the Wasm instruction {} at file offset {:#x}.\n",
+                    instruction, file_offset
+                )
+            }
         }
     }
 }
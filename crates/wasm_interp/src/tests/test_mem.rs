@@ -1,8 +1,8 @@
 use super::create_exported_function_no_locals;
-use crate::{DefaultImportDispatcher, Instance};
+use crate::{DefaultImportDispatcher, Error, Instance};
 use bumpalo::{collections::Vec, Bump};
 use roc_wasm_module::{
-    opcodes::OpCode,
+    opcodes::{MiscOpCode, OpCode, SimdOpCode},
     sections::{DataMode, DataSegment, MemorySection},
     ConstExpr, SerialBuffer, Signature, Value, ValueType, WasmModule,
 };
@@ -49,6 +49,71 @@ fn test_growmemory() {
     assert_eq!(state.memory.len(), 5 * MemorySection::PAGE_SIZE as usize);
 }
 
+#[test]
+fn test_memory_copy() {
+    let arena = Bump::new();
+    let mut module = WasmModule::new(&arena);
+
+    let pages = 1;
+    let pc = 0;
+    module.memory = MemorySection::new(&arena, pages * MemorySection::PAGE_SIZE);
+    for arg in [0x20, 0x10, 5] {
+        module.code.bytes.push(OpCode::I32CONST as u8);
+        module.code.bytes.encode_i32(arg);
+    }
+    module.code.bytes.push(OpCode::MISCPREFIX as u8);
+    module.code.bytes.encode_u32(MiscOpCode::MEMORYCOPY as u32);
+    module.code.bytes.extend([0, 0]);
+
+    let mut state = Instance::new(&arena, pages, pc, [], DefaultImportDispatcher::default());
+    state.memory[0x10..][..8].copy_from_slice("abcdefgh".as_bytes());
+    for _ in 0..4 {
+        state.execute_next_instruction(&module).unwrap();
+    }
+    assert_eq!(&state.memory[0x20..][..8], "abcde\0\0\0".as_bytes());
+}
+
+#[test]
+fn test_memory_fill() {
+    let arena = Bump::new();
+    let mut module = WasmModule::new(&arena);
+
+    let pages = 1;
+    let pc = 0;
+    module.memory = MemorySection::new(&arena, pages * MemorySection::PAGE_SIZE);
+    for arg in [0x11, 0x2a, 3] {
+        module.code.bytes.push(OpCode::I32CONST as u8);
+        module.code.bytes.encode_i32(arg);
+    }
+    module.code.bytes.push(OpCode::MISCPREFIX as u8);
+    module.code.bytes.encode_u32(MiscOpCode::MEMORYFILL as u32);
+    module.code.bytes.push(0);
+
+    let mut state = Instance::new(&arena, pages, pc, [], DefaultImportDispatcher::default());
+    for _ in 0..4 {
+        state.execute_next_instruction(&module).unwrap();
+    }
+    assert_eq!(&state.memory[0x10..][..5], &[0, 0x2a, 0x2a, 0x2a, 0]);
+}
+
+#[test]
+fn test_simd_is_an_error() {
+    let arena = Bump::new();
+    let mut module = WasmModule::new(&arena);
+
+    let pages = 1;
+    let pc = 0;
+    module.memory = MemorySection::new(&arena, pages * MemorySection::PAGE_SIZE);
+    module.code.bytes.push(OpCode::SIMDPREFIX as u8);
+    module.code.bytes.encode_u32(SimdOpCode::V128LOAD as u32);
+
+    let mut state = Instance::new(&arena, pages, pc, [], DefaultImportDispatcher::default());
+    assert_eq!(
+        state.execute_next_instruction(&module).err(),
+        Some(Error::UnsupportedOp("0xfd 0 (SIMD)".into()))
+    );
+}
+
 fn test_load(load_op: OpCode, ty: ValueType, data: &[u8], addr: u32, offset: u32) -> Value {
     let arena = Bump::new();
     let mut module = WasmModule::new(&arena);
@@ -18,8 +18,8 @@ use self::linking::{IndexRelocType, LinkingSection, RelocationSection, WasmObjec
 use self::parse::{Parse, ParseError};
 use self::sections::{
     CodeSection, DataSection, ElementSection, ExportSection, FunctionSection, GlobalSection,
-    ImportDesc, ImportSection, MemorySection, NameSection, OpaqueSection, Section, SectionId,
-    TableSection, TypeSection,
+    Import, ImportDesc, ImportSection, MemorySection, NameSection, OpaqueSection, Section,
+    SectionId, TableSection, TypeSection,
 };
 pub use self::serialize::{SerialBuffer, Serialize};
 
@@ -98,6 +98,37 @@ impl<'a> WasmModule<'a> {
         self.names.serialize(buffer);
     }
 
+    /// Find where each function body in the Code section of a serialized module starts, as a byte
+    /// offset from the start of the module. Body `i` belongs to the function whose index is `i`
+    /// plus the number of imported functions.
+    pub fn function_body_offsets(module_bytes: &[u8]) -> Result<std::vec::Vec<u32>, ParseError> {
+        let mut cursor: usize = 8;
+
+        while cursor < module_bytes.len() {
+            let id = module_bytes[cursor];
+            cursor += 1;
+            let section_size = u32::parse((), module_bytes, &mut cursor)? as usize;
+
+            if id != SectionId::Code as u8 {
+                cursor += section_size;
+                continue;
+            }
+
+            let function_count = u32::parse((), module_bytes, &mut cursor)?;
+            let mut offsets = std::vec::Vec::with_capacity(function_count as usize);
+
+            for _ in 0..function_count {
+                offsets.push(cursor as u32);
+                let body_size = u32::parse((), module_bytes, &mut cursor)? as usize;
+                cursor += body_size;
+            }
+
+            return Ok(offsets);
+        }
+
+        Ok(std::vec::Vec::new())
+    }
+
     /// Module size in bytes (assuming no linker data)
     /// May be slightly overestimated. Intended for allocating buffer capacity.
     pub fn size(&self) -> usize {
@@ -572,6 +603,51 @@ impl<'a> WasmModule<'a> {
         }
     }
 
+    /// Import a function into the slot of one of the dummies that stand in for removed imports,
+    /// so that no other function index changes. Returns the index of the imported function,
+    /// or None if there are no dummies left.
+    pub fn import_fn_in_dummy_slot(
+        &mut self,
+        module: &'a str,
+        name: &'a str,
+        signature: Signature<'a>,
+    ) -> Option<u32> {
+        if self.code.dead_import_dummy_count == 0 {
+            return None;
+        }
+
+        // The dummies come straight after the imports, so the new import takes the first one's index
+        let fn_index = self.import.function_count() as u32;
+        let signature_index = self.types.insert(signature);
+        self.import.imports.push(Import {
+            module,
+            name,
+            description: ImportDesc::Func { signature_index },
+        });
+
+        self.code.dead_import_dummy_count -= 1;
+        self.function.signatures.remove(0);
+
+        // Calls to imports are relocated through the symbol table when dead imports are removed
+        self.linking
+            .symbol_table
+            .push(SymInfo::Function(WasmObjectSymbol::ImplicitlyNamed {
+                flags: linking::WASM_SYM_UNDEFINED,
+                index: fn_index,
+            }));
+
+        if let Some((_, debug_name)) = self
+            .names
+            .function_names
+            .iter_mut()
+            .find(|(i, _)| *i == fn_index)
+        {
+            *debug_name = name;
+        }
+
+        Some(fn_index)
+    }
+
     /// Create a name->index lookup table for host functions that may be called from the app
     pub fn get_host_function_lookup(&self, arena: &'a Bump) -> Vec<'a, (&'a str, u32)> {
         // Functions beginning with `roc_` go first, since they're most likely to be called
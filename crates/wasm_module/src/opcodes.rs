@@ -183,6 +183,36 @@ pub enum OpCode {
     I64REINTERPRETF64 = 0xbd,
     F32REINTERPRETI32 = 0xbe,
     F64REINTERPRETI64 = 0xbf,
+
+    /// Prefix for the instructions in [MiscOpCode]
+    MISCPREFIX = 0xfc,
+    /// Prefix for the instructions in [SimdOpCode]
+    SIMDPREFIX = 0xfd,
+}
+
+/// Instructions that follow the [OpCode::MISCPREFIX] byte, as a LEB-encoded sub-opcode.
+/// Only the ones we generate are listed here. `memory.copy` and `memory.fill` are from
+/// the bulk memory proposal.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MiscOpCode {
+    MEMORYCOPY = 10,
+    MEMORYFILL = 11,
+}
+
+/// Instructions that follow the [OpCode::SIMDPREFIX] byte, as a LEB-encoded sub-opcode.
+/// Only the ones we generate are listed here.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SimdOpCode {
+    V128LOAD = 0,
+    V128STORE = 11,
+    I8X16EQ = 35,
+    V128NOT = 77,
+    V128AND = 78,
+    V128OR = 80,
+    V128XOR = 81,
+    I8X16ALLTRUE = 99,
 }
 
 impl From<u8> for OpCode {
@@ -204,6 +234,8 @@ enum OpImmediates {
     Leb64x1,
     Leb32x2,
     BrTable,
+    Misc,
+    Simd,
 }
 
 fn immediates_for(op: OpCode) -> Result<OpImmediates, String> {
@@ -257,6 +289,9 @@ fn immediates_for(op: OpCode) -> Result<OpImmediates, String> {
             NoImmediate
         }
 
+        MISCPREFIX => Misc,
+        SIMDPREFIX => Simd,
+
         // Catch-all in case of an invalid cast from u8 to OpCode while parsing binary
         // (rustc keeps this code, I verified in Compiler Explorer)
         #[allow(unreachable_patterns)]
@@ -312,6 +347,58 @@ impl SkipBytes for OpCode {
                     u32::skip_bytes(bytes, cursor)?;
                 }
             }
+            Misc => {
+                *cursor += 1;
+                let sub_opcode = u32::parse((), bytes, cursor)?;
+                match sub_opcode {
+                    // saturating float-to-int conversions
+                    0..=7 => {}
+                    // memory.init
+                    8 => {
+                        u32::skip_bytes(bytes, cursor)?;
+                        *cursor += 1;
+                    }
+                    // memory.copy
+                    10 => *cursor += 2,
+                    // memory.fill
+                    11 => *cursor += 1,
+                    // table.init, table.copy
+                    12 | 14 => {
+                        u32::skip_bytes(bytes, cursor)?;
+                        u32::skip_bytes(bytes, cursor)?;
+                    }
+                    // data.drop, elem.drop, table.grow, table.size, table.fill
+                    9 | 13 | 15..=17 => u32::skip_bytes(bytes, cursor)?,
+                    _ => {
+                        return Err(ParseError {
+                            message: format!("Unknown Wasm instruction 0xfc {}", sub_opcode),
+                            offset: *cursor,
+                        })
+                    }
+                }
+            }
+            Simd => {
+                *cursor += 1;
+                let sub_opcode = u32::parse((), bytes, cursor)?;
+                match sub_opcode {
+                    // loads and stores
+                    0..=11 | 92 | 93 => {
+                        u32::skip_bytes(bytes, cursor)?;
+                        u32::skip_bytes(bytes, cursor)?;
+                    }
+                    // v128.const, i8x16.shuffle
+                    12 | 13 => *cursor += 16,
+                    // extract_lane, replace_lane
+                    21..=34 => *cursor += 1,
+                    // load_lane, store_lane
+                    84..=91 => {
+                        u32::skip_bytes(bytes, cursor)?;
+                        u32::skip_bytes(bytes, cursor)?;
+                        *cursor += 1;
+                    }
+                    _ => {}
+                }
+            }
         }
         Ok(())
     }
@@ -322,3 +409,28 @@ impl Serialize for OpCode {
         (*self as u8).serialize(buffer)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skip_prefixed_instructions() {
+        let bytes = [
+            0xfc, 10, 0, 0, // memory.copy
+            0xfc, 11, 0, // memory.fill
+            0xfd, 0, 4, 0x80, 0x01, // v128.load align=4 offset=128
+            0xfd, 78, // v128.and
+            0xfd, 12, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16,   // v128.const
+            0x0b, // end
+        ];
+        let mut cursor = 0;
+        let mut instruction_starts = std::vec::Vec::new();
+        while bytes[cursor] != OpCode::END as u8 {
+            instruction_starts.push(cursor);
+            OpCode::skip_bytes(&bytes, &mut cursor).unwrap();
+        }
+        assert_eq!(instruction_starts, [0, 4, 7, 12, 14]);
+        assert_eq!(cursor, bytes.len() - 1);
+    }
+}